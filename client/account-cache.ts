@@ -0,0 +1,244 @@
+import * as crypto from 'crypto'
+import * as fs from 'fs'
+import * as os from 'os'
+import * as path from 'path'
+import {
+  AccountInfo,
+  Commitment,
+  Connection,
+  ConnectionConfig,
+  GetAccountInfoConfig,
+  GetMultipleAccountsConfig,
+  LoadedAddresses,
+  PublicKey,
+  RpcResponseAndContext,
+  VersionedMessage,
+} from '@solana/web3.js'
+
+export const DEFAULT_CACHE_TTL_SECONDS = 15
+// past its TTL an entry is still served for this long while it is refetched in the background
+export const DEFAULT_CACHE_STALE_SECONDS = 60
+
+// one directory per RPC endpoint, the same address holds different accounts on devnet and mainnet
+export function defaultCacheDir(rpcUrl: string): string {
+  const endpoint = crypto.createHash('sha256').update(rpcUrl).digest('hex').slice(0, 16)
+  return path.join(os.homedir(), '.config', 'focx-vault', 'cache', endpoint)
+}
+
+interface StoredAccount {
+  data: string
+  owner: string
+  lamports: number
+  executable: boolean
+  rentEpoch?: number
+}
+
+interface CacheEntry {
+  slot: number
+  fetchedAt: number
+  // missing on invalidated keys: reads miss and writes from older slots are dropped
+  account?: StoredAccount | null
+}
+
+type Account = AccountInfo<Buffer> | null
+
+interface Hit {
+  account: Account
+  slot: number
+  fresh: boolean
+}
+
+export interface AccountCacheOptions {
+  ttlSeconds?: number
+  staleSeconds?: number
+  // milliseconds, injectable for tests
+  now?: () => number
+}
+
+function encode(account: Account): StoredAccount | null {
+  if (!account) {
+    return null
+  }
+  return {
+    data: account.data.toString('base64'),
+    owner: account.owner.toBase58(),
+    lamports: account.lamports,
+    executable: account.executable,
+    rentEpoch: account.rentEpoch,
+  }
+}
+
+function decode(stored: StoredAccount | null): Account {
+  if (!stored) {
+    return null
+  }
+  return {
+    data: Buffer.from(stored.data, 'base64'),
+    owner: new PublicKey(stored.owner),
+    lamports: stored.lamports,
+    executable: stored.executable,
+    rentEpoch: stored.rentEpoch,
+  }
+}
+
+// accounts a transaction may have changed, i.e. what to invalidate once it is confirmed
+export function writableAccounts(message: VersionedMessage, loadedAddresses?: LoadedAddresses): PublicKey[] {
+  const keys =
+    message.version === 'legacy'
+      ? message.getAccountKeys()
+      : message.getAccountKeys({ accountKeysFromLookups: loadedAddresses })
+  const writable: PublicKey[] = []
+  for (let i = 0; i < keys.length; i++) {
+    if (message.isAccountWritable(i)) {
+      writable.push(keys.get(i) as PublicKey)
+    }
+  }
+  return writable
+}
+
+// on-disk account store with stale-while-revalidate reads: one JSON file per pubkey holding
+// the account and the slot it was read at. A write never replaces an entry from a later slot,
+// so a slow read cannot bring back an account a confirmed transaction invalidated
+export class AccountCache {
+  private ttlMs: number
+  private staleMs: number
+  private now: () => number
+  private revalidations: Promise<unknown>[] = []
+  // bumped by every invalidation, reads started before it do not store their result
+  private generation = 0
+
+  constructor(private dir: string, options: AccountCacheOptions = {}) {
+    this.ttlMs = (options.ttlSeconds ?? DEFAULT_CACHE_TTL_SECONDS) * 1000
+    this.staleMs = (options.staleSeconds ?? DEFAULT_CACHE_STALE_SECONDS) * 1000
+    this.now = options.now ?? Date.now
+  }
+
+  private file(key: PublicKey): string {
+    return path.join(this.dir, `${key.toBase58()}.json`)
+  }
+
+  private readEntry(key: PublicKey): CacheEntry | undefined {
+    try {
+      return JSON.parse(fs.readFileSync(this.file(key), 'utf8'))
+    } catch (error) {
+      return undefined
+    }
+  }
+
+  private writeEntry(key: PublicKey, entry: CacheEntry) {
+    const current = this.readEntry(key)
+    if (current && current.slot > entry.slot) {
+      return
+    }
+    fs.mkdirSync(this.dir, { recursive: true })
+    fs.writeFileSync(this.file(key), JSON.stringify(entry))
+  }
+
+  // the cached account, unless it is missing, invalidated or past TTL + stale window
+  lookup(key: PublicKey): Hit | undefined {
+    const entry = this.readEntry(key)
+    if (!entry || entry.account === undefined) {
+      return undefined
+    }
+    const age = this.now() - entry.fetchedAt
+    if (age >= this.ttlMs + this.staleMs) {
+      return undefined
+    }
+    return { account: decode(entry.account), slot: entry.slot, fresh: age < this.ttlMs }
+  }
+
+  store(key: PublicKey, slot: number, account: Account) {
+    this.writeEntry(key, { slot, fetchedAt: this.now(), account: encode(account) })
+  }
+
+  // drop `keys`, written by a transaction confirmed at `slot`
+  invalidate(keys: PublicKey[], slot: number) {
+    this.generation++
+    keys.forEach((key) => this.writeEntry(key, { slot, fetchedAt: this.now() }))
+  }
+
+  clear() {
+    this.generation++
+    fs.rmSync(this.dir, { recursive: true, force: true })
+  }
+
+  // `keys` through the cache, `load` reads all of them in one RPC call. Fresh entries are
+  // returned as is, stale ones are returned and refetched in the background, anything else
+  // waits for `load`
+  async fetch(
+    keys: PublicKey[],
+    load: () => Promise<RpcResponseAndContext<Account[]>>
+  ): Promise<RpcResponseAndContext<Account[]>> {
+    const hits = keys.map((key) => this.lookup(key))
+    if (keys.length === 0 || hits.some((hit) => hit === undefined)) {
+      return this.loadAndStore(keys, load)
+    }
+    const cached = hits as Hit[]
+    if (cached.some((hit) => !hit.fresh)) {
+      this.revalidations.push(this.loadAndStore(keys, load).catch(() => undefined))
+    }
+    return {
+      context: { slot: Math.min(...cached.map((hit) => hit.slot)) },
+      value: cached.map((hit) => hit.account),
+    }
+  }
+
+  private async loadAndStore(
+    keys: PublicKey[],
+    load: () => Promise<RpcResponseAndContext<Account[]>>
+  ): Promise<RpcResponseAndContext<Account[]>> {
+    const generation = this.generation
+    const loaded = await load()
+    if (generation === this.generation) {
+      keys.forEach((key, index) => this.store(key, loaded.context.slot, loaded.value[index]))
+    }
+    return loaded
+  }
+
+  // wait for background refreshes so they reach the disk before the process exits
+  async settle(): Promise<void> {
+    const pending = this.revalidations
+    this.revalidations = []
+    await Promise.all(pending)
+  }
+}
+
+// reads pinned to a slot or sliced are not cached
+function cacheable(commitmentOrConfig?: Commitment | GetAccountInfoConfig | GetMultipleAccountsConfig): boolean {
+  if (typeof commitmentOrConfig !== 'object') {
+    return true
+  }
+  return commitmentOrConfig.minContextSlot === undefined && commitmentOrConfig.dataSlice === undefined
+}
+
+// Connection whose single and multiple account reads go through an `AccountCache`, which
+// covers Anchor's fetch/fetchMultiple and spl-token's getAccount/getMint
+export class CachedConnection extends Connection {
+  constructor(endpoint: string, config: ConnectionConfig, readonly cache: AccountCache) {
+    super(endpoint, config)
+  }
+
+  async getAccountInfoAndContext(
+    publicKey: PublicKey,
+    commitmentOrConfig?: Commitment | GetAccountInfoConfig
+  ): Promise<RpcResponseAndContext<Account>> {
+    if (!cacheable(commitmentOrConfig)) {
+      return super.getAccountInfoAndContext(publicKey, commitmentOrConfig)
+    }
+    const loaded = await this.cache.fetch([publicKey], async () => {
+      const single = await super.getAccountInfoAndContext(publicKey, commitmentOrConfig)
+      return { context: single.context, value: [single.value] }
+    })
+    return { context: loaded.context, value: loaded.value[0] }
+  }
+
+  async getMultipleAccountsInfoAndContext(
+    publicKeys: PublicKey[],
+    commitmentOrConfig?: Commitment | GetMultipleAccountsConfig
+  ): Promise<RpcResponseAndContext<Account[]>> {
+    if (!cacheable(commitmentOrConfig)) {
+      return super.getMultipleAccountsInfoAndContext(publicKeys, commitmentOrConfig)
+    }
+    return this.cache.fetch(publicKeys, () => super.getMultipleAccountsInfoAndContext(publicKeys, commitmentOrConfig))
+  }
+}
@@ -0,0 +1,101 @@
+import * as anchor from '@coral-xyz/anchor'
+import { PublicKey } from '@solana/web3.js'
+
+// mirrors DEFAULT_CHECKPOINT_INTERVAL in constants.rs
+export const DEFAULT_CHECKPOINT_INTERVAL = 24 * 60 * 60
+const SECONDS_PER_DAY = 24 * 60 * 60
+const SECONDS_PER_YEAR = 365 * SECONDS_PER_DAY
+
+export interface Checkpoint {
+  timestamp: number
+  activeShareValue: anchor.BN
+  totalAssets: anchor.BN
+  totalShares: anchor.BN
+  sharesBase: number
+}
+
+export interface TrailingApy {
+  days: number
+  // checkpoints the rate was measured between, further apart than `days` after a sparse period
+  from: Checkpoint
+  to: Checkpoint
+  apr: number
+  apy: number
+}
+
+export function checkpointsPDA(programId: PublicKey, vault: PublicKey): PublicKey {
+  return PublicKey.findProgramAddressSync([Buffer.from('vault_checkpoints'), vault.toBuffer()], programId)[0]
+}
+
+// mirrors Vault::effective_checkpoint_interval: vaults from before the field read 0
+export function checkpointInterval(vault: any): number {
+  const interval = vault.checkpointInterval ? vault.checkpointInterval.toNumber() : 0
+  return interval === 0 ? DEFAULT_CHECKPOINT_INTERVAL : interval
+}
+
+// mirrors VaultCheckpoints::iter_chronological
+export function chronologicalCheckpoints(account: any): Checkpoint[] {
+  const capacity = account.checkpoints.length
+  const start = (account.nextIndex + capacity - account.len) % capacity
+  const checkpoints: Checkpoint[] = []
+  for (let offset = 0; offset < account.len; offset++) {
+    const entry = account.checkpoints[(start + offset) % capacity]
+    checkpoints.push({
+      timestamp: entry.timestamp.toNumber(),
+      activeShareValue: entry.activeShareValue,
+      totalAssets: entry.totalAssets,
+      totalShares: entry.totalShares,
+      sharesBase: entry.sharesBase,
+    })
+  }
+  return checkpoints
+}
+
+// mirrors VaultCheckpoints::is_due
+export function checkpointDue(vault: any, account: any, now: number): boolean {
+  const checkpoints = chronologicalCheckpoints(account)
+  const latest = checkpoints[checkpoints.length - 1]
+  return !latest || now >= latest.timestamp + checkpointInterval(vault)
+}
+
+// mirrors VaultCheckpoints::at_or_before
+export function checkpointAtOrBefore(checkpoints: Checkpoint[], timestamp: number): Checkpoint | null {
+  let found: Checkpoint | null = null
+  for (const checkpoint of checkpoints) {
+    if (checkpoint.timestamp > timestamp) {
+      break
+    }
+    found = checkpoint
+  }
+  return found
+}
+
+// share value growth from `from` to `to`, undoing the x10 per rebase step in between
+function shareValueGrowth(from: Checkpoint, to: Checkpoint): number {
+  const growth = Number(to.activeShareValue.toString()) / Number(from.activeShareValue.toString())
+  return growth * Math.pow(10, from.sharesBase - to.sharesBase)
+}
+
+// APR and APY over the `days` before the latest checkpoint. Without a checkpoint exactly
+// `days` back the newest older one is used and the rate is annualized over the real gap.
+// Null until the history reaches back `days`
+export function trailingApy(checkpoints: Checkpoint[], days: number): TrailingApy | null {
+  const to = checkpoints[checkpoints.length - 1]
+  if (!to) {
+    return null
+  }
+  const from = checkpointAtOrBefore(checkpoints, to.timestamp - days * SECONDS_PER_DAY)
+  if (!from || from.activeShareValue.isZero()) {
+    return null
+  }
+
+  const years = (to.timestamp - from.timestamp) / SECONDS_PER_YEAR
+  const growth = shareValueGrowth(from, to)
+  return {
+    days,
+    from,
+    to,
+    apr: ((growth - 1) / years) * 100,
+    apy: (Math.pow(growth, 1 / years) - 1) * 100,
+  }
+}
@@ -1,14 +1,26 @@
 #!/usr/bin/env node
 
 import { VaultUserOperations, createConfig, loadWallet } from './user-operation'
+import * as anchor from '@coral-xyz/anchor'
 import * as fs from 'fs'
 import * as os from 'os'
 import { PublicKey } from '@solana/web3.js'
 import contract_info from './contract_info.json'
+import { createPriceSource } from './price-source'
+import { MetricsSink, MultiMetricsSink, PrometheusMetricsSink, TimingMetricsSink } from './metrics'
+import { SORT_KEYS, SortKey } from './compare'
+import { parseInterval } from './keeper'
+import { parseHoldersCsv, verifyHolders } from './holders'
+import { checkStake, checkUnstakeDust, confirmGuardrails, loadGuardrailConfig } from './guardrails'
+import { DEFAULT_SLIPPAGE_BPS, PRECISION, maxIn, minOut, stakeRejection } from './quote'
+import { AccountCache, DEFAULT_CACHE_TTL_SECONDS, defaultCacheDir } from './account-cache'
 
 // Command line parameter parsing
 const args = process.argv.slice(2)
 const command = args[0]
+const jsonOutput = args.includes('--json')
+// commands that poll or need every account read at the same moment skip the account cache
+const UNCACHED_COMMANDS = ['keeper', 'monitor', 'snapshot-holders', 'dashboard']
 
 // Help information
 const HELP_TEXT = `
@@ -21,27 +33,69 @@ Available commands:
   help                     Show help information
   init                     Initialize user depositor account
   stake <amount>           Stake specified amount (USDC)
-  request-unstake <amount> Request unstake specified amount
+  stake-for <beneficiary> <amount>  Stake from your wallet into another wallet's position
+  stake-term <amount>      Stake and request the unstake at once, pays out after one lockup period
+  request-unstake <amount|all> Request unstake specified amount or the whole position
   unstake                  Execute unstake (requires lockup period to end)
   cancel-unstake           Cancel unstake request
-  sync-rebase              Sync rebase
+  migrate-depositor        Grow a depositor account opened before layout versioning to the current layout
+  transfer-shares <recipient> <shares>|--all  Move active shares to another wallet's position, no lockup
+  instant-unstake <amount>|--all  Unstake now without the lockup, paying the vault's instant unstake penalty
+  sync-rebase [--all]      Sync rebase (--all syncs every depositor of the vault in batches)
   vault-info                     View vault information
+  list-vaults              List every vault in the on-chain registry
+  attest-upgrade-authority       Record the program's current upgrade authority on chain (shown by vault-info)
   balance                  View user token balance
   depositor-info                View user depositor information
   asset-value              View user asset value
+  preview-stake <amount>   Shares a stake of <amount> USDC would mint now (simulated, nothing is sent)
+  preview-unstake <shares> Assets requesting <shares> would freeze and pay out now (simulated)
+  vault-stats              Share accounting and active share value as the next instruction sees them
   unstake-status           View unstake request status
   reward-info              View reward distribution information
   report                   Generate complete user report
   add-rewards <amount>     Add rewards (USDC) to vault with 50/50 split
   simulate-rewards <amount> Simulate reward injection (dry run)
+  add-secondary-rewards <amount>  Distribute whole tokens of the vault's secondary reward mint to the stakers
+  claim-secondary-rewards  Claim the secondary reward tokens your position earned
   apy [days]               Calculate APY/APR based on vault performance (default: 30 days)
   apr [days]               Calculate APY/APR based on vault performance (default: 30 days)
+  init-checkpoints         Create the vault's on-chain share value history, used by apy for trailing 7/30-day yields
   stake-stats              View detailed stake statistics and user rankings
   stakers                  Alias for stake-stats
+  monitor [seconds]        Poll vault state and print changes (default: every 30 seconds)
+  dashboard [--vault <name>] [--vaults a,b]  Live read-only terminal view: totals, share value, events,
+                           pending unstakes and crank status (v switches between the vaults)
+  health                   Check the vault for warning signs (exits 2 when any are found)
+  doctor [--vault <name>] [--json]  Diagnose your depositor state and print the command fixing each problem
+  exit-plan [--amount X|--all]      Dated schedule and expected amounts for exiting X USDC or the whole position
+  keeper run [--interval 60s] [--once] [--priority-fee <micro-lamports>]
+                           Run the permissionless cranks (rebase sync, stats roll-up, forced distribution)
+  snapshot-holders [--slot N] [--out holders.csv] [--exclude-owner-fees]
+                           Write authority, shares and share fraction of every depositor as CSV
+  verify-holders <file> [--tolerance 1e-9]  Check a holders CSV: fractions sum to 1 and match the shares
+  compare --vaults a,b,c | --mint <address> [--owner <address>] [--sort-by <key>] [--json]
+                           Side-by-side TVL, share value, APY, lockup, fee, min stake, pause and pending ratio
 
 Configuration options:
   --wallet <path>          Specify wallet file path (default: ~/.config/solana/id.json)
   --rpc <url>              Specify RPC node URL (default: devnet)
+  --client-id <hex>        16-byte reference echoed in stake/request-unstake/unstake events
+  --metrics-listen <addr>  Serve Prometheus metrics on host:port at /metrics
+  --cache-ttl <seconds>    Serve account reads from the on-disk cache for this long (default: ${DEFAULT_CACHE_TTL_SECONDS})
+  --no-cache               Read every account from the RPC node
+  --timing [--timing-threshold <ms>]  Print RPC call counts, latency, retries and confirmation time at the end
+                           and flag calls slower than the threshold (default: 1000 ms)
+  --mint <address>         vault-info/compare: look vaults up by token mint instead of the configured name
+  --owner <address>        Vault owner for --mint lookups (default: wallet)
+  --price-source <spec>    USD price source for vault-info/stake-stats/report: file:prices.toml | pyth
+  --vault <name>           Vault name to operate on (default: contract_info.json vault_name)
+  --json                   doctor/compare: print machine-readable output only
+  --force                  stake/request-unstake: skip the guardrail confirmation
+  --slippage-bps <bps>     stake/request-unstake/unstake: fail on-chain when the result is this much worse
+                           than quoted (default: ${DEFAULT_SLIPPAGE_BPS})
+  --profile <path>         Profile config with a \`guardrails\` section (default: vault-profile.json)
+  --sort-by <key>          compare: ${SORT_KEYS.join(' | ')} (default: apy)
 
 Examples:
   node cli.ts init                          # Initialize user account
@@ -56,6 +110,14 @@ Examples:
   node cli.ts apy                               # Calculate APY/APR based on 30 days performance
   node cli.ts apr 7                             # Calculate APY/APR based on 7 days performance
   node cli.ts stake-stats                       # View all stakers and their amounts
+  node cli.ts vault-info --price-source file:prices.toml  # Show TVL in USD from a static price file
+  node cli.ts doctor --json > doctor.json       # Diagnose problems and save the result for support
+  node cli.ts exit-plan --all                   # When and how much an exit of the whole position pays out
+  node cli.ts keeper run --vault FOCX_Vault --interval 5m --metrics-listen 9464  # Long-running keeper
+  node cli.ts keeper run --once                 # One pass, e.g. from cron
+  node cli.ts dashboard --vault FOCX_Vault --vaults FOCX_Vault_2  # Live view, v switches vaults
+  node cli.ts snapshot-holders --slot 250000000 --out holders.csv --exclude-owner-fees  # Airdrop snapshot
+  node cli.ts compare --vaults FOCX_Vault,FOCX_Vault_2 --sort-by tvl  # Compare two vaults by TVL
 `
 
 // Get option value
@@ -67,12 +129,38 @@ function getOption(option: string, defaultValue?: string): string | undefined {
   return defaultValue
 }
 
+// --slippage-bps tolerance the stake and unstake bounds are derived from
+function slippageBps(): number {
+  const bps = Number(getOption('--slippage-bps', `${DEFAULT_SLIPPAGE_BPS}`))
+  if (!Number.isInteger(bps) || bps < 0 || bps > 10000) {
+    throw new Error('Please provide --slippage-bps as a whole number between 0 and 10000')
+  }
+  return bps
+}
+
+// PRECISION scaled share value from a view instruction, for display
+function shareValueText(value: anchor.BN): string {
+  return `${(Number(value.toString()) / Number(PRECISION.toString())).toFixed(9)} per share`
+}
+
+// on-disk account cache shared by CLI runs against the same RPC endpoint
+function createAccountCache(rpcUrl: string): AccountCache | undefined {
+  if (args.includes('--no-cache') || UNCACHED_COMMANDS.includes(command)) {
+    return undefined
+  }
+  const ttlSeconds = parseInt(getOption('--cache-ttl', `${DEFAULT_CACHE_TTL_SECONDS}`) || `${DEFAULT_CACHE_TTL_SECONDS}`)
+  if (isNaN(ttlSeconds) || ttlSeconds < 0) {
+    throw new Error('Please provide a valid --cache-ttl in seconds')
+  }
+  return new AccountCache(defaultCacheDir(rpcUrl), { ttlSeconds })
+}
+
 // Load configuration
 async function loadConfig() {
   // Load configuration from contract_info.json
   const config = createConfig(
     contract_info.programId,
-    contract_info.vault_name || 'FOCX_Vault',
+    getOption('--vault') || contract_info.vault_name || 'FOCX_Vault',
     contract_info.usdc_address,
     getOption('--rpc', 'https://api.devnet.solana.com') ||
       'https://api.devnet.solana.com'
@@ -88,23 +176,70 @@ async function loadConfig() {
   }
 
   const wallet = loadWallet(walletPath)
-  console.log(`🔑 Using wallet: ${wallet.publicKey.toString()}`)
-  console.log(`📄 Wallet file: ${walletPath}`)
+  if (!jsonOutput) {
+    console.log(`🔑 Using wallet: ${wallet.publicKey.toString()}`)
+    console.log(`📄 Wallet file: ${walletPath}`)
+  }
 
   return { config, wallet }
 }
 
 // Main function
 async function main() {
+  let timing: TimingMetricsSink | undefined
+  let cache: AccountCache | undefined
   try {
     if (!command || command === 'help') {
       console.log(HELP_TEXT)
       return
     }
 
+    // Offline commands
+    if (command === 'verify-holders') {
+      const holdersPath = args[1]
+      if (!holdersPath || !fs.existsSync(holdersPath)) {
+        throw new Error('Please provide an existing holders CSV file')
+      }
+      const tolerance = parseFloat(getOption('--tolerance', '1e-9') || '1e-9')
+      const holders = parseHoldersCsv(fs.readFileSync(holdersPath, 'utf-8'))
+      const verification = verifyHolders(holders, tolerance)
+      console.log(`🔎 ${holders.length} holders, fractions sum to ${verification.fractionSum}`)
+      verification.problems.forEach((problem) => console.log(`❌ ${problem}`))
+      if (!verification.ok) {
+        process.exit(2)
+      }
+      console.log('✅ holders file is consistent')
+      return
+    }
+
     // Load configuration
     const { config, wallet } = await loadConfig()
-    const operations = new VaultUserOperations(config, wallet)
+    const metricsListen = getOption('--metrics-listen')
+    const prometheus = metricsListen ? new PrometheusMetricsSink() : undefined
+    if (prometheus && metricsListen) {
+      prometheus.listen(metricsListen)
+    }
+    if (args.includes('--timing')) {
+      const threshold = parseInt(getOption('--timing-threshold', '1000') || '1000')
+      if (isNaN(threshold) || threshold <= 0) {
+        throw new Error('Please provide a valid --timing-threshold in milliseconds')
+      }
+      timing = new TimingMetricsSink(threshold)
+    }
+    const sinks = [prometheus, timing].filter((sink) => sink !== undefined) as MetricsSink[]
+    const metrics = sinks.length > 1 ? new MultiMetricsSink(sinks) : sinks[0]
+    cache = createAccountCache(config.rpcUrl)
+    const operations = new VaultUserOperations(config, wallet, metrics, cache)
+
+    const priceSourceSpec = getOption('--price-source')
+    if (priceSourceSpec) {
+      const pythFeeds: Record<string, string> = {}
+      const feedId = (contract_info as any).pyth_feed_id
+      if (feedId) {
+        pythFeeds[contract_info.usdc_address] = feedId
+      }
+      operations.setPriceSource(createPriceSource(priceSourceSpec, { pythFeeds }))
+    }
 
     // Execute command
     switch (command) {
@@ -118,22 +253,110 @@ async function main() {
         if (isNaN(stakeAmount) || stakeAmount <= 0) {
           throw new Error('Please provide a valid stake amount')
         }
-        console.log(`💰 Staking ${stakeAmount} USDC...`)
-        await operations.stake(stakeAmount * 1e9)
+        const stakeRejected = stakeRejection(await operations.stakeCapacity(), stakeAmount * 1e9)
+        if (stakeRejected) {
+          throw new Error(stakeRejected)
+        }
+        const stakeContext = await operations.guardrailContext()
+        await confirmGuardrails(
+          checkStake(stakeAmount * 1e9, stakeContext.walletBalance, loadGuardrailConfig(getOption('--profile'))),
+          args.includes('--force')
+        )
+        const minSharesOut = minOut((await operations.previewStake(stakeAmount * 1e9)).shares, slippageBps())
+        console.log(`💰 Staking ${stakeAmount} USDC for at least ${minSharesOut} shares...`)
+        await operations.stake(stakeAmount * 1e9, getOption('--client-id'), minSharesOut)
+        break
+
+      case 'stake-for':
+        const beneficiary = new PublicKey(args[1])
+        const stakeForAmount = parseFloat(args[2])
+        if (isNaN(stakeForAmount) || stakeForAmount <= 0) {
+          throw new Error('Please provide a valid stake amount')
+        }
+        const stakeForRejected = stakeRejection(await operations.stakeCapacity(beneficiary), stakeForAmount * 1e9)
+        if (stakeForRejected) {
+          throw new Error(stakeForRejected)
+        }
+        const stakeForContext = await operations.guardrailContext()
+        await confirmGuardrails(
+          checkStake(stakeForAmount * 1e9, stakeForContext.walletBalance, loadGuardrailConfig(getOption('--profile'))),
+          args.includes('--force')
+        )
+        console.log(`💰 Staking ${stakeForAmount} USDC for ${beneficiary.toString()}...`)
+        await operations.stakeFor(beneficiary, stakeForAmount * 1e9, getOption('--client-id'))
+        break
+
+      case 'stake-term':
+        const termAmount = parseFloat(args[1])
+        if (isNaN(termAmount) || termAmount <= 0) {
+          throw new Error('Please provide a valid stake amount')
+        }
+        const termRejected = stakeRejection(await operations.stakeCapacity(), termAmount * 1e9)
+        if (termRejected) {
+          throw new Error(termRejected)
+        }
+        const termContext = await operations.guardrailContext()
+        await confirmGuardrails(
+          checkStake(termAmount * 1e9, termContext.walletBalance, loadGuardrailConfig(getOption('--profile'))),
+          args.includes('--force')
+        )
+        console.log(`🔒 Staking ${termAmount} USDC for one lockup term...`)
+        await operations.stakeWithTerm(termAmount * 1e9, getOption('--client-id'))
         break
 
       case 'request-unstake':
+        if (args[1] === 'all') {
+          const minFrozen = minOut(await operations.quoteRequestUnstake('all'), slippageBps())
+          console.log(`📤 Requesting unstake of the whole position, freezing at least ${minFrozen.toNumber() / 1e9} USDC...`)
+          await operations.requestUnstake('all', getOption('--client-id'), minFrozen)
+          break
+        }
         const requestAmount = parseFloat(args[1])
         if (isNaN(requestAmount) || requestAmount <= 0) {
-          throw new Error('Please provide a valid unstake amount')
+          throw new Error('Please provide a valid unstake amount or all')
         }
-        console.log(`📤 Requesting unstake ${requestAmount} USDC...`)
-        await operations.requestUnstake(requestAmount * 1e9)
+        const unstakeContext = await operations.guardrailContext()
+        await confirmGuardrails(
+          checkUnstakeDust(unstakeContext.positionValue, requestAmount * 1e9, unstakeContext.minStakeAmount),
+          args.includes('--force')
+        )
+        const maxSharesIn = maxIn(await operations.quoteRequestUnstake(requestAmount * 1e9), slippageBps())
+        console.log(`📤 Requesting unstake ${requestAmount} USDC for at most ${maxSharesIn} shares...`)
+        await operations.requestUnstake(requestAmount * 1e9, getOption('--client-id'), maxSharesIn)
         break
 
       case 'unstake':
-        console.log('💸 Executing unstake...')
-        await operations.unstake()
+        const minAmountOut = minOut(await operations.quoteUnstakePayout(), slippageBps())
+        console.log(`💸 Executing unstake, receiving at least ${minAmountOut.toNumber() / 1e9} USDC...`)
+        await operations.unstake(getOption('--client-id'), minAmountOut)
+        break
+
+      case 'instant-unstake':
+        if (args.includes('--all')) {
+          console.log('⚡ Instant unstaking all active shares...')
+          await operations.instantUnstake('all', getOption('--client-id'))
+          break
+        }
+        const instantAmount = parseFloat(args[1])
+        if (isNaN(instantAmount) || instantAmount <= 0) {
+          throw new Error('Please provide a valid unstake amount or --all')
+        }
+        console.log(`⚡ Instant unstaking ${instantAmount} USDC...`)
+        await operations.instantUnstake(instantAmount * 1e9, getOption('--client-id'))
+        break
+
+      case 'transfer-shares':
+        const shareRecipient = new PublicKey(args[1])
+        if (args.includes('--all')) {
+          console.log(`🔁 Transferring all active shares to ${shareRecipient.toString()}...`)
+          await operations.transferShares(shareRecipient, 'all')
+          break
+        }
+        if (!/^[1-9][0-9]*$/.test(args[2] ?? '')) {
+          throw new Error('Please provide a whole number of shares or --all')
+        }
+        console.log(`🔁 Transferring ${args[2]} shares to ${shareRecipient.toString()}...`)
+        await operations.transferShares(shareRecipient, new anchor.BN(args[2]))
         break
 
       case 'cancel-unstake':
@@ -141,16 +364,60 @@ async function main() {
         await operations.cancelUnstakeRequest()
         break
 
+      case 'migrate-depositor':
+        console.log('📦 Migrating depositor account...')
+        await operations.migrateDepositor()
+        break
+
       case 'sync-rebase':
-        console.log('🔄 Syncing rebase...')
-        await operations.syncRebase()
+        if (args.includes('--all')) {
+          console.log('🔄 Syncing rebase for all depositors...')
+          await operations.syncRebaseAll()
+        } else {
+          console.log('🔄 Syncing rebase...')
+          await operations.syncRebase()
+        }
         break
 
       case 'vault-info':
+        const mintOption = getOption('--mint')
+        if (mintOption) {
+          const owner = new PublicKey(getOption('--owner') || wallet.publicKey.toString())
+          const vaults = await operations.findVaults(owner, new PublicKey(mintOption))
+          if (vaults.length === 0) {
+            throw new Error(`No registered vaults for owner ${owner.toString()} and mint ${mintOption}`)
+          }
+          for (const registered of vaults) {
+            console.log(`\n📊 Vault #${registered.index} "${registered.name}" (${registered.vault.toString()})`)
+            operations.useVault(registered.name)
+            await operations.getVaultInfo()
+          }
+          break
+        }
         console.log('📊 Getting vault information...')
         await operations.getVaultInfo()
         break
 
+      case 'list-vaults':
+        const listed = await operations.listVaults()
+        if (jsonOutput) {
+          console.log(JSON.stringify(listed.map((entry) => ({ ...entry, vault: entry.vault.toString() })), null, 2))
+          return
+        }
+        if (listed.length === 0) {
+          console.log('No vaults registered')
+          break
+        }
+        for (const entry of listed) {
+          console.log(`📊 "${entry.name}" ${entry.vault.toString()} (page ${entry.page})`)
+        }
+        break
+
+      case 'attest-upgrade-authority':
+        console.log('🔏 Attesting the program upgrade authority...')
+        await operations.attestUpgradeAuthority()
+        break
+
       case 'balance':
         console.log('💰 Getting user token balance...')
         await operations.getUserTokenBalance()
@@ -166,6 +433,41 @@ async function main() {
         await operations.getUserAssetValue()
         break
 
+      case 'preview-stake':
+        const previewAmount = parseFloat(args[1])
+        if (isNaN(previewAmount) || previewAmount <= 0) {
+          throw new Error('Please provide a valid stake amount')
+        }
+        const stakePreview = await operations.previewStake(previewAmount * 1e9)
+        console.log(`shares: ${stakePreview.shares.toString()} (shares base 10^${stakePreview.sharesBase})`)
+        console.log(`deposit fee: ${stakePreview.depositFee.toNumber() / 1e9} USDC`)
+        console.log(`share value after: ${shareValueText(stakePreview.activeShareValue)}`)
+        break
+
+      case 'preview-unstake':
+        if (!args[1] || !/^[0-9]+$/.test(args[1])) {
+          throw new Error('Please provide a whole number of shares')
+        }
+        const unstakePreview = await operations.previewUnstake(new anchor.BN(args[1]))
+        console.log(`shares: ${unstakePreview.shares.toString()} (shares base 10^${unstakePreview.sharesBase})`)
+        console.log(`frozen amount: ${unstakePreview.frozenAmount.toNumber() / 1e9} USDC`)
+        console.log(`withdrawal fee: ${unstakePreview.withdrawalFee.toNumber() / 1e9} USDC`)
+        console.log(`paid out: ${unstakePreview.netAmount.toNumber() / 1e9} USDC`)
+        break
+
+      case 'vault-stats':
+        const stats = await operations.vaultStats()
+        if (jsonOutput) {
+          console.log(JSON.stringify(stats, (_, value) => (value instanceof anchor.BN ? value.toString() : value), 2))
+          break
+        }
+        console.log(`total assets: ${stats.totalAssets.toNumber() / 1e9} USDC (${stats.unvestedRewards.toNumber() / 1e9} USDC still vesting)`)
+        console.log(`total shares: ${stats.totalShares.toString()} (${stats.activeShares.toString()} active, ${stats.pendingUnstakeShares.toString()} pending)`)
+        console.log(`reserved assets: ${stats.reservedAssets.toNumber() / 1e9} USDC`)
+        console.log(`active share value: ${shareValueText(stats.activeShareValue)}`)
+        console.log(`rebase: v${stats.rebaseVersion}, base 10^${stats.sharesBase}`)
+        break
+
       case 'unstake-status':
         console.log('⏰ Checking unstake request status...')
         await operations.checkUnstakeRequestStatus()
@@ -190,6 +492,20 @@ async function main() {
         await operations.addRewards(rewardAmount * 1e9)
         break
 
+      case 'add-secondary-rewards':
+        const secondaryAmount = parseFloat(args[1])
+        if (isNaN(secondaryAmount) || secondaryAmount <= 0) {
+          throw new Error('Please provide a valid reward amount')
+        }
+        console.log(`🎁 Adding ${secondaryAmount} secondary reward tokens to vault...`)
+        await operations.addSecondaryRewards(secondaryAmount)
+        break
+
+      case 'claim-secondary-rewards':
+        console.log('🎁 Claiming secondary rewards...')
+        await operations.claimSecondaryRewards()
+        break
+
       case 'simulate-rewards':
         const simulateAmount = parseFloat(args[1])
         if (isNaN(simulateAmount) || simulateAmount <= 0) {
@@ -211,12 +527,108 @@ async function main() {
         }
         break
 
+      case 'init-checkpoints':
+        console.log('📐 Creating the share value checkpoint history...')
+        await operations.initCheckpoints()
+        break
+
       case 'stake-stats':
       case 'stakers':
         console.log('📊 Getting stake statistics...')
         await operations.getStakeStatistics()
         break
 
+      case 'monitor':
+        const monitorInterval = args[1] && !args[1].startsWith('--') ? parseInt(args[1]) : 30
+        if (isNaN(monitorInterval) || monitorInterval <= 0) {
+          throw new Error('Please provide a valid polling interval in seconds')
+        }
+        console.log(`👀 Monitoring vault every ${monitorInterval}s (Ctrl+C to stop)...`)
+        await operations.monitor(monitorInterval)
+        break
+
+      case 'health':
+        console.log('🩺 Checking vault health...')
+        const warnings = await operations.healthCheck()
+        if (warnings.length > 0) {
+          process.exit(2)
+        }
+        break
+
+      case 'doctor':
+        if (!jsonOutput) {
+          console.log('🩺 Diagnosing depositor state...')
+        }
+        await operations.doctor(jsonOutput)
+        return
+
+      case 'exit-plan':
+        if (args.includes('--all')) {
+          await operations.exitPlan('all')
+          break
+        }
+        const exitAmount = parseFloat(getOption('--amount') || '')
+        if (isNaN(exitAmount) || exitAmount <= 0) {
+          throw new Error('Please provide --amount <USDC> or --all')
+        }
+        await operations.exitPlan(exitAmount * 1e9)
+        break
+
+      case 'keeper':
+        if (args[1] !== 'run') {
+          throw new Error("Usage: keeper run [--interval 60s] [--once] [--priority-fee <micro-lamports>]")
+        }
+        const priorityFee = parseInt(getOption('--priority-fee', '0') || '0')
+        if (isNaN(priorityFee) || priorityFee < 0) {
+          throw new Error('Please provide a valid --priority-fee in micro-lamports')
+        }
+        await operations.keeper({
+          intervalSeconds: parseInterval(getOption('--interval')),
+          once: args.includes('--once'),
+          priorityFeeMicroLamports: priorityFee,
+        })
+        break
+
+      case 'dashboard':
+        const dashboardNames = [config.vaultName].concat(
+          (getOption('--vaults') || '').split(',').filter((name) => name.length > 0 && name !== config.vaultName)
+        )
+        await operations.dashboard(dashboardNames)
+        return
+
+      case 'snapshot-holders':
+        const snapshotSlot = getOption('--slot')
+        if (snapshotSlot !== undefined && isNaN(parseInt(snapshotSlot))) {
+          throw new Error('Please provide a valid --slot')
+        }
+        await operations.snapshotHolders(
+          getOption('--out', 'holders.csv') || 'holders.csv',
+          snapshotSlot !== undefined ? parseInt(snapshotSlot) : undefined,
+          args.includes('--exclude-owner-fees')
+        )
+        break
+
+      case 'compare':
+        const sortBy = (getOption('--sort-by') || 'apy') as SortKey
+        if (!SORT_KEYS.includes(sortBy)) {
+          throw new Error(`--sort-by must be one of ${SORT_KEYS.join(', ')}`)
+        }
+        let compareNames = (getOption('--vaults') || '').split(',').filter((name) => name.length > 0)
+        const compareMint = getOption('--mint')
+        if (compareMint) {
+          const owner = new PublicKey(getOption('--owner') || wallet.publicKey.toString())
+          const registered = await operations.findVaults(owner, new PublicKey(compareMint))
+          compareNames = compareNames.concat(registered.map((entry) => entry.name))
+        }
+        if (compareNames.length === 0) {
+          throw new Error('Please provide --vaults a,b,c or --mint <address>')
+        }
+        await operations.compareVaults(compareNames, sortBy, jsonOutput)
+        if (jsonOutput) {
+          return
+        }
+        break
+
       default:
         console.error(`❌ Unknown command: ${command}`)
         console.log("\nUse 'node cli.ts help' to view help information")
@@ -226,7 +638,17 @@ async function main() {
     console.log('\n✅ Operation completed!')
   } catch (error) {
     console.error('❌ Operation failed:', error)
+    if (timing) {
+      console.error(timing.report())
+    }
     process.exit(1)
+  } finally {
+    if (cache) {
+      await cache.settle()
+    }
+    if (timing) {
+      console.error(timing.report())
+    }
   }
 }
 
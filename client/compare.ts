@@ -0,0 +1,157 @@
+import { PublicKey } from '@solana/web3.js'
+import { PRECISION, activeShareValue } from './quote'
+import { isOperationPaused } from './pause'
+
+const SECONDS_PER_DAY = 24 * 60 * 60
+
+export interface YieldEstimate {
+  apr: number | null
+  apy: number | null
+  // why the estimate is missing, when it is
+  note?: string
+}
+
+// trailing yield over the vault's lifetime: total rewards relative to current assets,
+// annualized simple (APR) and compounded daily (APY), both in percent
+export function estimateYield(vault: any, now: number): YieldEstimate & { dailyYield: number } {
+  const totalAssets = vault.totalAssets.toNumber()
+  const totalRewards = vault.totalRewards.toNumber()
+  const ageDays = (now - vault.createdAt.toNumber()) / SECONDS_PER_DAY
+
+  if (totalRewards === 0) {
+    return { apr: null, apy: null, dailyYield: 0, note: 'no rewards distributed yet' }
+  }
+  if (totalAssets <= 0 || ageDays <= 0) {
+    return { apr: null, apy: null, dailyYield: 0, note: 'insufficient data' }
+  }
+  const dailyYield = totalRewards / totalAssets / ageDays
+  return {
+    apr: dailyYield * 365 * 100,
+    apy: (Math.pow(1 + dailyYield, 365) - 1) * 100,
+    dailyYield,
+  }
+}
+
+export interface VaultSummary {
+  name: string
+  vault: string
+  found: boolean
+  tvl: number | null
+  shareValue: number | null
+  apy: number | null
+  lockupHours: number | null
+  platformSharePercent: number | null
+  minStake: number | null
+  isPaused: boolean | null
+  pendingUnstakeRatio: number | null
+  notes: string[]
+}
+
+export const SORT_KEYS = ['name', 'tvl', 'share-value', 'apy', 'lockup', 'fee', 'min-stake', 'pending'] as const
+export type SortKey = typeof SORT_KEYS[number]
+
+// amounts in UI units (9 decimals), share value in tokens per share
+export function summarizeVault(name: string, vault: PublicKey, account: any | null, now: number): VaultSummary {
+  if (!account) {
+    return {
+      name,
+      vault: vault.toBase58(),
+      found: false,
+      tvl: null,
+      shareValue: null,
+      apy: null,
+      lockupHours: null,
+      platformSharePercent: null,
+      minStake: null,
+      isPaused: null,
+      pendingUnstakeRatio: null,
+      notes: ['vault account not found'],
+    }
+  }
+
+  const notes: string[] = []
+  const estimate = estimateYield(account, now)
+  if (estimate.note) {
+    notes.push(`APY n/a: ${estimate.note}`)
+  }
+  const totalShares = account.totalShares.toNumber()
+  if (totalShares === 0) {
+    notes.push('pending ratio n/a: no shares')
+  }
+  return {
+    name,
+    vault: vault.toBase58(),
+    found: true,
+    tvl: account.totalAssets.toNumber() / 1e9,
+    shareValue: activeShareValue(account).toNumber() / PRECISION.toNumber(),
+    apy: estimate.apy,
+    lockupHours: account.unstakeLockupPeriod.toNumber() / 3600,
+    platformSharePercent: account.platformShareBps.toNumber() / 100,
+    minStake: account.minStakeAmount.toNumber() / 1e9,
+    isPaused: isOperationPaused(account, 'stake'),
+    pendingUnstakeRatio: totalShares === 0 ? null : account.pendingUnstakeShares.toNumber() / totalShares,
+    notes,
+  }
+}
+
+function sortValue(summary: VaultSummary, key: SortKey): number | string | null {
+  switch (key) {
+    case 'name':
+      return summary.name
+    case 'tvl':
+      return summary.tvl
+    case 'share-value':
+      return summary.shareValue
+    case 'apy':
+      return summary.apy
+    case 'lockup':
+      return summary.lockupHours
+    case 'fee':
+      return summary.platformSharePercent
+    case 'min-stake':
+      return summary.minStake
+    case 'pending':
+      return summary.pendingUnstakeRatio
+  }
+}
+
+// name, lockup, fee, min-stake and pending ascend; tvl, share-value and apy descend; n/a always last
+export function sortSummaries(summaries: VaultSummary[], key: SortKey): VaultSummary[] {
+  const descending = key === 'tvl' || key === 'share-value' || key === 'apy'
+  return [...summaries].sort((a, b) => {
+    const left = sortValue(a, key)
+    const right = sortValue(b, key)
+    if (left === null || right === null) {
+      return left === right ? 0 : left === null ? 1 : -1
+    }
+    const order = left < right ? -1 : left > right ? 1 : 0
+    return descending ? -order : order
+  })
+}
+
+function cell(value: number | boolean | null, format: (value: number) => string): string {
+  if (value === null) return 'n/a'
+  if (typeof value === 'boolean') return value ? 'yes' : 'no'
+  return format(value)
+}
+
+export function formatCompareTable(summaries: VaultSummary[]): string {
+  const header = ['vault', 'TVL (USDC)', 'share value', 'APY', 'lockup', 'platform share', 'min stake', 'paused', 'pending']
+  const rows = summaries.map((s) => [
+    s.name,
+    cell(s.tvl, (v) => v.toFixed(2)),
+    cell(s.shareValue, (v) => v.toFixed(6)),
+    cell(s.apy, (v) => `${v.toFixed(2)}%`),
+    cell(s.lockupHours, (v) => `${v}h`),
+    cell(s.platformSharePercent, (v) => `${v}%`),
+    cell(s.minStake, (v) => v.toString()),
+    cell(s.isPaused, (v) => v.toString()),
+    cell(s.pendingUnstakeRatio, (v) => `${(v * 100).toFixed(2)}%`),
+  ])
+  const widths = header.map((title, column) => Math.max(title.length, ...rows.map((row) => row[column].length)))
+  const line = (row: string[]) => row.map((value, column) => value.padEnd(widths[column])).join('  ')
+
+  const lines = [line(header), widths.map((width) => '-'.repeat(width)).join('  '), ...rows.map(line)]
+  summaries.forEach((s) => s.notes.forEach((note) => lines.push(`* ${s.name}: ${note}`)))
+  return lines.join('\n')
+}
@@ -0,0 +1,142 @@
+import { createHash } from 'crypto'
+import * as anchor from '@coral-xyz/anchor'
+import { PublicKey } from '@solana/web3.js'
+import { applyPauseParams, pausedMask } from './pause'
+
+// the fields hashed by `Vault::config_digest`, in hashing order. The pause mask is hashed
+// in place of `isPaused`, with the legacy flags folded in
+export interface VaultConfigFields {
+  owner: PublicKey
+  platformAccount: PublicKey
+  tokenMint: PublicKey
+  unstakeLockupPeriod: anchor.BN
+  platformShareBps: anchor.BN
+  minStakeAmount: anchor.BN
+  maxTotalAssets: anchor.BN
+  pausedOperations: number
+  isPaused: boolean
+  instantUnstakeFeeBps: anchor.BN
+  stakeCooldownSeconds: anchor.BN
+  withdrawalsPaused: boolean
+  permissioned: boolean
+  maxStakePerDepositor: anchor.BN
+  rewardVestingPeriod: anchor.BN
+  performanceFeeBps: anchor.BN
+  withdrawalFeeBps: anchor.BN
+  unstakeRequestExpiry: anchor.BN
+  rewardAuthority: PublicKey
+  permissionlessRewards: boolean
+  pauseOnShortfall: boolean
+  configTimelockSeconds: anchor.BN
+  minUnstakeAmount: anchor.BN
+  checkpointInterval: anchor.BN
+  managementFeeBps: anchor.BN
+  depositFeeBps: anchor.BN
+  depositFeeToOwner: boolean
+}
+
+function le32(value: number): Buffer {
+  const bytes = Buffer.alloc(4)
+  bytes.writeUInt32LE(value >>> 0)
+  return bytes
+}
+
+function le64(value: anchor.BN): Buffer {
+  return value.toTwos(64).toArrayLike(Buffer, 'le', 8)
+}
+
+// SHA-256 over the Borsh serialization of the config fields, same bytes as the program hashes
+export function configDigest(config: VaultConfigFields): string {
+  const bytes = Buffer.concat([
+    config.owner.toBuffer(),
+    config.platformAccount.toBuffer(),
+    config.tokenMint.toBuffer(),
+    le64(config.unstakeLockupPeriod),
+    le64(config.platformShareBps),
+    le64(config.minStakeAmount),
+    le64(config.maxTotalAssets),
+    le32(pausedMask(config)),
+    le64(config.instantUnstakeFeeBps),
+    le64(config.stakeCooldownSeconds),
+    Buffer.from([config.permissioned ? 1 : 0]),
+    le64(config.maxStakePerDepositor),
+    le64(config.rewardVestingPeriod),
+    le64(config.performanceFeeBps),
+    le64(config.withdrawalFeeBps),
+    le64(config.unstakeRequestExpiry),
+    config.rewardAuthority.toBuffer(),
+    Buffer.from([config.permissionlessRewards ? 1 : 0]),
+    Buffer.from([config.pauseOnShortfall ? 1 : 0]),
+    le64(config.configTimelockSeconds),
+    le64(config.minUnstakeAmount),
+    le64(config.checkpointInterval),
+    le64(config.managementFeeBps),
+    le64(config.depositFeeBps),
+    Buffer.from([config.depositFeeToOwner ? 1 : 0]),
+  ])
+  return createHash('sha256').update(bytes).digest('hex')
+}
+
+// digest the vault will have after `update_vault_config(params)`; null params leave a field unchanged
+export function predictConfigDigest(
+  current: VaultConfigFields,
+  params: {
+    unstakeLockupPeriod?: anchor.BN | null
+    platformShareBps?: anchor.BN | null
+    minStakeAmount?: anchor.BN | null
+    maxTotalAssets?: anchor.BN | null
+    isPaused?: boolean | null
+    platformAccount?: PublicKey | null
+    instantUnstakeFeeBps?: anchor.BN | null
+    stakeCooldownSeconds?: anchor.BN | null
+    depositsPaused?: boolean | null
+    withdrawalsPaused?: boolean | null
+    permissioned?: boolean | null
+    maxStakePerDepositor?: anchor.BN | null
+    pausedOperations?: number | null
+    rewardVestingPeriod?: anchor.BN | null
+    performanceFeeBps?: anchor.BN | null
+    withdrawalFeeBps?: anchor.BN | null
+    unstakeRequestExpiry?: anchor.BN | null
+    rewardAuthority?: PublicKey | null
+    permissionlessRewards?: boolean | null
+    pauseOnShortfall?: boolean | null
+    configTimelockSeconds?: anchor.BN | null
+    minUnstakeAmount?: anchor.BN | null
+    checkpointInterval?: anchor.BN | null
+    managementFeeBps?: anchor.BN | null
+    depositFeeBps?: anchor.BN | null
+    depositFeeToOwner?: boolean | null
+  }
+): string {
+  return configDigest({
+    owner: current.owner,
+    platformAccount: params.platformAccount ?? current.platformAccount,
+    tokenMint: current.tokenMint,
+    unstakeLockupPeriod: params.unstakeLockupPeriod ?? current.unstakeLockupPeriod,
+    platformShareBps: params.platformShareBps ?? current.platformShareBps,
+    minStakeAmount: params.minStakeAmount ?? current.minStakeAmount,
+    maxTotalAssets: params.maxTotalAssets ?? current.maxTotalAssets,
+    // an update folds the legacy flags into the mask and clears them
+    pausedOperations: applyPauseParams(pausedMask(current), params),
+    isPaused: false,
+    instantUnstakeFeeBps: params.instantUnstakeFeeBps ?? current.instantUnstakeFeeBps,
+    stakeCooldownSeconds: params.stakeCooldownSeconds ?? current.stakeCooldownSeconds,
+    withdrawalsPaused: false,
+    permissioned: params.permissioned ?? current.permissioned,
+    maxStakePerDepositor: params.maxStakePerDepositor ?? current.maxStakePerDepositor,
+    rewardVestingPeriod: params.rewardVestingPeriod ?? current.rewardVestingPeriod,
+    performanceFeeBps: params.performanceFeeBps ?? current.performanceFeeBps,
+    withdrawalFeeBps: params.withdrawalFeeBps ?? current.withdrawalFeeBps,
+    unstakeRequestExpiry: params.unstakeRequestExpiry ?? current.unstakeRequestExpiry,
+    rewardAuthority: params.rewardAuthority ?? current.rewardAuthority,
+    permissionlessRewards: params.permissionlessRewards ?? current.permissionlessRewards,
+    pauseOnShortfall: params.pauseOnShortfall ?? current.pauseOnShortfall,
+    configTimelockSeconds: params.configTimelockSeconds ?? current.configTimelockSeconds,
+    minUnstakeAmount: params.minUnstakeAmount ?? current.minUnstakeAmount,
+    checkpointInterval: params.checkpointInterval ?? current.checkpointInterval,
+    managementFeeBps: params.managementFeeBps ?? current.managementFeeBps,
+    depositFeeBps: params.depositFeeBps ?? current.depositFeeBps,
+    depositFeeToOwner: params.depositFeeToOwner ?? current.depositFeeToOwner,
+  })
+}
@@ -0,0 +1,217 @@
+import * as anchor from '@coral-xyz/anchor'
+import { formatDuration } from './doctor'
+import { VaultEvent } from './events'
+import { DECOMMISSION_FORCE_TIMEOUT, forceDistributionDue, rebaseDue } from './keeper'
+import { PRECISION, activeShareValue, frozenAmount, unlockTime } from './quote'
+import { pausedMask, pausedOperationNames } from './pause'
+
+// everything one frame shows, gathered by the caller so rendering needs no RPC
+export interface DashboardData {
+  vaultName: string
+  vaultAddress: string
+  vault: any
+  // depositor accounts of the vault, for pending unstakes and sync status
+  depositors: any[]
+  // active share value samples, oldest first
+  shareValues: number[]
+  // newest last
+  events: { at: number; event: VaultEvent }[]
+  now: number
+  // last refresh error, shown next to the key help
+  status?: string
+}
+
+// samples and events kept per vault while the dashboard runs
+export const MAX_SHARE_VALUE_SAMPLES = 500
+export const MAX_DASHBOARD_EVENTS = 50
+
+export const DASHBOARD_KEYS = 'r refresh   v next vault   q quit   (read-only, nothing is signed)'
+
+const SPARK_LEVELS = '▁▂▃▄▅▆▇█'
+
+function bn(value: anchor.BN | number | string): anchor.BN {
+  return new anchor.BN(value.toString())
+}
+
+// base units (1e9) as USDC with two decimals, exact for any u64
+export function formatUsdc(value: anchor.BN | number | string): string {
+  const digits = bn(value).toString().padStart(10, '0')
+  return `${digits.slice(0, -9)}.${digits.slice(-9, -7)}`
+}
+
+function shortKey(key: string): string {
+  return key.length > 10 ? `${key.slice(0, 4)}…${key.slice(-4)}` : key
+}
+
+// active share value as a float, 1.0 = one base unit per share
+export function shareValueSample(vault: any): number {
+  return activeShareValue(vault).toNumber() / PRECISION.toNumber()
+}
+
+export function sparkline(values: number[], width: number): string {
+  const recent = values.slice(-width)
+  if (recent.length === 0) {
+    return ''
+  }
+  const min = Math.min(...recent)
+  const max = Math.max(...recent)
+  return recent
+    .map((value) => {
+      const level = max === min ? 0 : Math.round(((value - min) / (max - min)) * (SPARK_LEVELS.length - 1))
+      return SPARK_LEVELS[level]
+    })
+    .join('')
+}
+
+// what the keeper would do for this vault right now
+export function crankStatus(vault: any, depositors: any[], now: number): { name: string; status: string }[] {
+  const behind = depositors.filter((depositor) => depositor.lastRebaseVersion < vault.rebaseVersion).length
+  let forceDistribute = 'idle'
+  if (forceDistributionDue(vault, now)) {
+    forceDistribute = 'due'
+  } else if (bn(vault.decommissionStartedAt).toNumber() !== 0) {
+    const dueAt = bn(vault.decommissionNoticeEndsAt).toNumber() + DECOMMISSION_FORCE_TIMEOUT
+    forceDistribute = `in ${formatDuration(dueAt - now)}`
+  }
+  return [
+    { name: 'crank_rebase', status: rebaseDue(vault) ? 'due' : 'idle' },
+    { name: 'sync_rebase_batch', status: behind > 0 ? `due, ${behind} depositor(s) behind` : 'idle' },
+    { name: 'force_distribute', status: forceDistribute },
+  ]
+}
+
+// one line per event: name and the fields an operator looks at
+export function summarizeEvent(event: VaultEvent): string {
+  const parts = [event.name]
+  if (event.data.authority) {
+    parts.push(shortKey(event.data.authority.toString()))
+  }
+  if (event.data.amount !== undefined) {
+    parts.push(`${formatUsdc(event.data.amount)} USDC`)
+  }
+  if (event.data.shares !== undefined) {
+    parts.push(`${event.data.shares.toString()} shares`)
+  }
+  // every depositor's share count was divided, say by how much and what is left
+  if (event.data.rebaseDivisor !== undefined) {
+    parts.push(`÷${event.data.rebaseDivisor.toString()}`, `base 10^${event.data.sharesBase}`)
+    if (event.data.totalShares !== undefined) {
+      parts.push(`${event.data.totalShares.toString()} total shares`)
+    }
+  }
+  return parts.join('  ')
+}
+
+function fit(text: string, width: number): string {
+  if (text.length > width) {
+    return text.slice(0, Math.max(width - 1, 0)) + '…'
+  }
+  return text + ' '.repeat(width - text.length)
+}
+
+// framed pane of exactly `width` x `height` characters
+export function pane(title: string, lines: string[], width: number, height: number): string[] {
+  const inner = width - 2
+  const label = `─ ${title} `
+  const heading = label.length >= inner ? fit(label, inner) : label + '─'.repeat(inner - label.length)
+  const body = lines.slice(0, height - 2)
+  while (body.length < height - 2) {
+    body.push('')
+  }
+  return ['┌' + heading + '┐', ...body.map((line) => '│' + fit(line, inner) + '│'), '└' + '─'.repeat(inner) + '┘']
+}
+
+function sideBySide(left: string[], right: string[]): string[] {
+  return left.map((line, index) => line + right[index])
+}
+
+// the whole dashboard as `height` lines of `width` characters
+export function renderDashboard(data: DashboardData, width: number = 100, height: number = 32): string[] {
+  const { vault, now } = data
+  const leftWidth = Math.floor(width / 2)
+  const rightWidth = width - leftWidth
+  const topHeight = 8
+  const middleHeight = 8
+  const eventsHeight = Math.max(height - topHeight - middleHeight - 2, 3)
+
+  const totals = pane(
+    'Vault',
+    [
+      `total assets    ${formatUsdc(vault.totalAssets)} USDC`,
+      `reserved        ${formatUsdc(vault.reservedAssets)} USDC`,
+      `total shares    ${vault.totalShares.toString()}`,
+      `pending shares  ${vault.pendingUnstakeShares.toString()}`,
+      `rebase          v${vault.rebaseVersion}, base 10^${vault.sharesBase}`,
+      `paused          ${pausedOperationNames(pausedMask(vault)).join(', ') || 'nothing'}`,
+    ],
+    leftWidth,
+    topHeight
+  )
+
+  const values = data.shareValues
+  const shareValue = pane(
+    'Share value',
+    values.length === 0
+      ? ['no samples yet']
+      : [
+          `${values[values.length - 1].toFixed(9)} per share`,
+          '',
+          sparkline(values, rightWidth - 4),
+          '',
+          `min ${Math.min(...values).toFixed(9)}  max ${Math.max(...values).toFixed(9)}`,
+          `${values.length} sample(s)`,
+        ],
+    rightWidth,
+    topHeight
+  )
+
+  const pending = data.depositors
+    .filter((depositor) => !bn(depositor.unstakeRequest.shares).isZero())
+    .map((depositor) => ({
+      authority: depositor.authority.toString(),
+      amount: frozenAmount(depositor.unstakeRequest),
+      unlocksAt: unlockTime(vault, bn(depositor.unstakeRequest.requestTime).toNumber()),
+    }))
+    .sort((a, b) => a.unlocksAt - b.unlocksAt)
+  const pendingRows = middleHeight - 2
+  const pendingLines = pending.map(
+    (request) =>
+      `${shortKey(request.authority)}  ${formatUsdc(request.amount)} USDC  ` +
+      (request.unlocksAt > now ? `in ${formatDuration(request.unlocksAt - now)}` : 'ready')
+  )
+  if (pendingLines.length > pendingRows) {
+    pendingLines.splice(pendingRows - 1, pendingLines.length, `+${pending.length - pendingRows + 1} more`)
+  }
+  const pendingPane = pane(
+    `Pending unstakes (${pending.length})`,
+    pendingLines.length > 0 ? pendingLines : ['none'],
+    leftWidth,
+    middleHeight
+  )
+
+  const cranks = pane(
+    'Cranks',
+    crankStatus(vault, data.depositors, now).map((crank) => `${crank.name.padEnd(18)} ${crank.status}`),
+    rightWidth,
+    middleHeight
+  )
+
+  const events = pane(
+    'Recent events',
+    data.events
+      .slice()
+      .reverse()
+      .map((entry) => `${new Date(entry.at * 1000).toISOString().slice(11, 19)}  ${summarizeEvent(entry.event)}`),
+    width,
+    eventsHeight
+  )
+
+  const lines = [
+    fit(`${data.vaultName}  ${data.vaultAddress}  ${new Date(now * 1000).toISOString()}`, width),
+    ...sideBySide(totals, shareValue),
+    ...sideBySide(pendingPane, cranks),
+    ...events,
+    fit(data.status ? `${DASHBOARD_KEYS}   ${data.status}` : DASHBOARD_KEYS, width),
+  ]
+  return lines.slice(0, height)
+}
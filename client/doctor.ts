@@ -0,0 +1,147 @@
+import * as anchor from '@coral-xyz/anchor'
+import { stakeCooldown } from './quote'
+import { isOperationPaused } from './pause'
+
+export type FindingSeverity = 'error' | 'action' | 'wait' | 'info'
+
+export interface Finding {
+  // lower runs first
+  priority: number
+  severity: FindingSeverity
+  code: string
+  message: string
+  fix?: string
+}
+
+// everything `diagnose` looks at, fetched by the caller in one round trip
+export interface DoctorInput {
+  vault: any | null
+  depositor: any | null
+  userTokenAccountExists: boolean
+  // the wallet's allowlist marker exists, only relevant on permissioned vaults
+  allowlisted: boolean
+  tokenMint: string
+  now: number
+}
+
+const SEVERITY_ORDER: Record<FindingSeverity, number> = { error: 0, action: 1, wait: 2, info: 3 }
+
+export function formatDuration(seconds: number): string {
+  const days = Math.floor(seconds / 86400)
+  const hours = Math.floor((seconds % 86400) / 3600)
+  const minutes = Math.floor((seconds % 3600) / 60)
+  const parts: string[] = []
+  if (days > 0) parts.push(`${days}d`)
+  if (hours > 0) parts.push(`${hours}h`)
+  if (minutes > 0 && days === 0) parts.push(`${minutes}m`)
+  if (parts.length === 0) parts.push(`${Math.max(seconds, 0)}s`)
+  return parts.join(' ')
+}
+
+function toNumber(value: anchor.BN | number): number {
+  return typeof value === 'number' ? value : value.toNumber()
+}
+
+// read-only diagnosis of the common user-state problems, most urgent first
+export function diagnose(input: DoctorInput): Finding[] {
+  const findings: Finding[] = []
+  const add = (severity: FindingSeverity, code: string, message: string, fix?: string) =>
+    findings.push({ priority: 0, severity, code, message, fix })
+
+  const { vault, depositor, now } = input
+  if (!vault) {
+    add('error', 'vault_not_found', 'vault account does not exist', 'check --vault / contract_info.json vault_name and --rpc')
+    return rank(findings)
+  }
+
+  if (!input.userTokenAccountExists) {
+    add(
+      'action',
+      'token_account_missing',
+      'associated token account for the vault mint is missing',
+      `run \`spl-token create-account ${input.tokenMint}\``
+    )
+  }
+
+  if (isOperationPaused(vault, 'stake')) {
+    add('info', 'vault_paused', 'deposits are paused, new stakes are rejected (unstaking still works)', 'wait for the vault owner to unpause before staking')
+  }
+
+  const pausedExits = ['request-unstake', 'unstake'].filter((operation) => isOperationPaused(vault, operation))
+  if (pausedExits.length > 0) {
+    add(
+      'wait',
+      'withdrawals_paused',
+      `withdrawals are paused, ${pausedExits.join(', ')} and instant-unstake are rejected`,
+      'wait for the vault owner to unpause withdrawals'
+    )
+  }
+
+  if (isOperationPaused(vault, 'cancel')) {
+    add('info', 'cancel_paused', 'cancelling unstake requests is paused', 'wait for the vault owner to unpause cancel')
+  }
+
+  if (vault.permissioned && !input.allowlisted) {
+    add(
+      'info',
+      'not_allowlisted',
+      'vault is permissioned and this wallet is not on its allowlist, init and stake are rejected (unstaking still works)',
+      'ask the vault owner to run `allow-depositor` for this wallet'
+    )
+  }
+
+  if (!depositor) {
+    add('action', 'depositor_not_initialized', 'depositor account is not initialized', 'run `node cli.ts init`')
+    return rank(findings)
+  }
+
+  // stake, request-unstake, unstake and cancel-unstake sync the position themselves
+  if (depositor.lastRebaseVersion !== vault.rebaseVersion) {
+    add(
+      'info',
+      'rebase_out_of_sync',
+      `depositor synced rebase ${depositor.lastRebaseVersion}, vault is at ${vault.rebaseVersion}, ` +
+        'the next stake or unstake syncs it'
+    )
+  }
+
+  const request = depositor.unstakeRequest
+  if (toNumber(request.shares) > 0) {
+    const decommissionEnds = toNumber(vault.decommissionNoticeEndsAt)
+    const lockupWaived = toNumber(vault.decommissionStartedAt) !== 0 && now >= decommissionEnds
+    const lockup = lockupWaived ? 0 : toNumber(vault.unstakeLockupPeriod)
+    const remaining = toNumber(request.requestTime) + lockup - now
+    if (remaining > 0) {
+      add(
+        'wait',
+        'unstake_not_matured',
+        `unstake request of ${request.shares.toString()} shares is still locked`,
+        `wait ${formatDuration(remaining)} then run \`node cli.ts unstake\``
+      )
+    } else {
+      add('action', 'unstake_ready', 'unstake request has matured', 'run `node cli.ts unstake`')
+    }
+  }
+
+  const cooldownLeft = toNumber(depositor.lastStakeTime) + stakeCooldown(vault) - now
+  if (cooldownLeft > 0) {
+    add(
+      'wait',
+      'stake_cooldown',
+      'stake cooldown has not elapsed, request-unstake and unstake will fail',
+      `wait ${formatDuration(cooldownLeft)} and retry`
+    )
+  }
+
+  if (toNumber(vault.decommissionStartedAt) !== 0) {
+    add('info', 'vault_decommissioning', 'vault is being decommissioned, new stakes are rejected', 'request unstake and withdraw')
+  }
+
+  return rank(findings)
+}
+
+function rank(findings: Finding[]): Finding[] {
+  findings.sort((a, b) => SEVERITY_ORDER[a.severity] - SEVERITY_ORDER[b.severity])
+  findings.forEach((finding, index) => (finding.priority = index + 1))
+  return findings
+}
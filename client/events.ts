@@ -0,0 +1,38 @@
+import { BorshCoder, EventParser, Program } from '@coral-xyz/anchor'
+
+// decoded vault event with byte-array fields rendered for humans
+export interface VaultEvent {
+  name: string
+  data: Record<string, any>
+}
+
+// 16-byte reconciliation id from a 32 char hex string (empty = all zeros, "not provided")
+export function parseClientId(hex?: string): number[] {
+  if (!hex) {
+    return new Array(16).fill(0)
+  }
+  const normalized = hex.startsWith('0x') ? hex.slice(2) : hex
+  if (!/^[0-9a-fA-F]{1,32}$/.test(normalized)) {
+    throw new Error('client id must be up to 32 hex characters (16 bytes)')
+  }
+  return Array.from(Buffer.from(normalized.padStart(32, '0'), 'hex'))
+}
+
+export function formatClientId(bytes: number[] | Uint8Array): string | null {
+  const buffer = Buffer.from(bytes)
+  return buffer.every((b) => b === 0) ? null : buffer.toString('hex')
+}
+
+// decode all vault events emitted in a transaction's log messages
+export function parseVaultEvents(program: Program<any>, logs: string[]): VaultEvent[] {
+  const parser = new EventParser(program.programId, new BorshCoder(program.idl))
+  const events: VaultEvent[] = []
+  for (const event of parser.parseLogs(logs)) {
+    const data: Record<string, any> = { ...event.data }
+    if ('clientId' in data) {
+      data.clientId = formatClientId(data.clientId)
+    }
+    events.push({ name: event.name, data })
+  }
+  return events
+}
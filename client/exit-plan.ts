@@ -0,0 +1,142 @@
+import * as anchor from '@coral-xyz/anchor'
+import {
+  PRECISION,
+  activeShareValue,
+  frozenAmount,
+  quoteUnstake,
+  stakeCooldown,
+  syncedShares,
+  unlockTime,
+  withdrawalFee,
+} from './quote'
+
+export interface ExitStep {
+  // unix seconds
+  at: number
+  action: 'request-unstake' | 'unstake'
+  // token base units, frozen (exact) for requests that already exist, estimated otherwise
+  amount: anchor.BN
+  estimated: boolean
+  note: string
+}
+
+export interface ExitPlan {
+  positionValue: anchor.BN
+  target: anchor.BN
+  fitsInOneRequest: boolean
+  steps: ExitStep[]
+  completesAt: number
+  caveats: string[]
+}
+
+// everything the plan is computed from, taken from live accounts
+export interface ExitPlanInput {
+  vault: any
+  depositor: any
+  // 'all' exits the whole position
+  amount: anchor.BN | 'all'
+  vaultTokenBalance: anchor.BN
+  now: number
+}
+
+// a dated exit schedule under the vault's live lockup, decommission state and liquidity.
+// One depositor holds at most one unstake request, so an exit on top of a locked request
+// is split: the locked request settles first, the remainder is requested after it
+export function planExit(input: ExitPlanInput): ExitPlan {
+  const { vault, depositor, now } = input
+  const steps: ExitStep[] = []
+  const caveats: string[] = []
+
+  const requestReady = Math.max(now, depositor.lastStakeTime.toNumber() + stakeCooldown(vault))
+  // request-unstake syncs a lagging position itself, so the plan prices it synced
+  const shares = syncedShares(vault, depositor, depositor.shares)
+  const price = activeShareValue(vault)
+  const pending = depositor.unstakeRequest
+  const hasPending = !new anchor.BN(pending.shares.toString()).isZero()
+  const pendingAmount = hasPending ? frozenAmount(pending) : new anchor.BN(0)
+  const pendingUnlock = hasPending ? unlockTime(vault, pending.requestTime.toNumber()) : now
+
+  const activeValue = shares.mul(price).div(PRECISION)
+  const positionValue = activeValue.add(pendingAmount)
+  const target = input.amount === 'all' ? positionValue : input.amount
+  if (target.gt(positionValue)) {
+    throw new Error(`requested ${target} exceeds the position value ${positionValue}`)
+  }
+
+  let remaining = target
+  let nextRequestAt = requestReady
+  if (hasPending) {
+    steps.push({
+      at: Math.max(now, pendingUnlock),
+      action: 'unstake',
+      amount: pendingAmount,
+      estimated: false,
+      note: pendingUnlock <= now ? 'existing request has matured' : 'existing request matures',
+    })
+    if (pendingAmount.gt(target)) {
+      caveats.push(`the existing request pays its full frozen amount (${pendingAmount}), more than requested`)
+    }
+    remaining = anchor.BN.max(target.sub(pendingAmount), new anchor.BN(0))
+    nextRequestAt = Math.max(requestReady, pendingUnlock)
+    if (!remaining.isZero() && pendingUnlock > now) {
+      caveats.push(
+        `a new request is rejected while the locked one is pending, cancelling it instead restarts its lockup, ` +
+          `settling everything at ${new Date(unlockTime(vault, requestReady) * 1000).toISOString()}`
+      )
+    }
+  }
+
+  if (!remaining.isZero()) {
+    const all = input.amount === 'all'
+    const quote = quoteUnstake(vault, shares, all ? 'all' : remaining)
+    steps.push({
+      at: nextRequestAt,
+      action: 'request-unstake',
+      amount: quote.frozenAmount,
+      estimated: nextRequestAt > now,
+      note: `${quote.shares} shares at ${quote.assetPerShare} per share (scaled by 1e12)`,
+    })
+    steps.push({
+      at: unlockTime(vault, nextRequestAt),
+      action: 'unstake',
+      amount: quote.frozenAmount,
+      estimated: nextRequestAt > now,
+      note: 'new request matures',
+    })
+    if (nextRequestAt > now) {
+      caveats.push('the share price may change until the request is made, later amounts are estimates at today\'s price')
+    }
+
+    const freeLiquidity = input.vaultTokenBalance.sub(new anchor.BN(vault.reservedAssets.toString()))
+    if (quote.frozenAmount.gt(freeLiquidity)) {
+      caveats.push(
+        `the vault token account holds ${freeLiquidity} beyond reserved assets, less than the ${quote.frozenAmount} ` +
+          'this request freezes, execution fails until the balance is restored'
+      )
+    }
+  }
+
+  const fee = withdrawalFee(vault, target)
+  if (!fee.isZero()) {
+    caveats.push(
+      `unstake keeps a ${vault.withdrawalFeeBps.toNumber() / 100}% withdrawal fee of each frozen amount ` +
+        `for the remaining stakers, about ${fee} of this exit`
+    )
+  }
+
+  if (vault.decommissionStartedAt.toNumber() !== 0) {
+    caveats.push(
+      `vault is decommissioning, the lockup is waived from ${new Date(vault.decommissionNoticeEndsAt.toNumber() * 1000).toISOString()}`
+    )
+  }
+
+  const requests = steps.filter((step) => step.action === 'request-unstake').length + (hasPending ? 1 : 0)
+  return {
+    positionValue,
+    target,
+    fitsInOneRequest: requests <= 1,
+    steps,
+    completesAt: steps.length > 0 ? Math.max(...steps.map((step) => step.at)) : now,
+    caveats,
+  }
+}
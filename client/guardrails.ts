@@ -0,0 +1,121 @@
+import * as fs from 'fs'
+import * as readline from 'readline'
+
+// token base units per UI unit (9 decimals)
+const BASE_UNITS = 1e9
+
+export interface GuardrailConfig {
+  // warn when a stake exceeds this share of the wallet balance (percent)
+  maxStakeWalletPercent: number
+  // warn when a stake exceeds this many tokens (UI units), unset disables the check
+  maxStakeAmount?: number
+  // warn when a platform share of rewards exceeds this many basis points
+  maxPlatformShareBps: number
+}
+
+export const DEFAULT_GUARDRAILS: GuardrailConfig = {
+  maxStakeWalletPercent: 90,
+  maxPlatformShareBps: 5000,
+}
+
+export const DEFAULT_PROFILE_PATH = 'vault-profile.json'
+
+export interface GuardrailWarning {
+  rule: 'stake-wallet-percent' | 'stake-absolute' | 'fee-bound' | 'unstake-dust'
+  message: string
+}
+
+// guardrails from the `guardrails` section of the profile config, defaults for anything unset
+export function loadGuardrailConfig(profilePath: string = DEFAULT_PROFILE_PATH): GuardrailConfig {
+  if (!fs.existsSync(profilePath)) {
+    return { ...DEFAULT_GUARDRAILS }
+  }
+  const profile = JSON.parse(fs.readFileSync(profilePath, 'utf-8'))
+  const guardrails = profile.guardrails || {}
+  // profiles written before the platform share was its own field
+  if (guardrails.maxPlatformShareBps === undefined && guardrails.maxManagementFeeBps !== undefined) {
+    guardrails.maxPlatformShareBps = guardrails.maxManagementFeeBps
+  }
+  return { ...DEFAULT_GUARDRAILS, ...guardrails }
+}
+
+// `amount` and `walletBalance` in base units
+export function checkStake(amount: number, walletBalance: number, config: GuardrailConfig): GuardrailWarning[] {
+  const warnings: GuardrailWarning[] = []
+  if (walletBalance > 0 && (amount / walletBalance) * 100 > config.maxStakeWalletPercent) {
+    warnings.push({
+      rule: 'stake-wallet-percent',
+      message:
+        `stake of ${amount / BASE_UNITS} is ${((amount / walletBalance) * 100).toFixed(1)}% of the wallet balance ` +
+        `(${walletBalance / BASE_UNITS}), above the ${config.maxStakeWalletPercent}% guardrail`,
+    })
+  }
+  if (config.maxStakeAmount !== undefined && amount > config.maxStakeAmount * BASE_UNITS) {
+    warnings.push({
+      rule: 'stake-absolute',
+      message: `stake of ${amount / BASE_UNITS} exceeds the ${config.maxStakeAmount} guardrail from the profile`,
+    })
+  }
+  return warnings
+}
+
+// `feeBps` in basis points, as update_vault_config takes it
+export function checkPlatformShare(feeBps: number, config: GuardrailConfig): GuardrailWarning[] {
+  if (feeBps <= config.maxPlatformShareBps) {
+    return []
+  }
+  return [
+    {
+      rule: 'fee-bound',
+      message:
+        `platform share of ${feeBps} basis points is ${feeBps / 100}% of rewards, above the ` +
+        `${config.maxPlatformShareBps / 100}% guardrail (the value is in basis points: 100 = 1%)`,
+    },
+  ]
+}
+
+// all amounts in base units, `unstakeAmount` of null means the whole position
+export function checkUnstakeDust(
+  positionValue: number,
+  unstakeAmount: number | null,
+  minStakeAmount: number
+): GuardrailWarning[] {
+  if (unstakeAmount === null) {
+    return []
+  }
+  const remaining = positionValue - unstakeAmount
+  if (remaining <= 0 || remaining >= minStakeAmount) {
+    return []
+  }
+  return [
+    {
+      rule: 'unstake-dust',
+      message:
+        `unstaking ${unstakeAmount / BASE_UNITS} leaves ${remaining / BASE_UNITS}, below the vault minimum stake ` +
+        `of ${minStakeAmount / BASE_UNITS}; consider unstaking the whole position`,
+    },
+  ]
+}
+
+// print the warnings and ask for confirmation, `force` skips the prompt.
+// Without a terminal to ask on, any warning aborts
+export async function confirmGuardrails(warnings: GuardrailWarning[], force: boolean): Promise<void> {
+  if (warnings.length === 0) {
+    return
+  }
+  warnings.forEach((warning) => console.warn(`⚠️  ${warning.message}`))
+  if (force) {
+    console.warn('⚠️  --force given, continuing')
+    return
+  }
+  if (!process.stdin.isTTY) {
+    throw new Error('guardrail check failed, re-run with --force to proceed')
+  }
+
+  const prompt = readline.createInterface({ input: process.stdin, output: process.stdout })
+  const answer = await new Promise<string>((resolve) => prompt.question('Proceed anyway? [y/N] ', resolve))
+  prompt.close()
+  if (answer.trim().toLowerCase() !== 'y') {
+    throw new Error('aborted by user')
+  }
+}
@@ -0,0 +1,160 @@
+import * as anchor from '@coral-xyz/anchor'
+import { Program } from '@coral-xyz/anchor'
+import { Commitment, PublicKey } from '@solana/web3.js'
+import { syncedShares } from './quote'
+
+// share fractions are written with this many decimals
+export const FRACTION_DECIMALS = 18
+const FRACTION_ONE = new anchor.BN(10).pow(new anchor.BN(FRACTION_DECIMALS))
+
+export interface Holder {
+  authority: string
+  shares: anchor.BN
+  // fraction of all listed shares, scaled by 10^FRACTION_DECIMALS and rounded down
+  fraction: anchor.BN
+}
+
+export interface HolderSnapshot {
+  vault: string
+  requestedSlot?: number
+  // slot the RPC actually served the accounts at
+  slot: number
+  holders: Holder[]
+}
+
+export function getOwnerFeeDepositorPDA(programId: PublicKey, vault: PublicKey): PublicKey {
+  return PublicKey.findProgramAddressSync([Buffer.from('owner_fee_depositor'), vault.toBuffer()], programId)[0]
+}
+
+// every depositor with shares, active plus pending unstake, normalized to the vault's current
+// rebase. RPC nodes only serve current state, so `slot` is a lower bound (minContextSlot) and
+// the slot actually used is reported back
+export async function snapshotHolders(
+  program: Program<any>,
+  vault: PublicKey,
+  options: { slot?: number; commitment?: Commitment; excludeOwnerFees?: boolean } = {}
+): Promise<HolderSnapshot> {
+  const commitment = options.commitment ?? 'finalized'
+  const connection = program.provider.connection
+  const vaultInfo = await connection.getAccountInfo(vault, { commitment, minContextSlot: options.slot })
+  if (!vaultInfo) {
+    throw new Error(`vault ${vault.toBase58()} not found`)
+  }
+  const vaultAccount = program.coder.accounts.decode('vault', vaultInfo.data)
+
+  const response = await connection.getProgramAccounts(program.programId, {
+    commitment,
+    minContextSlot: options.slot,
+    withContext: true,
+    filters: [
+      { dataSize: program.account.vaultDepositor.size },
+      { memcmp: { offset: 8, bytes: vault.toBase58() } },
+    ],
+  } as any)
+  const { context, value } = response as any
+
+  const ownerFeeDepositor = getOwnerFeeDepositorPDA(program.programId, vault)
+  const positions = (value as { pubkey: PublicKey; account: { data: Buffer } }[])
+    .filter((entry) => !(options.excludeOwnerFees && entry.pubkey.equals(ownerFeeDepositor)))
+    .map((entry) => {
+      const depositor = program.coder.accounts.decode('vaultDepositor', entry.account.data)
+      const shares = syncedShares(vaultAccount, depositor, depositor.shares).add(
+        syncedShares(vaultAccount, depositor, depositor.unstakeRequest.shares)
+      )
+      return { authority: depositor.authority.toBase58() as string, shares }
+    })
+    .filter((position) => !position.shares.isZero())
+
+  return {
+    vault: vault.toBase58(),
+    requestedSlot: options.slot,
+    slot: context.slot,
+    holders: withFractions(positions),
+  }
+}
+
+export function withFractions(positions: { authority: string; shares: anchor.BN }[]): Holder[] {
+  const total = positions.reduce((sum, position) => sum.add(position.shares), new anchor.BN(0))
+  return positions
+    .map((position) => ({
+      ...position,
+      fraction: total.isZero() ? new anchor.BN(0) : position.shares.mul(FRACTION_ONE).div(total),
+    }))
+    .sort((a, b) => b.shares.cmp(a.shares))
+}
+
+export function formatFraction(fraction: anchor.BN): string {
+  const digits = fraction.toString().padStart(FRACTION_DECIMALS + 1, '0')
+  return `${digits.slice(0, -FRACTION_DECIMALS)}.${digits.slice(-FRACTION_DECIMALS)}`
+}
+
+export function parseFraction(text: string): anchor.BN {
+  const [whole, decimals = ''] = text.trim().split('.')
+  if (!/^\d+$/.test(whole) || !/^\d*$/.test(decimals) || decimals.length > FRACTION_DECIMALS) {
+    throw new Error(`invalid share fraction ${text}`)
+  }
+  return new anchor.BN(whole + decimals.padEnd(FRACTION_DECIMALS, '0'))
+}
+
+// CSV with a `#` comment line recording the vault and slots
+export function formatHoldersCsv(snapshot: HolderSnapshot): string {
+  const lines = [
+    `# vault=${snapshot.vault} slot=${snapshot.slot}` +
+      (snapshot.requestedSlot !== undefined ? ` requested_slot=${snapshot.requestedSlot}` : ''),
+    'authority,shares,share_fraction',
+    ...snapshot.holders.map((h) => `${h.authority},${h.shares.toString()},${formatFraction(h.fraction)}`),
+  ]
+  return lines.join('\n') + '\n'
+}
+
+export function parseHoldersCsv(csv: string): Holder[] {
+  const rows = csv
+    .split('\n')
+    .map((line) => line.trim())
+    .filter((line) => line.length > 0 && !line.startsWith('#'))
+  if (rows[0] !== 'authority,shares,share_fraction') {
+    throw new Error('missing authority,shares,share_fraction header')
+  }
+  return rows.slice(1).map((row, index) => {
+    const [authority, shares, fraction] = row.split(',')
+    if (!authority || !shares || !fraction) {
+      throw new Error(`malformed row ${index + 2}: ${row}`)
+    }
+    return { authority, shares: new anchor.BN(shares), fraction: parseFraction(fraction) }
+  })
+}
+
+export interface HolderVerification {
+  ok: boolean
+  fractionSum: string
+  problems: string[]
+}
+
+// fractions must sum to 1 within `tolerance` and each must match its shares / total shares.
+// Rounding each fraction down loses at most one unit in the last decimal per row
+export function verifyHolders(holders: Holder[], tolerance: number = 1e-9): HolderVerification {
+  const problems: string[] = []
+  const sum = holders.reduce((acc, h) => acc.add(h.fraction), new anchor.BN(0))
+  const allowed = parseFraction(tolerance.toFixed(FRACTION_DECIMALS))
+  if (sum.sub(FRACTION_ONE).abs().gt(allowed)) {
+    problems.push(`share fractions sum to ${formatFraction(sum)}, off by more than ${tolerance}`)
+  }
+
+  const seen = new Set<string>()
+  const expected = withFractions(holders.map((h) => ({ authority: h.authority, shares: h.shares })))
+  const expectedByAuthority = new Map(expected.map((h) => [h.authority, h.fraction]))
+  for (const holder of holders) {
+    if (seen.has(holder.authority)) {
+      problems.push(`${holder.authority} is listed twice`)
+    }
+    seen.add(holder.authority)
+    const fraction = expectedByAuthority.get(holder.authority)
+    if (fraction && holder.fraction.sub(fraction).abs().gt(allowed)) {
+      problems.push(
+        `${holder.authority}: fraction ${formatFraction(holder.fraction)} does not match ` +
+          `its shares (${formatFraction(fraction)})`
+      )
+    }
+  }
+  return { ok: problems.length === 0, fractionSum: formatFraction(sum), problems }
+}
@@ -0,0 +1,551 @@
+import { BN, Program } from '@coral-xyz/anchor'
+import { createAssociatedTokenAccountIdempotentInstruction, getAssociatedTokenAddress } from '@solana/spl-token'
+import {
+  ComputeBudgetProgram,
+  Keypair,
+  PublicKey,
+  SystemProgram,
+  TransactionInstruction,
+} from '@solana/web3.js'
+import { checkpointDue, checkpointsPDA } from './checkpoints'
+import { isOperationPaused } from './pause'
+import { activeShareValue } from './quote'
+
+// mirrors DECOMMISSION_FORCE_TIMEOUT in constants.rs
+export const DECOMMISSION_FORCE_TIMEOUT = 180 * 24 * 60 * 60
+// mirrors MAX_SYNC_BATCH in sync_rebase_batch.rs
+export const MAX_SYNC_BATCH = 20
+// mirrors MAX_UNSTAKE_BATCH in process_matured_unstakes.rs
+export const MAX_UNSTAKE_BATCH = 8
+// failures back a crank off exponentially, capped at interval * 2^MAX_BACKOFF_EXPONENT
+export const MAX_BACKOFF_EXPONENT = 6
+
+export interface KeeperOptions {
+  intervalSeconds: number
+  once: boolean
+  // compute unit price in micro-lamports, 0 sends without a priority fee
+  priorityFeeMicroLamports: number
+}
+
+// mirrors Vault::apply_rebase: a rebase divides by the largest power of ten not above the
+// shares per asset, so it happens once shares reach ten times the assets
+export function rebaseDue(vault: any): boolean {
+  return vault.totalAssets.gtn(0) && vault.totalShares.gte(vault.totalAssets.muln(10))
+}
+
+// a decommissioning vault can be force distributed once the timeout after its notice passed,
+// while shares other than the dead shares are left
+export function forceDistributionDue(vault: any, now: number): boolean {
+  if (vault.decommissionStartedAt.toNumber() === 0) {
+    return false
+  }
+  return now >= vault.decommissionNoticeEndsAt.toNumber() + DECOMMISSION_FORCE_TIMEOUT && vault.totalShares.gt(vault.deadShares)
+}
+
+// rewards added with a vesting period are released by `accrue_rewards` as time passes
+export function accrualDue(vault: any, now: number): boolean {
+  return vault.unvestedRewards.gtn(0) && now > vault.lastVestingUpdate.toNumber()
+}
+
+// mirrors Vault::crystallize_performance_fee: a fee is charged once the share value is above the mark
+export function performanceFeeDue(vault: any): boolean {
+  return (
+    vault.version > 0 &&
+    vault.performanceFeeBps.gtn(0) &&
+    !isOperationPaused(vault, 'fee-accrual') &&
+    vault.totalShares.gt(vault.pendingUnstakeShares) &&
+    activeShareValue(vault).gt(vault.highWaterMark)
+  )
+}
+
+// mirrors VaultDepositor::can_unstake with Vault::effective_unstake_lockup, which drops the
+// lockup once a decommission notice ended
+export function unstakeRequestMatured(vault: any, depositor: any, now: number): boolean {
+  if (depositor.unstakeRequest.shares.isZero()) {
+    return false
+  }
+  const decommissioning = vault.decommissionStartedAt.toNumber() !== 0
+  const lockup =
+    decommissioning && now >= vault.decommissionNoticeEndsAt.toNumber() ? 0 : vault.unstakeLockupPeriod.toNumber()
+  return now >= depositor.unstakeRequest.requestTime.toNumber() + lockup
+}
+
+// mirrors Vault::is_unstake_request_expired: a matured request left uncollected for the expiry
+export function unstakeRequestExpired(vault: any, depositor: any, now: number): boolean {
+  const expiry = vault.unstakeRequestExpiry ? vault.unstakeRequestExpiry.toNumber() : 0
+  if (expiry === 0 || vault.decommissionStartedAt.toNumber() !== 0 || depositor.unstakeRequest.shares.isZero()) {
+    return false
+  }
+  return now >= depositor.unstakeRequest.requestTime.toNumber() + vault.unstakeLockupPeriod.toNumber() + expiry
+}
+
+// mirrors Vault::skimmable_surplus: tokens sent straight to the vault token account, beyond
+// total_assets and the rewards still vesting
+export function skimmableSurplus(vault: any, balance: BN): BN {
+  const accounted = vault.totalAssets.add(vault.unvestedRewards)
+  return balance.gt(accounted) ? balance.sub(accounted) : new BN(0)
+}
+
+// mirrors Vault::reconcile: the vault token account holds less than the vault accounts for
+export function balanceShortfall(vault: any, balance: BN): BN {
+  const accounted = vault.totalAssets.add(vault.unvestedRewards)
+  return accounted.gt(balance) ? accounted.sub(balance) : new BN(0)
+}
+
+// a permissionless crank: `due` must only read accounts, `run` sends what is due.
+// Every crank is guarded on-chain, so concurrent keepers can only waste a transaction
+interface Crank {
+  name: string
+  due(vault: any): Promise<boolean>
+  run(vault: any): Promise<string[]>
+}
+
+// sends one transaction through the caller's metrics tracking
+export type TrackedSend = (instruction: string, send: () => Promise<string>) => Promise<string>
+
+export class Keeper {
+  private cranks: Crank[]
+  private failures = new Map<string, number>()
+  private retryAt = new Map<string, number>()
+  // rebase version every depositor was seen synced at, skips the depositor scan until it moves
+  private syncedRebaseVersion = -1
+
+  constructor(
+    private program: Program<any>,
+    private wallet: Keypair,
+    private vault: PublicKey,
+    private track: TrackedSend,
+    private options: KeeperOptions
+  ) {
+    this.cranks = [
+      { name: 'crankRebase', due: async (v) => rebaseDue(v), run: () => this.crankRebase() },
+      {
+        name: 'accrueRewards',
+        due: async (v) => accrualDue(v, Math.floor(Date.now() / 1000)),
+        run: () => this.accrueRewards(),
+      },
+      { name: 'crystallizeFees', due: async (v) => performanceFeeDue(v), run: () => this.crystallizeFees() },
+      {
+        name: 'processMaturedUnstakes',
+        due: async (v) => v.pendingUnstakeShares.gtn(0) && !isOperationPaused(v, 'unstake'),
+        run: (v) => this.processMaturedUnstakes(v),
+      },
+      {
+        name: 'expireUnstakeRequests',
+        due: async (v) =>
+          !!v.unstakeRequestExpiry?.gtn(0) &&
+          v.decommissionStartedAt.toNumber() === 0 &&
+          !isOperationPaused(v, 'cancel'),
+        run: (v) => this.expireUnstakeRequests(v),
+      },
+      {
+        name: 'reconcile',
+        due: async (v) => balanceShortfall(v, await this.vaultTokenBalance(v)).gtn(0),
+        run: () => this.reconcile(),
+      },
+      {
+        name: 'skim',
+        due: async (v) =>
+          v.decommissionStartedAt.toNumber() === 0 &&
+          !isOperationPaused(v, 'add-rewards') &&
+          skimmableSurplus(v, await this.vaultTokenBalance(v)).gtn(0),
+        run: () => this.skim(),
+      },
+      { name: 'recordCheckpoint', due: (v) => this.checkpointDue(v), run: () => this.recordCheckpoint() },
+      { name: 'syncRebaseBatch', due: (v) => this.syncDue(v), run: (v) => this.syncRebase(v) },
+      { name: 'rollUpVaultStats', due: (v) => this.rollUpDue(v), run: () => this.rollUp() },
+      {
+        name: 'forceDistribute',
+        due: async (v) => forceDistributionDue(v, Math.floor(Date.now() / 1000)),
+        run: (v) => this.forceDistribute(v),
+      },
+    ]
+  }
+
+  async run(): Promise<void> {
+    for (;;) {
+      await this.tick()
+      if (this.options.once) {
+        return
+      }
+      await new Promise((resolve) => setTimeout(resolve, this.options.intervalSeconds * 1000))
+    }
+  }
+
+  // one pass over every crank, returns the names of the cranks that sent transactions
+  async tick(): Promise<string[]> {
+    const sent: string[] = []
+    const vaultAccount = await this.program.account.vault.fetch(this.vault)
+    const now = Date.now()
+
+    for (const crank of this.cranks) {
+      if ((this.retryAt.get(crank.name) ?? 0) > now) {
+        continue
+      }
+      try {
+        if (!(await crank.due(vaultAccount))) {
+          continue
+        }
+        const signatures = await crank.run(vaultAccount)
+        this.failures.set(crank.name, 0)
+        if (signatures.length > 0) {
+          sent.push(crank.name)
+          console.log(`✅ ${crank.name}: ${signatures.join(', ')}`)
+        }
+      } catch (error) {
+        const failures = (this.failures.get(crank.name) ?? 0) + 1
+        const delaySeconds = this.options.intervalSeconds * Math.pow(2, Math.min(failures, MAX_BACKOFF_EXPONENT))
+        this.failures.set(crank.name, failures)
+        this.retryAt.set(crank.name, now + delaySeconds * 1000)
+        console.error(`❌ ${crank.name} failed (${failures} in a row), retrying in ${delaySeconds}s:`, error)
+      }
+    }
+    return sent
+  }
+
+  private preInstructions(): TransactionInstruction[] {
+    if (this.options.priorityFeeMicroLamports <= 0) {
+      return []
+    }
+    return [ComputeBudgetProgram.setComputeUnitPrice({ microLamports: this.options.priorityFeeMicroLamports })]
+  }
+
+  // the depositors are synced on the next pass, once the new rebase version is read
+  private async crankRebase(): Promise<string[]> {
+    const signature = await this.track('crankRebase', () =>
+      this.program.methods
+        .crankRebase()
+        .accounts({ vault: this.vault } as any)
+        .preInstructions(this.preInstructions())
+        .signers([this.wallet])
+        .rpc()
+    )
+    return [signature]
+  }
+
+  // releases what vested since the last accrual, stakes and exits do the same on their own
+  private async accrueRewards(): Promise<string[]> {
+    const signature = await this.track('accrueRewards', () =>
+      this.program.methods
+        .accrueRewards()
+        .accounts({ vault: this.vault } as any)
+        .preInstructions(this.preInstructions())
+        .signers([this.wallet])
+        .rpc()
+    )
+    return [signature]
+  }
+
+  // charges the performance fee on vested gains, add_rewards already does it for instant ones
+  private async crystallizeFees(): Promise<string[]> {
+    const signature = await this.track('crystallizeFees', () =>
+      this.program.methods
+        .crystallizeFees()
+        .accounts({ vault: this.vault } as any)
+        .preInstructions(this.preInstructions())
+        .signers([this.wallet])
+        .rpc()
+    )
+    return [signature]
+  }
+
+  // only vaults whose checkpoint history was created with `initialize_vault_checkpoints`
+  private async checkpointDue(vault: any): Promise<boolean> {
+    const account = await this.program.account.vaultCheckpoints.fetchNullable(
+      checkpointsPDA(this.program.programId, this.vault)
+    )
+    return !!account && checkpointDue(vault, account, Math.floor(Date.now() / 1000))
+  }
+
+  private async recordCheckpoint(): Promise<string[]> {
+    const signature = await this.track('recordCheckpoint', () =>
+      this.program.methods
+        .recordCheckpoint()
+        .accounts({
+          vault: this.vault,
+          vaultCheckpoints: checkpointsPDA(this.program.programId, this.vault),
+        } as any)
+        .preInstructions(this.preInstructions())
+        .signers([this.wallet])
+        .rpc()
+    )
+    return [signature]
+  }
+
+  private async vaultTokenBalance(vault: any): Promise<BN> {
+    const balance = await this.program.provider.connection.getTokenAccountBalance(vault.vaultTokenAccount)
+    return new BN(balance.value.amount)
+  }
+
+  // puts a shortfall on-chain: the alert event, and the pause on vaults that opted into it
+  private async reconcile(): Promise<string[]> {
+    const signature = await this.track('reconcile', () =>
+      this.program.methods
+        .reconcile()
+        .accounts({ vault: this.vault } as any)
+        .preInstructions(this.preInstructions())
+        .signers([this.wallet])
+        .rpc()
+    )
+    return [signature]
+  }
+
+  // adds tokens sent straight to the vault token account to the stakers' assets
+  private async skim(): Promise<string[]> {
+    const signature = await this.track('skim', () =>
+      this.program.methods
+        .skim()
+        .accounts({ vault: this.vault } as any)
+        .preInstructions(this.preInstructions())
+        .signers([this.wallet])
+        .rpc()
+    )
+    return [signature]
+  }
+
+  private async depositors(): Promise<{ publicKey: PublicKey; account: any }[]> {
+    return this.program.account.vaultDepositor.all([
+      { memcmp: { offset: 8, bytes: this.vault.toBase58() } },
+    ])
+  }
+
+  // pays every matured request out to the depositor's associated token account, in batches of
+  // MAX_UNSTAKE_BATCH. Depositors without that account are left to unstake themselves
+  private async processMaturedUnstakes(vault: any): Promise<string[]> {
+    const connection = this.program.provider.connection
+    const now = Math.floor(Date.now() / 1000)
+    const matured = (await this.depositors()).filter((depositor) =>
+      unstakeRequestMatured(vault, depositor.account, now)
+    )
+    if (matured.length === 0) {
+      return []
+    }
+
+    const [vaultTokenAccount] = PublicKey.findProgramAddressSync(
+      [Buffer.from('vault_token_account'), this.vault.toBuffer()],
+      this.program.programId
+    )
+    // the mint's owner is the token program, the original one or Token-2022
+    const tokenProgram = (await connection.getAccountInfo(vault.tokenMint))!.owner
+    const tokenAccounts = await Promise.all(
+      matured.map((depositor) =>
+        getAssociatedTokenAddress(vault.tokenMint, depositor.account.authority, false, tokenProgram)
+      )
+    )
+    const existing = await connection.getMultipleAccountsInfo(tokenAccounts)
+    const pairs = matured.flatMap((depositor, i) => {
+      if (!existing[i]) {
+        console.warn(`⚠️  processMaturedUnstakes: ${depositor.account.authority.toBase58()} has no token account, skipped`)
+        return []
+      }
+      return [
+        { pubkey: depositor.publicKey, isWritable: true, isSigner: false },
+        { pubkey: tokenAccounts[i], isWritable: true, isSigner: false },
+      ]
+    })
+
+    const signatures: string[] = []
+    for (let i = 0; i < pairs.length; i += MAX_UNSTAKE_BATCH * 2) {
+      const batch = pairs.slice(i, i + MAX_UNSTAKE_BATCH * 2)
+      signatures.push(
+        await this.track('processMaturedUnstakes', () =>
+          this.program.methods
+            .processMaturedUnstakes()
+            .accounts({
+              vault: this.vault,
+              vaultTokenAccount,
+              tokenMint: vault.tokenMint,
+              tokenProgram,
+            } as any)
+            .remainingAccounts(batch)
+            .preInstructions(this.preInstructions())
+            .signers([this.wallet])
+            .rpc()
+        )
+      )
+    }
+    return signatures
+  }
+
+  // restores every matured request left uncollected past the vault's expiry. Share tokens of a
+  // tokenized vault are minted back to the depositor's associated account, created when missing
+  private async expireUnstakeRequests(vault: any): Promise<string[]> {
+    const now = Math.floor(Date.now() / 1000)
+    const expired = (await this.depositors()).filter((depositor) =>
+      unstakeRequestExpired(vault, depositor.account, now)
+    )
+    const tokenized = !vault.shareMint.equals(PublicKey.default)
+    const tokenProgram = tokenized
+      ? (await this.program.provider.connection.getAccountInfo(vault.shareMint))!.owner
+      : null
+
+    const signatures: string[] = []
+    for (const depositor of expired) {
+      const authority = depositor.account.authority
+      const shareAccount = tokenProgram
+        ? await getAssociatedTokenAddress(vault.shareMint, authority, false, tokenProgram)
+        : null
+      const preInstructions = this.preInstructions()
+      if (tokenProgram && shareAccount) {
+        preInstructions.push(
+          createAssociatedTokenAccountIdempotentInstruction(
+            this.wallet.publicKey,
+            shareAccount,
+            authority,
+            vault.shareMint,
+            tokenProgram
+          )
+        )
+      }
+      signatures.push(
+        await this.track('expireUnstakeRequest', () =>
+          this.program.methods
+            .expireUnstakeRequest()
+            .accounts({
+              vault: this.vault,
+              vaultDepositor: depositor.publicKey,
+              shareMint: tokenized ? vault.shareMint : null,
+              depositorShareAccount: shareAccount,
+              tokenProgram,
+            } as any)
+            .preInstructions(preInstructions)
+            .signers([this.wallet])
+            .rpc()
+        )
+      )
+    }
+    return signatures
+  }
+
+  private async syncDue(vault: any): Promise<boolean> {
+    return vault.rebaseVersion !== this.syncedRebaseVersion
+  }
+
+  private async syncRebase(vault: any): Promise<string[]> {
+    const stale = (await this.depositors()).filter(
+      (depositor) => depositor.account.lastRebaseVersion < vault.rebaseVersion
+    )
+    const signatures: string[] = []
+    for (let i = 0; i < stale.length; i += MAX_SYNC_BATCH) {
+      const batch = stale.slice(i, i + MAX_SYNC_BATCH)
+      signatures.push(
+        await this.track('syncRebaseBatch', () =>
+          this.program.methods
+            .syncRebaseBatch()
+            .accounts({ vault: this.vault } as any)
+            .remainingAccounts(
+              batch.map((depositor) => ({ pubkey: depositor.publicKey, isWritable: true, isSigner: false }))
+            )
+            .preInstructions(this.preInstructions())
+            .signers([this.wallet])
+            .rpc()
+        )
+      )
+    }
+    this.syncedRebaseVersion = vault.rebaseVersion
+    return signatures
+  }
+
+  private statsPDAs(): { protocolStats: PublicKey; vaultStats: PublicKey } {
+    const [protocolStats] = PublicKey.findProgramAddressSync([Buffer.from('protocol_stats')], this.program.programId)
+    const [vaultStats] = PublicKey.findProgramAddressSync(
+      [Buffer.from('vault_stats'), this.vault.toBuffer()],
+      this.program.programId
+    )
+    return { protocolStats, vaultStats }
+  }
+
+  // due when protocol stats exist and the vault's contribution is missing or out of date
+  private async rollUpDue(vault: any): Promise<boolean> {
+    const { protocolStats, vaultStats } = this.statsPDAs()
+    const [statsInfo, contribution] = await Promise.all([
+      this.program.provider.connection.getAccountInfo(protocolStats),
+      this.program.account.vaultStatsContribution.fetchNullable(vaultStats),
+    ])
+    if (!statsInfo) {
+      return false
+    }
+    return (
+      !contribution ||
+      !contribution.totalAssets.eq(vault.totalAssets) ||
+      !contribution.totalRewards.eq(vault.totalRewards)
+    )
+  }
+
+  private async rollUp(): Promise<string[]> {
+    const { protocolStats, vaultStats } = this.statsPDAs()
+    const signature = await this.track('rollUpVaultStats', () =>
+      this.program.methods
+        .rollUpVaultStats()
+        .accounts({
+          protocolStats,
+          vault: this.vault,
+          vaultStats,
+          payer: this.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        } as any)
+        .preInstructions(this.preInstructions())
+        .signers([this.wallet])
+        .rpc()
+    )
+    return [signature]
+  }
+
+  // pays out every remaining position whose owner still has a token account for the mint
+  private async forceDistribute(vault: any): Promise<string[]> {
+    const [vaultTokenAccount] = PublicKey.findProgramAddressSync(
+      [Buffer.from('vault_token_account'), this.vault.toBuffer()],
+      this.program.programId
+    )
+    const remaining = (await this.depositors()).filter(
+      (depositor) => depositor.account.shares.gtn(0) || depositor.account.unstakeRequest.shares.gtn(0)
+    )
+
+    // the mint's owner is the token program, the original one or Token-2022
+    const tokenProgram = (await this.program.provider.connection.getAccountInfo(vault.tokenMint))!.owner
+
+    const signatures: string[] = []
+    for (const depositor of remaining) {
+      const depositorTokenAccount = await getAssociatedTokenAddress(
+        vault.tokenMint,
+        depositor.account.authority,
+        false,
+        tokenProgram
+      )
+      if (!(await this.program.provider.connection.getAccountInfo(depositorTokenAccount))) {
+        console.warn(`⚠️  forceDistribute: ${depositor.account.authority.toBase58()} has no token account, skipped`)
+        continue
+      }
+      signatures.push(
+        await this.track('forceDistribute', () =>
+          this.program.methods
+            .forceDistribute()
+            .accounts({
+              vault: this.vault,
+              vaultDepositor: depositor.publicKey,
+              vaultTokenAccount,
+              depositorTokenAccount,
+              tokenMint: vault.tokenMint,
+              tokenProgram,
+            } as any)
+            .preInstructions(this.preInstructions())
+            .signers([this.wallet])
+            .rpc()
+        )
+      )
+    }
+    return signatures
+  }
+}
+
+export function parseInterval(value: string | undefined, defaultSeconds: number = 60): number {
+  if (!value) {
+    return defaultSeconds
+  }
+  const match = /^(\d+)(s|m|h)?$/.exec(value)
+  if (!match) {
+    throw new Error(`invalid interval ${value}, expected e.g. 60s, 5m or 1h`)
+  }
+  const multiplier = { s: 1, m: 60, h: 3600 }[(match[2] || 's') as 's' | 'm' | 'h']
+  return parseInt(match[1]) * multiplier
+}
@@ -0,0 +1,248 @@
+import * as http from 'http'
+
+// hooks invoked by the client so bots can export metrics without forking it
+export interface MetricsSink {
+  // one JSON-RPC request, `retries` counts 429/5xx retries before the final response
+  recordRpcCall(method: string, latencyMs: number, retries: number, ok: boolean): void
+  // one signed transaction sent and confirmed (or failed)
+  recordTransaction(tx: {
+    instruction: string
+    confirmMs: number
+    ok: boolean
+    computeUnits?: number
+    priorityFeeLamports?: number
+  }): void
+  // one decoded vault event
+  recordEvent(name: string): void
+}
+
+export class NoopMetricsSink implements MetricsSink {
+  recordRpcCall() {}
+  recordTransaction() {}
+  recordEvent() {}
+}
+
+// fan-out to several sinks
+export class MultiMetricsSink implements MetricsSink {
+  constructor(private sinks: MetricsSink[]) {}
+
+  recordRpcCall(method: string, latencyMs: number, retries: number, ok: boolean) {
+    this.sinks.forEach((s) => s.recordRpcCall(method, latencyMs, retries, ok))
+  }
+
+  recordTransaction(tx: Parameters<MetricsSink['recordTransaction']>[0]) {
+    this.sinks.forEach((s) => s.recordTransaction(tx))
+  }
+
+  recordEvent(name: string) {
+    this.sinks.forEach((s) => s.recordEvent(name))
+  }
+}
+
+// latency buckets in milliseconds
+const LATENCY_BUCKETS = [10, 25, 50, 100, 250, 500, 1000, 2500, 5000, 10000, 30000]
+
+class Histogram {
+  private counts = new Map<string, number[]>()
+  private sums = new Map<string, number>()
+
+  constructor(private buckets: number[]) {}
+
+  observe(labels: string, value: number) {
+    const counts = this.counts.get(labels) ?? new Array(this.buckets.length + 1).fill(0)
+    const index = this.buckets.findIndex((b) => value <= b)
+    counts[index === -1 ? this.buckets.length : index] += 1
+    this.counts.set(labels, counts)
+    this.sums.set(labels, (this.sums.get(labels) ?? 0) + value)
+  }
+
+  render(name: string, help: string): string[] {
+    const lines = [`# HELP ${name} ${help}`, `# TYPE ${name} histogram`]
+    for (const [labels, counts] of this.counts) {
+      let cumulative = 0
+      this.buckets.forEach((bucket, i) => {
+        cumulative += counts[i]
+        lines.push(`${name}_bucket{${joinLabels(labels, `le="${bucket}"`)}} ${cumulative}`)
+      })
+      cumulative += counts[this.buckets.length]
+      lines.push(`${name}_bucket{${joinLabels(labels, 'le="+Inf"')}} ${cumulative}`)
+      lines.push(`${name}_sum{${labels}} ${this.sums.get(labels)}`)
+      lines.push(`${name}_count{${labels}} ${cumulative}`)
+    }
+    return lines
+  }
+}
+
+class Counter {
+  private values = new Map<string, number>()
+
+  inc(labels: string, by: number = 1) {
+    this.values.set(labels, (this.values.get(labels) ?? 0) + by)
+  }
+
+  render(name: string, help: string): string[] {
+    const lines = [`# HELP ${name} ${help}`, `# TYPE ${name} counter`]
+    for (const [labels, value] of this.values) {
+      lines.push(`${name}{${labels}} ${value}`)
+    }
+    return lines
+  }
+}
+
+function joinLabels(a: string, b: string): string {
+  return a ? `${a},${b}` : b
+}
+
+// Prometheus text exposition of the client metrics
+export class PrometheusMetricsSink implements MetricsSink {
+  private rpcLatency = new Histogram(LATENCY_BUCKETS)
+  private rpcCalls = new Counter()
+  private rpcRetries = new Counter()
+  private txConfirm = new Histogram(LATENCY_BUCKETS)
+  private txSent = new Counter()
+  private txComputeUnits = new Counter()
+  private txPriorityFees = new Counter()
+  private events = new Counter()
+
+  recordRpcCall(method: string, latencyMs: number, retries: number, ok: boolean) {
+    const labels = `method="${method}"`
+    this.rpcLatency.observe(labels, latencyMs)
+    this.rpcCalls.inc(`${labels},status="${ok ? 'ok' : 'error'}"`)
+    if (retries > 0) {
+      this.rpcRetries.inc(labels, retries)
+    }
+  }
+
+  recordTransaction(tx: Parameters<MetricsSink['recordTransaction']>[0]) {
+    const labels = `instruction="${tx.instruction}"`
+    this.txConfirm.observe(labels, tx.confirmMs)
+    this.txSent.inc(`${labels},status="${tx.ok ? 'ok' : 'error'}"`)
+    if (tx.computeUnits !== undefined) {
+      this.txComputeUnits.inc(labels, tx.computeUnits)
+    }
+    if (tx.priorityFeeLamports !== undefined) {
+      this.txPriorityFees.inc(labels, tx.priorityFeeLamports)
+    }
+  }
+
+  recordEvent(name: string) {
+    this.events.inc(`event="${name}"`)
+  }
+
+  render(): string {
+    return [
+      ...this.rpcLatency.render('vault_rpc_latency_ms', 'JSON-RPC call latency'),
+      ...this.rpcCalls.render('vault_rpc_calls_total', 'JSON-RPC calls by method and status'),
+      ...this.rpcRetries.render('vault_rpc_retries_total', 'JSON-RPC retries by method'),
+      ...this.txConfirm.render('vault_tx_confirm_ms', 'Transaction send-to-confirm time'),
+      ...this.txSent.render('vault_tx_total', 'Transactions sent by instruction and status'),
+      ...this.txComputeUnits.render('vault_tx_compute_units_total', 'Compute units consumed'),
+      ...this.txPriorityFees.render('vault_tx_priority_fee_lamports_total', 'Priority fees paid'),
+      ...this.events.render('vault_events_total', 'Decoded vault events by name'),
+    ].join('\n') + '\n'
+  }
+
+  // serve /metrics on "host:port" (or just "port")
+  listen(address: string): http.Server {
+    const [host, port] = address.includes(':') ? address.split(':') : ['0.0.0.0', address]
+    const server = http.createServer((req, res) => {
+      if (req.url === '/metrics') {
+        res.writeHead(200, { 'Content-Type': 'text/plain; version=0.0.4' })
+        res.end(this.render())
+      } else {
+        res.writeHead(404)
+        res.end()
+      }
+    })
+    server.listen(Number(port), host)
+    console.log(`📈 Serving metrics on http://${host}:${port}/metrics`)
+    return server
+  }
+}
+
+// per-command breakdown of RPC and confirmation time for `--timing`
+export class TimingMetricsSink implements MetricsSink {
+  private rpc = new Map<string, { calls: number; totalMs: number; retries: number; errors: number }>()
+  private slow: { method: string; latencyMs: number }[] = []
+  private transactions: Parameters<MetricsSink['recordTransaction']>[0][] = []
+  private started = Date.now()
+
+  // calls slower than `slowThresholdMs` are listed individually
+  constructor(private slowThresholdMs: number = 1000) {}
+
+  recordRpcCall(method: string, latencyMs: number, retries: number, ok: boolean) {
+    const entry = this.rpc.get(method) ?? { calls: 0, totalMs: 0, retries: 0, errors: 0 }
+    entry.calls += 1
+    entry.totalMs += latencyMs
+    entry.retries += retries
+    entry.errors += ok ? 0 : 1
+    this.rpc.set(method, entry)
+    if (latencyMs > this.slowThresholdMs) {
+      this.slow.push({ method, latencyMs })
+    }
+  }
+
+  recordTransaction(tx: Parameters<MetricsSink['recordTransaction']>[0]) {
+    this.transactions.push(tx)
+  }
+
+  recordEvent() {}
+
+  report(): string {
+    const lines = [`⏱️  timing (${Date.now() - this.started} ms total):`]
+    const methods = [...this.rpc.entries()].sort((a, b) => b[1].totalMs - a[1].totalMs)
+    if (methods.length === 0) {
+      lines.push('  no RPC calls')
+    }
+    for (const [method, entry] of methods) {
+      const extras = [
+        entry.retries > 0 ? `${entry.retries} retries` : '',
+        entry.errors > 0 ? `${entry.errors} failed` : '',
+      ].filter((extra) => extra.length > 0)
+      lines.push(
+        `  ${entry.calls} ${method} call${entry.calls === 1 ? '' : 's'} totalling ${entry.totalMs} ms` +
+          (extras.length > 0 ? ` (${extras.join(', ')})` : '')
+      )
+    }
+    const sends = this.rpc.get('sendTransaction')
+    if (sends) {
+      lines.push(`  ${sends.calls + sends.retries} send attempt${sends.calls + sends.retries === 1 ? '' : 's'}`)
+    }
+    for (const tx of this.transactions) {
+      lines.push(`  ${tx.instruction}: ${tx.ok ? 'confirmed' : 'failed'} in ${tx.confirmMs} ms`)
+    }
+    for (const call of this.slow) {
+      lines.push(`  ⚠️  slow ${call.method}: ${call.latencyMs} ms (threshold ${this.slowThresholdMs} ms)`)
+    }
+    return lines.join('\n')
+  }
+}
+
+// fetch wrapper for `new Connection(url, { fetch })` that reports every RPC call
+export function instrumentedFetch(sink: MetricsSink, baseFetch: any = (globalThis as any).fetch): any {
+  return async (input: any, init?: any) => {
+    let method = 'unknown'
+    try {
+      const body = JSON.parse(init?.body ?? '{}')
+      method = Array.isArray(body) ? 'batch' : body.method ?? 'unknown'
+    } catch (_) {}
+
+    const started = Date.now()
+    let retries = 0
+    for (;;) {
+      try {
+        const response = await baseFetch(input, init)
+        if ((response.status === 429 || response.status >= 500) && retries < 3) {
+          retries += 1
+          await new Promise((resolve) => setTimeout(resolve, 500 * retries))
+          continue
+        }
+        sink.recordRpcCall(method, Date.now() - started, retries, response.ok)
+        return response
+      } catch (error) {
+        sink.recordRpcCall(method, Date.now() - started, retries, false)
+        throw error
+      }
+    }
+  }
+}
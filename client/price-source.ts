@@ -0,0 +1,214 @@
+import * as anchor from '@coral-xyz/anchor'
+import { PublicKey } from '@solana/web3.js'
+import * as fs from 'fs'
+
+// exact decimal mantissa * 10^expo, the way Pyth publishes prices
+export interface DecimalPrice {
+  mantissa: anchor.BN
+  expo: number
+}
+
+// price quote returned by every price source
+export interface PriceQuote {
+  mint: PublicKey
+  price: DecimalPrice // USD per whole token
+  source: string // human readable label, e.g. "file:prices.toml" or "pyth"
+  timestamp: number // unix seconds the price was published / loaded
+}
+
+// pluggable USD price source for reporting commands
+export interface PriceSource {
+  readonly label: string
+  price(mint: PublicKey): Promise<PriceQuote>
+}
+
+// static prices from a config file
+//
+// Accepts a minimal TOML subset (or JSON) mapping mint -> USD price:
+//
+//   # prices.toml
+//   "DXDVt289yXEcqXDd9Ub3HqSBTWwrmNB8DzQEagv9Svtu" = 1.0
+export class FilePriceSource implements PriceSource {
+  readonly label: string
+  private prices: Map<string, DecimalPrice>
+  private loadedAt: number
+
+  constructor(path: string) {
+    this.label = `file:${path}`
+    this.prices = FilePriceSource.parse(fs.readFileSync(path, 'utf8'))
+    this.loadedAt = Math.floor(fs.statSync(path).mtimeMs / 1000)
+  }
+
+  static parse(content: string): Map<string, DecimalPrice> {
+    const prices = new Map<string, DecimalPrice>()
+    const trimmed = content.trim()
+
+    if (trimmed.startsWith('{')) {
+      const json = JSON.parse(trimmed)
+      for (const [mint, value] of Object.entries(json)) {
+        // JSON numbers print back as their shortest literal, which is what the file spelled
+        prices.set(mint, parseDecimal(String(value)))
+      }
+      return prices
+    }
+
+    for (const rawLine of content.split('\n')) {
+      const line = rawLine.split('#')[0].trim()
+      if (!line || line.startsWith('[')) {
+        continue
+      }
+      const match = line.match(/^"?([1-9A-HJ-NP-Za-km-z]{32,44})"?\s*=\s*"?([0-9.eE+-]+)"?$/)
+      if (!match) {
+        throw new Error(`Invalid price entry: ${rawLine}`)
+      }
+      prices.set(match[1], parseDecimal(match[2]))
+    }
+    return prices
+  }
+
+  async price(mint: PublicKey): Promise<PriceQuote> {
+    const price = this.prices.get(mint.toBase58())
+    if (price === undefined) {
+      throw new Error(`No price configured for mint ${mint.toBase58()} in ${this.label}`)
+    }
+    return { mint, price, source: this.label, timestamp: this.loadedAt }
+  }
+}
+
+// Pyth Hermes HTTP API price source
+//
+// Feed ids are configured per mint because Pyth identifies feeds by id, not mint.
+export const HERMES_URL = 'https://hermes.pyth.network'
+
+export const DEFAULT_PYTH_FEEDS: Record<string, string> = {
+  // USDC mainnet mint -> Crypto.USDC/USD
+  EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v:
+    'eaa020c61cc479712813461ce153894a96a6c00b21ed0cfc2798d1f9a9e9c94a',
+}
+
+export class PythHermesPriceSource implements PriceSource {
+  readonly label = 'pyth'
+  private feeds: Record<string, string>
+  private endpoint: string
+
+  constructor(feeds: Record<string, string> = {}, endpoint: string = HERMES_URL) {
+    this.feeds = { ...DEFAULT_PYTH_FEEDS, ...feeds }
+    this.endpoint = endpoint
+  }
+
+  async price(mint: PublicKey): Promise<PriceQuote> {
+    const feedId = this.feeds[mint.toBase58()]
+    if (!feedId) {
+      throw new Error(`No Pyth feed id configured for mint ${mint.toBase58()}`)
+    }
+
+    const url = `${this.endpoint}/v2/updates/price/latest?ids[]=${feedId}&parsed=true`
+    const response = await (globalThis as any).fetch(url)
+    if (!response.ok) {
+      throw new Error(`Pyth Hermes request failed: ${response.status} ${response.statusText}`)
+    }
+
+    const body = await response.json()
+    const parsed = body?.parsed?.[0]?.price
+    if (!parsed) {
+      throw new Error(`Pyth Hermes returned no price for feed ${feedId}`)
+    }
+
+    return {
+      mint,
+      price: { mantissa: new anchor.BN(parsed.price), expo: Number(parsed.expo) },
+      source: this.label,
+      timestamp: Number(parsed.publish_time),
+    }
+  }
+}
+
+// TTL cache wrapper around any price source
+export class CachedPriceSource implements PriceSource {
+  readonly label: string
+  private inner: PriceSource
+  private ttlSeconds: number
+  private cache = new Map<string, { quote: PriceQuote; fetchedAt: number }>()
+
+  constructor(inner: PriceSource, ttlSeconds: number = 60) {
+    this.inner = inner
+    this.label = inner.label
+    this.ttlSeconds = ttlSeconds
+  }
+
+  async price(mint: PublicKey): Promise<PriceQuote> {
+    const key = mint.toBase58()
+    const now = Math.floor(Date.now() / 1000)
+    const cached = this.cache.get(key)
+    if (cached && now - cached.fetchedAt < this.ttlSeconds) {
+      return cached.quote
+    }
+
+    const quote = await this.inner.price(mint)
+    this.cache.set(key, { quote, fetchedAt: now })
+    return quote
+  }
+}
+
+// parse the --price-source flag: "file:<path>" or "pyth"
+export function createPriceSource(
+  spec: string,
+  options: { pythFeeds?: Record<string, string>; ttlSeconds?: number } = {}
+): PriceSource {
+  let source: PriceSource
+  if (spec.startsWith('file:')) {
+    source = new FilePriceSource(spec.slice('file:'.length))
+  } else if (spec === 'pyth') {
+    source = new PythHermesPriceSource(options.pythFeeds)
+  } else {
+    throw new Error(`Unknown price source "${spec}", expected file:<path> or pyth`)
+  }
+  return new CachedPriceSource(source, options.ttlSeconds)
+}
+
+// parse a decimal literal such as "1.0", "0.9998" or "1e-3" without going through a float
+export function parseDecimal(text: string): DecimalPrice {
+  const match = text.trim().match(/^\+?(\d*)(?:\.(\d*))?(?:[eE]([+-]?\d+))?$/)
+  if (!match || (!match[1] && !match[2])) {
+    throw new Error(`Invalid price: ${text}`)
+  }
+  const fraction = match[2] ?? ''
+  return {
+    mantissa: new anchor.BN(`${match[1]}${fraction}`),
+    expo: Number(match[3] ?? 0) - fraction.length,
+  }
+}
+
+function pow10(exponent: number): anchor.BN {
+  return new anchor.BN(10).pow(new anchor.BN(exponent))
+}
+
+// mantissa * 10^expo with every digit, e.g. "0.99980000"
+export function formatDecimal(value: DecimalPrice): string {
+  const negative = value.mantissa.isNeg()
+  let text: string
+  if (value.expo >= 0) {
+    text = value.mantissa.abs().mul(pow10(value.expo)).toString()
+  } else {
+    const padded = value.mantissa.abs().toString().padStart(-value.expo + 1, '0')
+    text = `${padded.slice(0, value.expo)}.${padded.slice(value.expo)}`
+  }
+  return negative ? `-${text}` : text
+}
+
+// format a token amount in base units as USD with the price provenance, rounded to the cent
+export function formatUsd(baseUnits: anchor.BN, quote: PriceQuote, decimals: number = 9): string {
+  // cents = baseUnits * mantissa * 10^(expo - decimals + 2)
+  const scaled = baseUnits.mul(quote.price.mantissa)
+  const shift = quote.price.expo - decimals + 2
+  let cents: anchor.BN
+  if (shift >= 0) {
+    cents = scaled.mul(pow10(shift))
+  } else {
+    const divisor = pow10(-shift)
+    cents = scaled.add(divisor.divn(2)).div(divisor)
+  }
+  const usd = formatDecimal({ mantissa: cents, expo: -2 })
+  const at = new Date(quote.timestamp * 1000).toISOString()
+  return `$${usd} (price ${formatDecimal(quote.price)} via ${quote.source} @ ${at})`
+}
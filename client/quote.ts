@@ -0,0 +1,213 @@
+import * as anchor from '@coral-xyz/anchor'
+
+// 1e12 as defined in constants.rs
+export const PRECISION = new anchor.BN('1000000000000')
+
+const ZERO = new anchor.BN(0)
+
+// the program rejects this amount with AmbiguousMaxAmount, exits of everything use the `*_all` instructions
+export const U64_MAX = new anchor.BN('18446744073709551615')
+
+function bn(value: anchor.BN | number | string): anchor.BN {
+  return new anchor.BN(value.toString())
+}
+
+// mirrors Vault::withdrawal_fee: rounded up, waived while decommissioning or with no active shares
+// left besides the dead shares
+export function withdrawalFee(vault: any, amount: anchor.BN): anchor.BN {
+  const feeBps = vault.withdrawalFeeBps ? bn(vault.withdrawalFeeBps) : ZERO
+  const activeShares = bn(vault.totalShares).sub(bn(vault.pendingUnstakeShares))
+  const deadShares = vault.deadShares ? bn(vault.deadShares) : ZERO
+  if (feeBps.isZero() || bn(vault.decommissionStartedAt).toNumber() !== 0 || activeShares.lte(deadShares)) {
+    return ZERO
+  }
+  return amount.mul(feeBps).addn(9999).divn(10000)
+}
+
+// mirrors Vault::get_active_share_value: (total_assets - reserved) / (total_shares - pending)
+export function activeShareValue(vault: any): anchor.BN {
+  const availableAssets = bn(vault.totalAssets).sub(bn(vault.reservedAssets))
+  const activeShares = bn(vault.totalShares).sub(bn(vault.pendingUnstakeShares))
+  if (activeShares.isZero()) {
+    return PRECISION
+  }
+  return availableAssets.mul(PRECISION).div(activeShares)
+}
+
+// mirrors UnstakeRequest::frozen_amount
+export function frozenAmount(unstakeRequest: any): anchor.BN {
+  return bn(unstakeRequest.shares).mul(bn(unstakeRequest.assetPerShareAtRequest)).div(PRECISION)
+}
+
+// mirrors Vault::restore_unstake: active shares worth the frozen amount at today's value
+export function restoredShares(vault: any, unstakeRequest: any): anchor.BN {
+  if (bn(unstakeRequest.shares).isZero()) {
+    return ZERO
+  }
+  return frozenAmount(unstakeRequest).mul(PRECISION).div(activeShareValue(vault))
+}
+
+export interface UnstakeQuote {
+  shares: anchor.BN
+  frozenAmount: anchor.BN
+  assetPerShare: anchor.BN
+}
+
+// `shares` of a depositor once its pending rebase is synced (VaultDepositor::sync_rebase),
+// including the 1-share precision floor
+export function syncedShares(vault: any, depositor: any, shares: anchor.BN): anchor.BN {
+  if (depositor.lastRebaseVersion === vault.rebaseVersion) {
+    return bn(shares)
+  }
+  const exponent = Math.max(0, vault.sharesBase - depositor.lastSharesBase)
+  const synced = bn(shares).div(new anchor.BN(10).pow(new anchor.BN(exponent)))
+  return synced.isZero() && !bn(shares).isZero() ? new anchor.BN(1) : synced
+}
+
+// mirrors Vault::freeze_unstake, `amount === 'all'` freezes every available share
+export function quoteUnstake(
+  vault: any,
+  availableShares: anchor.BN,
+  amount: anchor.BN | 'all'
+): UnstakeQuote {
+  const assetPerShare = activeShareValue(vault)
+  if (assetPerShare.isZero()) {
+    // the program rejects this with ShareValueUnderflow
+    throw new Error('active share value rounds to zero, the vault cannot price its shares')
+  }
+  // the program converts at the exact ratio: assets paid round down, shares burned round up
+  const availableAssets = bn(vault.totalAssets).sub(bn(vault.reservedAssets))
+  const activeShares = bn(vault.totalShares).sub(bn(vault.pendingUnstakeShares))
+  if (activeShares.isZero()) {
+    throw new Error('the vault has no active shares to exit with')
+  }
+  if (amount === 'all') {
+    return {
+      shares: availableShares,
+      frozenAmount: availableShares.mul(availableAssets).div(activeShares),
+      assetPerShare,
+    }
+  }
+  const product = amount.mul(activeShares)
+  const shares = product.add(availableAssets).subn(1).div(availableAssets)
+  if (shares.isZero()) {
+    throw new Error('amount is below the value of one share')
+  }
+  if (shares.gt(availableShares)) {
+    throw new Error(`amount needs ${shares} shares but only ${availableShares} are available`)
+  }
+  return { shares, frozenAmount: amount, assetPerShare }
+}
+
+// earliest time a request made at `requestTime` can be executed, following
+// Vault::effective_unstake_lockup (the lockup is waived once the decommission notice ends)
+export function unlockTime(vault: any, requestTime: number): number {
+  const lockupEnd = requestTime + bn(vault.unstakeLockupPeriod).toNumber()
+  if (bn(vault.decommissionStartedAt).isZero()) {
+    return lockupEnd
+  }
+  const noticeEnds = bn(vault.decommissionNoticeEndsAt).toNumber()
+  return Math.min(lockupEnd, Math.max(requestTime, noticeEnds))
+}
+
+// DEFAULT_STAKE_COOLDOWN_SECONDS in constants.rs
+export const DEFAULT_STAKE_COOLDOWN_SECONDS = 300
+
+// mirrors Vault::stake_cooldown: seconds after a stake before the depositor can exit,
+// vaults that never set `stakeCooldownSeconds` (0) use the default
+export function stakeCooldown(vault: any): number {
+  const seconds = bn(vault.stakeCooldownSeconds ?? 0).toNumber()
+  return seconds === 0 ? DEFAULT_STAKE_COOLDOWN_SECONDS : seconds
+}
+
+// output of the get_stake_capacity view instruction
+export interface StakeCapacity {
+  vaultRemainingCapacity: anchor.BN
+  // u64::MAX when the vault has no per-depositor cap
+  depositorRemainingCapacity: anchor.BN
+  minStakeAmount: anchor.BN
+  depositorValue: anchor.BN
+  isPaused: boolean
+  isDecommissioning: boolean
+}
+
+// output of the preview_stake view instruction
+export interface StakePreview {
+  shares: anchor.BN
+  depositFee: anchor.BN
+  // scaled by PRECISION
+  activeShareValue: anchor.BN
+  sharesBase: number
+}
+
+// output of the preview_unstake view instruction, shares in the returned `sharesBase`
+export interface UnstakePreview {
+  shares: anchor.BN
+  frozenAmount: anchor.BN
+  withdrawalFee: anchor.BN
+  netAmount: anchor.BN
+  sharesBase: number
+}
+
+// output of the get_vault_stats view instruction, with vested rewards and a due rebase applied
+export interface VaultStats {
+  totalAssets: anchor.BN
+  totalShares: anchor.BN
+  activeShares: anchor.BN
+  pendingUnstakeShares: anchor.BN
+  reservedAssets: anchor.BN
+  unvestedRewards: anchor.BN
+  // scaled by PRECISION, 0 when the active shares are worthless
+  activeShareValue: anchor.BN
+  sharesBase: number
+  rebaseVersion: number
+}
+
+// the error Vault::stake would return for `amount`, in the same order and with the program's
+// error messages, or null when the stake passes these checks
+export function stakeRejection(capacity: StakeCapacity, amount: anchor.BN | number): string | null {
+  const value = bn(amount)
+  if (value.eq(U64_MAX)) {
+    return 'u64::MAX is not a stake amount, pass the exact number of tokens to stake'
+  }
+  if (capacity.isPaused) {
+    return 'Vault is paused'
+  }
+  if (capacity.isDecommissioning) {
+    return 'Vault is being decommissioned'
+  }
+  if (value.lt(bn(capacity.minStakeAmount))) {
+    return `Minimum stake amount not met: at least ${capacity.minStakeAmount} base units required`
+  }
+  if (value.gt(bn(capacity.vaultRemainingCapacity))) {
+    return `Vault is full: ${capacity.vaultRemainingCapacity} base units of capacity left`
+  }
+  if (value.gt(bn(capacity.depositorRemainingCapacity))) {
+    return `Stake would take the depositor's position above the per-depositor cap: ${capacity.depositorRemainingCapacity} base units left`
+  }
+  return null
+}
+
+// tolerance the CLI applies when --slippage-bps is not given
+export const DEFAULT_SLIPPAGE_BPS = 50
+
+function checkSlippageBps(slippageBps: number) {
+  if (!Number.isInteger(slippageBps) || slippageBps < 0 || slippageBps > 10000) {
+    throw new Error(`slippage must be a whole number of basis points between 0 and 10000, got ${slippageBps}`)
+  }
+}
+
+// lower bound for min_shares_out / min_amount_out, `expected` less `slippageBps` rounded down.
+// 0 disables the check on-chain, so a non-zero expectation never rounds to it
+export function minOut(expected: anchor.BN | number, slippageBps: number): anchor.BN {
+  checkSlippageBps(slippageBps)
+  const value = bn(expected)
+  const bound = value.muln(10000 - slippageBps).divn(10000)
+  return bound.isZero() && !value.isZero() ? new anchor.BN(1) : bound
+}
+
+// upper bound for max_shares_in, `expected` plus `slippageBps` rounded up
+export function maxIn(expected: anchor.BN | number, slippageBps: number): anchor.BN {
+  checkSlippageBps(slippageBps)
+  return bn(expected).muln(10000 + slippageBps).addn(9999).divn(10000)
+}
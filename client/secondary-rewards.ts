@@ -0,0 +1,28 @@
+import * as anchor from '@coral-xyz/anchor'
+import { PublicKey } from '@solana/web3.js'
+
+// mirrors SHARE_PRECISION in constants.rs
+const SHARE_PRECISION = new anchor.BN('1000000000000000000')
+
+export function secondaryRewardTokenAccountPDA(programId: PublicKey, vault: PublicKey): PublicKey {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from('secondary_reward_token_account'), vault.toBuffer()],
+    programId
+  )[0]
+}
+
+export function hasSecondaryRewards(vault: any): boolean {
+  return !!vault.secondaryRewardMint && !vault.secondaryRewardMint.equals(PublicKey.default)
+}
+
+// mirrors VaultDepositor::calculate_pending_rewards plus what was settled already: the
+// amount claim_secondary_rewards would pay out now
+export function claimableSecondaryRewards(vault: any, depositor: any): anchor.BN {
+  const owed = depositor.secondaryRewardsOwed ?? new anchor.BN(0)
+  const perShare: anchor.BN = vault.secondaryRewardsPerShare ?? new anchor.BN(0)
+  if (depositor.shares.isZero() || perShare.lte(depositor.rewardsDebt)) {
+    return owed
+  }
+  const baseShares = depositor.shares.mul(new anchor.BN(10).pow(new anchor.BN(depositor.lastSharesBase)))
+  return owed.add(baseShares.mul(perShare.sub(depositor.rewardsDebt)).div(SHARE_PRECISION))
+}
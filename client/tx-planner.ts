@@ -0,0 +1,171 @@
+import {
+  AddressLookupTableAccount,
+  PublicKey,
+  TransactionInstruction,
+  TransactionMessage,
+} from '@solana/web3.js'
+
+// Solana packet limit for a serialized transaction
+export const PACKET_DATA_SIZE = 1232
+
+// blockhash used only for size measurement, the real one is set at send time
+const PLACEHOLDER_BLOCKHASH = PublicKey.default.toBase58()
+
+// an instruction with ordering / atomicity annotations
+export interface PlannedInstruction {
+  instruction: TransactionInstruction
+  label?: string
+  // indices of earlier instructions that must have executed first
+  dependsOn?: number[]
+  // indices of instructions that must land in the same transaction
+  atomicWith?: number[]
+  // what to do if the transaction carrying this instruction fails after earlier ones landed
+  rollbackNote?: string
+}
+
+export interface PlannedTransaction {
+  instructions: TransactionInstruction[]
+  labels: string[]
+  size: number
+  rollbackNotes: string[]
+}
+
+export interface TxPlan {
+  transactions: PlannedTransaction[]
+}
+
+export interface TxPlannerOptions {
+  payer: PublicKey
+  signerCount: number
+  lookupTables?: AddressLookupTableAccount[]
+  maxSize?: number
+}
+
+export class TxPlannerError extends Error {}
+
+// splits an ordered instruction list into the fewest transactions that fit the packet limit
+export class TxPlanner {
+  private options: TxPlannerOptions
+
+  constructor(options: TxPlannerOptions) {
+    if (options.signerCount < 1) {
+      throw new TxPlannerError('signerCount must be at least 1 (the fee payer)')
+    }
+    this.options = options
+  }
+
+  private get maxSize(): number {
+    return this.options.maxSize ?? PACKET_DATA_SIZE
+  }
+
+  // serialized size of a transaction carrying the given instructions
+  measure(instructions: TransactionInstruction[]): number {
+    const message = new TransactionMessage({
+      payerKey: this.options.payer,
+      recentBlockhash: PLACEHOLDER_BLOCKHASH,
+      instructions,
+    })
+    const compiled = this.options.lookupTables
+      ? message.compileToV0Message(this.options.lookupTables)
+      : message.compileToLegacyMessage()
+    const messageSize = compiled.serialize().length
+    // compact-u16 signature count followed by 64-byte signatures
+    return compactU16Length(this.options.signerCount) + 64 * this.options.signerCount + messageSize
+  }
+
+  plan(items: PlannedInstruction[]): TxPlan {
+    const groups = this.buildGroups(items)
+    const transactions: PlannedTransaction[] = []
+    let current: number[] = []
+
+    const flush = () => {
+      if (current.length === 0) {
+        return
+      }
+      const instructions = current.map((i) => items[i].instruction)
+      transactions.push({
+        instructions,
+        labels: current.map((i) => items[i].label ?? `#${i}`),
+        size: this.measure(instructions),
+        rollbackNotes: current
+          .filter((i) => items[i].rollbackNote)
+          .map((i) => items[i].rollbackNote as string),
+      })
+      current = []
+    }
+
+    for (const group of groups) {
+      const groupInstructions = group.map((i) => items[i].instruction)
+      const groupSize = this.measure(groupInstructions)
+      if (groupSize > this.maxSize) {
+        const labels = group.map((i) => items[i].label ?? `#${i}`).join(', ')
+        throw new TxPlannerError(
+          `Atomic group [${labels}] needs ${groupSize} bytes which exceeds the ${this.maxSize} byte limit and cannot be split`
+        )
+      }
+
+      const candidate = current.concat(group)
+      if (this.measure(candidate.map((i) => items[i].instruction)) <= this.maxSize) {
+        current = candidate
+      } else {
+        flush()
+        current = group.slice()
+      }
+    }
+    flush()
+
+    return { transactions }
+  }
+
+  // validates annotations and merges atomic instructions into contiguous groups
+  private buildGroups(items: PlannedInstruction[]): number[][] {
+    const parent = items.map((_, i) => i)
+    const find = (i: number): number => (parent[i] === i ? i : (parent[i] = find(parent[i])))
+
+    items.forEach((item, i) => {
+      for (const dep of item.dependsOn ?? []) {
+        if (dep < 0 || dep >= items.length) {
+          throw new TxPlannerError(`Instruction #${i} depends on unknown instruction #${dep}`)
+        }
+        if (dep >= i) {
+          throw new TxPlannerError(
+            `Instruction #${i} depends on #${dep} which is not ordered before it`
+          )
+        }
+      }
+      for (const other of item.atomicWith ?? []) {
+        if (other < 0 || other >= items.length) {
+          throw new TxPlannerError(`Instruction #${i} is atomic with unknown instruction #${other}`)
+        }
+        parent[find(other)] = find(i)
+      }
+    })
+
+    const groups: number[][] = []
+    const seenRoots = new Map<number, number>()
+    items.forEach((_, i) => {
+      const root = find(i)
+      const groupIndex = seenRoots.get(root)
+      if (groupIndex === undefined) {
+        seenRoots.set(root, groups.length)
+        groups.push([i])
+        return
+      }
+      if (groupIndex !== groups.length - 1) {
+        throw new TxPlannerError(
+          `Instruction #${i} must share a transaction with #${groups[groupIndex][0]} but is not contiguous with it`
+        )
+      }
+      groups[groupIndex].push(i)
+    })
+
+    return groups
+  }
+}
+
+function compactU16Length(value: number): number {
+  if (value < 0x80) {
+    return 1
+  }
+  return value < 0x4000 ? 2 : 3
+}
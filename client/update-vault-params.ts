@@ -3,10 +3,19 @@
 import * as anchor from '@coral-xyz/anchor'
 import { Program } from '@coral-xyz/anchor'
 import { SimpleVault } from '../target/types/simple_vault'
-import { PublicKey, Connection, Keypair } from '@solana/web3.js'
+import { PublicKey, Connection, Keypair, SystemProgram } from '@solana/web3.js'
+import { getAccount, getAssociatedTokenAddress } from '@solana/spl-token'
 import * as fs from 'fs'
 import * as os from 'os'
 import contract_info from './contract_info.json'
+import { configDigest, predictConfigDigest } from './config-digest'
+import { checkpointInterval } from './checkpoints'
+import { hasSecondaryRewards, secondaryRewardTokenAccountPDA } from './secondary-rewards'
+import { checkPlatformShare, confirmGuardrails, loadGuardrailConfig } from './guardrails'
+import { getOwnerFeeDepositorPDA } from './holders'
+import { U64_MAX, stakeCooldown } from './quote'
+import { PAUSE_BITS, parsePausedOperations, pausedMask, pausedOperationNames } from './pause'
+import { AccountCache, defaultCacheDir } from './account-cache'
 
 interface VaultAdminConfig {
   programId: PublicKey
@@ -14,6 +23,17 @@ interface VaultAdminConfig {
   rpcUrl: string
 }
 
+// mirrors MAX_PLATFORM_SHARE_CHANGE_BPS and MIN_PLATFORM_SHARE_CHANGE_INTERVAL in constants.rs
+const MAX_FEE_CHANGE_BPS = 500
+const FEE_CHANGE_INTERVAL_SECONDS = 7 * 24 * 60 * 60
+// mirrors MAX_MANAGEMENT_FEE_BPS in constants.rs
+const MAX_MANAGEMENT_FEE_BPS = 500
+// mirrors MAX_DEPOSIT_FEE_BPS in constants.rs
+const MAX_DEPOSIT_FEE_BPS = 100
+
+// update params `update_vault_config` applies at once on a timelocked vault
+const PAUSE_PARAMS = ['isPaused', 'depositsPaused', 'withdrawalsPaused', 'pausedOperations']
+
 export class VaultAdminOperations {
   private program: Program<SimpleVault>
   private provider: anchor.AnchorProvider
@@ -64,10 +84,29 @@ export class VaultAdminOperations {
 
   async updateVaultConfig(params: {
     unstakeLockupPeriod?: number // in hours
-    managementFee?: number // in basis points (e.g., 100 = 1%)
+    platformShare?: number // in basis points of each add_rewards (e.g., 100 = 1%)
     minStakeAmount?: number // in USDC (will be converted to 6 decimals)
+    minUnstakeAmount?: number // in USDC, partial unstake requests below it are rejected, 0 disables
     maxTotalAssets?: number | null // in USDC (will be converted to 6 decimals), null means unlimited
-    isPaused?: boolean
+    isPaused?: boolean // pauses everything except the unstake flow
+    withdrawalsPaused?: boolean
+    pausedOperations?: number // replaces the whole pause mask, see pause.ts
+    instantUnstakeFee?: number // in basis points, 0 disables instant unstake
+    stakeCooldown?: number // in seconds
+    permissioned?: boolean // only allowlisted wallets may open positions and stake
+    maxStakePerDepositor?: number | null // in USDC, null means unlimited
+    rewardVestingPeriod?: number // in seconds, 0 releases rewards instantly
+    performanceFee?: number // in basis points of the gain above the high-water mark
+    withdrawalFee?: number // in basis points of each matured unstake, kept for the remaining stakers
+    unstakeRequestExpiry?: number // in seconds after maturity, 0 means requests never expire
+    rewardAuthority?: PublicKey // wallet allowed to add rewards besides registered program sources
+    permissionlessRewards?: boolean // accept rewards from any signer
+    pauseOnShortfall?: boolean // let reconcile pause stakes and withdrawals on a token shortfall
+    configTimelockSeconds?: number // delay before a proposed update can be committed, always proposed
+    checkpointInterval?: number // in seconds between two share value checkpoints
+    managementFee?: number // in basis points of assets a year, stored for a later accrual
+    depositFee?: number // in basis points of each stake, no shares are minted for it
+    depositFeeToOwner?: boolean // mint the deposit fee as owner fee shares instead of leaving it to stakers
   }): Promise<string> {
     try {
       const [vaultPDA] = this.getVaultPDA()
@@ -79,11 +118,31 @@ export class VaultAdminOperations {
       // Convert parameters to appropriate format - need to provide all fields
       const updateParams: any = {
         unstakeLockupPeriod: null,
-        managementFee: null,
+        platformShareBps: null,
         minStakeAmount: null,
         maxTotalAssets: null,
         isPaused: null,
         platformAccount: null,
+        instantUnstakeFeeBps: null,
+        stakeCooldownSeconds: null,
+        depositsPaused: null,
+        withdrawalsPaused: null,
+        pausedOperations: null,
+        permissioned: null,
+        maxStakePerDepositor: null,
+        rewardVestingPeriod: null,
+        performanceFeeBps: null,
+        withdrawalFeeBps: null,
+        unstakeRequestExpiry: null,
+        rewardAuthority: null,
+        permissionlessRewards: null,
+        pauseOnShortfall: null,
+        configTimelockSeconds: null,
+        minUnstakeAmount: null,
+        checkpointInterval: null,
+        managementFeeBps: null,
+        depositFeeBps: null,
+        depositFeeToOwner: null,
       }
 
       if (params.unstakeLockupPeriod !== undefined) {
@@ -95,12 +154,12 @@ export class VaultAdminOperations {
         )
       }
 
-      if (params.managementFee !== undefined) {
-        updateParams.managementFee = new anchor.BN(params.managementFee)
+      if (params.platformShare !== undefined) {
+        updateParams.platformShareBps = new anchor.BN(params.platformShare)
         console.log(
-          `📝 Management fee: ${params.managementFee} basis points (${
-            params.managementFee / 100
-          }%)`
+          `📝 Platform share: ${params.platformShare} basis points (${
+            params.platformShare / 100
+          }% of each reward)`
         )
       }
 
@@ -109,6 +168,11 @@ export class VaultAdminOperations {
         console.log(`📝 Minimum stake amount: ${params.minStakeAmount} USDC`)
       }
 
+      if (params.minUnstakeAmount !== undefined) {
+        updateParams.minUnstakeAmount = new anchor.BN(Math.round(params.minUnstakeAmount * 1e9))
+        console.log(`📝 Minimum unstake amount: ${params.minUnstakeAmount} USDC`)
+      }
+
       if (params.maxTotalAssets !== undefined) {
         if (params.maxTotalAssets === null) {
           updateParams.maxTotalAssets = new anchor.BN(0) // 0 means unlimited in Rust
@@ -121,9 +185,126 @@ export class VaultAdminOperations {
         }
       }
 
+      if (params.maxStakePerDepositor !== undefined) {
+        if (params.maxStakePerDepositor === null) {
+          updateParams.maxStakePerDepositor = new anchor.BN(0) // 0 means unlimited in Rust
+          console.log(`📝 Maximum stake per depositor: Unlimited`)
+        } else {
+          updateParams.maxStakePerDepositor = new anchor.BN(params.maxStakePerDepositor * 1e9)
+          console.log(`📝 Maximum stake per depositor: ${params.maxStakePerDepositor} USDC`)
+        }
+      }
+
       if (params.isPaused !== undefined) {
         updateParams.isPaused = params.isPaused
-        console.log(`📝 Vault paused status: ${params.isPaused}`)
+        console.log(`📝 Deposits paused: ${params.isPaused}`)
+      }
+
+      if (params.withdrawalsPaused !== undefined) {
+        updateParams.withdrawalsPaused = params.withdrawalsPaused
+        console.log(`📝 Withdrawals paused: ${params.withdrawalsPaused}`)
+      }
+
+      if (params.pausedOperations !== undefined) {
+        updateParams.pausedOperations = params.pausedOperations
+        console.log(`📝 Paused operations: ${pausedOperationNames(params.pausedOperations).join(', ') || 'none'}`)
+      }
+
+      if (params.permissioned !== undefined) {
+        updateParams.permissioned = params.permissioned
+        console.log(`📝 Permissioned: ${params.permissioned}`)
+      }
+
+      if (params.instantUnstakeFee !== undefined) {
+        updateParams.instantUnstakeFeeBps = new anchor.BN(params.instantUnstakeFee)
+        console.log(
+          `📝 Instant unstake fee: ${params.instantUnstakeFee} basis points (${
+            params.instantUnstakeFee / 100
+          }%)`
+        )
+      }
+
+      if (params.stakeCooldown !== undefined) {
+        updateParams.stakeCooldownSeconds = new anchor.BN(params.stakeCooldown)
+        console.log(`📝 Stake cooldown: ${params.stakeCooldown} seconds`)
+      }
+
+      if (params.rewardVestingPeriod !== undefined) {
+        updateParams.rewardVestingPeriod = new anchor.BN(params.rewardVestingPeriod)
+        console.log(`📝 Reward vesting period: ${params.rewardVestingPeriod / 3600} hours`)
+      }
+
+      if (params.performanceFee !== undefined) {
+        updateParams.performanceFeeBps = new anchor.BN(params.performanceFee)
+        console.log(`📝 Performance fee: ${params.performanceFee} basis points (${params.performanceFee / 100}%)`)
+      }
+
+      if (params.withdrawalFee !== undefined) {
+        updateParams.withdrawalFeeBps = new anchor.BN(params.withdrawalFee)
+        console.log(`📝 Withdrawal fee: ${params.withdrawalFee} basis points (${params.withdrawalFee / 100}%)`)
+      }
+
+      if (params.rewardAuthority !== undefined) {
+        updateParams.rewardAuthority = params.rewardAuthority
+        console.log(`📝 Reward authority: ${params.rewardAuthority.toString()}`)
+      }
+
+      if (params.permissionlessRewards !== undefined) {
+        updateParams.permissionlessRewards = params.permissionlessRewards
+        console.log(`📝 Permissionless rewards: ${params.permissionlessRewards}`)
+      }
+
+      if (params.pauseOnShortfall !== undefined) {
+        updateParams.pauseOnShortfall = params.pauseOnShortfall
+        console.log(`📝 Pause on shortfall: ${params.pauseOnShortfall}`)
+      }
+
+      if (params.unstakeRequestExpiry !== undefined) {
+        updateParams.unstakeRequestExpiry = new anchor.BN(params.unstakeRequestExpiry)
+        console.log(
+          `📝 Unstake request expiry: ${
+            params.unstakeRequestExpiry === 0 ? 'never' : `${params.unstakeRequestExpiry / 86400} days after maturity`
+          }`
+        )
+      }
+
+      if (params.checkpointInterval !== undefined) {
+        updateParams.checkpointInterval = new anchor.BN(params.checkpointInterval)
+        console.log(`📝 Checkpoint interval: ${params.checkpointInterval / 3600} hours`)
+      }
+
+      if (params.managementFee !== undefined) {
+        updateParams.managementFeeBps = new anchor.BN(params.managementFee)
+        console.log(`📝 Management fee: ${params.managementFee} basis points (${params.managementFee / 100}% a year)`)
+      }
+
+      if (params.depositFee !== undefined) {
+        updateParams.depositFeeBps = new anchor.BN(params.depositFee)
+        console.log(`📝 Deposit fee: ${params.depositFee} basis points of each stake`)
+      }
+
+      if (params.depositFeeToOwner !== undefined) {
+        updateParams.depositFeeToOwner = params.depositFeeToOwner
+        console.log(`📝 Deposit fee goes to: ${params.depositFeeToOwner ? 'the owner' : 'the stakers'}`)
+      }
+
+      if (params.configTimelockSeconds !== undefined) {
+        updateParams.configTimelockSeconds = new anchor.BN(params.configTimelockSeconds)
+        console.log(`📝 Config timelock: ${params.configTimelockSeconds / 3600} hours`)
+      }
+
+      const vaultAccount = await this.program.account.vault.fetch(vaultPDA)
+      console.log(`Current config digest:   ${configDigest(vaultAccount)}`)
+      console.log(
+        `Predicted config digest: ${predictConfigDigest(vaultAccount, updateParams)}`
+      )
+
+      // mirrors Vault::check_instant_config_update
+      const pauseOnly = Object.entries(updateParams).every(
+        ([key, value]) => value === null || PAUSE_PARAMS.includes(key)
+      )
+      if (updateParams.configTimelockSeconds !== null || (vaultAccount.configTimelockSeconds.gtn(0) && !pauseOnly)) {
+        return await this.proposeConfigUpdate(updateParams)
       }
 
       const tx = await this.program.methods
@@ -144,6 +325,490 @@ export class VaultAdminOperations {
     }
   }
 
+  private getPendingConfigUpdatePDA(): PublicKey {
+    const [vaultPDA] = this.getVaultPDA()
+    const [pending] = PublicKey.findProgramAddressSync(
+      [Buffer.from('pending_config_update'), vaultPDA.toBuffer()],
+      this.program.programId
+    )
+    return pending
+  }
+
+  // stages `updateParams` behind the vault's config timelock, commit-config-update applies them
+  async proposeConfigUpdate(updateParams: any): Promise<string> {
+    const [vaultPDA] = this.getVaultPDA()
+    const tx = await this.program.methods
+      .proposeConfigUpdate(updateParams)
+      .accounts({
+        vault: vaultPDA,
+        pendingConfigUpdate: this.getPendingConfigUpdatePDA(),
+        owner: this.adminWallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      } as any)
+      .signers([this.adminWallet])
+      .rpc()
+    const pending = await this.program.account.pendingConfigUpdate.fetch(this.getPendingConfigUpdatePDA())
+    console.log(
+      `⏳ Config update proposed, run commit-config-update after ${new Date(
+        pending.effectiveAt.toNumber() * 1000
+      ).toLocaleString()}`
+    )
+    console.log(`Transaction: ${tx}`)
+    return tx
+  }
+
+  async commitConfigUpdate(): Promise<string> {
+    const [vaultPDA] = this.getVaultPDA()
+    const tx = await this.program.methods
+      .commitConfigUpdate()
+      .accounts({
+        vault: vaultPDA,
+        pendingConfigUpdate: this.getPendingConfigUpdatePDA(),
+        owner: this.adminWallet.publicKey,
+      } as any)
+      .signers([this.adminWallet])
+      .rpc()
+    console.log('✅ Proposed config update committed')
+    console.log(`Transaction: ${tx}`)
+    return tx
+  }
+
+  async cancelConfigUpdate(): Promise<string> {
+    const [vaultPDA] = this.getVaultPDA()
+    const tx = await this.program.methods
+      .cancelConfigUpdate()
+      .accounts({
+        vault: vaultPDA,
+        pendingConfigUpdate: this.getPendingConfigUpdatePDA(),
+        owner: this.adminWallet.publicKey,
+      } as any)
+      .signers([this.adminWallet])
+      .rpc()
+    console.log('✅ Proposed config update cancelled')
+    console.log(`Transaction: ${tx}`)
+    return tx
+  }
+
+  // grows a vault created by an older program to the current layout, the admin wallet pays the
+  // rent of the added bytes. A current vault is left as it is
+  async migrateVault(): Promise<string> {
+    const [vaultPDA] = this.getVaultPDA()
+    const tx = await this.program.methods
+      .migrateVault()
+      .accounts({
+        vault: vaultPDA,
+        owner: this.adminWallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      } as any)
+      .signers([this.adminWallet])
+      .rpc()
+    console.log('✅ Vault migrated to the current account layout')
+    console.log(`Transaction: ${tx}`)
+    return tx
+  }
+
+  // first step of an ownership transfer, nothing changes until `newOwner` runs accept-ownership
+  async proposeOwner(newOwner: PublicKey): Promise<string> {
+    const [vaultPDA] = this.getVaultPDA()
+    console.log(`👤 Proposing ${newOwner.toString()} as the new vault owner...`)
+    const tx = await this.program.methods
+      .proposeOwner(newOwner)
+      .accounts({ vault: vaultPDA, owner: this.adminWallet.publicKey } as any)
+      .signers([this.adminWallet])
+      .rpc()
+    console.log('✅ Ownership transfer proposed, the new owner must now run accept-ownership')
+    console.log(`Transaction: ${tx}`)
+    return tx
+  }
+
+  // signed by the proposed owner's wallet (--wallet). Fee shares accrued under the previous
+  // owner are settled into their personal depositor, which this wallet pays rent for if missing
+  async acceptOwnership(): Promise<string> {
+    const [vaultPDA] = this.getVaultPDA()
+    const vaultAccount = await this.program.account.vault.fetch(vaultPDA)
+    const [previousOwnerDepositor] = PublicKey.findProgramAddressSync(
+      [Buffer.from('vault_depositor'), vaultPDA.toBuffer(), vaultAccount.owner.toBuffer()],
+      this.program.programId
+    )
+    const tx = await this.program.methods
+      .acceptOwnership()
+      .accounts({
+        vault: vaultPDA,
+        ownerFeeDepositor: getOwnerFeeDepositorPDA(this.program.programId, vaultPDA),
+        previousOwnerDepositor,
+        pendingOwner: this.adminWallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      } as any)
+      .signers([this.adminWallet])
+      .rpc()
+    console.log(`✅ ${this.adminWallet.publicKey.toString()} now owns the vault`)
+    console.log(`Fees accrued so far were settled to ${vaultAccount.owner.toString()}'s own position`)
+    console.log(`Transaction: ${tx}`)
+    return tx
+  }
+
+  async cancelOwnerTransfer(): Promise<string> {
+    const [vaultPDA] = this.getVaultPDA()
+    const tx = await this.program.methods
+      .cancelOwnerTransfer()
+      .accounts({ vault: vaultPDA, owner: this.adminWallet.publicKey } as any)
+      .signers([this.adminWallet])
+      .rpc()
+    console.log('✅ Pending ownership transfer cancelled')
+    console.log(`Transaction: ${tx}`)
+    return tx
+  }
+
+  private getProtocolConfigPDA(): PublicKey {
+    return PublicKey.findProgramAddressSync([Buffer.from('protocol_config')], this.program.programId)[0]
+  }
+
+  // signed by the program's upgrade authority (--wallet), `admin` then manages the config
+  async initializeProtocolConfig(admin: PublicKey): Promise<string> {
+    const programData = PublicKey.findProgramAddressSync(
+      [this.program.programId.toBuffer()],
+      new PublicKey('BPFLoaderUpgradeab1e11111111111111111111111')
+    )[0]
+    const tx = await this.program.methods
+      .initializeProtocolConfig(admin, {
+        platformAccount: null,
+        defaultUnstakeLockupPeriod: null,
+        defaultPlatformShareBps: null,
+        maxPlatformShareBps: null,
+        defaultManagementFeeBps: null,
+        maxManagementFeeBps: null,
+      })
+      .accounts({
+        protocolConfig: this.getProtocolConfigPDA(),
+        programData,
+        upgradeAuthority: this.adminWallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      } as any)
+      .signers([this.adminWallet])
+      .rpc()
+    console.log(`✅ Protocol config created with admin ${admin.toString()}`)
+    console.log(`Transaction: ${tx}`)
+    return tx
+  }
+
+  // new vaults read the defaults and caps, existing vaults are not changed
+  async updateProtocolConfig(params: {
+    platformAccount?: PublicKey
+    defaultUnstakeLockupPeriod?: number // in seconds
+    defaultPlatformShareBps?: number
+    maxPlatformShareBps?: number
+    defaultManagementFeeBps?: number
+    maxManagementFeeBps?: number
+  }): Promise<string> {
+    const bn = (value?: number) => (value === undefined ? null : new anchor.BN(value))
+    const tx = await this.program.methods
+      .updateProtocolConfig({
+        platformAccount: params.platformAccount ?? null,
+        defaultUnstakeLockupPeriod: bn(params.defaultUnstakeLockupPeriod),
+        defaultPlatformShareBps: bn(params.defaultPlatformShareBps),
+        maxPlatformShareBps: bn(params.maxPlatformShareBps),
+        defaultManagementFeeBps: bn(params.defaultManagementFeeBps),
+        maxManagementFeeBps: bn(params.maxManagementFeeBps),
+      })
+      .accounts({ protocolConfig: this.getProtocolConfigPDA(), admin: this.adminWallet.publicKey } as any)
+      .signers([this.adminWallet])
+      .rpc()
+    console.log('✅ Protocol config updated')
+    console.log(`Transaction: ${tx}`)
+    return tx
+  }
+
+  async proposeProtocolAdmin(newAdmin: PublicKey): Promise<string> {
+    const tx = await this.program.methods
+      .proposeProtocolAdmin(newAdmin)
+      .accounts({ protocolConfig: this.getProtocolConfigPDA(), admin: this.adminWallet.publicKey } as any)
+      .signers([this.adminWallet])
+      .rpc()
+    console.log('✅ Protocol admin transfer proposed, the new admin must now run accept-protocol-admin')
+    console.log(`Transaction: ${tx}`)
+    return tx
+  }
+
+  // signed by the proposed admin's wallet (--wallet)
+  async acceptProtocolAdmin(): Promise<string> {
+    const tx = await this.program.methods
+      .acceptProtocolAdmin()
+      .accounts({ protocolConfig: this.getProtocolConfigPDA(), pendingAdmin: this.adminWallet.publicKey } as any)
+      .signers([this.adminWallet])
+      .rpc()
+    console.log(`✅ ${this.adminWallet.publicKey.toString()} now administers the protocol config`)
+    console.log(`Transaction: ${tx}`)
+    return tx
+  }
+
+  // signed by the protocol admin (--wallet). Only lowers fees of vaults created under the
+  // protocol config or opted in at creation, exits are never paused
+  async overrideVaultFees(params: {
+    platformShareBps?: number
+    managementFeeBps?: number
+    performanceFeeBps?: number
+    withdrawalFeeBps?: number
+    instantUnstakeFeeBps?: number
+    depositFeeBps?: number
+    pauseDeposits?: boolean
+  }): Promise<string> {
+    const [vaultPDA] = this.getVaultPDA()
+    const bn = (value?: number) => (value === undefined ? null : new anchor.BN(value))
+    const tx = await this.program.methods
+      .overrideVaultFees({
+        platformShareBps: bn(params.platformShareBps),
+        managementFeeBps: bn(params.managementFeeBps),
+        performanceFeeBps: bn(params.performanceFeeBps),
+        withdrawalFeeBps: bn(params.withdrawalFeeBps),
+        instantUnstakeFeeBps: bn(params.instantUnstakeFeeBps),
+        depositFeeBps: bn(params.depositFeeBps),
+        pauseDeposits: params.pauseDeposits ?? false,
+      })
+      .accounts({
+        protocolConfig: this.getProtocolConfigPDA(),
+        vault: vaultPDA,
+        admin: this.adminWallet.publicKey,
+      } as any)
+      .signers([this.adminWallet])
+      .rpc()
+    console.log('✅ Vault fees overridden')
+    console.log(`Transaction: ${tx}`)
+    return tx
+  }
+
+  // first step of a platform account change, rewards keep going to the current platform account
+  // until `newPlatformAccount` runs accept-platform-account. `platformCosigner` is the current
+  // platform account's keypair, needed once the platform requires co-signing
+  async proposePlatformAccount(newPlatformAccount: PublicKey, platformCosigner?: Keypair): Promise<string> {
+    const [vaultPDA] = this.getVaultPDA()
+    console.log(`🏦 Proposing ${newPlatformAccount.toString()} as the new platform account...`)
+    const tx = await this.program.methods
+      .proposePlatformAccount(newPlatformAccount)
+      .accounts({
+        vault: vaultPDA,
+        owner: this.adminWallet.publicKey,
+        platform: platformCosigner ? platformCosigner.publicKey : null,
+      } as any)
+      .signers(platformCosigner ? [this.adminWallet, platformCosigner] : [this.adminWallet])
+      .rpc()
+    console.log('✅ Platform account change proposed, the new account must now run accept-platform-account')
+    console.log(`Transaction: ${tx}`)
+    return tx
+  }
+
+  // signed by the proposed platform account's wallet (--wallet)
+  async acceptPlatformAccount(): Promise<string> {
+    const [vaultPDA] = this.getVaultPDA()
+    const tx = await this.program.methods
+      .acceptPlatformAccount()
+      .accounts({ vault: vaultPDA, pendingPlatformAccount: this.adminWallet.publicKey } as any)
+      .signers([this.adminWallet])
+      .rpc()
+    console.log(`✅ ${this.adminWallet.publicKey.toString()} now receives the platform share`)
+    console.log(`Transaction: ${tx}`)
+    return tx
+  }
+
+  async cancelPlatformAccountProposal(): Promise<string> {
+    const [vaultPDA] = this.getVaultPDA()
+    const tx = await this.program.methods
+      .cancelPlatformAccountProposal()
+      .accounts({ vault: vaultPDA, owner: this.adminWallet.publicKey } as any)
+      .signers([this.adminWallet])
+      .rpc()
+    console.log('✅ Pending platform account change cancelled')
+    console.log(`Transaction: ${tx}`)
+    return tx
+  }
+
+  // signed by the current platform account's wallet (--wallet)
+  async setPlatformCosignRequired(required: boolean): Promise<string> {
+    const [vaultPDA] = this.getVaultPDA()
+    const tx = await this.program.methods
+      .setPlatformCosignRequired(required)
+      .accounts({ vault: vaultPDA, platform: this.adminWallet.publicKey } as any)
+      .signers([this.adminWallet])
+      .rpc()
+    console.log(`✅ Platform co-signature on platform account proposals ${required ? 'required' : 'no longer required'}`)
+    console.log(`Transaction: ${tx}`)
+    return tx
+  }
+
+  // redeem owner fee shares at the active share value into the owner's token account,
+  // `amount` in base units or 'all'
+  async withdrawOwnerShares(amount: anchor.BN | 'all'): Promise<string> {
+    const [vaultPDA] = this.getVaultPDA()
+    const vaultAccount = await this.program.account.vault.fetch(vaultPDA)
+    const tokenProgram = (await this.provider.connection.getAccountInfo(vaultAccount.tokenMint))!.owner
+    const ownerTokenAccount = await getAssociatedTokenAddress(
+      vaultAccount.tokenMint,
+      this.adminWallet.publicKey,
+      false,
+      tokenProgram
+    )
+    const method =
+      amount === 'all'
+        ? this.program.methods.withdrawOwnerSharesAll(new anchor.BN(0))
+        : this.program.methods.withdrawOwnerShares(amount)
+    const tx = await method
+      .accounts({
+        vault: vaultPDA,
+        ownerFeeDepositor: getOwnerFeeDepositorPDA(this.program.programId, vaultPDA),
+        vaultTokenAccount: vaultAccount.vaultTokenAccount,
+        ownerTokenAccount,
+        tokenMint: vaultAccount.tokenMint,
+        owner: this.adminWallet.publicKey,
+        tokenProgram,
+      } as any)
+      .signers([this.adminWallet])
+      .rpc()
+    console.log('✅ Owner fee shares withdrawn')
+    console.log(`Transaction: ${tx}`)
+    return tx
+  }
+
+  // send the whole balance of a foreign token account owned by the vault PDA to `destination`,
+  // the staked mint can never be recovered
+  async recoverTokens(sourceTokenAccount: PublicKey, destination: PublicKey): Promise<string> {
+    const [vaultPDA] = this.getVaultPDA()
+    const tokenProgram = (await this.provider.connection.getAccountInfo(sourceTokenAccount))!.owner
+    const source = await getAccount(this.provider.connection, sourceTokenAccount, undefined, tokenProgram)
+    console.log(`Recovering ${source.amount} base units of ${source.mint.toBase58()}`)
+    const tx = await this.program.methods
+      .recoverTokens()
+      .accounts({
+        vault: vaultPDA,
+        sourceTokenAccount,
+        destinationTokenAccount: destination,
+        mint: source.mint,
+        owner: this.adminWallet.publicKey,
+        tokenProgram,
+      } as any)
+      .signers([this.adminWallet])
+      .rpc()
+    console.log('✅ Tokens recovered')
+    console.log(`Transaction: ${tx}`)
+    return tx
+  }
+
+  // start paying `mint` as a second reward token, once per vault; the owner pays the rent of
+  // the vault's token account of it
+  async setSecondaryRewardMint(mint: PublicKey): Promise<string> {
+    const [vaultPDA] = this.getVaultPDA()
+    const tokenProgram = (await this.provider.connection.getAccountInfo(mint))!.owner
+    const tx = await this.program.methods
+      .setSecondaryRewardMint()
+      .accounts({
+        vault: vaultPDA,
+        secondaryRewardMint: mint,
+        secondaryRewardTokenAccount: secondaryRewardTokenAccountPDA(this.program.programId, vaultPDA),
+        owner: this.adminWallet.publicKey,
+        tokenProgram,
+        systemProgram: SystemProgram.programId,
+      } as any)
+      .signers([this.adminWallet])
+      .rpc()
+    console.log('✅ Secondary reward mint set')
+    console.log(`Transaction: ${tx}`)
+    return tx
+  }
+
+  // pause `pause` and resume `resume` (PAUSE_BITS masks), leaving every other operation as it is
+  async updatePausedOperations(pause: number, resume: number): Promise<string> {
+    const [vaultPDA] = this.getVaultPDA()
+    const vaultAccount = await this.program.account.vault.fetch(vaultPDA)
+    const current = pausedMask(vaultAccount)
+    console.log(`Currently paused: ${pausedOperationNames(current).join(', ') || 'none'}`)
+    return this.updateVaultConfig({ pausedOperations: (current | pause) & ~resume })
+  }
+
+  private getAllowedDepositorPDA(vaultPDA: PublicKey, wallet: PublicKey): PublicKey {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from('allow'), vaultPDA.toBuffer(), wallet.toBuffer()],
+      this.program.programId
+    )[0]
+  }
+
+  // create the allowlist marker for `wallet`, the owner pays its rent
+  async allowDepositor(wallet: PublicKey): Promise<string> {
+    const [vaultPDA] = this.getVaultPDA()
+    const tx = await this.program.methods
+      .addAllowedDepositor(wallet)
+      .accounts({
+        vault: vaultPDA,
+        allowedDepositor: this.getAllowedDepositorPDA(vaultPDA, wallet),
+        owner: this.adminWallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      } as any)
+      .signers([this.adminWallet])
+      .rpc()
+    console.log(`✅ ${wallet.toString()} added to the allowlist`)
+    console.log(`Transaction: ${tx}`)
+    return tx
+  }
+
+  // close the marker and refund its rent; an existing position can still unstake
+  async disallowDepositor(wallet: PublicKey): Promise<string> {
+    const [vaultPDA] = this.getVaultPDA()
+    const tx = await this.program.methods
+      .removeAllowedDepositor()
+      .accounts({
+        vault: vaultPDA,
+        allowedDepositor: this.getAllowedDepositorPDA(vaultPDA, wallet),
+        owner: this.adminWallet.publicKey,
+      } as any)
+      .signers([this.adminWallet])
+      .rpc()
+    console.log(`✅ ${wallet.toString()} removed from the allowlist, its position can still exit`)
+    console.log(`Transaction: ${tx}`)
+    return tx
+  }
+
+  private getRewardSourcePDA(vaultPDA: PublicKey, authority: PublicKey): PublicKey {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from('reward_source'), vaultPDA.toBuffer(), authority.toBuffer()],
+      this.program.programId
+    )[0]
+  }
+
+  // accept rewards only from `programId`'s PDA for `seeds` (and other registered sources).
+  // Seeds are utf8 strings, or hex with a 0x prefix
+  async registerRewardSource(programId: PublicKey, seeds: string[]): Promise<string> {
+    const [vaultPDA] = this.getVaultPDA()
+    const seedBytes = seeds.map((seed) => (seed.startsWith('0x') ? Buffer.from(seed.slice(2), 'hex') : Buffer.from(seed)))
+    const [authority] = PublicKey.findProgramAddressSync(seedBytes, programId)
+    const tx = await this.program.methods
+      .registerProgramRewardSource(programId, seedBytes)
+      .accounts({
+        vault: vaultPDA,
+        rewardSource: this.getRewardSourcePDA(vaultPDA, authority),
+        owner: this.adminWallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      } as any)
+      .signers([this.adminWallet])
+      .rpc()
+    console.log(`✅ ${authority.toString()} of program ${programId.toString()} registered as reward source`)
+    console.log(`Transaction: ${tx}`)
+    return tx
+  }
+
+  // removing the last registered source lets any signer add rewards again
+  async removeRewardSource(authority: PublicKey): Promise<string> {
+    const [vaultPDA] = this.getVaultPDA()
+    const tx = await this.program.methods
+      .removeProgramRewardSource()
+      .accounts({
+        vault: vaultPDA,
+        rewardSource: this.getRewardSourcePDA(vaultPDA, authority),
+        owner: this.adminWallet.publicKey,
+      } as any)
+      .signers([this.adminWallet])
+      .rpc()
+    console.log(`✅ reward source ${authority.toString()} removed`)
+    console.log(`Transaction: ${tx}`)
+    return tx
+  }
+
   async getVaultInfo(): Promise<any> {
     try {
       const [vaultPDA] = this.getVaultPDA()
@@ -154,6 +819,9 @@ export class VaultAdminOperations {
         `Name: ${Buffer.from(vaultAccount.name).toString().replace(/\0/g, '')}`
       )
       console.log(`Owner: ${vaultAccount.owner.toString()}`)
+      if (!vaultAccount.pendingOwner.equals(PublicKey.default)) {
+        console.log(`Pending owner: ${vaultAccount.pendingOwner.toString()} (not accepted yet)`)
+      }
       console.log(
         `Platform account: ${vaultAccount.platformAccount.toString()}`
       )
@@ -164,17 +832,40 @@ export class VaultAdminOperations {
       )
       console.log(`Total shares: ${vaultAccount.totalShares.toString()}`)
       console.log(
-        `Management fee: ${Number(
-          vaultAccount.managementFee.toString()
+        `Depositors: ${vaultAccount.activeDepositorCount} active of ${vaultAccount.depositorCount} opened`
+      )
+      console.log(
+        `Platform share: ${Number(
+          vaultAccount.platformShareBps.toString()
         )} basis points (${
-          Number(vaultAccount.managementFee.toString()) / 100
-        }%)`
+          Number(vaultAccount.platformShareBps.toString()) / 100
+        }% of each reward)`
+      )
+      // mirrors MIN_PLATFORM_SHARE_CHANGE_INTERVAL in constants.rs
+      const nextFeeChange = vaultAccount.lastFeeChange.toNumber() + FEE_CHANGE_INTERVAL_SECONDS
+      if (vaultAccount.lastFeeChange.gtn(0) && nextFeeChange > Date.now() / 1000) {
+        console.log(`Next platform share change: after ${new Date(nextFeeChange * 1000).toLocaleString()}`)
+      }
+      console.log(
+        `Management fee: ${vaultAccount.managementFeeBps.toString()} basis points (${
+          vaultAccount.managementFeeBps.toNumber() / 100
+        }% a year, not accrued yet)`
+      )
+      console.log(
+        `Deposit fee: ${vaultAccount.depositFeeBps.toString()} basis points, kept for ${
+          vaultAccount.depositFeeToOwner ? 'the owner' : 'the stakers'
+        }`
       )
       console.log(
         `Minimum stake amount: ${
           Number(vaultAccount.minStakeAmount.toString()) / 1e9
         } USDC`
       )
+      console.log(
+        `Minimum unstake amount: ${
+          Number(vaultAccount.minUnstakeAmount.toString()) / 1e9
+        } USDC (a full exit is always allowed)`
+      )
       console.log(
         `Maximum total assets: ${
           vaultAccount.maxTotalAssets.toString() === '0'
@@ -182,12 +873,95 @@ export class VaultAdminOperations {
             : Number(vaultAccount.maxTotalAssets.toString()) / 1e9 + ' USDC'
         }`
       )
+      const depositorCap = vaultAccount.maxStakePerDepositor
+      console.log(
+        `Maximum stake per depositor: ${
+          depositorCap.isZero() || depositorCap.eq(U64_MAX)
+            ? 'Unlimited'
+            : Number(depositorCap.toString()) / 1e9 + ' USDC'
+        }`
+      )
       console.log(
         `Unstake lockup period: ${
           Number(vaultAccount.unstakeLockupPeriod.toString()) / 3600
         } hours`
       )
-      console.log(`Is paused: ${vaultAccount.isPaused}`)
+      console.log(`Stake cooldown: ${stakeCooldown(vaultAccount)} seconds`)
+      console.log(
+        `Reward vesting period: ${
+          vaultAccount.rewardVestingPeriod.isZero()
+            ? 'None (rewards are released instantly)'
+            : Number(vaultAccount.rewardVestingPeriod.toString()) / 3600 + ' hours'
+        }`
+      )
+      if (!vaultAccount.unvestedRewards.isZero()) {
+        console.log(
+          `Unvested rewards: ${Number(vaultAccount.unvestedRewards.toString()) / 1e9} USDC until ${new Date(
+            Number(vaultAccount.vestingEnd.toString()) * 1000
+          ).toLocaleString()}`
+        )
+      }
+      console.log(
+        `Performance fee: ${
+          vaultAccount.performanceFeeBps.isZero()
+            ? 'None'
+            : Number(vaultAccount.performanceFeeBps.toString()) / 100 + '% above the high-water mark'
+        }`
+      )
+      console.log(
+        `Withdrawal fee: ${
+          vaultAccount.withdrawalFeeBps.isZero()
+            ? 'None'
+            : Number(vaultAccount.withdrawalFeeBps.toString()) / 100 + '% of each unstake, kept by the vault'
+        }`
+      )
+      console.log(
+        `Unstake request expiry: ${
+          vaultAccount.unstakeRequestExpiry.isZero()
+            ? 'Never'
+            : vaultAccount.unstakeRequestExpiry.toNumber() / 86400 + ' days after maturity'
+        }`
+      )
+      console.log(`Checkpoint interval: ${checkpointInterval(vaultAccount) / 3600} hours`)
+      console.log(
+        `Secondary reward mint: ${
+          hasSecondaryRewards(vaultAccount)
+            ? `${vaultAccount.secondaryRewardMint.toString()} (${vaultAccount.totalSecondaryRewards.toString()} base units distributed)`
+            : 'none'
+        }`
+      )
+      console.log(
+        `High-water mark: ${(Number(vaultAccount.highWaterMark.toString()) / 1e12).toFixed(9)} per share`
+      )
+      console.log(`Paused operations: ${pausedOperationNames(pausedMask(vaultAccount)).join(', ') || 'none'}`)
+      console.log(`Permissioned: ${vaultAccount.permissioned}`)
+      console.log(
+        `Reward sources: ${
+          vaultAccount.permissionlessRewards
+            ? 'any signer'
+            : `${
+                vaultAccount.rewardAuthority.equals(PublicKey.default)
+                  ? 'no reward authority'
+                  : vaultAccount.rewardAuthority.toString()
+              } and ${vaultAccount.programRewardSources} registered program source(s)`
+        }`
+      )
+      console.log(
+        `Reconcile: ${vaultAccount.pauseOnShortfall ? 'pauses on shortfall' : 'alerts only'}, last covered at slot ${
+          vaultAccount.lastReconcileSlot.toNumber() || 'never'
+        }`
+      )
+      const pendingUpdate = await this.program.account.pendingConfigUpdate.fetchNullable(
+        this.getPendingConfigUpdatePDA()
+      )
+      console.log(
+        `Config timelock: ${vaultAccount.configTimelockSeconds.toNumber() / 3600} hours${
+          pendingUpdate
+            ? `, update pending until ${new Date(pendingUpdate.effectiveAt.toNumber() * 1000).toLocaleString()}`
+            : ''
+        }`
+      )
+      console.log(`Config digest: ${configDigest(vaultAccount)}`)
       console.log(
         `Created at: ${new Date(
           Number(vaultAccount.createdAt.toString()) * 1000
@@ -217,22 +991,70 @@ Available commands:
   info                              Show current vault configuration
   update-lockup <hours>             Update unstake lockup period (hours)
   update-lockup-min <minutes>       Update unstake lockup period (minutes)
-  update-fee <basis_points>         Update management fee (basis points, e.g., 100 = 1%)
+  update-platform-share <basis_points>  Update the platform's share of each reward (basis points, e.g., 100 = 1%), at most ${MAX_FEE_CHANGE_BPS} bps per week
+  update-fee <basis_points>         Alias for update-platform-share
+  update-management-fee <basis_points>  Update the annual management fee on assets (max ${MAX_MANAGEMENT_FEE_BPS}), stored only until it accrues
+  update-deposit-fee <basis_points> Update the entry fee withheld from each stake (max ${MAX_DEPOSIT_FEE_BPS})
+  set-deposit-fee-to-owner <on|off> Mint the deposit fee as owner fee shares instead of leaving it to the stakers
   update-min-stake <amount>         Update minimum stake amount (USDC)
+  update-min-unstake <amount>       Update the smallest partial unstake request (USDC, at most the minimum stake, 0 disables)
   update-max-assets <amount>        Update maximum total assets (USDC), use 'unlimited' for no limit
-  pause                             Pause the vault
-  unpause                           Unpause the vault
+  update-max-per-depositor <amount> Update the largest position one depositor can stake up to (USDC), use 'unlimited' for no limit
+  update-instant-unstake-fee <basis_points>  Update the instant unstake penalty (max 5000, 0 disables instant unstake)
+  update-stake-cooldown <seconds>   Update the cooldown between a stake and the next exit (1 to 86400 seconds)
+  update-reward-vesting <hours>     Release each add_rewards linearly over this period (0 to 720 hours, 0 = instantly)
+  update-performance-fee <basis_points>  Update the fee on share value gains above the high-water mark (max 5000)
+  update-withdrawal-fee <basis_points>   Update the exit fee kept in the vault on each matured unstake (max 200)
+  update-unstake-request-expiry <days>   Let anyone restore matured requests uncollected this long (7 to 365 days, 0 = never)
+  update-checkpoint-interval <hours>     Update the least spacing of share value checkpoints (1 to 168 hours)
+  propose-owner <address>           Propose a new owner (step 1 of 2, signed by the current owner)
+  accept-ownership                  Accept a proposed transfer (step 2 of 2, run with the new owner's --wallet)
+  cancel-owner-transfer             Withdraw a pending ownership proposal
+  propose-platform-account <address> [cosigner_keypair]  Propose a new platform account (step 1 of 2)
+  accept-platform-account           Accept a proposed platform account (step 2 of 2, run with its --wallet)
+  cancel-platform-account           Withdraw a pending platform account proposal
+  set-platform-cosign <on|off>      Require the platform account to co-sign proposals (run with its --wallet)
+  init-protocol-config [admin]      Create the protocol config of defaults and caps for new vaults (upgrade authority)
+  update-protocol-config <field> <value>  Set platform-account, default-lockup (hours), default-platform-share,
+                                    max-platform-share, default-management-fee or max-management-fee (basis points)
+  propose-protocol-admin <address>  Propose a new protocol admin (step 1 of 2)
+  accept-protocol-admin             Accept the protocol admin role (step 2 of 2, run with the new admin's --wallet)
+  override-fees <fee> <basis_points> [--pause]  Lower platform-share, management-fee, performance-fee,
+                                    withdrawal-fee, instant-unstake-fee or deposit-fee of a governed vault (protocol admin)
+  withdraw-owner-shares <amount|all>  Redeem owner fee shares (USDC) into the owner's token account
+  recover-tokens <source> <destination>  Send a foreign token balance held by the vault to a token account of the same mint
+  set-secondary-reward-mint <mint>  Pay a second reward token to stakers, claimed instead of compounded (once per vault)
+  pause                             Pause everything except request-unstake, unstake and cancel
+  unpause                           Undo pause
+  pause-withdrawals                 Pause unstake requests, their execution and instant unstakes
+  unpause-withdrawals               Unpause withdrawals
+  pause-ops <ops|all>               Pause operations, comma separated: ${Object.keys(PAUSE_BITS).join(', ')}
+  unpause-ops <ops|all>             Unpause operations, other paused operations stay paused
+  set-permissioned <on|off>         Require an allowlist marker to open a position or stake
+  allow-depositor <address>         Add a wallet to the allowlist
+  disallow-depositor <address>      Remove a wallet from the allowlist (its position can still exit)
+  set-reward-authority <address>    Set the wallet allowed to add rewards, besides registered program sources
+  set-permissionless-rewards <on|off>  Accept rewards from any signer (the behaviour before reward authorities)
+  set-pause-on-shortfall <on|off>   Let reconcile pause stakes and withdrawals when the vault holds fewer tokens than it accounts for
+  set-config-timelock <hours>       Propose a delay for later config changes, pauses stay instant (0 to 720 hours)
+  commit-config-update              Apply the proposed config update once its timelock elapsed
+  cancel-config-update              Withdraw the proposed config update
+  register-reward-source <program> <seed>...  Also accept rewards signed by the program's PDA for these seeds
+  remove-reward-source <pda>        Remove a registered reward source PDA
+  migrate-vault                     Grow a vault created by an older program to the current layout
   update-multiple                   Update multiple parameters interactively
 
 Configuration options:
   --wallet <path>                   Specify admin wallet file path (default: ~/.config/solana/id.json)
   --rpc <url>                       Specify RPC node URL (default: devnet)
+  --force                           Skip the guardrail confirmation (e.g. fees above the sanity bound)
+  --profile <path>                  Profile config with a \`guardrails\` section (default: vault-profile.json)
 
 Examples:
   node update-vault-params.ts info                           # Show current vault configuration
   node update-vault-params.ts update-lockup 48              # Set lockup period to 48 hours
   node update-vault-params.ts update-lockup-min 5          # Set lockup period to 5 minutes
-  node update-vault-params.ts update-fee 200                # Set management fee to 2%
+  node update-vault-params.ts update-platform-share 4500    # Send 45% of each reward to the platform
   node update-vault-params.ts update-min-stake 10           # Set minimum stake to 10 USDC
   node update-vault-params.ts update-max-assets unlimited   # Remove asset limit
   node update-vault-params.ts pause                         # Pause the vault
@@ -358,19 +1180,50 @@ async function main() {
         })
         break
 
+      case 'update-platform-share':
       case 'update-fee':
         const feeBasisPoints = parseInt(args[1])
         if (isNaN(feeBasisPoints) || feeBasisPoints < 0) {
           throw new Error(
-            'Please provide a valid management fee in basis points (e.g., 100 = 1%)'
+            'Please provide a valid platform share in basis points (e.g., 100 = 1%)'
           )
         }
+        await confirmGuardrails(
+          checkPlatformShare(feeBasisPoints, loadGuardrailConfig(getOption('--profile'))),
+          args.includes('--force')
+        )
         console.log(
-          `💰 Updating management fee to ${feeBasisPoints} basis points (${
+          `💰 Updating platform share to ${feeBasisPoints} basis points (${
             feeBasisPoints / 100
-          }%)...`
+          }% of each reward)...`
         )
-        await operations.updateVaultConfig({ managementFee: feeBasisPoints })
+        await operations.updateVaultConfig({ platformShare: feeBasisPoints })
+        break
+
+      case 'update-management-fee':
+        const managementFeeBps = parseInt(args[1])
+        if (isNaN(managementFeeBps) || managementFeeBps < 0 || managementFeeBps > MAX_MANAGEMENT_FEE_BPS) {
+          throw new Error(`Please provide a management fee between 0 and ${MAX_MANAGEMENT_FEE_BPS} basis points`)
+        }
+        console.log(`💰 Updating management fee to ${managementFeeBps} basis points (${managementFeeBps / 100}% a year)...`)
+        await operations.updateVaultConfig({ managementFee: managementFeeBps })
+        break
+
+      case 'update-deposit-fee':
+        const depositFee = parseInt(args[1])
+        if (isNaN(depositFee) || depositFee < 0 || depositFee > MAX_DEPOSIT_FEE_BPS) {
+          throw new Error(`Please provide a deposit fee between 0 and ${MAX_DEPOSIT_FEE_BPS} basis points`)
+        }
+        console.log(`💰 Updating deposit fee to ${depositFee} basis points...`)
+        await operations.updateVaultConfig({ depositFee })
+        break
+
+      case 'set-deposit-fee-to-owner':
+        if (args[1] !== 'on' && args[1] !== 'off') {
+          throw new Error('Usage: set-deposit-fee-to-owner <on|off>')
+        }
+        console.log(`💰 Deposit fee goes to ${args[1] === 'on' ? 'the owner' : 'the stakers'}...`)
+        await operations.updateVaultConfig({ depositFeeToOwner: args[1] === 'on' })
         break
 
       case 'update-min-stake':
@@ -384,6 +1237,15 @@ async function main() {
         await operations.updateVaultConfig({ minStakeAmount })
         break
 
+      case 'update-min-unstake':
+        const minUnstakeAmount = parseFloat(args[1])
+        if (isNaN(minUnstakeAmount) || minUnstakeAmount < 0) {
+          throw new Error('Please provide a valid minimum unstake amount in USDC')
+        }
+        console.log(`💵 Updating minimum unstake amount to ${minUnstakeAmount} USDC...`)
+        await operations.updateVaultConfig({ minUnstakeAmount })
+        break
+
       case 'update-max-assets':
         const maxAssetsInput = args[1]
         if (!maxAssetsInput) {
@@ -411,16 +1273,337 @@ async function main() {
         await operations.updateVaultConfig({ maxTotalAssets })
         break
 
+      case 'update-max-per-depositor':
+        const perDepositorInput = args[1]
+        if (!perDepositorInput) {
+          throw new Error("Please provide the per-depositor cap in USDC or 'unlimited'")
+        }
+        if (perDepositorInput.toLowerCase() === 'unlimited') {
+          console.log('📈 Removing the per-depositor cap...')
+          await operations.updateVaultConfig({ maxStakePerDepositor: null })
+          break
+        }
+        const perDepositorAmount = parseFloat(perDepositorInput)
+        if (isNaN(perDepositorAmount) || perDepositorAmount <= 0) {
+          throw new Error("Please provide a valid per-depositor cap in USDC or 'unlimited'")
+        }
+        console.log(`📈 Capping each depositor at ${perDepositorAmount} USDC...`)
+        await operations.updateVaultConfig({ maxStakePerDepositor: perDepositorAmount })
+        break
+
+      case 'update-instant-unstake-fee':
+        const instantFeeBasisPoints = parseInt(args[1])
+        if (isNaN(instantFeeBasisPoints) || instantFeeBasisPoints < 0 || instantFeeBasisPoints > 5000) {
+          throw new Error('Please provide an instant unstake fee between 0 and 5000 basis points')
+        }
+        console.log(
+          `⚡ Updating instant unstake fee to ${instantFeeBasisPoints} basis points (${
+            instantFeeBasisPoints / 100
+          }%)...`
+        )
+        await operations.updateVaultConfig({ instantUnstakeFee: instantFeeBasisPoints })
+        break
+
+      case 'update-stake-cooldown':
+        const cooldownSeconds = parseInt(args[1])
+        if (isNaN(cooldownSeconds) || cooldownSeconds < 1 || cooldownSeconds > 86400) {
+          throw new Error('Please provide a stake cooldown between 1 and 86400 seconds')
+        }
+        console.log(`⏱️ Updating stake cooldown to ${cooldownSeconds} seconds...`)
+        await operations.updateVaultConfig({ stakeCooldown: cooldownSeconds })
+        break
+
+      case 'update-reward-vesting':
+        const vestingHours = parseFloat(args[1])
+        if (isNaN(vestingHours) || vestingHours < 0 || vestingHours > 720) {
+          throw new Error('Please provide a reward vesting period between 0 and 720 hours')
+        }
+        console.log(`🕰️ Updating reward vesting period to ${vestingHours} hours...`)
+        await operations.updateVaultConfig({ rewardVestingPeriod: Math.round(vestingHours * 3600) })
+        break
+
+      case 'update-performance-fee':
+        const performanceFee = parseInt(args[1])
+        if (isNaN(performanceFee) || performanceFee < 0 || performanceFee > 5000) {
+          throw new Error('Please provide a performance fee between 0 and 5000 basis points')
+        }
+        console.log(`💰 Updating performance fee to ${performanceFee} basis points...`)
+        await operations.updateVaultConfig({ performanceFee })
+        break
+
+      case 'update-withdrawal-fee':
+        const withdrawalFee = parseInt(args[1])
+        if (isNaN(withdrawalFee) || withdrawalFee < 0 || withdrawalFee > 200) {
+          throw new Error('Please provide a withdrawal fee between 0 and 200 basis points')
+        }
+        console.log(`🚪 Updating withdrawal fee to ${withdrawalFee} basis points...`)
+        await operations.updateVaultConfig({ withdrawalFee })
+        break
+
+      case 'update-unstake-request-expiry':
+        const expiryDays = parseInt(args[1])
+        if (isNaN(expiryDays) || (expiryDays !== 0 && (expiryDays < 7 || expiryDays > 365))) {
+          throw new Error('Please provide an unstake request expiry between 7 and 365 days, or 0 to disable it')
+        }
+        console.log(`⏳ Updating unstake request expiry to ${expiryDays} days...`)
+        await operations.updateVaultConfig({ unstakeRequestExpiry: expiryDays * 86400 })
+        break
+
+      case 'update-checkpoint-interval':
+        const checkpointHours = parseFloat(args[1])
+        if (isNaN(checkpointHours) || checkpointHours < 1 || checkpointHours > 168) {
+          throw new Error('Please provide a checkpoint interval between 1 and 168 hours')
+        }
+        console.log(`📈 Updating checkpoint interval to ${checkpointHours} hours...`)
+        await operations.updateVaultConfig({ checkpointInterval: Math.round(checkpointHours * 3600) })
+        break
+
+      case 'propose-owner':
+        if (!args[1]) {
+          throw new Error('Please provide the new owner address')
+        }
+        await operations.proposeOwner(new PublicKey(args[1]))
+        break
+
+      case 'accept-ownership':
+        await operations.acceptOwnership()
+        break
+
+      case 'cancel-owner-transfer':
+        await operations.cancelOwnerTransfer()
+        break
+
+      case 'propose-platform-account':
+        if (!args[1]) {
+          throw new Error('Please provide the new platform account address')
+        }
+        const platformCosigner = args[2]
+          ? Keypair.fromSecretKey(Uint8Array.from(JSON.parse(fs.readFileSync(args[2], 'utf8'))))
+          : undefined
+        await operations.proposePlatformAccount(new PublicKey(args[1]), platformCosigner)
+        break
+
+      case 'accept-platform-account':
+        await operations.acceptPlatformAccount()
+        break
+
+      case 'cancel-platform-account':
+        await operations.cancelPlatformAccountProposal()
+        break
+
+      case 'set-platform-cosign':
+        if (args[1] !== 'on' && args[1] !== 'off') {
+          throw new Error('Please provide on or off')
+        }
+        await operations.setPlatformCosignRequired(args[1] === 'on')
+        break
+
+      case 'init-protocol-config':
+        await operations.initializeProtocolConfig(args[1] ? new PublicKey(args[1]) : adminWallet.publicKey)
+        break
+
+      case 'update-protocol-config':
+        const protocolField = args[1]
+        if (!args[2]) {
+          throw new Error('Please provide a field and a value')
+        }
+        if (protocolField === 'platform-account') {
+          await operations.updateProtocolConfig({ platformAccount: new PublicKey(args[2]) })
+          break
+        }
+        const protocolValue = parseFloat(args[2])
+        if (isNaN(protocolValue) || protocolValue < 0) {
+          throw new Error('Please provide a non-negative number')
+        }
+        const protocolParams: { [field: string]: object } = {
+          'default-lockup': { defaultUnstakeLockupPeriod: Math.round(protocolValue * 3600) },
+          'default-platform-share': { defaultPlatformShareBps: protocolValue },
+          'max-platform-share': { maxPlatformShareBps: protocolValue },
+          'default-management-fee': { defaultManagementFeeBps: protocolValue },
+          'max-management-fee': { maxManagementFeeBps: protocolValue },
+        }
+        if (!protocolParams[protocolField]) {
+          throw new Error(`Unknown protocol config field: ${protocolField}`)
+        }
+        await operations.updateProtocolConfig(protocolParams[protocolField])
+        break
+
+      case 'propose-protocol-admin':
+        if (!args[1]) {
+          throw new Error('Please provide the new protocol admin address')
+        }
+        await operations.proposeProtocolAdmin(new PublicKey(args[1]))
+        break
+
+      case 'accept-protocol-admin':
+        await operations.acceptProtocolAdmin()
+        break
+
+      case 'override-fees':
+        const overrideValue = parseFloat(args[2])
+        if (isNaN(overrideValue) || overrideValue < 0) {
+          throw new Error('Please provide a fee and its lowered value in basis points')
+        }
+        const overrideFields: { [fee: string]: string } = {
+          'platform-share': 'platformShareBps',
+          'management-fee': 'managementFeeBps',
+          'performance-fee': 'performanceFeeBps',
+          'withdrawal-fee': 'withdrawalFeeBps',
+          'instant-unstake-fee': 'instantUnstakeFeeBps',
+          'deposit-fee': 'depositFeeBps',
+        }
+        if (!overrideFields[args[1]]) {
+          throw new Error(`Unknown fee: ${args[1]}`)
+        }
+        await operations.overrideVaultFees({
+          [overrideFields[args[1]]]: overrideValue,
+          pauseDeposits: args.includes('--pause'),
+        })
+        break
+
+      case 'withdraw-owner-shares':
+        if (args[1] === 'all') {
+          console.log('💸 Withdrawing all owner fee shares...')
+          await operations.withdrawOwnerShares('all')
+          break
+        }
+        const withdrawAmount = parseFloat(args[1])
+        if (isNaN(withdrawAmount) || withdrawAmount <= 0) {
+          throw new Error('Please provide a USDC amount or all')
+        }
+        console.log(`💸 Withdrawing ${withdrawAmount} USDC of owner fee shares...`)
+        await operations.withdrawOwnerShares(new anchor.BN(Math.round(withdrawAmount * 1e9)))
+        break
+
+      case 'recover-tokens':
+        if (!args[1] || !args[2]) {
+          throw new Error('Please provide the source and destination token accounts')
+        }
+        await operations.recoverTokens(new PublicKey(args[1]), new PublicKey(args[2]))
+        break
+
+      case 'set-secondary-reward-mint':
+        if (!args[1]) {
+          throw new Error('Please provide the secondary reward mint')
+        }
+        await operations.setSecondaryRewardMint(new PublicKey(args[1]))
+        break
+
       case 'pause':
-        console.log('⏸️ Pausing the vault...')
+        console.log('⏸️ Pausing deposits...')
         await operations.updateVaultConfig({ isPaused: true })
         break
 
       case 'unpause':
-        console.log('▶️ Unpausing the vault...')
+        console.log('▶️ Unpausing deposits...')
         await operations.updateVaultConfig({ isPaused: false })
         break
 
+      case 'pause-withdrawals':
+        console.log('⏸️ Pausing withdrawals...')
+        await operations.updateVaultConfig({ withdrawalsPaused: true })
+        break
+
+      case 'unpause-withdrawals':
+        console.log('▶️ Unpausing withdrawals...')
+        await operations.updateVaultConfig({ withdrawalsPaused: false })
+        break
+
+      case 'pause-ops':
+      case 'unpause-ops':
+        if (!args[1]) {
+          throw new Error(`Usage: ${command} <ops|all>, ops from: ${Object.keys(PAUSE_BITS).join(', ')}`)
+        }
+        const operationsMask = parsePausedOperations(args[1])
+        console.log(`${command === 'pause-ops' ? '⏸️ Pausing' : '▶️ Unpausing'} ${pausedOperationNames(operationsMask).join(', ')}...`)
+        if (command === 'pause-ops') {
+          await operations.updatePausedOperations(operationsMask, 0)
+        } else {
+          await operations.updatePausedOperations(0, operationsMask)
+        }
+        break
+
+      case 'set-permissioned':
+        if (args[1] !== 'on' && args[1] !== 'off') {
+          throw new Error('Usage: set-permissioned <on|off>')
+        }
+        console.log(`🔒 ${args[1] === 'on' ? 'Restricting' : 'Opening'} deposits...`)
+        await operations.updateVaultConfig({ permissioned: args[1] === 'on' })
+        break
+
+      case 'allow-depositor':
+        if (!args[1]) {
+          throw new Error('Usage: allow-depositor <address>')
+        }
+        await operations.allowDepositor(new PublicKey(args[1]))
+        break
+
+      case 'disallow-depositor':
+        if (!args[1]) {
+          throw new Error('Usage: disallow-depositor <address>')
+        }
+        await operations.disallowDepositor(new PublicKey(args[1]))
+        break
+
+      case 'set-reward-authority':
+        if (!args[1]) {
+          throw new Error('Usage: set-reward-authority <address>')
+        }
+        console.log(`🎁 Setting reward authority to ${args[1]}...`)
+        await operations.updateVaultConfig({ rewardAuthority: new PublicKey(args[1]) })
+        break
+
+      case 'set-permissionless-rewards':
+        if (args[1] !== 'on' && args[1] !== 'off') {
+          throw new Error('Usage: set-permissionless-rewards <on|off>')
+        }
+        console.log(`🎁 ${args[1] === 'on' ? 'Opening' : 'Restricting'} add_rewards...`)
+        await operations.updateVaultConfig({ permissionlessRewards: args[1] === 'on' })
+        break
+
+      case 'set-pause-on-shortfall':
+        if (args[1] !== 'on' && args[1] !== 'off') {
+          throw new Error('Usage: set-pause-on-shortfall <on|off>')
+        }
+        console.log(`🚨 ${args[1] === 'on' ? 'Arming' : 'Disarming'} the shortfall pause...`)
+        await operations.updateVaultConfig({ pauseOnShortfall: args[1] === 'on' })
+        break
+
+      case 'set-config-timelock':
+        const timelockHours = parseFloat(args[1])
+        if (isNaN(timelockHours) || timelockHours < 0 || timelockHours > 720) {
+          throw new Error('Config timelock must be between 0 and 720 hours')
+        }
+        console.log(`⏳ Proposing a ${timelockHours} hour config timelock...`)
+        await operations.updateVaultConfig({ configTimelockSeconds: Math.round(timelockHours * 3600) })
+        break
+
+      case 'commit-config-update':
+        await operations.commitConfigUpdate()
+        break
+
+      case 'cancel-config-update':
+        await operations.cancelConfigUpdate()
+        break
+
+      case 'migrate-vault':
+        await operations.migrateVault()
+        break
+
+      case 'register-reward-source':
+        if (!args[1] || args.length < 3) {
+          throw new Error('Usage: register-reward-source <program_id> <seed>...')
+        }
+        await operations.registerRewardSource(new PublicKey(args[1]), args.slice(2))
+        break
+
+      case 'remove-reward-source':
+        if (!args[1]) {
+          throw new Error('Usage: remove-reward-source <pda>')
+        }
+        await operations.removeRewardSource(new PublicKey(args[1]))
+        break
+
       case 'update-multiple':
         console.log(
           '🔧 Interactive multiple parameter update not implemented yet.'
@@ -436,6 +1619,11 @@ async function main() {
         process.exit(1)
     }
 
+    // admin transactions do not go through the user CLI, drop its cached accounts for this RPC
+    if (command !== 'info') {
+      new AccountCache(defaultCacheDir(config.rpcUrl)).clear()
+    }
+
     console.log('\n✅ Operation completed!')
   } catch (error) {
     console.error('❌ Operation failed:', error)
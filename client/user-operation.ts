@@ -2,18 +2,54 @@ import * as anchor from '@coral-xyz/anchor'
 import { Program } from '@coral-xyz/anchor'
 import { SimpleVault } from '../target/types/simple_vault'
 import {
-  TOKEN_PROGRAM_ID,
+  createAssociatedTokenAccountIdempotentInstruction,
   getAccount,
   getAssociatedTokenAddress,
+  getMint,
 } from '@solana/spl-token'
 import {
   SystemProgram,
   Keypair,
   PublicKey,
   Connection,
+  TransactionInstruction,
+  VersionedTransactionResponse,
   clusterApiUrl,
 } from '@solana/web3.js'
 import * as fs from 'fs'
+import { PriceSource, PriceQuote, formatUsd } from './price-source'
+import { MetricsSink, instrumentedFetch } from './metrics'
+import { VaultEvent, parseClientId, parseVaultEvents } from './events'
+import { configDigest } from './config-digest'
+import { TrailingApy, checkpointsPDA, chronologicalCheckpoints, trailingApy } from './checkpoints'
+import { claimableSecondaryRewards, hasSecondaryRewards, secondaryRewardTokenAccountPDA } from './secondary-rewards'
+import { isOperationPaused, pausedMask, pausedOperationNames } from './pause'
+import { ListedVault, RegisteredVault, findVaults, listVaults } from './vault-registry'
+import { Finding, diagnose } from './doctor'
+import { ExitPlan, planExit } from './exit-plan'
+import {
+  PRECISION,
+  StakeCapacity,
+  StakePreview,
+  UnstakePreview,
+  VaultStats,
+  activeShareValue,
+  frozenAmount,
+  quoteUnstake,
+  syncedShares,
+  withdrawalFee,
+} from './quote'
+import { Keeper, KeeperOptions } from './keeper'
+import { HolderSnapshot, formatHoldersCsv, snapshotHolders } from './holders'
+import { SortKey, VaultSummary, estimateYield, formatCompareTable, sortSummaries, summarizeVault } from './compare'
+import { AccountCache, CachedConnection, writableAccounts } from './account-cache'
+import {
+  DashboardData,
+  MAX_DASHBOARD_EVENTS,
+  MAX_SHARE_VALUE_SAMPLES,
+  renderDashboard,
+  shareValueSample,
+} from './dashboard'
 
 // config
 interface VaultConfig {
@@ -29,13 +65,25 @@ export class VaultUserOperations {
   private provider: anchor.AnchorProvider
   private config: VaultConfig
   private userWallet: Keypair
+  private priceSource?: PriceSource
+  private metrics?: MetricsSink
+  private cache?: AccountCache
+  private tokenProgramId?: PublicKey
 
-  constructor(config: VaultConfig, userWallet: Keypair) {
+  constructor(config: VaultConfig, userWallet: Keypair, metrics?: MetricsSink, cache?: AccountCache) {
     this.config = config
     this.userWallet = userWallet
+    this.metrics = metrics
+    this.cache = cache
 
-    // set connection
-    const connection = new Connection(config.rpcUrl, 'confirmed')
+    // set connection, account reads go through the on-disk cache when one is given
+    const connectionConfig = {
+      commitment: 'confirmed' as const,
+      fetch: metrics ? instrumentedFetch(metrics) : undefined,
+    }
+    const connection = cache
+      ? new CachedConnection(config.rpcUrl, connectionConfig, cache)
+      : new Connection(config.rpcUrl, connectionConfig)
     this.provider = new anchor.AnchorProvider(
       connection,
       new anchor.Wallet(userWallet),
@@ -65,10 +113,96 @@ export class VaultUserOperations {
     this.program = new Program(idl, this.provider) as Program<SimpleVault>
   }
 
+  // vaults `owner` registered for `mint`
+  async findVaults(owner: PublicKey, mint: PublicKey): Promise<RegisteredVault[]> {
+    return findVaults(this.program, owner, mint)
+  }
+
+  // every vault in the on-chain registry
+  async listVaults(): Promise<ListedVault[]> {
+    return listVaults(this.program)
+  }
+
+  // switch the vault the following operations act on
+  useVault(vaultName: string) {
+    this.config.vaultName = vaultName
+  }
+
+  // optional USD price source for reporting
+  setPriceSource(priceSource: PriceSource) {
+    this.priceSource = priceSource
+  }
+
+  private async getPriceQuote(): Promise<PriceQuote | undefined> {
+    if (!this.priceSource) {
+      return undefined
+    }
+    try {
+      return await this.priceSource.price(this.config.tokenMint)
+    } catch (error) {
+      console.warn(`⚠️ USD price unavailable from ${this.priceSource.label}: ${error}`)
+      return undefined
+    }
+  }
+
+  // send a transaction, report confirm time / compute units to the metrics sink and
+  // invalidate the cached accounts it wrote
+  private async track(instruction: string, send: () => Promise<string>): Promise<string> {
+    if (!this.metrics) {
+      const signature = await send()
+      await this.invalidateCache(signature)
+      return signature
+    }
+
+    const started = Date.now()
+    let signature: string
+    try {
+      signature = await send()
+    } catch (error) {
+      this.metrics.recordTransaction({ instruction, confirmMs: Date.now() - started, ok: false })
+      throw error
+    }
+    const confirmMs = Date.now() - started
+
+    const details = await this.provider.connection
+      .getTransaction(signature, { commitment: 'confirmed', maxSupportedTransactionVersion: 0 })
+      .catch(() => null)
+    await this.invalidateCache(signature, details)
+    this.metrics.recordTransaction({
+      instruction,
+      confirmMs,
+      ok: true,
+      computeUnits: details?.meta?.computeUnitsConsumed,
+      priorityFeeLamports: details?.meta ? Math.max(0, details.meta.fee - 5000) : undefined,
+    })
+    return signature
+  }
+
+  // drop the cached accounts a confirmed transaction wrote, or the whole cache when the
+  // transaction cannot be read back
+  private async invalidateCache(signature: string, details?: VersionedTransactionResponse | null) {
+    if (!this.cache) {
+      return
+    }
+    const transaction =
+      details ??
+      (await this.provider.connection
+        .getTransaction(signature, { commitment: 'confirmed', maxSupportedTransactionVersion: 0 })
+        .catch(() => null))
+    if (!transaction) {
+      this.cache.clear()
+      return
+    }
+    this.cache.invalidate(
+      writableAccounts(transaction.transaction.message, transaction.meta?.loadedAddresses),
+      transaction.slot
+    )
+  }
+
   // get pda address
-  private getVaultPDA(): [PublicKey, number] {
+  private getVaultPDA(vaultName: string = this.config.vaultName): [PublicKey, number] {
     const vaultNameBuffer = Buffer.alloc(32)
-    vaultNameBuffer.write(this.config.vaultName)
+    vaultNameBuffer.write(vaultName)
 
     return PublicKey.findProgramAddressSync(
       [Buffer.from('vault'), vaultNameBuffer],
@@ -89,6 +223,58 @@ export class VaultUserOperations {
     )
   }
 
+  // allowlist marker of `wallet` when the vault is permissioned, null otherwise
+  private async allowedDepositorFor(vaultPDA: PublicKey, wallet: PublicKey): Promise<PublicKey | null> {
+    const vault = await this.program.account.vault.fetch(vaultPDA)
+    if (!vault.permissioned) {
+      return null
+    }
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from('allow'), vaultPDA.toBuffer(), wallet.toBuffer()],
+      this.program.programId
+    )[0]
+  }
+
+  // token program owning the vault's mint, the original token program or Token-2022. The vault
+  // token account and the share mint live under the same program
+  private async tokenProgram(): Promise<PublicKey> {
+    if (!this.tokenProgramId) {
+      const mint = await this.provider.connection.getAccountInfo(this.config.tokenMint)
+      if (!mint) {
+        throw new Error(`token mint ${this.config.tokenMint.toString()} not found`)
+      }
+      this.tokenProgramId = mint.owner
+    }
+    return this.tokenProgramId
+  }
+
+  // share mint and `holder`'s share token account on tokenized vaults, nulls otherwise. The
+  // account is created first when missing, for instructions that mint share tokens
+  private async shareTokenAccountsFor(
+    vaultPDA: PublicKey,
+    holder: PublicKey
+  ): Promise<{ shareMint: PublicKey | null; shareAccount: PublicKey | null; preInstructions: TransactionInstruction[] }> {
+    const vault = await this.program.account.vault.fetch(vaultPDA)
+    if (vault.shareMint.equals(PublicKey.default)) {
+      return { shareMint: null, shareAccount: null, preInstructions: [] }
+    }
+    const tokenProgram = await this.tokenProgram()
+    const shareAccount = await getAssociatedTokenAddress(vault.shareMint, holder, false, tokenProgram)
+    return {
+      shareMint: vault.shareMint,
+      shareAccount,
+      preInstructions: [
+        createAssociatedTokenAccountIdempotentInstruction(
+          this.userWallet.publicKey,
+          shareAccount,
+          holder,
+          vault.shareMint,
+          tokenProgram
+        ),
+      ],
+    }
+  }
+
   private getVaultTokenAccountPDA(): [PublicKey, number] {
     const [vaultPDA] = this.getVaultPDA()
 
@@ -107,17 +293,21 @@ export class VaultUserOperations {
       console.log('🔧 initialize user depositor account...')
       console.log(`Vault PDA: ${vaultPDA.toString()}`)
       console.log(`User Depositor PDA: ${vaultDepositorPDA.toString()}`)
-
-      const tx = await this.program.methods
-        .initializeVaultDepositor()
-        .accounts({
-          vault: vaultPDA,
-          vaultDepositor: vaultDepositorPDA,
-          authority: this.userWallet.publicKey,
-          systemProgram: SystemProgram.programId,
-        } as any)
-        .signers([this.userWallet])
-        .rpc()
+      const allowedDepositor = await this.allowedDepositorFor(vaultPDA, this.userWallet.publicKey)
+
+      const tx = await this.track('initializeVaultDepositor', () =>
+        this.program.methods
+          .initializeVaultDepositor()
+          .accounts({
+            vault: vaultPDA,
+            vaultDepositor: vaultDepositorPDA,
+            allowedDepositor,
+            authority: this.userWallet.publicKey,
+            systemProgram: SystemProgram.programId,
+          } as any)
+          .signers([this.userWallet])
+            .rpc()
+      )
 
       console.log('✅ depositor account initialized successfully!')
       console.log(`Transaction: ${tx}`)
@@ -129,7 +319,8 @@ export class VaultUserOperations {
   }
 
   // 2. stake operation
-  async stake(amount: number): Promise<string> {
+  // `minSharesOut` fails the stake with SlippageExceeded below that many shares, 0 is no bound
+  async stake(amount: number, clientId?: string, minSharesOut: anchor.BN | number = 0): Promise<string> {
     try {
       const [vaultPDA] = this.getVaultPDA()
       const [vaultDepositorPDA] = this.getVaultDepositorPDA()
@@ -138,25 +329,37 @@ export class VaultUserOperations {
       // get user token account
       const userTokenAccount = await getAssociatedTokenAddress(
         this.config.tokenMint,
-        this.userWallet.publicKey
+        this.userWallet.publicKey,
+        false,
+        await this.tokenProgram()
       )
 
       console.log('💰 execute stake operation...')
       console.log(`stake amount: ${amount / 1e9} USDC`)
       console.log(`user token account: ${userTokenAccount.toString()}`)
-
-      const tx = await this.program.methods
-        .stake(new anchor.BN(amount))
-        .accounts({
-          vault: vaultPDA,
-          vaultDepositor: vaultDepositorPDA,
-          vaultTokenAccount: vaultTokenAccountPDA,
-          userTokenAccount: userTokenAccount,
-          authority: this.userWallet.publicKey,
-          tokenProgram: TOKEN_PROGRAM_ID,
-        } as any)
-        .signers([this.userWallet])
-        .rpc()
+      const allowedDepositor = await this.allowedDepositorFor(vaultPDA, this.userWallet.publicKey)
+      const shareTokens = await this.shareTokenAccountsFor(vaultPDA, this.userWallet.publicKey)
+
+      const tokenProgram = await this.tokenProgram()
+      const tx = await this.track('stake', () =>
+        this.program.methods
+          .stake(new anchor.BN(amount), new anchor.BN(minSharesOut), parseClientId(clientId))
+          .accounts({
+            vault: vaultPDA,
+            vaultDepositor: vaultDepositorPDA,
+            vaultTokenAccount: vaultTokenAccountPDA,
+            userTokenAccount: userTokenAccount,
+            tokenMint: this.config.tokenMint,
+            allowedDepositor,
+            shareMint: shareTokens.shareMint,
+            userShareAccount: shareTokens.shareAccount,
+            authority: this.userWallet.publicKey,
+            tokenProgram,
+          } as any)
+          .preInstructions(shareTokens.preInstructions)
+          .signers([this.userWallet])
+            .rpc()
+      )
 
       console.log('✅ stake operation successful!')
       console.log(`Transaction: ${tx}`)
@@ -167,24 +370,326 @@ export class VaultUserOperations {
     }
   }
 
-  // 3. request unstake
-  async requestUnstake(amount: number): Promise<string> {
+  // stake and request the unstake of the minted shares in one transaction, the deposit
+  // matures after one lockup period and earns nothing meanwhile
+  async stakeWithTerm(amount: number, clientId?: string): Promise<string> {
     try {
       const [vaultPDA] = this.getVaultPDA()
       const [vaultDepositorPDA] = this.getVaultDepositorPDA()
+      const [vaultTokenAccountPDA] = this.getVaultTokenAccountPDA()
 
-      console.log('📤 request unstake...')
-      console.log(`unstake amount: ${amount / 1e9} USDC`)
+      const userTokenAccount = await getAssociatedTokenAddress(
+        this.config.tokenMint,
+        this.userWallet.publicKey,
+        false,
+        await this.tokenProgram()
+      )
 
-      const tx = await this.program.methods
-        .requestUnstake(new anchor.BN(amount))
-        .accounts({
-          vault: vaultPDA,
-          vaultDepositor: vaultDepositorPDA,
-          authority: this.userWallet.publicKey,
-        } as any)
-        .signers([this.userWallet])
-        .rpc()
+      console.log('🔒 execute term stake operation...')
+      console.log(`stake amount: ${amount / 1e9} USDC`)
+      const allowedDepositor = await this.allowedDepositorFor(vaultPDA, this.userWallet.publicKey)
+
+      const tokenProgram = await this.tokenProgram()
+      const tx = await this.track('stake-with-term', () =>
+        this.program.methods
+          .stakeWithTerm(new anchor.BN(amount), parseClientId(clientId))
+          .accounts({
+            vault: vaultPDA,
+            vaultDepositor: vaultDepositorPDA,
+            vaultTokenAccount: vaultTokenAccountPDA,
+            userTokenAccount: userTokenAccount,
+            tokenMint: this.config.tokenMint,
+            allowedDepositor,
+            // term shares are pending from the start, no share tokens are minted
+            shareMint: null,
+            userShareAccount: null,
+            authority: this.userWallet.publicKey,
+            tokenProgram,
+          } as any)
+          .signers([this.userWallet])
+          .rpc()
+      )
+
+      console.log('✅ term stake successful, run `unstake` once the lockup period ends')
+      console.log(`Transaction: ${tx}`)
+      return tx
+    } catch (error) {
+      console.error('❌ term stake failed:', error)
+      throw error
+    }
+  }
+
+  // stake limits for `authority` from the get_stake_capacity view, see `stakeRejection`
+  async stakeCapacity(authority: PublicKey = this.userWallet.publicKey): Promise<StakeCapacity> {
+    const [vaultPDA] = this.getVaultPDA()
+    const [vaultDepositorPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from('vault_depositor'), vaultPDA.toBuffer(), authority.toBuffer()],
+      this.program.programId
+    )
+    const depositor = await this.program.account.vaultDepositor.fetchNullable(vaultDepositorPDA)
+    return (await this.program.methods
+      .getStakeCapacity()
+      .accounts({
+        vault: vaultPDA,
+        vaultDepositor: depositor ? vaultDepositorPDA : null,
+      } as any)
+      .view()) as StakeCapacity
+  }
+
+  // shares a stake of `amount` base units would mint now, from the preview_stake view
+  async previewStake(amount: number): Promise<StakePreview> {
+    const [vaultPDA] = this.getVaultPDA()
+    const [vaultDepositorPDA] = this.getVaultDepositorPDA()
+    const depositor = await this.program.account.vaultDepositor.fetchNullable(vaultDepositorPDA)
+    return (await this.program.methods
+      .previewStake(new anchor.BN(amount))
+      .accounts({
+        vault: vaultPDA,
+        vaultDepositor: depositor ? vaultDepositorPDA : null,
+      } as any)
+      .view()) as StakePreview
+  }
+
+  // what requesting `shares` would freeze and pay, from the preview_unstake view
+  async previewUnstake(shares: anchor.BN | number): Promise<UnstakePreview> {
+    const [vaultPDA] = this.getVaultPDA()
+    return (await this.program.methods
+      .previewUnstake(new anchor.BN(shares))
+      .accounts({ vault: vaultPDA } as any)
+      .view()) as UnstakePreview
+  }
+
+  // expected outcome of request_unstake the slippage bound is taken from: shares an amount
+  // takes, or assets the whole position freezes
+  async quoteRequestUnstake(amount: number | 'all'): Promise<anchor.BN> {
+    const [vaultDepositorPDA] = this.getVaultDepositorPDA()
+    const [stats, depositor] = await Promise.all([
+      this.vaultStats(),
+      this.program.account.vaultDepositor.fetch(vaultDepositorPDA),
+    ])
+    if (!depositor.unstakeRequest.shares.isZero()) {
+      // the program rejects this with UnstakeRequestAlreadyExists
+      throw new Error('an unstake request is already pending, unstake or cancel-unstake it first')
+    }
+    const available = syncedShares(stats, depositor, depositor.shares)
+    const quote = quoteUnstake(stats, available, amount === 'all' ? 'all' : new anchor.BN(Math.floor(amount)))
+    return amount === 'all' ? quote.frozenAmount : quote.shares
+  }
+
+  // what executing the pending request pays before any transfer fee of the mint
+  async quoteUnstakePayout(): Promise<anchor.BN> {
+    const [vaultPDA] = this.getVaultPDA()
+    const [vaultDepositorPDA] = this.getVaultDepositorPDA()
+    const [vault, depositor] = await Promise.all([
+      this.program.account.vault.fetch(vaultPDA),
+      this.program.account.vaultDepositor.fetch(vaultDepositorPDA),
+    ])
+    const frozen = frozenAmount(depositor.unstakeRequest)
+    return frozen.sub(withdrawalFee(vault, frozen))
+  }
+
+  // share accounting as the next instruction sees it, from the get_vault_stats view
+  async vaultStats(): Promise<VaultStats> {
+    const [vaultPDA] = this.getVaultPDA()
+    return (await this.program.methods
+      .getVaultStats()
+      .accounts({ vault: vaultPDA } as any)
+      .view()) as VaultStats
+  }
+
+  // stake from this wallet into `beneficiary`'s position, creating their depositor if needed.
+  // The cooldown before unstaking applies to the beneficiary
+  async stakeFor(beneficiary: PublicKey, amount: number, clientId?: string): Promise<string> {
+    try {
+      const [vaultPDA] = this.getVaultPDA()
+      const [vaultTokenAccountPDA] = this.getVaultTokenAccountPDA()
+      const [beneficiaryDepositorPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from('vault_depositor'), vaultPDA.toBuffer(), beneficiary.toBuffer()],
+        this.program.programId
+      )
+      const payerTokenAccount = await getAssociatedTokenAddress(
+        this.config.tokenMint,
+        this.userWallet.publicKey,
+        false,
+        await this.tokenProgram()
+      )
+
+      console.log('💰 execute stake-for operation...')
+      console.log(`stake amount: ${amount / 1e9} USDC`)
+      console.log(`beneficiary: ${beneficiary.toString()}`)
+      const allowedDepositor = await this.allowedDepositorFor(vaultPDA, beneficiary)
+      const shareTokens = await this.shareTokenAccountsFor(vaultPDA, beneficiary)
+
+      const tokenProgram = await this.tokenProgram()
+      const tx = await this.track('stake_for', () =>
+        this.program.methods
+          .stakeFor(beneficiary, new anchor.BN(amount), parseClientId(clientId))
+          .accounts({
+            vault: vaultPDA,
+            vaultDepositor: beneficiaryDepositorPDA,
+            vaultTokenAccount: vaultTokenAccountPDA,
+            payerTokenAccount: payerTokenAccount,
+            tokenMint: this.config.tokenMint,
+            allowedDepositor,
+            shareMint: shareTokens.shareMint,
+            beneficiaryShareAccount: shareTokens.shareAccount,
+            payer: this.userWallet.publicKey,
+            tokenProgram,
+            systemProgram: SystemProgram.programId,
+          } as any)
+          .preInstructions(shareTokens.preInstructions)
+          .signers([this.userWallet])
+          .rpc()
+      )
+
+      console.log('✅ stake-for operation successful!')
+      console.log(`Transaction: ${tx}`)
+      return tx
+    } catch (error) {
+      console.error('❌ stake-for operation failed:', error)
+      throw error
+    }
+  }
+
+  // exit now without waiting for the lockup, `amount === 'all'` exits every active share.
+  // The vault keeps `instantUnstakeFeeBps` of the value for the remaining stakers
+  async instantUnstake(amount: number | 'all', clientId?: string): Promise<string> {
+    try {
+      const [vaultPDA] = this.getVaultPDA()
+      const [vaultDepositorPDA] = this.getVaultDepositorPDA()
+      const [vaultTokenAccountPDA] = this.getVaultTokenAccountPDA()
+      const userTokenAccount = await getAssociatedTokenAddress(
+        this.config.tokenMint,
+        this.userWallet.publicKey,
+        false,
+        await this.tokenProgram()
+      )
+
+      const vaultAccount = await this.program.account.vault.fetch(vaultPDA)
+      const feeBps = vaultAccount.instantUnstakeFeeBps.toNumber()
+      if (feeBps === 0) {
+        throw new Error('Instant unstake is disabled for this vault')
+      }
+
+      console.log('⚡ execute instant unstake...')
+      console.log(`unstake amount: ${amount === 'all' ? 'all active shares' : `${amount / 1e9} USDC`}`)
+      console.log(`penalty: ${feeBps / 100}% stays in the vault`)
+
+      const shareTokens = await this.shareTokenAccountsFor(vaultPDA, this.userWallet.publicKey)
+      const tokenProgram = await this.tokenProgram()
+      const method =
+        amount === 'all'
+          ? this.program.methods.instantUnstakeAll(new anchor.BN(0), parseClientId(clientId))
+          : this.program.methods.instantUnstake(new anchor.BN(amount), parseClientId(clientId))
+      const tx = await this.track('instantUnstake', () =>
+        method
+          .accounts({
+            vault: vaultPDA,
+            vaultDepositor: vaultDepositorPDA,
+            vaultTokenAccount: vaultTokenAccountPDA,
+            userTokenAccount: userTokenAccount,
+            tokenMint: this.config.tokenMint,
+            shareMint: shareTokens.shareMint,
+            userShareAccount: shareTokens.shareAccount,
+            authority: this.userWallet.publicKey,
+            tokenProgram,
+          } as any)
+          .signers([this.userWallet])
+          .rpc()
+      )
+
+      console.log('✅ instant unstake successful!')
+      console.log(`Transaction: ${tx}`)
+      return tx
+    } catch (error) {
+      console.error('❌ instant unstake failed:', error)
+      throw error
+    }
+  }
+
+  // move active shares (raw share units, or 'all') to `recipient`'s position without the
+  // unstake lockup, creating their depositor if needed. A pending unstake request stays here
+  async transferShares(recipient: PublicKey, shares: anchor.BN | 'all'): Promise<string> {
+    try {
+      const [vaultPDA] = this.getVaultPDA()
+      const [vaultDepositorPDA] = this.getVaultDepositorPDA()
+      const [recipientDepositorPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from('vault_depositor'), vaultPDA.toBuffer(), recipient.toBuffer()],
+        this.program.programId
+      )
+
+      console.log('🔁 execute share transfer...')
+      console.log(`shares: ${shares === 'all' ? 'all active shares' : shares.toString()}`)
+      console.log(`recipient: ${recipient.toString()}`)
+      const allowedDepositor = await this.allowedDepositorFor(vaultPDA, recipient)
+      const shareTokens = await this.shareTokenAccountsFor(vaultPDA, this.userWallet.publicKey)
+      const recipientShareTokens = await this.shareTokenAccountsFor(vaultPDA, recipient)
+
+      const tokenProgram = await this.tokenProgram()
+      const method =
+        shares === 'all'
+          ? this.program.methods.transferSharesAll(recipient)
+          : this.program.methods.transferShares(recipient, shares)
+      const tx = await this.track('transferShares', () =>
+        method
+          .accounts({
+            vault: vaultPDA,
+            vaultDepositor: vaultDepositorPDA,
+            recipientDepositor: recipientDepositorPDA,
+            allowedDepositor,
+            shareMint: shareTokens.shareMint,
+            userShareAccount: shareTokens.shareAccount,
+            recipientShareAccount: recipientShareTokens.shareAccount,
+            authority: this.userWallet.publicKey,
+            tokenProgram: shareTokens.shareMint ? tokenProgram : null,
+          } as any)
+          .preInstructions(recipientShareTokens.preInstructions)
+          .signers([this.userWallet])
+          .rpc()
+      )
+
+      console.log('✅ share transfer successful!')
+      console.log(`Transaction: ${tx}`)
+      return tx
+    } catch (error) {
+      console.error('❌ share transfer failed:', error)
+      throw error
+    }
+  }
+
+  // 3. request unstake, `amount === 'all'` requests the whole position. `bound` is the most
+  // shares an amount may take, or the least a whole-position request may freeze, 0 is no bound
+  async requestUnstake(amount: number | 'all', clientId?: string, bound: anchor.BN | number = 0): Promise<string> {
+    try {
+      const [vaultPDA] = this.getVaultPDA()
+      const [vaultDepositorPDA] = this.getVaultDepositorPDA()
+      const [vaultTokenAccountPDA] = this.getVaultTokenAccountPDA()
+
+      console.log('📤 request unstake...')
+      console.log(`unstake amount: ${amount === 'all' ? 'whole position' : `${amount / 1e9} USDC`}`)
+      // share token accounts of a tokenized vault, the requested shares are burned from them
+      const shareTokens = await this.shareTokenAccountsFor(vaultPDA, this.userWallet.publicKey)
+
+      const tokenProgram = await this.tokenProgram()
+      const method =
+        amount === 'all'
+          ? this.program.methods.requestUnstakeAll(new anchor.BN(bound), parseClientId(clientId))
+          : this.program.methods.requestUnstake(new anchor.BN(amount), new anchor.BN(bound), parseClientId(clientId))
+      const tx = await this.track('requestUnstake', () =>
+        method
+          .accounts({
+            vault: vaultPDA,
+            vaultDepositor: vaultDepositorPDA,
+            vaultTokenAccount: vaultTokenAccountPDA,
+            shareMint: shareTokens.shareMint,
+            userShareAccount: shareTokens.shareAccount,
+            authority: this.userWallet.publicKey,
+            tokenProgram,
+          } as any)
+          .preInstructions(shareTokens.preInstructions)
+          .signers([this.userWallet])
+            .rpc()
+      )
 
       console.log('✅ request unstake submitted successfully!')
       console.log(`Transaction: ${tx}`)
@@ -198,8 +703,9 @@ export class VaultUserOperations {
     }
   }
 
-  // 4. execute unstake
-  async unstake(): Promise<string> {
+  // 4. execute unstake, `minAmountOut` fails it with SlippageExceeded when the wallet would
+  // receive less, 0 is no bound
+  async unstake(clientId?: string, minAmountOut: anchor.BN | number = 0): Promise<string> {
     try {
       const [vaultPDA] = this.getVaultPDA()
       const [vaultDepositorPDA] = this.getVaultDepositorPDA()
@@ -208,23 +714,29 @@ export class VaultUserOperations {
       // get user token account
       const userTokenAccount = await getAssociatedTokenAddress(
         this.config.tokenMint,
-        this.userWallet.publicKey
+        this.userWallet.publicKey,
+        false,
+        await this.tokenProgram()
       )
 
       console.log('💸 execute unstake...')
 
-      const tx = await this.program.methods
-        .unstake()
-        .accounts({
-          vault: vaultPDA,
-          vaultDepositor: vaultDepositorPDA,
-          vaultTokenAccount: vaultTokenAccountPDA,
-          userTokenAccount: userTokenAccount,
-          authority: this.userWallet.publicKey,
-          tokenProgram: TOKEN_PROGRAM_ID,
-        } as any)
-        .signers([this.userWallet])
-        .rpc()
+      const tokenProgram = await this.tokenProgram()
+      const tx = await this.track('unstake', () =>
+        this.program.methods
+          .unstake(new anchor.BN(minAmountOut), parseClientId(clientId))
+          .accounts({
+            vault: vaultPDA,
+            vaultDepositor: vaultDepositorPDA,
+            vaultTokenAccount: vaultTokenAccountPDA,
+            userTokenAccount: userTokenAccount,
+            tokenMint: this.config.tokenMint,
+            authority: this.userWallet.publicKey,
+            tokenProgram,
+          } as any)
+          .signers([this.userWallet])
+            .rpc()
+      )
 
       console.log('✅ unstake operation successful!')
       console.log(`Transaction: ${tx}`)
@@ -240,18 +752,28 @@ export class VaultUserOperations {
     try {
       const [vaultPDA] = this.getVaultPDA()
       const [vaultDepositorPDA] = this.getVaultDepositorPDA()
+      const [vaultTokenAccountPDA] = this.getVaultTokenAccountPDA()
 
       console.log('🚫 cancel unstake request...')
-
-      const tx = await this.program.methods
-        .cancelUnstakeRequest()
-        .accounts({
-          vault: vaultPDA,
-          vaultDepositor: vaultDepositorPDA,
-          authority: this.userWallet.publicKey,
-        } as any)
-        .signers([this.userWallet])
-        .rpc()
+      const shareTokens = await this.shareTokenAccountsFor(vaultPDA, this.userWallet.publicKey)
+
+      const tokenProgram = await this.tokenProgram()
+      const tx = await this.track('cancelUnstakeRequest', () =>
+        this.program.methods
+          .cancelUnstakeRequest()
+          .accounts({
+            vault: vaultPDA,
+            vaultDepositor: vaultDepositorPDA,
+            vaultTokenAccount: vaultTokenAccountPDA,
+            shareMint: shareTokens.shareMint,
+            userShareAccount: shareTokens.shareAccount,
+            authority: this.userWallet.publicKey,
+            tokenProgram,
+          } as any)
+          .preInstructions(shareTokens.preInstructions)
+          .signers([this.userWallet])
+            .rpc()
+      )
 
       console.log('✅ unstake request cancelled!')
       console.log(`Transaction: ${tx}`)
@@ -262,23 +784,188 @@ export class VaultUserOperations {
     }
   }
 
-  // 6. sync rebase
-  async syncRebase(): Promise<string> {
+  // grows a depositor opened before layout versioning, the wallet pays the added rent
+  async migrateDepositor(): Promise<string> {
     try {
       const [vaultPDA] = this.getVaultPDA()
       const [vaultDepositorPDA] = this.getVaultDepositorPDA()
+      const tx = await this.track('migrateDepositor', () =>
+        this.program.methods
+          .migrateDepositor()
+          .accounts({
+            vault: vaultPDA,
+            vaultDepositor: vaultDepositorPDA,
+            authority: this.userWallet.publicKey,
+            systemProgram: SystemProgram.programId,
+          } as any)
+          .signers([this.userWallet])
+          .rpc()
+      )
 
-      console.log('🔄 sync rebase...')
+      console.log('✅ depositor migrated!')
+      console.log(`Transaction: ${tx}`)
+      return tx
+    } catch (error) {
+      console.error('❌ migrate depositor failed:', error)
+      throw error
+    }
+  }
+
+  // permissionless: creates the vault's share value checkpoint history, paid by this wallet
+  async initCheckpoints(): Promise<string> {
+    try {
+      const [vaultPDA] = this.getVaultPDA()
+      const tx = await this.track('initializeVaultCheckpoints', () =>
+        this.program.methods
+          .initializeVaultCheckpoints()
+          .accounts({
+            vault: vaultPDA,
+            vaultCheckpoints: checkpointsPDA(this.program.programId, vaultPDA),
+            payer: this.userWallet.publicKey,
+            systemProgram: SystemProgram.programId,
+          } as any)
+          .signers([this.userWallet])
+          .rpc()
+      )
+
+      console.log('✅ checkpoint history created, the keeper records into it from now on')
+      console.log(`Transaction: ${tx}`)
+      return tx
+    } catch (error) {
+      console.error('❌ init checkpoints failed:', error)
+      throw error
+    }
+  }
+
+  // the vault's secondary reward mint with its token program and decimals, throws when unset
+  private async secondaryRewardMint(vaultPDA: PublicKey): Promise<{ mint: PublicKey; tokenProgram: PublicKey; decimals: number }> {
+    const vault = await this.program.account.vault.fetch(vaultPDA)
+    if (!hasSecondaryRewards(vault)) {
+      throw new Error('vault has no secondary reward mint')
+    }
+    const tokenProgram = (await this.provider.connection.getAccountInfo(vault.secondaryRewardMint))!.owner
+    const mint = await getMint(this.provider.connection, vault.secondaryRewardMint, undefined, tokenProgram)
+    return { mint: vault.secondaryRewardMint, tokenProgram, decimals: mint.decimals }
+  }
+
+  // claim every secondary reward token the position earned, into this wallet's associated
+  // token account of the mint, created when missing
+  async claimSecondaryRewards(): Promise<string> {
+    try {
+      const [vaultPDA] = this.getVaultPDA()
+      const [vaultDepositorPDA] = this.getVaultDepositorPDA()
+      const { mint, tokenProgram, decimals } = await this.secondaryRewardMint(vaultPDA)
+      const userTokenAccount = await getAssociatedTokenAddress(mint, this.userWallet.publicKey, false, tokenProgram)
+
+      const vault = await this.program.account.vault.fetch(vaultPDA)
+      const depositor = await this.program.account.vaultDepositor.fetch(vaultDepositorPDA)
+      const claimable = claimableSecondaryRewards(vault, depositor)
+      console.log(`Claiming ${Number(claimable.toString()) / 10 ** decimals} of ${mint.toString()}`)
+
+      const tx = await this.track('claimSecondaryRewards', () =>
+        this.program.methods
+          .claimSecondaryRewards()
+          .accounts({
+            vault: vaultPDA,
+            vaultDepositor: vaultDepositorPDA,
+            secondaryRewardTokenAccount: secondaryRewardTokenAccountPDA(this.program.programId, vaultPDA),
+            userTokenAccount,
+            secondaryRewardMint: mint,
+            authority: this.userWallet.publicKey,
+            tokenProgram,
+          } as any)
+          .preInstructions([
+            createAssociatedTokenAccountIdempotentInstruction(
+              this.userWallet.publicKey,
+              userTokenAccount,
+              this.userWallet.publicKey,
+              mint,
+              tokenProgram
+            ),
+          ])
+          .signers([this.userWallet])
+          .rpc()
+      )
+
+      console.log('✅ secondary rewards claimed!')
+      console.log(`Transaction: ${tx}`)
+      return tx
+    } catch (error) {
+      console.error('❌ claim secondary rewards failed:', error)
+      throw error
+    }
+  }
+
+  // trailing APY measured between on-chain share value checkpoints, null without the history
+  async getTrailingApy(days: number): Promise<TrailingApy | null> {
+    const [vaultPDA] = this.getVaultPDA()
+    const account = await this.program.account.vaultCheckpoints.fetchNullable(
+      checkpointsPDA(this.program.programId, vaultPDA)
+    )
+    return account ? trailingApy(chronologicalCheckpoints(account), days) : null
+  }
+
+  // 6. sync rebase
+  // ProgramData account of this program under the upgradeable loader
+  private getProgramDataAddress(): PublicKey {
+    return PublicKey.findProgramAddressSync(
+      [this.program.programId.toBuffer()],
+      new PublicKey('BPFLoaderUpgradeab1e11111111111111111111111')
+    )[0]
+  }
+
+  private getProgramConfigPDA(): PublicKey {
+    return PublicKey.findProgramAddressSync([Buffer.from('program_config')], this.program.programId)[0]
+  }
 
-      const tx = await this.program.methods
-        .syncRebase()
+  // permissionless: copies the program's current upgrade authority into the program_config PDA
+  async attestUpgradeAuthority(): Promise<string> {
+    const tx = await this.track('attestUpgradeAuthority', () =>
+      this.program.methods
+        .attestUpgradeAuthority()
         .accounts({
-          vault: vaultPDA,
-          vaultDepositor: vaultDepositorPDA,
-          authority: this.userWallet.publicKey,
+          programConfig: this.getProgramConfigPDA(),
+          programData: this.getProgramDataAddress(),
+          payer: this.userWallet.publicKey,
+          systemProgram: SystemProgram.programId,
         } as any)
         .signers([this.userWallet])
         .rpc()
+    )
+    console.log('✅ upgrade authority attested')
+    console.log(`Transaction: ${tx}`)
+    return tx
+  }
+
+  // "upgradeable by X as of slot N", "immutable as of slot N" or not attested yet
+  async describeUpgradeability(): Promise<string> {
+    const config = await this.program.account.programConfig.fetchNullable(this.getProgramConfigPDA())
+    if (!config || config.attestedSlot.isZero()) {
+      return 'not attested yet (run attest-upgrade-authority)'
+    }
+    return config.upgradeAuthority
+      ? `upgradeable by ${config.upgradeAuthority.toString()} as of slot ${config.attestedSlot.toString()}`
+      : `immutable as of slot ${config.attestedSlot.toString()}`
+  }
+
+  async syncRebase(): Promise<string> {
+    try {
+      const [vaultPDA] = this.getVaultPDA()
+      const [vaultDepositorPDA] = this.getVaultDepositorPDA()
+
+      console.log('🔄 sync rebase...')
+
+      const tx = await this.track('syncRebase', () =>
+        this.program.methods
+          .syncRebase()
+          .accounts({
+            vault: vaultPDA,
+            vaultDepositor: vaultDepositorPDA,
+            authority: this.userWallet.publicKey,
+          } as any)
+          .signers([this.userWallet])
+            .rpc()
+      )
 
       console.log('✅ rebase sync successful!')
       console.log(`Transaction: ${tx}`)
@@ -289,6 +976,57 @@ export class VaultUserOperations {
     }
   }
 
+  // sync every out-of-date depositor of the vault in batches (permissionless)
+  async syncRebaseAll(batchSize: number = 20): Promise<string[]> {
+    try {
+      const [vaultPDA] = this.getVaultPDA()
+      const vaultAccount = await this.program.account.vault.fetch(vaultPDA)
+      const depositorAccounts = await this.program.account.vaultDepositor.all([
+        {
+          memcmp: {
+            offset: 8, // Skip discriminator
+            bytes: vaultPDA.toBase58(), // Filter by vault
+          },
+        },
+      ])
+
+      const stale = depositorAccounts.filter(
+        (account) => account.account.lastRebaseVersion < vaultAccount.rebaseVersion
+      )
+      console.log(
+        `🔄 ${stale.length} of ${depositorAccounts.length} depositors need rebase sync (version ${vaultAccount.rebaseVersion})`
+      )
+
+      const signatures: string[] = []
+      for (let i = 0; i < stale.length; i += batchSize) {
+        const batch = stale.slice(i, i + batchSize)
+        const tx = await this.track('syncRebaseBatch', () =>
+          this.program.methods
+            .syncRebaseBatch()
+            .accounts({
+              vault: vaultPDA,
+            } as any)
+            .remainingAccounts(
+              batch.map((account) => ({
+                pubkey: account.publicKey,
+                isWritable: true,
+                isSigner: false,
+              }))
+            )
+            .signers([this.userWallet])
+            .rpc()
+        )
+        console.log(`✅ synced ${batch.length} depositors: ${tx}`)
+        signatures.push(tx)
+      }
+
+      return signatures
+    } catch (error) {
+      console.error('❌ batch sync rebase failed:', error)
+      throw error
+    }
+  }
+
   // === query methods ===
 
   // query vault info
@@ -306,12 +1044,31 @@ export class VaultUserOperations {
         `total assets: ${vaultAccount.totalAssets.toNumber() / 1e9} USDC`
       )
       console.log(`total shares: ${vaultAccount.totalShares.toNumber()}`)
+      console.log(
+        `depositors: ${vaultAccount.activeDepositorCount} active, ${vaultAccount.depositorCount} opened`
+      )
       console.log(
         `total rewards: ${vaultAccount.totalRewards.toNumber() / 1e9} USDC`
       )
+      // SHARE_PRECISION (1e18) scaled product of every reward's share value growth
+      const cumulativeReturn = Number(vaultAccount.cumulativeReturn.toString()) / 1e18
+      if (cumulativeReturn > 0) {
+        console.log(`cumulative return from rewards: ${((cumulativeReturn - 1) * 100).toFixed(4)}%`)
+      }
+      const quote = await this.getPriceQuote()
+      if (quote) {
+        console.log(
+          `TVL (USD): ${formatUsd(vaultAccount.totalAssets, quote)}`
+        )
+      }
       console.log(`owner shares: ${vaultAccount.ownerShares.toNumber()}`)
+      console.log(`dead shares: ${vaultAccount.deadShares.toNumber()} (held by nobody)`)
+      console.log(`last activity: ${new Date(vaultAccount.lastActivity.toNumber() * 1000).toISOString()}`)
       console.log(
-        `management fee: ${vaultAccount.managementFee.toNumber() / 100}%`
+        `platform share of rewards: ${vaultAccount.platformShareBps.toNumber() / 100}%`
+      )
+      console.log(
+        `management fee: ${vaultAccount.managementFeeBps.toNumber() / 100}% a year (not accrued yet)`
       )
       console.log(
         `minimum stake amount: ${
@@ -323,9 +1080,12 @@ export class VaultUserOperations {
           vaultAccount.unstakeLockupPeriod.toNumber() / 3600
         } hours`
       )
-      console.log(`is paused: ${vaultAccount.isPaused}`)
+      console.log(`paused operations: ${pausedOperationNames(pausedMask(vaultAccount)).join(', ') || 'none'}`)
       console.log(`shares base: ${vaultAccount.sharesBase}`)
       console.log(`rebase version: ${vaultAccount.rebaseVersion}`)
+      console.log(`precision floor events: ${vaultAccount.precisionFloorEvents}`)
+      console.log(`config digest: ${configDigest(vaultAccount)}`)
+      console.log(`program: ${await this.describeUpgradeability()}`)
       console.log(
         `created at: ${new Date(
           vaultAccount.createdAt.toNumber() * 1000
@@ -357,6 +1117,14 @@ export class VaultUserOperations {
       )
       console.log(`last rebase version: ${depositorAccount.lastRebaseVersion}`)
 
+      const [vaultPDA] = this.getVaultPDA()
+      const vaultAccount = await this.program.account.vault.fetch(vaultPDA)
+      if (hasSecondaryRewards(vaultAccount)) {
+        const { decimals } = await this.secondaryRewardMint(vaultPDA)
+        const claimable = claimableSecondaryRewards(vaultAccount, depositorAccount)
+        console.log(`claimable secondary rewards: ${Number(claimable.toString()) / 10 ** decimals}`)
+      }
+
       // unstake request info
       const unstakeRequest = depositorAccount.unstakeRequest
       if (unstakeRequest.shares.toNumber() > 0) {
@@ -433,7 +1201,9 @@ export class VaultUserOperations {
     try {
       const userTokenAccount = await getAssociatedTokenAddress(
         this.config.tokenMint,
-        this.userWallet.publicKey
+        this.userWallet.publicKey,
+        false,
+        await this.tokenProgram()
       )
 
       // check if account exists
@@ -448,7 +1218,9 @@ export class VaultUserOperations {
 
       const tokenAccountInfo = await getAccount(
         this.provider.connection,
-        userTokenAccount
+        userTokenAccount,
+        undefined,
+        await this.tokenProgram()
       )
       const balance = Number(tokenAccountInfo.amount)
 
@@ -512,6 +1284,12 @@ export class VaultUserOperations {
         )} minutes`
       )
       console.log(`can unstake: ${canUnstake ? 'yes' : 'no'}`)
+      if (!vaultAccount.unstakeRequestExpiry.isZero()) {
+        const expiresAt = unlockTime + vaultAccount.unstakeRequestExpiry.toNumber()
+        console.log(
+          `expires: ${new Date(expiresAt * 1000).toLocaleString()}, then anyone can restore it to active shares`
+        )
+      }
 
       return { canUnstake, remainingTime }
     } catch (error) {
@@ -618,6 +1396,382 @@ export class VaultUserOperations {
     }
   }
 
+  // poll vault state and print every change until interrupted
+  async monitor(intervalSeconds: number): Promise<void> {
+    const [vaultPDA] = this.getVaultPDA()
+    let previous: Record<string, string> | undefined
+
+    for (;;) {
+      try {
+        const vaultAccount = await this.program.account.vault.fetch(vaultPDA)
+        const snapshot: Record<string, string> = {
+          totalAssets: vaultAccount.totalAssets.toString(),
+          totalShares: vaultAccount.totalShares.toString(),
+          totalRewards: vaultAccount.totalRewards.toString(),
+          pendingUnstakeShares: vaultAccount.pendingUnstakeShares.toString(),
+          reservedAssets: vaultAccount.reservedAssets.toString(),
+          rebaseVersion: vaultAccount.rebaseVersion.toString(),
+          precisionFloorEvents: vaultAccount.precisionFloorEvents.toString(),
+          pausedOperations: pausedMask(vaultAccount).toString(),
+        }
+
+        const changes = Object.keys(snapshot).filter(
+          (key) => previous && previous[key] !== snapshot[key]
+        )
+        const now = new Date().toISOString()
+        if (!previous) {
+          console.log(`[${now}] ${JSON.stringify(snapshot)}`)
+        } else if (changes.length > 0) {
+          changes.forEach((key) => {
+            console.log(`[${now}] ${key}: ${previous![key]} -> ${snapshot[key]}`)
+          })
+          if (changes.includes('precisionFloorEvents')) {
+            console.log(`[${now}] ⚠️  1-share precision floor fired again, check for dust positions`)
+          }
+        }
+        previous = snapshot
+      } catch (error) {
+        console.error('❌ monitor poll failed:', error)
+      }
+      await new Promise((resolve) => setTimeout(resolve, intervalSeconds * 1000))
+    }
+  }
+
+  // check vault state for warning signs, returns the list of warnings
+  // the last seen counters are kept in `statePath` so growth between runs can be flagged
+  async healthCheck(statePath: string = '.vault-health.json'): Promise<string[]> {
+    const [vaultPDA] = this.getVaultPDA()
+    const vaultAccount = await this.program.account.vault.fetch(vaultPDA)
+    const warnings: string[] = []
+
+    let state: Record<string, { precisionFloorEvents: number; checkedAt: string }> = {}
+    if (fs.existsSync(statePath)) {
+      state = JSON.parse(fs.readFileSync(statePath, 'utf-8'))
+    }
+    const key = vaultPDA.toBase58()
+    const floorEvents: number = vaultAccount.precisionFloorEvents
+    const last = state[key]
+
+    if (last && floorEvents > last.precisionFloorEvents) {
+      warnings.push(
+        `precision floor fired ${floorEvents - last.precisionFloorEvents} time(s) since ${last.checkedAt} ` +
+          `(total ${floorEvents}): dust positions or min_stake_amount too small ` +
+          `(${vaultAccount.minStakeAmount.toNumber() / 1e9} USDC)`
+      )
+    } else if (!last && floorEvents > 0) {
+      warnings.push(`precision floor has fired ${floorEvents} time(s), run again later to see if it keeps growing`)
+    }
+    if (isOperationPaused(vaultAccount, 'stake')) {
+      warnings.push('vault is paused')
+    }
+    if (vaultAccount.reservedAssets.gt(vaultAccount.totalAssets)) {
+      warnings.push('reserved assets exceed total assets')
+    }
+    if (vaultAccount.pendingUnstakeShares.gt(vaultAccount.totalShares)) {
+      warnings.push('pending unstake shares exceed total shares')
+    }
+
+    state[key] = { precisionFloorEvents: floorEvents, checkedAt: new Date().toISOString() }
+    fs.writeFileSync(statePath, JSON.stringify(state, null, 2))
+
+    if (warnings.length === 0) {
+      console.log('✅ vault looks healthy')
+    } else {
+      warnings.forEach((warning) => console.log(`⚠️  ${warning}`))
+    }
+    return warnings
+  }
+
+  // read-only check of the signer's accounts for common problems, prioritized with fixes
+  // all accounts come from a single getMultipleAccountsInfo call
+  async doctor(json: boolean = false): Promise<Finding[]> {
+    const [vaultPDA] = this.getVaultPDA()
+    const [vaultDepositorPDA] = this.getVaultDepositorPDA()
+    const userTokenAccount = await getAssociatedTokenAddress(
+      this.config.tokenMint,
+      this.userWallet.publicKey,
+      false,
+      await this.tokenProgram()
+    )
+
+    const [allowedDepositorPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from('allow'), vaultPDA.toBuffer(), this.userWallet.publicKey.toBuffer()],
+      this.program.programId
+    )
+
+    const [vaultInfo, depositorInfo, tokenAccountInfo, allowedDepositorInfo] =
+      await this.provider.connection.getMultipleAccountsInfo([
+        vaultPDA,
+        vaultDepositorPDA,
+        userTokenAccount,
+        allowedDepositorPDA,
+      ])
+    const findings = diagnose({
+      vault: vaultInfo ? this.program.coder.accounts.decode('vault', vaultInfo.data) : null,
+      depositor: depositorInfo
+        ? this.program.coder.accounts.decode('vaultDepositor', depositorInfo.data)
+        : null,
+      userTokenAccountExists: tokenAccountInfo !== null,
+      allowlisted: allowedDepositorInfo !== null,
+      tokenMint: this.config.tokenMint.toBase58(),
+      now: Math.floor(Date.now() / 1000),
+    })
+
+    if (json) {
+      console.log(
+        JSON.stringify(
+          {
+            vault: vaultPDA.toBase58(),
+            vaultName: this.config.vaultName,
+            wallet: this.userWallet.publicKey.toBase58(),
+            depositor: vaultDepositorPDA.toBase58(),
+            checkedAt: new Date().toISOString(),
+            findings,
+          },
+          null,
+          2
+        )
+      )
+    } else if (findings.length === 0) {
+      console.log('✅ no problems found')
+    } else {
+      const icons = { error: '❌', action: '🔧', wait: '⏳', info: 'ℹ️ ' }
+      findings.forEach((finding) => {
+        console.log(`${finding.priority}. ${icons[finding.severity]} ${finding.message}`)
+        if (finding.fix) {
+          console.log(`   → ${finding.fix}`)
+        }
+      })
+    }
+    return findings
+  }
+
+  // dated schedule for exiting `amount` base units (or everything) under the vault's live config
+  async exitPlan(amount: number | 'all'): Promise<ExitPlan> {
+    const [vaultPDA] = this.getVaultPDA()
+    const [vaultDepositorPDA] = this.getVaultDepositorPDA()
+    const [vaultTokenAccountPDA] = this.getVaultTokenAccountPDA()
+
+    const [vaultAccount, depositorAccount, tokenBalance] = await Promise.all([
+      this.program.account.vault.fetch(vaultPDA),
+      this.program.account.vaultDepositor.fetch(vaultDepositorPDA),
+      this.provider.connection.getTokenAccountBalance(vaultTokenAccountPDA),
+    ])
+    const plan = planExit({
+      vault: vaultAccount,
+      depositor: depositorAccount,
+      amount: amount === 'all' ? 'all' : new anchor.BN(Math.floor(amount)),
+      vaultTokenBalance: new anchor.BN(tokenBalance.value.amount),
+      now: Math.floor(Date.now() / 1000),
+    })
+
+    const usdc = (value: anchor.BN) => (value.toNumber() / 1e9).toFixed(6)
+    console.log(`🚪 exit plan for ${usdc(plan.target)} of ${usdc(plan.positionValue)} USDC`)
+    console.log(
+      plan.fitsInOneRequest
+        ? '  fits in a single unstake request'
+        : '  must be split: the locked request settles before the remainder can be requested'
+    )
+    plan.steps.forEach((step, index) => {
+      const amountText = ` ${usdc(step.amount)} USDC${step.estimated ? ' (est.)' : ''}`
+      console.log(
+        `  ${index + 1}. ${new Date(step.at * 1000).toLocaleString()}  ${step.action}${amountText}  - ${step.note}`
+      )
+    })
+    console.log(`  completes: ${new Date(plan.completesAt * 1000).toLocaleString()}`)
+    plan.caveats.forEach((caveat) => console.log(`  ⚠️  ${caveat}`))
+    return plan
+  }
+
+  // balances the CLI guardrails check against, all in base units. The position is the active
+  // shares only, a pending request cannot be requested again
+  async guardrailContext(): Promise<{ walletBalance: number; positionValue: number; minStakeAmount: number }> {
+    const [vaultPDA] = this.getVaultPDA()
+    const [vaultDepositorPDA] = this.getVaultDepositorPDA()
+    const userTokenAccount = await getAssociatedTokenAddress(
+      this.config.tokenMint,
+      this.userWallet.publicKey,
+      false,
+      await this.tokenProgram()
+    )
+
+    const [vaultAccount, depositorAccount, tokenAccount] = await Promise.all([
+      this.program.account.vault.fetch(vaultPDA),
+      this.program.account.vaultDepositor.fetchNullable(vaultDepositorPDA),
+      getAccount(this.provider.connection, userTokenAccount, undefined, await this.tokenProgram()).catch(() => null),
+    ])
+    const positionValue = depositorAccount
+      ? depositorAccount.shares
+          .mul(activeShareValue(vaultAccount))
+          .div(PRECISION)
+          .toNumber()
+      : 0
+    return {
+      walletBalance: tokenAccount ? Number(tokenAccount.amount) : 0,
+      positionValue,
+      minStakeAmount: vaultAccount.minStakeAmount.toNumber(),
+    }
+  }
+
+  // run the permissionless cranks for the configured vault, see `Keeper`
+  async keeper(options: KeeperOptions): Promise<void> {
+    const [vaultPDA] = this.getVaultPDA()
+    const keeper = new Keeper(this.program, this.userWallet, vaultPDA, this.track.bind(this), options)
+    console.log(
+      `🤖 keeper for ${this.config.vaultName} (${vaultPDA.toBase58()}), ` +
+        (options.once ? 'single pass' : `every ${options.intervalSeconds}s`)
+    )
+    await keeper.run()
+  }
+
+  // live read-only view of the first of `vaultNames`, redrawn from the vault's account and log
+  // subscriptions. Keys: r refresh, v next vault, q quit. Without a TTY one frame is printed
+  async dashboard(vaultNames: string[]): Promise<void> {
+    const connection = this.provider.connection
+    const shareValues = new Map<string, number[]>()
+    let index = 0
+    let data: DashboardData | undefined
+    let events: { at: number; event: VaultEvent }[] = []
+    let accountSubscription: number | undefined
+    let logsSubscription: number | undefined
+
+    const draw = () => {
+      if (!data) {
+        return
+      }
+      data.now = Math.floor(Date.now() / 1000)
+      const lines = renderDashboard(data, process.stdout.columns || 100, process.stdout.rows || 32)
+      process.stdout.write('\x1b[2J\x1b[H' + lines.join('\n'))
+    }
+    const report = (error: any) => {
+      if (data) {
+        data.status = `refresh failed: ${error}`
+        draw()
+      }
+    }
+
+    const refresh = async () => {
+      const name = vaultNames[index]
+      const [vaultPDA] = this.getVaultPDA(name)
+      const [vault, depositors] = await Promise.all([
+        this.program.account.vault.fetch(vaultPDA),
+        this.program.account.vaultDepositor.all([{ memcmp: { offset: 8, bytes: vaultPDA.toBase58() } }]),
+      ])
+      const samples = (shareValues.get(name) ?? []).concat(shareValueSample(vault)).slice(-MAX_SHARE_VALUE_SAMPLES)
+      shareValues.set(name, samples)
+      data = {
+        vaultName: name,
+        vaultAddress: vaultPDA.toBase58(),
+        vault,
+        depositors: depositors.map((depositor) => depositor.account),
+        shareValues: samples,
+        events,
+        now: Math.floor(Date.now() / 1000),
+      }
+      draw()
+    }
+
+    const unsubscribe = async () => {
+      if (accountSubscription !== undefined) {
+        await connection.removeAccountChangeListener(accountSubscription)
+      }
+      if (logsSubscription !== undefined) {
+        await connection.removeOnLogsListener(logsSubscription)
+      }
+    }
+
+    const subscribe = async () => {
+      await unsubscribe()
+      const [vaultPDA] = this.getVaultPDA(vaultNames[index])
+      events = []
+      accountSubscription = connection.onAccountChange(vaultPDA, () => refresh().catch(report), 'confirmed')
+      logsSubscription = connection.onLogs(
+        this.program.programId,
+        (logs) => {
+          const at = Math.floor(Date.now() / 1000)
+          parseVaultEvents(this.program, logs.logs)
+            .filter((event) => event.data.vault?.toString() === vaultPDA.toBase58())
+            .forEach((event) => events.push({ at, event }))
+          events.splice(0, Math.max(events.length - MAX_DASHBOARD_EVENTS, 0))
+          draw()
+        },
+        'confirmed'
+      )
+      await refresh()
+    }
+
+    await subscribe()
+    if (!process.stdin.isTTY) {
+      await unsubscribe()
+      process.stdout.write('\n')
+      return
+    }
+
+    // countdowns move every second, the data only on notifications and key presses
+    const ticker = setInterval(draw, 1000)
+    process.stdin.setRawMode(true)
+    process.stdin.resume()
+    await new Promise<void>((resolve) => {
+      process.stdin.on('data', (key: Buffer) => {
+        const pressed = key.toString()
+        if (pressed === 'q' || pressed === '\u0003') {
+          resolve()
+        } else if (pressed === 'r') {
+          refresh().catch(report)
+        } else if (pressed === 'v' && vaultNames.length > 1) {
+          index = (index + 1) % vaultNames.length
+          subscribe().catch(report)
+        }
+      })
+    })
+    clearInterval(ticker)
+    process.stdin.setRawMode(false)
+    process.stdin.pause()
+    await unsubscribe()
+    process.stdout.write('\n')
+  }
+
+  // depositor positions with share fractions, written as CSV to `outPath`
+  async snapshotHolders(outPath: string, slot?: number, excludeOwnerFees: boolean = false): Promise<HolderSnapshot> {
+    const [vaultPDA] = this.getVaultPDA()
+    const snapshot = await snapshotHolders(this.program, vaultPDA, { slot, excludeOwnerFees })
+    fs.writeFileSync(outPath, formatHoldersCsv(snapshot))
+
+    console.log(`📸 ${snapshot.holders.length} holders of ${this.config.vaultName} written to ${outPath}`)
+    console.log(`slot used: ${snapshot.slot}`)
+    if (slot !== undefined && snapshot.slot !== slot) {
+      console.log(
+        `⚠️  requested slot ${slot}, the RPC served slot ${snapshot.slot} (RPC nodes only serve current account state)`
+      )
+    }
+    if (excludeOwnerFees) {
+      console.log('owner fee depositor excluded')
+    }
+    return snapshot
+  }
+
+  // side-by-side summary of several vaults, fetched with one getMultipleAccounts call
+  async compareVaults(
+    vaultNames: string[],
+    sortBy: SortKey = 'apy',
+    json: boolean = false
+  ): Promise<VaultSummary[]> {
+    const vaults = vaultNames.map((name) => this.getVaultPDA(name)[0])
+    const accounts = await this.program.account.vault.fetchMultiple(vaults)
+    const now = Math.floor(Date.now() / 1000)
+    const summaries = sortSummaries(
+      vaultNames.map((name, index) => summarizeVault(name, vaults[index], accounts[index], now)),
+      sortBy
+    )
+
+    if (json) {
+      console.log(JSON.stringify(summaries, null, 2))
+    } else {
+      console.log(formatCompareTable(summaries))
+    }
+    return summaries
+  }
+
   // ============ ADMIN FUNCTIONS ============
 
   // add rewards (admin only) - 50/50 split between platform and users
@@ -635,7 +1789,9 @@ export class VaultUserOperations {
         rewardSourceAccount ||
         (await getAssociatedTokenAddress(
           this.config.tokenMint,
-          this.userWallet.publicKey
+          this.userWallet.publicKey,
+          false,
+          await this.tokenProgram()
         ))
 
       const platformAccount =
@@ -666,18 +1822,26 @@ export class VaultUserOperations {
         `✅ Source account balance sufficient: ${sourceBalance / 1e9} USDC`
       )
 
-      const tx = await this.program.methods
-        .addRewards(new anchor.BN(amount))
-        .accounts({
-          vault: vaultPDA,
-          vaultTokenAccount: vaultTokenAccountPDA,
-          rewardSourceAccount: sourceAccount,
-          platformTokenAccount: platformAccount,
-          rewardSourceAuthority: this.userWallet.publicKey,
-          tokenProgram: TOKEN_PROGRAM_ID,
-        } as any)
-        .signers([this.userWallet])
-        .rpc()
+      const tokenProgram = await this.tokenProgram()
+      // writes a share value checkpoint on the way when one is due
+      const checkpoints = checkpointsPDA(this.program.programId, vaultPDA)
+      const hasCheckpoints = !!(await this.program.provider.connection.getAccountInfo(checkpoints))
+      const tx = await this.track('addRewards', () =>
+        this.program.methods
+          .addRewards(new anchor.BN(amount))
+          .accounts({
+            vault: vaultPDA,
+            vaultTokenAccount: vaultTokenAccountPDA,
+            rewardSourceAccount: sourceAccount,
+            platformTokenAccount: platformAccount,
+            tokenMint: this.config.tokenMint,
+            rewardSourceAuthority: this.userWallet.publicKey,
+            vaultCheckpoints: hasCheckpoints ? checkpoints : null,
+            tokenProgram,
+          } as any)
+          .signers([this.userWallet])
+            .rpc()
+      )
 
       console.log('✅ Rewards added successfully!')
       console.log(`Transaction: ${tx}`)
@@ -692,6 +1856,39 @@ export class VaultUserOperations {
     }
   }
 
+  // distribute `amount` whole tokens of the secondary reward mint to the active shares, from this
+  // wallet's associated token account. Authorized like add_rewards
+  async addSecondaryRewards(amount: number): Promise<string> {
+    try {
+      const [vaultPDA] = this.getVaultPDA()
+      const { mint, tokenProgram, decimals } = await this.secondaryRewardMint(vaultPDA)
+      const sourceAccount = await getAssociatedTokenAddress(mint, this.userWallet.publicKey, false, tokenProgram)
+      const baseUnits = new anchor.BN(Math.round(amount * 10 ** decimals).toString())
+
+      const tx = await this.track('addSecondaryRewards', () =>
+        this.program.methods
+          .addSecondaryRewards(baseUnits)
+          .accounts({
+            vault: vaultPDA,
+            secondaryRewardTokenAccount: secondaryRewardTokenAccountPDA(this.program.programId, vaultPDA),
+            rewardSourceAccount: sourceAccount,
+            secondaryRewardMint: mint,
+            rewardSourceAuthority: this.userWallet.publicKey,
+            tokenProgram,
+          } as any)
+          .signers([this.userWallet])
+          .rpc()
+      )
+
+      console.log('✅ Secondary rewards added successfully!')
+      console.log(`Transaction: ${tx}`)
+      return tx
+    } catch (error) {
+      console.error('❌ Add secondary rewards failed:', error)
+      throw error
+    }
+  }
+
   // Helper function to get default platform token account
   private async getDefaultPlatformTokenAccount(): Promise<PublicKey> {
     try {
@@ -702,7 +1899,9 @@ export class VaultUserOperations {
       // Return the ATA for the platform account
       return await getAssociatedTokenAddress(
         this.config.tokenMint,
-        vaultAccount.platformAccount
+        vaultAccount.platformAccount,
+        false,
+        await this.tokenProgram()
       )
     } catch (error) {
       console.error('❌ Failed to get platform token account:', error)
@@ -722,7 +1921,9 @@ export class VaultUserOperations {
 
       const tokenAccountInfo = await getAccount(
         this.provider.connection,
-        tokenAccount
+        tokenAccount,
+        undefined,
+        await this.tokenProgram()
       )
       return Number(tokenAccountInfo.amount)
     } catch (error) {
@@ -744,7 +1945,9 @@ export class VaultUserOperations {
       // Check source balance
       const sourceAccount = await getAssociatedTokenAddress(
         this.config.tokenMint,
-        this.userWallet.publicKey
+        this.userWallet.publicKey,
+        false,
+        await this.tokenProgram()
       )
       const sourceBalance = await this.checkTokenBalance(sourceAccount)
 
@@ -817,13 +2020,13 @@ export class VaultUserOperations {
         // This assumes all current assets were there from the beginning (simplified)
         const avgAssets = totalAssets // Approximation: current total as average
         const totalYieldRate = totalRewards / avgAssets
-        const dailyYield = totalYieldRate / vaultAgeDays
         
         // APR (simple interest): daily yield × 365
-        apr = dailyYield * 365 * 100
-        
         // APY (compound interest): (1 + daily yield)^365 - 1
-        apy = (Math.pow(1 + dailyYield, 365) - 1) * 100
+        const estimate = estimateYield(vaultAccount, currentTime)
+        const dailyYield = estimate.dailyYield
+        apr = estimate.apr ?? 0
+        apy = estimate.apy ?? 0
         
         console.log(`\n📊 Yield Calculations:`)
         console.log(`   Current total assets: ${totalAssets / 1e9} USDC`)
@@ -855,6 +2058,27 @@ export class VaultUserOperations {
         console.log(`   Vault age: ${vaultAgeDays.toFixed(2)} days`)
       }
       
+      // measured from share value checkpoints when the vault has them, preferred over the estimate
+      const periods = [...new Set([7, 30, periodDays])].sort((a, b) => a - b)
+      const trailing = await Promise.all(periods.map((days) => this.getTrailingApy(days)))
+      if (trailing.some((result) => result)) {
+        console.log(`\n📐 Trailing yield from on-chain checkpoints:`)
+        for (const result of trailing) {
+          if (!result) {
+            continue
+          }
+          const spanDays = (result.to.timestamp - result.from.timestamp) / 86400
+          console.log(
+            `   ${result.days} days: APR ${result.apr.toFixed(2)}%, APY ${result.apy.toFixed(2)}% (measured over ${spanDays.toFixed(1)} days)`
+          )
+        }
+      }
+      const measured = trailing[periods.indexOf(periodDays)]
+      if (measured) {
+        apr = measured.apr
+        apy = measured.apy
+      }
+      
       console.log(`\n💡 Note: These calculations are based on historical performance and do not guarantee future returns.`)
       
       return { apy, apr }
@@ -905,6 +2129,9 @@ export class VaultUserOperations {
           const shares = depositor.shares.toNumber()
           const totalStaked = depositor.totalStaked.toNumber()
           const totalUnstaked = depositor.totalUnstaked.toNumber()
+          // Active shares plus the pending request, 0 on positions opened before tracking
+          const costBasis = depositor.costBasis.add(depositor.unstakeCostBasis).toNumber()
+          const realizedPnl = depositor.realizedPnl.toNumber()
           
           // Calculate current asset value using same logic as getUserAssetValue
           let currentValue = 0
@@ -920,6 +2147,8 @@ export class VaultUserOperations {
             shares,
             totalStaked,
             totalUnstaked,
+            costBasis,
+            realizedPnl,
             currentValue,
             sharePercentage,
             hasUnstakeRequest: depositor.unstakeRequest.shares.toNumber() > 0,
@@ -937,8 +2166,8 @@ export class VaultUserOperations {
       console.log(`   Active shares: ${activeShares}`)
       
       console.log(`\n📋 Detailed Staker Information:`)
-      console.log(`${'Rank'.padEnd(4)} ${'Address'.padEnd(44)} ${'Shares'.padEnd(12)} ${'Current Value'.padEnd(15)} ${'Share %'.padEnd(8)} ${'Total Staked'.padEnd(15)} ${'Total Unstaked'.padEnd(17)} ${'Pending Unstake'}`)
-      console.log('='.repeat(140))
+      console.log(`${'Rank'.padEnd(4)} ${'Address'.padEnd(44)} ${'Shares'.padEnd(12)} ${'Current Value'.padEnd(15)} ${'Share %'.padEnd(8)} ${'Total Staked'.padEnd(15)} ${'Total Unstaked'.padEnd(17)} ${'Cost Basis'.padEnd(15)} ${'Realized PnL'.padEnd(16)} ${'Pending Unstake'}`)
+      console.log('='.repeat(172))
       
       stakersData.forEach((staker, index) => {
         const rank = (index + 1).toString().padEnd(4)
@@ -948,23 +2177,38 @@ export class VaultUserOperations {
         const sharePercent = `${staker.sharePercentage.toFixed(2)}%`.padEnd(8)
         const totalStaked = `${(staker.totalStaked / 1e9).toFixed(6)} USDC`.padEnd(15)
         const totalUnstaked = `${(staker.totalUnstaked / 1e9).toFixed(6)} USDC`.padEnd(17)
+        const costBasis = `${(staker.costBasis / 1e9).toFixed(6)} USDC`.padEnd(15)
+        const realizedPnl = `${staker.realizedPnl >= 0 ? '+' : ''}${(staker.realizedPnl / 1e9).toFixed(6)} USDC`.padEnd(16)
         const pendingUnstake = staker.hasUnstakeRequest ? 
           `${staker.unstakeRequestShares} shares` : 
           'None'
         
-        console.log(`${rank} ${address} ${shares} ${currentValue} ${sharePercent} ${totalStaked} ${totalUnstaked} ${pendingUnstake}`)
+        console.log(`${rank} ${address} ${shares} ${currentValue} ${sharePercent} ${totalStaked} ${totalUnstaked} ${costBasis} ${realizedPnl} ${pendingUnstake}`)
       })
       
       // Summary statistics
       const totalCurrentValue = stakersData.reduce((sum, staker) => sum + staker.currentValue, 0)
       const totalStakedAmount = stakersData.reduce((sum, staker) => sum + staker.totalStaked, 0)
       const totalUnstakedAmount = stakersData.reduce((sum, staker) => sum + staker.totalUnstaked, 0)
+      const totalCostBasis = stakersData.reduce((sum, staker) => sum + staker.costBasis, 0)
+      const totalRealizedPnl = stakersData.reduce((sum, staker) => sum + staker.realizedPnl, 0)
       const stakersWithPendingUnstake = stakersData.filter(s => s.hasUnstakeRequest).length
       
       console.log('\n📈 Summary Statistics:')
       console.log(`   Total current value: ${totalCurrentValue / 1e9} USDC`)
+      const quote = await this.getPriceQuote()
+      if (quote) {
+        console.log(`   Total current value (USD): ${formatUsd(new anchor.BN(totalCurrentValue.toString()), quote)}`)
+        stakersData.slice(0, 10).forEach((staker, index) => {
+          console.log(
+            `   #${index + 1} ${staker.address.toBase58()}: ${formatUsd(new anchor.BN(staker.currentValue.toString()), quote)}`
+          )
+        })
+      }
       console.log(`   Total staked amount: ${totalStakedAmount / 1e9} USDC`)
       console.log(`   Total unstaked amount: ${totalUnstakedAmount / 1e9} USDC`)
+      console.log(`   Total cost basis: ${totalCostBasis / 1e9} USDC`)
+      console.log(`   Total realized PnL: ${totalRealizedPnl / 1e9} USDC`)
       console.log(`   Stakers with pending unstake: ${stakersWithPendingUnstake}`)
       console.log(`   Average stake per user: ${(totalCurrentValue / stakersData.length) / 1e9} USDC`)
       
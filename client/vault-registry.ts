@@ -0,0 +1,136 @@
+import { Program } from '@coral-xyz/anchor'
+import { PublicKey } from '@solana/web3.js'
+
+// how many registry indexes are probed per owner and mint
+export const DEFAULT_MAX_REGISTRY_INDEX = 16
+// vaults one `VaultRegistry` page lists, MAX_REGISTRY_PAGE_VAULTS on chain
+export const MAX_REGISTRY_PAGE_VAULTS = 256
+
+export interface RegisteredVault {
+  index: number
+  entry: PublicKey
+  vault: PublicKey
+  name: string
+}
+
+export function getRegistryEntryPDA(
+  programId: PublicKey,
+  owner: PublicKey,
+  mint: PublicKey,
+  index: number
+): [PublicKey, number] {
+  const indexBuffer = Buffer.alloc(2)
+  indexBuffer.writeUInt16LE(index)
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from('vault_by_mint'), owner.toBuffer(), mint.toBuffer(), indexBuffer],
+    programId
+  )
+}
+
+// list the vaults `owner` registered for `mint`, with one getMultipleAccounts call per lookup
+export async function findVaults(
+  program: Program<any>,
+  owner: PublicKey,
+  mint: PublicKey,
+  maxIndex: number = DEFAULT_MAX_REGISTRY_INDEX
+): Promise<RegisteredVault[]> {
+  const entries = Array.from({ length: maxIndex }, (_, index) => ({
+    index,
+    entry: getRegistryEntryPDA(program.programId, owner, mint, index)[0],
+  }))
+  const entryAccounts = await program.account.vaultRegistryEntry.fetchMultiple(
+    entries.map((e) => e.entry)
+  )
+
+  const found = entries
+    .map((e, i) => ({ ...e, account: entryAccounts[i] as any }))
+    .filter((e) => e.account !== null)
+  // skip entries whose vault was closed without closing the entry
+  const vaultAccounts = await program.account.vault.fetchMultiple(
+    found.map((e) => e.account.vault)
+  )
+
+  return found
+    .map((e, i) => ({
+      index: e.index,
+      entry: e.entry,
+      vault: e.account.vault as PublicKey,
+      name: vaultAccounts[i]
+        ? Buffer.from((vaultAccounts[i] as any).name).toString().replace(/\0/g, '')
+        : '',
+    }))
+    .filter((v, i) => vaultAccounts[i] !== null)
+}
+
+// first index with no registry entry, to pass as `registryIndex` when creating another vault
+export async function nextRegistryIndex(
+  program: Program<any>,
+  owner: PublicKey,
+  mint: PublicKey,
+  maxIndex: number = DEFAULT_MAX_REGISTRY_INDEX
+): Promise<number> {
+  const entries = Array.from(
+    { length: maxIndex },
+    (_, index) => getRegistryEntryPDA(program.programId, owner, mint, index)[0]
+  )
+  const accounts = await program.provider.connection.getMultipleAccountsInfo(entries)
+  const index = accounts.findIndex((account) => account === null)
+  if (index === -1) {
+    throw new Error(`all ${maxIndex} registry indexes are in use for this owner and mint`)
+  }
+  return index
+}
+
+export interface ListedVault {
+  page: number
+  vault: PublicKey
+  name: string
+}
+
+export function getVaultRegistryPDA(programId: PublicKey, page: number): [PublicKey, number] {
+  const pageBuffer = Buffer.alloc(2)
+  pageBuffer.writeUInt16LE(page)
+  return PublicKey.findProgramAddressSync([Buffer.from('vault_registry'), pageBuffer], programId)
+}
+
+// every vault listed in the on-chain registry, reading pages until the first missing one
+export async function listVaults(program: Program<any>): Promise<ListedVault[]> {
+  const listed: { page: number; vault: PublicKey }[] = []
+  for (let page = 0; ; page++) {
+    const registry = (await program.account.vaultRegistry.fetchNullable(
+      getVaultRegistryPDA(program.programId, page)[0]
+    )) as any
+    if (registry === null) {
+      break
+    }
+    for (const vault of registry.vaults as PublicKey[]) {
+      listed.push({ page, vault })
+    }
+  }
+
+  const vaultAccounts = await program.account.vault.fetchMultiple(listed.map((entry) => entry.vault))
+  return listed
+    .map((entry, i) => ({
+      ...entry,
+      name: vaultAccounts[i]
+        ? Buffer.from((vaultAccounts[i] as any).name).toString().replace(/\0/g, '')
+        : '',
+    }))
+    .filter((_, i) => vaultAccounts[i] !== null)
+}
+
+// first registry page with room for another vault, and whether it still has to be created
+// with initialize_registry
+export async function openRegistryPage(program: Program<any>): Promise<{ page: number; exists: boolean }> {
+  for (let page = 0; ; page++) {
+    const registry = (await program.account.vaultRegistry.fetchNullable(
+      getVaultRegistryPDA(program.programId, page)[0]
+    )) as any
+    if (registry === null) {
+      return { page, exists: false }
+    }
+    if (registry.vaults.length < MAX_REGISTRY_PAGE_VAULTS) {
+      return { page, exists: true }
+    }
+  }
+}
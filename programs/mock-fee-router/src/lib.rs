@@ -0,0 +1,71 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use simple_vault::cpi::accounts::AddRewards;
+use simple_vault::program::SimpleVault;
+
+declare_id!("3D8GajM3rr9LnanZu1EiDJLhFP7gVxkAn5fAjvqhTRuf");
+
+/// Test fixture standing in for a protocol fee router. Fees collect in a token account owned
+/// by one of its PDAs and are pushed into a vault by `add_rewards`, with the PDA signing
+#[program]
+pub mod mock_fee_router {
+    use super::*;
+
+    /// Push `amount` of the fees held by the `[b"fee_router", seed]` PDA into the vault
+    pub fn push_rewards(ctx: Context<PushRewards>, seed: Vec<u8>, amount: u64) -> Result<()> {
+        let bump = [ctx.bumps.router_authority];
+        let signer_seeds: &[&[&[u8]]] = &[&[b"fee_router", seed.as_ref(), &bump]];
+        
+        let cpi_accounts = AddRewards {
+            vault: ctx.accounts.vault.to_account_info(),
+            vault_token_account: ctx.accounts.vault_token_account.to_account_info(),
+            reward_source_account: ctx.accounts.fee_account.to_account_info(),
+            platform_token_account: ctx.accounts.platform_token_account.to_account_info(),
+            token_mint: ctx.accounts.token_mint.to_account_info(),
+            reward_source_authority: ctx.accounts.router_authority.to_account_info(),
+            reward_source: ctx.accounts.reward_source.as_ref().map(|source| source.to_account_info()),
+            vault_checkpoints: None,
+            token_program: ctx.accounts.token_program.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.vault_program.to_account_info(),
+            cpi_accounts,
+            signer_seeds,
+        );
+        simple_vault::cpi::add_rewards(cpi_ctx, amount)
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(seed: Vec<u8>)]
+pub struct PushRewards<'info> {
+    /// CHECK: validated by the vault program
+    #[account(mut)]
+    pub vault: UncheckedAccount<'info>,
+    
+    /// CHECK: validated by the vault program
+    #[account(mut)]
+    pub vault_token_account: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        constraint = fee_account.owner == router_authority.key(),
+    )]
+    pub fee_account: InterfaceAccount<'info, TokenAccount>,
+    
+    /// CHECK: validated by the vault program
+    #[account(mut)]
+    pub platform_token_account: UncheckedAccount<'info>,
+    
+    pub token_mint: InterfaceAccount<'info, Mint>,
+    
+    /// CHECK: signs the reward transfer, holds no data
+    #[account(seeds = [b"fee_router", seed.as_ref()], bump)]
+    pub router_authority: UncheckedAccount<'info>,
+    
+    /// CHECK: the vault's registration of `router_authority`, validated by the vault program
+    pub reward_source: Option<UncheckedAccount<'info>>,
+    
+    pub vault_program: Program<'info, SimpleVault>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
@@ -14,7 +14,90 @@ pub const MAX_UNSTAKE_LOCKUP_DAYS: i64 = 90;
 pub const MIN_UNSTAKE_LOCKUP_MINUTES: i64 = 10; // Changed from 1 day to 10 minutes
 pub const DEFAULT_UNSTAKE_LOCKUP: i64 = FOURTEEN_DAYS;
 
+/// Decommissioning limits
+pub const MIN_DECOMMISSION_NOTICE: i64 = ONE_WEEK;
+pub const MAX_DECOMMISSION_NOTICE: i64 = MAX_UNSTAKE_LOCKUP_DAYS * ONE_DAY;
+pub const DECOMMISSION_FORCE_TIMEOUT: i64 = ONE_DAY * 180; // after notice ends, before forced distribution
+
+/// A vault whose every share is pending and that saw no stake, request, unstake or reward for
+/// this long lets a new stake restart it at one share per token, `Vault::last_activity`
+pub const MAX_INACTIVE_PERIOD: i64 = ONE_WEEK;
+
+/// Account layout versions
+/// 0 = legacy vault created before versioning, 1 = decommissioning, precision floor counter and version.
+/// Fields added since read as zero on accounts `migrate_vault` grew, which every instruction
+/// treats as unset; a field needing another default bumps the version and `Vault::migrate_layout`
+pub const VAULT_VERSION: u8 = 1;
+
+/// Depositor layout versions
+/// 0 = `VaultDepositor::LEN` bytes, 2 = grown to `VaultDepositor::LEN_V2`
+pub const DEPOSITOR_VERSION: u8 = 2;
+/// Bytes the v2 depositor layout adds after the v1 fields
+pub const V2_EXTENSION_LEN: usize = 128;
+
 /// Fee constants (in basis points)
-pub const MAX_MANAGEMENT_FEE: u64 = 10000; // 100% (for platform share in add_rewards)
-pub const DEFAULT_MANAGEMENT_FEE: u64 = 5000; // 50% (default platform share in add_rewards)
-pub const BASIS_POINTS_PRECISION: u64 = 10000;
\ No newline at end of file
+/// The platform's cut of each add_rewards, `Vault::platform_share_bps`
+pub const MAX_PLATFORM_SHARE_BPS: u64 = 10000; // 100% of each reward to the platform
+pub const DEFAULT_PLATFORM_SHARE_BPS: u64 = 5000; // 50/50 split of each reward
+/// Annual fee on assets, `Vault::management_fee_bps`. Stored only, nothing accrues it yet
+pub const MAX_MANAGEMENT_FEE_BPS: u64 = 500; // 5% a year
+/// Largest move of `platform_share_bps` in one update, and the time before the next may move it
+pub const MAX_PLATFORM_SHARE_CHANGE_BPS: u64 = 500;
+pub const MIN_PLATFORM_SHARE_CHANGE_INTERVAL: i64 = ONE_WEEK;
+pub const BASIS_POINTS_PRECISION: u64 = 10000;
+pub const MAX_INSTANT_UNSTAKE_FEE: u64 = 5000; // 50% (penalty cap for instant_unstake)
+
+/// Operation bits of `Vault::paused_operations`, a set bit pauses that instruction family
+pub const PAUSE_STAKE: u32 = 1 << 0; // stake, stake_for
+pub const PAUSE_REQUEST_UNSTAKE: u32 = 1 << 1; // request_unstake, instant_unstake
+pub const PAUSE_UNSTAKE: u32 = 1 << 2; // unstake, instant_unstake, withdraw_owner_shares, claim_secondary_rewards
+pub const PAUSE_CANCEL: u32 = 1 << 3; // cancel_unstake_request
+pub const PAUSE_ADD_REWARDS: u32 = 1 << 4;
+pub const PAUSE_INIT_DEPOSITOR: u32 = 1 << 5; // initialize_vault_depositor, stake_for on a new depositor
+pub const PAUSE_FEE_ACCRUAL: u32 = 1 << 6; // add_rewards keeps the platform share in the vault
+pub const PAUSE_TRANSFER: u32 = 1 << 7; // transfer_shares
+pub const PAUSE_ALL: u32 = (1 << 8) - 1;
+/// What `withdrawals_paused` stops; cancelling a request keeps working
+pub const PAUSE_WITHDRAWALS: u32 = PAUSE_REQUEST_UNSTAKE | PAUSE_UNSTAKE;
+/// What the legacy `is_paused` flag stops: everything except the unstake flow
+pub const PAUSE_LEGACY_DEPOSITS: u32 = PAUSE_ALL & !(PAUSE_WITHDRAWALS | PAUSE_CANCEL);
+/// What `reconcile` pauses on a shortfall: no new money and no exits that would leave the
+/// shortfall to the remaining stakers, until the owner unpauses
+pub const PAUSE_ON_SHORTFALL: u32 = PAUSE_STAKE | PAUSE_INIT_DEPOSITOR | PAUSE_WITHDRAWALS;
+
+/// MEV cooldown between a stake and the depositor's next exit
+/// Vaults store 0 until the owner sets `stake_cooldown_seconds`, which reads as the default
+pub const DEFAULT_STAKE_COOLDOWN_SECONDS: i64 = 300;
+pub const MAX_STAKE_COOLDOWN_SECONDS: i64 = ONE_DAY;
+
+/// Longest period an add_rewards can be released over (0 = released instantly)
+pub const MAX_REWARD_VESTING_PERIOD: i64 = 30 * ONE_DAY;
+
+/// Highest performance fee, charged on the share value's gain above the high-water mark
+pub const MAX_PERFORMANCE_FEE_BPS: u64 = 5000; // 50%
+
+/// Highest exit fee withheld from a matured unstake for the remaining stakers
+pub const MAX_WITHDRAWAL_FEE_BPS: u64 = 200; // 2%
+
+/// Highest entry fee withheld from each stake, `Vault::deposit_fee_bps`
+pub const MAX_DEPOSIT_FEE_BPS: u64 = 100; // 1%
+
+/// Shares the first stake into an empty vault mints to nobody, `Vault::dead_shares`. They
+/// can never be redeemed, so a donation that inflates the share price is mostly theirs
+pub const DEAD_SHARES: u64 = 1_000;
+
+/// Largest power of ten one automatic rebase divides the shares by. A vault further out
+/// of range rebases again on its next operation, or at once with the owner's `force_rebase`
+pub const MAX_REBASE_EXPO: u32 = 6;
+
+/// Bounds of `Vault::unstake_request_expiry` when set, counted from the request's maturity
+pub const MIN_UNSTAKE_REQUEST_EXPIRY: i64 = 7 * ONE_DAY;
+pub const MAX_UNSTAKE_REQUEST_EXPIRY: i64 = 365 * ONE_DAY;
+
+/// Longest delay the owner can put between proposing and committing a config update
+pub const MAX_CONFIG_TIMELOCK_SECONDS: i64 = 30 * ONE_DAY;
+
+/// Spacing of `VaultCheckpoints` entries, one a day unless the owner sets `checkpoint_interval`
+pub const DEFAULT_CHECKPOINT_INTERVAL: i64 = ONE_DAY;
+pub const MIN_CHECKPOINT_INTERVAL: i64 = ONE_HOUR;
+pub const MAX_CHECKPOINT_INTERVAL: i64 = ONE_WEEK;
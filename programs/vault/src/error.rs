@@ -64,6 +64,172 @@ pub enum VaultError {
     
     #[msg("Unauthorized reward source")]
     UnauthorizedRewardSource,
+    
+    #[msg("Protocol stats cannot track more token mints")]
+    ProtocolStatsFull,
+    
+    #[msg("Vault is being decommissioned")]
+    VaultDecommissioning,
+    
+    #[msg("Vault is not being decommissioned")]
+    VaultNotDecommissioning,
+    
+    #[msg("Forced distribution timeout has not elapsed")]
+    DecommissionTimeoutNotReached,
+    
+    #[msg("Vault still has outstanding shares")]
+    VaultNotEmpty,
+    
+    #[msg("Vault token account is frozen by the mint's freeze authority")]
+    TokenAccountFrozen,
+    
+    #[msg("Instant unstake is disabled for this vault")]
+    InstantUnstakeDisabled,
+    
+    #[msg("Depositor must sync the latest rebase first")]
+    RebaseSyncRequired,
+    
+    #[msg("Active share value rounds to zero, the vault's assets are too small to price its shares")]
+    ShareValueUnderflow,
+    
+    #[msg("No ownership transfer is pending")]
+    NoPendingOwner,
+    
+    #[msg("Legacy owner_shares must be migrated into the owner fee depositor first")]
+    OwnerSharesNotMigrated,
+    
+    #[msg("Depositor synced a rebase before last_shares_base existed, migrate it first")]
+    DepositorNotMigrated,
+    
+    #[msg("u64::MAX is not an amount, pass the exact number of tokens")]
+    AmbiguousMaxAmount,
+    
+    #[msg("Withdrawals are paused")]
+    WithdrawalsPaused,
+    
+    #[msg("Account is not this program's ProgramData")]
+    InvalidProgramData,
+    
+    #[msg("Wallet is not on the vault's allowlist")]
+    DepositorNotAllowed,
+    
+    #[msg("Stake would take the depositor's position above the per-depositor cap")]
+    DepositorCapExceeded,
+    
+    #[msg("This operation is paused on the vault")]
+    OperationPaused,
+    
+    #[msg("Tokenized vault requires the share mint, the share token account and the token program")]
+    ShareTokenAccountsRequired,
+    
+    #[msg("Share mint or share token account does not belong to the vault")]
+    InvalidShareMint,
+    
+    #[msg("Rewards must be signed by a registered program reward source")]
+    RewardSourceNotRegistered,
+    
+    #[msg("Seeds do not derive a program address")]
+    InvalidRewardSourceSeeds,
+    
+    #[msg("Shares cannot be transferred to the sending depositor")]
+    SelfTransfer,
+    
+    #[msg("The token mint has a Token-2022 extension the vault does not support")]
+    UnsupportedMintExtension,
+    
+    #[msg("Unstake request has not expired")]
+    UnstakeRequestNotExpired,
+    
+    #[msg("This config change must go through propose_config_update and its timelock")]
+    ConfigUpdateTimelocked,
+    
+    #[msg("The config update timelock has not elapsed")]
+    ConfigTimelockNotElapsed,
+    
+    #[msg("Unstake amount is below the vault minimum, unstake the whole position instead")]
+    UnstakeBelowMinimum,
+    
+    #[msg("Vault account was written by a newer program version")]
+    UnsupportedVaultVersion,
+    
+    #[msg("The checkpoint interval since the latest checkpoint has not passed")]
+    CheckpointNotDue,
+    
+    #[msg("Vault has no secondary reward mint")]
+    SecondaryRewardsNotEnabled,
+    
+    #[msg("Secondary reward mint is already set")]
+    SecondaryRewardMintAlreadySet,
+    
+    #[msg("No secondary rewards to claim")]
+    NoSecondaryRewards,
+    
+    #[msg("The platform account changes through propose_platform_account and accept_platform_account")]
+    PlatformAccountNeedsAcceptance,
+    
+    #[msg("No platform account change is pending")]
+    NoPendingPlatformAccount,
+    
+    #[msg("The current platform account must co-sign this proposal")]
+    PlatformCosignRequired,
+    
+    #[msg("Vault registry page is full, register on the next page")]
+    RegistryPageFull,
+    
+    #[msg("Vault is not listed on this registry page")]
+    VaultNotRegistered,
+    
+    #[msg("Only closed vaults can be removed from the registry")]
+    VaultNotClosed,
+    
+    #[msg("Fee exceeds the cap set in the protocol config")]
+    ProtocolFeeCapExceeded,
+    
+    #[msg("No protocol admin transfer is pending")]
+    NoPendingProtocolAdmin,
+    
+    #[msg("Vault did not opt into protocol fee overrides")]
+    NotProtocolGoverned,
+    
+    #[msg("A fee override can only lower a fee")]
+    FeeOverrideRaisesFee,
+    
+    #[msg("Amount is worth less than one share")]
+    ZeroShares,
+    
+    #[msg("Slippage exceeded: the result is worse than the bound passed")]
+    SlippageExceeded,
+    
+    // No longer returned, `Vault::verify_solvency` checks Invariant 1 against the token balance
+    #[msg("Invariant: total_assets != available_assets + reserved_assets")]
+    InvariantTotalAssets,
+    
+    #[msg("Invariant: total_shares != active_shares + pending_unstake_shares")]
+    InvariantTotalShares,
+    
+    #[msg("Invariant: reserved_assets exceed total_assets")]
+    InvariantReservedAssets,
+    
+    #[msg("Invariant: pending_unstake_shares exceed total_shares")]
+    InvariantPendingShares,
+    
+    #[msg("Invariant: legacy owner_shares exceed active shares")]
+    InvariantOwnerShares,
+    
+    #[msg("Invariant: decommission timestamps are inconsistent")]
+    InvariantDecommissionWindow,
+    
+    #[msg("Invariant: share mint supply != active shares")]
+    InvariantShareSupply,
+    
+    #[msg("Invariant: tokenized vault shares were rebased")]
+    InvariantTokenizedRebase,
+    
+    #[msg("Invariant: unvested rewards have no vesting window")]
+    InvariantVestingWindow,
+    
+    #[msg("Invariant: an unstake request holds more shares than are pending")]
+    InvariantRequestShares,
 }
 
 pub type VaultResult<T> = std::result::Result<T, VaultError>;
\ No newline at end of file
@@ -0,0 +1,705 @@
+use anchor_lang::prelude::*;
+use crate::state::Vault;
+
+/// Emitted when tokens are staked into the vault
+#[event]
+pub struct StakeEvent {
+    pub vault: Pubkey,
+    pub authority: Pubkey,
+    /// Gross amount received by the vault, `deposit_fee` included
+    pub amount: u64,
+    /// Part of `amount` withheld as deposit fee
+    pub deposit_fee: u64,
+    /// Part of `amount` the shares were minted for
+    pub net_amount: u64,
+    pub shares: u64,
+    /// Active share value after the instruction (scaled by PRECISION)
+    pub active_share_value: u128,
+    /// Opaque caller reference echoed back for reconciliation (all zeros = not provided)
+    pub client_id: [u8; 16],
+    pub timestamp: i64,
+}
+
+/// Emitted when a payer stakes into another wallet's position
+#[event]
+pub struct StakeForEvent {
+    pub vault: Pubkey,
+    pub payer: Pubkey,
+    pub beneficiary: Pubkey,
+    /// Gross amount received by the vault, `deposit_fee` included
+    pub amount: u64,
+    /// Part of `amount` withheld as deposit fee
+    pub deposit_fee: u64,
+    /// Part of `amount` the shares were minted for
+    pub net_amount: u64,
+    pub shares: u64,
+    /// Active share value after the instruction (scaled by PRECISION)
+    pub active_share_value: u128,
+    /// Opaque caller reference echoed back for reconciliation (all zeros = not provided)
+    pub client_id: [u8; 16],
+    pub timestamp: i64,
+}
+
+/// Emitted when the owner redeems fee shares from the owner fee depositor
+#[event]
+pub struct OwnerSharesWithdrawnEvent {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub shares: u64,
+    /// Tokens paid to the owner
+    pub amount: u64,
+    /// Fee shares left in the owner fee depositor
+    pub remaining_shares: u64,
+    /// Active share value after the instruction (scaled by PRECISION)
+    pub active_share_value: u128,
+    pub timestamp: i64,
+}
+
+/// Emitted when a depositor exits immediately, paying the instant unstake penalty
+#[event]
+pub struct InstantUnstakeEvent {
+    pub vault: Pubkey,
+    pub authority: Pubkey,
+    /// Tokens paid out, after the penalty
+    pub amount: u64,
+    /// Tokens withheld and left in the vault for the remaining stakers
+    pub penalty: u64,
+    pub shares: u64,
+    /// Active share value after the instruction (scaled by PRECISION)
+    pub active_share_value: u128,
+    /// Opaque caller reference echoed back for reconciliation (all zeros = not provided)
+    pub client_id: [u8; 16],
+    pub timestamp: i64,
+}
+
+/// Emitted when an unstake request is created
+#[event]
+pub struct UnstakeRequestedEvent {
+    pub vault: Pubkey,
+    pub authority: Pubkey,
+    pub amount: u64,
+    pub shares: u64,
+    /// Active share value after the instruction (scaled by PRECISION)
+    pub active_share_value: u128,
+    pub client_id: [u8; 16],
+    pub timestamp: i64,
+}
+
+/// Emitted when a matured unstake request is paid out
+#[event]
+pub struct UnstakeExecutedEvent {
+    pub vault: Pubkey,
+    pub authority: Pubkey,
+    /// Tokens paid out, the frozen amount less the withdrawal fee
+    pub amount: u64,
+    /// Tokens withheld from the frozen amount and left in the vault for the remaining stakers
+    pub withdrawal_fee: u64,
+    /// Part of `amount` the mint's transfer fee withheld on its way to the depositor
+    pub transfer_fee: u64,
+    pub shares: u64,
+    /// Active share value after the instruction (scaled by PRECISION)
+    pub active_share_value: u128,
+    pub client_id: [u8; 16],
+    pub timestamp: i64,
+}
+
+/// Emitted when a pending unstake request is cancelled
+#[event]
+pub struct UnstakeCancelledEvent {
+    pub vault: Pubkey,
+    pub authority: Pubkey,
+    /// Frozen amount returned to the active pool
+    pub amount: u64,
+    /// Active shares restored to the depositor
+    pub shares: u64,
+    /// Active share value after the instruction (scaled by PRECISION)
+    pub active_share_value: u128,
+    pub timestamp: i64,
+}
+
+/// Emitted when an uncollected matured unstake request is restored to active shares
+#[event]
+pub struct UnstakeRequestExpiredEvent {
+    pub vault: Pubkey,
+    pub authority: Pubkey,
+    /// Frozen amount returned to the active pool
+    pub amount: u64,
+    /// Active shares restored to the depositor
+    pub shares: u64,
+    /// When the request matured
+    pub matured_at: i64,
+    /// Active share value after the instruction (scaled by PRECISION)
+    pub active_share_value: u128,
+    pub timestamp: i64,
+}
+
+/// Emitted when rewards are added and split between depositors and the platform
+#[event]
+pub struct RewardsAddedEvent {
+    pub vault: Pubkey,
+    /// Authority of the reward source account
+    pub authority: Pubkey,
+    pub amount: u64,
+    /// Part compounded into the vault
+    pub vault_share: u64,
+    /// Part sent to the platform account
+    pub platform_share: u64,
+    /// Active share value after the instruction (scaled by PRECISION)
+    pub active_share_value: u128,
+    pub timestamp: i64,
+}
+
+/// Emitted when the owner updates the vault configuration
+#[event]
+pub struct ConfigUpdatedEvent {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    /// `Vault::config_digest` before the update
+    pub old_digest: [u8; 32],
+    /// `Vault::config_digest` after the update
+    pub new_digest: [u8; 32],
+    pub timestamp: i64,
+}
+
+/// Emitted by a config update that changes which operations are paused
+#[event]
+pub struct PausedOperationsUpdatedEvent {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    /// `Vault::paused_mask` before the update
+    pub old_mask: u32,
+    /// `Vault::paused_mask` after the update
+    pub new_mask: u32,
+    pub timestamp: i64,
+}
+
+/// Emitted when the owner starts retiring a vault
+#[event]
+pub struct DecommissionStartedEvent {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    /// After this time the unstake lockup is waived for everyone
+    pub notice_ends_at: i64,
+    /// After this time unresponsive depositors may be paid out by the forced distribution crank
+    pub force_distribution_at: i64,
+    pub timestamp: i64,
+}
+
+/// Emitted when a depositor is paid out by the forced distribution crank
+#[event]
+pub struct ForcedDistributionEvent {
+    pub vault: Pubkey,
+    pub authority: Pubkey,
+    pub amount: u64,
+    pub shares: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when a decommissioned vault's accounts are closed
+#[event]
+pub struct VaultDecommissionedEvent {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    /// Leftover dust swept to the owner
+    pub swept_amount: u64,
+    /// Account that received the rent of the closed accounts
+    pub rent_refund_destination: Pubkey,
+    /// Lamports returned from the vault, its token account and its registry entry
+    pub rent_refunded: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when a rebase rounds a depositor's shares up to the 1-share precision floor
+#[event]
+pub struct PrecisionFloorApplied {
+    pub vault: Pubkey,
+    pub authority: Pubkey,
+    /// Shares the rebase division produced
+    pub computed_shares: u64,
+    /// Shares granted by the floor
+    pub granted_shares: u64,
+    /// Value handed to the depositor by the floor at the current share price
+    pub value_delta: u64,
+    /// Vault counter after this event
+    pub precision_floor_events: u32,
+    pub timestamp: i64,
+}
+
+/// Emitted by the batch rebase sync crank
+#[event]
+pub struct RebaseBatchSyncedEvent {
+    pub vault: Pubkey,
+    pub rebase_version: u32,
+    /// Depositors whose shares were synced
+    pub synced: Vec<Pubkey>,
+    /// Depositors that were already up to date
+    pub skipped: Vec<Pubkey>,
+}
+
+/// Emitted by the `process_matured_unstakes` crank, after one `UnstakeExecutedEvent` per payout
+#[event]
+pub struct UnstakeBatchProcessedEvent {
+    pub vault: Pubkey,
+    /// Depositors whose matured request was paid out
+    pub processed: Vec<Pubkey>,
+    /// Depositors without a matured request, with a frozen token account, or whose payout
+    /// exceeded the vault balance
+    pub skipped: Vec<Pubkey>,
+    /// Active share value after the instruction (scaled by PRECISION)
+    pub active_share_value: u128,
+    pub timestamp: i64,
+}
+
+/// Emitted when a vault is created
+#[event]
+pub struct VaultInitializedEvent {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub token_mint: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted when a depositor account is created
+#[event]
+pub struct DepositorInitializedEvent {
+    pub vault: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted when the vault divides all shares by a power of ten, by the owner, the crank or
+/// inside a stake or reward. Every depositor's nominal share count shrinks by the divisor
+#[event]
+pub struct RebaseAppliedEvent {
+    pub vault: Pubkey,
+    pub expo_diff: u32,
+    /// `10^expo_diff`
+    pub rebase_divisor: u128,
+    pub shares_base: u32,
+    pub rebase_version: u32,
+    /// `total_shares` after the rebase
+    pub total_shares: u64,
+    pub timestamp: i64,
+}
+
+impl RebaseAppliedEvent {
+    /// The rebase `vault` went through since it held `total_shares_before` in shares base
+    /// `shares_base_before`, if any. A rebase runs before anything else touches the shares, so
+    /// the rebased total is the earlier one divided, whatever was minted or burned afterwards
+    pub fn since(
+        vault_key: Pubkey,
+        vault: &Vault,
+        shares_base_before: u32,
+        total_shares_before: u64,
+        timestamp: i64,
+    ) -> Option<Self> {
+        let expo_diff = vault.shares_base.checked_sub(shares_base_before).filter(|diff| *diff > 0)?;
+        let rebase_divisor = 10u128.pow(expo_diff);
+        Some(RebaseAppliedEvent {
+            vault: vault_key,
+            expo_diff,
+            rebase_divisor,
+            shares_base: vault.shares_base,
+            rebase_version: vault.rebase_version,
+            total_shares: (total_shares_before as u128 / rebase_divisor) as u64,
+            timestamp,
+        })
+    }
+}
+
+/// Emitted when a depositor's shares are brought up to the vault's latest rebase
+#[event]
+pub struct RebaseSyncedEvent {
+    pub vault: Pubkey,
+    pub authority: Pubkey,
+    /// Active shares after the sync
+    pub shares: u64,
+    pub rebase_version: u32,
+    pub timestamp: i64,
+}
+
+/// Emitted when legacy vault-level owner shares move into the owner fee depositor
+#[event]
+pub struct OwnerSharesMigratedEvent {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub shares: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted by `migrate_vault`
+#[event]
+pub struct VaultMigratedEvent {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub from_version: u8,
+    /// Version after the migration, still 0 while legacy owner shares await `migrate_owner_shares`
+    pub to_version: u8,
+    /// Account size before the migration, in bytes
+    pub old_len: u32,
+    pub new_len: u32,
+    pub timestamp: i64,
+}
+
+/// Emitted by `migrate_depositor`
+#[event]
+pub struct DepositorMigratedEvent {
+    pub vault: Pubkey,
+    pub authority: Pubkey,
+    pub vault_depositor: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+    pub timestamp: i64,
+}
+
+/// Emitted when `repair_bump` replaces a vault's stored bump with the canonical one
+#[event]
+pub struct VaultBumpRepairedEvent {
+    pub vault: Pubkey,
+    pub old_bump: u8,
+    pub new_bump: u8,
+    pub timestamp: i64,
+}
+
+/// Emitted when the owner proposes a new owner
+#[event]
+pub struct OwnershipTransferProposedEvent {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub pending_owner: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted when the pending owner accepts the vault
+#[event]
+pub struct OwnershipTransferredEvent {
+    pub vault: Pubkey,
+    pub previous_owner: Pubkey,
+    pub new_owner: Pubkey,
+    /// Fee shares settled into the previous owner's personal depositor
+    pub settled_fee_shares: u64,
+    pub previous_owner_depositor: Pubkey,
+    /// Owner fee depositor, now held by the new owner with no shares
+    pub owner_fee_depositor: Pubkey,
+    /// Platform account after the transfer
+    pub platform_account: Pubkey,
+    /// Whether the platform account was the previous owner and moved to the new owner
+    pub platform_account_reset: bool,
+    pub timestamp: i64,
+}
+
+/// Emitted when the owner withdraws a pending proposal
+#[event]
+pub struct OwnershipTransferCancelledEvent {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub cancelled_owner: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted when the program's upgrade authority is read from ProgramData and recorded
+#[event]
+pub struct UpgradeAuthorityAttestedEvent {
+    pub program_config: Pubkey,
+    /// None = upgrades revoked, the program is immutable
+    pub upgrade_authority: Option<Pubkey>,
+    /// Authority recorded by the previous attestation
+    pub previous_upgrade_authority: Option<Pubkey>,
+    /// Slot the program was last deployed or upgraded at
+    pub deployed_slot: u64,
+    pub attested_slot: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when the owner adds a wallet to a permissioned vault's allowlist
+#[event]
+pub struct AllowedDepositorAddedEvent {
+    pub vault: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted when the owner removes a wallet from the allowlist; its position can still exit
+#[event]
+pub struct AllowedDepositorRemovedEvent {
+    pub vault: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted when the owner registers a program PDA as a reward source
+#[event]
+pub struct ProgramRewardSourceRegisteredEvent {
+    pub vault: Pubkey,
+    pub program_id: Pubkey,
+    pub authority: Pubkey,
+    pub seeds_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+/// Emitted when the owner removes a program reward source
+#[event]
+pub struct ProgramRewardSourceRemovedEvent {
+    pub vault: Pubkey,
+    pub program_id: Pubkey,
+    pub authority: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted when a depositor moves active shares to another wallet's position
+#[event]
+pub struct SharesTransferredEvent {
+    pub vault: Pubkey,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub shares: u64,
+    /// Value of the moved shares at the active share value
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when vesting rewards are released into the vault's assets
+#[event]
+pub struct RewardsVestedEvent {
+    pub vault: Pubkey,
+    pub amount: u64,
+    /// Rewards still vesting after this release
+    pub unvested_rewards: u64,
+    pub active_share_value: u128,
+    pub timestamp: i64,
+}
+
+/// Emitted when the performance fee on a share value gain is minted to the owner
+#[event]
+pub struct PerformanceFeeCrystallizedEvent {
+    pub vault: Pubkey,
+    /// Shares added to `owner_shares`
+    pub fee_shares: u64,
+    /// Value of the fee shares at the active share value after minting them
+    pub fee_amount: u64,
+    /// New high-water mark, the active share value after the fee
+    pub high_water_mark: u128,
+    pub timestamp: i64,
+}
+
+/// Emitted when a foreign token balance held by the vault PDA is recovered by the owner
+#[event]
+pub struct TokensRecoveredEvent {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub mint: Pubkey,
+    pub source: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    /// What reached `destination`, less a Token-2022 transfer fee of the mint
+    pub received: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when tokens sent straight to the vault token account are folded into the vault
+#[event]
+pub struct SurplusSkimmedEvent {
+    pub vault: Pubkey,
+    /// Tokens above `total_assets` and unvested rewards, added like a reward
+    pub amount: u64,
+    /// Active share value after the instruction (scaled by PRECISION)
+    pub active_share_value: u128,
+    pub timestamp: i64,
+}
+
+/// Emitted when `reconcile` finds fewer tokens in the vault token account than the vault
+/// accounts for
+#[event]
+pub struct BalanceShortfallEvent {
+    pub vault: Pubkey,
+    /// Vault token account balance
+    pub balance: u64,
+    pub total_assets: u64,
+    pub unvested_rewards: u64,
+    pub shortfall: u64,
+    /// Whether stakes and withdrawals were paused by this reconcile
+    pub paused: bool,
+    pub slot: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when the owner proposes a timelocked config update
+#[event]
+pub struct ConfigUpdateProposedEvent {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    /// `Vault::config_digest` now
+    pub current_digest: [u8; 32],
+    /// `Vault::config_digest` once the update is committed on the current config
+    pub proposed_digest: [u8; 32],
+    pub effective_at: i64,
+    pub timestamp: i64,
+}
+
+/// Emitted when a proposed config update is applied, after its `ConfigUpdatedEvent`
+#[event]
+pub struct ConfigUpdateCommittedEvent {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub proposed_at: i64,
+    pub timestamp: i64,
+}
+
+/// Emitted when the owner withdraws a proposed config update
+#[event]
+pub struct ConfigUpdateCancelledEvent {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub proposed_at: i64,
+    pub timestamp: i64,
+}
+
+/// Emitted when `record_checkpoint` or `add_rewards` appends to a vault's checkpoint history
+#[event]
+pub struct CheckpointRecordedEvent {
+    pub vault: Pubkey,
+    /// Slot of `VaultCheckpoints::checkpoints` written
+    pub index: u16,
+    pub active_share_value: u128,
+    pub total_assets: u64,
+    pub total_shares: u64,
+    pub shares_base: u32,
+    pub timestamp: i64,
+}
+
+/// Emitted when the owner sets the vault's secondary reward mint
+#[event]
+pub struct SecondaryRewardMintSetEvent {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub mint: Pubkey,
+    pub token_account: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted when secondary rewards are credited to the active shares
+#[event]
+pub struct SecondaryRewardsAddedEvent {
+    pub vault: Pubkey,
+    pub authority: Pubkey,
+    /// Tokens received by the secondary reward token account
+    pub amount: u64,
+    pub secondary_rewards_per_share: u128,
+    pub timestamp: i64,
+}
+
+/// Emitted when a depositor claims its secondary rewards
+#[event]
+pub struct SecondaryRewardsClaimedEvent {
+    pub vault: Pubkey,
+    pub authority: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when the owner proposes a new platform account
+#[event]
+pub struct PlatformAccountProposedEvent {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    /// Platform account still receiving the platform share
+    pub platform_account: Pubkey,
+    pub pending_platform_account: Pubkey,
+    /// Whether the current platform account co-signed the proposal
+    pub cosigned: bool,
+    pub timestamp: i64,
+}
+
+/// Emitted when the proposed platform account accepts
+#[event]
+pub struct PlatformAccountChangedEvent {
+    pub vault: Pubkey,
+    pub previous_platform_account: Pubkey,
+    pub new_platform_account: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted when the owner withdraws a pending platform account proposal
+#[event]
+pub struct PlatformAccountProposalCancelledEvent {
+    pub vault: Pubkey,
+    pub owner: Pubkey,
+    pub cancelled_platform_account: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted when the platform account turns its co-signature requirement on or off
+#[event]
+pub struct PlatformCosignRequiredSetEvent {
+    pub vault: Pubkey,
+    pub platform_account: Pubkey,
+    pub required: bool,
+    pub timestamp: i64,
+}
+
+/// Emitted when a vault is listed on a registry page
+#[event]
+pub struct VaultRegisteredEvent {
+    pub vault: Pubkey,
+    pub page: u16,
+    /// Vaults on the page afterwards
+    pub vault_count: u16,
+    pub timestamp: i64,
+}
+
+/// Emitted when a closed vault is dropped from its registry page
+#[event]
+pub struct VaultUnregisteredEvent {
+    pub vault: Pubkey,
+    pub page: u16,
+    /// Vaults on the page afterwards
+    pub vault_count: u16,
+    pub timestamp: i64,
+}
+
+/// Emitted when the protocol config is created or its admin updates it
+#[event]
+pub struct ProtocolConfigUpdatedEvent {
+    pub protocol_config: Pubkey,
+    pub admin: Pubkey,
+    pub platform_account: Pubkey,
+    pub default_unstake_lockup_period: i64,
+    pub default_platform_share_bps: u64,
+    pub max_platform_share_bps: u64,
+    pub default_management_fee_bps: u64,
+    pub max_management_fee_bps: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when the protocol admin proposes a new admin
+#[event]
+pub struct ProtocolAdminTransferProposedEvent {
+    pub protocol_config: Pubkey,
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted when the pending protocol admin accepts
+#[event]
+pub struct ProtocolAdminTransferredEvent {
+    pub protocol_config: Pubkey,
+    pub previous_admin: Pubkey,
+    pub new_admin: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted when the protocol admin lowers a vault's fees, with the fees afterwards
+#[event]
+pub struct VaultFeesOverriddenEvent {
+    pub vault: Pubkey,
+    pub admin: Pubkey,
+    pub platform_share_bps: u64,
+    pub management_fee_bps: u64,
+    pub performance_fee_bps: u64,
+    pub withdrawal_fee_bps: u64,
+    pub instant_unstake_fee_bps: u64,
+    /// Whether the override paused deposits
+    pub deposits_paused: bool,
+    pub timestamp: i64,
+}
@@ -0,0 +1,117 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::events::{DepositorInitializedEvent, OwnershipTransferredEvent, PrecisionFloorApplied};
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct AcceptOwnership<'info> {
+    #[account(
+        mut,
+        constraint = vault.has_pending_owner() @ VaultError::NoPendingOwner,
+        constraint = vault.pending_owner == pending_owner.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    /// Owner fee depositor, handed to the new owner once its shares are settled
+    #[account(
+        init_if_needed,
+        payer = pending_owner,
+        space = VaultDepositor::LEN_V2,
+        seeds = [b"owner_fee_depositor", vault.key().as_ref()],
+        bump
+    )]
+    pub owner_fee_depositor: Account<'info, VaultDepositor>,
+    
+    /// Previous owner's personal depositor, receiving the fee shares accrued before the transfer
+    #[account(
+        init_if_needed,
+        payer = pending_owner,
+        space = VaultDepositor::LEN_V2,
+        seeds = [b"vault_depositor", vault.key().as_ref(), vault.owner.as_ref()],
+        bump
+    )]
+    pub previous_owner_depositor: Account<'info, VaultDepositor>,
+    
+    #[account(mut)]
+    pub pending_owner: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+/// Second step of an ownership transfer, signed by the proposed owner.
+/// Fees accrued under the previous owner are settled into their personal position
+pub fn accept_ownership(
+    ctx: Context<AcceptOwnership>,
+) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let owner_fee_depositor = &mut ctx.accounts.owner_fee_depositor;
+    let previous_owner_depositor = &mut ctx.accounts.previous_owner_depositor;
+    let timestamp = get_current_timestamp();
+    
+    if owner_fee_depositor.vault == Pubkey::default() {
+        owner_fee_depositor.initialize(vault.key(), vault.owner)?;
+        owner_fee_depositor.last_rebase_version = vault.rebase_version;
+        owner_fee_depositor.last_shares_base = vault.shares_base;
+    }
+    
+    if previous_owner_depositor.vault == Pubkey::default() {
+        previous_owner_depositor.initialize(vault.key(), vault.owner)?;
+        previous_owner_depositor.last_rebase_version = vault.rebase_version;
+        previous_owner_depositor.last_shares_base = vault.shares_base;
+        vault.record_depositor_opened()?;
+        
+        emit!(DepositorInitializedEvent {
+            vault: vault.key(),
+            authority: vault.owner,
+            timestamp,
+        });
+    }
+    
+    if owner_fee_depositor.vault != vault.key() || previous_owner_depositor.vault != vault.key() {
+        return Err(VaultError::InvalidVaultConfig.into());
+    }
+    
+    // Fee shares are settled at the latest rebase
+    for depositor in [&mut **owner_fee_depositor, &mut **previous_owner_depositor] {
+        let sync = depositor.sync_rebase(vault)?;
+        if let Some(floor) = sync.floor {
+            let value_delta = vault.record_precision_floor(&floor)?;
+            emit!(PrecisionFloorApplied {
+                vault: vault.key(),
+                authority: depositor.authority,
+                computed_shares: floor.computed_shares,
+                granted_shares: floor.granted_shares,
+                value_delta,
+                precision_floor_events: vault.precision_floor_events,
+                timestamp,
+            });
+        }
+    }
+    
+    // The fee depositor is not counted, only the previous owner's personal position
+    let previous_owner_shares = previous_owner_depositor.shares;
+    let handoff = vault.hand_off_ownership(owner_fee_depositor, previous_owner_depositor)?;
+    vault.record_active_shares_change(previous_owner_shares, previous_owner_depositor.shares);
+    
+    emit!(OwnershipTransferredEvent {
+        vault: vault.key(),
+        previous_owner: handoff.previous_owner,
+        new_owner: vault.owner,
+        settled_fee_shares: handoff.settled_fee_shares,
+        previous_owner_depositor: previous_owner_depositor.key(),
+        owner_fee_depositor: owner_fee_depositor.key(),
+        platform_account: vault.platform_account,
+        platform_account_reset: handoff.platform_account_reset,
+        timestamp,
+    });
+    
+    msg!(
+        "Vault ownership transferred from {} to {}, {} fee shares settled to the previous owner",
+        handoff.previous_owner,
+        vault.owner,
+        handoff.settled_fee_shares
+    );
+    
+    Ok(())
+}
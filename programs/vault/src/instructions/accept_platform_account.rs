@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::events::PlatformAccountChangedEvent;
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct AcceptPlatformAccount<'info> {
+    #[account(
+        mut,
+        constraint = vault.has_pending_platform_account() @ VaultError::NoPendingPlatformAccount,
+        constraint = vault.pending_platform_account == pending_platform_account.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    pub pending_platform_account: Signer<'info>,
+}
+
+/// Second step of a platform account change, signed by the proposed account.
+/// The next `add_rewards` pays the platform share to it
+pub fn accept_platform_account(
+    ctx: Context<AcceptPlatformAccount>,
+) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    
+    let previous_platform_account = vault.accept_platform_account()?;
+    
+    emit!(PlatformAccountChangedEvent {
+        vault: vault.key(),
+        previous_platform_account,
+        new_platform_account: vault.platform_account,
+        timestamp: get_current_timestamp(),
+    });
+    
+    msg!(
+        "Platform account changed from {} to {}",
+        previous_platform_account,
+        vault.platform_account
+    );
+    
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::events::ProtocolAdminTransferredEvent;
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct AcceptProtocolAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = protocol_config.has_pending_admin() @ VaultError::NoPendingProtocolAdmin,
+        constraint = protocol_config.pending_admin == pending_admin.key() @ VaultError::Unauthorized
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    
+    pub pending_admin: Signer<'info>,
+}
+
+/// Second step of a protocol admin transfer, signed by the proposed admin
+pub fn accept_protocol_admin(
+    ctx: Context<AcceptProtocolAdmin>,
+) -> Result<()> {
+    let protocol_config = &mut ctx.accounts.protocol_config;
+    
+    let previous_admin = protocol_config.accept_admin()?;
+    
+    emit!(ProtocolAdminTransferredEvent {
+        protocol_config: protocol_config.key(),
+        previous_admin,
+        new_admin: protocol_config.admin,
+        timestamp: get_current_timestamp(),
+    });
+    
+    msg!("Protocol admin transferred from {} to {}", previous_admin, protocol_config.admin);
+    
+    Ok(())
+}
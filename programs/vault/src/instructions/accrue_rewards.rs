@@ -0,0 +1,45 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::events::RewardsVestedEvent;
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct AccrueRewards<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+}
+
+/// Permissionless release of vested rewards. Succeeds without changes when nothing has
+/// vested, so keepers can send it on a schedule
+pub fn accrue_rewards(
+    ctx: Context<AccrueRewards>,
+) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    
+    let released = accrue_vested_rewards(vault, get_current_timestamp())?;
+    msg!(
+        "Released {} vested rewards, {} still vesting until {}",
+        released,
+        vault.unvested_rewards,
+        vault.vesting_end
+    );
+    
+    Ok(())
+}
+
+/// Release the rewards vested by `timestamp` and emit what was released.
+/// Instructions pricing shares call this first, so prices include every vested reward
+pub(crate) fn accrue_vested_rewards(vault: &mut Account<Vault>, timestamp: i64) -> Result<u64> {
+    let released = vault.accrue_rewards(timestamp)?;
+    if released > 0 {
+        emit!(RewardsVestedEvent {
+            vault: vault.key(),
+            amount: released,
+            unvested_rewards: vault.unvested_rewards,
+            active_share_value: vault.active_share_value_or_zero()?,
+            timestamp,
+        });
+    }
+    
+    Ok(released)
+}
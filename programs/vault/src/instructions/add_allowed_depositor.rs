@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::events::AllowedDepositorAddedEvent;
+use crate::utils::*;
+
+#[derive(Accounts)]
+#[instruction(authority: Pubkey)]
+pub struct AddAllowedDepositor<'info> {
+    #[account(
+        constraint = vault.owner == owner.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        init,
+        payer = owner,
+        space = AllowedDepositor::LEN,
+        seeds = [b"allow", vault.key().as_ref(), authority.as_ref()],
+        bump
+    )]
+    pub allowed_depositor: Account<'info, AllowedDepositor>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+pub fn add_allowed_depositor(
+    ctx: Context<AddAllowedDepositor>,
+    authority: Pubkey,
+) -> Result<()> {
+    let timestamp = get_current_timestamp();
+    let allowed_depositor = &mut ctx.accounts.allowed_depositor;
+    
+    allowed_depositor.vault = ctx.accounts.vault.key();
+    allowed_depositor.authority = authority;
+    allowed_depositor.added_at = timestamp;
+    allowed_depositor.bump = ctx.bumps.allowed_depositor;
+    
+    emit!(AllowedDepositorAddedEvent {
+        vault: ctx.accounts.vault.key(),
+        authority,
+        timestamp,
+    });
+    
+    msg!("Allowed depositor {} on vault {}", authority, ctx.accounts.vault.key());
+    
+    Ok(())
+}
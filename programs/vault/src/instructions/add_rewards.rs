@@ -1,7 +1,15 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 use crate::state::*;
 use crate::error::*;
+use crate::events::RewardsAddedEvent;
+use crate::utils::*;
+use crate::constants::{BASIS_POINTS_PRECISION, PAUSE_ADD_REWARDS};
+use super::accrue_rewards::accrue_vested_rewards;
+use super::apply_rebase::emit_rebase;
+use super::crystallize_fees::crystallize_performance_fee;
+use super::record_checkpoint::write_checkpoint;
+use super::token_transfers::transfer_tokens;
 
 #[derive(Accounts)]
 pub struct AddRewards<'info> {
@@ -13,27 +21,54 @@ pub struct AddRewards<'info> {
         seeds = [b"vault_token_account", vault.key().as_ref()],
         bump,
         constraint = vault_token_account.key() == vault.vault_token_account @ VaultError::InvalidTokenAccount,
+        constraint = vault_token_account.owner == vault.key() @ VaultError::InvalidTokenAccount,
+        constraint = vault_token_account.mint == vault.token_mint @ VaultError::InvalidTokenAccount,
+        constraint = !vault_token_account.is_frozen() @ VaultError::TokenAccountFrozen,
     )]
-    pub vault_token_account: Account<'info, TokenAccount>,
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
     
+    /// Must be distinct from the vault and platform accounts, otherwise rewards are
+    /// "added" without any net token movement
     #[account(
         mut,
+        constraint = reward_source_account.key() != vault_token_account.key() @ VaultError::InvalidTokenAccount,
         constraint = reward_source_account.mint == vault.token_mint @ VaultError::InvalidTokenMint,
     )]
-    pub reward_source_account: Account<'info, TokenAccount>,
+    pub reward_source_account: InterfaceAccount<'info, TokenAccount>,
     
     #[account(
         mut,
+        constraint = platform_token_account.key() != vault_token_account.key() @ VaultError::InvalidTokenAccount,
+        constraint = platform_token_account.key() != reward_source_account.key() @ VaultError::InvalidTokenAccount,
         constraint = platform_token_account.mint == vault.token_mint @ VaultError::InvalidTokenMint,
         constraint = platform_token_account.owner == vault.platform_account @ VaultError::InvalidTokenAccount,
     )]
-    pub platform_token_account: Account<'info, TokenAccount>,
+    pub platform_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(constraint = token_mint.key() == vault.token_mint @ VaultError::InvalidTokenMint)]
+    pub token_mint: InterfaceAccount<'info, Mint>,
     
     /// CHECK: This account can be either a Signer or a PDA for CPI calls
     /// When called via CPI, this should be validated by the calling program
     pub reward_source_authority: AccountInfo<'info>,
     
-    pub token_program: Program<'info, Token>,
+    /// Registration of `reward_source_authority`, required when it is a program PDA rather
+    /// than the vault's `reward_authority`
+    #[account(
+        seeds = [b"reward_source", vault.key().as_ref(), reward_source_authority.key().as_ref()],
+        bump = reward_source.bump,
+    )]
+    pub reward_source: Option<Account<'info, ProgramRewardSource>>,
+    
+    /// The vault's checkpoint history, written to when a checkpoint is due
+    #[account(
+        mut,
+        seeds = [b"vault_checkpoints", vault.key().as_ref()],
+        bump = vault_checkpoints.bump,
+    )]
+    pub vault_checkpoints: Option<Account<'info, VaultCheckpoints>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
 pub fn add_rewards(
@@ -44,53 +79,78 @@ pub fn add_rewards(
     
     let vault = &mut ctx.accounts.vault;
     
-    if vault.is_paused {
-        return Err(VaultError::VaultPaused.into());
+    vault.check_not_paused(PAUSE_ADD_REWARDS)?;
+    
+    if vault.is_decommissioning() {
+        return Err(VaultError::VaultDecommissioning.into());
     }
     
     if amount == 0 {
         return Err(VaultError::InvalidAmount.into());
     }
     
-    // Calculate platform share using vault's management_fee setting
-    let platform_share_bps = vault.management_fee; // Platform share in basis points
-    const BASIS_POINTS: u64 = 10000;
+    vault.check_reward_source(
+        vault.key(),
+        ctx.accounts.reward_source_authority.key(),
+        ctx.accounts.reward_source_authority.is_signer,
+        ctx.accounts.reward_source.as_deref(),
+    )?;
     
+    // Platform cut of this reward, the vault's platform_share_bps.
+    // Nothing is taken while fee accrual is paused
     let platform_share = ((amount as u128)
-        .safe_mul(platform_share_bps as u128)?
-        .safe_div(BASIS_POINTS as u128)?)
+        .safe_mul(vault.accrued_platform_share_bps() as u128)?
+        .safe_div(BASIS_POINTS_PRECISION as u128)?)
         .safe_cast()?;
     
-    let vault_share = amount.safe_sub(platform_share)?;
-    
-    // Transfer vault share to vault token account
-    let vault_cpi_accounts = Transfer {
-        from: ctx.accounts.reward_source_account.to_account_info(),
-        to: ctx.accounts.vault_token_account.to_account_info(),
-        authority: ctx.accounts.reward_source_authority.to_account_info(),
-    };
-    let vault_cpi_ctx = CpiContext::new(
-        ctx.accounts.token_program.to_account_info(),
-        vault_cpi_accounts,
-    );
-    
-    token::transfer(vault_cpi_ctx, vault_share)?;
+    // Transfer vault share to vault token account, only what arrives after a transfer fee
+    // of the mint is added to the vault
+    let vault_share = transfer_tokens(
+        &ctx.accounts.token_program,
+        &ctx.accounts.token_mint,
+        ctx.accounts.reward_source_account.to_account_info(),
+        &mut ctx.accounts.vault_token_account,
+        ctx.accounts.reward_source_authority.to_account_info(),
+        &[],
+        amount.safe_sub(platform_share)?,
+    )?;
     
     // Transfer platform share to platform token account
-    let platform_cpi_accounts = Transfer {
-        from: ctx.accounts.reward_source_account.to_account_info(),
-        to: ctx.accounts.platform_token_account.to_account_info(),
-        authority: ctx.accounts.reward_source_authority.to_account_info(),
-    };
-    let platform_cpi_ctx = CpiContext::new(
-        ctx.accounts.token_program.to_account_info(),
-        platform_cpi_accounts,
-    );
+    transfer_tokens(
+        &ctx.accounts.token_program,
+        &ctx.accounts.token_mint,
+        ctx.accounts.reward_source_account.to_account_info(),
+        &mut ctx.accounts.platform_token_account,
+        ctx.accounts.reward_source_authority.to_account_info(),
+        &[],
+        platform_share,
+    )?;
     
-    token::transfer(platform_cpi_ctx, platform_share)?;
+    // What vested of earlier rewards is released first, a new reward restarts the vesting
+    accrue_vested_rewards(vault, get_current_timestamp())?;
     
-    // Update vault rewards with only the vault's share
+    // Update vault rewards with only the vault's share, rebasing first when shares grew too large
+    let (shares_base_before, total_shares_before) = (vault.shares_base, vault.total_shares);
     vault.add_rewards(vault_share)?;
+    emit_rebase(vault, shares_base_before, total_shares_before, get_current_timestamp());
+    
+    // The performance fee is charged on what the share value gained, unvested rewards later
+    crystallize_performance_fee(vault, get_current_timestamp())?;
+    
+    // Unlike `record_checkpoint`, a checkpoint that is not due yet does not fail the reward
+    if let Some(vault_checkpoints) = ctx.accounts.vault_checkpoints.as_mut() {
+        write_checkpoint(vault, vault_checkpoints, get_current_timestamp())?;
+    }
+    
+    emit!(RewardsAddedEvent {
+        vault: vault.key(),
+        authority: ctx.accounts.reward_source_authority.key(),
+        amount,
+        vault_share,
+        platform_share,
+        active_share_value: vault.active_share_value_or_zero()?,
+        timestamp: get_current_timestamp(),
+    });
     
     msg!(
         "Added {} total rewards: {} to vault users ({}%), {} to platform ({}% = {} bps)", 
@@ -99,7 +159,7 @@ pub fn add_rewards(
         (vault_share * 100) / amount,
         platform_share,
         (platform_share * 100) / amount,
-        platform_share_bps
+        vault.accrued_platform_share_bps()
     );
     
     Ok(())
@@ -0,0 +1,97 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::state::*;
+use crate::error::*;
+use crate::events::SecondaryRewardsAddedEvent;
+use crate::utils::*;
+use crate::constants::PAUSE_ADD_REWARDS;
+use super::token_transfers::transfer_tokens;
+
+#[derive(Accounts)]
+pub struct AddSecondaryRewards<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        mut,
+        seeds = [b"secondary_reward_token_account", vault.key().as_ref()],
+        bump,
+        constraint = secondary_reward_token_account.key() == vault.secondary_reward_token_account @ VaultError::InvalidTokenAccount,
+        constraint = !secondary_reward_token_account.is_frozen() @ VaultError::TokenAccountFrozen,
+    )]
+    pub secondary_reward_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = reward_source_account.key() != secondary_reward_token_account.key() @ VaultError::InvalidTokenAccount,
+        constraint = reward_source_account.mint == vault.secondary_reward_mint @ VaultError::InvalidTokenMint,
+    )]
+    pub reward_source_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(constraint = secondary_reward_mint.key() == vault.secondary_reward_mint @ VaultError::InvalidTokenMint)]
+    pub secondary_reward_mint: InterfaceAccount<'info, Mint>,
+    
+    /// CHECK: Signer or program PDA, authorized exactly like the `add_rewards` source
+    pub reward_source_authority: AccountInfo<'info>,
+    
+    /// Registration of `reward_source_authority`, required when it is a program PDA rather
+    /// than the vault's `reward_authority`
+    #[account(
+        seeds = [b"reward_source", vault.key().as_ref(), reward_source_authority.key().as_ref()],
+        bump = reward_source.bump,
+    )]
+    pub reward_source: Option<Account<'info, ProgramRewardSource>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Distribute `amount` of the secondary reward token to the shares active now, for each
+/// depositor to claim. There is no platform share and no vesting
+pub fn add_secondary_rewards(
+    ctx: Context<AddSecondaryRewards>,
+    amount: u64,
+) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    
+    vault.check_not_paused(PAUSE_ADD_REWARDS)?;
+    
+    if vault.is_decommissioning() {
+        return Err(VaultError::VaultDecommissioning.into());
+    }
+    
+    if amount == 0 {
+        return Err(VaultError::InvalidAmount.into());
+    }
+    
+    vault.check_reward_source(
+        vault.key(),
+        ctx.accounts.reward_source_authority.key(),
+        ctx.accounts.reward_source_authority.is_signer,
+        ctx.accounts.reward_source.as_deref(),
+    )?;
+    
+    // Only what arrives after a transfer fee of the mint is distributed
+    let received = transfer_tokens(
+        &ctx.accounts.token_program,
+        &ctx.accounts.secondary_reward_mint,
+        ctx.accounts.reward_source_account.to_account_info(),
+        &mut ctx.accounts.secondary_reward_token_account,
+        ctx.accounts.reward_source_authority.to_account_info(),
+        &[],
+        amount,
+    )?;
+    
+    vault.add_secondary_rewards(received)?;
+    
+    emit!(SecondaryRewardsAddedEvent {
+        vault: vault.key(),
+        authority: ctx.accounts.reward_source_authority.key(),
+        amount: received,
+        secondary_rewards_per_share: vault.secondary_rewards_per_share,
+        timestamp: get_current_timestamp(),
+    });
+    
+    msg!("Added {} secondary rewards ({} sent)", received, amount);
+    
+    Ok(())
+}
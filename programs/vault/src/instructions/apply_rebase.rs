@@ -1,6 +1,8 @@
 use anchor_lang::prelude::*;
 use crate::state::*;
 use crate::error::*;
+use crate::events::RebaseAppliedEvent;
+use crate::utils::*;
 
 #[derive(Accounts)]
 pub struct ApplyRebase<'info> {
@@ -17,9 +19,11 @@ pub fn apply_rebase(
     ctx: Context<ApplyRebase>,
 ) -> Result<()> {
     let vault = &mut ctx.accounts.vault;
+    let (shares_base_before, total_shares_before) = (vault.shares_base, vault.total_shares);
     
     // Apply vault rebase - this will affect all users' shares proportionally
     if let Some(rebase_divisor) = vault.apply_rebase()? {
+        emit_rebase(vault, shares_base_before, total_shares_before, get_current_timestamp());
         msg!("Global rebase applied to vault with divisor: {}", rebase_divisor);
         msg!("All user shares will be automatically adjusted by the same factor");
     } else {
@@ -27,4 +31,36 @@ pub fn apply_rebase(
     }
     
     Ok(())
-}
\ No newline at end of file
+}
+/// Rebase by `10^expo_diff` in one step, for a vault too far out of range for `apply_rebase`
+/// to fix in a single call
+pub fn force_rebase(
+    ctx: Context<ApplyRebase>,
+    expo_diff: u32,
+) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let (shares_base_before, total_shares_before) = (vault.shares_base, vault.total_shares);
+    
+    let rebase_divisor = vault.force_rebase(expo_diff)?;
+    emit_rebase(vault, shares_base_before, total_shares_before, get_current_timestamp());
+    msg!("Forced rebase applied to vault with divisor: {}", rebase_divisor);
+    
+    Ok(())
+}
+
+/// Emit `RebaseAppliedEvent` when the vault rebased since it held `total_shares_before` shares
+/// in base `shares_base_before`. Returns whether it did
+pub(crate) fn emit_rebase(
+    vault: &Account<Vault>,
+    shares_base_before: u32,
+    total_shares_before: u64,
+    timestamp: i64,
+) -> bool {
+    match RebaseAppliedEvent::since(vault.key(), vault, shares_base_before, total_shares_before, timestamp) {
+        Some(rebase) => {
+            emit!(rebase);
+            true
+        }
+        None => false,
+    }
+}
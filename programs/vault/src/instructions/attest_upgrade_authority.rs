@@ -0,0 +1,70 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::events::UpgradeAuthorityAttestedEvent;
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct AttestUpgradeAuthority<'info> {
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = ProgramConfig::LEN,
+        seeds = [b"program_config"],
+        bump
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    
+    /// Owned by the upgradeable loader (checked by `Account`) and derived from this
+    /// program's id, so a caller cannot substitute another program's ProgramData
+    #[account(
+        constraint = program_data.key() == program_data_address() @ VaultError::InvalidProgramData,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+/// ProgramData address of this program, derived from the program id under the upgradeable
+/// BPF loader (`ProgramData::owner()` is the loader's id)
+pub(crate) fn program_data_address() -> Pubkey {
+    Pubkey::find_program_address(&[crate::ID.as_ref()], &ProgramData::owner()).0
+}
+
+/// Record the program's current upgrade authority (None once revoked) and the slot it was
+/// read at. Permissionless: it only copies what the loader reports
+pub fn attest_upgrade_authority(
+    ctx: Context<AttestUpgradeAuthority>,
+) -> Result<()> {
+    let program_config = &mut ctx.accounts.program_config;
+    let program_data = &ctx.accounts.program_data;
+    let slot = Clock::get()?.slot;
+    let timestamp = get_current_timestamp();
+    
+    program_config.bump = ctx.bumps.program_config;
+    let previous_upgrade_authority = program_config.record_attestation(
+        program_data.upgrade_authority_address,
+        program_data.slot,
+        slot,
+        timestamp,
+    );
+    
+    emit!(UpgradeAuthorityAttestedEvent {
+        program_config: program_config.key(),
+        upgrade_authority: program_config.upgrade_authority,
+        previous_upgrade_authority,
+        deployed_slot: program_config.deployed_slot,
+        attested_slot: slot,
+        timestamp,
+    });
+    
+    match program_config.upgrade_authority {
+        Some(authority) => msg!("Program upgradeable by {} as of slot {}", authority, slot),
+        None => msg!("Program immutable as of slot {}", slot),
+    }
+    
+    Ok(())
+}
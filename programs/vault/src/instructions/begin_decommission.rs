@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+use crate::constants::*;
+use crate::state::*;
+use crate::error::*;
+use crate::events::DecommissionStartedEvent;
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct BeginDecommission<'info> {
+    #[account(
+        mut,
+        constraint = vault.owner == owner.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    pub owner: Signer<'info>,
+}
+
+pub fn begin_decommission(
+    ctx: Context<BeginDecommission>,
+    notice_period: i64,
+) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let current_time = get_current_timestamp();
+    
+    vault.begin_decommission(current_time, notice_period)?;
+    
+    emit!(DecommissionStartedEvent {
+        vault: vault.key(),
+        owner: vault.owner,
+        notice_ends_at: vault.decommission_notice_ends_at,
+        force_distribution_at: vault.decommission_notice_ends_at + DECOMMISSION_FORCE_TIMEOUT,
+        timestamp: current_time,
+    });
+    
+    msg!(
+        "VAULT DECOMMISSIONING: stakes and rewards disabled, unstake lockup waived from {}",
+        vault.decommission_notice_ends_at
+    );
+    
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::events::ConfigUpdateCancelledEvent;
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct CancelConfigUpdate<'info> {
+    #[account(
+        constraint = vault.owner == owner.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"pending_config_update", vault.key().as_ref()],
+        bump = pending_config_update.bump
+    )]
+    pub pending_config_update: Account<'info, PendingConfigUpdate>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+/// Withdraw a proposed config update before it is committed
+pub fn cancel_config_update(
+    ctx: Context<CancelConfigUpdate>,
+) -> Result<()> {
+    emit!(ConfigUpdateCancelledEvent {
+        vault: ctx.accounts.vault.key(),
+        owner: ctx.accounts.owner.key(),
+        proposed_at: ctx.accounts.pending_config_update.proposed_at,
+        timestamp: get_current_timestamp(),
+    });
+    
+    msg!("Proposed config update cancelled");
+    
+    Ok(())
+}
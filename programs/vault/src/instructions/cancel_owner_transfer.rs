@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::events::OwnershipTransferCancelledEvent;
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct CancelOwnerTransfer<'info> {
+    #[account(
+        mut,
+        constraint = vault.owner == owner.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    pub owner: Signer<'info>,
+}
+
+/// Withdraw a pending ownership proposal, e.g. one made to a mistyped pubkey
+pub fn cancel_owner_transfer(
+    ctx: Context<CancelOwnerTransfer>,
+) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    
+    let cancelled_owner = vault.cancel_owner_transfer()?;
+    
+    emit!(OwnershipTransferCancelledEvent {
+        vault: vault.key(),
+        owner: vault.owner,
+        cancelled_owner,
+        timestamp: get_current_timestamp(),
+    });
+    
+    msg!("Ownership transfer to {} cancelled", cancelled_owner);
+    
+    Ok(())
+}
@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::events::PlatformAccountProposalCancelledEvent;
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct CancelPlatformAccountProposal<'info> {
+    #[account(
+        mut,
+        constraint = vault.owner == owner.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    pub owner: Signer<'info>,
+}
+
+/// Withdraw a pending platform account proposal
+pub fn cancel_platform_account_proposal(
+    ctx: Context<CancelPlatformAccountProposal>,
+) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    
+    let cancelled_platform_account = vault.cancel_platform_account_proposal()?;
+    
+    emit!(PlatformAccountProposalCancelledEvent {
+        vault: vault.key(),
+        owner: vault.owner,
+        cancelled_platform_account,
+        timestamp: get_current_timestamp(),
+    });
+    
+    msg!("Platform account change to {} cancelled", cancelled_platform_account);
+    
+    Ok(())
+}
@@ -1,8 +1,13 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 use crate::state::*;
+use crate::constants::PAUSE_CANCEL;
 use crate::error::*;
-use crate::math::{SafeMath, SafeCast};
-use crate::constants::*;
+use crate::events::UnstakeCancelledEvent;
+use crate::utils::*;
+use super::share_tokens::{settle_share_tokens, ShareTokenAccounts};
+use super::accrue_rewards::accrue_vested_rewards;
+use super::sync_rebase::sync_depositor_rebase;
 
 #[derive(Accounts)]
 pub struct CancelUnstakeRequest<'info> {
@@ -18,7 +23,26 @@ pub struct CancelUnstakeRequest<'info> {
     )]
     pub vault_depositor: Account<'info, VaultDepositor>,
     
+    /// Only read, the restored shares must leave the vault solvent
+    #[account(
+        seeds = [b"vault_token_account", vault.key().as_ref()],
+        bump,
+        constraint = vault_token_account.key() == vault.vault_token_account @ VaultError::InvalidTokenAccount,
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    /// Share mint of a tokenized vault, required only when `vault.share_mint` is set
+    #[account(mut)]
+    pub share_mint: Option<InterfaceAccount<'info, Mint>>,
+    
+    /// `authority`'s token account of `share_mint`
+    #[account(mut)]
+    pub user_share_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
     pub authority: Signer<'info>,
+    
+    /// Required with `share_mint`
+    pub token_program: Option<Interface<'info, TokenInterface>>,
 }
 
 pub fn cancel_unstake_request(
@@ -26,59 +50,45 @@ pub fn cancel_unstake_request(
 ) -> Result<()> {
     let vault = &mut ctx.accounts.vault;
     let vault_depositor = &mut ctx.accounts.vault_depositor;
+    let timestamp = get_current_timestamp();
     
-    if !vault_depositor.unstake_request.is_pending() {
-        return Err(VaultError::NoUnstakeRequest.into());
-    }
-    
-    // Get the details from the request
-    let shares = vault_depositor.unstake_request.shares;
-    let asset_per_share_at_request = vault_depositor.unstake_request.asset_per_share_at_request;
-    
-    // CRITICAL ACCOUNTING FIX: Calculate the correct amount to unfreeze
-    // The original frozen amount should be used, not recalculated
-    let original_frozen_amount = SafeCast::<u128>::safe_cast(&shares)?
-        .safe_mul(asset_per_share_at_request)?
-        .safe_div(SafeCast::<u128>::safe_cast(&PRECISION)?)?;
-    let original_frozen_amount = SafeCast::<u64>::safe_cast(&original_frozen_amount)?;
+    vault.check_not_paused(PAUSE_CANCEL)?;
     
-    // Calculate current value of these shares for accounting adjustment
-    let current_share_value = vault.get_active_share_value()?;
-    let current_value = SafeCast::<u128>::safe_cast(&shares)?
-        .safe_mul(current_share_value)?
-        .safe_div(SafeCast::<u128>::safe_cast(&PRECISION)?)?;
-    let current_value = SafeCast::<u64>::safe_cast(&current_value)?;
+    accrue_vested_rewards(vault, timestamp)?;
     
-    // Return shares to active pool
-    vault.pending_unstake_shares = vault.pending_unstake_shares.safe_sub(shares)?;
+    // Restored shares are minted in the current base, the position must be in it too
+    sync_depositor_rebase(vault, vault_depositor, timestamp)?;
     
-    // CRITICAL FIX: Must restore user's active shares
-    // This allows them to earn rewards again on the cancelled portion
-    vault_depositor.shares = vault_depositor.shares.safe_add(shares)?;
+    // The frozen assets return to the active pool and the depositor gets active shares worth
+    // exactly that amount, so cancelling neither creates nor destroys value for other depositors
+    let shares_before = vault_depositor.shares;
+    let (frozen_amount, restored_shares) = vault_depositor.cancel_unstake(vault)?;
+    vault.record_active_shares_change(shares_before, vault_depositor.shares);
+    vault.verify_solvency(ctx.accounts.vault_token_account.amount)?;
     
-    // CRITICAL ACCOUNTING FIX: Properly handle asset difference during cancel
-    vault.reserved_assets = vault.reserved_assets.safe_sub(original_frozen_amount)?;
+    settle_share_tokens(
+        vault,
+        ShareTokenAccounts {
+            share_mint: ctx.accounts.share_mint.as_mut(),
+            share_account: ctx.accounts.user_share_account.as_ref(),
+            token_program: ctx.accounts.token_program.as_ref(),
+        },
+        ctx.accounts.authority.key(),
+        &ctx.accounts.authority.to_account_info(),
+        0,
+        restored_shares,
+    )?;
     
-    // CRITICAL: Must adjust total_assets to maintain accounting balance
-    // The shares are returning to active pool at current value, not frozen value
-    if current_value > original_frozen_amount {
-        // Vault gains from rewards - add the difference to total_assets
-        let gain = current_value.safe_sub(original_frozen_amount)?;
-        vault.total_assets = vault.total_assets.safe_add(gain)?;
-    } else if current_value < original_frozen_amount {
-        // Vault loses value (rare case) - subtract the difference from total_assets
-        let loss = original_frozen_amount.safe_sub(current_value)?;
-        vault.total_assets = vault.total_assets.safe_sub(loss)?;
-    }
-    // If equal, no adjustment needed
+    emit!(UnstakeCancelledEvent {
+        vault: vault.key(),
+        authority: ctx.accounts.authority.key(),
+        amount: frozen_amount,
+        shares: restored_shares,
+        active_share_value: vault.active_share_value_or_zero()?,
+        timestamp,
+    });
     
-    // Cancel the unstake request
-    vault_depositor.unstake_request.reset();
-    
-    // INVARIANT CHECK: Verify vault state consistency after cancel
-    vault.verify_invariants()?;
-    
-    msg!("Unstake request cancelled, {} shares returned (frozen: {}, current: {})", shares, original_frozen_amount, current_value);
+    msg!("Unstake request cancelled, {} frozen assets restored as {} shares", frozen_amount, restored_shares);
     
     Ok(())
-}
\ No newline at end of file
+}
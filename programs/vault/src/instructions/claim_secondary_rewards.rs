@@ -0,0 +1,83 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::state::*;
+use crate::error::*;
+use crate::events::SecondaryRewardsClaimedEvent;
+use crate::constants::PAUSE_UNSTAKE;
+use crate::utils::*;
+use super::token_transfers::transfer_tokens;
+
+#[derive(Accounts)]
+pub struct ClaimSecondaryRewards<'info> {
+    pub vault: Account<'info, Vault>,
+    
+    /// The authority's depositor, or the owner fee depositor when the owner claims what the
+    /// fee shares earned. No seeds: both are program accounts of this vault and authority
+    #[account(
+        mut,
+        constraint = vault_depositor.authority == authority.key() @ VaultError::Unauthorized,
+        constraint = vault_depositor.vault == vault.key() @ VaultError::InvalidVaultConfig,
+    )]
+    pub vault_depositor: Account<'info, VaultDepositor>,
+    
+    #[account(
+        mut,
+        seeds = [b"secondary_reward_token_account", vault.key().as_ref()],
+        bump,
+        constraint = secondary_reward_token_account.key() == vault.secondary_reward_token_account @ VaultError::InvalidTokenAccount,
+        constraint = !secondary_reward_token_account.is_frozen() @ VaultError::TokenAccountFrozen,
+    )]
+    pub secondary_reward_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = user_token_account.key() != secondary_reward_token_account.key() @ VaultError::InvalidTokenAccount,
+        constraint = user_token_account.mint == vault.secondary_reward_mint @ VaultError::InvalidTokenMint,
+        constraint = user_token_account.owner == authority.key() @ VaultError::Unauthorized,
+    )]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(constraint = secondary_reward_mint.key() == vault.secondary_reward_mint @ VaultError::InvalidTokenMint)]
+    pub secondary_reward_mint: InterfaceAccount<'info, Mint>,
+    
+    pub authority: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Pay out every secondary reward the depositor's active shares earned so far
+pub fn claim_secondary_rewards(
+    ctx: Context<ClaimSecondaryRewards>,
+) -> Result<()> {
+    let vault = &ctx.accounts.vault;
+    
+    vault.check_not_paused(PAUSE_UNSTAKE)?;
+    
+    if !vault.has_secondary_rewards() {
+        return Err(VaultError::SecondaryRewardsNotEnabled.into());
+    }
+    
+    let amount = ctx.accounts.vault_depositor.take_secondary_rewards(vault.secondary_rewards_per_share)?;
+    
+    let signer_seeds: &[&[&[u8]]] = &[&vault.get_signer_seeds()];
+    let received = transfer_tokens(
+        &ctx.accounts.token_program,
+        &ctx.accounts.secondary_reward_mint,
+        ctx.accounts.secondary_reward_token_account.to_account_info(),
+        &mut ctx.accounts.user_token_account,
+        vault.to_account_info(),
+        signer_seeds,
+        amount,
+    )?;
+    
+    emit!(SecondaryRewardsClaimedEvent {
+        vault: vault.key(),
+        authority: ctx.accounts.authority.key(),
+        amount,
+        timestamp: get_current_timestamp(),
+    });
+    
+    msg!("Claimed {} secondary rewards ({} received)", amount, received);
+    
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::events::ConfigUpdateCommittedEvent;
+use crate::utils::*;
+use super::update_vault_config::apply_config_update;
+
+#[derive(Accounts)]
+pub struct CommitConfigUpdate<'info> {
+    #[account(
+        mut,
+        constraint = vault.owner == owner.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"pending_config_update", vault.key().as_ref()],
+        bump = pending_config_update.bump
+    )]
+    pub pending_config_update: Account<'info, PendingConfigUpdate>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+/// Apply a proposed config update once its timelock elapsed. The params are validated again
+/// against the vault as it is now
+pub fn commit_config_update(
+    ctx: Context<CommitConfigUpdate>,
+) -> Result<()> {
+    let timestamp = get_current_timestamp();
+    let pending = &ctx.accounts.pending_config_update;
+    
+    if timestamp < pending.effective_at {
+        return Err(VaultError::ConfigTimelockNotElapsed.into());
+    }
+    
+    let vault = &mut ctx.accounts.vault;
+    apply_config_update(vault, ctx.accounts.owner.key(), pending.params.clone())?;
+    
+    emit!(ConfigUpdateCommittedEvent {
+        vault: vault.key(),
+        owner: ctx.accounts.owner.key(),
+        proposed_at: pending.proposed_at,
+        timestamp,
+    });
+    
+    msg!("Proposed config update committed");
+    
+    Ok(())
+}
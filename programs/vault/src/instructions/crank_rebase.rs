@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::utils::*;
+use super::apply_rebase::emit_rebase;
+
+#[derive(Accounts)]
+pub struct CrankRebase<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+}
+
+/// Permissionless `apply_rebase`. Succeeds without changes when the vault does not
+/// need a rebase, so keepers can send it on a schedule
+pub fn crank_rebase(
+    ctx: Context<CrankRebase>,
+) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let (shares_base_before, total_shares_before) = (vault.shares_base, vault.total_shares);
+    
+    match vault.apply_rebase()? {
+        Some(rebase_divisor) => {
+            emit_rebase(vault, shares_base_before, total_shares_before, get_current_timestamp());
+            msg!(
+                "Rebase cranked: divisor={}, rebase_version={}",
+                rebase_divisor,
+                vault.rebase_version
+            );
+        }
+        None => {
+            msg!("No rebase needed, rebase_version={}", vault.rebase_version);
+        }
+    }
+    
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::events::PerformanceFeeCrystallizedEvent;
+use crate::utils::*;
+use super::accrue_rewards::accrue_vested_rewards;
+
+#[derive(Accounts)]
+pub struct CrystallizeFees<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+}
+
+/// Permissionless performance fee crystallization, after releasing vested rewards.
+/// Succeeds without changes while the share value is at or below the high-water mark
+pub fn crystallize_fees(
+    ctx: Context<CrystallizeFees>,
+) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let timestamp = get_current_timestamp();
+    
+    accrue_vested_rewards(vault, timestamp)?;
+    let fee_shares = crystallize_performance_fee(vault, timestamp)?;
+    msg!(
+        "Crystallized {} performance fee shares, high-water mark {}",
+        fee_shares,
+        vault.high_water_mark
+    );
+    
+    Ok(())
+}
+
+/// Charge the performance fee on the gain since the last crystallization and emit it.
+/// Returns the fee shares added to `owner_shares`
+pub(crate) fn crystallize_performance_fee(vault: &mut Account<Vault>, timestamp: i64) -> Result<u64> {
+    let (fee_shares, fee_amount) = vault.crystallize_performance_fee()?;
+    if fee_shares > 0 {
+        emit!(PerformanceFeeCrystallizedEvent {
+            vault: vault.key(),
+            fee_shares,
+            fee_amount,
+            high_water_mark: vault.high_water_mark,
+            timestamp,
+        });
+    }
+    
+    Ok(fee_shares)
+}
@@ -0,0 +1,91 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::state::*;
+use crate::constants::PAUSE_CANCEL;
+use crate::error::*;
+use crate::events::UnstakeRequestExpiredEvent;
+use crate::utils::*;
+use super::share_tokens::{settle_share_tokens, ShareTokenAccounts};
+use super::accrue_rewards::accrue_vested_rewards;
+use super::sync_rebase::sync_depositor_rebase;
+
+#[derive(Accounts)]
+pub struct ExpireUnstakeRequest<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault_depositor", vault.key().as_ref(), vault_depositor.authority.as_ref()],
+        bump,
+        constraint = vault_depositor.vault == vault.key() @ VaultError::InvalidVaultConfig,
+    )]
+    pub vault_depositor: Account<'info, VaultDepositor>,
+    
+    /// Share mint of a tokenized vault, required only when `vault.share_mint` is set
+    #[account(mut)]
+    pub share_mint: Option<InterfaceAccount<'info, Mint>>,
+    
+    /// The depositor's token account of `share_mint`
+    #[account(mut)]
+    pub depositor_share_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// Required with `share_mint`
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+/// Permissionless crank restoring a matured unstake request that nobody collected within
+/// `vault.unstake_request_expiry`, so its reserved assets earn again
+pub fn expire_unstake_request(
+    ctx: Context<ExpireUnstakeRequest>,
+) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let vault_depositor = &mut ctx.accounts.vault_depositor;
+    let timestamp = get_current_timestamp();
+    
+    vault.check_not_paused(PAUSE_CANCEL)?;
+    
+    accrue_vested_rewards(vault, timestamp)?;
+    
+    // Restored shares are minted in the current base, the position must be in it too
+    sync_depositor_rebase(vault, vault_depositor, timestamp)?;
+    
+    // Same accounting as cancel_unstake_request: the depositor gets active shares worth exactly
+    // the frozen amount, nothing is lost and nobody else's share value moves
+    let matured_at = vault_depositor.unstake_request.request_time.saturating_add(vault.unstake_lockup_period);
+    let shares_before = vault_depositor.shares;
+    let (frozen_amount, restored_shares) = vault_depositor.expire_unstake(vault, timestamp)?;
+    vault.record_active_shares_change(shares_before, vault_depositor.shares);
+    
+    settle_share_tokens(
+        vault,
+        ShareTokenAccounts {
+            share_mint: ctx.accounts.share_mint.as_mut(),
+            share_account: ctx.accounts.depositor_share_account.as_ref(),
+            token_program: ctx.accounts.token_program.as_ref(),
+        },
+        vault_depositor.authority,
+        &vault_depositor.to_account_info(),
+        0,
+        restored_shares,
+    )?;
+    
+    emit!(UnstakeRequestExpiredEvent {
+        vault: vault.key(),
+        authority: vault_depositor.authority,
+        amount: frozen_amount,
+        shares: restored_shares,
+        matured_at,
+        active_share_value: vault.active_share_value_or_zero()?,
+        timestamp,
+    });
+    
+    msg!(
+        "Expired unstake request of {} restored, {} frozen assets as {} shares",
+        vault_depositor.authority,
+        frozen_amount,
+        restored_shares
+    );
+    
+    Ok(())
+}
@@ -0,0 +1,146 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, CloseAccount, Mint, TokenAccount, TokenInterface};
+use crate::state::*;
+use crate::error::*;
+use crate::events::VaultDecommissionedEvent;
+use crate::utils::*;
+use crate::math::SafeMath;
+use super::registry::unregister_vault;
+use super::token_transfers::{harvest_withheld_fees, transfer_tokens};
+
+#[derive(Accounts)]
+pub struct FinalizeDecommission<'info> {
+    #[account(
+        mut,
+        constraint = vault.owner == owner.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault_token_account", vault.key().as_ref()],
+        bump,
+        constraint = vault_token_account.key() == vault.vault_token_account @ VaultError::InvalidTokenAccount,
+        constraint = vault_token_account.owner == vault.key() @ VaultError::InvalidTokenAccount,
+        constraint = vault_token_account.mint == vault.token_mint @ VaultError::InvalidTokenAccount,
+        constraint = !vault_token_account.is_frozen() @ VaultError::TokenAccountFrozen,
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    /// Receives any rounding dust left in the vault token account
+    #[account(
+        mut,
+        constraint = owner_token_account.key() != vault_token_account.key() @ VaultError::InvalidTokenAccount,
+        constraint = owner_token_account.mint == vault.token_mint @ VaultError::InvalidTokenMint,
+        constraint = owner_token_account.owner == owner.key() @ VaultError::Unauthorized,
+    )]
+    pub owner_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    /// Writable so transfer fees withheld in the vault token account can be harvested into it
+    #[account(
+        mut,
+        constraint = token_mint.key() == vault.token_mint @ VaultError::InvalidTokenMint,
+    )]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+    
+    /// The vault's lookup entry, closed together with the vault when supplied
+    #[account(
+        mut,
+        constraint = registry_entry.vault == vault.key() @ VaultError::InvalidVaultConfig,
+    )]
+    pub registry_entry: Option<Account<'info, VaultRegistryEntry>>,
+    
+    /// The registry page listing the vault, which drops it when supplied
+    #[account(
+        mut,
+        seeds = [b"vault_registry", vault_registry.page.to_le_bytes().as_ref()],
+        bump = vault_registry.bump,
+    )]
+    pub vault_registry: Option<Account<'info, VaultRegistry>>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    /// Receives the rent of every closed account, e.g. a protocol treasury (default: owner)
+    #[account(mut)]
+    pub rent_refund_destination: Option<SystemAccount<'info>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Close a decommissioning vault once every share but the dead shares has exited
+pub fn finalize_decommission(
+    ctx: Context<FinalizeDecommission>,
+) -> Result<()> {
+    let vault = &ctx.accounts.vault;
+    
+    if !vault.is_decommissioning() {
+        return Err(VaultError::VaultNotDecommissioning.into());
+    }
+    if vault.total_shares != vault.dead_shares || vault.reserved_assets != 0 {
+        return Err(VaultError::VaultNotEmpty.into());
+    }
+    
+    let signer_seeds: &[&[&[u8]]] = &[&vault.get_signer_seeds()];
+    
+    // Sweep rounding dust and the dead shares' assets so the token account can be closed
+    let swept_amount = ctx.accounts.vault_token_account.amount;
+    if swept_amount > 0 {
+        transfer_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.token_mint,
+            ctx.accounts.vault_token_account.to_account_info(),
+            &mut ctx.accounts.owner_token_account,
+            vault.to_account_info(),
+            signer_seeds,
+            swept_amount,
+        )?;
+    }
+    
+    // Transfer fees withheld from stakes and rewards would keep the account from closing
+    harvest_withheld_fees(&ctx.accounts.token_program, &ctx.accounts.token_mint, &ctx.accounts.vault_token_account)?;
+    
+    let rent_refund_destination = match &ctx.accounts.rent_refund_destination {
+        Some(destination) => destination.to_account_info(),
+        None => ctx.accounts.owner.to_account_info(),
+    };
+    
+    // Rent of every account closed below, reported in the event
+    let rent_refunded = ctx.accounts.vault_token_account.to_account_info().lamports()
+        .safe_add(vault.to_account_info().lamports())?
+        .safe_add(ctx.accounts.registry_entry.as_ref().map_or(0, |entry| entry.to_account_info().lamports()))?;
+    
+    let close_accounts = CloseAccount {
+        account: ctx.accounts.vault_token_account.to_account_info(),
+        destination: rent_refund_destination.clone(),
+        authority: vault.to_account_info(),
+    };
+    let close_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        close_accounts,
+        signer_seeds,
+    );
+    token_interface::close_account(close_ctx)?;
+    
+    // Closed by hand rather than with `close = owner` so the rent can go elsewhere
+    if let Some(registry_entry) = &ctx.accounts.registry_entry {
+        registry_entry.close(rent_refund_destination.clone())?;
+    }
+    vault.close(rent_refund_destination.clone())?;
+    if let Some(vault_registry) = ctx.accounts.vault_registry.as_mut() {
+        unregister_vault(vault_registry, vault.key())?;
+    }
+    
+    emit!(VaultDecommissionedEvent {
+        vault: vault.key(),
+        owner: ctx.accounts.owner.key(),
+        swept_amount,
+        rent_refund_destination: rent_refund_destination.key(),
+        rent_refunded,
+        timestamp: get_current_timestamp(),
+    });
+    
+    msg!("Vault decommissioned and closed: {}", vault.key());
+    
+    Ok(())
+}
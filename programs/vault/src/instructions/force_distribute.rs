@@ -0,0 +1,121 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::state::*;
+use crate::error::*;
+use crate::events::ForcedDistributionEvent;
+use crate::math::SafeMath;
+use crate::utils::*;
+use super::accrue_rewards::accrue_vested_rewards;
+use super::sync_rebase::sync_depositor_rebase;
+use super::token_transfers::transfer_tokens;
+
+#[derive(Accounts)]
+pub struct ForceDistribute<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault_depositor", vault.key().as_ref(), vault_depositor.authority.as_ref()],
+        bump,
+        constraint = vault_depositor.vault == vault.key() @ VaultError::InvalidVaultConfig,
+    )]
+    pub vault_depositor: Account<'info, VaultDepositor>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault_token_account", vault.key().as_ref()],
+        bump,
+        constraint = vault_token_account.key() == vault.vault_token_account @ VaultError::InvalidTokenAccount,
+        constraint = vault_token_account.owner == vault.key() @ VaultError::InvalidTokenAccount,
+        constraint = vault_token_account.mint == vault.token_mint @ VaultError::InvalidTokenAccount,
+        constraint = !vault_token_account.is_frozen() @ VaultError::TokenAccountFrozen,
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = depositor_token_account.key() != vault_token_account.key() @ VaultError::InvalidTokenAccount,
+        constraint = depositor_token_account.mint == vault.token_mint @ VaultError::InvalidTokenMint,
+        constraint = depositor_token_account.owner == vault_depositor.authority @ VaultError::Unauthorized,
+    )]
+    pub depositor_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(constraint = token_mint.key() == vault.token_mint @ VaultError::InvalidTokenMint)]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Permissionless crank paying out an unresponsive depositor's whole position
+/// once the forced distribution timeout of a decommissioning vault has passed
+pub fn force_distribute(
+    ctx: Context<ForceDistribute>,
+) -> Result<()> {
+    let current_time = get_current_timestamp();
+    
+    if !ctx.accounts.vault.is_decommissioning() {
+        return Err(VaultError::VaultNotDecommissioning.into());
+    }
+    if !ctx.accounts.vault.can_force_distribute(current_time) {
+        return Err(VaultError::DecommissionTimeoutNotReached.into());
+    }
+    
+    accrue_vested_rewards(&mut ctx.accounts.vault, current_time)?;
+    
+    // Unresponsive depositors are the ones most likely to lag behind a rebase, their shares
+    // are priced and released in the current base
+    sync_depositor_rebase(&mut ctx.accounts.vault, &mut ctx.accounts.vault_depositor, current_time)?;
+    
+    let active_shares = ctx.accounts.vault_depositor.shares;
+    let request = ctx.accounts.vault_depositor.unstake_request.clone();
+    let frozen_amount = request.frozen_amount()?;
+    
+    // The accrual and rebase sync above are the state the position is priced on, a failed
+    // transfer rolls them back. The position is only closed after the transfer
+    let amount = ctx.accounts.vault.force_exit_amount(active_shares, frozen_amount)?;
+    
+    if ctx.accounts.vault_token_account.amount < amount {
+        return Err(VaultError::InsufficientLiquidity.into());
+    }
+    
+    if amount > 0 {
+        let vault = &ctx.accounts.vault;
+        let signer_seeds: &[&[&[u8]]] = &[&vault.get_signer_seeds()];
+        transfer_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.token_mint,
+            ctx.accounts.vault_token_account.to_account_info(),
+            &mut ctx.accounts.depositor_token_account,
+            vault.to_account_info(),
+            signer_seeds,
+            amount,
+        )?;
+    }
+    
+    ctx.accounts.vault.force_exit(active_shares, request.shares, frozen_amount)?;
+    ctx.accounts.vault.record_active_shares_change(active_shares, 0);
+    
+    let vault_depositor = &mut ctx.accounts.vault_depositor;
+    // Secondary rewards earned so far stay claimable after the position is paid out
+    vault_depositor.settle_secondary_rewards(ctx.accounts.vault.secondary_rewards_per_share)?;
+    let released = vault_depositor.release_cost_basis(active_shares, active_shares)?;
+    let released = released.safe_add(vault_depositor.unstake_cost_basis)?;
+    vault_depositor.shares = 0;
+    vault_depositor.unstake_request.reset();
+    vault_depositor.unstake_cost_basis = 0;
+    vault_depositor.total_unstaked = vault_depositor.total_unstaked.safe_add(amount)?;
+    vault_depositor.realize_pnl(amount, released)?;
+    
+    emit!(ForcedDistributionEvent {
+        vault: ctx.accounts.vault.key(),
+        authority: vault_depositor.authority,
+        amount,
+        shares: active_shares.safe_add(request.shares)?,
+        timestamp: current_time,
+    });
+    
+    msg!("Forced distribution paid {} tokens to {}", amount, vault_depositor.authority);
+    
+    Ok(())
+}
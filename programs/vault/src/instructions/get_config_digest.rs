@@ -0,0 +1,19 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct GetConfigDigest<'info> {
+    pub vault: Account<'info, Vault>,
+}
+
+/// Return the vault's configuration digest so change management can compare it
+/// against the digest of an approved proposal
+pub fn get_config_digest(
+    ctx: Context<GetConfigDigest>,
+) -> Result<[u8; 32]> {
+    let digest = ctx.accounts.vault.config_digest()?;
+    
+    msg!("Vault config digest: {}", anchor_lang::solana_program::hash::Hash::new_from_array(digest));
+    
+    Ok(digest)
+}
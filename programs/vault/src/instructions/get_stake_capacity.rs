@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::constants::PAUSE_STAKE;
+use crate::error::*;
+
+#[derive(Accounts)]
+pub struct GetStakeCapacity<'info> {
+    pub vault: Account<'info, Vault>,
+    
+    /// Depositor the minimum is computed for; omitted for wallets that never staked
+    #[account(
+        constraint = vault_depositor.vault == vault.key() @ VaultError::InvalidVaultConfig,
+    )]
+    pub vault_depositor: Option<Account<'info, VaultDepositor>>,
+}
+
+/// How much a depositor can stake right now, as checked by `Vault::stake`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct StakeCapacity {
+    /// max_total_assets - total_assets, saturating at zero
+    pub vault_remaining_capacity: u64,
+    /// max_stake_per_depositor - depositor_value, u64::MAX when there is no per-depositor cap
+    pub depositor_remaining_capacity: u64,
+    /// Smallest accepted stake, lowered by the depositor's existing position
+    pub min_stake_amount: u64,
+    /// Depositor's position value counted towards the minimum and the cap (0 until rebase-synced)
+    pub depositor_value: u64,
+    pub is_paused: bool,
+    pub is_decommissioning: bool,
+}
+
+/// Return the stake limits a UI needs before the user enters an amount
+pub fn get_stake_capacity(
+    ctx: Context<GetStakeCapacity>,
+) -> Result<StakeCapacity> {
+    let vault = &ctx.accounts.vault;
+    
+    let depositor_value = match &ctx.accounts.vault_depositor {
+        Some(depositor) => vault.depositor_stake_value(depositor)?,
+        None => 0,
+    };
+    
+    Ok(StakeCapacity {
+        vault_remaining_capacity: vault.remaining_capacity(),
+        depositor_remaining_capacity: vault.depositor_remaining_capacity(depositor_value),
+        min_stake_amount: vault.min_stake_for(depositor_value),
+        depositor_value,
+        is_paused: vault.is_operation_paused(PAUSE_STAKE),
+        is_decommissioning: vault.is_decommissioning(),
+    })
+}
@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct GetVaultStats<'info> {
+    pub vault: Account<'info, Vault>,
+}
+
+/// The vault's share accounting as the next instruction would see it
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct VaultStats {
+    pub total_assets: u64,
+    pub total_shares: u64,
+    pub active_shares: u64,
+    pub pending_unstake_shares: u64,
+    pub reserved_assets: u64,
+    pub unvested_rewards: u64,
+    /// Available assets per active share (scaled by PRECISION), 0 when they are worthless
+    pub active_share_value: u128,
+    pub shares_base: u32,
+    pub rebase_version: u32,
+}
+
+/// Return the share accounting with vested rewards released and a due rebase applied,
+/// without changing any account
+pub fn get_vault_stats(
+    ctx: Context<GetVaultStats>,
+) -> Result<VaultStats> {
+    let vault = ctx.accounts.vault.projected(get_current_timestamp())?;
+    
+    Ok(VaultStats {
+        total_assets: vault.total_assets,
+        total_shares: vault.total_shares,
+        active_shares: vault.get_active_shares()?,
+        pending_unstake_shares: vault.pending_unstake_shares,
+        reserved_assets: vault.reserved_assets,
+        unvested_rewards: vault.unvested_rewards,
+        active_share_value: vault.active_share_value_or_zero()?,
+        shares_base: vault.shares_base,
+        rebase_version: vault.rebase_version,
+    })
+}
@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::events::ProtocolConfigUpdatedEvent;
+use crate::utils::*;
+use super::attest_upgrade_authority::program_data_address;
+
+#[derive(Accounts)]
+pub struct InitializeProtocolConfig<'info> {
+    #[account(
+        init,
+        payer = upgrade_authority,
+        space = ProtocolConfig::LEN,
+        seeds = [b"protocol_config"],
+        bump
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    
+    /// Only the program's upgrade authority can create the config, so nobody can claim the
+    /// singleton first
+    #[account(
+        constraint = program_data.key() == program_data_address() @ VaultError::InvalidProgramData,
+        constraint = program_data.upgrade_authority_address == Some(upgrade_authority.key()) @ VaultError::Unauthorized,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+    
+    #[account(mut)]
+    pub upgrade_authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+/// Create the protocol config with `admin` as its admin. Unset params take the program defaults
+pub fn initialize_protocol_config(
+    ctx: Context<InitializeProtocolConfig>,
+    admin: Pubkey,
+    params: ProtocolConfigParams,
+) -> Result<()> {
+    let protocol_config = &mut ctx.accounts.protocol_config;
+    
+    protocol_config.initialize(admin, params, ctx.bumps.protocol_config)?;
+    
+    emit!(ProtocolConfigUpdatedEvent {
+        protocol_config: protocol_config.key(),
+        admin,
+        platform_account: protocol_config.platform_account,
+        default_unstake_lockup_period: protocol_config.default_unstake_lockup_period,
+        default_platform_share_bps: protocol_config.default_platform_share_bps,
+        max_platform_share_bps: protocol_config.max_platform_share_bps,
+        default_management_fee_bps: protocol_config.default_management_fee_bps,
+        max_management_fee_bps: protocol_config.max_management_fee_bps,
+        timestamp: get_current_timestamp(),
+    });
+    
+    msg!("Protocol config initialized: {}, admin {}", protocol_config.key(), admin);
+    
+    Ok(())
+}
@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct InitializeProtocolStats<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = ProtocolStats::LEN,
+        seeds = [b"protocol_stats"],
+        bump
+    )]
+    pub protocol_stats: Account<'info, ProtocolStats>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+pub fn initialize_protocol_stats(
+    ctx: Context<InitializeProtocolStats>,
+) -> Result<()> {
+    let protocol_stats = &mut ctx.accounts.protocol_stats;
+    protocol_stats.bump = ctx.bumps.protocol_stats;
+    
+    msg!("Protocol stats initialized: {}", protocol_stats.key());
+    
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+
+#[derive(Accounts)]
+#[instruction(page: u16)]
+pub struct InitializeRegistry<'info> {
+    #[account(
+        init,
+        payer = payer,
+        space = VaultRegistry::space(0),
+        seeds = [b"vault_registry", page.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub vault_registry: Account<'info, VaultRegistry>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+/// Create an empty registry page, grown by every `initialize_vault` registering on it
+pub fn initialize_registry(
+    ctx: Context<InitializeRegistry>,
+    page: u16,
+) -> Result<()> {
+    let vault_registry = &mut ctx.accounts.vault_registry;
+    vault_registry.page = page;
+    vault_registry.bump = ctx.bumps.vault_registry;
+    
+    msg!("Vault registry page {} initialized: {}", page, vault_registry.key());
+    
+    Ok(())
+}
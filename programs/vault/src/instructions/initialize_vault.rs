@@ -1,7 +1,12 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Mint, Token, TokenAccount};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 use crate::state::*;
 use crate::error::*;
+use crate::math::SafeMath;
+use crate::events::VaultInitializedEvent;
+use crate::utils::*;
+use super::registry::register_vault;
+use super::token_transfers::check_mint_extensions;
 
 #[derive(Accounts)]
 #[instruction(params: InitializeVaultParams)]
@@ -18,7 +23,8 @@ pub struct InitializeVault<'info> {
     #[account(mut)]
     pub owner: Signer<'info>,
     
-    pub token_mint: Account<'info, Mint>,
+    /// Underlying mint, of the original token program or Token-2022 with supported extensions
+    pub token_mint: InterfaceAccount<'info, Mint>,
     
     #[account(
         init,
@@ -28,10 +34,60 @@ pub struct InitializeVault<'info> {
         seeds = [b"vault_token_account", vault.key().as_ref()],
         bump
     )]
-    pub vault_token_account: Account<'info, TokenAccount>,
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
     
+    /// Optional protocol stats singleton, vault count is incremented when supplied
+    #[account(
+        mut,
+        seeds = [b"protocol_stats"],
+        bump = protocol_stats.bump,
+    )]
+    pub protocol_stats: Option<Account<'info, ProtocolStats>>,
+    
+    /// Optional lookup entry for finding the vault by owner and mint, at `params.registry_index`
+    #[account(
+        init,
+        payer = owner,
+        space = VaultRegistryEntry::LEN,
+        seeds = [
+            b"vault_by_mint",
+            owner.key().as_ref(),
+            token_mint.key().as_ref(),
+            &params.registry_index.unwrap_or_default().to_le_bytes(),
+        ],
+        bump
+    )]
+    pub registry_entry: Option<Account<'info, VaultRegistryEntry>>,
+    
+    /// Optional registry page the vault is listed on, grown by one entry if needed
+    #[account(
+        mut,
+        seeds = [b"vault_registry", vault_registry.page.to_le_bytes().as_ref()],
+        bump = vault_registry.bump,
+    )]
+    pub vault_registry: Option<Account<'info, VaultRegistry>>,
+    
+    /// Optional protocol config, filling unset params with its defaults and capping the fees
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+    )]
+    pub protocol_config: Option<Account<'info, ProtocolConfig>>,
     
-    pub token_program: Program<'info, Token>,
+    /// Optional share mint, supplying it makes the vault tokenized: depositors receive one
+    /// token per active share. Decimals and token program follow `token_mint`, the vault is
+    /// mint authority
+    #[account(
+        init,
+        payer = owner,
+        mint::decimals = token_mint.decimals,
+        mint::authority = vault,
+        seeds = [b"share_mint", vault.key().as_ref()],
+        bump
+    )]
+    pub share_mint: Option<InterfaceAccount<'info, Mint>>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
 }
@@ -40,25 +96,68 @@ pub fn initialize_vault(
     ctx: Context<InitializeVault>,
     params: InitializeVaultParams,
 ) -> Result<()> {
+    check_mint_extensions(&ctx.accounts.token_mint)?;
+    
     let vault = &mut ctx.accounts.vault;
     let vault_key = vault.key();
     
+    let mut vault_params = crate::state::vault::InitializeVaultParams {
+        unstake_lockup_period: params.unstake_lockup_period,
+        platform_share_bps: params.platform_share_bps,
+        min_stake_amount: params.min_stake_amount,
+        max_total_assets: params.max_total_assets,
+        management_fee_bps: params.management_fee_bps,
+        // Vaults created under the protocol config are always governed by it
+        protocol_governed: ctx.accounts.protocol_config.is_some() || params.protocol_governed.unwrap_or(false),
+    };
+    let platform_account = match ctx.accounts.protocol_config.as_ref() {
+        Some(protocol_config) => protocol_config.fill_vault_defaults(params.platform_account, &mut vault_params),
+        None => params.platform_account,
+    };
+    
     vault.initialize(
         params.name,
         vault_key,
         ctx.accounts.owner.key(),
-        params.platform_account,
+        platform_account,
         ctx.accounts.token_mint.key(),
         ctx.accounts.vault_token_account.key(),
-        crate::state::vault::InitializeVaultParams {
-            unstake_lockup_period: params.unstake_lockup_period,
-            management_fee: params.management_fee,
-            min_stake_amount: params.min_stake_amount,
-            max_total_assets: params.max_total_assets,
-        },
+        vault_params,
         ctx.bumps.vault,
     )?;
     
+    if let Some(protocol_config) = ctx.accounts.protocol_config.as_ref() {
+        protocol_config.check_vault_caps(vault.platform_share_bps, vault.management_fee_bps)?;
+    }
+    
+    if let Some(share_mint) = ctx.accounts.share_mint.as_ref() {
+        vault.share_mint = share_mint.key();
+        msg!("Shares tokenized with mint {}", share_mint.key());
+    }
+    
+    if let Some(protocol_stats) = ctx.accounts.protocol_stats.as_mut() {
+        protocol_stats.total_vaults = protocol_stats.total_vaults.safe_add(1)?;
+    }
+    
+    if let Some(registry_entry) = ctx.accounts.registry_entry.as_mut() {
+        registry_entry.vault = vault_key;
+        registry_entry.owner = ctx.accounts.owner.key();
+        registry_entry.token_mint = ctx.accounts.token_mint.key();
+        registry_entry.index = params.registry_index.unwrap_or_default();
+        registry_entry.bump = ctx.bumps.registry_entry.unwrap_or_default();
+    }
+    
+    if let Some(vault_registry) = ctx.accounts.vault_registry.as_mut() {
+        register_vault(vault_registry, vault_key, &ctx.accounts.owner, &ctx.accounts.system_program)?;
+    }
+    
+    emit!(VaultInitializedEvent {
+        vault: vault_key,
+        owner: ctx.accounts.owner.key(),
+        token_mint: ctx.accounts.token_mint.key(),
+        timestamp: get_current_timestamp(),
+    });
+    
     msg!("Vault initialized: {}", vault.key());
     
     Ok(())
@@ -67,9 +166,17 @@ pub fn initialize_vault(
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
 pub struct InitializeVaultParams {
     pub name: [u8; 32],
+    /// Default pubkey = the protocol config's platform account, when it is supplied
     pub platform_account: Pubkey,
     pub unstake_lockup_period: Option<i64>,
-    pub management_fee: Option<u64>,
+    pub platform_share_bps: Option<u64>,
     pub min_stake_amount: Option<u64>,
     pub max_total_assets: Option<u64>,
+    /// Annual fee on assets, stored for a later time-based accrual (default 0)
+    pub management_fee_bps: Option<u64>,
+    /// Index of the `registry_entry` among the owner's vaults of this mint (default 0)
+    pub registry_index: Option<u16>,
+    /// Let the protocol config admin lower the vault's fees and pause its deposits, implied
+    /// when `protocol_config` is supplied (default false)
+    pub protocol_governed: Option<bool>,
 }
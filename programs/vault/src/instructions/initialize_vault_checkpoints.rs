@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct InitializeVaultCheckpoints<'info> {
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        init,
+        payer = payer,
+        space = VaultCheckpoints::LEN,
+        seeds = [b"vault_checkpoints", vault.key().as_ref()],
+        bump
+    )]
+    pub vault_checkpoints: Account<'info, VaultCheckpoints>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+/// Create the optional checkpoint history of a vault. Anyone may pay for it
+pub fn initialize_vault_checkpoints(
+    ctx: Context<InitializeVaultCheckpoints>,
+) -> Result<()> {
+    let vault_checkpoints = &mut ctx.accounts.vault_checkpoints;
+    vault_checkpoints.vault = ctx.accounts.vault.key();
+    vault_checkpoints.bump = ctx.bumps.vault_checkpoints;
+    
+    msg!("Checkpoints for vault {} initialized: {}", vault_checkpoints.vault, vault_checkpoints.key());
+    
+    Ok(())
+}
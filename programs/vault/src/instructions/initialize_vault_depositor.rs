@@ -1,20 +1,28 @@
 use anchor_lang::prelude::*;
 use crate::state::*;
+use crate::constants::PAUSE_INIT_DEPOSITOR;
 use crate::error::*;
+use crate::events::DepositorInitializedEvent;
+use crate::utils::*;
 
 #[derive(Accounts)]
 pub struct InitializeVaultDepositor<'info> {
+    /// Writable for `depositor_count`
+    #[account(mut)]
     pub vault: Account<'info, Vault>,
     
     #[account(
         init,
         payer = authority,
-        space = VaultDepositor::LEN,
+        space = VaultDepositor::LEN_V2,
         seeds = [b"vault_depositor", vault.key().as_ref(), authority.key().as_ref()],
         bump
     )]
     pub vault_depositor: Account<'info, VaultDepositor>,
     
+    /// Allowlist marker for `authority`, required only while the vault is permissioned
+    pub allowed_depositor: Option<Account<'info, AllowedDepositor>>,
+    
     #[account(mut)]
     pub authority: Signer<'info>,
     
@@ -25,12 +33,29 @@ pub struct InitializeVaultDepositor<'info> {
 pub fn initialize_vault_depositor(
     ctx: Context<InitializeVaultDepositor>,
 ) -> Result<()> {
+    ctx.accounts.vault.check_not_paused(PAUSE_INIT_DEPOSITOR)?;
+    ctx.accounts.vault.check_depositor_allowed(
+        ctx.accounts.vault.key(),
+        ctx.accounts.authority.key(),
+        ctx.accounts.allowed_depositor.as_deref(),
+    )?;
+    
     let vault_depositor = &mut ctx.accounts.vault_depositor;
     
     vault_depositor.initialize(
         ctx.accounts.vault.key(),
         ctx.accounts.authority.key(),
     )?;
+    // Shares minted from now on are already in the vault's current rebase units
+    vault_depositor.last_rebase_version = ctx.accounts.vault.rebase_version;
+    vault_depositor.last_shares_base = ctx.accounts.vault.shares_base;
+    ctx.accounts.vault.record_depositor_opened()?;
+    
+    emit!(DepositorInitializedEvent {
+        vault: ctx.accounts.vault.key(),
+        authority: ctx.accounts.authority.key(),
+        timestamp: get_current_timestamp(),
+    });
     
     msg!("Vault depositor initialized: {}", vault_depositor.key());
     
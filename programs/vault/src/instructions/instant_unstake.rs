@@ -0,0 +1,170 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::state::*;
+use crate::constants::{PAUSE_REQUEST_UNSTAKE, PAUSE_UNSTAKE};
+use crate::error::*;
+use crate::events::InstantUnstakeEvent;
+use crate::utils::*;
+use super::share_tokens::{settle_share_tokens, ShareTokenAccounts};
+use super::accrue_rewards::accrue_vested_rewards;
+use super::sync_rebase::sync_depositor_rebase;
+use super::token_transfers::transfer_tokens;
+
+#[derive(Accounts)]
+pub struct InstantUnstake<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault_depositor", vault.key().as_ref(), authority.key().as_ref()],
+        bump,
+        constraint = vault_depositor.authority == authority.key() @ VaultError::Unauthorized,
+        constraint = vault_depositor.vault == vault.key() @ VaultError::InvalidVaultConfig,
+    )]
+    pub vault_depositor: Account<'info, VaultDepositor>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault_token_account", vault.key().as_ref()],
+        bump,
+        constraint = vault_token_account.key() == vault.vault_token_account @ VaultError::InvalidTokenAccount,
+        constraint = vault_token_account.owner == vault.key() @ VaultError::InvalidTokenAccount,
+        constraint = vault_token_account.mint == vault.token_mint @ VaultError::InvalidTokenAccount,
+        constraint = !vault_token_account.is_frozen() @ VaultError::TokenAccountFrozen,
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = user_token_account.key() != vault_token_account.key() @ VaultError::InvalidTokenAccount,
+        constraint = user_token_account.mint == vault.token_mint @ VaultError::InvalidTokenMint,
+        constraint = user_token_account.owner == authority.key() @ VaultError::Unauthorized,
+    )]
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(constraint = token_mint.key() == vault.token_mint @ VaultError::InvalidTokenMint)]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+    
+    /// Share mint of a tokenized vault, required only when `vault.share_mint` is set
+    #[account(mut)]
+    pub share_mint: Option<InterfaceAccount<'info, Mint>>,
+    
+    /// `authority`'s token account of `share_mint`
+    #[account(mut)]
+    pub user_share_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    pub authority: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Exit exactly `amount` assets without waiting for the lockup. The penalty stays in the
+/// vault for the remaining stakers; frozen requests are not touched
+pub fn instant_unstake(
+    ctx: Context<InstantUnstake>,
+    amount: u64,
+    client_id: [u8; 16],
+) -> Result<()> {
+    // An overflowed amount must not exit the whole position, that is `instant_unstake_all`
+    if amount == u64::MAX {
+        return Err(VaultError::AmbiguousMaxAmount.into());
+    }
+    
+    instant_unstake_inner(ctx, Some(amount), client_id)?;
+    Ok(())
+}
+
+/// Exit every active share without waiting for the lockup. Fails with `SlippageExceeded`
+/// when the user would receive less than `min_amount_out` (0 = any), after the penalty and
+/// any transfer fee
+pub fn instant_unstake_all(
+    ctx: Context<InstantUnstake>,
+    min_amount_out: u64,
+    client_id: [u8; 16],
+) -> Result<()> {
+    let received = instant_unstake_inner(ctx, None, client_id)?;
+    check_min_out(received, min_amount_out)
+}
+
+/// Exits exactly `amount` assets, or every active share without one. Returns what the user
+/// received
+fn instant_unstake_inner(
+    ctx: Context<InstantUnstake>,
+    amount: Option<u64>,
+    client_id: [u8; 16],
+) -> Result<u64> {
+    let current_time = get_current_timestamp();
+    
+    ctx.accounts.vault.check_not_paused(PAUSE_REQUEST_UNSTAKE | PAUSE_UNSTAKE)?;
+    
+    accrue_vested_rewards(&mut ctx.accounts.vault, current_time)?;
+    sync_depositor_rebase(&mut ctx.accounts.vault, &mut ctx.accounts.vault_depositor, current_time)?;
+    
+    let vault_depositor = &ctx.accounts.vault_depositor;
+    let (shares, payout, penalty) = match amount {
+        Some(amount) => vault_depositor.quote_instant_unstake(&ctx.accounts.vault, amount, current_time)?,
+        None => vault_depositor.quote_instant_unstake_all(&ctx.accounts.vault, current_time)?,
+    };
+    
+    // Assets reserved for pending requests and rewards still vesting are not available to
+    // instant exits
+    let free_balance = ctx.accounts.vault_token_account.amount
+        .saturating_sub(ctx.accounts.vault.reserved_assets)
+        .saturating_sub(ctx.accounts.vault.unvested_rewards);
+    if free_balance < payout {
+        return Err(VaultError::InsufficientLiquidity.into());
+    }
+    
+    // The accrual and rebase sync above only bring the vault up to date for the quote, a
+    // failed transfer rolls them back with the rest of the instruction. The exit itself is
+    // recorded after the transfer
+    let received = if payout > 0 {
+        let vault = &ctx.accounts.vault;
+        let signer_seeds: &[&[&[u8]]] = &[&vault.get_signer_seeds()];
+        transfer_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.token_mint,
+            ctx.accounts.vault_token_account.to_account_info(),
+            &mut ctx.accounts.user_token_account,
+            vault.to_account_info(),
+            signer_seeds,
+            payout,
+        )?
+    } else {
+        0
+    };
+    
+    let vault = &mut ctx.accounts.vault;
+    let shares_before = ctx.accounts.vault_depositor.shares;
+    ctx.accounts.vault_depositor.execute_instant_unstake(vault, shares, payout)?;
+    vault.record_active_shares_change(shares_before, ctx.accounts.vault_depositor.shares);
+    
+    settle_share_tokens(
+        vault,
+        ShareTokenAccounts {
+            share_mint: ctx.accounts.share_mint.as_mut(),
+            share_account: ctx.accounts.user_share_account.as_ref(),
+            token_program: Some(&ctx.accounts.token_program),
+        },
+        ctx.accounts.authority.key(),
+        &ctx.accounts.authority.to_account_info(),
+        shares,
+        0,
+    )?;
+    
+    emit!(InstantUnstakeEvent {
+        vault: vault.key(),
+        authority: ctx.accounts.authority.key(),
+        amount: payout,
+        penalty,
+        shares,
+        active_share_value: vault.active_share_value_or_zero()?,
+        client_id,
+        timestamp: current_time,
+    });
+    
+    msg!("Instant unstake burned {} shares, paid {} tokens, left {} penalty in the vault", shares, payout, penalty);
+    
+    Ok(received)
+}
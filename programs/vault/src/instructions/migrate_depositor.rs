@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::constants::DEPOSITOR_VERSION;
+use crate::error::*;
+use crate::events::DepositorMigratedEvent;
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct MigrateDepositor<'info> {
+    pub vault: Account<'info, Vault>,
+    
+    /// Any of the authority's depositors, its personal one or the owner fee depositor
+    #[account(
+        mut,
+        realloc = VaultDepositor::LEN_V2,
+        realloc::payer = authority,
+        realloc::zero = true,
+        constraint = vault_depositor.authority == authority.key() @ VaultError::Unauthorized,
+        constraint = vault_depositor.vault == vault.key() @ VaultError::InvalidVaultConfig,
+    )]
+    pub vault_depositor: Account<'info, VaultDepositor>,
+    
+    /// Pays the rent of the added bytes
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+/// Grow a depositor created at `VaultDepositor::LEN` to `LEN_V2`, the added bytes zeroed, and
+/// stamp `DEPOSITOR_VERSION`. Shares and a pending unstake request are left as they are.
+/// A depositor synced with the latest rebase gets its `last_shares_base` filled in, without it
+/// the next rebase would divide its shares by the earlier ones again. Safe to call again
+pub fn migrate_depositor(
+    ctx: Context<MigrateDepositor>,
+) -> Result<()> {
+    let vault_depositor = &mut ctx.accounts.vault_depositor;
+    
+    if vault_depositor.backfill_shares_base(&ctx.accounts.vault) {
+        msg!("Shares base set to {}", vault_depositor.last_shares_base);
+    } else {
+        msg!("Depositor is behind rebase version {}, shares base left unset", ctx.accounts.vault.rebase_version);
+    }
+    
+    let from_version = vault_depositor.version;
+    if vault_depositor.has_v2_layout() {
+        msg!("Depositor already at layout version {}", from_version);
+        return Ok(());
+    }
+    vault_depositor.version = DEPOSITOR_VERSION;
+    
+    emit!(DepositorMigratedEvent {
+        vault: vault_depositor.vault,
+        authority: vault_depositor.authority,
+        vault_depositor: vault_depositor.key(),
+        from_version,
+        to_version: DEPOSITOR_VERSION,
+        timestamp: get_current_timestamp(),
+    });
+    
+    msg!("Depositor migrated from layout version {} to {}", from_version, DEPOSITOR_VERSION);
+    
+    Ok(())
+}
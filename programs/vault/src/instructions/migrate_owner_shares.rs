@@ -0,0 +1,73 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::math::SafeMath;
+use crate::constants::*;
+use crate::events::OwnerSharesMigratedEvent;
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct MigrateOwnerShares<'info> {
+    #[account(
+        mut,
+        constraint = vault.owner == owner.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    /// Owner-controlled depositor holding fee shares, seeded apart from the owner's
+    /// personal `vault_depositor` so both positions can coexist
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = VaultDepositor::LEN_V2,
+        seeds = [b"owner_fee_depositor", vault.key().as_ref()],
+        bump
+    )]
+    pub owner_fee_depositor: Account<'info, VaultDepositor>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+/// Move the vault-level `owner_shares` counter into the owner fee depositor
+pub fn migrate_owner_shares(
+    ctx: Context<MigrateOwnerShares>,
+) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let owner_fee_depositor = &mut ctx.accounts.owner_fee_depositor;
+    
+    if owner_fee_depositor.vault == Pubkey::default() {
+        owner_fee_depositor.initialize(vault.key(), vault.owner)?;
+        owner_fee_depositor.last_rebase_version = vault.rebase_version;
+        owner_fee_depositor.last_shares_base = vault.shares_base;
+    }
+    
+    if owner_fee_depositor.vault != vault.key() || owner_fee_depositor.authority != vault.owner {
+        return Err(VaultError::Unauthorized.into());
+    }
+    
+    let migrated_shares = vault.owner_shares;
+    owner_fee_depositor.settle_secondary_rewards(vault.secondary_rewards_per_share)?;
+    owner_fee_depositor.shares = owner_fee_depositor.shares.safe_add(migrated_shares)?;
+    vault.owner_shares = 0;
+    vault.version = VAULT_VERSION;
+    
+    vault.verify_invariants()?;
+    
+    emit!(OwnerSharesMigratedEvent {
+        vault: vault.key(),
+        owner: ctx.accounts.owner.key(),
+        shares: migrated_shares,
+        timestamp: get_current_timestamp(),
+    });
+    
+    msg!(
+        "Migrated {} owner shares into fee depositor {}",
+        migrated_shares,
+        owner_fee_depositor.key()
+    );
+    
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+use crate::state::*;
+use crate::error::*;
+use crate::events::VaultMigratedEvent;
+use crate::utils::*;
+
+/// Byte offset of `Vault::owner`: discriminator, name, pubkey
+const VAULT_OWNER_OFFSET: usize = 8 + 32 + 32;
+
+#[derive(Accounts)]
+pub struct MigrateVault<'info> {
+    /// CHECK: an account created by an older program can be shorter than `Vault::LEN` and does
+    /// not deserialize, so the discriminator and owner are checked by hand in the handler
+    #[account(mut, owner = crate::ID @ VaultError::InvalidVaultConfig)]
+    pub vault: UncheckedAccount<'info>,
+    
+    /// Pays the rent of the added bytes
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+/// Grow the vault account to `Vault::LEN`, the added fields reading as zero, and move it to
+/// `VAULT_VERSION`. Safe to call again, a current account is left as it is
+pub fn migrate_vault(
+    ctx: Context<MigrateVault>,
+) -> Result<()> {
+    let vault_info = ctx.accounts.vault.to_account_info();
+    let old_len = vault_info.data_len();
+    {
+        let data = vault_info.try_borrow_data()?;
+        if old_len < VAULT_OWNER_OFFSET + 32 || !data.starts_with(Vault::DISCRIMINATOR) {
+            return Err(VaultError::InvalidVaultConfig.into());
+        }
+        if data[VAULT_OWNER_OFFSET..VAULT_OWNER_OFFSET + 32] != ctx.accounts.owner.key().to_bytes() {
+            return Err(VaultError::Unauthorized.into());
+        }
+    }
+    
+    if old_len < Vault::LEN {
+        let rent_due = Rent::get()?.minimum_balance(Vault::LEN).saturating_sub(vault_info.lamports());
+        if rent_due > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.owner.to_account_info(),
+                        to: vault_info.clone(),
+                    },
+                ),
+                rent_due,
+            )?;
+        }
+        vault_info.resize(Vault::LEN)?;
+    }
+    
+    let mut vault = Vault::try_deserialize(&mut &vault_info.try_borrow_data()?[..])?;
+    let from_version = vault.migrate_layout()?;
+    vault.verify_invariants()?;
+    vault.try_serialize(&mut &mut vault_info.try_borrow_mut_data()?[..])?;
+    
+    emit!(VaultMigratedEvent {
+        vault: vault_info.key(),
+        owner: ctx.accounts.owner.key(),
+        from_version,
+        to_version: vault.version,
+        old_len: old_len as u32,
+        new_len: Vault::LEN as u32,
+        timestamp: get_current_timestamp(),
+    });
+    
+    msg!(
+        "Vault migrated from version {} ({} bytes) to version {} ({} bytes)",
+        from_version,
+        old_len,
+        vault.version,
+        Vault::LEN
+    );
+    
+    Ok(())
+}
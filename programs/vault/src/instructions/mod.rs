@@ -8,6 +8,64 @@ pub mod add_rewards;
 pub mod update_vault_config;
 pub mod apply_rebase;
 pub mod sync_rebase;
+pub mod initialize_protocol_stats;
+pub mod roll_up_vault_stats;
+pub mod begin_decommission;
+pub mod force_distribute;
+pub mod finalize_decommission;
+pub mod migrate_owner_shares;
+pub mod migrate_vault;
+pub mod migrate_depositor;
+pub mod repair_bump;
+pub mod sync_rebase_batch;
+pub mod process_matured_unstakes;
+pub mod initialize_vault_checkpoints;
+pub mod record_checkpoint;
+pub mod get_config_digest;
+pub mod stake_for;
+pub mod get_stake_capacity;
+pub mod preview_stake;
+pub mod preview_unstake;
+pub mod get_vault_stats;
+pub mod instant_unstake;
+pub mod propose_owner;
+pub mod accept_ownership;
+pub mod cancel_owner_transfer;
+pub mod withdraw_owner_shares;
+pub mod attest_upgrade_authority;
+pub mod crank_rebase;
+pub mod add_allowed_depositor;
+pub mod remove_allowed_depositor;
+pub mod stake_with_term;
+pub mod register_program_reward_source;
+pub mod remove_program_reward_source;
+pub mod transfer_shares;
+pub mod accrue_rewards;
+pub mod crystallize_fees;
+pub mod expire_unstake_request;
+pub mod recover_tokens;
+pub mod skim;
+pub mod reconcile;
+pub mod propose_config_update;
+pub mod commit_config_update;
+pub mod cancel_config_update;
+pub mod set_secondary_reward_mint;
+pub mod add_secondary_rewards;
+pub mod claim_secondary_rewards;
+pub mod propose_platform_account;
+pub mod accept_platform_account;
+pub mod cancel_platform_account_proposal;
+pub mod set_platform_cosign_required;
+pub mod initialize_registry;
+pub mod remove_from_registry;
+pub mod initialize_protocol_config;
+pub mod update_protocol_config;
+pub mod propose_protocol_admin;
+pub mod accept_protocol_admin;
+pub mod override_vault_fees;
+pub(crate) mod registry;
+pub(crate) mod share_tokens;
+pub(crate) mod token_transfers;
 
 pub use initialize_vault::*;
 pub use initialize_vault_depositor::*;
@@ -18,4 +76,59 @@ pub use cancel_unstake_request::*;
 pub use add_rewards::*;
 pub use update_vault_config::*;
 pub use apply_rebase::*;
-pub use sync_rebase::*;
\ No newline at end of file
+pub use sync_rebase::*;
+pub use initialize_protocol_stats::*;
+pub use roll_up_vault_stats::*;
+pub use begin_decommission::*;
+pub use force_distribute::*;
+pub use finalize_decommission::*;
+pub use migrate_owner_shares::*;
+pub use migrate_vault::*;
+pub use migrate_depositor::*;
+pub use repair_bump::*;
+pub use sync_rebase_batch::*;
+pub use process_matured_unstakes::*;
+pub use initialize_vault_checkpoints::*;
+pub use record_checkpoint::*;
+pub use get_config_digest::*;
+pub use stake_for::*;
+pub use get_stake_capacity::*;
+pub use preview_stake::*;
+pub use preview_unstake::*;
+pub use get_vault_stats::*;
+pub use instant_unstake::*;
+pub use propose_owner::*;
+pub use accept_ownership::*;
+pub use cancel_owner_transfer::*;
+pub use withdraw_owner_shares::*;
+pub use attest_upgrade_authority::*;
+pub use crank_rebase::*;
+pub use add_allowed_depositor::*;
+pub use remove_allowed_depositor::*;
+pub use stake_with_term::*;
+pub use register_program_reward_source::*;
+pub use remove_program_reward_source::*;
+pub use transfer_shares::*;
+pub use accrue_rewards::*;
+pub use crystallize_fees::*;
+pub use expire_unstake_request::*;
+pub use recover_tokens::*;
+pub use skim::*;
+pub use reconcile::*;
+pub use propose_config_update::*;
+pub use commit_config_update::*;
+pub use cancel_config_update::*;
+pub use set_secondary_reward_mint::*;
+pub use add_secondary_rewards::*;
+pub use claim_secondary_rewards::*;
+pub use propose_platform_account::*;
+pub use accept_platform_account::*;
+pub use cancel_platform_account_proposal::*;
+pub use set_platform_cosign_required::*;
+pub use initialize_registry::*;
+pub use remove_from_registry::*;
+pub use initialize_protocol_config::*;
+pub use update_protocol_config::*;
+pub use propose_protocol_admin::*;
+pub use accept_protocol_admin::*;
+pub use override_vault_fees::*;
\ No newline at end of file
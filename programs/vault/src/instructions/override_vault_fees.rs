@@ -0,0 +1,70 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::events::{ConfigUpdatedEvent, PausedOperationsUpdatedEvent, VaultFeesOverriddenEvent};
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct OverrideVaultFees<'info> {
+    #[account(
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = protocol_config.admin == admin.key() @ VaultError::Unauthorized
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    
+    pub admin: Signer<'info>,
+}
+
+/// Lower a protocol governed vault's fees and optionally pause its deposits (protocol admin only)
+pub fn override_vault_fees(
+    ctx: Context<OverrideVaultFees>,
+    params: FeeOverrideParams,
+) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let admin = ctx.accounts.admin.key();
+    let timestamp = get_current_timestamp();
+    
+    let old_digest = vault.config_digest()?;
+    let old_mask = vault.paused_mask();
+    vault.override_fees(&params)?;
+    let new_digest = vault.config_digest()?;
+    let new_mask = vault.paused_mask();
+    
+    // Same events as an owner update, so config watchers see the change either way
+    emit!(ConfigUpdatedEvent {
+        vault: vault.key(),
+        owner: vault.owner,
+        old_digest,
+        new_digest,
+        timestamp,
+    });
+    if new_mask != old_mask {
+        emit!(PausedOperationsUpdatedEvent {
+            vault: vault.key(),
+            owner: vault.owner,
+            old_mask,
+            new_mask,
+            timestamp,
+        });
+    }
+    
+    emit!(VaultFeesOverriddenEvent {
+        vault: vault.key(),
+        admin,
+        platform_share_bps: vault.platform_share_bps,
+        management_fee_bps: vault.management_fee_bps,
+        performance_fee_bps: vault.performance_fee_bps,
+        withdrawal_fee_bps: vault.withdrawal_fee_bps,
+        instant_unstake_fee_bps: vault.instant_unstake_fee_bps,
+        deposits_paused: params.pause_deposits,
+        timestamp,
+    });
+    
+    msg!("Vault fees overridden by protocol admin {}", admin);
+    
+    Ok(())
+}
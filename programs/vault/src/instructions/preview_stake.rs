@@ -0,0 +1,56 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct PreviewStake<'info> {
+    pub vault: Account<'info, Vault>,
+    
+    /// Depositor the minimum stake and the cap are checked for; omitted for new wallets
+    #[account(
+        constraint = vault_depositor.vault == vault.key() @ VaultError::InvalidVaultConfig,
+    )]
+    pub vault_depositor: Option<Account<'info, VaultDepositor>>,
+}
+
+/// What a stake of `amount` would mint right now, priced by `Vault::stake`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct StakePreview {
+    /// Shares credited to the depositor, in `shares_base`
+    pub shares: u64,
+    /// Part of `amount` withheld as deposit fee
+    pub deposit_fee: u64,
+    /// Active share value after the stake (scaled by PRECISION)
+    pub active_share_value: u128,
+    /// Share base after any rebase the stake would apply
+    pub shares_base: u32,
+}
+
+/// Simulate a stake of `amount` received tokens without changing any account. A mint with a
+/// transfer fee credits less than the amount sent, see `StakeEvent.amount`
+pub fn preview_stake(
+    ctx: Context<PreviewStake>,
+    amount: u64,
+) -> Result<StakePreview> {
+    let mut vault = ctx.accounts.vault.projected(get_current_timestamp())?;
+    
+    let depositor_value = match &ctx.accounts.vault_depositor {
+        Some(depositor) => {
+            let mut depositor = (**depositor).clone();
+            vault.sync_depositor(&mut depositor)?;
+            vault.depositor_stake_value(&depositor)?
+        }
+        None => 0,
+    };
+    
+    let deposit_fee = vault.deposit_fee(amount)?;
+    let shares = vault.stake(amount, depositor_value)?;
+    
+    Ok(StakePreview {
+        shares,
+        deposit_fee,
+        active_share_value: vault.active_share_value_or_zero()?,
+        shares_base: vault.shares_base,
+    })
+}
@@ -0,0 +1,47 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::math::SafeMath;
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct PreviewUnstake<'info> {
+    pub vault: Account<'info, Vault>,
+}
+
+/// What requesting `shares` would freeze and what their execution would pay today
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct UnstakePreview {
+    /// The requested shares in `shares_base`, fewer than asked when a rebase is due
+    pub shares: u64,
+    /// Assets frozen by the request, as `request_unstake_all` prices them
+    pub frozen_amount: u64,
+    /// Withdrawal fee charged when the request is executed now
+    pub withdrawal_fee: u64,
+    /// `frozen_amount` less `withdrawal_fee`
+    pub net_amount: u64,
+    /// Share base after any rebase the request would apply
+    pub shares_base: u32,
+}
+
+/// Price `shares` active shares, counted in the vault's current `shares_base`, without
+/// changing any account
+pub fn preview_unstake(
+    ctx: Context<PreviewUnstake>,
+    shares: u64,
+) -> Result<UnstakePreview> {
+    let stored_base = ctx.accounts.vault.shares_base;
+    let vault = ctx.accounts.vault.projected(get_current_timestamp())?;
+    
+    // A depositor's shares follow a due rebase before they are priced
+    let shares = shares.safe_div(10u64.pow(vault.shares_base.safe_sub(stored_base)?))?;
+    let (shares, frozen_amount) = vault.price_exit_all(shares)?;
+    let withdrawal_fee = vault.withdrawal_fee(frozen_amount)?;
+    
+    Ok(UnstakePreview {
+        shares,
+        frozen_amount,
+        withdrawal_fee,
+        net_amount: frozen_amount.safe_sub(withdrawal_fee)?,
+        shares_base: vault.shares_base,
+    })
+}
@@ -0,0 +1,154 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::state::*;
+use crate::constants::PAUSE_UNSTAKE;
+use crate::error::*;
+use crate::events::{UnstakeBatchProcessedEvent, UnstakeExecutedEvent};
+use crate::math::SafeMath;
+use crate::utils::*;
+use super::accrue_rewards::accrue_vested_rewards;
+use super::sync_rebase::sync_depositor_rebase;
+use super::token_transfers::transfer_tokens;
+
+/// Maximum (depositor, token account) pairs per batch; each payout costs roughly 20k CU
+/// (two deserializations, a `transfer_checked` CPI and the event), so 8 stays inside the
+/// default 200k CU budget
+pub const MAX_UNSTAKE_BATCH: usize = 8;
+
+#[derive(Accounts)]
+pub struct ProcessMaturedUnstakes<'info> {
+    #[account(
+        mut,
+        constraint = !vault.is_operation_paused(PAUSE_UNSTAKE) @ VaultError::WithdrawalsPaused,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_token_account", vault.key().as_ref()],
+        bump,
+        constraint = vault_token_account.key() == vault.vault_token_account @ VaultError::InvalidTokenAccount,
+        constraint = vault_token_account.owner == vault.key() @ VaultError::InvalidTokenAccount,
+        constraint = vault_token_account.mint == vault.token_mint @ VaultError::InvalidTokenAccount,
+        constraint = !vault_token_account.is_frozen() @ VaultError::TokenAccountFrozen,
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(constraint = token_mint.key() == vault.token_mint @ VaultError::InvalidTokenMint)]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Permissionless crank executing matured unstake requests for their depositors. Takes up to
+/// `MAX_UNSTAKE_BATCH` pairs of writable (VaultDepositor, depositor's token account) in
+/// `remaining_accounts` and pays each one exactly as `unstake` would. Requests that are not
+/// matured yet, token accounts that are frozen and payouts the vault cannot cover are skipped;
+/// accounts that do not belong together fail the batch
+pub fn process_matured_unstakes<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ProcessMaturedUnstakes<'info>>,
+) -> Result<()> {
+    let remaining = ctx.remaining_accounts;
+    if remaining.is_empty() || !remaining.len().is_multiple_of(2) || remaining.len() / 2 > MAX_UNSTAKE_BATCH {
+        return Err(VaultError::InvalidAmount.into());
+    }
+
+    let current_time = get_current_timestamp();
+    accrue_vested_rewards(&mut ctx.accounts.vault, current_time)?;
+    let lockup_period = ctx.accounts.vault.effective_unstake_lockup(current_time);
+
+    let mut processed = Vec::with_capacity(remaining.len() / 2);
+    let mut skipped = Vec::new();
+
+    for pair in remaining.chunks(2) {
+        let (depositor_info, token_account_info) = (&pair[0], &pair[1]);
+        if !depositor_info.is_writable || !token_account_info.is_writable {
+            return Err(VaultError::InvalidVaultConfig.into());
+        }
+
+        let mut vault_depositor = Account::<VaultDepositor>::try_from(depositor_info)?;
+        if vault_depositor.vault != ctx.accounts.vault.key() {
+            return Err(VaultError::InvalidVaultConfig.into());
+        }
+
+        // The payout only ever goes to the depositor's own account of the underlying mint
+        let mut user_token_account = InterfaceAccount::<TokenAccount>::try_from(token_account_info)?;
+        if user_token_account.key() == ctx.accounts.vault_token_account.key() {
+            return Err(VaultError::InvalidTokenAccount.into());
+        }
+        if user_token_account.mint != ctx.accounts.vault.token_mint {
+            return Err(VaultError::InvalidTokenMint.into());
+        }
+        if user_token_account.owner != vault_depositor.authority {
+            return Err(VaultError::Unauthorized.into());
+        }
+
+        // The request's pending shares are released from the vault counters in the current base
+        let sync = sync_depositor_rebase(&mut ctx.accounts.vault, &mut vault_depositor, current_time)?;
+
+        let frozen_amount = vault_depositor.unstake_request.frozen_amount()?;
+        let withdrawal_fee = ctx.accounts.vault.withdrawal_fee(frozen_amount)?;
+        let amount = frozen_amount.safe_sub(withdrawal_fee)?;
+
+        if !vault_depositor.can_unstake(current_time, lockup_period)
+            || user_token_account.is_frozen()
+            || ctx.accounts.vault_token_account.amount < amount
+        {
+            if sync.synced {
+                vault_depositor.exit(ctx.program_id)?;
+            }
+            skipped.push(depositor_info.key());
+            continue;
+        }
+
+        // A failed transfer reverts the whole crank, the rebase sync above included. The
+        // request is only executed after the payout
+        let received = {
+            let vault = &ctx.accounts.vault;
+            let signer_seeds: &[&[&[u8]]] = &[&vault.get_signer_seeds()];
+            transfer_tokens(
+                &ctx.accounts.token_program,
+                &ctx.accounts.token_mint,
+                ctx.accounts.vault_token_account.to_account_info(),
+                &mut user_token_account,
+                vault.to_account_info(),
+                signer_seeds,
+                amount,
+            )?
+        };
+        ctx.accounts.vault_token_account.reload()?;
+
+        let vault = &mut ctx.accounts.vault;
+        let (shares, _) = vault_depositor.execute_unstake(vault, current_time)?;
+        vault_depositor.exit(ctx.program_id)?;
+
+        emit!(UnstakeExecutedEvent {
+            vault: vault.key(),
+            authority: vault_depositor.authority,
+            amount,
+            withdrawal_fee,
+            transfer_fee: amount.safe_sub(received)?,
+            shares,
+            active_share_value: vault.active_share_value_or_zero()?,
+            client_id: [0; 16],
+            timestamp: current_time,
+        });
+        processed.push(depositor_info.key());
+    }
+
+    msg!(
+        "Batch unstake: {} processed, {} skipped",
+        processed.len(),
+        skipped.len()
+    );
+
+    emit!(UnstakeBatchProcessedEvent {
+        vault: ctx.accounts.vault.key(),
+        processed,
+        skipped,
+        active_share_value: ctx.accounts.vault.active_share_value_or_zero()?,
+        timestamp: current_time,
+    });
+
+    Ok(())
+}
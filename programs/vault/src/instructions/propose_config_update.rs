@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::events::ConfigUpdateProposedEvent;
+use crate::math::SafeMath;
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct ProposeConfigUpdate<'info> {
+    #[account(
+        constraint = vault.owner == owner.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    /// Fails to initialize while another proposal is pending, cancel it first
+    #[account(
+        init,
+        payer = owner,
+        space = PendingConfigUpdate::LEN,
+        seeds = [b"pending_config_update", vault.key().as_ref()],
+        bump
+    )]
+    pub pending_config_update: Account<'info, PendingConfigUpdate>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+/// Stage a config update that `commit_config_update` applies after the vault's config
+/// timelock. Invalid params are rejected now rather than when the delay is over
+pub fn propose_config_update(
+    ctx: Context<ProposeConfigUpdate>,
+    params: UpdateVaultConfigParams,
+) -> Result<()> {
+    let vault = &ctx.accounts.vault;
+    let timestamp = get_current_timestamp();
+    
+    let mut preview: Vault = (**vault).clone();
+    preview.update_config(params.clone())?;
+    
+    let effective_at = timestamp.safe_add(vault.config_timelock_seconds)?;
+    
+    let pending = &mut ctx.accounts.pending_config_update;
+    pending.vault = vault.key();
+    pending.params = params;
+    pending.proposed_at = timestamp;
+    pending.effective_at = effective_at;
+    pending.bump = ctx.bumps.pending_config_update;
+    
+    emit!(ConfigUpdateProposedEvent {
+        vault: vault.key(),
+        owner: ctx.accounts.owner.key(),
+        current_digest: vault.config_digest()?,
+        proposed_digest: preview.config_digest()?,
+        effective_at,
+        timestamp,
+    });
+    
+    msg!("Config update proposed, effective at {}", effective_at);
+    
+    Ok(())
+}
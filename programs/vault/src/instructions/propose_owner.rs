@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::events::OwnershipTransferProposedEvent;
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct ProposeOwner<'info> {
+    #[account(
+        mut,
+        constraint = vault.owner == owner.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    pub owner: Signer<'info>,
+}
+
+/// Propose `new_owner`; ownership only moves once they sign `accept_ownership`
+pub fn propose_owner(
+    ctx: Context<ProposeOwner>,
+    new_owner: Pubkey,
+) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    
+    vault.propose_owner(new_owner)?;
+    
+    emit!(OwnershipTransferProposedEvent {
+        vault: vault.key(),
+        owner: vault.owner,
+        pending_owner: new_owner,
+        timestamp: get_current_timestamp(),
+    });
+    
+    msg!("Ownership transfer proposed to {}", new_owner);
+    
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::events::PlatformAccountProposedEvent;
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct ProposePlatformAccount<'info> {
+    #[account(
+        mut,
+        constraint = vault.owner == owner.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    pub owner: Signer<'info>,
+    
+    /// Current platform account, required as a co-signer once the platform turned on
+    /// `platform_cosign_required`
+    #[account(
+        constraint = platform.key() == vault.platform_account @ VaultError::Unauthorized
+    )]
+    pub platform: Option<Signer<'info>>,
+}
+
+/// Propose `new_platform_account`; rewards keep going to the current platform account until
+/// the new one signs `accept_platform_account`
+pub fn propose_platform_account(
+    ctx: Context<ProposePlatformAccount>,
+    new_platform_account: Pubkey,
+) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let cosigned = ctx.accounts.platform.is_some();
+    
+    vault.propose_platform_account(new_platform_account, cosigned)?;
+    
+    emit!(PlatformAccountProposedEvent {
+        vault: vault.key(),
+        owner: vault.owner,
+        platform_account: vault.platform_account,
+        pending_platform_account: new_platform_account,
+        cosigned,
+        timestamp: get_current_timestamp(),
+    });
+    
+    msg!("Platform account change proposed to {}", new_platform_account);
+    
+    Ok(())
+}
@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::events::ProtocolAdminTransferProposedEvent;
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct ProposeProtocolAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = protocol_config.admin == admin.key() @ VaultError::Unauthorized
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    
+    pub admin: Signer<'info>,
+}
+
+/// Propose `new_admin`; the config only moves once they sign `accept_protocol_admin`
+pub fn propose_protocol_admin(
+    ctx: Context<ProposeProtocolAdmin>,
+    new_admin: Pubkey,
+) -> Result<()> {
+    let protocol_config = &mut ctx.accounts.protocol_config;
+    
+    protocol_config.propose_admin(new_admin)?;
+    
+    emit!(ProtocolAdminTransferProposedEvent {
+        protocol_config: protocol_config.key(),
+        admin: protocol_config.admin,
+        pending_admin: new_admin,
+        timestamp: get_current_timestamp(),
+    });
+    
+    msg!("Protocol admin transfer proposed to {}", new_admin);
+    
+    Ok(())
+}
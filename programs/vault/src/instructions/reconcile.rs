@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+use crate::state::*;
+use crate::error::*;
+use crate::events::BalanceShortfallEvent;
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct Reconcile<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        seeds = [b"vault_token_account", vault.key().as_ref()],
+        bump,
+        constraint = vault_token_account.key() == vault.vault_token_account @ VaultError::InvalidTokenAccount,
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+}
+
+/// Permissionless check that the vault token account holds at least `total_assets` plus the
+/// rewards still vesting, for monitoring bots. A shortfall does not fail the instruction, so
+/// the alert event and the pause it may set are kept
+pub fn reconcile(
+    ctx: Context<Reconcile>,
+) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let balance = ctx.accounts.vault_token_account.amount;
+    let slot = Clock::get()?.slot;
+
+    let was_paused = vault.paused_mask();
+    let shortfall = vault.reconcile(balance, slot)?;
+    if shortfall == 0 {
+        msg!("Vault token account balance {} covers the accounted assets", balance);
+        return Ok(());
+    }
+
+    msg!("SHORTFALL: vault token account holds {} tokens, {} short", balance, shortfall);
+    emit!(BalanceShortfallEvent {
+        vault: vault.key(),
+        balance,
+        total_assets: vault.total_assets,
+        unvested_rewards: vault.unvested_rewards,
+        shortfall,
+        paused: vault.paused_mask() != was_paused,
+        slot,
+        timestamp: get_current_timestamp(),
+    });
+
+    Ok(())
+}
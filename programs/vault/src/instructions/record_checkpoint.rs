@@ -0,0 +1,62 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::events::CheckpointRecordedEvent;
+use crate::utils::*;
+use super::accrue_rewards::accrue_vested_rewards;
+
+#[derive(Accounts)]
+pub struct RecordCheckpoint<'info> {
+    /// Writable to release vested rewards before the share value is read
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault_checkpoints", vault.key().as_ref()],
+        bump = vault_checkpoints.bump
+    )]
+    pub vault_checkpoints: Account<'info, VaultCheckpoints>,
+}
+
+/// Permissionless crank appending the vault's share value to its checkpoint history,
+/// at most once per `checkpoint_interval`
+pub fn record_checkpoint(
+    ctx: Context<RecordCheckpoint>,
+) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let timestamp = get_current_timestamp();
+    
+    accrue_vested_rewards(vault, timestamp)?;
+    
+    if !write_checkpoint(vault, &mut ctx.accounts.vault_checkpoints, timestamp)? {
+        return Err(VaultError::CheckpointNotDue.into());
+    }
+    
+    Ok(())
+}
+
+/// Append a checkpoint when the interval since the latest one has passed and emit it.
+/// Returns whether one was written
+pub(crate) fn write_checkpoint(
+    vault: &Account<Vault>,
+    vault_checkpoints: &mut Account<VaultCheckpoints>,
+    timestamp: i64,
+) -> Result<bool> {
+    let checkpoint = vault.share_value_checkpoint(timestamp)?;
+    let Some(index) = vault_checkpoints.record(checkpoint, vault.effective_checkpoint_interval()) else {
+        return Ok(false);
+    };
+    
+    emit!(CheckpointRecordedEvent {
+        vault: vault.key(),
+        index: index as u16,
+        active_share_value: checkpoint.active_share_value,
+        total_assets: checkpoint.total_assets,
+        total_shares: checkpoint.total_shares,
+        shares_base: checkpoint.shares_base,
+        timestamp,
+    });
+    
+    Ok(true)
+}
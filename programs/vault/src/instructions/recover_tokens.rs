@@ -0,0 +1,82 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::state::*;
+use crate::error::*;
+use crate::events::TokensRecoveredEvent;
+use crate::utils::*;
+use super::token_transfers::transfer_tokens;
+
+#[derive(Accounts)]
+pub struct RecoverTokens<'info> {
+    #[account(
+        constraint = vault.owner == owner.key() @ VaultError::Unauthorized,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    /// Any token account owned by the vault PDA, except of the staked mint and the secondary
+    /// reward token account: the staked asset pool and unclaimed rewards never leave this way
+    #[account(
+        mut,
+        constraint = source_token_account.owner == vault.key() @ VaultError::InvalidTokenAccount,
+        constraint = source_token_account.mint != vault.token_mint @ VaultError::InvalidTokenMint,
+        constraint = source_token_account.key() != vault.secondary_reward_token_account @ VaultError::InvalidTokenAccount,
+    )]
+    pub source_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = destination_token_account.key() != source_token_account.key() @ VaultError::InvalidTokenAccount,
+        constraint = destination_token_account.mint == source_token_account.mint @ VaultError::InvalidTokenMint,
+    )]
+    pub destination_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(constraint = mint.key() == source_token_account.mint @ VaultError::InvalidTokenMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    pub owner: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Move the whole balance of a stray token account owned by the vault PDA, tokens of a foreign
+/// mint sent to the vault by mistake, to `destination_token_account`
+pub fn recover_tokens(
+    ctx: Context<RecoverTokens>,
+) -> Result<()> {
+    let amount = ctx.accounts.source_token_account.amount;
+    if amount == 0 {
+        return Err(VaultError::InvalidAmount.into());
+    }
+    
+    let vault = &ctx.accounts.vault;
+    let signer_seeds: &[&[&[u8]]] = &[&vault.get_signer_seeds()];
+    let received = transfer_tokens(
+        &ctx.accounts.token_program,
+        &ctx.accounts.mint,
+        ctx.accounts.source_token_account.to_account_info(),
+        &mut ctx.accounts.destination_token_account,
+        vault.to_account_info(),
+        signer_seeds,
+        amount,
+    )?;
+    
+    emit!(TokensRecoveredEvent {
+        vault: vault.key(),
+        owner: ctx.accounts.owner.key(),
+        mint: ctx.accounts.mint.key(),
+        source: ctx.accounts.source_token_account.key(),
+        destination: ctx.accounts.destination_token_account.key(),
+        amount,
+        received,
+        timestamp: get_current_timestamp(),
+    });
+    
+    msg!(
+        "Recovered {} tokens of mint {} to {}",
+        amount,
+        ctx.accounts.mint.key(),
+        ctx.accounts.destination_token_account.key()
+    );
+    
+    Ok(())
+}
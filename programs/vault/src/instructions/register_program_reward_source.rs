@@ -0,0 +1,71 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::events::ProgramRewardSourceRegisteredEvent;
+use crate::math::SafeMath;
+use crate::utils::*;
+
+#[derive(Accounts)]
+#[instruction(program_id: Pubkey, seeds: Vec<Vec<u8>>)]
+pub struct RegisterProgramRewardSource<'info> {
+    #[account(
+        mut,
+        constraint = vault.owner == owner.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        init,
+        payer = owner,
+        space = ProgramRewardSource::LEN,
+        seeds = [
+            b"reward_source",
+            vault.key().as_ref(),
+            ProgramRewardSource::derive_authority(&program_id, &seeds).unwrap_or_default().as_ref(),
+        ],
+        bump
+    )]
+    pub reward_source: Account<'info, ProgramRewardSource>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+/// Accept rewards signed by `program_id`'s PDA for `seeds` (without the bump). The PDA is
+/// derived here, so add_rewards only compares keys. The first registration closes the vault
+/// to rewards from any other source
+pub fn register_program_reward_source(
+    ctx: Context<RegisterProgramRewardSource>,
+    program_id: Pubkey,
+    seeds: Vec<Vec<u8>>,
+) -> Result<()> {
+    let timestamp = get_current_timestamp();
+    let authority = ProgramRewardSource::derive_authority(&program_id, &seeds)
+        .ok_or(VaultError::InvalidRewardSourceSeeds)?;
+    let seeds_hash = ProgramRewardSource::hash_seeds(&seeds);
+    
+    let reward_source = &mut ctx.accounts.reward_source;
+    reward_source.vault = ctx.accounts.vault.key();
+    reward_source.program_id = program_id;
+    reward_source.seeds_hash = seeds_hash;
+    reward_source.authority = authority;
+    reward_source.registered_at = timestamp;
+    reward_source.bump = ctx.bumps.reward_source;
+    
+    let vault = &mut ctx.accounts.vault;
+    vault.program_reward_sources = vault.program_reward_sources.safe_add(1)?;
+    
+    emit!(ProgramRewardSourceRegisteredEvent {
+        vault: vault.key(),
+        program_id,
+        authority,
+        seeds_hash,
+        timestamp,
+    });
+    
+    msg!("Registered reward source {} of program {} on vault {}", authority, program_id, vault.key());
+    
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+use crate::state::*;
+use crate::events::{VaultRegisteredEvent, VaultUnregisteredEvent};
+use crate::utils::*;
+
+/// Append `vault` to a registry page, growing the page by one entry when its space is used up.
+/// `payer` covers the rent of the added bytes
+pub(crate) fn register_vault<'info>(
+    registry: &mut Account<'info, VaultRegistry>,
+    vault: Pubkey,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    let registry_info = registry.to_account_info();
+    let needed_len = VaultRegistry::space(registry.vaults.len() + 1);
+    
+    // Space freed by `unregister` is reused before the page grows
+    if registry_info.data_len() < needed_len {
+        let rent_due = Rent::get()?.minimum_balance(needed_len).saturating_sub(registry_info.lamports());
+        if rent_due > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    system_program.to_account_info(),
+                    Transfer {
+                        from: payer.to_account_info(),
+                        to: registry_info.clone(),
+                    },
+                ),
+                rent_due,
+            )?;
+        }
+        registry_info.resize(needed_len)?;
+    }
+    
+    registry.register(vault)?;
+    
+    emit!(VaultRegisteredEvent {
+        vault,
+        page: registry.page,
+        vault_count: registry.vaults.len() as u16,
+        timestamp: get_current_timestamp(),
+    });
+    
+    Ok(())
+}
+
+/// Drop `vault` from a registry page
+pub(crate) fn unregister_vault(
+    registry: &mut Account<'_, VaultRegistry>,
+    vault: Pubkey,
+) -> Result<()> {
+    registry.unregister(&vault)?;
+    
+    emit!(VaultUnregisteredEvent {
+        vault,
+        page: registry.page,
+        vault_count: registry.vaults.len() as u16,
+        timestamp: get_current_timestamp(),
+    });
+    
+    Ok(())
+}
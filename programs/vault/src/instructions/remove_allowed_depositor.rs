@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::events::AllowedDepositorRemovedEvent;
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct RemoveAllowedDepositor<'info> {
+    #[account(
+        constraint = vault.owner == owner.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"allow", vault.key().as_ref(), allowed_depositor.authority.as_ref()],
+        bump = allowed_depositor.bump
+    )]
+    pub allowed_depositor: Account<'info, AllowedDepositor>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+/// Existing positions keep full exit rights, only new stakes are refused
+pub fn remove_allowed_depositor(
+    ctx: Context<RemoveAllowedDepositor>,
+) -> Result<()> {
+    let authority = ctx.accounts.allowed_depositor.authority;
+    
+    emit!(AllowedDepositorRemovedEvent {
+        vault: ctx.accounts.vault.key(),
+        authority,
+        timestamp: get_current_timestamp(),
+    });
+    
+    msg!("Removed depositor {} from vault {} allowlist", authority, ctx.accounts.vault.key());
+    
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use super::registry::unregister_vault;
+
+#[derive(Accounts)]
+pub struct RemoveFromRegistry<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault_registry", vault_registry.page.to_le_bytes().as_ref()],
+        bump = vault_registry.bump,
+    )]
+    pub vault_registry: Account<'info, VaultRegistry>,
+    
+    /// CHECK: only the address is used, and the account must no longer hold a vault
+    pub vault: UncheckedAccount<'info>,
+}
+
+/// Drop a closed vault from its registry page, for vaults closed without passing the page to
+/// `finalize_decommission` (permissionless)
+pub fn remove_from_registry(
+    ctx: Context<RemoveFromRegistry>,
+) -> Result<()> {
+    let vault_info = ctx.accounts.vault.to_account_info();
+    if vault_info.owner == &crate::ID && !vault_info.data_is_empty() {
+        return Err(VaultError::VaultNotClosed.into());
+    }
+    
+    unregister_vault(&mut ctx.accounts.vault_registry, vault_info.key())?;
+    
+    msg!("Vault {} removed from registry page {}", vault_info.key(), ctx.accounts.vault_registry.page);
+    
+    Ok(())
+}
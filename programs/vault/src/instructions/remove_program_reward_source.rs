@@ -0,0 +1,45 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::events::ProgramRewardSourceRemovedEvent;
+use crate::math::SafeMath;
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct RemoveProgramRewardSource<'info> {
+    #[account(
+        mut,
+        constraint = vault.owner == owner.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"reward_source", vault.key().as_ref(), reward_source.authority.as_ref()],
+        bump = reward_source.bump
+    )]
+    pub reward_source: Account<'info, ProgramRewardSource>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+/// Removing the last source reopens add_rewards to any signing source
+pub fn remove_program_reward_source(
+    ctx: Context<RemoveProgramRewardSource>,
+) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    vault.program_reward_sources = vault.program_reward_sources.safe_sub(1)?;
+    
+    emit!(ProgramRewardSourceRemovedEvent {
+        vault: vault.key(),
+        program_id: ctx.accounts.reward_source.program_id,
+        authority: ctx.accounts.reward_source.authority,
+        timestamp: get_current_timestamp(),
+    });
+    
+    msg!("Removed reward source {} from vault {}", ctx.accounts.reward_source.authority, vault.key());
+    
+    Ok(())
+}
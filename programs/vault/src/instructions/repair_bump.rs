@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::events::VaultBumpRepairedEvent;
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct RepairBump<'info> {
+    /// Derived with the canonical bump, whatever `vault.bump` holds
+    #[account(
+        mut,
+        seeds = [b"vault", vault.name.as_ref()],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+}
+
+/// Permissionless one-time migration rewriting `vault.bump` to the canonical bump of the vault
+/// PDA, which every vault-signed transfer and the `bump = vault.bump` constraints rely on.
+/// Succeeds without changes when the stored bump is already canonical
+pub fn repair_bump(
+    ctx: Context<RepairBump>,
+) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let canonical_bump = ctx.bumps.vault;
+    
+    if vault.bump == canonical_bump {
+        msg!("Vault bump {} is already canonical", canonical_bump);
+        return Ok(());
+    }
+    
+    let old_bump = vault.bump;
+    vault.bump = canonical_bump;
+    
+    emit!(VaultBumpRepairedEvent {
+        vault: vault.key(),
+        old_bump,
+        new_bump: canonical_bump,
+        timestamp: get_current_timestamp(),
+    });
+    
+    msg!("Vault bump repaired from {} to {}", old_bump, canonical_bump);
+    
+    Ok(())
+}
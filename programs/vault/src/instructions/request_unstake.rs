@@ -1,9 +1,13 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 use crate::state::*;
+use crate::constants::PAUSE_REQUEST_UNSTAKE;
 use crate::error::*;
+use crate::events::UnstakeRequestedEvent;
 use crate::utils::*;
-use crate::math::{vault_math, SafeMath, SafeCast};
-use crate::constants::*;
+use super::share_tokens::{settle_share_tokens, ShareTokenAccounts};
+use super::accrue_rewards::accrue_vested_rewards;
+use super::sync_rebase::sync_depositor_rebase;
 
 #[derive(Accounts)]
 pub struct RequestUnstake<'info> {
@@ -19,109 +23,110 @@ pub struct RequestUnstake<'info> {
     )]
     pub vault_depositor: Account<'info, VaultDepositor>,
     
+    /// Only read, the request must leave the vault solvent
+    #[account(
+        seeds = [b"vault_token_account", vault.key().as_ref()],
+        bump,
+        constraint = vault_token_account.key() == vault.vault_token_account @ VaultError::InvalidTokenAccount,
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    /// Share mint of a tokenized vault, required only when `vault.share_mint` is set
+    #[account(mut)]
+    pub share_mint: Option<InterfaceAccount<'info, Mint>>,
+    
+    /// `authority`'s token account of `share_mint`
+    #[account(mut)]
+    pub user_share_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
     pub authority: Signer<'info>,
+    
+    /// Required with `share_mint`
+    pub token_program: Option<Interface<'info, TokenInterface>>,
 }
 
+/// Freezes exactly `amount` assets. Fails with `SlippageExceeded` when that takes more than
+/// `max_shares_in` shares (0 = any)
 pub fn request_unstake(
     ctx: Context<RequestUnstake>,
     amount: u64,
+    max_shares_in: u64,
+    client_id: [u8; 16],
 ) -> Result<()> {
-    let vault = &mut ctx.accounts.vault;
-    let vault_depositor = &mut ctx.accounts.vault_depositor;
-    
-    if amount == 0 {
-        return Err(VaultError::InvalidAmount.into());
-    }
-    
-    // Check if there are any active shares to provide a price reference
-    if vault.get_active_shares()? == 0 {
-        return Err(VaultError::NoActiveShares.into());
-    }
-    
-    // MEV PROTECTION: Apply same cooldown to request_unstake
-    let current_time = get_current_timestamp();
-    const MIN_STAKE_DURATION: i64 = 1; // 1 second for testing (change to 300 for production)
-    if current_time < vault_depositor.last_stake_time + MIN_STAKE_DURATION {
-        return Err(VaultError::StakeCooldownNotMet.into());
+    // Unstaking everything is its own instruction, a literal u64::MAX is always a mistake
+    if amount == u64::MAX {
+        return Err(VaultError::AmbiguousMaxAmount.into());
     }
     
-    // CRITICAL FIX: Handle existing unstake request to prevent double counting
-    let existing_unstake_request = vault_depositor.unstake_request.clone();
-    if existing_unstake_request.is_pending() {
-        // Restore previously frozen shares and assets to vault totals
-        let old_shares = existing_unstake_request.shares;
-        let old_freeze_amount = SafeCast::<u128>::safe_cast(&old_shares)?
-            .safe_mul(existing_unstake_request.asset_per_share_at_request)?
-            .safe_div(SafeCast::<u128>::safe_cast(&PRECISION)?)?
-            .safe_cast()?;
-        
-        // Restore vault counters
-        vault.pending_unstake_shares = vault.pending_unstake_shares.safe_sub(old_shares)?;
-        vault.reserved_assets = vault.reserved_assets.safe_sub(old_freeze_amount)?;
-        
-        // Restore user's shares
-        vault_depositor.shares = vault_depositor.shares.safe_add(old_shares)?;
-        
-        msg!("Cancelled previous unstake request: {} shares, {} assets restored", old_shares, old_freeze_amount);
-    }
+    let (shares, _) = request_unstake_inner(ctx, Some(amount), client_id)?;
+    check_max_in(shares, max_shares_in)
+}
 
-    // Calculate current active share value once for consistency
-    let asset_per_share = vault.get_active_share_value()?;
-    
-    // CRITICAL PRECISION FIX: Calculate shares and freeze amount to prevent rounding attacks
-    let (shares, freeze_amount) = if amount == u64::MAX {
-        // Unstake all shares - use exact current value
-        let shares = vault_depositor.shares;
-        let freeze_amount = SafeCast::<u128>::safe_cast(&shares)?
-            .safe_mul(asset_per_share)?
-            .safe_div(SafeCast::<u128>::safe_cast(&PRECISION)?)?
-            .safe_cast()?;
-        (shares, freeze_amount)
-    } else {
-        // ANTI-ROUNDING ATTACK: For partial unstake, prioritize exact asset amount
-        // Instead of: amount -> shares -> freeze_amount (double rounding)
-        // We use: amount -> freeze_amount directly, then calculate shares
-        
-        // First, freeze the exact requested amount
-        let freeze_amount = amount;
-        
-        // Then calculate shares based on frozen amount to ensure consistency
-        let shares = SafeCast::<u128>::safe_cast(&freeze_amount)?
-            .safe_mul(SafeCast::<u128>::safe_cast(&PRECISION)?)?
-            .safe_div(asset_per_share)?
-            .safe_cast()?;
-            
-        (shares, freeze_amount)
-    };
+/// Requests every share of the depositor, priced at the current share value. Fails with
+/// `SlippageExceeded` when they freeze less than `min_amount_out` (0 = any)
+pub fn request_unstake_all(
+    ctx: Context<RequestUnstake>,
+    min_amount_out: u64,
+    client_id: [u8; 16],
+) -> Result<()> {
+    let (_, frozen_amount) = request_unstake_inner(ctx, None, client_id)?;
+    check_min_out(frozen_amount, min_amount_out)
+}
+
+/// Freezes exactly `amount` assets, or the whole position without one.
+/// Returns (shares, frozen_amount)
+fn request_unstake_inner(
+    ctx: Context<RequestUnstake>,
+    amount: Option<u64>,
+    client_id: [u8; 16],
+) -> Result<(u64, u64)> {
+    let vault = &mut ctx.accounts.vault;
+    let vault_depositor = &mut ctx.accounts.vault_depositor;
+    let current_time = get_current_timestamp();
     
-    if shares == 0 {
-        return Err(VaultError::InvalidAmount.into());
-    }
+    vault.check_not_paused(PAUSE_REQUEST_UNSTAKE)?;
     
-    // Verify user has enough shares
-    if shares > vault_depositor.shares {
-        return Err(VaultError::InsufficientFunds.into());
-    }
+    // The request is frozen at a share value including every vested reward
+    accrue_vested_rewards(vault, current_time)?;
     
-    // CRITICAL: Immediately freeze both shares and corresponding assets
-    // This ensures strict separation between active and pending resources
-    vault.pending_unstake_shares = vault.pending_unstake_shares.safe_add(shares)?;
-    vault.reserved_assets = vault.reserved_assets.safe_add(freeze_amount)?;
+    // The amount is priced against shares in the current base
+    sync_depositor_rebase(vault, vault_depositor, current_time)?;
+    let shares_before = vault_depositor.shares;
     
-    // CRITICAL FIX: Must reduce user's active shares immediately
-    // This ensures the requested shares stop earning rewards
-    vault_depositor.shares = vault_depositor.shares.safe_sub(shares)?;
+    // A pending request is never replaced, that would silently restart its lockup
+    let (shares, freeze_amount) = match amount {
+        Some(amount) => vault_depositor.request_unstake(vault, amount, current_time)?,
+        None => vault_depositor.request_unstake_all(vault, current_time)?,
+    };
+    vault.record_active_shares_change(shares_before, vault_depositor.shares);
+    vault.verify_solvency(ctx.accounts.vault_token_account.amount)?;
+    let asset_per_share = vault_depositor.unstake_request.asset_per_share_at_request;
     
-    // Create unstake request with frozen share value
-    let current_time = get_current_timestamp();
-    vault_depositor.unstake_request.shares = shares;
-    vault_depositor.unstake_request.request_time = current_time;
-    vault_depositor.unstake_request.asset_per_share_at_request = asset_per_share;
+    // Burns the requested shares' tokens
+    settle_share_tokens(
+        vault,
+        ShareTokenAccounts {
+            share_mint: ctx.accounts.share_mint.as_mut(),
+            share_account: ctx.accounts.user_share_account.as_ref(),
+            token_program: ctx.accounts.token_program.as_ref(),
+        },
+        ctx.accounts.authority.key(),
+        &ctx.accounts.authority.to_account_info(),
+        shares_before,
+        vault_depositor.shares,
+    )?;
     
-    // INVARIANT CHECK: Verify vault state consistency after request
-    vault.verify_invariants()?;
+    emit!(UnstakeRequestedEvent {
+        vault: vault.key(),
+        authority: ctx.accounts.authority.key(),
+        amount: freeze_amount,
+        shares,
+        active_share_value: vault.active_share_value_or_zero()?,
+        client_id,
+        timestamp: current_time,
+    });
     
     msg!("Unstake request created for {} shares, froze {} assets at {} per share", shares, freeze_amount, asset_per_share);
     
-    Ok(())
-}
\ No newline at end of file
+    Ok((shares, freeze_amount))
+}
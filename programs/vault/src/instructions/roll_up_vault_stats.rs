@@ -0,0 +1,76 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct RollUpVaultStats<'info> {
+    #[account(
+        mut,
+        seeds = [b"protocol_stats"],
+        bump = protocol_stats.bump,
+    )]
+    pub protocol_stats: Account<'info, ProtocolStats>,
+    
+    pub vault: Account<'info, Vault>,
+    
+    /// Per-vault record of what was last contributed, bound to the vault pubkey
+    /// so repeated roll-ups replace the vault's totals instead of adding them
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = VaultStatsContribution::LEN,
+        seeds = [b"vault_stats", vault.key().as_ref()],
+        bump
+    )]
+    pub vault_stats: Account<'info, VaultStatsContribution>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+/// Permissionless crank folding a vault's current totals into the per-mint aggregates
+pub fn roll_up_vault_stats(
+    ctx: Context<RollUpVaultStats>,
+) -> Result<()> {
+    let vault = &ctx.accounts.vault;
+    let vault_stats = &mut ctx.accounts.vault_stats;
+    let protocol_stats = &mut ctx.accounts.protocol_stats;
+    let slot = Clock::get()?.slot;
+    
+    // Nothing changes within a slot, skip so the crank can be called blindly
+    if vault_stats.last_rolled_slot == slot {
+        msg!("Vault stats already rolled up in slot {}", slot);
+        return Ok(());
+    }
+    
+    let previous = if vault_stats.last_rolled_slot == 0 {
+        vault_stats.vault = vault.key();
+        vault_stats.token_mint = vault.token_mint;
+        vault_stats.bump = ctx.bumps.vault_stats;
+        None
+    } else {
+        Some((vault_stats.total_assets, vault_stats.total_rewards))
+    };
+    
+    protocol_stats.apply_contribution(
+        vault_stats.token_mint,
+        previous,
+        vault.total_assets,
+        vault.total_rewards,
+        slot,
+    )?;
+    
+    vault_stats.total_assets = vault.total_assets;
+    vault_stats.total_rewards = vault.total_rewards;
+    vault_stats.last_rolled_slot = slot;
+    
+    msg!(
+        "Rolled up vault {}: total_assets={}, total_rewards={}",
+        vault.key(),
+        vault.total_assets,
+        vault.total_rewards
+    );
+    
+    Ok(())
+}
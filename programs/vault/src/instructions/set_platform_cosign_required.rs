@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::events::PlatformCosignRequiredSetEvent;
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct SetPlatformCosignRequired<'info> {
+    #[account(
+        mut,
+        constraint = vault.platform_account == platform.key() @ VaultError::Unauthorized
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    pub platform: Signer<'info>,
+}
+
+/// Require (or stop requiring) the platform account's signature on platform account proposals
+pub fn set_platform_cosign_required(
+    ctx: Context<SetPlatformCosignRequired>,
+    required: bool,
+) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    
+    vault.platform_cosign_required = required;
+    
+    emit!(PlatformCosignRequiredSetEvent {
+        vault: vault.key(),
+        platform_account: vault.platform_account,
+        required,
+        timestamp: get_current_timestamp(),
+    });
+    
+    msg!("Platform co-signature on platform account proposals: {}", required);
+    
+    Ok(())
+}
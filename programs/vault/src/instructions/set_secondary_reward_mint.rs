@@ -0,0 +1,62 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::state::*;
+use crate::error::*;
+use crate::events::SecondaryRewardMintSetEvent;
+use crate::utils::*;
+use super::token_transfers::check_mint_extensions;
+
+#[derive(Accounts)]
+pub struct SetSecondaryRewardMint<'info> {
+    #[account(
+        mut,
+        constraint = vault.owner == owner.key() @ VaultError::Unauthorized,
+        constraint = !vault.has_secondary_rewards() @ VaultError::SecondaryRewardMintAlreadySet,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    /// Second reward token, of the original token program or Token-2022 with supported
+    /// extensions. Never the staked mint, whose rewards compound
+    #[account(constraint = secondary_reward_mint.key() != vault.token_mint @ VaultError::InvalidTokenMint)]
+    pub secondary_reward_mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(
+        init,
+        payer = owner,
+        token::mint = secondary_reward_mint,
+        token::authority = vault,
+        seeds = [b"secondary_reward_token_account", vault.key().as_ref()],
+        bump
+    )]
+    pub secondary_reward_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Start paying a second reward token to the stakers. The mint cannot be changed afterwards,
+/// depositors' unclaimed rewards are denominated in it
+pub fn set_secondary_reward_mint(
+    ctx: Context<SetSecondaryRewardMint>,
+) -> Result<()> {
+    check_mint_extensions(&ctx.accounts.secondary_reward_mint)?;
+    
+    let vault = &mut ctx.accounts.vault;
+    vault.secondary_reward_mint = ctx.accounts.secondary_reward_mint.key();
+    vault.secondary_reward_token_account = ctx.accounts.secondary_reward_token_account.key();
+    
+    emit!(SecondaryRewardMintSetEvent {
+        vault: vault.key(),
+        owner: ctx.accounts.owner.key(),
+        mint: vault.secondary_reward_mint,
+        token_account: vault.secondary_reward_token_account,
+        timestamp: get_current_timestamp(),
+    });
+    
+    msg!("Secondary reward mint set to {}", vault.secondary_reward_mint);
+    
+    Ok(())
+}
@@ -0,0 +1,104 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{self, Burn, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked};
+use crate::state::*;
+use crate::error::*;
+
+/// The optional share token accounts of a depositor instruction
+pub(crate) struct ShareTokenAccounts<'a, 'info> {
+    pub share_mint: Option<&'a mut InterfaceAccount<'info, Mint>>,
+    pub share_account: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: Option<&'a Interface<'info, TokenInterface>>,
+}
+
+/// Follow a depositor's active share balance with share tokens on a tokenized vault: shares
+/// gained are minted to `holder`'s share account, shares given up are burned from it with
+/// `authority`'s signature. The mint supply is reconciled with the vault afterwards.
+/// Nothing happens on untokenized vaults or when the balance did not change
+pub(crate) fn settle_share_tokens<'info>(
+    vault: &Account<'info, Vault>,
+    accounts: ShareTokenAccounts<'_, 'info>,
+    holder: Pubkey,
+    authority: &AccountInfo<'info>,
+    shares_before: u64,
+    shares_after: u64,
+) -> Result<()> {
+    if !vault.is_tokenized() || shares_before == shares_after {
+        return Ok(());
+    }
+    
+    let (Some(share_mint), Some(share_account), Some(token_program)) =
+        (accounts.share_mint, accounts.share_account, accounts.token_program)
+    else {
+        return Err(VaultError::ShareTokenAccountsRequired.into());
+    };
+    if share_mint.key() != vault.share_mint || share_account.mint != vault.share_mint {
+        return Err(VaultError::InvalidShareMint.into());
+    }
+    if share_account.owner != holder {
+        return Err(VaultError::Unauthorized.into());
+    }
+    
+    if shares_after > shares_before {
+        let signer_seeds: &[&[&[u8]]] = &[&vault.get_signer_seeds()];
+        let cpi_accounts = MintTo {
+            mint: share_mint.to_account_info(),
+            to: share_account.to_account_info(),
+            authority: vault.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, signer_seeds);
+        token_interface::mint_to(cpi_ctx, shares_after - shares_before)?;
+    } else {
+        // The holder must still have the tokens: shares lent out as collateral cannot exit
+        let cpi_accounts = Burn {
+            mint: share_mint.to_account_info(),
+            from: share_account.to_account_info(),
+            authority: authority.clone(),
+        };
+        let cpi_ctx = CpiContext::new(token_program.to_account_info(), cpi_accounts);
+        token_interface::burn(cpi_ctx, shares_before - shares_after)?;
+    }
+    
+    share_mint.reload()?;
+    vault.verify_share_supply(share_mint.supply)?;
+    
+    Ok(())
+}
+
+/// Move the share tokens of `shares` transferred between positions, from `authority`'s share
+/// account to `recipient`'s. The supply does not change. Nothing happens on untokenized vaults
+pub(crate) fn transfer_share_tokens<'info>(
+    vault: &Account<'info, Vault>,
+    accounts: ShareTokenAccounts<'_, 'info>,
+    recipient_share_account: Option<&InterfaceAccount<'info, TokenAccount>>,
+    recipient: Pubkey,
+    authority: &AccountInfo<'info>,
+    shares: u64,
+) -> Result<()> {
+    if !vault.is_tokenized() {
+        return Ok(());
+    }
+    
+    let (Some(share_mint), Some(share_account), Some(recipient_share_account), Some(token_program)) =
+        (accounts.share_mint, accounts.share_account, recipient_share_account, accounts.token_program)
+    else {
+        return Err(VaultError::ShareTokenAccountsRequired.into());
+    };
+    if share_mint.key() != vault.share_mint
+        || share_account.mint != vault.share_mint
+        || recipient_share_account.mint != vault.share_mint
+    {
+        return Err(VaultError::InvalidShareMint.into());
+    }
+    if share_account.owner != authority.key() || recipient_share_account.owner != recipient {
+        return Err(VaultError::Unauthorized.into());
+    }
+    
+    let cpi_accounts = TransferChecked {
+        from: share_account.to_account_info(),
+        mint: share_mint.to_account_info(),
+        to: recipient_share_account.to_account_info(),
+        authority: authority.clone(),
+    };
+    let cpi_ctx = CpiContext::new(token_program.to_account_info(), cpi_accounts);
+    token_interface::transfer_checked(cpi_ctx, shares, share_mint.decimals)
+}
@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::TokenAccount;
+use crate::state::*;
+use crate::error::*;
+use crate::events::SurplusSkimmedEvent;
+use crate::utils::*;
+use crate::constants::PAUSE_ADD_REWARDS;
+use super::accrue_rewards::accrue_vested_rewards;
+use super::apply_rebase::emit_rebase;
+use super::crystallize_fees::crystallize_performance_fee;
+
+#[derive(Accounts)]
+pub struct Skim<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        seeds = [b"vault_token_account", vault.key().as_ref()],
+        bump,
+        constraint = vault_token_account.key() == vault.vault_token_account @ VaultError::InvalidTokenAccount,
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+}
+
+/// Permissionless: tokens transferred straight into the vault token account are added to the
+/// vault like an `add_rewards` without a platform share, vesting included. Succeeds without
+/// changes when the balance holds nothing beyond `total_assets` and unvested rewards
+pub fn skim(
+    ctx: Context<Skim>,
+) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let timestamp = get_current_timestamp();
+
+    vault.check_not_paused(PAUSE_ADD_REWARDS)?;
+
+    // Whatever is left goes to the owner when the vault is finalized
+    if vault.is_decommissioning() {
+        return Err(VaultError::VaultDecommissioning.into());
+    }
+
+    accrue_vested_rewards(vault, timestamp)?;
+
+    let surplus = vault.skimmable_surplus(ctx.accounts.vault_token_account.amount)?;
+    if surplus == 0 {
+        msg!("Nothing to skim");
+        return Ok(());
+    }
+
+    let (shares_base_before, total_shares_before) = (vault.shares_base, vault.total_shares);
+    vault.add_rewards(surplus)?;
+    emit_rebase(vault, shares_base_before, total_shares_before, timestamp);
+    crystallize_performance_fee(vault, timestamp)?;
+
+    emit!(SurplusSkimmedEvent {
+        vault: vault.key(),
+        amount: surplus,
+        active_share_value: vault.active_share_value_or_zero()?,
+        timestamp,
+    });
+
+    msg!("Skimmed {} tokens into the vault", surplus);
+
+    Ok(())
+}
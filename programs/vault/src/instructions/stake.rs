@@ -1,8 +1,15 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 use crate::state::*;
+use crate::constants::PAUSE_STAKE;
 use crate::error::*;
+use crate::events::StakeEvent;
 use crate::math::SafeMath;
+use super::apply_rebase::emit_rebase;
+use super::share_tokens::{settle_share_tokens, ShareTokenAccounts};
+use super::accrue_rewards::accrue_vested_rewards;
+use super::sync_rebase::sync_depositor_rebase;
+use super::token_transfers::transfer_tokens;
 
 #[derive(Accounts)]
 pub struct Stake<'info> {
@@ -23,52 +30,146 @@ pub struct Stake<'info> {
         seeds = [b"vault_token_account", vault.key().as_ref()],
         bump,
         constraint = vault_token_account.key() == vault.vault_token_account @ VaultError::InvalidTokenAccount,
+        constraint = vault_token_account.owner == vault.key() @ VaultError::InvalidTokenAccount,
+        constraint = vault_token_account.mint == vault.token_mint @ VaultError::InvalidTokenAccount,
+        constraint = !vault_token_account.is_frozen() @ VaultError::TokenAccountFrozen,
     )]
-    pub vault_token_account: Account<'info, TokenAccount>,
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
     
     #[account(
         mut,
+        constraint = user_token_account.key() != vault_token_account.key() @ VaultError::InvalidTokenAccount,
         constraint = user_token_account.mint == vault.token_mint @ VaultError::InvalidTokenMint,
         constraint = user_token_account.owner == authority.key() @ VaultError::Unauthorized,
     )]
-    pub user_token_account: Account<'info, TokenAccount>,
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(constraint = token_mint.key() == vault.token_mint @ VaultError::InvalidTokenMint)]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+    
+    /// Allowlist marker for `authority`, required only while the vault is permissioned
+    pub allowed_depositor: Option<Account<'info, AllowedDepositor>>,
+    
+    /// Share mint of a tokenized vault, required only when `vault.share_mint` is set
+    #[account(mut)]
+    pub share_mint: Option<InterfaceAccount<'info, Mint>>,
+    
+    /// `authority`'s token account of `share_mint`
+    #[account(mut)]
+    pub user_share_account: Option<InterfaceAccount<'info, TokenAccount>>,
     
     #[account(mut)]
     pub authority: Signer<'info>,
     
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
+/// Stake exactly `amount` tokens. `u64::MAX` is not a sentinel and fails with
+/// `AmbiguousMaxAmount`, as in every amount taking instruction. Shares are minted for what the vault
+/// received, `amount` less any transfer fee of the mint, less the vault's deposit fee.
+/// Fails with `SlippageExceeded` when fewer than `min_shares_out` (0 = any) are minted
 pub fn stake(
     ctx: Context<Stake>,
     amount: u64,
+    min_shares_out: u64,
+    client_id: [u8; 16],
 ) -> Result<()> {
-    let vault = &mut ctx.accounts.vault;
-    let vault_depositor = &mut ctx.accounts.vault_depositor;
+    let timestamp = crate::utils::get_current_timestamp();
+    
+    let (shares, received, deposit_fee) = stake_into_depositor(ctx.accounts, amount, timestamp)?;
+    crate::utils::check_min_out(shares, min_shares_out)?;
+    
+    settle_share_tokens(
+        &ctx.accounts.vault,
+        ShareTokenAccounts {
+            share_mint: ctx.accounts.share_mint.as_mut(),
+            share_account: ctx.accounts.user_share_account.as_ref(),
+            token_program: Some(&ctx.accounts.token_program),
+        },
+        ctx.accounts.authority.key(),
+        &ctx.accounts.authority.to_account_info(),
+        0,
+        shares,
+    )?;
+    
+    emit!(StakeEvent {
+        vault: ctx.accounts.vault.key(),
+        authority: ctx.accounts.authority.key(),
+        amount: received,
+        deposit_fee,
+        net_amount: received.safe_sub(deposit_fee)?,
+        shares,
+        active_share_value: ctx.accounts.vault.active_share_value_or_zero()?,
+        client_id,
+        timestamp,
+    });
+    
+    msg!("Staked {} tokens ({} sent, {} deposit fee), received {} shares", received, amount, deposit_fee, shares);
+    
+    Ok(())
+}
+
+/// Transfer `amount` from the authority and mint shares for what arrived into the depositor.
+/// Returns (minted shares, amount received by the vault, deposit fee withheld from it)
+pub(crate) fn stake_into_depositor(accounts: &mut Stake, amount: u64, timestamp: i64) -> Result<(u64, u64, u64)> {
+    let vault = &mut accounts.vault;
+    let vault_depositor = &mut accounts.vault_depositor;
+    
+    vault.check_not_paused(PAUSE_STAKE)?;
     
     if amount == 0 {
         return Err(VaultError::InvalidAmount.into());
     }
     
-    // Transfer tokens from user to vault FIRST
-    let cpi_accounts = Transfer {
-        from: ctx.accounts.user_token_account.to_account_info(),
-        to: ctx.accounts.vault_token_account.to_account_info(),
-        authority: ctx.accounts.authority.to_account_info(),
-    };
-    let cpi_program = ctx.accounts.token_program.to_account_info();
-    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+    // There is no stake-everything form, a stake never guesses a balance
+    if amount == u64::MAX {
+        return Err(VaultError::AmbiguousMaxAmount.into());
+    }
+    
+    vault.check_depositor_allowed(
+        vault.key(),
+        accounts.authority.key(),
+        accounts.allowed_depositor.as_deref(),
+    )?;
+    
+    // Transfer tokens from user to vault FIRST, a transfer fee is withheld from what arrives
+    let received = transfer_tokens(
+        &accounts.token_program,
+        &accounts.token_mint,
+        accounts.user_token_account.to_account_info(),
+        &mut accounts.vault_token_account,
+        accounts.authority.to_account_info(),
+        &[],
+        amount,
+    )?;
     
-    token::transfer(cpi_ctx, amount)?;
+    // New shares are priced with every vested reward, but none still vesting
+    accrue_vested_rewards(vault, timestamp)?;
+    
+    // Existing position for the minimum stake check, in the current share base
+    sync_depositor_rebase(vault, vault_depositor, timestamp)?;
+    let depositor_value = vault.depositor_stake_value(vault_depositor)?;
     
     // Calculate shares to mint AFTER successful token transfer
-    let shares = vault.stake(amount)?;
+    let (shares_base_before, total_shares_before) = (vault.shares_base, vault.total_shares);
+    let deposit_fee = vault.deposit_fee(received)?;
+    let shares = vault.stake(received, depositor_value)?;
+    
+    // `Vault::stake` rebases first when shares grew too large
+    if emit_rebase(vault, shares_base_before, total_shares_before, timestamp) {
+        
+        // The new shares are in the new base, the existing ones follow before they are added
+        sync_depositor_rebase(vault, vault_depositor, timestamp)?;
+    }
     
     // Update vault depositor
-    vault_depositor.stake(shares, 0)?;
-    vault_depositor.total_staked = vault_depositor.total_staked.safe_add(amount)?;
+    let shares_before = vault_depositor.shares;
+    vault_depositor.stake(shares, vault.secondary_rewards_per_share)?;
+    vault.record_active_shares_change(shares_before, vault_depositor.shares);
+    vault_depositor.record_staked(received)?;
     
-    msg!("Staked {} tokens, received {} shares", amount, shares);
+    // `transfer_tokens` reloaded the balance, which now has to back the new shares too
+    vault.verify_solvency(accounts.vault_token_account.amount)?;
     
-    Ok(())
-}
\ No newline at end of file
+    Ok((shares, received, deposit_fee))
+}
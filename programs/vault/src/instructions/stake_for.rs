@@ -0,0 +1,177 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::state::*;
+use crate::constants::{PAUSE_INIT_DEPOSITOR, PAUSE_STAKE};
+use crate::error::*;
+use crate::events::{DepositorInitializedEvent, StakeForEvent};
+use crate::math::SafeMath;
+use super::apply_rebase::emit_rebase;
+use super::share_tokens::{settle_share_tokens, ShareTokenAccounts};
+use super::accrue_rewards::accrue_vested_rewards;
+use super::sync_rebase::sync_depositor_rebase;
+use super::token_transfers::transfer_tokens;
+
+#[derive(Accounts)]
+#[instruction(beneficiary: Pubkey)]
+pub struct StakeFor<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    
+    /// Beneficiary's depositor, created on first use with the payer covering rent
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = VaultDepositor::LEN_V2,
+        seeds = [b"vault_depositor", vault.key().as_ref(), beneficiary.as_ref()],
+        bump
+    )]
+    pub vault_depositor: Account<'info, VaultDepositor>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault_token_account", vault.key().as_ref()],
+        bump,
+        constraint = vault_token_account.key() == vault.vault_token_account @ VaultError::InvalidTokenAccount,
+        constraint = vault_token_account.owner == vault.key() @ VaultError::InvalidTokenAccount,
+        constraint = vault_token_account.mint == vault.token_mint @ VaultError::InvalidTokenAccount,
+        constraint = !vault_token_account.is_frozen() @ VaultError::TokenAccountFrozen,
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = payer_token_account.key() != vault_token_account.key() @ VaultError::InvalidTokenAccount,
+        constraint = payer_token_account.mint == vault.token_mint @ VaultError::InvalidTokenMint,
+        constraint = payer_token_account.owner == payer.key() @ VaultError::Unauthorized,
+    )]
+    pub payer_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(constraint = token_mint.key() == vault.token_mint @ VaultError::InvalidTokenMint)]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+    
+    /// Allowlist marker for `beneficiary`, required only while the vault is permissioned
+    pub allowed_depositor: Option<Account<'info, AllowedDepositor>>,
+    
+    /// Share mint of a tokenized vault, required only when `vault.share_mint` is set
+    #[account(mut)]
+    pub share_mint: Option<InterfaceAccount<'info, Mint>>,
+    
+    /// `beneficiary`'s token account of `share_mint`
+    #[account(mut)]
+    pub beneficiary_share_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Stake the payer's tokens into the beneficiary's position. The beneficiary's
+/// `last_stake_time` is refreshed, so the stake cooldown applies to them as usual.
+/// `u64::MAX` fails with `AmbiguousMaxAmount`, as in `stake`, and shares are minted for what
+/// the vault received after any transfer fee
+pub fn stake_for(
+    ctx: Context<StakeFor>,
+    beneficiary: Pubkey,
+    amount: u64,
+    client_id: [u8; 16],
+) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let vault_depositor = &mut ctx.accounts.vault_depositor;
+    let timestamp = crate::utils::get_current_timestamp();
+    
+    vault.check_not_paused(PAUSE_STAKE)?;
+    
+    if amount == 0 {
+        return Err(VaultError::InvalidAmount.into());
+    }
+    
+    // There is no stake-everything form, a stake never guesses a balance
+    if amount == u64::MAX {
+        return Err(VaultError::AmbiguousMaxAmount.into());
+    }
+    
+    // The beneficiary is who ends up holding shares, so it is the one that must be allowed
+    vault.check_depositor_allowed(vault.key(), beneficiary, ctx.accounts.allowed_depositor.as_deref())?;
+    
+    if vault_depositor.vault == Pubkey::default() {
+        vault.check_not_paused(PAUSE_INIT_DEPOSITOR)?;
+        vault_depositor.initialize(vault.key(), beneficiary)?;
+        vault_depositor.last_rebase_version = vault.rebase_version;
+        vault_depositor.last_shares_base = vault.shares_base;
+        vault.record_depositor_opened()?;
+        
+        emit!(DepositorInitializedEvent {
+            vault: vault.key(),
+            authority: beneficiary,
+            timestamp,
+        });
+    }
+    
+    if vault_depositor.vault != vault.key() || vault_depositor.authority != beneficiary {
+        return Err(VaultError::Unauthorized.into());
+    }
+    
+    // Transfer tokens from payer to vault FIRST
+    let received = transfer_tokens(
+        &ctx.accounts.token_program,
+        &ctx.accounts.token_mint,
+        ctx.accounts.payer_token_account.to_account_info(),
+        &mut ctx.accounts.vault_token_account,
+        ctx.accounts.payer.to_account_info(),
+        &[],
+        amount,
+    )?;
+    
+    accrue_vested_rewards(vault, timestamp)?;
+    
+    // Same minimum stake rule as `stake`, against the beneficiary's synced position
+    sync_depositor_rebase(vault, vault_depositor, timestamp)?;
+    let depositor_value = vault.depositor_stake_value(vault_depositor)?;
+    
+    let (shares_base_before, total_shares_before) = (vault.shares_base, vault.total_shares);
+    let deposit_fee = vault.deposit_fee(received)?;
+    let shares = vault.stake(received, depositor_value)?;
+    
+    if emit_rebase(vault, shares_base_before, total_shares_before, timestamp) {
+        sync_depositor_rebase(vault, vault_depositor, timestamp)?;
+    }
+    
+    let shares_before = vault_depositor.shares;
+    vault_depositor.stake(shares, vault.secondary_rewards_per_share)?;
+    vault.record_active_shares_change(shares_before, vault_depositor.shares);
+    vault_depositor.record_staked(received)?;
+    vault.verify_solvency(ctx.accounts.vault_token_account.amount)?;
+    
+    // Share tokens go to the beneficiary, who owns the position
+    settle_share_tokens(
+        vault,
+        ShareTokenAccounts {
+            share_mint: ctx.accounts.share_mint.as_mut(),
+            share_account: ctx.accounts.beneficiary_share_account.as_ref(),
+            token_program: Some(&ctx.accounts.token_program),
+        },
+        beneficiary,
+        &ctx.accounts.payer.to_account_info(),
+        0,
+        shares,
+    )?;
+    
+    emit!(StakeForEvent {
+        vault: vault.key(),
+        payer: ctx.accounts.payer.key(),
+        beneficiary,
+        amount: received,
+        deposit_fee,
+        net_amount: received.safe_sub(deposit_fee)?,
+        shares,
+        active_share_value: vault.active_share_value_or_zero()?,
+        client_id,
+        timestamp,
+    });
+    
+    msg!("Staked {} tokens ({} sent, {} deposit fee) for {}, minted {} shares", received, amount, deposit_fee, beneficiary, shares);
+    
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use anchor_lang::prelude::*;
+use crate::constants::{PAUSE_REQUEST_UNSTAKE, PAUSE_STAKE};
+use crate::error::*;
+use crate::math::SafeMath;
+use crate::events::{StakeEvent, UnstakeRequestedEvent};
+use super::stake::{stake_into_depositor, Stake};
+
+/// Stake `amount` and request the unstake of every minted share in the same instruction,
+/// so the deposit matures after exactly one lockup term. Pending shares earn no rewards.
+/// Fails with `UnstakeRequestAlreadyExists` if the depositor already has a request. On a
+/// tokenized vault no share tokens are minted, the shares are pending from the start
+pub fn stake_with_term(
+    ctx: Context<Stake>,
+    amount: u64,
+    client_id: [u8; 16],
+) -> Result<()> {
+    let timestamp = crate::utils::get_current_timestamp();
+    
+    // Both halves run or neither does
+    ctx.accounts.vault.check_not_paused(PAUSE_STAKE | PAUSE_REQUEST_UNSTAKE)?;
+    if ctx.accounts.vault_depositor.unstake_request.is_pending() {
+        return Err(VaultError::UnstakeRequestAlreadyExists.into());
+    }
+    
+    let (shares, received, deposit_fee) = stake_into_depositor(ctx.accounts, amount, timestamp)?;
+    
+    let vault = &mut ctx.accounts.vault;
+    let shares_before = ctx.accounts.vault_depositor.shares;
+    let frozen_amount = ctx.accounts.vault_depositor.request_term_unstake(vault, shares, timestamp)?;
+    vault.record_active_shares_change(shares_before, ctx.accounts.vault_depositor.shares);
+    let active_share_value = vault.active_share_value_or_zero()?;
+    
+    emit!(StakeEvent {
+        vault: vault.key(),
+        authority: ctx.accounts.authority.key(),
+        amount: received,
+        deposit_fee,
+        net_amount: received.safe_sub(deposit_fee)?,
+        shares,
+        active_share_value,
+        client_id,
+        timestamp,
+    });
+    emit!(UnstakeRequestedEvent {
+        vault: vault.key(),
+        authority: ctx.accounts.authority.key(),
+        amount: frozen_amount,
+        shares,
+        active_share_value,
+        client_id,
+        timestamp,
+    });
+    
+    msg!("Staked {} tokens for one term, {} shares matured into {} assets", received, shares, frozen_amount);
+    
+    Ok(())
+}
@@ -1,9 +1,12 @@
 use anchor_lang::prelude::*;
 use crate::state::*;
 use crate::error::*;
+use crate::events::{PrecisionFloorApplied, RebaseSyncedEvent};
+use crate::utils::*;
 
 #[derive(Accounts)]
 pub struct SyncRebase<'info> {
+    #[account(mut)]
     pub vault: Account<'info, Vault>,
     
     #[account(
@@ -21,25 +24,50 @@ pub struct SyncRebase<'info> {
 pub fn sync_rebase(
     ctx: Context<SyncRebase>,
 ) -> Result<()> {
-    let vault = &ctx.accounts.vault;
+    let vault = &mut ctx.accounts.vault;
     let vault_depositor = &mut ctx.accounts.vault_depositor;
     
-    // Check if user needs to sync with vault rebase
-    if vault_depositor.needs_rebase_sync(vault.rebase_version) {
-        // Calculate the rebase divisor needed to sync user shares with vault
-        if vault.shares_base > 0 {
-            let rebase_divisor = 10u128.pow(vault.shares_base);
-            vault_depositor.apply_rebase(rebase_divisor, vault.rebase_version)?;
-            
-            msg!("User shares synced with vault rebase, divisor: {}, version: {}", rebase_divisor, vault.rebase_version);
-        } else {
-            // Even if no shares_base, update the version to prevent unnecessary sync calls
-            vault_depositor.last_rebase_version = vault.rebase_version;
-            msg!("Rebase version updated to: {}", vault.rebase_version);
-        }
+    let sync = sync_depositor_rebase(vault, vault_depositor, get_current_timestamp())?;
+    
+    if sync.synced {
+        msg!("User shares synced with vault rebase, shares_base: {}, version: {}", vault.shares_base, vault.rebase_version);
     } else {
         msg!("User already synced with latest rebase version: {}", vault.rebase_version);
     }
     
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Sync `vault_depositor` with the vault's latest rebase and emit what changed.
+/// Instructions pricing a depositor's shares call this first, so a lagging
+/// depositor is never priced in the old share base
+pub(crate) fn sync_depositor_rebase(
+    vault: &mut Account<Vault>,
+    vault_depositor: &mut VaultDepositor,
+    timestamp: i64,
+) -> Result<RebaseSync> {
+    let (sync, value_delta) = vault.sync_depositor(vault_depositor)?;
+    if let Some(floor) = sync.floor {
+        emit!(PrecisionFloorApplied {
+            vault: vault.key(),
+            authority: vault_depositor.authority,
+            computed_shares: floor.computed_shares,
+            granted_shares: floor.granted_shares,
+            value_delta,
+            precision_floor_events: vault.precision_floor_events,
+            timestamp,
+        });
+    }
+    
+    if sync.synced {
+        emit!(RebaseSyncedEvent {
+            vault: vault.key(),
+            authority: vault_depositor.authority,
+            shares: vault_depositor.shares,
+            rebase_version: vault.rebase_version,
+            timestamp,
+        });
+    }
+    
+    Ok(sync)
+}
@@ -0,0 +1,79 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::events::{PrecisionFloorApplied, RebaseBatchSyncedEvent};
+use crate::utils::*;
+
+/// Maximum depositors per batch; each sync costs roughly 5k CU (deserialize, divide,
+/// serialize), so 20 stays well inside the default 200k CU budget
+pub const MAX_SYNC_BATCH: usize = 20;
+
+#[derive(Accounts)]
+pub struct SyncRebaseBatch<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+}
+
+/// Permissionless crank syncing up to `MAX_SYNC_BATCH` depositors passed as
+/// writable `remaining_accounts`
+pub fn sync_rebase_batch<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SyncRebaseBatch<'info>>,
+) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let current_time = get_current_timestamp();
+    
+    if ctx.remaining_accounts.is_empty() || ctx.remaining_accounts.len() > MAX_SYNC_BATCH {
+        return Err(VaultError::InvalidAmount.into());
+    }
+    
+    let mut synced = Vec::with_capacity(ctx.remaining_accounts.len());
+    let mut skipped = Vec::new();
+    
+    for account_info in ctx.remaining_accounts.iter() {
+        if !account_info.is_writable {
+            return Err(VaultError::InvalidVaultConfig.into());
+        }
+        
+        let mut vault_depositor = Account::<VaultDepositor>::try_from(account_info)?;
+        if vault_depositor.vault != vault.key() {
+            return Err(VaultError::InvalidVaultConfig.into());
+        }
+        
+        let sync = vault_depositor.sync_rebase(vault)?;
+        if let Some(floor) = sync.floor {
+            let value_delta = vault.record_precision_floor(&floor)?;
+            emit!(PrecisionFloorApplied {
+                vault: vault.key(),
+                authority: vault_depositor.authority,
+                computed_shares: floor.computed_shares,
+                granted_shares: floor.granted_shares,
+                value_delta,
+                precision_floor_events: vault.precision_floor_events,
+                timestamp: current_time,
+            });
+        }
+        
+        if sync.synced {
+            vault_depositor.exit(ctx.program_id)?;
+            synced.push(account_info.key());
+        } else {
+            skipped.push(account_info.key());
+        }
+    }
+    
+    msg!(
+        "Batch rebase sync: {} synced, {} already up to date (version {})",
+        synced.len(),
+        skipped.len(),
+        vault.rebase_version
+    );
+    
+    emit!(RebaseBatchSyncedEvent {
+        vault: vault.key(),
+        rebase_version: vault.rebase_version,
+        synced,
+        skipped,
+    });
+    
+    Ok(())
+}
@@ -0,0 +1,105 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::spl_token_2022::extension::{
+    BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+};
+use anchor_spl::token_interface::spl_token_2022::state::Mint as MintState;
+use anchor_spl::token_interface::{
+    self, HarvestWithheldTokensToMint, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+use crate::error::*;
+use crate::math::SafeMath;
+
+/// Token-2022 extensions a vault's underlying mint may carry. Rejected are the ones that let a
+/// third party move or freeze the vault's tokens (permanent delegate, frozen default account
+/// state), need extra accounts on every transfer (transfer hook), forbid transfers
+/// (non-transferable) or hide balances (confidential transfers)
+const SUPPORTED_MINT_EXTENSIONS: [ExtensionType; 9] = [
+    ExtensionType::TransferFeeConfig,
+    ExtensionType::MintCloseAuthority,
+    ExtensionType::InterestBearingConfig,
+    ExtensionType::MetadataPointer,
+    ExtensionType::TokenMetadata,
+    ExtensionType::GroupPointer,
+    ExtensionType::TokenGroup,
+    ExtensionType::GroupMemberPointer,
+    ExtensionType::TokenGroupMember,
+];
+
+/// Reject underlying mints with Token-2022 extensions the vault accounting cannot follow.
+/// Mints of the original token program have none
+pub(crate) fn check_mint_extensions(mint: &InterfaceAccount<Mint>) -> Result<()> {
+    let mint_info = mint.to_account_info();
+    if *mint_info.owner != token_interface::spl_token_2022::ID {
+        return Ok(());
+    }
+    
+    let data = mint_info.try_borrow_data()?;
+    let state = StateWithExtensions::<MintState>::unpack(&data)?;
+    for extension in state.get_extension_types()? {
+        if !SUPPORTED_MINT_EXTENSIONS.contains(&extension) {
+            msg!("Unsupported mint extension: {:?}", extension);
+            return Err(VaultError::UnsupportedMintExtension.into());
+        }
+    }
+    
+    Ok(())
+}
+
+/// Whether `mint` charges a Token-2022 transfer fee, which leaves withheld tokens behind in
+/// the accounts it is paid into
+pub(crate) fn has_transfer_fee(mint: &InterfaceAccount<Mint>) -> Result<bool> {
+    let mint_info = mint.to_account_info();
+    if *mint_info.owner != token_interface::spl_token_2022::ID {
+        return Ok(false);
+    }
+    
+    let data = mint_info.try_borrow_data()?;
+    let state = StateWithExtensions::<MintState>::unpack(&data)?;
+    Ok(state.get_extension_types()?.contains(&ExtensionType::TransferFeeConfig))
+}
+
+/// Move `amount` tokens of `mint` with `transfer_checked`, signed by `authority`, or by the
+/// vault PDA when `signer_seeds` are given. Returns what `to` was credited, which is less than
+/// `amount` when the mint charges a transfer fee: the fee stays withheld in `to`
+pub(crate) fn transfer_tokens<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    mint: &InterfaceAccount<'info, Mint>,
+    from: AccountInfo<'info>,
+    to: &mut InterfaceAccount<'info, TokenAccount>,
+    authority: AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+    amount: u64,
+) -> Result<u64> {
+    let balance_before = to.amount;
+    
+    let cpi_accounts = TransferChecked {
+        from,
+        mint: mint.to_account_info(),
+        to: to.to_account_info(),
+        authority,
+    };
+    let cpi_ctx = CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, signer_seeds);
+    token_interface::transfer_checked(cpi_ctx, amount, mint.decimals)?;
+    
+    to.reload()?;
+    Ok(to.amount.safe_sub(balance_before)?)
+}
+
+/// Move the transfer fees withheld in `token_account` to the mint, where the mint's withdraw
+/// authority collects them. Permissionless; a token account holding withheld fees cannot be closed
+pub(crate) fn harvest_withheld_fees<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    mint: &InterfaceAccount<'info, Mint>,
+    token_account: &InterfaceAccount<'info, TokenAccount>,
+) -> Result<()> {
+    if !has_transfer_fee(mint)? {
+        return Ok(());
+    }
+    
+    let cpi_accounts = HarvestWithheldTokensToMint {
+        token_program_id: token_program.to_account_info(),
+        mint: mint.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new(token_program.to_account_info(), cpi_accounts);
+    token_interface::harvest_withheld_tokens_to_mint(cpi_ctx, vec![token_account.to_account_info()])
+}
@@ -0,0 +1,159 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::state::*;
+use crate::constants::{PAUSE_INIT_DEPOSITOR, PAUSE_TRANSFER};
+use crate::error::*;
+use crate::events::{DepositorInitializedEvent, SharesTransferredEvent};
+use crate::utils::*;
+use super::share_tokens::{transfer_share_tokens, ShareTokenAccounts};
+use super::sync_rebase::sync_depositor_rebase;
+
+#[derive(Accounts)]
+#[instruction(recipient: Pubkey)]
+pub struct TransferShares<'info> {
+    #[account(mut)]
+    pub vault: Account<'info, Vault>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault_depositor", vault.key().as_ref(), authority.key().as_ref()],
+        bump,
+        constraint = vault_depositor.authority == authority.key() @ VaultError::Unauthorized,
+        constraint = vault_depositor.vault == vault.key() @ VaultError::InvalidVaultConfig,
+    )]
+    pub vault_depositor: Account<'info, VaultDepositor>,
+    
+    /// Recipient's depositor, created on first use with the sender covering rent
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = VaultDepositor::LEN_V2,
+        seeds = [b"vault_depositor", vault.key().as_ref(), recipient.as_ref()],
+        bump
+    )]
+    pub recipient_depositor: Account<'info, VaultDepositor>,
+    
+    /// Allowlist marker for `recipient`, required only while the vault is permissioned
+    pub allowed_depositor: Option<Account<'info, AllowedDepositor>>,
+    
+    /// Share mint of a tokenized vault, required only when `vault.share_mint` is set
+    pub share_mint: Option<InterfaceAccount<'info, Mint>>,
+    
+    /// `authority`'s token account of `share_mint`
+    #[account(mut)]
+    pub user_share_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    /// `recipient`'s token account of `share_mint`
+    #[account(mut)]
+    pub recipient_share_account: Option<InterfaceAccount<'info, TokenAccount>>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// Required with `share_mint`
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Move exactly `shares` active shares to `recipient`'s position without going through the
+/// unstake lockup. Totals do not change, a pending request stays with the sender and the
+/// recipient inherits the sender's stake cooldown if it ends later
+pub fn transfer_shares(
+    ctx: Context<TransferShares>,
+    recipient: Pubkey,
+    shares: u64,
+) -> Result<()> {
+    // Only `transfer_shares_all` gives away the whole position
+    if shares == u64::MAX {
+        return Err(VaultError::AmbiguousMaxAmount.into());
+    }
+    
+    transfer_shares_inner(ctx, recipient, Some(shares))
+}
+
+/// Move every active share to `recipient`'s position, as `transfer_shares` does
+pub fn transfer_shares_all(
+    ctx: Context<TransferShares>,
+    recipient: Pubkey,
+) -> Result<()> {
+    transfer_shares_inner(ctx, recipient, None)
+}
+
+/// Moves exactly `shares` active shares, or all of them without a count
+fn transfer_shares_inner(
+    ctx: Context<TransferShares>,
+    recipient: Pubkey,
+    shares: Option<u64>,
+) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let vault_depositor = &mut ctx.accounts.vault_depositor;
+    let recipient_depositor = &mut ctx.accounts.recipient_depositor;
+    let timestamp = get_current_timestamp();
+    
+    vault.check_not_paused(PAUSE_TRANSFER)?;
+    
+    if recipient == ctx.accounts.authority.key() {
+        return Err(VaultError::SelfTransfer.into());
+    }
+    
+    // The recipient ends up holding shares, so it must be allowed like a staker
+    vault.check_depositor_allowed(vault.key(), recipient, ctx.accounts.allowed_depositor.as_deref())?;
+    
+    if recipient_depositor.vault == Pubkey::default() {
+        vault.check_not_paused(PAUSE_INIT_DEPOSITOR)?;
+        recipient_depositor.initialize(vault.key(), recipient)?;
+        recipient_depositor.last_rebase_version = vault.rebase_version;
+        recipient_depositor.last_shares_base = vault.shares_base;
+        vault.record_depositor_opened()?;
+        
+        emit!(DepositorInitializedEvent {
+            vault: vault.key(),
+            authority: recipient,
+            timestamp,
+        });
+    }
+    
+    if recipient_depositor.vault != vault.key() || recipient_depositor.authority != recipient {
+        return Err(VaultError::Unauthorized.into());
+    }
+    
+    sync_depositor_rebase(vault, vault_depositor, timestamp)?;
+    sync_depositor_rebase(vault, recipient_depositor, timestamp)?;
+    
+    let sender_before = vault_depositor.shares;
+    let recipient_before = recipient_depositor.shares;
+    let shares = match shares {
+        Some(shares) => vault_depositor.transfer_shares(recipient_depositor, vault, shares)?,
+        None => vault_depositor.transfer_shares_all(recipient_depositor, vault)?,
+    };
+    vault.record_active_shares_change(sender_before, vault_depositor.shares);
+    vault.record_active_shares_change(recipient_before, recipient_depositor.shares);
+    
+    transfer_share_tokens(
+        vault,
+        ShareTokenAccounts {
+            share_mint: ctx.accounts.share_mint.as_mut(),
+            share_account: ctx.accounts.user_share_account.as_ref(),
+            token_program: ctx.accounts.token_program.as_ref(),
+        },
+        ctx.accounts.recipient_share_account.as_ref(),
+        recipient,
+        &ctx.accounts.authority.to_account_info(),
+        shares,
+    )?;
+    
+    let amount = vault.shares_value(shares)?;
+    
+    emit!(SharesTransferredEvent {
+        vault: vault.key(),
+        from: ctx.accounts.authority.key(),
+        to: recipient,
+        shares,
+        amount,
+        timestamp,
+    });
+    
+    msg!("Transferred {} shares ({} tokens) to {}", shares, amount, recipient);
+    
+    Ok(())
+}
@@ -1,14 +1,23 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 use crate::state::*;
+use crate::constants::PAUSE_UNSTAKE;
 use crate::error::*;
+use crate::events::UnstakeExecutedEvent;
+use crate::math::SafeMath;
 use crate::utils::*;
-use crate::math::{SafeMath, SafeCast};
-use crate::constants::*;
+use super::accrue_rewards::accrue_vested_rewards;
+use super::sync_rebase::sync_depositor_rebase;
+use super::token_transfers::transfer_tokens;
 
 #[derive(Accounts)]
 pub struct Unstake<'info> {
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"vault", vault.name.as_ref()],
+        bump = vault.bump,
+        constraint = !vault.is_operation_paused(PAUSE_UNSTAKE) @ VaultError::WithdrawalsPaused,
+    )]
     pub vault: Account<'info, Vault>,
     
     #[account(
@@ -25,128 +34,100 @@ pub struct Unstake<'info> {
         seeds = [b"vault_token_account", vault.key().as_ref()],
         bump,
         constraint = vault_token_account.key() == vault.vault_token_account @ VaultError::InvalidTokenAccount,
+        constraint = vault_token_account.owner == vault.key() @ VaultError::InvalidTokenAccount,
+        constraint = vault_token_account.mint == vault.token_mint @ VaultError::InvalidTokenAccount,
+        constraint = !vault_token_account.is_frozen() @ VaultError::TokenAccountFrozen,
     )]
-    pub vault_token_account: Account<'info, TokenAccount>,
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
     
     #[account(
         mut,
+        constraint = user_token_account.key() != vault_token_account.key() @ VaultError::InvalidTokenAccount,
         constraint = user_token_account.mint == vault.token_mint @ VaultError::InvalidTokenMint,
         constraint = user_token_account.owner == authority.key() @ VaultError::Unauthorized,
     )]
-    pub user_token_account: Account<'info, TokenAccount>,
+    pub user_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(constraint = token_mint.key() == vault.token_mint @ VaultError::InvalidTokenMint)]
+    pub token_mint: InterfaceAccount<'info, Mint>,
     
     pub authority: Signer<'info>,
     
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
 }
 
+/// Pay out the matured request. Fails with `SlippageExceeded` when the user would receive
+/// less than `min_amount_out` (0 = any), after the withdrawal fee and any transfer fee
 pub fn unstake(
     ctx: Context<Unstake>,
+    min_amount_out: u64,
+    client_id: [u8; 16],
 ) -> Result<()> {
-    // Manually verify that the vault account is the correct PDA
-    let expected_vault_key = Pubkey::find_program_address(
-        &[b"vault", &ctx.accounts.vault.name],
-        ctx.program_id
-    ).0;
-    
-    if ctx.accounts.vault.key() != expected_vault_key {
-        return Err(VaultError::InvalidVaultConfig.into());
-    }
+    let current_time = get_current_timestamp();
     
     // Check if unstake request exists and lockup period has passed
-    let current_time = get_current_timestamp();
-    if !ctx.accounts.vault_depositor.can_unstake(current_time, ctx.accounts.vault.unstake_lockup_period) {
+    let lockup_period = ctx.accounts.vault.effective_unstake_lockup(current_time);
+    if !ctx.accounts.vault_depositor.can_unstake(current_time, lockup_period) {
         return Err(VaultError::UnstakeLockupNotFinished.into());
     }
     
-    // Get unstake request details
-    let shares = ctx.accounts.vault_depositor.unstake_request.shares;
-    let asset_per_share_at_request = ctx.accounts.vault_depositor.unstake_request.asset_per_share_at_request;
+    // The payout is frozen, releasing vested rewards only keeps the vault's assets current
+    accrue_vested_rewards(&mut ctx.accounts.vault, current_time)?;
     
-    if shares == 0 {
-        return Err(VaultError::NoUnstakeRequest.into());
-    }
+    // The request's pending shares are released from the vault counters in the current base
+    sync_depositor_rebase(&mut ctx.accounts.vault, &mut ctx.accounts.vault_depositor, current_time)?;
     
-    // Calculate amount based on the frozen share value at request time
-    let amount = SafeCast::<u128>::safe_cast(&shares)?
-        .safe_mul(asset_per_share_at_request)?
-        .safe_div(SafeCast::<u128>::safe_cast(&PRECISION)?)?;
-    let amount = SafeCast::<u64>::safe_cast(&amount)?;
+    // The request pays the amount frozen at request time, less the withdrawal fee that stays
+    // with the remaining stakers
+    let frozen_amount = ctx.accounts.vault_depositor.unstake_request.frozen_amount()?;
+    let withdrawal_fee = ctx.accounts.vault.withdrawal_fee(frozen_amount)?;
+    let amount = frozen_amount.safe_sub(withdrawal_fee)?;
     
     // CRITICAL SECURITY FIX: Verify vault has sufficient liquidity
     if ctx.accounts.vault_token_account.amount < amount {
         return Err(VaultError::InsufficientLiquidity.into());
     }
     
-    // Prepare vault seeds for signing before any mutations
-    // Use complete 32-byte name array (including trailing zeros) for PDA calculation
-    let vault_name = ctx.accounts.vault.name;
-    let vault_bump = ctx.accounts.vault.bump;
-    
-    // Debug: Verify our PDA calculation
-    let expected_vault_pda = Pubkey::find_program_address(
-        &[b"vault", &vault_name],
-        ctx.program_id
-    ).0;
-    
-    msg!("Vault account: {}", ctx.accounts.vault.key());
-    msg!("Expected PDA: {}", expected_vault_pda);
-    msg!("Vault bump: {}", vault_bump);
-    msg!("PDA matches: {}", ctx.accounts.vault.key() == expected_vault_pda);
-    
-    // CRITICAL FIX: Use the actual bump from PDA calculation, not the stored (wrong) bump
-    let (_, actual_bump) = Pubkey::find_program_address(
-        &[b"vault", &vault_name],
-        ctx.program_id
-    );
-    
-    msg!("Using actual bump: {} instead of stored bump: {}", actual_bump, vault_bump);
-    
-    let vault_seeds = &[
-        b"vault",
-        vault_name.as_ref(),
-        &[actual_bump]
-    ];
-    let signer_seeds = &[vault_seeds.as_slice()];
-    
-    // Transfer tokens from vault to user BEFORE state updates to avoid borrowing issues
-    let cpi_accounts = Transfer {
-        from: ctx.accounts.vault_token_account.to_account_info(),
-        to: ctx.accounts.user_token_account.to_account_info(),
-        authority: ctx.accounts.vault.to_account_info(),
+    // Transfer tokens from vault to user. The vault pays exactly `amount`, a transfer fee of
+    // the mint is withheld from what the user receives
+    let received = {
+        let vault = &ctx.accounts.vault;
+        let signer_seeds: &[&[&[u8]]] = &[&vault.get_signer_seeds()];
+        transfer_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.token_mint,
+            ctx.accounts.vault_token_account.to_account_info(),
+            &mut ctx.accounts.user_token_account,
+            vault.to_account_info(),
+            signer_seeds,
+            amount,
+        )?
     };
-    let cpi_program = ctx.accounts.token_program.to_account_info();
-    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
-    
-    token::transfer(cpi_ctx, amount)?;
+    check_min_out(received, min_amount_out)?;
     
     // Now update state after successful transfer
     let vault = &mut ctx.accounts.vault;
-    let vault_depositor = &mut ctx.accounts.vault_depositor;
-    
-    // CRITICAL: Release both pending shares and corresponding reserved assets
-    // This maintains the strict separation between active and frozen resources
-    vault.pending_unstake_shares = vault.pending_unstake_shares.safe_sub(shares)?;
-    vault.reserved_assets = vault.reserved_assets.safe_sub(amount)?;
-    
-    // Update vault state - subtract from both total counters
-    vault.total_shares = vault.total_shares.safe_sub(shares)?;
-    vault.total_assets = vault.total_assets.safe_sub(amount)?;
-    
-    // Mathematical verification:
-    // - User gets exactly the frozen asset amount (predictable)
-    // - Available assets = total_assets - reserved_assets (unchanged ratio)
-    // - Active share value = available_assets / active_shares (unchanged)
-    
-    // Note: User's shares were already reduced during request_unstake
-    // No need to reduce again here
-    vault_depositor.total_unstaked = vault_depositor.total_unstaked.safe_add(amount)?;
-    vault_depositor.unstake_request.reset();
-    
-    // INVARIANT CHECK: Verify vault state consistency after unstake
-    vault.verify_invariants()?;
-    
-    msg!("Unstaked {} shares, received {} tokens (frozen value), released {} reserved assets", shares, amount, amount);
+    let (shares, _) = ctx.accounts.vault_depositor.execute_unstake(vault, current_time)?;
+    
+    emit!(UnstakeExecutedEvent {
+        vault: vault.key(),
+        authority: ctx.accounts.authority.key(),
+        amount,
+        withdrawal_fee,
+        transfer_fee: amount.safe_sub(received)?,
+        shares,
+        active_share_value: vault.active_share_value_or_zero()?,
+        client_id,
+        timestamp: current_time,
+    });
+    
+    msg!(
+        "Unstaked {} shares, received {} tokens (frozen value less a {} withdrawal fee), released {} reserved assets",
+        shares,
+        amount,
+        withdrawal_fee,
+        frozen_amount
+    );
     
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::events::ProtocolConfigUpdatedEvent;
+use crate::utils::*;
+
+#[derive(Accounts)]
+pub struct UpdateProtocolConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"protocol_config"],
+        bump = protocol_config.bump,
+        constraint = protocol_config.admin == admin.key() @ VaultError::Unauthorized
+    )]
+    pub protocol_config: Account<'info, ProtocolConfig>,
+    
+    pub admin: Signer<'info>,
+}
+
+/// Change the protocol defaults and caps. Vaults that already exist keep their configuration
+pub fn update_protocol_config(
+    ctx: Context<UpdateProtocolConfig>,
+    params: ProtocolConfigParams,
+) -> Result<()> {
+    let protocol_config = &mut ctx.accounts.protocol_config;
+    
+    protocol_config.update(params)?;
+    
+    emit!(ProtocolConfigUpdatedEvent {
+        protocol_config: protocol_config.key(),
+        admin: protocol_config.admin,
+        platform_account: protocol_config.platform_account,
+        default_unstake_lockup_period: protocol_config.default_unstake_lockup_period,
+        default_platform_share_bps: protocol_config.default_platform_share_bps,
+        max_platform_share_bps: protocol_config.max_platform_share_bps,
+        default_management_fee_bps: protocol_config.default_management_fee_bps,
+        max_management_fee_bps: protocol_config.max_management_fee_bps,
+        timestamp: get_current_timestamp(),
+    });
+    
+    msg!("Protocol config updated");
+    
+    Ok(())
+}
@@ -1,6 +1,8 @@
 use anchor_lang::prelude::*;
 use crate::state::*;
 use crate::error::*;
+use crate::events::{ConfigUpdatedEvent, PausedOperationsUpdatedEvent};
+use crate::utils::*;
 
 #[derive(Accounts)]
 pub struct UpdateVaultConfig<'info> {
@@ -13,15 +15,53 @@ pub struct UpdateVaultConfig<'info> {
     pub owner: Signer<'info>,
 }
 
+/// Applies at once: pauses always, other changes only while the vault has no config timelock
 pub fn update_vault_config(
     ctx: Context<UpdateVaultConfig>,
     params: UpdateVaultConfigParams,
 ) -> Result<()> {
     let vault = &mut ctx.accounts.vault;
     
-    vault.update_config(params)?;
+    vault.check_instant_config_update(&params)?;
+    apply_config_update(vault, ctx.accounts.owner.key(), params)?;
     
     msg!("Vault configuration updated");
     
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Apply `params` to the vault and emit the config and pause changes
+pub(crate) fn apply_config_update(
+    vault: &mut Account<Vault>,
+    owner: Pubkey,
+    params: UpdateVaultConfigParams,
+) -> Result<()> {
+    let timestamp = get_current_timestamp();
+    
+    let old_digest = vault.config_digest()?;
+    let old_mask = vault.paused_mask();
+    vault.update_config(params)?;
+    let new_digest = vault.config_digest()?;
+    let new_mask = vault.paused_mask();
+    
+    emit!(ConfigUpdatedEvent {
+        vault: vault.key(),
+        owner,
+        old_digest,
+        new_digest,
+        timestamp,
+    });
+    
+    if new_mask != old_mask {
+        emit!(PausedOperationsUpdatedEvent {
+            vault: vault.key(),
+            owner,
+            old_mask,
+            new_mask,
+            timestamp,
+        });
+        msg!("Paused operations: {:#09b} -> {:#09b}", old_mask, new_mask);
+    }
+    
+    Ok(())
+}
@@ -0,0 +1,170 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::state::*;
+use crate::constants::PAUSE_UNSTAKE;
+use crate::error::*;
+use crate::math::SafeMath;
+use crate::events::{OwnerSharesWithdrawnEvent, PrecisionFloorApplied};
+use crate::utils::*;
+use super::accrue_rewards::accrue_vested_rewards;
+use super::token_transfers::transfer_tokens;
+
+#[derive(Accounts)]
+pub struct WithdrawOwnerShares<'info> {
+    #[account(
+        mut,
+        constraint = vault.owner == owner.key() @ VaultError::Unauthorized,
+    )]
+    pub vault: Account<'info, Vault>,
+    
+    /// Handed to each new owner by `accept_ownership` after its shares are settled
+    #[account(
+        mut,
+        seeds = [b"owner_fee_depositor", vault.key().as_ref()],
+        bump,
+        constraint = owner_fee_depositor.vault == vault.key() @ VaultError::InvalidVaultConfig,
+        constraint = owner_fee_depositor.authority == owner.key() @ VaultError::Unauthorized,
+    )]
+    pub owner_fee_depositor: Account<'info, VaultDepositor>,
+    
+    #[account(
+        mut,
+        seeds = [b"vault_token_account", vault.key().as_ref()],
+        bump,
+        constraint = vault_token_account.key() == vault.vault_token_account @ VaultError::InvalidTokenAccount,
+        constraint = vault_token_account.owner == vault.key() @ VaultError::InvalidTokenAccount,
+        constraint = vault_token_account.mint == vault.token_mint @ VaultError::InvalidTokenAccount,
+        constraint = !vault_token_account.is_frozen() @ VaultError::TokenAccountFrozen,
+    )]
+    pub vault_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        constraint = owner_token_account.key() != vault_token_account.key() @ VaultError::InvalidTokenAccount,
+        constraint = owner_token_account.mint == vault.token_mint @ VaultError::InvalidTokenMint,
+        constraint = owner_token_account.owner == owner.key() @ VaultError::Unauthorized,
+    )]
+    pub owner_token_account: InterfaceAccount<'info, TokenAccount>,
+    
+    #[account(constraint = token_mint.key() == vault.token_mint @ VaultError::InvalidTokenMint)]
+    pub token_mint: InterfaceAccount<'info, Mint>,
+    
+    pub owner: Signer<'info>,
+    
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Redeem exactly `amount` assets of owner fee shares at the active share value, including
+/// unclaimed performance fees. Only liquidity beyond `reserved_assets` can be withdrawn
+pub fn withdraw_owner_shares(
+    ctx: Context<WithdrawOwnerShares>,
+    amount: u64,
+) -> Result<()> {
+    // `withdraw_owner_shares_all` redeems every fee share, an overflowed amount must not
+    if amount == u64::MAX {
+        return Err(VaultError::AmbiguousMaxAmount.into());
+    }
+    
+    withdraw_owner_shares_inner(ctx, Some(amount))?;
+    Ok(())
+}
+
+/// Redeem every owner fee share, as `withdraw_owner_shares` does. Fails with
+/// `SlippageExceeded` when the owner would receive less than `min_amount_out` (0 = any)
+pub fn withdraw_owner_shares_all(
+    ctx: Context<WithdrawOwnerShares>,
+    min_amount_out: u64,
+) -> Result<()> {
+    let received = withdraw_owner_shares_inner(ctx, None)?;
+    check_min_out(received, min_amount_out)
+}
+
+/// Redeems exactly `amount` assets, or every fee share without one. Returns what the owner
+/// received
+fn withdraw_owner_shares_inner(
+    ctx: Context<WithdrawOwnerShares>,
+    amount: Option<u64>,
+) -> Result<u64> {
+    let current_time = get_current_timestamp();
+    
+    ctx.accounts.vault.check_not_paused(PAUSE_UNSTAKE)?;
+    
+    accrue_vested_rewards(&mut ctx.accounts.vault, current_time)?;
+    
+    // Nobody else can sync this depositor, so follow the latest rebase here
+    let sync = ctx.accounts.owner_fee_depositor.sync_rebase(&mut ctx.accounts.vault)?;
+    if let Some(floor) = sync.floor {
+        let vault = &mut ctx.accounts.vault;
+        let value_delta = vault.record_precision_floor(&floor)?;
+        emit!(PrecisionFloorApplied {
+            vault: vault.key(),
+            authority: ctx.accounts.owner_fee_depositor.authority,
+            computed_shares: floor.computed_shares,
+            granted_shares: floor.granted_shares,
+            value_delta,
+            precision_floor_events: vault.precision_floor_events,
+            timestamp: current_time,
+        });
+    }
+    
+    // Performance fees minted since the last withdrawal join the redeemable fee shares
+    let claimed_shares = ctx.accounts.vault.claim_owner_shares(&mut ctx.accounts.owner_fee_depositor)?;
+    if claimed_shares > 0 {
+        msg!("Claimed {} performance fee shares", claimed_shares);
+    }
+    
+    let fee_shares = ctx.accounts.owner_fee_depositor.shares;
+    let (shares, payout) = match amount {
+        Some(amount) => ctx.accounts.vault.owner_redemption_quote(amount, fee_shares)?,
+        None => ctx.accounts.vault.owner_redemption_all_quote(fee_shares)?,
+    };
+    
+    // Assets reserved for pending requests stay with the depositors who froze them, rewards
+    // still vesting with the active shares
+    let free_balance = ctx.accounts.vault_token_account.amount
+        .saturating_sub(ctx.accounts.vault.reserved_assets)
+        .saturating_sub(ctx.accounts.vault.unvested_rewards);
+    if free_balance < payout {
+        return Err(VaultError::InsufficientLiquidity.into());
+    }
+    
+    // Accruing, syncing and claiming above only price the redemption, a failed transfer
+    // reverts them with the instruction. The shares are redeemed once the payout went through
+    let received = {
+        let vault = &ctx.accounts.vault;
+        let signer_seeds: &[&[&[u8]]] = &[&vault.get_signer_seeds()];
+        transfer_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.token_mint,
+            ctx.accounts.vault_token_account.to_account_info(),
+            &mut ctx.accounts.owner_token_account,
+            vault.to_account_info(),
+            signer_seeds,
+            payout,
+        )?
+    };
+    
+    let vault = &mut ctx.accounts.vault;
+    let owner_fee_depositor = &mut ctx.accounts.owner_fee_depositor;
+    owner_fee_depositor.settle_secondary_rewards(vault.secondary_rewards_per_share)?;
+    let shares_held = owner_fee_depositor.shares;
+    let released = owner_fee_depositor.release_cost_basis(shares, shares_held)?;
+    owner_fee_depositor.shares = owner_fee_depositor.shares.safe_sub(shares)?;
+    owner_fee_depositor.total_unstaked = owner_fee_depositor.total_unstaked.safe_add(payout)?;
+    owner_fee_depositor.realize_pnl(payout, released)?;
+    vault.redeem_owner_shares(shares, payout)?;
+    
+    emit!(OwnerSharesWithdrawnEvent {
+        vault: vault.key(),
+        owner: ctx.accounts.owner.key(),
+        shares,
+        amount: payout,
+        remaining_shares: owner_fee_depositor.shares,
+        active_share_value: vault.active_share_value_or_zero()?,
+        timestamp: current_time,
+    });
+    
+    msg!("Owner redeemed {} fee shares for {} tokens, {} left", shares, payout, owner_fee_depositor.shares);
+    
+    Ok(received)
+}
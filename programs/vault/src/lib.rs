@@ -4,6 +4,7 @@ use state::*;
 
 pub mod constants;
 pub mod error;
+pub mod events;
 mod instructions;
 pub mod math;
 pub mod state;
@@ -30,27 +31,43 @@ pub mod simple_vault {
         instructions::initialize_vault_depositor(ctx)
     }
 
-    /// Stake tokens to the vault
+    /// Stake tokens to the vault (`u64::MAX` is rejected, not read as "all")
+    /// `client_id` is echoed in the emitted event (all zeros = not provided)
     pub fn stake(
         ctx: Context<Stake>,
         amount: u64,
+        min_shares_out: u64,
+        client_id: [u8; 16],
     ) -> Result<()> {
-        instructions::stake(ctx, amount)
+        instructions::stake(ctx, amount, min_shares_out, client_id)
     }
 
     /// Request to unstake tokens (14 days lockup)
     pub fn request_unstake(
         ctx: Context<RequestUnstake>,
         amount: u64,
+        max_shares_in: u64,
+        client_id: [u8; 16],
     ) -> Result<()> {
-        instructions::request_unstake(ctx, amount)
+        instructions::request_unstake(ctx, amount, max_shares_in, client_id)
+    }
+
+    /// Request to unstake the whole position (14 days lockup)
+    pub fn request_unstake_all(
+        ctx: Context<RequestUnstake>,
+        min_amount_out: u64,
+        client_id: [u8; 16],
+    ) -> Result<()> {
+        instructions::request_unstake_all(ctx, min_amount_out, client_id)
     }
 
     /// Execute unstake after lockup period
     pub fn unstake(
         ctx: Context<Unstake>,
+        min_amount_out: u64,
+        client_id: [u8; 16],
     ) -> Result<()> {
-        instructions::unstake(ctx)
+        instructions::unstake(ctx, min_amount_out, client_id)
     }
 
     /// Cancel unstake request
@@ -60,6 +77,13 @@ pub mod simple_vault {
         instructions::cancel_unstake_request(ctx)
     }
 
+    /// Restore a matured unstake request left uncollected past the vault's expiry (permissionless)
+    pub fn expire_unstake_request(
+        ctx: Context<ExpireUnstakeRequest>,
+    ) -> Result<()> {
+        instructions::expire_unstake_request(ctx)
+    }
+
     /// Add rewards to the vault (only owner/admin)
     pub fn add_rewards(
         ctx: Context<AddRewards>,
@@ -69,7 +93,8 @@ pub mod simple_vault {
     }
 
 
-    /// Update vault configuration (only owner)
+    /// Update vault configuration (only owner). With a config timelock only pauses apply
+    /// here, everything else goes through `propose_config_update`
     pub fn update_vault_config(
         ctx: Context<UpdateVaultConfig>,
         params: UpdateVaultConfigParams,
@@ -77,6 +102,28 @@ pub mod simple_vault {
         instructions::update_vault_config(ctx, params)
     }
 
+    /// Stage a config update for `commit_config_update` after the config timelock (only owner)
+    pub fn propose_config_update(
+        ctx: Context<ProposeConfigUpdate>,
+        params: UpdateVaultConfigParams,
+    ) -> Result<()> {
+        instructions::propose_config_update(ctx, params)
+    }
+
+    /// Apply the proposed config update once its timelock elapsed (only owner)
+    pub fn commit_config_update(
+        ctx: Context<CommitConfigUpdate>,
+    ) -> Result<()> {
+        instructions::commit_config_update(ctx)
+    }
+
+    /// Withdraw the proposed config update (only owner)
+    pub fn cancel_config_update(
+        ctx: Context<CancelConfigUpdate>,
+    ) -> Result<()> {
+        instructions::cancel_config_update(ctx)
+    }
+
     /// Apply rebase to vault (only vault owner)
     pub fn apply_rebase(
         ctx: Context<ApplyRebase>,
@@ -84,6 +131,14 @@ pub mod simple_vault {
         instructions::apply_rebase(ctx)
     }
 
+    /// Rebase by a chosen power of ten in one step (only vault owner)
+    pub fn force_rebase(
+        ctx: Context<ApplyRebase>,
+        expo_diff: u32,
+    ) -> Result<()> {
+        instructions::force_rebase(ctx, expo_diff)
+    }
+
     /// Sync user shares with vault rebase
     pub fn sync_rebase(
         ctx: Context<SyncRebase>,
@@ -91,4 +146,422 @@ pub mod simple_vault {
         instructions::sync_rebase(ctx)
     }
 
-}
\ No newline at end of file
+    /// Initialize the protocol-wide statistics singleton
+    pub fn initialize_protocol_stats(
+        ctx: Context<InitializeProtocolStats>,
+    ) -> Result<()> {
+        instructions::initialize_protocol_stats(ctx)
+    }
+
+    /// Fold a vault's current totals into protocol stats (permissionless)
+    pub fn roll_up_vault_stats(
+        ctx: Context<RollUpVaultStats>,
+    ) -> Result<()> {
+        instructions::roll_up_vault_stats(ctx)
+    }
+
+    /// Begin retiring the vault (only owner)
+    pub fn begin_decommission(
+        ctx: Context<BeginDecommission>,
+        notice_period: i64,
+    ) -> Result<()> {
+        instructions::begin_decommission(ctx, notice_period)
+    }
+
+    /// Pay out an unresponsive depositor after the decommission timeout (permissionless)
+    pub fn force_distribute(
+        ctx: Context<ForceDistribute>,
+    ) -> Result<()> {
+        instructions::force_distribute(ctx)
+    }
+
+    /// Close a decommissioned vault once all shares have exited (only owner)
+    pub fn finalize_decommission(
+        ctx: Context<FinalizeDecommission>,
+    ) -> Result<()> {
+        instructions::finalize_decommission(ctx)
+    }
+
+    /// Move legacy owner_shares into the owner fee depositor (only owner)
+    pub fn migrate_owner_shares(
+        ctx: Context<MigrateOwnerShares>,
+    ) -> Result<()> {
+        instructions::migrate_owner_shares(ctx)
+    }
+
+    /// Grow a vault created by an older program to the current layout (only owner)
+    pub fn migrate_vault(
+        ctx: Context<MigrateVault>,
+    ) -> Result<()> {
+        instructions::migrate_vault(ctx)
+    }
+
+    /// Grow a depositor account to the v2 layout (only the depositor's authority)
+    pub fn migrate_depositor(
+        ctx: Context<MigrateDepositor>,
+    ) -> Result<()> {
+        instructions::migrate_depositor(ctx)
+    }
+
+    /// Store the canonical bump of a vault created with a wrong one (permissionless)
+    pub fn repair_bump(
+        ctx: Context<RepairBump>,
+    ) -> Result<()> {
+        instructions::repair_bump(ctx)
+    }
+
+    /// Sync a batch of depositors with vault rebase (permissionless)
+    pub fn sync_rebase_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SyncRebaseBatch<'info>>,
+    ) -> Result<()> {
+        instructions::sync_rebase_batch(ctx)
+    }
+
+    /// Pay out a batch of matured unstake requests to their depositors (permissionless)
+    pub fn process_matured_unstakes<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ProcessMaturedUnstakes<'info>>,
+    ) -> Result<()> {
+        instructions::process_matured_unstakes(ctx)
+    }
+
+    /// View the SHA-256 digest of the vault configuration (returned via return data)
+    pub fn get_config_digest(
+        ctx: Context<GetConfigDigest>,
+    ) -> Result<[u8; 32]> {
+        instructions::get_config_digest(ctx)
+    }
+
+    /// Stake the payer's tokens on behalf of `beneficiary`, creating their depositor if needed
+    /// `client_id` is echoed in the emitted event (all zeros = not provided)
+    pub fn stake_for(
+        ctx: Context<StakeFor>,
+        beneficiary: Pubkey,
+        amount: u64,
+        client_id: [u8; 16],
+    ) -> Result<()> {
+        instructions::stake_for(ctx, beneficiary, amount, client_id)
+    }
+
+    /// View remaining vault capacity and the caller's minimum stake (returned via return data)
+    pub fn get_stake_capacity(
+        ctx: Context<GetStakeCapacity>,
+    ) -> Result<StakeCapacity> {
+        instructions::get_stake_capacity(ctx)
+    }
+
+    /// View the shares a stake of `amount` would mint (returned via return data)
+    pub fn preview_stake(
+        ctx: Context<PreviewStake>,
+        amount: u64,
+    ) -> Result<StakePreview> {
+        instructions::preview_stake(ctx, amount)
+    }
+
+    /// View what requesting `shares` would freeze and pay out (returned via return data)
+    pub fn preview_unstake(
+        ctx: Context<PreviewUnstake>,
+        shares: u64,
+    ) -> Result<UnstakePreview> {
+        instructions::preview_unstake(ctx, shares)
+    }
+
+    /// View the vault's share accounting and active share value (returned via return data)
+    pub fn get_vault_stats(
+        ctx: Context<GetVaultStats>,
+    ) -> Result<VaultStats> {
+        instructions::get_vault_stats(ctx)
+    }
+
+    /// Unstake immediately, paying the vault's instant unstake penalty
+    /// `client_id` is echoed in the emitted event (all zeros = not provided)
+    pub fn instant_unstake(
+        ctx: Context<InstantUnstake>,
+        amount: u64,
+        client_id: [u8; 16],
+    ) -> Result<()> {
+        instructions::instant_unstake(ctx, amount, client_id)
+    }
+
+    /// Unstake every active share immediately, paying the vault's instant unstake penalty
+    pub fn instant_unstake_all(
+        ctx: Context<InstantUnstake>,
+        min_amount_out: u64,
+        client_id: [u8; 16],
+    ) -> Result<()> {
+        instructions::instant_unstake_all(ctx, min_amount_out, client_id)
+    }
+
+    /// Propose a new vault owner (owner only)
+    pub fn propose_owner(
+        ctx: Context<ProposeOwner>,
+        new_owner: Pubkey,
+    ) -> Result<()> {
+        instructions::propose_owner(ctx, new_owner)
+    }
+
+    /// Accept a proposed ownership transfer (pending owner only)
+    pub fn accept_ownership(
+        ctx: Context<AcceptOwnership>,
+    ) -> Result<()> {
+        instructions::accept_ownership(ctx)
+    }
+
+    /// Cancel a pending ownership transfer (owner only)
+    pub fn cancel_owner_transfer(
+        ctx: Context<CancelOwnerTransfer>,
+    ) -> Result<()> {
+        instructions::cancel_owner_transfer(ctx)
+    }
+
+    /// Redeem owner fee shares at the active share value (owner only)
+    pub fn withdraw_owner_shares(
+        ctx: Context<WithdrawOwnerShares>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::withdraw_owner_shares(ctx, amount)
+    }
+
+    /// Redeem every owner fee share (owner only)
+    pub fn withdraw_owner_shares_all(
+        ctx: Context<WithdrawOwnerShares>,
+        min_amount_out: u64,
+    ) -> Result<()> {
+        instructions::withdraw_owner_shares_all(ctx, min_amount_out)
+    }
+
+    /// Record the program's upgrade authority from its ProgramData account (permissionless)
+    pub fn attest_upgrade_authority(
+        ctx: Context<AttestUpgradeAuthority>,
+    ) -> Result<()> {
+        instructions::attest_upgrade_authority(ctx)
+    }
+
+    /// Apply a pending vault rebase (permissionless, no-op when none is needed)
+    pub fn crank_rebase(
+        ctx: Context<CrankRebase>,
+    ) -> Result<()> {
+        instructions::crank_rebase(ctx)
+    }
+
+    /// Add a wallet to the vault's depositor allowlist (only owner)
+    pub fn add_allowed_depositor(
+        ctx: Context<AddAllowedDepositor>,
+        authority: Pubkey,
+    ) -> Result<()> {
+        instructions::add_allowed_depositor(ctx, authority)
+    }
+
+    /// Remove a wallet from the vault's depositor allowlist (only owner)
+    pub fn remove_allowed_depositor(
+        ctx: Context<RemoveAllowedDepositor>,
+    ) -> Result<()> {
+        instructions::remove_allowed_depositor(ctx)
+    }
+
+    /// Stake and request the unstake of the minted shares at once, for fixed-term deposits
+    /// that mature after one lockup period
+    pub fn stake_with_term(
+        ctx: Context<Stake>,
+        amount: u64,
+        client_id: [u8; 16],
+    ) -> Result<()> {
+        instructions::stake_with_term(ctx, amount, client_id)
+    }
+
+    /// Register a foreign program's PDA as a reward source (only owner)
+    pub fn register_program_reward_source(
+        ctx: Context<RegisterProgramRewardSource>,
+        program_id: Pubkey,
+        seeds: Vec<Vec<u8>>,
+    ) -> Result<()> {
+        instructions::register_program_reward_source(ctx, program_id, seeds)
+    }
+
+    /// Remove a program reward source (only owner)
+    pub fn remove_program_reward_source(
+        ctx: Context<RemoveProgramRewardSource>,
+    ) -> Result<()> {
+        instructions::remove_program_reward_source(ctx)
+    }
+
+    /// Move active shares to another wallet's position at face value, without the unstake lockup
+    pub fn transfer_shares(
+        ctx: Context<TransferShares>,
+        recipient: Pubkey,
+        shares: u64,
+    ) -> Result<()> {
+        instructions::transfer_shares(ctx, recipient, shares)
+    }
+
+    /// Move every active share to another wallet's position at face value
+    pub fn transfer_shares_all(
+        ctx: Context<TransferShares>,
+        recipient: Pubkey,
+    ) -> Result<()> {
+        instructions::transfer_shares_all(ctx, recipient)
+    }
+
+    /// Release rewards vested so far into the vault's assets (permissionless, no-op when none)
+    pub fn accrue_rewards(
+        ctx: Context<AccrueRewards>,
+    ) -> Result<()> {
+        instructions::accrue_rewards(ctx)
+    }
+
+    /// Mint the performance fee on the share value gain above the high-water mark
+    /// (permissionless, no-op without a gain)
+    pub fn crystallize_fees(
+        ctx: Context<CrystallizeFees>,
+    ) -> Result<()> {
+        instructions::crystallize_fees(ctx)
+    }
+
+    /// Send a foreign token balance held by the vault PDA to a destination (only owner).
+    /// Tokens of the staked mint can never be recovered
+    pub fn recover_tokens(
+        ctx: Context<RecoverTokens>,
+    ) -> Result<()> {
+        instructions::recover_tokens(ctx)
+    }
+
+    /// Add tokens sent straight to the vault token account to the stakers' assets like a reward
+    /// (permissionless, no-op without a surplus)
+    pub fn skim(
+        ctx: Context<Skim>,
+    ) -> Result<()> {
+        instructions::skim(ctx)
+    }
+
+    /// Check the vault token account covers the accounted assets (permissionless). A shortfall
+    /// emits an alert and pauses stakes and withdrawals on vaults with `pause_on_shortfall`
+    pub fn reconcile(
+        ctx: Context<Reconcile>,
+    ) -> Result<()> {
+        instructions::reconcile(ctx)
+    }
+
+    /// Create the optional share value checkpoint history of a vault (permissionless)
+    pub fn initialize_vault_checkpoints(
+        ctx: Context<InitializeVaultCheckpoints>,
+    ) -> Result<()> {
+        instructions::initialize_vault_checkpoints(ctx)
+    }
+
+    /// Append the share value to the vault's checkpoint history, at most once per
+    /// `checkpoint_interval` (permissionless)
+    pub fn record_checkpoint(
+        ctx: Context<RecordCheckpoint>,
+    ) -> Result<()> {
+        instructions::record_checkpoint(ctx)
+    }
+
+    /// Set the mint of a second reward token and create its vault token account (only owner,
+    /// once)
+    pub fn set_secondary_reward_mint(
+        ctx: Context<SetSecondaryRewardMint>,
+    ) -> Result<()> {
+        instructions::set_secondary_reward_mint(ctx)
+    }
+
+    /// Distribute secondary reward tokens to the active shares, authorized like `add_rewards`
+    pub fn add_secondary_rewards(
+        ctx: Context<AddSecondaryRewards>,
+        amount: u64,
+    ) -> Result<()> {
+        instructions::add_secondary_rewards(ctx, amount)
+    }
+
+    /// Transfer the secondary rewards a depositor's active shares earned to its authority
+    pub fn claim_secondary_rewards(
+        ctx: Context<ClaimSecondaryRewards>,
+    ) -> Result<()> {
+        instructions::claim_secondary_rewards(ctx)
+    }
+
+    /// Propose a new platform account (owner only, plus the current platform account once it
+    /// requires co-signing)
+    pub fn propose_platform_account(
+        ctx: Context<ProposePlatformAccount>,
+        new_platform_account: Pubkey,
+    ) -> Result<()> {
+        instructions::propose_platform_account(ctx, new_platform_account)
+    }
+
+    /// Accept a proposed platform account (pending platform account only)
+    pub fn accept_platform_account(
+        ctx: Context<AcceptPlatformAccount>,
+    ) -> Result<()> {
+        instructions::accept_platform_account(ctx)
+    }
+
+    /// Cancel a pending platform account change (owner only)
+    pub fn cancel_platform_account_proposal(
+        ctx: Context<CancelPlatformAccountProposal>,
+    ) -> Result<()> {
+        instructions::cancel_platform_account_proposal(ctx)
+    }
+
+    /// Require the platform account to co-sign platform account proposals (platform account only)
+    pub fn set_platform_cosign_required(
+        ctx: Context<SetPlatformCosignRequired>,
+        required: bool,
+    ) -> Result<()> {
+        instructions::set_platform_cosign_required(ctx, required)
+    }
+
+    /// Create a page of the vault registry (permissionless)
+    pub fn initialize_registry(
+        ctx: Context<InitializeRegistry>,
+        page: u16,
+    ) -> Result<()> {
+        instructions::initialize_registry(ctx, page)
+    }
+
+    /// Drop a closed vault from its registry page (permissionless)
+    pub fn remove_from_registry(
+        ctx: Context<RemoveFromRegistry>,
+    ) -> Result<()> {
+        instructions::remove_from_registry(ctx)
+    }
+
+    /// Create the protocol config of defaults and caps for new vaults (upgrade authority only)
+    pub fn initialize_protocol_config(
+        ctx: Context<InitializeProtocolConfig>,
+        admin: Pubkey,
+        params: ProtocolConfigParams,
+    ) -> Result<()> {
+        instructions::initialize_protocol_config(ctx, admin, params)
+    }
+
+    /// Update the protocol defaults and caps (protocol admin only)
+    pub fn update_protocol_config(
+        ctx: Context<UpdateProtocolConfig>,
+        params: ProtocolConfigParams,
+    ) -> Result<()> {
+        instructions::update_protocol_config(ctx, params)
+    }
+
+    /// Propose a new protocol admin (protocol admin only)
+    pub fn propose_protocol_admin(
+        ctx: Context<ProposeProtocolAdmin>,
+        new_admin: Pubkey,
+    ) -> Result<()> {
+        instructions::propose_protocol_admin(ctx, new_admin)
+    }
+
+    /// Accept a proposed protocol admin transfer (pending admin only)
+    pub fn accept_protocol_admin(
+        ctx: Context<AcceptProtocolAdmin>,
+    ) -> Result<()> {
+        instructions::accept_protocol_admin(ctx)
+    }
+
+    /// Lower a protocol governed vault's fees, optionally pausing deposits (protocol admin only)
+    pub fn override_vault_fees(
+        ctx: Context<OverrideVaultFees>,
+        params: FeeOverrideParams,
+    ) -> Result<()> {
+        instructions::override_vault_fees(ctx, params)
+    }
+
+}
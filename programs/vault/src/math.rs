@@ -148,7 +148,29 @@ pub mod vault_math {
     use super::*;
     use crate::constants::*;
 
-    /// Calculate shares to mint for a given amount
+    /// Direction a share/asset conversion rounds to. Every conversion rounds in the vault's
+    /// favour: shares minted and assets paid round down, shares burned for an exact amount
+    /// of assets round up
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum Rounding {
+        Up,
+        Down,
+    }
+
+    /// `a * b / c` in u128, rounded in `rounding` direction
+    pub fn mul_div(a: u64, b: u64, c: u64, rounding: Rounding) -> VaultResult<u64> {
+        let product = (a as u128).safe_mul(b as u128)?;
+        let quotient = product.safe_div(c as u128)?;
+        let quotient = if rounding == Rounding::Up && quotient.safe_mul(c as u128)? < product {
+            quotient.safe_add(1)?
+        } else {
+            quotient
+        };
+        quotient.safe_cast()
+    }
+
+    /// Shares to mint for depositing `amount`, rounded down. A deposit worth less than one
+    /// share fails with `ZeroShares` rather than being rounded up to one
     pub fn calculate_shares(amount: u64, total_supply: u64, total_assets: u64) -> VaultResult<u64> {
         if total_supply == 0 {
             return Ok(amount);
@@ -158,35 +180,24 @@ pub mod vault_math {
             return Err(VaultError::DivisionByZero);
         }
         
-        let shares = (amount as u128)
-            .safe_mul(total_supply as u128)?
-            .safe_div(total_assets as u128)?;
-        
-        let shares_u64 = shares.safe_cast()?;
-        
-        // Prevent precision loss: ensure user gets at least 1 share if they deposit non-zero amount
-        // This prevents users from losing funds due to rounding down to zero
-        if shares_u64 == 0 && amount > 0 {
-            return Ok(1);
+        let shares = mul_div(amount, total_supply, total_assets, Rounding::Down)?;
+        if shares == 0 && amount > 0 {
+            return Err(VaultError::ZeroShares);
         }
         
-        Ok(shares_u64)
+        Ok(shares)
     }
 
-    /// Calculate assets to return for a given amount of shares
+    /// Assets to pay for redeeming `shares`, rounded down
     pub fn calculate_assets(shares: u64, total_supply: u64, total_assets: u64) -> VaultResult<u64> {
         if total_supply == 0 {
             return Ok(0);
         }
         
-        let assets = (shares as u128)
-            .safe_mul(total_assets as u128)?
-            .safe_div(total_supply as u128)?;
-        
-        assets.safe_cast()
+        mul_div(shares, total_assets, total_supply, Rounding::Down)
     }
 
-    /// Calculate shares needed to withdraw a specific amount of assets
+    /// Shares to burn for withdrawing exactly `amount` assets, rounded up
     pub fn calculate_shares_for_assets(amount: u64, total_supply: u64, total_assets: u64) -> VaultResult<u64> {
         if total_supply == 0 {
             return Err(VaultError::InvalidSharesCalculation);
@@ -196,11 +207,7 @@ pub mod vault_math {
             return Err(VaultError::DivisionByZero);
         }
         
-        let shares = (amount as u128)
-            .safe_mul(total_supply as u128)?
-            .safe_div(total_assets as u128)?;
-        
-        shares.safe_cast()
+        mul_div(amount, total_supply, total_assets, Rounding::Up)
     }
 
     /// Calculate rewards per share with high precision
@@ -220,6 +227,36 @@ pub mod vault_math {
         last_rewards_per_share.safe_add(rewards_per_share)
     }
 
+    /// `cumulative` grown by the factor `assets_after / assets_before`, rounded down.
+    /// Saturates at u128::MAX instead of failing, so the accumulator never blocks a reward
+    pub fn compound_growth(cumulative: u128, assets_before: u64, assets_after: u64) -> u128 {
+        if assets_before == 0 {
+            return cumulative;
+        }
+
+        // cumulative * after / before without the 256-bit product: the remainder term
+        // is below before * after, which fits in u128
+        let before = assets_before as u128;
+        let after = assets_after as u128;
+        let remainder = (cumulative % before) * after / before;
+        (cumulative / before)
+            .checked_mul(after)
+            .and_then(|whole| whole.checked_add(remainder))
+            .unwrap_or(u128::MAX)
+    }
+
+    /// `amount` per share in the units before any rebase (`active_shares * 10^shares_base`),
+    /// scaled by SHARE_PRECISION. 0 when there are no such shares
+    pub fn rewards_per_base_share(amount: u64, active_shares: u64, shares_base: u32) -> u128 {
+        let base_shares = 10u128
+            .checked_pow(shares_base)
+            .and_then(|scale| scale.checked_mul(active_shares as u128));
+        match base_shares {
+            Some(base_shares) if base_shares > 0 => (amount as u128) * SHARE_PRECISION / base_shares,
+            _ => 0,
+        }
+    }
+
     /// Calculate pending rewards for a user
     pub fn calculate_pending_rewards(
         user_shares: u64,
@@ -237,25 +274,66 @@ pub mod vault_math {
         }
     }
 
-    /// Calculate rebase factor when shares become too large
+    /// Performance fee on the gain of the active share value above `high_water_mark`, both in
+    /// PRECISION units. Returns the fee shares to mint and the new mark, the share value after
+    /// minting them. No gain, or a recovery still below the mark, charges nothing and keeps the
+    /// mark; a gain too small to be worth one share is left for the next crystallization
+    pub fn calculate_performance_fee(
+        available_assets: u64,
+        active_shares: u64,
+        high_water_mark: u128,
+        fee_bps: u64,
+    ) -> VaultResult<(u64, u128)> {
+        if active_shares == 0 {
+            return Ok((0, high_water_mark));
+        }
+        
+        let share_value = (available_assets as u128)
+            .safe_mul(PRECISION as u128)?
+            .safe_div(active_shares as u128)?;
+        if share_value <= high_water_mark {
+            return Ok((0, high_water_mark));
+        }
+        if fee_bps == 0 {
+            return Ok((0, share_value));
+        }
+        
+        // What the active shares would hold at the mark, the rest is profit
+        let mark_assets = high_water_mark
+            .safe_mul(active_shares as u128)?
+            .safe_div(PRECISION as u128)?;
+        let fee_amount = (available_assets as u128)
+            .safe_sub(mark_assets)?
+            .safe_mul(fee_bps as u128)?
+            .safe_div(BASIS_POINTS_PRECISION as u128)?;
+        
+        // Shares worth `fee_amount` once minted, out of assets that do not grow
+        let fee_shares: u64 = fee_amount
+            .safe_mul(active_shares as u128)?
+            .safe_div((available_assets as u128).safe_sub(fee_amount)?)?
+            .safe_cast()?;
+        if fee_shares == 0 {
+            return Ok((0, high_water_mark));
+        }
+        
+        let new_mark = (available_assets as u128)
+            .safe_mul(PRECISION as u128)?
+            .safe_div((active_shares as u128).safe_add(fee_shares as u128)?)?;
+        Ok((fee_shares, new_mark.max(high_water_mark)))
+    }
+
+    /// Calculate rebase factor when shares become too large: the largest power of ten not
+    /// above the shares per asset, so the rebased shares stay between 1x and 10x the assets
     pub fn calculate_rebase_factor(total_shares: u64, total_assets: u64) -> VaultResult<(u32, u128)> {
         if total_assets == 0 || total_shares <= total_assets {
             return Ok((0, 1));
         }
 
-        // Calculate how many times shares exceed assets
+        // Calculate how many times shares exceed assets, at least 1 here
         let ratio = (total_shares as u128).safe_div(total_assets as u128)?;
         
-        // Find the appropriate power of 10 to divide by
-        let mut expo_diff = 0u32;
-        let mut divisor = 1u128;
-        
-        while divisor < ratio && expo_diff < 20 { // Limit to prevent infinite loop
-            divisor = divisor.safe_mul(10)?;
-            expo_diff = expo_diff.safe_add(1)?;
-        }
-        
-        Ok((expo_diff, divisor))
+        let expo_diff = ratio.ilog10();
+        Ok((expo_diff, 10u128.pow(expo_diff)))
     }
 
 }
@@ -264,6 +342,7 @@ pub mod vault_math {
 mod tests {
     use super::*;
     use super::vault_math::*;
+    use crate::constants::{PRECISION, SHARE_PRECISION};
 
     #[test]
     fn test_safe_math_operations() {
@@ -292,8 +371,80 @@ mod tests {
         // Subsequent deposits should maintain proportional shares
         assert_eq!(calculate_shares(1000, 2000, 2000).unwrap(), 1000);
         assert_eq!(calculate_shares(500, 2000, 1000).unwrap(), 1000);
+
+        // Worth less than a share: no longer bumped up to one
+        assert!(matches!(calculate_shares(1, 1, 3), Err(VaultError::ZeroShares)));
     }
 
+    #[test]
+    fn test_rounding_always_favours_the_vault() {
+        assert_eq!(mul_div(7, 10, 3, Rounding::Down).unwrap(), 23);
+        assert_eq!(mul_div(7, 10, 3, Rounding::Up).unwrap(), 24);
+        assert_eq!(mul_div(6, 10, 3, Rounding::Up).unwrap(), 20);
+        assert!(matches!(mul_div(1, 1, 0, Rounding::Up), Err(VaultError::DivisionByZero)));
+
+        // Over a grid of prices, a deposit redeemed at once never returns more than it paid,
+        // and the shares burned for an exact amount are always worth at least that amount
+        for (supply, assets) in [(1_000u64, 1_000u64), (1_000, 3_001), (7, 1_000_003), (999_983, 17)] {
+            for amount in [1u64, 2, 3, 10, 333, 1_000, 65_537] {
+                if let Ok(shares) = calculate_shares(amount, supply, assets) {
+                    let paid = calculate_assets(shares, supply + shares, assets + amount).unwrap();
+                    assert!(paid <= amount, "{} in, {} out at {}/{}", amount, paid, supply, assets);
+                }
+                let burned = calculate_shares_for_assets(amount, supply, assets).unwrap();
+                assert!(calculate_assets(burned, supply, assets).unwrap() >= amount);
+            }
+        }
+    }
+
+
+    #[test]
+    fn test_performance_fee_without_gain_charges_nothing() {
+        let mark = PRECISION as u128 * 2;
+        // 1_000 shares at exactly the mark, then below it
+        assert_eq!(calculate_performance_fee(2_000, 1_000, mark, 2_000).unwrap(), (0, mark));
+        assert_eq!(calculate_performance_fee(1_500, 1_000, mark, 2_000).unwrap(), (0, mark));
+        assert_eq!(calculate_performance_fee(0, 0, mark, 2_000).unwrap(), (0, mark));
+        // Without a fee the mark follows the gain, so enabling one later charges nothing past
+        assert_eq!(calculate_performance_fee(3_000, 1_000, mark, 0).unwrap(), (0, PRECISION as u128 * 3));
+    }
+
+    #[test]
+    fn test_performance_fee_on_partial_recovery_below_mark() {
+        let mark = PRECISION as u128 * 2;
+        // The value fell to 1.5 and recovered to 1.9: still below the mark, nothing is owed
+        assert_eq!(calculate_performance_fee(1_900_000, 1_000_000, mark, 2_000).unwrap(), (0, mark));
+        // Only the part of a recovery above the mark is profit: 2.2 - 2.0 on 1M shares
+        let (fee_shares, new_mark) = calculate_performance_fee(2_200_000, 1_000_000, mark, 2_000).unwrap();
+        // 20% of 200_000 = 40_000 of value: 40_000 * 1M / 2_160_000 shares
+        assert_eq!(fee_shares, 18_518);
+        assert!(new_mark > mark && new_mark < PRECISION as u128 * 22 / 10);
+    }
+
+    #[test]
+    fn test_performance_fee_over_multiple_periods() {
+        let mut shares = 1_000_000u64;
+        let mut mark = PRECISION as u128;
+        let mut owner_shares = 0u64;
+        // +10%, -5%, back to +10%, then +5% on top, in assets held by the same active shares
+        let mut charged = Vec::new();
+        for assets in [1_100_000u64, 1_045_000, 1_100_000, 1_155_000] {
+            let (fee_shares, new_mark) = calculate_performance_fee(assets, shares, mark, 1_000).unwrap();
+            shares += fee_shares;
+            owner_shares += fee_shares;
+            mark = new_mark;
+            charged.push(fee_shares);
+        }
+        // The drawdown and the recovery to the previous peak are free
+        assert!(charged[0] > 0);
+        assert_eq!(charged[1], 0);
+        assert_eq!(charged[2], 0);
+        assert!(charged[3] > 0);
+        // 10_000 from the first gain, grown 5% with the vault, plus 10% of the last 55_000
+        // gain, less the dilution of the owner's own shares by that last fee
+        let owner_value = owner_shares as u128 * 1_155_000 / shares as u128;
+        assert!((15_940..=15_950).contains(&owner_value), "{}", owner_value);
+    }
 
     #[test]
     fn test_rebase_calculation() {
@@ -301,4 +452,62 @@ mod tests {
         assert_eq!(expo_diff, 4); // 10^4 = 10,000
         assert_eq!(divisor, 10_000);
     }
+
+    #[test]
+    fn test_rebase_divisor_never_exceeds_the_ratio() {
+        for (ratio, expo_diff) in [(9, 0), (10, 1), (11, 1), (999, 2), (1_000, 3), (1_001, 3), (1_500, 3)] {
+            assert_eq!(
+                calculate_rebase_factor(ratio * 1_000, 1_000).unwrap(),
+                (expo_diff, 10u128.pow(expo_diff)),
+                "ratio {}",
+                ratio
+            );
+        }
+        // Shares at most equal to the assets never rebase
+        assert_eq!(calculate_rebase_factor(1_000, 1_000).unwrap(), (0, 1));
+        assert_eq!(calculate_rebase_factor(1_000, 0).unwrap(), (0, 1));
+    }
+
+    #[test]
+    fn test_compound_growth_over_years_of_daily_rewards() {
+        // 1M USDC earning 5% a year, paid out daily for ten years
+        let mut assets = 1_000_000_000_000_000u64;
+        let mut cumulative = SHARE_PRECISION;
+        for _ in 0..3_650 {
+            let reward = assets / 7_300; // 5% / 365
+            cumulative = compound_growth(cumulative, assets, assets + reward);
+            assets += reward;
+        }
+
+        // The accumulator follows the asset growth up to rounding, and matches (1 + 5%/365)^3650
+        let expected = assets as u128 * SHARE_PRECISION / 1_000_000_000_000_000;
+        assert!(expected - cumulative < 10_000, "{} vs {}", cumulative, expected);
+        let growth = cumulative as f64 / SHARE_PRECISION as f64;
+        assert!((growth - (1.0 + 0.05 / 365.0f64).powi(3_650)).abs() < 1e-6, "{}", growth);
+    }
+
+    #[test]
+    fn test_compound_growth_is_exact_and_saturates() {
+        // The split multiplication loses nothing against the full-width product
+        assert_eq!(compound_growth(SHARE_PRECISION * 3 + 7, 3, 4), (SHARE_PRECISION * 3 + 7) * 4 / 3);
+        assert_eq!(compound_growth(u128::MAX / 2, u64::MAX, u64::MAX), u128::MAX / 2);
+
+        // No assets to grow leaves it alone
+        assert_eq!(compound_growth(SHARE_PRECISION, 0, 1_000), SHARE_PRECISION);
+
+        // Near the top it saturates instead of failing, and stays there
+        assert_eq!(compound_growth(u128::MAX / 2, 1, 3), u128::MAX);
+        assert_eq!(compound_growth(u128::MAX, 1_000, 1_001), u128::MAX);
+    }
+
+    #[test]
+    fn test_rewards_per_base_share_ignores_rebases() {
+        // 1_000 active shares before a rebase, 10 after a 10^2 rebase: same value per base share
+        let before = rewards_per_base_share(500, 1_000, 0);
+        assert_eq!(before, SHARE_PRECISION / 2);
+        assert_eq!(rewards_per_base_share(500, 10, 2), before);
+
+        assert_eq!(rewards_per_base_share(500, 0, 0), 0);
+        assert_eq!(rewards_per_base_share(u64::MAX, u64::MAX, 40), 0);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,24 @@
+use anchor_lang::prelude::*;
+
+/// Allowlist marker letting `authority` open a position and stake in a permissioned vault
+/// Seeds: `[b"allow", vault, authority]`
+#[account]
+#[derive(Default)]
+pub struct AllowedDepositor {
+    /// The vault the wallet is allowed into
+    pub vault: Pubkey,
+    /// The allowed wallet
+    pub authority: Pubkey,
+    /// When the owner added the wallet
+    pub added_at: i64,
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl AllowedDepositor {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // vault
+        32 + // authority
+        8 + // added_at
+        1; // bump
+}
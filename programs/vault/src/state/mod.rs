@@ -1,7 +1,31 @@
 pub mod vault;
 pub mod vault_depositor;
 pub mod unstake_request;
+pub mod protocol_stats;
+pub mod vault_registry_entry;
+pub mod program_config;
+pub mod allowed_depositor;
+pub mod program_reward_source;
+pub mod pending_config_update;
+pub mod vault_checkpoints;
+pub mod vault_registry;
+pub mod protocol_config;
 
 pub use vault::*;
 pub use vault_depositor::*;
-pub use unstake_request::*;
\ No newline at end of file
+pub use unstake_request::*;
+pub use protocol_stats::*;
+pub use vault_registry_entry::*;
+pub use program_config::*;
+pub use allowed_depositor::*;
+pub use program_reward_source::*;
+pub use pending_config_update::*;
+pub use vault_checkpoints::*;
+pub use vault_registry::*;
+pub use protocol_config::*;
+#[cfg(test)]
+mod test_clock;
+#[cfg(test)]
+mod state_machine_tests;
+#[cfg(test)]
+mod scenario_tests;
@@ -0,0 +1,28 @@
+use anchor_lang::prelude::*;
+use crate::state::UpdateVaultConfigParams;
+
+/// A config update proposed by the owner, applied by `commit_config_update` once
+/// `effective_at` is reached. One per vault
+/// Seeds: `[b"pending_config_update", vault]`
+#[account]
+pub struct PendingConfigUpdate {
+    /// The vault the update applies to
+    pub vault: Pubkey,
+    /// The update, validated against the vault when it was proposed and again on commit
+    pub params: UpdateVaultConfigParams,
+    /// When the owner proposed it
+    pub proposed_at: i64,
+    /// Earliest time it can be committed
+    pub effective_at: i64,
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl PendingConfigUpdate {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // vault
+        UpdateVaultConfigParams::MAX_LEN + // params
+        8 + // proposed_at
+        8 + // effective_at
+        1; // bump
+}
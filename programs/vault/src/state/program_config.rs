@@ -0,0 +1,71 @@
+use anchor_lang::prelude::*;
+
+/// Program-wide singleton recording the program's upgradeability, as last attested from
+/// its ProgramData account
+#[account]
+#[derive(Default)]
+pub struct ProgramConfig {
+    /// Upgrade authority at the last attestation (None = upgrades revoked, program immutable)
+    pub upgrade_authority: Option<Pubkey>,
+    /// Slot the program was last deployed or upgraded at, from ProgramData
+    pub deployed_slot: u64,
+    /// Slot of the last attestation (0 = never attested)
+    pub attested_slot: u64,
+    /// Unix time of the last attestation
+    pub attested_at: i64,
+    /// Bump seed for PDA
+    pub bump: u8,
+    /// Reserved for future use
+    pub _reserved: [u8; 32],
+}
+
+impl ProgramConfig {
+    pub const LEN: usize = 8 + // discriminator
+        33 + // upgrade_authority
+        8 + // deployed_slot
+        8 + // attested_slot
+        8 + // attested_at
+        1 + // bump
+        32; // _reserved
+
+    /// Record what ProgramData says at `slot`. Returns the authority recorded before,
+    /// so callers can tell whether upgradeability changed since the last attestation
+    pub fn record_attestation(
+        &mut self,
+        upgrade_authority: Option<Pubkey>,
+        deployed_slot: u64,
+        slot: u64,
+        timestamp: i64,
+    ) -> Option<Pubkey> {
+        let previous = self.upgrade_authority;
+        self.upgrade_authority = upgrade_authority;
+        self.deployed_slot = deployed_slot;
+        self.attested_slot = slot;
+        self.attested_at = timestamp;
+        previous
+    }
+
+    pub fn is_immutable(&self) -> bool {
+        self.attested_slot != 0 && self.upgrade_authority.is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attestation_tracks_revoked_authority() {
+        let mut config = ProgramConfig::default();
+        // Never attested says nothing about upgradeability
+        assert!(!config.is_immutable());
+
+        let authority = Pubkey::new_unique();
+        assert_eq!(config.record_attestation(Some(authority), 10, 20, 1_000), None);
+        assert!(!config.is_immutable());
+
+        assert_eq!(config.record_attestation(None, 10, 30, 2_000), Some(authority));
+        assert!(config.is_immutable());
+        assert_eq!((config.deployed_slot, config.attested_slot), (10, 30));
+    }
+}
@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+
+/// A foreign program's PDA registered as a reward source. Only `program_id` can sign for
+/// `authority`, so rewards signed by it provably come from that program
+/// Seeds: `[b"reward_source", vault, authority]`
+#[account]
+#[derive(Default)]
+pub struct ProgramRewardSource {
+    /// The vault accepting rewards from the source
+    pub vault: Pubkey,
+    /// Program the authority is derived from
+    pub program_id: Pubkey,
+    /// Hash of the seeds the authority is derived from, without the bump
+    pub seeds_hash: [u8; 32],
+    /// The derived PDA, expected as a signing `reward_source_authority` in `add_rewards`
+    pub authority: Pubkey,
+    /// When the owner registered the source
+    pub registered_at: i64,
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl ProgramRewardSource {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // vault
+        32 + // program_id
+        32 + // seeds_hash
+        32 + // authority
+        8 + // registered_at
+        1; // bump
+
+    /// Canonical PDA of `program_id` for `seeds`, None if the seeds cannot derive one
+    pub fn derive_authority(program_id: &Pubkey, seeds: &[Vec<u8>]) -> Option<Pubkey> {
+        let seeds: Vec<&[u8]> = seeds.iter().map(Vec::as_slice).collect();
+        if seeds.len() >= 16 || seeds.iter().any(|seed| seed.len() > 32) {
+            return None;
+        }
+        Pubkey::try_find_program_address(&seeds, program_id).map(|(authority, _)| authority)
+    }
+
+    pub fn hash_seeds(seeds: &[Vec<u8>]) -> [u8; 32] {
+        let seeds: Vec<&[u8]> = seeds.iter().map(Vec::as_slice).collect();
+        hashv(&seeds).to_bytes()
+    }
+}
@@ -0,0 +1,235 @@
+use anchor_lang::prelude::*;
+use crate::constants::*;
+use crate::error::*;
+use super::vault::InitializeVaultParams;
+
+/// Protocol-wide defaults and caps for new vaults, held by a protocol admin. `initialize_vault`
+/// reads it when supplied, so many vaults share one configuration
+/// Seeds: `[b"protocol_config"]`
+#[account]
+#[derive(Default)]
+pub struct ProtocolConfig {
+    /// Account allowed to update the config
+    pub admin: Pubkey,
+    /// Proposed admin awaiting `accept_protocol_admin` (default pubkey = none)
+    pub pending_admin: Pubkey,
+    /// Platform account of vaults created without one (default pubkey = none)
+    pub platform_account: Pubkey,
+    /// Unstake lockup of vaults created without one
+    pub default_unstake_lockup_period: i64,
+    /// Platform share of vaults created without one
+    pub default_platform_share_bps: u64,
+    /// Highest platform share a new vault can be created with
+    pub max_platform_share_bps: u64,
+    /// Management fee of vaults created without one
+    pub default_management_fee_bps: u64,
+    /// Highest management fee a new vault can be created with
+    pub max_management_fee_bps: u64,
+    /// Bump seed for PDA
+    pub bump: u8,
+    /// Reserved for future use
+    pub _reserved: [u8; 32],
+}
+
+/// Fields of `ProtocolConfig` to set. On `initialize_protocol_config` None takes the program's
+/// own default, on `update_protocol_config` None keeps the current value
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq)]
+pub struct ProtocolConfigParams {
+    pub platform_account: Option<Pubkey>,
+    pub default_unstake_lockup_period: Option<i64>,
+    pub default_platform_share_bps: Option<u64>,
+    pub max_platform_share_bps: Option<u64>,
+    pub default_management_fee_bps: Option<u64>,
+    pub max_management_fee_bps: Option<u64>,
+}
+
+impl ProtocolConfig {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // admin
+        32 + // pending_admin
+        32 + // platform_account
+        8 + // default_unstake_lockup_period
+        8 + // default_platform_share_bps
+        8 + // max_platform_share_bps
+        8 + // default_management_fee_bps
+        8 + // max_management_fee_bps
+        1 + // bump
+        32; // _reserved
+
+    pub fn initialize(&mut self, admin: Pubkey, params: ProtocolConfigParams, bump: u8) -> VaultResult<()> {
+        self.admin = admin;
+        self.pending_admin = Pubkey::default();
+        self.platform_account = Pubkey::default();
+        self.default_unstake_lockup_period = DEFAULT_UNSTAKE_LOCKUP;
+        self.default_platform_share_bps = DEFAULT_PLATFORM_SHARE_BPS;
+        self.max_platform_share_bps = MAX_PLATFORM_SHARE_BPS;
+        self.default_management_fee_bps = 0;
+        self.max_management_fee_bps = MAX_MANAGEMENT_FEE_BPS;
+        self.bump = bump;
+        self.update(params)
+    }
+
+    /// Apply the set fields, rejecting defaults above their cap and caps above the program's
+    pub fn update(&mut self, params: ProtocolConfigParams) -> VaultResult<()> {
+        if let Some(platform_account) = params.platform_account {
+            self.platform_account = platform_account;
+        }
+        if let Some(default_unstake_lockup_period) = params.default_unstake_lockup_period {
+            self.default_unstake_lockup_period = default_unstake_lockup_period;
+        }
+        if let Some(default_platform_share_bps) = params.default_platform_share_bps {
+            self.default_platform_share_bps = default_platform_share_bps;
+        }
+        if let Some(max_platform_share_bps) = params.max_platform_share_bps {
+            self.max_platform_share_bps = max_platform_share_bps;
+        }
+        if let Some(default_management_fee_bps) = params.default_management_fee_bps {
+            self.default_management_fee_bps = default_management_fee_bps;
+        }
+        if let Some(max_management_fee_bps) = params.max_management_fee_bps {
+            self.max_management_fee_bps = max_management_fee_bps;
+        }
+
+        if self.default_unstake_lockup_period < MIN_UNSTAKE_LOCKUP_MINUTES * ONE_MINUTE
+            || self.default_unstake_lockup_period > MAX_UNSTAKE_LOCKUP_DAYS * ONE_DAY
+        {
+            return Err(VaultError::InvalidVaultConfig);
+        }
+        if self.max_platform_share_bps > MAX_PLATFORM_SHARE_BPS
+            || self.default_platform_share_bps > self.max_platform_share_bps
+        {
+            return Err(VaultError::InvalidVaultConfig);
+        }
+        if self.max_management_fee_bps > MAX_MANAGEMENT_FEE_BPS
+            || self.default_management_fee_bps > self.max_management_fee_bps
+        {
+            return Err(VaultError::InvalidVaultConfig);
+        }
+
+        Ok(())
+    }
+
+    /// Fill what a new vault leaves unset with the protocol defaults. Returns the platform
+    /// account to use, the protocol's when `platform_account` is the default pubkey
+    pub fn fill_vault_defaults(&self, platform_account: Pubkey, params: &mut InitializeVaultParams) -> Pubkey {
+        params.unstake_lockup_period.get_or_insert(self.default_unstake_lockup_period);
+        params.platform_share_bps.get_or_insert(self.default_platform_share_bps);
+        params.management_fee_bps.get_or_insert(self.default_management_fee_bps);
+
+        if platform_account == Pubkey::default() {
+            self.platform_account
+        } else {
+            platform_account
+        }
+    }
+
+    /// Reject a new vault whose fees exceed the protocol caps
+    pub fn check_vault_caps(&self, platform_share_bps: u64, management_fee_bps: u64) -> VaultResult<()> {
+        if platform_share_bps > self.max_platform_share_bps || management_fee_bps > self.max_management_fee_bps {
+            return Err(VaultError::ProtocolFeeCapExceeded);
+        }
+        Ok(())
+    }
+
+    /// First step of an admin transfer; a new proposal replaces a pending one
+    pub fn propose_admin(&mut self, new_admin: Pubkey) -> VaultResult<()> {
+        if new_admin == Pubkey::default() || new_admin == self.admin {
+            return Err(VaultError::InvalidVaultConfig);
+        }
+        self.pending_admin = new_admin;
+        Ok(())
+    }
+
+    /// Hand the config to the pending admin. Returns the previous admin
+    pub fn accept_admin(&mut self) -> VaultResult<Pubkey> {
+        if !self.has_pending_admin() {
+            return Err(VaultError::NoPendingProtocolAdmin);
+        }
+
+        let previous_admin = self.admin;
+        self.admin = self.pending_admin;
+        self.pending_admin = Pubkey::default();
+        Ok(previous_admin)
+    }
+
+    pub fn has_pending_admin(&self) -> bool {
+        self.pending_admin != Pubkey::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn protocol_config() -> ProtocolConfig {
+        let mut config = ProtocolConfig::default();
+        config.initialize(Pubkey::new_unique(), ProtocolConfigParams::default(), 255).unwrap();
+        config
+    }
+
+    #[test]
+    fn test_defaults_fill_unset_vault_params() {
+        let platform = Pubkey::new_unique();
+        let mut config = protocol_config();
+        config
+            .update(ProtocolConfigParams {
+                platform_account: Some(platform),
+                default_unstake_lockup_period: Some(ONE_WEEK),
+                default_management_fee_bps: Some(100),
+                ..Default::default()
+            })
+            .unwrap();
+
+        let mut params = InitializeVaultParams { platform_share_bps: Some(2_000), ..Default::default() };
+        assert_eq!(config.fill_vault_defaults(Pubkey::default(), &mut params), platform);
+        assert_eq!(params.unstake_lockup_period, Some(ONE_WEEK));
+        assert_eq!(params.platform_share_bps, Some(2_000));
+        assert_eq!(params.management_fee_bps, Some(100));
+
+        // A vault's own platform account is kept
+        let own = Pubkey::new_unique();
+        assert_eq!(config.fill_vault_defaults(own, &mut params), own);
+    }
+
+    #[test]
+    fn test_caps_bound_defaults_and_vaults() {
+        let mut config = protocol_config();
+        let over_cap = ProtocolConfigParams {
+            max_platform_share_bps: Some(3_000),
+            ..Default::default()
+        };
+        // The default 50% share would exceed the new cap
+        assert!(matches!(config.update(over_cap.clone()), Err(VaultError::InvalidVaultConfig)));
+
+        let mut config = protocol_config();
+        config
+            .update(ProtocolConfigParams { default_platform_share_bps: Some(3_000), ..over_cap })
+            .unwrap();
+        config.check_vault_caps(3_000, 0).unwrap();
+        assert!(matches!(config.check_vault_caps(3_001, 0), Err(VaultError::ProtocolFeeCapExceeded)));
+        assert!(matches!(
+            config.check_vault_caps(0, MAX_MANAGEMENT_FEE_BPS + 1),
+            Err(VaultError::ProtocolFeeCapExceeded)
+        ));
+        assert!(matches!(
+            config.update(ProtocolConfigParams { max_management_fee_bps: Some(MAX_MANAGEMENT_FEE_BPS + 1), ..Default::default() }),
+            Err(VaultError::InvalidVaultConfig)
+        ));
+    }
+
+    #[test]
+    fn test_two_step_admin_transfer() {
+        let mut config = protocol_config();
+        let admin = config.admin;
+        let new_admin = Pubkey::new_unique();
+
+        assert!(matches!(config.accept_admin(), Err(VaultError::NoPendingProtocolAdmin)));
+        assert!(matches!(config.propose_admin(admin), Err(VaultError::InvalidVaultConfig)));
+
+        config.propose_admin(new_admin).unwrap();
+        assert_eq!(config.admin, admin);
+        assert_eq!(config.accept_admin().unwrap(), admin);
+        assert_eq!(config.admin, new_admin);
+        assert!(!config.has_pending_admin());
+    }
+}
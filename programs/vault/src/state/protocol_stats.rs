@@ -0,0 +1,167 @@
+use anchor_lang::prelude::*;
+use crate::error::*;
+use crate::math::SafeMath;
+
+/// Maximum number of distinct token mints tracked in protocol stats
+pub const MAX_STATS_MINTS: usize = 16;
+
+/// Aggregated totals for all rolled-up vaults of one token mint
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct MintStats {
+    /// The token mint
+    pub mint: Pubkey,
+    /// Number of vaults contributing to this entry
+    pub vault_count: u32,
+    /// Sum of total_assets of contributing vaults
+    pub total_assets: u64,
+    /// Sum of total_rewards of contributing vaults
+    pub total_rewards: u64,
+}
+
+impl MintStats {
+    pub const LEN: usize = 32 + // mint
+        4 + // vault_count
+        8 + // total_assets
+        8; // total_rewards
+}
+
+/// Protocol-wide statistics singleton
+#[account]
+#[derive(Default)]
+pub struct ProtocolStats {
+    /// Number of vaults created since stats were initialized
+    pub total_vaults: u64,
+    /// Number of used entries in `mints`
+    pub mint_count: u8,
+    /// Per-mint aggregates
+    pub mints: [MintStats; MAX_STATS_MINTS],
+    /// Last slot any roll-up touched the aggregates
+    pub last_updated_slot: u64,
+    /// Bump seed for PDA
+    pub bump: u8,
+    /// Reserved for future use
+    pub _reserved: [u8; 32],
+}
+
+impl ProtocolStats {
+    pub const LEN: usize = 8 + // discriminator
+        8 + // total_vaults
+        1 + // mint_count
+        MintStats::LEN * MAX_STATS_MINTS + // mints
+        8 + // last_updated_slot
+        1 + // bump
+        32; // _reserved
+
+    /// Replace a vault's previous contribution with its current totals.
+    /// `previous` is None the first time the vault is rolled up.
+    pub fn apply_contribution(
+        &mut self,
+        mint: Pubkey,
+        previous: Option<(u64, u64)>,
+        total_assets: u64,
+        total_rewards: u64,
+        slot: u64,
+    ) -> VaultResult<()> {
+        let index = match self.mints[..self.mint_count as usize]
+            .iter()
+            .position(|entry| entry.mint == mint)
+        {
+            Some(index) => index,
+            None => {
+                if self.mint_count as usize >= MAX_STATS_MINTS {
+                    return Err(VaultError::ProtocolStatsFull);
+                }
+                let index = self.mint_count as usize;
+                self.mints[index] = MintStats {
+                    mint,
+                    ..MintStats::default()
+                };
+                self.mint_count += 1;
+                index
+            }
+        };
+
+        let entry = &mut self.mints[index];
+        match previous {
+            Some((old_assets, old_rewards)) => {
+                entry.total_assets = entry.total_assets.safe_sub(old_assets)?;
+                entry.total_rewards = entry.total_rewards.safe_sub(old_rewards)?;
+            }
+            None => {
+                entry.vault_count = entry.vault_count.safe_add(1)?;
+            }
+        }
+        entry.total_assets = entry.total_assets.safe_add(total_assets)?;
+        entry.total_rewards = entry.total_rewards.safe_add(total_rewards)?;
+        self.last_updated_slot = slot;
+
+        Ok(())
+    }
+}
+
+/// Last totals a vault contributed to `ProtocolStats`, so roll-ups replace instead of add
+#[account]
+#[derive(Default)]
+pub struct VaultStatsContribution {
+    /// The vault this contribution belongs to
+    pub vault: Pubkey,
+    /// The vault's token mint at the time of the last roll-up
+    pub token_mint: Pubkey,
+    /// Contributed total_assets
+    pub total_assets: u64,
+    /// Contributed total_rewards
+    pub total_rewards: u64,
+    /// Slot of the last roll-up (0 = never rolled up)
+    pub last_rolled_slot: u64,
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl VaultStatsContribution {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // vault
+        32 + // token_mint
+        8 + // total_assets
+        8 + // total_rewards
+        8 + // last_rolled_slot
+        1; // bump
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roll_up_replaces_previous_contribution() {
+        let mut stats = ProtocolStats::default();
+        let mint = Pubkey::new_unique();
+
+        stats.apply_contribution(mint, None, 1_000, 10, 1).unwrap();
+        assert_eq!(stats.mint_count, 1);
+        assert_eq!(stats.mints[0].vault_count, 1);
+        assert_eq!(stats.mints[0].total_assets, 1_000);
+
+        // Rolling the same vault again replaces its totals instead of adding them
+        stats.apply_contribution(mint, Some((1_000, 10)), 1_500, 20, 2).unwrap();
+        assert_eq!(stats.mints[0].vault_count, 1);
+        assert_eq!(stats.mints[0].total_assets, 1_500);
+        assert_eq!(stats.mints[0].total_rewards, 20);
+
+        // A second vault of the same mint adds up
+        stats.apply_contribution(mint, None, 500, 5, 3).unwrap();
+        assert_eq!(stats.mints[0].vault_count, 2);
+        assert_eq!(stats.mints[0].total_assets, 2_000);
+    }
+
+    #[test]
+    fn test_roll_up_rejects_too_many_mints() {
+        let mut stats = ProtocolStats::default();
+        for _ in 0..MAX_STATS_MINTS {
+            stats.apply_contribution(Pubkey::new_unique(), None, 1, 0, 1).unwrap();
+        }
+        assert!(matches!(
+            stats.apply_contribution(Pubkey::new_unique(), None, 1, 0, 1),
+            Err(VaultError::ProtocolStatsFull)
+        ));
+    }
+}
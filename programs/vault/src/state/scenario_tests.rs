@@ -0,0 +1,1136 @@
+//! Multi-week scenarios driven through simulated time.
+//!
+//! Each scenario replays what the instructions do to the vault and depositor state,
+//! day by day on a warped clock, and checks the vault invariants and the token
+//! accounting at every checkpoint. New features add their scenario here.
+
+use anchor_lang::prelude::Pubkey;
+
+use super::test_clock::{self, START_TIME};
+use super::*;
+use crate::constants::*;
+use crate::error::*;
+use crate::events::RebaseAppliedEvent;
+
+const USDC: u64 = 1_000_000;
+
+#[derive(Clone)]
+struct Scenario {
+    vault: Vault,
+    depositors: Vec<VaultDepositor>,
+    /// Balance of the vault token account
+    token_balance: u64,
+    platform_balance: u64,
+    deposited: u64,
+    /// Vault share of all rewards, after the platform split
+    rewarded: u64,
+    paid: Vec<u64>,
+    /// Paid to the owner for fee shares
+    owner_paid: u64,
+    /// Share token balances, followed only once the vault is tokenized
+    share_tokens: Vec<u64>,
+}
+
+impl Scenario {
+    fn new(depositors: usize, lockup: i64) -> Self {
+        test_clock::set_time(START_TIME);
+
+        let mut vault = Vault::default();
+        let params = InitializeVaultParams {
+            unstake_lockup_period: Some(lockup),
+            platform_share_bps: None,
+            min_stake_amount: Some(USDC),
+            max_total_assets: None,
+            management_fee_bps: None,
+            protocol_governed: false,
+        };
+        let key = Pubkey::new_unique();
+        vault.initialize([0; 32], key, key, key, key, key, params, 255).unwrap();
+
+        let depositors = (0..depositors)
+            .map(|_| {
+                let mut depositor = VaultDepositor::default();
+                depositor.initialize(key, Pubkey::new_unique()).unwrap();
+                depositor
+            })
+            .collect::<Vec<_>>();
+
+        Scenario {
+            vault,
+            paid: vec![0; depositors.len()],
+            share_tokens: vec![0; depositors.len()],
+            depositors,
+            token_balance: 0,
+            platform_balance: 0,
+            deposited: 0,
+            rewarded: 0,
+            owner_paid: 0,
+        }
+    }
+
+    /// As `settle_share_tokens`: mint or burn the change of an active share balance
+    fn settle_share_tokens(&mut self, i: usize, shares_before: u64) -> VaultResult<()> {
+        if !self.vault.is_tokenized() {
+            return Ok(());
+        }
+        let shares_after = self.depositors[i].shares;
+        self.share_tokens[i] = (self.share_tokens[i] + shares_after)
+            .checked_sub(shares_before)
+            .ok_or(VaultError::InsufficientFunds)?;
+        Ok(())
+    }
+
+    /// The vested rewards release every pricing instruction starts with
+    fn accrue(&mut self) -> VaultResult<()> {
+        self.vault.accrue_rewards(test_clock::now())?;
+        Ok(())
+    }
+
+    /// The inline rebase sync every depositor instruction starts with
+    fn sync(&mut self, i: usize) -> VaultResult<()> {
+        self.vault.sync_depositor(&mut self.depositors[i])?;
+        Ok(())
+    }
+
+    /// As the `stake` instruction: minimum stake against the synced position, then mint.
+    /// A mint that rebases the vault syncs the position again before the shares are added
+    fn stake(&mut self, i: usize, amount: u64) -> VaultResult<u64> {
+        self.accrue()?;
+        self.sync(i)?;
+        let depositor_value = self.vault.depositor_stake_value(&self.depositors[i])?;
+        let shares_base_before = self.vault.shares_base;
+        let shares = self.vault.stake(amount, depositor_value)?;
+        if self.vault.shares_base != shares_base_before {
+            self.sync(i)?;
+        }
+        let shares_before = self.depositors[i].shares;
+        self.depositors[i].stake(shares, 0)?;
+        self.settle_share_tokens(i, shares_before)?;
+        self.token_balance += amount;
+        self.deposited += amount;
+        self.vault.verify_solvency(self.token_balance)?;
+        Ok(shares)
+    }
+
+    /// As `stake_with_term`: the minted shares are frozen in the same step, no cooldown
+    fn stake_with_term(&mut self, i: usize, amount: u64) -> VaultResult<u64> {
+        if self.depositors[i].unstake_request.is_pending() {
+            return Err(VaultError::UnstakeRequestAlreadyExists);
+        }
+        let shares = self.stake(i, amount)?;
+        let shares_before = self.depositors[i].shares;
+        let frozen_amount = self.depositors[i].request_term_unstake(&mut self.vault, shares, test_clock::now())?;
+        self.settle_share_tokens(i, shares_before)?;
+        Ok(frozen_amount)
+    }
+
+    /// As `add_rewards`: the platform share never reaches the vault, unless fee accrual is paused.
+    /// The performance fee on the gain is minted to the owner afterwards
+    fn add_rewards(&mut self, amount: u64) -> VaultResult<()> {
+        let platform_share = amount * self.vault.accrued_platform_share_bps() / BASIS_POINTS_PRECISION;
+        self.vault.add_rewards(amount - platform_share)?;
+        self.vault.crystallize_performance_fee()?;
+        self.platform_balance += platform_share;
+        self.token_balance += amount - platform_share;
+        self.rewarded += amount - platform_share;
+        Ok(())
+    }
+
+    fn request(&mut self, i: usize, amount: u64) -> VaultResult<u64> {
+        self.request_position(i, Some(amount))
+    }
+
+    /// As `request_unstake_all`
+    fn request_all(&mut self, i: usize) -> VaultResult<u64> {
+        self.request_position(i, None)
+    }
+
+    fn request_position(&mut self, i: usize, amount: Option<u64>) -> VaultResult<u64> {
+        self.accrue()?;
+        self.sync(i)?;
+        let shares_before = self.depositors[i].shares;
+        let now = test_clock::now();
+        let (_, frozen_amount) = match amount {
+            Some(amount) => self.depositors[i].request_unstake(&mut self.vault, amount, now)?,
+            None => self.depositors[i].request_unstake_all(&mut self.vault, now)?,
+        };
+        self.vault.verify_solvency(self.token_balance)?;
+        self.settle_share_tokens(i, shares_before)?;
+        Ok(frozen_amount)
+    }
+
+    /// As `unstake`: pays the frozen amount less the withdrawal fee out of the token account
+    fn execute(&mut self, i: usize) -> VaultResult<u64> {
+        self.accrue()?;
+        self.sync(i)?;
+        let frozen_amount = self.depositors[i].unstake_request.frozen_amount()?;
+        let amount = frozen_amount - self.vault.withdrawal_fee(frozen_amount)?;
+        if self.token_balance < amount {
+            return Err(VaultError::InsufficientLiquidity);
+        }
+        let (_, amount) = self.depositors[i].execute_unstake(&mut self.vault, test_clock::now())?;
+        self.token_balance -= amount;
+        self.paid[i] += amount;
+        Ok(amount)
+    }
+
+    /// As `cancel_unstake_request`: returns the shares restored for the frozen amount
+    fn cancel(&mut self, i: usize) -> VaultResult<u64> {
+        self.accrue()?;
+        self.sync(i)?;
+        let shares_before = self.depositors[i].shares;
+        let (_, restored_shares) = self.depositors[i].cancel_unstake(&mut self.vault)?;
+        self.vault.verify_solvency(self.token_balance)?;
+        self.settle_share_tokens(i, shares_before)?;
+        Ok(restored_shares)
+    }
+
+    /// As `expire_unstake_request`: returns the shares restored for the frozen amount
+    fn expire(&mut self, i: usize) -> VaultResult<u64> {
+        self.accrue()?;
+        self.sync(i)?;
+        let shares_before = self.depositors[i].shares;
+        let (_, restored_shares) = self.depositors[i].expire_unstake(&mut self.vault, test_clock::now())?;
+        self.settle_share_tokens(i, shares_before)?;
+        Ok(restored_shares)
+    }
+
+    /// As `force_distribute`: pays a depositor's whole position once the timeout has passed
+    fn force_distribute(&mut self, i: usize) -> VaultResult<u64> {
+        if !self.vault.can_force_distribute(test_clock::now()) {
+            return Err(VaultError::DecommissionTimeoutNotReached);
+        }
+        self.accrue()?;
+        self.sync(i)?;
+        let depositor = &self.depositors[i];
+        let (active_shares, request) = (depositor.shares, depositor.unstake_request.clone());
+        let amount = self.vault.force_exit(active_shares, request.shares, request.frozen_amount()?)?;
+        self.token_balance -= amount;
+        self.paid[i] += amount;
+        self.depositors[i].shares = 0;
+        self.depositors[i].unstake_request.reset();
+        Ok(amount)
+    }
+
+    /// Keeper behaviour: every depositor follows the vault's latest rebase
+    fn sync_all(&mut self) {
+        for depositor in self.depositors.iter_mut() {
+            depositor.sync_rebase(&mut self.vault).unwrap();
+        }
+    }
+
+    /// Active shares at today's price plus the frozen request
+    fn position_value(&self, i: usize) -> u64 {
+        let depositor = &self.depositors[i];
+        self.vault.shares_value(depositor.shares).unwrap()
+            + depositor.unstake_request.frozen_amount().unwrap()
+    }
+
+    fn checkpoint(&self, label: &str) {
+        self.vault
+            .verify_invariants()
+            .unwrap_or_else(|error| panic!("{}: {:?}", label, error));
+        assert_eq!(
+            self.vault.total_assets + self.vault.unvested_rewards,
+            self.token_balance,
+            "{}: total_assets",
+            label
+        );
+        assert_eq!(
+            self.deposited + self.rewarded,
+            self.token_balance + self.paid.iter().sum::<u64>() + self.owner_paid,
+            "{}: value created or lost",
+            label
+        );
+        let frozen: u64 = self
+            .depositors
+            .iter()
+            .map(|d| d.unstake_request.frozen_amount().unwrap())
+            .sum();
+        assert_eq!(self.vault.reserved_assets, frozen, "{}: reserved_assets", label);
+        if self.vault.is_tokenized() {
+            self.vault
+                .verify_share_supply(self.share_tokens.iter().sum())
+                .unwrap_or_else(|error| panic!("{}: share supply {:?}", label, error));
+        }
+    }
+
+    /// As `withdraw_owner_shares` of every fee share, claiming the performance fees first
+    fn withdraw_owner_shares(&mut self, owner_fee_depositor: &mut VaultDepositor) -> VaultResult<u64> {
+        self.accrue()?;
+        self.vault.sync_depositor(owner_fee_depositor)?;
+        self.vault.claim_owner_shares(owner_fee_depositor)?;
+        let (shares, amount) = self.vault.owner_redemption_all_quote(owner_fee_depositor.shares)?;
+        owner_fee_depositor.shares -= shares;
+        self.vault.redeem_owner_shares(shares, amount)?;
+        self.token_balance -= amount;
+        self.owner_paid += amount;
+        Ok(amount)
+    }
+
+    /// Everyone exits at the end of the scenario; only the dead shares and their dust stay behind
+    fn drain(&mut self) {
+        for i in 0..self.depositors.len() {
+            if self.depositors[i].unstake_request.is_pending() {
+                test_clock::warp_seconds(self.vault.unstake_lockup_period);
+                self.execute(i).unwrap();
+            }
+            if self.depositors[i].shares > 0 {
+                self.request_all(i).unwrap();
+                test_clock::warp_seconds(self.vault.unstake_lockup_period);
+                self.execute(i).unwrap();
+            }
+            self.checkpoint("drain");
+        }
+        assert_eq!(self.vault.total_shares, self.vault.dead_shares);
+        assert!(self.vault.total_assets <= 2 * self.vault.dead_shares + 3, "dust left: {}", self.vault.total_assets);
+    }
+}
+
+#[test]
+fn scenario_four_weeks_of_daily_rewards_and_staggered_exits() {
+    let mut scenario = Scenario::new(3, FOURTEEN_DAYS);
+    scenario.stake(0, 1_000 * USDC).unwrap();
+    scenario.checkpoint("day 0");
+
+    let mut request_a_at = 0;
+    let mut frozen_a = 0;
+    let mut frozen_b = 0;
+    for day in 1..=28 {
+        test_clock::warp_days(1);
+        match day {
+            2 => {
+                scenario.stake(1, 3_000 * USDC).unwrap();
+            }
+            5 => {
+                scenario.stake(2, 500 * USDC).unwrap();
+            }
+            7 => {
+                request_a_at = test_clock::now();
+                frozen_a = scenario.request(0, 400 * USDC).unwrap();
+                assert_eq!(frozen_a, 400 * USDC);
+            }
+            10 => {
+                frozen_b = scenario.request_all(1).unwrap();
+            }
+            20 => {
+                assert!(matches!(scenario.execute(0), Err(VaultError::UnstakeLockupNotFinished)));
+            }
+            21 => {
+                // Matures at exactly request time + lockup, not a second earlier
+                test_clock::set_time(request_a_at + FOURTEEN_DAYS - 1);
+                assert!(matches!(scenario.execute(0), Err(VaultError::UnstakeLockupNotFinished)));
+                test_clock::set_time(request_a_at + FOURTEEN_DAYS);
+                assert_eq!(scenario.execute(0).unwrap(), frozen_a);
+            }
+            24 => {
+                // Two weeks of rewards after the request did not change B's payout
+                assert_eq!(scenario.execute(1).unwrap(), frozen_b);
+            }
+            _ => {}
+        }
+        scenario.add_rewards(20 * USDC).unwrap();
+        scenario.checkpoint(&format!("day {}", day));
+    }
+
+    // Half of 28 daily rewards reached the vault; C was in for 23 of them
+    assert_eq!(scenario.platform_balance, 28 * 10 * USDC);
+    assert!(scenario.position_value(2) > 500 * USDC);
+    assert!(scenario.position_value(0) > 600 * USDC);
+
+    scenario.drain();
+    assert!(scenario.paid[2] > 500 * USDC);
+}
+
+#[test]
+fn scenario_rebase_and_lockup_change_during_pending_requests() {
+    let mut scenario = Scenario::new(3, FOURTEEN_DAYS);
+    // Shares diluted 1000:1, as left behind by a vault that never rebased
+    for (i, amount) in [(0, 1_000 * USDC), (1, 2_000 * USDC)] {
+        scenario.vault.total_shares += amount * 1_000;
+        scenario.vault.total_assets += amount;
+        scenario.depositors[i].shares += amount * 1_000;
+        scenario.token_balance += amount;
+        scenario.deposited += amount;
+    }
+    scenario.checkpoint("diluted");
+
+    test_clock::warp_days(1);
+    let frozen_a = scenario.request(0, 250 * USDC).unwrap();
+    scenario.checkpoint("day 1");
+
+    // The apply_rebase crank shrinks the share counts; the keeper syncs everyone afterwards
+    test_clock::warp_days(1);
+    scenario.vault.apply_rebase().unwrap();
+    assert!(scenario.vault.shares_base > 0);
+    scenario.sync_all();
+    scenario.stake(2, 100 * USDC).unwrap();
+    assert_eq!(scenario.depositors[0].unstake_request.frozen_amount().unwrap(), frozen_a);
+    scenario.add_rewards(30 * USDC).unwrap();
+    scenario.checkpoint("day 2");
+
+    // The owner shortens the lockup to three days and raises the minimum stake
+    test_clock::warp_days(1);
+    let params = UpdateVaultConfigParams {
+        unstake_lockup_period: Some(3 * ONE_DAY),
+        platform_share_bps: None,
+        min_stake_amount: Some(500 * USDC),
+        max_total_assets: None,
+        is_paused: None,
+        platform_account: None,
+        instant_unstake_fee_bps: None,
+        stake_cooldown_seconds: None,
+        deposits_paused: None,
+        withdrawals_paused: None,
+        permissioned: None,
+        max_stake_per_depositor: None,
+        paused_operations: None,
+        reward_vesting_period: None,
+        performance_fee_bps: None,
+        withdrawal_fee_bps: None,
+        unstake_request_expiry: None,
+        reward_authority: None,
+        permissionless_rewards: None,
+        pause_on_shortfall: None,
+        config_timelock_seconds: None,
+        min_unstake_amount: None,
+        checkpoint_interval: None,
+        management_fee_bps: None,
+        deposit_fee_bps: None,
+        deposit_fee_to_owner: None,
+    };
+    scenario.vault.update_config(params).unwrap();
+    assert!(matches!(scenario.execute(0), Err(VaultError::UnstakeLockupNotFinished)));
+    // B's position is far above the new minimum, C's small top-up is not
+    scenario.stake(1, 10 * USDC).unwrap();
+    assert!(matches!(scenario.stake(2, 10 * USDC), Err(VaultError::MinimumStakeAmountNotMet)));
+    scenario.checkpoint("day 3");
+
+    // The lockup in force at execution applies to requests already pending
+    test_clock::warp_days(1);
+    assert_eq!(scenario.execute(0).unwrap(), frozen_a);
+    scenario.checkpoint("day 4");
+
+    scenario.drain();
+}
+
+#[test]
+fn scenario_new_stake_after_every_share_went_pending() {
+    let mut scenario = Scenario::new(3, FOURTEEN_DAYS);
+    // Bootstrapped 1:1 before dead shares, which otherwise always stay active
+    for i in 0..2 {
+        scenario.vault.total_shares += 1_000 * USDC;
+        scenario.vault.total_assets += 1_000 * USDC;
+        scenario.depositors[i].shares += 1_000 * USDC;
+        scenario.token_balance += 1_000 * USDC;
+        scenario.deposited += 1_000 * USDC;
+    }
+
+    test_clock::warp_days(1);
+    scenario.add_rewards(200 * USDC).unwrap();
+    let frozen_a = scenario.request_all(0).unwrap();
+    let frozen_b = scenario.request_all(1).unwrap();
+    assert_eq!(scenario.vault.get_active_shares().unwrap(), 0);
+    scenario.checkpoint("all pending");
+
+    // Within the 7-day inactivity window a new stake is priced at the pending share value
+    let mut early = Scenario {
+        vault: scenario.vault.clone(),
+        depositors: scenario.depositors.clone(),
+        paid: scenario.paid.clone(),
+        ..Scenario::new(0, FOURTEEN_DAYS)
+    };
+    test_clock::set_time(START_TIME + 5 * ONE_DAY);
+    // 2_100 USDC of assets behind 2_000 pending shares after the vault's half of the reward
+    let early_shares = early.vault.stake(105 * USDC, 0).unwrap();
+    assert_eq!(early_shares, 100 * USDC);
+
+    // After 7 days without activity the vault restarts at 1:1
+    test_clock::set_time(START_TIME + 9 * ONE_DAY);
+    assert_eq!(scenario.stake(2, 110 * USDC).unwrap(), 110 * USDC);
+    scenario.checkpoint("restart");
+
+    // The pending requests pay exactly what they froze
+    test_clock::set_time(START_TIME + ONE_DAY + FOURTEEN_DAYS);
+    assert_eq!(scenario.execute(0).unwrap(), frozen_a);
+    assert_eq!(scenario.execute(1).unwrap(), frozen_b);
+    scenario.checkpoint("requests paid");
+
+    scenario.drain();
+    assert!(scenario.paid[2] >= 110 * USDC);
+}
+
+#[test]
+fn scenario_recent_requests_keep_an_all_pending_vault_out_of_restart() {
+    let mut scenario = Scenario::new(3, FOURTEEN_DAYS);
+    // Bootstrapped 1:1 before dead shares, as above
+    for i in 0..2 {
+        scenario.vault.total_shares += 1_000 * USDC;
+        scenario.vault.total_assets += 1_000 * USDC;
+        scenario.depositors[i].shares += 1_000 * USDC;
+        scenario.token_balance += 1_000 * USDC;
+        scenario.deposited += 1_000 * USDC;
+    }
+
+    test_clock::warp_days(1);
+    scenario.add_rewards(200 * USDC).unwrap();
+    test_clock::warp_days(1);
+    scenario.request_all(0).unwrap();
+    test_clock::warp_days(6);
+    scenario.request_all(1).unwrap();
+    assert_eq!(scenario.vault.get_active_shares().unwrap(), 0);
+
+    // A week past the last reward but a day after the last request, the stake is still
+    // priced at the pending share value rather than 1:1
+    test_clock::warp_days(1);
+    assert_eq!(scenario.vault.last_activity, START_TIME + 8 * ONE_DAY);
+    assert_eq!(scenario.stake(2, 105 * USDC).unwrap(), 100 * USDC);
+    scenario.checkpoint("priced");
+
+    scenario.drain();
+    assert!(scenario.paid[2] >= 105 * USDC - 1);
+}
+
+#[test]
+fn scenario_ownership_handoff_with_pending_requests_and_unwithdrawn_fees() {
+    // 0 = owner fee depositor, 1 = previous owner's personal position, 2 and 3 = stakers
+    let mut scenario = Scenario::new(4, FOURTEEN_DAYS);
+    let previous_owner = scenario.vault.owner;
+    let new_owner = Pubkey::new_unique();
+    scenario.depositors[0].authority = previous_owner;
+    scenario.depositors[1].authority = previous_owner;
+
+    scenario.stake(2, 1_000 * USDC).unwrap();
+    scenario.stake(3, 1_000 * USDC).unwrap();
+    // Fee shares migrated from the legacy owner_shares counter
+    scenario.stake(0, 100 * USDC).unwrap();
+    scenario.checkpoint("day 0");
+
+    test_clock::warp_days(1);
+    scenario.add_rewards(42 * USDC).unwrap();
+    let frozen = scenario.request(2, 500 * USDC).unwrap();
+    scenario.checkpoint("day 1");
+
+    // The transfer lands while a request is pending and the fees are still in the vault
+    test_clock::warp_days(1);
+    scenario.vault.propose_owner(new_owner).unwrap();
+    let fee_shares = scenario.depositors[0].shares;
+    let fee_value = scenario.position_value(0);
+    let (fee_depositor, rest) = scenario.depositors.split_at_mut(1);
+    let handoff = scenario
+        .vault
+        .hand_off_ownership(&mut fee_depositor[0], &mut rest[0])
+        .unwrap();
+    assert_eq!(handoff.previous_owner, previous_owner);
+    assert_eq!(handoff.settled_fee_shares, fee_shares);
+    assert!(handoff.platform_account_reset);
+    assert_eq!(scenario.vault.platform_account, new_owner);
+    scenario.checkpoint("handoff");
+
+    // The previous owner's fees now sit in their own position at the same value;
+    // the new owner holds the fee depositor and nothing in it
+    assert_eq!(scenario.depositors[0].authority, new_owner);
+    assert_eq!(scenario.depositors[0].shares, 0);
+    assert_eq!(scenario.depositors[1].authority, previous_owner);
+    assert_eq!(scenario.position_value(1), fee_value);
+    assert!(matches!(
+        scenario.vault.owner_redemption_all_quote(scenario.depositors[0].shares),
+        Err(VaultError::InvalidAmount)
+    ));
+
+    // A later handoff settles only positions held by the owner of the day
+    scenario.vault.propose_owner(Pubkey::new_unique()).unwrap();
+    let (fee_depositor, rest) = scenario.depositors.split_at_mut(1);
+    assert!(matches!(
+        scenario.vault.hand_off_ownership(&mut fee_depositor[0], &mut rest[0]),
+        Err(VaultError::Unauthorized)
+    ));
+    scenario.vault.cancel_owner_transfer().unwrap();
+
+    // Fees earned under the new owner stay apart from the previous owner's
+    test_clock::warp_days(1);
+    scenario.add_rewards(42 * USDC).unwrap();
+    scenario.stake(0, 10 * USDC).unwrap();
+    assert!(scenario.position_value(1) > fee_value);
+
+    // The pending request pays what it froze before the transfer
+    test_clock::set_time(START_TIME + ONE_DAY + FOURTEEN_DAYS);
+    assert_eq!(scenario.execute(2).unwrap(), frozen);
+    scenario.checkpoint("request paid");
+
+    scenario.drain();
+    assert!(scenario.paid[1] >= fee_value);
+}
+
+/// Shares diluted 1000:1 for depositor `i`, as left behind by a vault that never rebased
+fn dilute(scenario: &mut Scenario, i: usize, amount: u64) {
+    scenario.vault.total_shares += amount * 1_000;
+    scenario.vault.total_assets += amount;
+    scenario.depositors[i].shares += amount * 1_000;
+    scenario.token_balance += amount;
+    scenario.deposited += amount;
+}
+
+#[test]
+fn scenario_vault_rebases_between_stake_and_unstake() {
+    let mut scenario = Scenario::new(3, FOURTEEN_DAYS);
+    dilute(&mut scenario, 0, 1_000 * USDC);
+    dilute(&mut scenario, 1, 2_000 * USDC);
+
+    test_clock::warp_days(1);
+    let request_a_at = test_clock::now();
+    let frozen_a = scenario.request(0, 250 * USDC).unwrap();
+    scenario.checkpoint("day 1");
+
+    // The rebase is cranked and nobody syncs afterwards
+    test_clock::warp_days(1);
+    scenario.vault.apply_rebase().unwrap().unwrap();
+    assert!(scenario.depositors[1].needs_rebase_sync(scenario.vault.rebase_version));
+
+    // B's request is priced in the new share base without a separate sync_rebase
+    assert_eq!(scenario.request(1, 500 * USDC).unwrap(), 500 * USDC);
+    assert!(!scenario.depositors[1].needs_rebase_sync(scenario.vault.rebase_version));
+    assert_eq!(scenario.position_value(1), 2_000 * USDC);
+    assert_eq!(scenario.stake(2, 100 * USDC).unwrap(), 100 * USDC);
+    scenario.checkpoint("day 2");
+
+    // A's request from before the rebase still pays what it froze, and the rest of
+    // A's position keeps its value
+    test_clock::set_time(request_a_at + FOURTEEN_DAYS);
+    assert!(scenario.depositors[0].needs_rebase_sync(scenario.vault.rebase_version));
+    assert_eq!(scenario.execute(0).unwrap(), frozen_a);
+    assert_eq!(scenario.position_value(0), 750 * USDC);
+    scenario.checkpoint("request paid");
+
+    scenario.drain();
+}
+
+#[test]
+fn scenario_stake_that_rebases_the_vault_syncs_the_staker() {
+    let mut scenario = Scenario::new(2, FOURTEEN_DAYS);
+    dilute(&mut scenario, 0, 1_000 * USDC);
+    dilute(&mut scenario, 1, 2_000 * USDC);
+
+    test_clock::warp_days(1);
+    let frozen_b = scenario.request(1, 500 * USDC).unwrap();
+    scenario.checkpoint("day 1");
+
+    // A's stake rebases the vault before minting; A's old shares follow before the
+    // new ones are added
+    test_clock::warp_days(1);
+    let (shares_base, total_shares) = (scenario.vault.shares_base, scenario.vault.total_shares);
+    assert_eq!(scenario.stake(0, 10 * USDC).unwrap(), 10 * USDC);
+    assert!(scenario.vault.shares_base > shares_base);
+    assert_eq!(scenario.position_value(0), 1_010 * USDC);
+    scenario.checkpoint("day 2");
+
+    // The stake instruction emits the rebase with the total from before its own mint
+    let vault_key = Pubkey::new_unique();
+    let rebase = RebaseAppliedEvent::since(vault_key, &scenario.vault, shares_base, total_shares, test_clock::now())
+        .expect("the stake rebased");
+    assert_eq!((rebase.vault, rebase.expo_diff, rebase.rebase_divisor), (vault_key, 3, 1_000));
+    assert_eq!((rebase.shares_base, rebase.rebase_version), (scenario.vault.shares_base, scenario.vault.rebase_version));
+    assert_eq!(rebase.total_shares, scenario.vault.total_shares - 10 * USDC);
+
+    // A stake that does not rebase emits nothing
+    let (shares_base, total_shares) = (scenario.vault.shares_base, scenario.vault.total_shares);
+    scenario.stake(0, 10 * USDC).unwrap();
+    assert!(RebaseAppliedEvent::since(vault_key, &scenario.vault, shares_base, total_shares, test_clock::now()).is_none());
+    scenario.checkpoint("second stake");
+
+    // B cancels a request frozen in the old base and gets back shares worth it
+    assert!(scenario.depositors[1].needs_rebase_sync(scenario.vault.rebase_version));
+    scenario.cancel(1).unwrap();
+    assert_eq!(scenario.depositors[1].unstake_request.shares, 0);
+    assert_eq!(scenario.position_value(1), 2_000 * USDC);
+    assert_eq!(frozen_b, 500 * USDC);
+    scenario.checkpoint("cancelled");
+
+    test_clock::warp_days(1);
+    scenario.drain();
+}
+
+#[test]
+fn scenario_rewards_push_a_capped_depositor_over_the_cap() {
+    let mut scenario = Scenario::new(2, FOURTEEN_DAYS);
+    scenario.vault.max_stake_per_depositor = 1_000 * USDC;
+
+    scenario.stake(0, 600 * USDC).unwrap();
+    scenario.stake(1, 1_000 * USDC).unwrap();
+    assert!(matches!(scenario.stake(0, 401 * USDC), Err(VaultError::DepositorCapExceeded)));
+    scenario.stake(0, 400 * USDC).unwrap();
+    scenario.checkpoint("day 0");
+
+    // Rewards grow both positions past the cap: stakes stop, exits do not
+    for _ in 0..7 {
+        test_clock::warp_days(1);
+        scenario.add_rewards(50 * USDC).unwrap();
+    }
+    assert!(scenario.position_value(0) > 1_000 * USDC);
+    assert!(matches!(scenario.stake(0, USDC), Err(VaultError::DepositorCapExceeded)));
+    scenario.checkpoint("day 7");
+
+    let frozen = scenario.request(0, 500 * USDC).unwrap();
+    test_clock::warp_days(14);
+    assert_eq!(scenario.execute(0).unwrap(), frozen);
+    scenario.checkpoint("partial exit");
+
+    // Back under the cap, A can top up to it again
+    let room = scenario.vault.depositor_remaining_capacity(scenario.position_value(0));
+    assert!(room > 0 && room < 500 * USDC);
+    scenario.stake(0, room).unwrap();
+    scenario.checkpoint("topped up");
+
+    test_clock::warp_days(1);
+    scenario.drain();
+}
+
+#[test]
+fn scenario_term_stake_matures_after_one_lockup_without_earning() {
+    let mut scenario = Scenario::new(2, FOURTEEN_DAYS);
+    scenario.stake(0, 1_000 * USDC).unwrap();
+    test_clock::warp_days(1);
+    scenario.add_rewards(100 * USDC).unwrap();
+
+    // A plain stake cannot be requested in the same block, a term stake is frozen at
+    // the share value it was minted at
+    scenario.stake(0, 100 * USDC).unwrap();
+    assert!(matches!(scenario.request(0, 100 * USDC), Err(VaultError::StakeCooldownNotMet)));
+    let frozen = scenario.stake_with_term(1, 500 * USDC).unwrap();
+    assert!(frozen <= 500 * USDC && frozen + 1 >= 500 * USDC);
+    assert_eq!(scenario.depositors[1].shares, 0);
+    scenario.checkpoint("term stake");
+
+    // A second term stake would restart the lockup of the first one
+    assert!(matches!(scenario.stake_with_term(1, 500 * USDC), Err(VaultError::UnstakeRequestAlreadyExists)));
+
+    // Rewards during the term go to the active stakers only, A and the dead shares
+    let a_before = scenario.position_value(0);
+    let dead_before = scenario.vault.shares_value(scenario.vault.dead_shares).unwrap();
+    let rewarded_before = scenario.rewarded;
+    for _ in 0..7 {
+        test_clock::warp_days(1);
+        scenario.add_rewards(10 * USDC).unwrap();
+    }
+    let a_gain = scenario.position_value(0) - a_before;
+    let dead_gain = scenario.vault.shares_value(scenario.vault.dead_shares).unwrap() - dead_before;
+    assert!(a_gain + dead_gain + 2 >= scenario.rewarded - rewarded_before);
+    assert_eq!(scenario.position_value(1), frozen);
+    scenario.checkpoint("day 8");
+
+    assert!(matches!(scenario.execute(1), Err(VaultError::UnstakeLockupNotFinished)));
+    test_clock::warp_days(7);
+    assert_eq!(scenario.execute(1).unwrap(), frozen);
+    scenario.checkpoint("matured");
+
+    // Once paid out, the depositor can take another term
+    scenario.stake_with_term(1, 200 * USDC).unwrap();
+    scenario.checkpoint("second term");
+
+    scenario.drain();
+}
+
+#[test]
+fn scenario_share_tokens_follow_active_shares_through_every_exit() {
+    let mut scenario = Scenario::new(3, FOURTEEN_DAYS);
+    scenario.vault.share_mint = Pubkey::new_unique();
+
+    scenario.stake(0, 1_000 * USDC).unwrap();
+    scenario.stake(1, 500 * USDC).unwrap();
+    scenario.checkpoint("day 0");
+
+    for _ in 0..10 {
+        test_clock::warp_days(1);
+        scenario.add_rewards(20 * USDC).unwrap();
+    }
+    // Rewards raise the share value, not the token balances
+    assert_eq!(scenario.share_tokens[0], 1_000 * USDC - DEAD_SHARES);
+    scenario.stake(2, 300 * USDC).unwrap();
+    assert!(scenario.share_tokens[2] < 300 * USDC);
+    scenario.checkpoint("day 10");
+
+    // Requests burn, cancels mint the restored shares back
+    scenario.request(0, 400 * USDC).unwrap();
+    assert_eq!(scenario.share_tokens[0], scenario.depositors[0].shares);
+    scenario.cancel(0).unwrap();
+    scenario.request(1, 100 * USDC).unwrap();
+    scenario.cancel(1).unwrap();
+    scenario.request(1, 300 * USDC).unwrap();
+    scenario.checkpoint("requests");
+
+    test_clock::warp_days(14);
+    scenario.execute(1).unwrap();
+    scenario.checkpoint("executed");
+
+    scenario.drain();
+    assert!(scenario.share_tokens.iter().all(|&tokens| tokens == 0));
+}
+
+#[test]
+fn scenario_vested_rewards_do_not_pay_a_stake_made_just_before_them() {
+    let mut scenario = Scenario::new(2, FOURTEEN_DAYS);
+    scenario.vault.reward_vesting_period = 7 * ONE_DAY;
+    scenario.stake(0, 1_000 * USDC).unwrap();
+    let a_staked = scenario.position_value(0);
+    test_clock::warp_days(1);
+
+    // B stakes right before a large reward and leaves as soon as the cooldown allows
+    scenario.stake(1, 1_000 * USDC).unwrap();
+    scenario.add_rewards(140 * USDC).unwrap();
+    assert_eq!(scenario.vault.unvested_rewards, 70 * USDC);
+    assert_eq!(scenario.position_value(1), 1_000 * USDC);
+    scenario.checkpoint("reward vesting");
+
+    test_clock::warp_seconds(DEFAULT_STAKE_COOLDOWN_SECONDS);
+    let frozen_b = scenario.request_all(1).unwrap();
+    // One cooldown of a week-long release, split with A
+    let sniped = frozen_b - 1_000 * USDC;
+    assert!(sniped <= 70 * USDC * DEFAULT_STAKE_COOLDOWN_SECONDS as u64 / (7 * ONE_DAY as u64) / 2 + 1);
+    scenario.checkpoint("sniper left");
+
+    // The rest reaches A linearly over the week
+    test_clock::set_time(scenario.vault.vesting_end - 7 * ONE_DAY / 2);
+    scenario.accrue().unwrap();
+    let halfway = scenario.position_value(0);
+    assert!(halfway > 1_034 * USDC && halfway < 1_036 * USDC);
+    scenario.checkpoint("halfway");
+
+    test_clock::set_time(scenario.vault.vesting_end);
+    scenario.accrue().unwrap();
+    assert_eq!(scenario.vault.unvested_rewards, 0);
+    let a_gain = scenario.position_value(0) - a_staked;
+    let dead_gain = scenario.vault.shares_value(scenario.vault.dead_shares).unwrap() - DEAD_SHARES;
+    assert!(a_gain + dead_gain + sniped + 2 >= 70 * USDC && a_gain + dead_gain + sniped <= 70 * USDC);
+    scenario.checkpoint("vested");
+
+    scenario.drain();
+}
+
+#[test]
+fn scenario_performance_fee_charges_active_gains_above_the_mark_only() {
+    let mut scenario = Scenario::new(2, FOURTEEN_DAYS);
+    scenario.vault.performance_fee_bps = 2_000;
+    let mut owner_fee_depositor = VaultDepositor::default();
+    owner_fee_depositor.initialize(scenario.vault.pubkey, scenario.vault.owner).unwrap();
+
+    scenario.stake(0, 1_000 * USDC).unwrap();
+    scenario.stake(1, 1_000 * USDC).unwrap();
+    test_clock::warp_days(1);
+    // 200 reach the vault: 10% on both positions, 20% of it goes to the owner
+    scenario.add_rewards(400 * USDC).unwrap();
+    let owner_value = scenario.vault.shares_value(scenario.vault.owner_shares).unwrap();
+    assert!(owner_value + 2 >= 40 * USDC && owner_value <= 40 * USDC);
+    // A's first stake paid for the dead shares
+    assert!(scenario.position_value(0) + 1 >= (1_000 * USDC - DEAD_SHARES) * 108 / 100);
+    let mark = scenario.vault.high_water_mark;
+    scenario.checkpoint("first gain");
+
+    // B's frozen request is paid in full, later gains are charged on A's and the owner's shares
+    let frozen_b = scenario.request_all(1).unwrap();
+    test_clock::warp_days(1);
+    let owner_shares_before = scenario.vault.owner_shares;
+    scenario.add_rewards(200 * USDC).unwrap();
+    let charged_shares = scenario.vault.owner_shares - owner_shares_before;
+    // 20% of 100, at the new share value
+    let charged = scenario.vault.shares_value(charged_shares).unwrap();
+    assert!(charged + 2 >= 20 * USDC && charged <= 20 * USDC);
+    assert!(scenario.vault.high_water_mark > mark);
+    assert_eq!(scenario.position_value(1), frozen_b);
+    scenario.checkpoint("second gain");
+
+    // A reward too small to lift the value past the mark after a stake changes nothing
+    let mark = scenario.vault.high_water_mark;
+    let owner_shares_before = scenario.vault.owner_shares;
+    scenario.stake(1, 500 * USDC).unwrap();
+    assert_eq!(scenario.vault.crystallize_performance_fee().unwrap(), (0, 0));
+    assert_eq!((scenario.vault.owner_shares, scenario.vault.high_water_mark), (owner_shares_before, mark));
+
+    test_clock::warp_days(14);
+    assert_eq!(scenario.execute(1).unwrap(), frozen_b);
+    let withdrawn = scenario.withdraw_owner_shares(&mut owner_fee_depositor).unwrap();
+    // 20 fresh, plus the first 40 grown with the net 80 the 1_120 active shares earned
+    assert!(withdrawn + 3 >= 62_857_142 && withdrawn <= 62_857_143);
+    assert_eq!(scenario.vault.owner_shares, 0);
+    scenario.checkpoint("owner withdrew");
+
+    scenario.drain();
+}
+
+#[test]
+fn scenario_uncollected_request_expires_back_into_active_shares() {
+    let mut scenario = Scenario::new(2, FOURTEEN_DAYS);
+    scenario.vault.unstake_request_expiry = 30 * ONE_DAY;
+    scenario.stake(0, 1_000 * USDC).unwrap();
+    scenario.stake(1, 1_000 * USDC).unwrap();
+    test_clock::warp_days(1);
+    let frozen = scenario.request_all(1).unwrap();
+    scenario.checkpoint("requested");
+
+    // B never collects: the matured request earns nothing while A takes every reward
+    test_clock::warp_days(14 + 29);
+    scenario.add_rewards(100 * USDC).unwrap();
+    assert!(matches!(scenario.expire(1), Err(VaultError::UnstakeRequestNotExpired)));
+    assert!(matches!(scenario.expire(0), Err(VaultError::NoUnstakeRequest)));
+    assert_eq!(scenario.position_value(1), frozen);
+    scenario.checkpoint("matured");
+
+    test_clock::warp_days(1);
+    scenario.expire(1).unwrap();
+    assert!(!scenario.depositors[1].unstake_request.is_pending());
+    assert_eq!((scenario.vault.reserved_assets, scenario.vault.pending_unstake_shares), (0, 0));
+    let restored = scenario.position_value(1);
+    assert!(restored <= frozen && restored + 1 >= frozen);
+    scenario.checkpoint("expired");
+
+    // Restored shares earn again, alongside A
+    let a_before = scenario.position_value(0);
+    test_clock::warp_days(1);
+    scenario.add_rewards(100 * USDC).unwrap();
+    let a_gain = scenario.position_value(0) - a_before;
+    let b_gain = scenario.position_value(1) - restored;
+    assert!(b_gain > 0 && b_gain < a_gain);
+    scenario.checkpoint("earning");
+
+    scenario.drain();
+}
+
+#[test]
+fn scenario_dust_requests_rejected_but_small_positions_still_exit() {
+    let mut scenario = Scenario::new(2, FOURTEEN_DAYS);
+    scenario.stake(0, 1_000 * USDC).unwrap();
+    scenario.stake(1, USDC).unwrap();
+
+    // The minimum can never exceed the stake minimum
+    let too_high = UpdateVaultConfigParams { min_unstake_amount: Some(2 * USDC), ..Default::default() };
+    assert!(matches!(scenario.vault.update_config(too_high), Err(VaultError::InvalidVaultConfig)));
+    let raised = UpdateVaultConfigParams {
+        min_stake_amount: Some(5 * USDC),
+        min_unstake_amount: Some(5 * USDC),
+        ..Default::default()
+    };
+    scenario.vault.update_config(raised).unwrap();
+    test_clock::warp_days(1);
+
+    assert!(matches!(scenario.request(0, 2), Err(VaultError::UnstakeBelowMinimum)));
+    assert!(matches!(scenario.request(0, 5 * USDC - 1), Err(VaultError::UnstakeBelowMinimum)));
+    scenario.request(0, 5 * USDC).unwrap();
+    scenario.checkpoint("minimum request");
+
+    // B's whole position is below the minimum: only a partial request is refused
+    assert!(matches!(scenario.request(1, USDC / 2), Err(VaultError::UnstakeBelowMinimum)));
+    let frozen = scenario.request_all(1).unwrap();
+    assert_eq!(frozen, USDC);
+    assert_eq!(scenario.depositors[1].shares, 0);
+    scenario.checkpoint("full exit");
+
+    // A's pending request has to be cancelled before the full exit, which is allowed too
+    assert!(matches!(scenario.request_all(0), Err(VaultError::UnstakeRequestAlreadyExists)));
+    scenario.cancel(0).unwrap();
+    scenario.request_all(0).unwrap();
+    assert_eq!(scenario.depositors[0].shares, 0);
+    scenario.checkpoint("full exit after a cancel");
+
+    scenario.drain();
+}
+
+/// Attacker and victim of the first-depositor inflation attack: the attacker holds the only
+/// share, donates as a reward, and the victim's stake rounds down to a single share
+const VICTIM_STAKE: u64 = 5_000 * USDC;
+const DONATION: u64 = VICTIM_STAKE / 2;
+
+#[test]
+fn scenario_donation_inflates_a_single_share_without_dead_shares() {
+    let mut scenario = Scenario::new(2, FOURTEEN_DAYS);
+    scenario.vault.platform_share_bps = 0;
+    scenario.vault.min_stake_amount = 0;
+    // One share for one unit, as bootstrapped before dead shares
+    scenario.vault.total_shares = 1;
+    scenario.vault.total_assets = 1;
+    scenario.depositors[0].shares = 1;
+    scenario.token_balance = 1;
+    scenario.deposited = 1;
+
+    test_clock::warp_days(1);
+    scenario.add_rewards(DONATION).unwrap();
+    // A stake worth less than the single share is refused rather than minting nothing
+    assert!(matches!(scenario.stake(1, DONATION), Err(VaultError::ZeroShares)));
+    assert_eq!(scenario.stake(1, VICTIM_STAKE).unwrap(), 1);
+    scenario.checkpoint("victim staked");
+
+    // Both hold one share, the attacker takes a quarter of the victim's stake
+    let total = 1 + DONATION + VICTIM_STAKE;
+    assert_eq!(scenario.position_value(1), total / 2);
+    assert_eq!(scenario.position_value(0), total / 2);
+    assert!(VICTIM_STAKE - scenario.position_value(1) >= VICTIM_STAKE / 4 - 1);
+}
+
+#[test]
+fn scenario_dead_shares_bound_the_donation_attack_to_dust() {
+    let mut scenario = Scenario::new(2, FOURTEEN_DAYS);
+    scenario.vault.platform_share_bps = 0;
+    scenario.vault.min_stake_amount = 0;
+
+    // The first stake must cover the dead shares, the rest is the attacker's single share
+    assert!(matches!(scenario.stake(0, DEAD_SHARES), Err(VaultError::MinimumStakeAmountNotMet)));
+    assert_eq!(scenario.stake(0, DEAD_SHARES + 1).unwrap(), 1);
+    assert_eq!(scenario.vault.dead_shares, DEAD_SHARES);
+    scenario.checkpoint("bootstrap");
+
+    test_clock::warp_days(1);
+    scenario.add_rewards(DONATION).unwrap();
+    assert!(scenario.stake(1, VICTIM_STAKE).unwrap() > 0);
+    scenario.checkpoint("victim staked");
+
+    // The victim loses less than one share, the attacker nearly all of the donation
+    let victim_loss = VICTIM_STAKE - scenario.position_value(1);
+    assert!(victim_loss <= VICTIM_STAKE / DEAD_SHARES, "victim lost {}", victim_loss);
+    let attacker_value = scenario.position_value(0);
+    assert!(attacker_value < (DEAD_SHARES + 1 + DONATION) / 100);
+
+    // The donation stays with the dead shares
+    test_clock::warp_days(1);
+    scenario.request_all(0).unwrap();
+    scenario.request_all(1).unwrap();
+    assert!(scenario.vault.shares_value(scenario.vault.dead_shares).unwrap() > DONATION * 99 / 100);
+    scenario.checkpoint("both left");
+}
+
+#[test]
+fn scenario_reward_during_the_lockup_goes_to_the_active_shares_only() {
+    let mut quiet = Scenario::new(2, FOURTEEN_DAYS);
+    quiet.stake(0, 1_000 * USDC).unwrap();
+    quiet.stake(1, 1_000 * USDC).unwrap();
+    test_clock::warp_days(1);
+    let frozen = quiet.request_all(0).unwrap();
+    let active_before = quiet.position_value(1);
+
+    // Same vault, one reward landing halfway through the lockup
+    let mut rewarded = quiet.clone();
+    test_clock::warp_days(7);
+    rewarded.add_rewards(200 * USDC).unwrap();
+    rewarded.checkpoint("mid-lockup reward");
+    test_clock::warp_days(7);
+
+    assert_eq!(quiet.execute(0).unwrap(), frozen);
+    assert_eq!(rewarded.execute(0).unwrap(), frozen);
+    assert_eq!(quiet.position_value(1), active_before);
+    // The whole reward stayed in the vault, all but the dead shares' cut with B
+    assert_eq!(rewarded.token_balance, quiet.token_balance + rewarded.rewarded);
+    assert!(rewarded.position_value(1) > active_before + 99 * USDC);
+    rewarded.checkpoint("executed");
+}
+
+#[test]
+fn scenario_token_shortfall_fails_stakes_requests_and_cancels() {
+    let mut scenario = Scenario::new(2, FOURTEEN_DAYS);
+    scenario.stake(0, 1_000 * USDC).unwrap();
+    scenario.stake(1, 1_000 * USDC).unwrap();
+    test_clock::warp_days(1);
+    scenario.request(1, 400 * USDC).unwrap();
+    scenario.checkpoint("funded");
+
+    // Tokens leave the vault token account behind the program's back
+    scenario.token_balance -= 300 * USDC;
+    let failed = |result: VaultResult<u64>| matches!(result, Err(VaultError::InvariantViolation));
+    assert!(failed(scenario.clone().stake(0, 100 * USDC)));
+    assert!(failed(scenario.clone().request(0, 100 * USDC)));
+    assert!(failed(scenario.clone().cancel(1)));
+
+    // Short of the frozen requests as well
+    scenario.token_balance = scenario.vault.reserved_assets - 1;
+    assert!(failed(scenario.clone().request(0, 100 * USDC)));
+
+    // A stake brings its own backing only, the gap has to be refilled by a plain transfer
+    assert!(failed(scenario.clone().stake(0, 10_000 * USDC)));
+    scenario.token_balance = scenario.vault.accounted_balance().unwrap();
+    scenario.stake(0, 100 * USDC).unwrap();
+    scenario.cancel(1).unwrap();
+}
+
+#[test]
+fn scenario_token_shortfall_fails_unstake_cleanly_until_refilled() {
+    let mut scenario = Scenario::new(2, FOURTEEN_DAYS);
+    scenario.stake(0, 1_000 * USDC).unwrap();
+    scenario.stake(1, 1_000 * USDC).unwrap();
+    test_clock::warp_days(1);
+    let frozen = scenario.request(0, 600 * USDC).unwrap();
+    test_clock::warp_days(14);
+
+    // The token account lost tokens behind the program's back: the execution fails with the
+    // program's error before any transfer and the request stays pending
+    scenario.token_balance = frozen - 1;
+    assert!(matches!(scenario.execute(0), Err(VaultError::InsufficientLiquidity)));
+    assert!(scenario.depositors[0].unstake_request.is_pending());
+    assert_eq!(scenario.vault.reserved_assets, frozen);
+
+    scenario.token_balance = frozen;
+    assert_eq!(scenario.execute(0).unwrap(), frozen);
+    assert_eq!(scenario.vault.reserved_assets, 0);
+}
+
+#[test]
+fn scenario_vault_past_the_rebase_limit_keeps_operating() {
+    const DILUTION: u64 = 1_000_000_000;
+    let mut scenario = Scenario::new(2, FOURTEEN_DAYS);
+    scenario.stake(0, 1_000 * USDC).unwrap();
+    scenario.stake(1, 500 * USDC).unwrap();
+    scenario.checkpoint("staked");
+
+    // Every share count blown up alike, as a long slide in share value would leave them
+    scenario.vault.total_shares *= DILUTION;
+    scenario.vault.dead_shares *= DILUTION;
+    for depositor in scenario.depositors.iter_mut() {
+        depositor.shares *= DILUTION;
+    }
+    assert!(scenario.vault.total_shares / scenario.vault.total_assets > 1_000_000);
+    scenario.checkpoint("diluted");
+
+    // Rewards rebase by the largest single step, the next stake finishes the job
+    test_clock::warp_days(1);
+    scenario.add_rewards(10 * USDC).unwrap();
+    assert_eq!(scenario.vault.shares_base, MAX_REBASE_EXPO);
+    scenario.stake(1, 100 * USDC).unwrap();
+    assert!(scenario.vault.shares_base > MAX_REBASE_EXPO);
+    assert!(scenario.vault.total_shares < 10 * scenario.vault.total_assets);
+    scenario.checkpoint("rebased");
+
+    // Both depositors still exit with what they put in plus their share of the rewards
+    let values = [scenario.position_value(0), scenario.position_value(1)];
+    assert!(values[0] >= 1_000 * USDC && values[1] >= 600 * USDC, "{:?}", values);
+    scenario.drain();
+    assert!(scenario.paid[0] >= 1_000 * USDC && scenario.paid[1] >= 600 * USDC);
+}
+
+#[test]
+fn scenario_forced_distribution_pays_a_depositor_lagging_behind_a_rebase() {
+    const DILUTION: u64 = 1_000;
+    let mut scenario = Scenario::new(2, FOURTEEN_DAYS);
+    scenario.stake(0, 1_000 * USDC).unwrap();
+    scenario.stake(1, 500 * USDC).unwrap();
+
+    // Shares blown up alike, as a slide in share value would leave them
+    scenario.vault.total_shares *= DILUTION;
+    scenario.vault.dead_shares *= DILUTION;
+    for depositor in scenario.depositors.iter_mut() {
+        depositor.shares *= DILUTION;
+    }
+    let values = [scenario.position_value(0), scenario.position_value(1)];
+
+    test_clock::warp_days(1);
+    scenario.vault.begin_decommission(test_clock::now(), MIN_DECOMMISSION_NOTICE).unwrap();
+    // The keeper rebases, B follows and exits, A never shows up
+    assert!(scenario.vault.apply_rebase().unwrap().is_some());
+    scenario.sync(1).unwrap();
+    scenario.request_all(1).unwrap();
+    test_clock::warp_seconds(MIN_DECOMMISSION_NOTICE);
+    scenario.execute(1).unwrap();
+    assert!(scenario.paid[1] <= values[1]);
+    assert_ne!(scenario.depositors[0].last_rebase_version, scenario.vault.rebase_version);
+
+    test_clock::warp_seconds(DECOMMISSION_FORCE_TIMEOUT);
+    let paid = scenario.force_distribute(0).unwrap();
+    // The synced value, less at most one share of rebase rounding
+    assert!(paid <= values[0] && paid + 10 >= values[0], "{} vs {}", paid, values[0]);
+    scenario.checkpoint("forced");
+    assert_eq!(scenario.vault.total_shares, scenario.vault.dead_shares);
+}
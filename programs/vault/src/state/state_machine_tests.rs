@@ -0,0 +1,555 @@
+//! Exhaustive state-machine tests for the unstake lifecycle.
+//!
+//! Every sequence of request / cancel / rewards / rebase / execute steps (up to a fixed
+//! depth) is replayed against the vault and depositor state, checking exact payouts and
+//! the vault invariants after each step. Failing steps are rolled back like a reverted
+//! transaction and must fail with an expected error.
+
+use super::test_clock::{self, START_TIME};
+use super::*;
+use crate::constants::*;
+use crate::error::*;
+
+const LOCKUP: i64 = 2 * ONE_DAY;
+/// Time between two steps, so a request matures two steps after it was made
+const STEP: i64 = ONE_DAY;
+
+const STAKE_A: u64 = 1_000_000;
+const STAKE_B: u64 = 3_000_000;
+const REQUEST_A: u64 = 250_000;
+const REWARD: u64 = 100_003;
+/// Shares per asset in a vault that took a loss before rebasing
+const DILUTION: u64 = 1_000;
+
+/// State methods that still read `Clock::get()` see `START_TIME`
+fn install_clock() {
+    test_clock::set_time(START_TIME);
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Step {
+    RequestA,
+    RequestAllB,
+    CancelA,
+    CancelB,
+    Rewards,
+    Rebase,
+    ExecuteA,
+    ExecuteB,
+}
+
+const STEPS: [Step; 8] = [
+    Step::RequestA,
+    Step::RequestAllB,
+    Step::CancelA,
+    Step::CancelB,
+    Step::Rewards,
+    Step::Rebase,
+    Step::ExecuteA,
+    Step::ExecuteB,
+];
+
+/// What a depositor's pending request must look like, independent of rebases
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct ExpectedRequest {
+    frozen_amount: u64,
+    request_time: i64,
+}
+
+#[derive(Clone)]
+struct Harness {
+    vault: Vault,
+    depositors: [VaultDepositor; 2],
+    expected: [Option<ExpectedRequest>; 2],
+    /// Balance of the vault token account
+    token_balance: u64,
+    deposited: u64,
+    rewarded: u64,
+    paid: [u64; 2],
+    now: i64,
+}
+
+impl Harness {
+    fn new(diluted: bool) -> Self {
+        install_clock();
+
+        let mut harness = Harness {
+            vault: Vault {
+                unstake_lockup_period: LOCKUP,
+                max_total_assets: u64::MAX,
+                ..Vault::default()
+            },
+            depositors: [VaultDepositor::default(), VaultDepositor::default()],
+            expected: [None; 2],
+            token_balance: 0,
+            deposited: 0,
+            rewarded: 0,
+            paid: [0; 2],
+            now: START_TIME + STEP,
+        };
+
+        for (i, amount) in [STAKE_A, STAKE_B].into_iter().enumerate() {
+            if diluted {
+                // Share price of 1/DILUTION, as left behind by a loss before any rebase
+                let shares = amount * DILUTION;
+                harness.vault.total_shares += shares;
+                harness.vault.total_assets += amount;
+                harness.depositors[i].shares += shares;
+            } else {
+                let shares = harness.vault.stake(amount, 0).unwrap();
+                harness.depositors[i].stake(shares, 0).unwrap();
+            }
+            harness.token_balance += amount;
+            harness.deposited += amount;
+        }
+
+        harness.check();
+        harness
+    }
+
+    /// Value of a depositor's position in assets: active shares at today's price plus its frozen request
+    fn position_value(&self, i: usize) -> u128 {
+        let share_value = self.vault.get_active_share_value().unwrap();
+        self.depositors[i].shares as u128 * share_value / PRECISION as u128
+            + self.depositors[i].unstake_request.frozen_amount().unwrap() as u128
+    }
+
+    /// Rounding allowance when comparing position values: one share plus one base unit
+    fn rounding_allowance(&self) -> u128 {
+        self.vault.get_active_share_value().unwrap() / PRECISION as u128 + 1
+    }
+
+    fn apply(&mut self, step: Step) -> VaultResult<()> {
+        match step {
+            Step::RequestA => self.request(0, Some(REQUEST_A)),
+            Step::RequestAllB => self.request(1, None),
+            Step::CancelA => self.cancel(0),
+            Step::CancelB => self.cancel(1),
+            Step::Rewards => {
+                self.vault.add_rewards(REWARD)?;
+                self.token_balance += REWARD;
+                self.rewarded += REWARD;
+                Ok(())
+            }
+            Step::Rebase => self.vault.apply_rebase().map(|_| ()),
+            Step::ExecuteA => self.execute(0),
+            Step::ExecuteB => self.execute(1),
+        }
+    }
+
+    /// Requests exactly `amount` assets, or the whole position without one
+    fn request(&mut self, i: usize, amount: Option<u64>) -> VaultResult<()> {
+        let other = 1 - i;
+        let other_value = self.position_value(other);
+        let value_before = self.position_value(i);
+
+        let (_, frozen_amount) = match amount {
+            Some(amount) => {
+                let (shares, frozen_amount) = self.depositors[i].request_unstake(&mut self.vault, amount, self.now)?;
+                assert_eq!(frozen_amount, amount, "partial requests freeze the exact amount");
+                (shares, frozen_amount)
+            }
+            None => self.depositors[i].request_unstake_all(&mut self.vault, self.now)?,
+        };
+        self.expected[i] = Some(ExpectedRequest {
+            frozen_amount,
+            request_time: self.now,
+        });
+
+        // Requesting only moves value between the depositor's own active and frozen buckets
+        assert!(self.position_value(i) <= value_before + self.rounding_allowance());
+        assert!(self.position_value(other) + self.rounding_allowance() >= other_value);
+        Ok(())
+    }
+
+    fn cancel(&mut self, i: usize) -> VaultResult<()> {
+        let other = 1 - i;
+        let other_value = self.position_value(other);
+        let value_before = self.position_value(i);
+
+        let (frozen_amount, _) = self.depositors[i].cancel_unstake(&mut self.vault)?;
+        assert_eq!(Some(frozen_amount), self.expected[i].map(|e| e.frozen_amount));
+        self.expected[i] = None;
+
+        // Reactivating must not create value for anyone beyond rounding
+        assert!(self.position_value(i) <= value_before + self.rounding_allowance());
+        assert!(self.position_value(i) + self.rounding_allowance() >= value_before);
+        assert!(self.position_value(other) >= other_value);
+        Ok(())
+    }
+
+    fn execute(&mut self, i: usize) -> VaultResult<()> {
+        let other = 1 - i;
+        let other_value = self.position_value(other);
+
+        let (_, amount) = self.depositors[i].execute_unstake(&mut self.vault, self.now)?;
+        let expected = self.expected[i].take().expect("executed without a request");
+        assert_eq!(amount, expected.frozen_amount, "execution pays exactly the frozen amount");
+        assert!(self.now >= expected.request_time + LOCKUP);
+
+        self.token_balance -= amount;
+        self.paid[i] += amount;
+
+        assert!(self.position_value(other) >= other_value);
+        Ok(())
+    }
+
+    /// Keeper behaviour: depositors are synced with the vault's rebase after every step
+    fn sync(&mut self) {
+        for depositor in self.depositors.iter_mut() {
+            depositor.sync_rebase(&mut self.vault).unwrap();
+        }
+    }
+
+    fn check(&self) {
+        self.vault.verify_invariants().unwrap();
+
+        // Accounting matches the token account exactly, no value is created or lost
+        assert_eq!(self.vault.total_assets, self.token_balance);
+        assert_eq!(
+            self.deposited + self.rewarded,
+            self.token_balance + self.paid[0] + self.paid[1]
+        );
+
+        // Reserved assets are exactly the sum of the frozen requests
+        let frozen: u64 = self
+            .depositors
+            .iter()
+            .map(|d| d.unstake_request.frozen_amount().unwrap())
+            .sum();
+        assert_eq!(self.vault.reserved_assets, frozen);
+
+        // Share counters agree with the depositors up to per-depositor rebase rounding, the
+        // dead shares are active shares no depositor holds
+        let pending: u64 = self.depositors.iter().map(|d| d.unstake_request.shares).sum();
+        let active: u64 = self.depositors.iter().map(|d| d.shares).sum::<u64>() + self.vault.dead_shares;
+        assert!(self.vault.pending_unstake_shares.abs_diff(pending) <= 2);
+        assert!(self.vault.get_active_shares().unwrap().abs_diff(active) <= 2);
+
+        // Pending requests keep their price and lockup start across rewards and rebases
+        for (depositor, expected) in self.depositors.iter().zip(self.expected.iter()) {
+            match expected {
+                Some(expected) => {
+                    assert_eq!(depositor.unstake_request.frozen_amount().unwrap(), expected.frozen_amount);
+                    assert_eq!(depositor.unstake_request.request_time, expected.request_time);
+                }
+                None => assert!(!depositor.unstake_request.is_pending()),
+            }
+        }
+    }
+
+    /// Run one step like a transaction: roll back on error, then advance the clock
+    fn run(&mut self, step: Step) -> VaultResult<()> {
+        let snapshot = self.clone();
+        let result = self.apply(step);
+        match result {
+            Ok(()) => self.sync(),
+            Err(_) => {
+                let now = self.now;
+                *self = snapshot;
+                self.now = now;
+            }
+        }
+        self.check();
+        self.now += STEP;
+        result
+    }
+
+    /// Everyone exits; only rounding dust may stay behind
+    fn drain(&mut self) {
+        for i in 0..2 {
+            if self.depositors[i].unstake_request.is_pending() {
+                self.now += LOCKUP;
+                self.execute(i).unwrap();
+                self.check();
+            }
+            if self.depositors[i].shares > 0 {
+                self.request(i, None).unwrap();
+                self.sync();
+                self.check();
+                self.now += LOCKUP;
+                self.execute(i).unwrap();
+                self.check();
+            }
+        }
+        // The dead shares stay behind with what they are worth
+        assert_eq!(self.vault.total_shares, self.vault.dead_shares);
+        assert!(self.vault.total_assets <= 2 * self.vault.dead_shares + 2, "dust left: {}", self.vault.total_assets);
+    }
+}
+
+fn expect_allowed_error(step: Step, error: VaultError) {
+    let allowed = match step {
+        Step::RequestA | Step::RequestAllB => matches!(
+            error,
+            VaultError::NoActiveShares
+                | VaultError::InsufficientFunds
+                | VaultError::InvalidAmount
+                | VaultError::UnstakeRequestAlreadyExists
+        ),
+        Step::CancelA | Step::CancelB => matches!(error, VaultError::NoUnstakeRequest),
+        Step::ExecuteA | Step::ExecuteB => matches!(error, VaultError::UnstakeLockupNotFinished),
+        Step::Rewards | Step::Rebase => false,
+    };
+    assert!(allowed, "{:?} failed with {:?}", step, error);
+}
+
+#[test]
+fn test_all_unstake_sequences_pay_exact_amounts() {
+    const DEPTH: u32 = 4;
+
+    for diluted in [false, true] {
+        let base = Harness::new(diluted);
+        for index in 0..STEPS.len().pow(DEPTH) {
+            let mut harness = base.clone();
+            let mut rest = index;
+            for _ in 0..DEPTH {
+                let step = STEPS[rest % STEPS.len()];
+                rest /= STEPS.len();
+                if let Err(error) = harness.run(step) {
+                    expect_allowed_error(step, error);
+                }
+            }
+            harness.drain();
+        }
+    }
+}
+
+#[test]
+fn test_pending_request_survives_rebase() {
+    let mut harness = Harness::new(true);
+    harness.run(Step::RequestA).unwrap();
+    let frozen = harness.depositors[0].unstake_request.frozen_amount().unwrap();
+
+    harness.run(Step::Rebase).unwrap();
+    assert!(harness.vault.shares_base > 0);
+    assert!(harness.vault.pending_unstake_shares < REQUEST_A * DILUTION);
+    assert_eq!(harness.depositors[0].unstake_request.frozen_amount().unwrap(), frozen);
+
+    harness.run(Step::ExecuteA).unwrap();
+    assert_eq!(harness.paid[0], REQUEST_A);
+}
+
+#[test]
+fn test_cancel_after_rewards_does_not_mint_value() {
+    let mut harness = Harness::new(false);
+    harness.run(Step::RequestA).unwrap();
+    harness.run(Step::Rewards).unwrap();
+
+    // A's frozen assets earned nothing, so A gets fewer shares back than it froze
+    let shares_before = harness.depositors[0].shares + harness.depositors[0].unstake_request.shares;
+    harness.run(Step::CancelA).unwrap();
+    assert!(harness.depositors[0].shares < shares_before);
+    assert_eq!(harness.vault.total_assets, STAKE_A + STAKE_B + REWARD);
+}
+
+#[test]
+fn test_rerequest_is_rejected_and_keeps_the_lockup() {
+    let mut harness = Harness::new(false);
+    harness.run(Step::RequestA).unwrap();
+    let request = harness.depositors[0].unstake_request.clone();
+    harness.run(Step::Rewards).unwrap();
+
+    // Neither a second partial request nor a full one replaces or merges into the first
+    assert!(matches!(harness.run(Step::RequestA), Err(VaultError::UnstakeRequestAlreadyExists)));
+    assert!(matches!(
+        harness.depositors[0].request_unstake_all(&mut harness.vault, harness.now),
+        Err(VaultError::UnstakeRequestAlreadyExists)
+    ));
+    let pending = &harness.depositors[0].unstake_request;
+    assert_eq!((pending.shares, pending.request_time), (request.shares, request.request_time));
+    assert_eq!(pending.frozen_amount().unwrap(), REQUEST_A);
+    harness.check();
+
+    // The first request matures on its own schedule
+    harness.now = request.request_time + LOCKUP;
+    harness.run(Step::ExecuteA).unwrap();
+    assert_eq!(harness.paid[0], REQUEST_A);
+}
+
+#[test]
+fn test_cancel_then_request_starts_a_new_lockup_at_todays_price() {
+    let mut harness = Harness::new(false);
+    harness.run(Step::RequestA).unwrap();
+    let first_request_time = harness.depositors[0].unstake_request.request_time;
+    harness.run(Step::Rewards).unwrap();
+    harness.run(Step::CancelA).unwrap();
+    harness.run(Step::RequestA).unwrap();
+
+    let request = harness.depositors[0].unstake_request.clone();
+    assert!(request.request_time > first_request_time);
+    assert_eq!(request.frozen_amount().unwrap(), REQUEST_A);
+
+    // Cancelling gave up the first request's maturity
+    harness.now = first_request_time + LOCKUP;
+    assert!(matches!(harness.run(Step::ExecuteA), Err(VaultError::UnstakeLockupNotFinished)));
+}
+
+#[test]
+fn test_requests_floored_by_a_rebase_release_exactly_their_shares() {
+    install_clock();
+    // Two 5-share requests frozen at 1 asset each, next to ~10^4 shares per active asset
+    let request = UnstakeRequest {
+        shares: 5,
+        request_time: START_TIME,
+        asset_per_share_at_request: PRECISION as u128 / 5,
+    };
+    let depositor = VaultDepositor {
+        shares: 4_999_995,
+        unstake_request: request,
+        ..VaultDepositor::default()
+    };
+    let mut harness = Harness {
+        vault: Vault {
+            unstake_lockup_period: LOCKUP,
+            max_total_assets: u64::MAX,
+            total_shares: 10_000_000,
+            pending_unstake_shares: 10,
+            total_assets: 1_002,
+            reserved_assets: 2,
+            ..Vault::default()
+        },
+        depositors: [depositor.clone(), depositor],
+        expected: [Some(ExpectedRequest { frozen_amount: 1, request_time: START_TIME }); 2],
+        token_balance: 1_002,
+        deposited: 1_002,
+        rewarded: 0,
+        paid: [0; 2],
+        now: START_TIME + STEP,
+    };
+    harness.check();
+
+    // The vault's 10 pending shares divide down to 0, each request keeps its floor share and
+    // the vault counts both
+    harness.run(Step::Rebase).unwrap();
+    assert!(harness.vault.shares_base >= 2);
+    assert_eq!(harness.depositors.each_ref().map(|d| d.unstake_request.shares), [1, 1]);
+    assert_eq!(harness.vault.pending_unstake_shares, 2);
+
+    // Each execution burns exactly its request's share, nothing is left to dilute the rest
+    harness.now += LOCKUP;
+    let total_shares = harness.vault.total_shares;
+    harness.run(Step::ExecuteA).unwrap();
+    harness.run(Step::ExecuteB).unwrap();
+    assert_eq!(harness.vault.pending_unstake_shares, 0);
+    assert_eq!(harness.vault.total_shares, total_shares - 2);
+    assert_eq!(harness.paid, [1, 1]);
+
+    // A request the counter does not cover is refused instead of released partially
+    let mut vault = harness.vault.clone();
+    let orphan = UnstakeRequest { shares: 1, request_time: 0, asset_per_share_at_request: 0 };
+    assert!(matches!(vault.complete_unstake(&orphan), Err(VaultError::InvariantRequestShares)));
+}
+
+#[test]
+fn test_sync_rebase_applies_each_rebase_once() {
+    install_clock();
+    let mut vault = Vault {
+        total_shares: 100_000_000,
+        total_assets: 1_000,
+        ..Vault::default()
+    };
+    let mut depositor = VaultDepositor {
+        shares: 100_000_000,
+        ..VaultDepositor::default()
+    };
+
+    vault.apply_rebase().unwrap();
+    depositor.sync_rebase(&mut vault).unwrap();
+    assert_eq!(depositor.shares, vault.total_shares);
+
+    // A second rebase only divides by its own factor
+    vault.total_assets = 10;
+    vault.apply_rebase().unwrap();
+    assert_eq!(vault.rebase_version, 2);
+    depositor.sync_rebase(&mut vault).unwrap();
+    assert_eq!(depositor.shares, vault.total_shares);
+}
+
+#[test]
+fn test_baseline_synced_depositor_needs_its_shares_base_before_the_next_rebase() {
+    install_clock();
+    let mut vault = Vault {
+        total_shares: 100_000_000,
+        total_assets: 1_000,
+        ..Vault::default()
+    };
+    let mut depositor = VaultDepositor {
+        shares: 100_000_000,
+        ..VaultDepositor::default()
+    };
+
+    // The baseline sync divided by the whole shares_base and left last_shares_base at 0
+    vault.apply_rebase().unwrap();
+    depositor.apply_rebase(10u128.pow(vault.shares_base), vault.rebase_version).unwrap();
+    assert_eq!((depositor.shares, depositor.last_shares_base), (vault.total_shares, 0));
+
+    // Unmigrated, the next rebase is refused instead of dividing by the first one again
+    let mut unmigrated = (vault.clone(), depositor.clone());
+    unmigrated.0.total_assets = 10;
+    unmigrated.0.apply_rebase().unwrap();
+    assert!(matches!(unmigrated.1.sync_rebase(&mut unmigrated.0), Err(VaultError::DepositorNotMigrated)));
+    assert_eq!(unmigrated.1.shares, depositor.shares);
+    // Behind the latest rebase its base cannot be told any more
+    assert!(!unmigrated.1.backfill_shares_base(&unmigrated.0));
+
+    // Backfilled while synced, the next rebase only divides by its own factor
+    assert!(depositor.backfill_shares_base(&vault));
+    vault.total_assets = 10;
+    vault.apply_rebase().unwrap();
+    assert_eq!(vault.rebase_version, 2);
+    depositor.sync_rebase(&mut vault).unwrap();
+    assert_eq!(depositor.shares, vault.total_shares);
+}
+
+#[test]
+fn test_stake_and_exit_round_trips_never_lose_vault_value() {
+    install_clock();
+    // Prices below, at and far above one asset per share, none of them round or rebase
+    let prices = [(1_000_000u64, 1_000_000u64), (1_999_999, 1_000_003), (999_983, 7_777_777), (7, 5_000_011)];
+    let amounts = [1u64, 2, 3, 999, 10_007, 1_000_003];
+
+    for (existing_shares, assets) in prices {
+        for amount in amounts {
+            for partial in [0, 1, amount / 3, amount - 1] {
+                let mut vault = Vault {
+                    total_shares: existing_shares,
+                    total_assets: assets,
+                    max_total_assets: u64::MAX,
+                    ..Vault::default()
+                };
+                let existing_value = vault.shares_value(existing_shares).unwrap();
+
+                // Shares are rounded down, a stake worth less than one is refused
+                let minted = match vault.stake(amount, 0) {
+                    Ok(minted) => minted,
+                    Err(error) => {
+                        assert!(matches!(error, VaultError::ZeroShares));
+                        continue;
+                    }
+                };
+
+                // An exact partial request burns shares rounded up, the rest leaves in full
+                let mut requests = Vec::new();
+                let mut remaining = minted;
+                if partial > 0 {
+                    if let Ok((shares, frozen_amount, price)) = vault.freeze_unstake(partial, remaining) {
+                        assert_eq!(frozen_amount, partial);
+                        requests.push(UnstakeRequest { shares, request_time: 0, asset_per_share_at_request: price });
+                        remaining -= shares;
+                    }
+                }
+                if remaining > 0 {
+                    let (shares, _, price) = vault.freeze_unstake_all(remaining).unwrap();
+                    requests.push(UnstakeRequest { shares, request_time: 0, asset_per_share_at_request: price });
+                }
+                let paid: u64 = requests.iter().map(|request| vault.complete_unstake(request).unwrap().0).sum();
+
+                let label = format!("{} staked, {} requested at {}/{}", amount, partial, existing_shares, assets);
+                assert!(paid <= amount, "{}: paid {}", label, paid);
+                assert!(vault.shares_value(existing_shares).unwrap() >= existing_value, "{}", label);
+                assert_eq!(vault.total_shares, existing_shares, "{}", label);
+            }
+        }
+    }
+}
@@ -0,0 +1,59 @@
+//! Off-chain `Clock` for state tests.
+//!
+//! `Clock::get()` fails outside the runtime, so tests install a syscall stub that serves
+//! a per-thread unix timestamp. Each test runs on its own thread and moves its clock with
+//! `set_time` / `warp_seconds` / `warp_days` without affecting tests running in parallel.
+
+use crate::constants::*;
+use anchor_lang::solana_program::clock::Clock;
+use anchor_lang::solana_program::program_stubs::{set_syscall_stubs, SyscallStubs};
+use std::cell::Cell;
+use std::sync::Once;
+
+/// Time every thread's clock starts at
+pub const START_TIME: i64 = 1_700_000_000;
+
+thread_local! {
+    static NOW: Cell<i64> = const { Cell::new(START_TIME) };
+}
+
+struct ThreadClock;
+
+impl SyscallStubs for ThreadClock {
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = Clock {
+            unix_timestamp: now(),
+            ..Clock::default()
+        };
+        unsafe { *(var_addr as *mut Clock) = clock };
+        0
+    }
+
+    fn sol_log(&self, _message: &str) {}
+}
+
+/// Install the stub once per test binary; safe to call from every test
+pub fn install() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        set_syscall_stubs(Box::new(ThreadClock));
+    });
+}
+
+pub fn now() -> i64 {
+    NOW.with(|now| now.get())
+}
+
+pub fn set_time(timestamp: i64) {
+    install();
+    NOW.with(|now| now.set(timestamp));
+}
+
+pub fn warp_seconds(seconds: i64) -> i64 {
+    set_time(now() + seconds);
+    now()
+}
+
+pub fn warp_days(days: i64) -> i64 {
+    warp_seconds(days * ONE_DAY)
+}
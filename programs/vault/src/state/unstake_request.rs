@@ -1,4 +1,7 @@
 use anchor_lang::prelude::*;
+use crate::constants::*;
+use crate::error::*;
+use crate::math::{SafeMath, SafeCast};
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
 pub struct UnstakeRequest {
@@ -28,4 +31,42 @@ impl UnstakeRequest {
     pub fn can_execute(&self, current_time: i64, lockup_period: i64) -> bool {
         self.is_pending() && current_time >= self.request_time + lockup_period
     }
+
+    /// Assets frozen for this request, i.e. the exact amount paid out on execution
+    pub fn frozen_amount(&self) -> VaultResult<u64> {
+        SafeCast::<u128>::safe_cast(&self.shares)?
+            .safe_mul(self.asset_per_share_at_request)?
+            .safe_div(SafeCast::<u128>::safe_cast(&PRECISION)?)?
+            .safe_cast()
+    }
+
+    /// Divide the request's shares by a rebase divisor without changing its frozen amount.
+    /// The per-share price is scaled up (rounded up) so `frozen_amount` stays exact.
+    /// Shares round down like the vault's pending counter, so the requests never add up to
+    /// more than it. Returns the share the 1-share floor added on top, which the vault has
+    /// to count as pending as well
+    pub fn rescale(&mut self, rebase_divisor: u128) -> VaultResult<u64> {
+        if !self.is_pending() || rebase_divisor <= 1 {
+            return Ok(0);
+        }
+
+        let frozen_amount = self.frozen_amount()?;
+        let shares: u64 = (SafeCast::<u128>::safe_cast(&self.shares)?.safe_div(rebase_divisor)?).safe_cast()?;
+        // Keep at least 1 share so the request stays pending
+        self.shares = shares.max(1);
+
+        self.asset_per_share_at_request = Self::exact_price(frozen_amount, self.shares)?;
+
+        self.shares.safe_sub(shares)
+    }
+
+    /// Per-share price (scaled by PRECISION) at which `shares` are worth exactly `frozen_amount`.
+    /// Rounded up so `frozen_amount()` reproduces the amount instead of one unit less.
+    pub fn exact_price(frozen_amount: u64, shares: u64) -> VaultResult<u128> {
+        let shares = SafeCast::<u128>::safe_cast(&shares)?;
+        SafeCast::<u128>::safe_cast(&frozen_amount)?
+            .safe_mul(SafeCast::<u128>::safe_cast(&PRECISION)?)?
+            .safe_add(shares.safe_sub(1)?)?
+            .safe_div(shares)
+    }
 }
\ No newline at end of file
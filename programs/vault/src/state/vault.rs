@@ -1,6 +1,7 @@
 use crate::constants::*;
 use crate::error::*;
-use crate::math::{vault_math, SafeCast, SafeMath};
+use crate::math::{vault_math, vault_math::Rounding, SafeCast, SafeMath};
+use crate::state::{AllowedDepositor, PrecisionFloor, ProgramRewardSource, RebaseSync, ShareValueCheckpoint, UnstakeRequest, VaultDepositor};
 use crate::utils::*;
 use anchor_lang::prelude::*;
 
@@ -31,13 +32,15 @@ pub struct Vault {
     pub last_rewards_update: i64,
     /// Unstake lockup period in seconds
     pub unstake_lockup_period: i64,
-    /// Platform share percentage for add_rewards (in basis points)
-    pub management_fee: u64,
+    /// Share of each add_rewards sent to `platform_account` (in basis points). Stored where
+    /// the former `management_fee` field was, which always held this split
+    pub platform_share_bps: u64,
     /// Minimum stake amount
     pub min_stake_amount: u64,
     /// Maximum total assets
     pub max_total_assets: u64,
-    /// Whether the vault is paused
+    /// Legacy deposits pause, folded into `paused_operations` by the next `update_config`.
+    /// Kept for layout and IDL compatibility, read pauses through `paused_mask`
     pub is_paused: bool,
     /// Vault creation timestamp
     pub created_at: i64,
@@ -45,7 +48,8 @@ pub struct Vault {
     pub shares_base: u32,
     /// Current rebase version for tracking
     pub rebase_version: u32,
-    /// Owner shares (owner as a normal depositor)
+    /// Owner fee shares not in the `owner_fee_depositor` yet: legacy ones, moved by
+    /// `migrate_owner_shares`, and performance fees, claimed by the owner's next withdrawal
     pub owner_shares: u64,
     /// Total shares pending unstake (not participating in rewards)
     pub pending_unstake_shares: u64,
@@ -53,8 +57,118 @@ pub struct Vault {
     pub reserved_assets: u64,
     /// Bump seed for PDA
     pub bump: u8,
-    /// Reserved for future use
-    pub _reserved: [u8; 16],
+    /// When decommissioning began (0 = not decommissioning)
+    pub decommission_started_at: i64,
+    /// When the decommission notice period ends and the unstake lockup is waived
+    pub decommission_notice_ends_at: i64,
+    /// Number of times a rebase rounded a depositor up to the 1-share precision floor
+    pub precision_floor_events: u32,
+    /// Account layout version (0 = legacy account not migrated yet)
+    pub version: u8,
+    /// Penalty on instant unstakes in basis points, kept by the vault (0 = instant unstake disabled)
+    pub instant_unstake_fee_bps: u64,
+    /// Proposed new owner awaiting `accept_ownership` (default pubkey = none)
+    pub pending_owner: Pubkey,
+    /// Seconds after a stake before the depositor can exit (0 = DEFAULT_STAKE_COOLDOWN_SECONDS)
+    pub stake_cooldown_seconds: i64,
+    /// Legacy withdrawals pause, folded into `paused_operations` like `is_paused`
+    pub withdrawals_paused: bool,
+    /// Only wallets with an `AllowedDepositor` marker can open a position and stake
+    pub permissioned: bool,
+    /// Largest position value a single depositor can stake up to (0 or u64::MAX = unlimited)
+    pub max_stake_per_depositor: u64,
+    /// Paused instruction families, a mask of the `PAUSE_*` bits
+    pub paused_operations: u32,
+    /// SPL mint tracking active shares 1:1, created by `initialize_vault` on opt-in
+    /// (default pubkey = shares are not tokenized). Tokenized vaults never rebase
+    pub share_mint: Pubkey,
+    /// Registered `ProgramRewardSource`s. While nonzero, `add_rewards` only accepts a
+    /// registered program PDA as the signing reward source authority
+    pub program_reward_sources: u32,
+    /// Seconds over which each add_rewards is released into `total_assets` (0 = instantly)
+    pub reward_vesting_period: i64,
+    /// Rewards held in the vault token account but not released into `total_assets` yet
+    pub unvested_rewards: u64,
+    /// When `unvested_rewards` is fully released
+    pub vesting_end: i64,
+    /// Last time vested rewards were released, the start of the remaining linear release
+    pub last_vesting_update: i64,
+    /// Share of every active share value gain above `high_water_mark` minted to the owner
+    /// (in basis points)
+    pub performance_fee_bps: u64,
+    /// Highest active share value the performance fee was charged up to (scaled by PRECISION,
+    /// 0 = not set yet)
+    pub high_water_mark: u128,
+    /// Share of a matured unstake's frozen amount left in the vault for the remaining stakers
+    /// (in basis points)
+    pub withdrawal_fee_bps: u64,
+    /// Seconds a matured unstake request may stay uncollected before anyone can restore it to
+    /// active shares (0 = never expires)
+    pub unstake_request_expiry: i64,
+    /// Wallet allowed to sign `add_rewards` besides the registered program reward sources
+    /// (default pubkey = not set, on vaults created before it existed)
+    pub reward_authority: Pubkey,
+    /// `add_rewards` accepts any signing source, ignoring `reward_authority` and the registry
+    pub permissionless_rewards: bool,
+    /// `reconcile` pauses stakes and withdrawals when the token balance falls short of the
+    /// accounted assets
+    pub pause_on_shortfall: bool,
+    /// Slot of the last `reconcile` that found the token balance covering the accounted assets
+    pub last_reconcile_slot: u64,
+    /// Seconds a proposed config update waits before `commit_config_update` can apply it
+    /// (0 = at once). Only changed through a proposal itself
+    pub config_timelock_seconds: i64,
+    /// When `platform_share_bps` last changed (0 = never since it was tracked)
+    pub last_fee_change: i64,
+    /// Smallest unstake request below a full exit of the position (0 = no minimum)
+    pub min_unstake_amount: u64,
+    /// Depositor positions opened, the owner fee depositor not included. Vaults created before
+    /// the count existed only count positions opened since
+    pub depositor_count: u32,
+    /// Depositor positions holding active shares, the owner fee depositor not included. On
+    /// vaults created before the count existed it only follows positions that changed since
+    pub active_depositor_count: u32,
+    /// Least spacing between two `VaultCheckpoints` entries, in seconds
+    pub checkpoint_interval: i64,
+    /// Product of the share value growth of every reward released to the active shares,
+    /// scaled by SHARE_PRECISION (starts at SHARE_PRECISION). Stakes and exits leave it alone
+    pub cumulative_return: u128,
+    /// Rewards released per active share, in share units before any rebase and scaled by
+    /// SHARE_PRECISION. Unlike `rewards_per_share`, rebases do not change its unit
+    pub lifetime_rewards_per_active_share: u128,
+    /// Mint of a second reward token paid out by claim instead of compounded, set once by
+    /// `set_secondary_reward_mint` (default pubkey = no secondary rewards)
+    pub secondary_reward_mint: Pubkey,
+    /// Vault-owned token account holding the secondary rewards until they are claimed
+    pub secondary_reward_token_account: Pubkey,
+    /// Secondary rewards added per active share, in share units before any rebase and scaled
+    /// by SHARE_PRECISION. A depositor's `rewards_debt` is the value it was last settled at
+    pub secondary_rewards_per_share: u128,
+    /// Secondary rewards added over the vault's life
+    pub total_secondary_rewards: u64,
+    /// Annual fee on the vault's assets (in basis points), kept for a time-based accrual that
+    /// is not charged yet. Unrelated to the reward split in `platform_share_bps`
+    pub management_fee_bps: u64,
+    /// Proposed platform account awaiting `accept_platform_account` (default pubkey = none).
+    /// Rewards keep going to `platform_account` until it is accepted
+    pub pending_platform_account: Pubkey,
+    /// A platform account proposal must be co-signed by the current platform account. Only
+    /// the platform account can change it
+    pub platform_cosign_required: bool,
+    /// The protocol config admin may lower this vault's fees and pause its deposits with
+    /// `override_vault_fees`. Set at creation only, false on vaults created before
+    pub protocol_governed: bool,
+    /// Entry fee withheld from each stake (in basis points). Shares are minted for the rest
+    pub deposit_fee_bps: u64,
+    /// The deposit fee is minted as owner fee shares instead of staying in the vault for the
+    /// existing stakers
+    pub deposit_fee_to_owner: bool,
+    /// Active shares minted to nobody by the first stake into an empty vault (`DEAD_SHARES`,
+    /// rebased with the rest). No depositor holds them and nothing redeems them
+    pub dead_shares: u64,
+    /// Last stake, unstake request, executed unstake or reward. An inactive vault whose
+    /// shares are all pending may be restarted, see `MAX_INACTIVE_PERIOD`
+    pub last_activity: i64,
 }
 
 impl Vault {
@@ -71,7 +185,7 @@ impl Vault {
         16 + // rewards_per_share
         8 + // last_rewards_update
         8 + // unstake_lockup_period
-        8 + // management_fee
+        8 + // platform_share_bps
         8 + // min_stake_amount
         8 + // max_total_assets
         1 + // is_paused
@@ -82,7 +196,51 @@ impl Vault {
         8 + // pending_unstake_shares
         8 + // reserved_assets
         1 + // bump
-        16; // _reserved
+        8 + // decommission_started_at
+        8 + // decommission_notice_ends_at
+        4 + // precision_floor_events
+        1 + // version
+        8 + // instant_unstake_fee_bps
+        32 + // pending_owner
+        8 + // stake_cooldown_seconds
+        1 + // withdrawals_paused
+        1 + // permissioned
+        8 + // max_stake_per_depositor
+        4 + // paused_operations
+        32 + // share_mint
+        4 + // program_reward_sources
+        8 + // reward_vesting_period
+        8 + // unvested_rewards
+        8 + // vesting_end
+        8 + // last_vesting_update
+        8 + // performance_fee_bps
+        16 + // high_water_mark
+        8 + // withdrawal_fee_bps
+        8 + // unstake_request_expiry
+        32 + // reward_authority
+        1 + // permissionless_rewards
+        1 + // pause_on_shortfall
+        8 + // last_reconcile_slot
+        8 + // config_timelock_seconds
+        8 + // last_fee_change
+        8 + // min_unstake_amount
+        4 + // depositor_count
+        4 + // active_depositor_count
+        8 + // checkpoint_interval
+        16 + // cumulative_return
+        16 + // lifetime_rewards_per_active_share
+        32 + // secondary_reward_mint
+        32 + // secondary_reward_token_account
+        16 + // secondary_rewards_per_share
+        8 + // total_secondary_rewards
+        8 + // management_fee_bps
+        32 + // pending_platform_account
+        1 + // platform_cosign_required
+        1 + // protocol_governed
+        8 + // deposit_fee_bps
+        1 + // deposit_fee_to_owner
+        8 + // dead_shares
+        8; // last_activity
 
     pub fn initialize(
         &mut self,
@@ -109,7 +267,7 @@ impl Vault {
         self.unstake_lockup_period = params
             .unstake_lockup_period
             .unwrap_or(DEFAULT_UNSTAKE_LOCKUP);
-        self.management_fee = params.management_fee.unwrap_or(DEFAULT_MANAGEMENT_FEE);
+        self.platform_share_bps = params.platform_share_bps.unwrap_or(DEFAULT_PLATFORM_SHARE_BPS);
         self.min_stake_amount = params.min_stake_amount.unwrap_or(0);
         self.max_total_assets = params.max_total_assets.unwrap_or(u64::MAX);
         self.is_paused = false;
@@ -120,6 +278,49 @@ impl Vault {
         self.pending_unstake_shares = 0;
         self.reserved_assets = 0;
         self.bump = bump;
+        self.decommission_started_at = 0;
+        self.decommission_notice_ends_at = 0;
+        self.precision_floor_events = 0;
+        self.version = VAULT_VERSION;
+        self.instant_unstake_fee_bps = 0;
+        self.pending_owner = Pubkey::default();
+        self.stake_cooldown_seconds = DEFAULT_STAKE_COOLDOWN_SECONDS;
+        self.withdrawals_paused = false;
+        self.permissioned = false;
+        self.max_stake_per_depositor = 0;
+        self.paused_operations = 0;
+        self.reward_vesting_period = 0;
+        self.unvested_rewards = 0;
+        self.vesting_end = 0;
+        self.last_vesting_update = 0;
+        self.performance_fee_bps = 0;
+        self.high_water_mark = SafeCast::<u128>::safe_cast(&PRECISION)?;
+        self.withdrawal_fee_bps = 0;
+        self.unstake_request_expiry = 0;
+        self.reward_authority = owner;
+        self.permissionless_rewards = false;
+        self.pause_on_shortfall = false;
+        self.last_reconcile_slot = 0;
+        self.config_timelock_seconds = 0;
+        self.last_fee_change = 0;
+        self.min_unstake_amount = 0;
+        self.depositor_count = 0;
+        self.active_depositor_count = 0;
+        self.checkpoint_interval = DEFAULT_CHECKPOINT_INTERVAL;
+        self.cumulative_return = SHARE_PRECISION;
+        self.lifetime_rewards_per_active_share = 0;
+        self.secondary_reward_mint = Pubkey::default();
+        self.secondary_reward_token_account = Pubkey::default();
+        self.secondary_rewards_per_share = 0;
+        self.total_secondary_rewards = 0;
+        self.management_fee_bps = params.management_fee_bps.unwrap_or(0);
+        self.pending_platform_account = Pubkey::default();
+        self.platform_cosign_required = false;
+        self.protocol_governed = params.protocol_governed;
+        self.deposit_fee_bps = 0;
+        self.deposit_fee_to_owner = false;
+        self.dead_shares = 0;
+        self.last_activity = get_current_timestamp();
 
         // Validate configuration
         if self.unstake_lockup_period < MIN_UNSTAKE_LOCKUP_MINUTES * ONE_MINUTE {
@@ -128,7 +329,10 @@ impl Vault {
         if self.unstake_lockup_period > MAX_UNSTAKE_LOCKUP_DAYS * ONE_DAY {
             return Err(VaultError::InvalidVaultConfig);
         }
-        if self.management_fee > MAX_MANAGEMENT_FEE {
+        if self.platform_share_bps > MAX_PLATFORM_SHARE_BPS {
+            return Err(VaultError::InvalidVaultConfig);
+        }
+        if self.management_fee_bps > MAX_MANAGEMENT_FEE_BPS {
             return Err(VaultError::InvalidVaultConfig);
         }
         
@@ -140,69 +344,98 @@ impl Vault {
         Ok(())
     }
 
-    pub fn stake(&mut self, amount: u64) -> VaultResult<u64> {
-        if self.is_paused {
-            return Err(VaultError::VaultPaused);
+    /// Stake `amount` assets. `depositor_value` is the depositor's position before this stake;
+    /// the minimum is met by either the stake itself or the resulting position, so depositors
+    /// already above a raised `min_stake_amount` can still top up in small steps. Limits apply
+    /// to `amount` before the deposit fee, the returned shares are worth `amount` less the fee.
+    /// The first stake into an empty vault must exceed `DEAD_SHARES`
+    pub fn stake(&mut self, amount: u64, depositor_value: u64) -> VaultResult<u64> {
+        self.check_not_paused(PAUSE_STAKE)?;
+
+        if self.is_decommissioning() {
+            return Err(VaultError::VaultDecommissioning);
         }
 
-        if amount < self.min_stake_amount {
+        if amount < self.min_stake_for(depositor_value) {
             return Err(VaultError::MinimumStakeAmountNotMet);
         }
 
-        if self.total_assets.safe_add(amount)? > self.max_total_assets {
+        if amount > self.remaining_capacity() {
             return Err(VaultError::VaultIsFull);
         }
 
+        if amount > self.depositor_remaining_capacity(depositor_value) {
+            return Err(VaultError::DepositorCapExceeded);
+        }
+
         // Apply rebase if needed before calculating shares
         self.apply_rebase()?;
+        let current_time = get_current_timestamp();
 
         // CRITICAL FIX: Calculate shares based on active share value, not total
         // This ensures new stakers get fair share allocation without diluting existing users
-        let shares = if self.get_active_shares()? == 0 {
+        let gross_shares = if self.get_active_shares()? == 0 {
             // CRITICAL BOOTSTRAP LOGIC REDESIGN
             // When no active shares exist, we must handle this very carefully
             
             if self.total_shares == 0 {
-                // TRUE BOOTSTRAP: First user ever, 1:1 ratio
+                // TRUE BOOTSTRAP: First user ever, 1:1 ratio less the dead shares below
                 amount
             } else {
                 // FALSE BOOTSTRAP: All shares are pending unstake
                 // SECURITY FIX: Allow limited new stakes to prevent permanent DoS
                 // But protect existing pending shareholders from dilution
                 
-                // Check if this is a potential DoS attack (vault has been inactive too long)
-                let current_time = crate::utils::get_current_timestamp();
-                let vault_inactive_time = current_time - self.last_rewards_update;
-                const MAX_INACTIVE_PERIOD: i64 = 7 * 24 * 3600; // 7 days
-                
-                if vault_inactive_time > MAX_INACTIVE_PERIOD {
+                // Check if this is a potential DoS attack (vault has been inactive too long).
+                // Vaults from before `last_activity` only have their last reward to go by
+                let last_activity = self.last_activity.max(self.last_rewards_update);
+                if current_time.safe_sub(last_activity)? > MAX_INACTIVE_PERIOD {
                     // Vault has been inactive too long, allow emergency restart
                     // Use conservative 1:1 ratio for new entrants
                     amount
                 } else {
                     // Calculate shares based on pending shares value to prevent dilution
                     // Use the last known share value from when shares became pending
-                    let pending_share_value = SafeCast::<u128>::safe_cast(&self.total_assets)?
-                        .safe_mul(SafeCast::<u128>::safe_cast(&PRECISION)?)?
-                        .safe_div(SafeCast::<u128>::safe_cast(&self.total_shares)?)?;
-                    
-                    SafeCast::<u128>::safe_cast(&amount)?
-                        .safe_mul(SafeCast::<u128>::safe_cast(&PRECISION)?)?
-                        .safe_div(pending_share_value)?
-                        .safe_cast()?
+                    vault_math::mul_div(amount, self.total_shares, self.total_assets, Rounding::Down)?
                 }
             }
         } else {
-            // Normal case: Calculate shares based on active share value
-            let active_share_value = self.get_active_share_value()?;
-            SafeCast::<u128>::safe_cast(&amount)?
-                .safe_mul(SafeCast::<u128>::safe_cast(&PRECISION)?)?
-                .safe_div(active_share_value)?
-                .safe_cast()?
+            // Normal case: priced at the exact active share value, rounded down. The share
+            // value is still checked so a vault whose shares price at zero refuses stakes
+            self.get_active_share_value()?;
+            vault_math::mul_div(amount, self.get_active_shares()?, self.get_available_assets()?, Rounding::Down)?
         };
 
-        self.total_shares = self.total_shares.safe_add(shares)?;
+        // The first stake into an empty vault leaves DEAD_SHARES with nobody, so the price of
+        // a share cannot be inflated by donating to a vault holding a single share
+        let dead_shares = if self.total_shares == 0 { DEAD_SHARES } else { 0 };
+        if dead_shares > 0 && gross_shares <= dead_shares {
+            return Err(VaultError::MinimumStakeAmountNotMet);
+        }
+        let gross_shares = gross_shares.safe_sub(dead_shares)?;
+
+        // The deposit fee is the share of `amount` the depositor gets no shares for, rounded
+        // in the depositor's favour
+        let fee_shares: u64 = SafeCast::<u128>::safe_cast(&gross_shares)?
+            .safe_mul(SafeCast::<u128>::safe_cast(&self.deposit_fee(amount)?)?)?
+            .safe_div(SafeCast::<u128>::safe_cast(&amount)?)?
+            .safe_cast()?;
+        let shares = gross_shares.safe_sub(fee_shares)?;
+        if shares == 0 {
+            return Err(VaultError::ZeroShares);
+        }
+
+        self.total_shares = self.total_shares.safe_add(shares)?.safe_add(dead_shares)?;
         self.total_assets = self.total_assets.safe_add(amount)?;
+        self.dead_shares = self.dead_shares.safe_add(dead_shares)?;
+
+        // Minted like performance fee shares. Left unminted, the fee raises the share value
+        if self.deposit_fee_to_owner && self.version != 0 && fee_shares > 0 {
+            self.total_shares = self.total_shares.safe_add(fee_shares)?;
+            self.owner_shares = self.owner_shares.safe_add(fee_shares)?;
+        }
+
+        self.last_activity = current_time;
 
         // INVARIANT CHECK: Verify state consistency after stake
         self.verify_invariants()?;
@@ -223,12 +456,9 @@ impl Vault {
         self.apply_rebase()?;
 
         // CRITICAL FIX: Calculate assets based on active share value, not total
-        // This ensures users get the correct current value of their shares
-        let active_share_value = self.get_active_share_value()?;
-        let assets = SafeCast::<u128>::safe_cast(&shares)?
-            .safe_mul(active_share_value)?
-            .safe_div(SafeCast::<u128>::safe_cast(&PRECISION)?)?
-            .safe_cast()?;
+        // This ensures users get the correct current value of their shares, rounded down
+        self.get_active_share_value()?;
+        let assets = vault_math::mul_div(shares, self.get_available_assets()?, self.get_active_shares()?, Rounding::Down)?;
 
         self.total_shares = self.total_shares.safe_sub(shares)?;
         self.total_assets = self.total_assets.safe_sub(assets)?;
@@ -240,11 +470,72 @@ impl Vault {
     }
 
     pub fn add_rewards(&mut self, amount: u64) -> VaultResult<()> {
+        self.check_not_paused(PAUSE_ADD_REWARDS)?;
+
         // Apply rebase before updating rewards
         self.apply_rebase()?;
 
+        let current_time = get_current_timestamp();
+        self.accrue_rewards(current_time)?;
+
+        if self.reward_vesting_period == 0 {
+            self.release_rewards(amount)?;
+        } else {
+            // Rewards still vesting are spread over the new period together with this one,
+            // so a stream of small rewards cannot release an earlier large one faster
+            self.unvested_rewards = self.unvested_rewards.safe_add(amount)?;
+            self.vesting_end = current_time.safe_add(self.reward_vesting_period)?;
+        }
+
+        self.last_rewards_update = current_time;
+        self.last_activity = current_time;
+
+        // INVARIANT CHECK: Verify state consistency after adding rewards
+        self.verify_invariants()?;
+
+        Ok(())
+    }
+
+    /// Unvested rewards released by `current_time`: the remaining bucket vests linearly from
+    /// the last release until `vesting_end`
+    pub fn vested_rewards(&self, current_time: i64) -> VaultResult<u64> {
+        if self.unvested_rewards == 0 || current_time <= self.last_vesting_update {
+            return Ok(0);
+        }
+        if current_time >= self.vesting_end {
+            return Ok(self.unvested_rewards);
+        }
+
+        let elapsed = current_time.safe_sub(self.last_vesting_update)?;
+        let remaining = self.vesting_end.safe_sub(self.last_vesting_update)?;
+        SafeCast::<u128>::safe_cast(&self.unvested_rewards)?
+            .safe_mul(SafeCast::<u128>::safe_cast(&elapsed)?)?
+            .safe_div(SafeCast::<u128>::safe_cast(&remaining)?)?
+            .safe_cast()
+    }
+
+    /// Release the rewards vested by `current_time` into `total_assets`, raising the active
+    /// share value. Returns the amount released
+    pub fn accrue_rewards(&mut self, current_time: i64) -> VaultResult<u64> {
+        let vested = self.vested_rewards(current_time)?;
+        self.last_vesting_update = self.last_vesting_update.max(current_time);
+        if vested == 0 {
+            return Ok(0);
+        }
+
+        self.unvested_rewards = self.unvested_rewards.safe_sub(vested)?;
+        self.release_rewards(vested)?;
+
+        self.verify_invariants()?;
+
+        Ok(vested)
+    }
+
+    /// Add `amount` of rewards to the assets of the active shares
+    fn release_rewards(&mut self, amount: u64) -> VaultResult<()> {
         // Get active shares using helper function for consistency
         let active_shares = self.get_active_shares()?;
+        let available_before = self.get_available_assets()?;
 
         // Add rewards to total_assets - this increases available assets
         // Reserved assets remain unchanged, ensuring strict separation
@@ -261,13 +552,47 @@ impl Vault {
                 active_shares,
                 self.rewards_per_share,
             )?;
+
+            // Both accumulators saturate, a reward is never refused for them
+            self.cumulative_return = vault_math::compound_growth(
+                self.cumulative_return,
+                available_before,
+                available_before.safe_add(amount)?,
+            );
+            self.lifetime_rewards_per_active_share = self.lifetime_rewards_per_active_share.saturating_add(
+                vault_math::rewards_per_base_share(amount, active_shares, self.shares_base),
+            );
         }
         // If no active shares, rewards accumulate in vault waiting for new participants
 
-        self.last_rewards_update = get_current_timestamp();
+        Ok(())
+    }
 
-        // INVARIANT CHECK: Verify state consistency after adding rewards
-        self.verify_invariants()?;
+    /// Whether the vault pays a second reward token
+    pub fn has_secondary_rewards(&self) -> bool {
+        self.secondary_reward_mint != Pubkey::default()
+    }
+
+    /// Credit `amount` of the secondary reward token to the shares active right now. Shares
+    /// staked later or pending unstake earn none of it, and nothing is compounded
+    pub fn add_secondary_rewards(&mut self, amount: u64) -> VaultResult<()> {
+        if !self.has_secondary_rewards() {
+            return Err(VaultError::SecondaryRewardsNotEnabled);
+        }
+        if amount == 0 {
+            return Err(VaultError::InvalidAmount);
+        }
+
+        // Unlike the primary rewards, nobody could ever claim a reward without active shares
+        let active_shares = self.get_active_shares()?;
+        if active_shares == 0 {
+            return Err(VaultError::NoActiveShares);
+        }
+
+        self.secondary_rewards_per_share = self.secondary_rewards_per_share.safe_add(
+            vault_math::rewards_per_base_share(amount, active_shares, self.shares_base),
+        )?;
+        self.total_secondary_rewards = self.total_secondary_rewards.safe_add(amount)?;
 
         Ok(())
     }
@@ -282,170 +607,3092 @@ impl Vault {
             self.unstake_lockup_period = unstake_lockup_period;
         }
 
-        if let Some(management_fee) = params.management_fee {
-            if management_fee > MAX_MANAGEMENT_FEE {
+        // Moves in steps of at most MAX_PLATFORM_SHARE_CHANGE_BPS, one step per interval, so
+        // depositors see a fee change coming before the next reward
+        if let Some(platform_share_bps) = params.platform_share_bps {
+            if platform_share_bps > MAX_PLATFORM_SHARE_BPS {
                 return Err(VaultError::InvalidVaultConfig);
             }
-            self.management_fee = management_fee;
+            if platform_share_bps != self.platform_share_bps {
+                let current_time = get_current_timestamp();
+                if platform_share_bps.abs_diff(self.platform_share_bps) > MAX_PLATFORM_SHARE_CHANGE_BPS
+                    || (self.last_fee_change != 0
+                        && current_time < self.last_fee_change.safe_add(MIN_PLATFORM_SHARE_CHANGE_INTERVAL)?)
+                {
+                    return Err(VaultError::InvalidVaultConfig);
+                }
+                self.platform_share_bps = platform_share_bps;
+                self.last_fee_change = current_time;
+            }
         }
 
         if let Some(min_stake_amount) = params.min_stake_amount {
             self.min_stake_amount = min_stake_amount;
         }
 
+        // Never above the stake minimum, checked after both may have changed
+        if let Some(min_unstake_amount) = params.min_unstake_amount {
+            self.min_unstake_amount = min_unstake_amount;
+        }
+        if self.min_unstake_amount > self.min_stake_amount {
+            return Err(VaultError::InvalidVaultConfig);
+        }
+
         if let Some(max_total_assets) = params.max_total_assets {
             self.max_total_assets = max_total_assets;
         }
 
-        if let Some(is_paused) = params.is_paused {
-            self.is_paused = is_paused;
+        self.paused_operations = self.update_paused_operations(&params)?;
+        self.is_paused = false;
+        self.withdrawals_paused = false;
+
+        // Turning the allowlist off or on leaves existing markers in place
+        if let Some(permissioned) = params.permissioned {
+            self.permissioned = permissioned;
+        }
+
+        // Lowering the cap below existing positions only blocks their further stakes
+        if let Some(max_stake_per_depositor) = params.max_stake_per_depositor {
+            self.max_stake_per_depositor = max_stake_per_depositor;
+        }
+
+        // The platform must accept a new account, see `propose_platform_account`
+        if params.platform_account.is_some() {
+            return Err(VaultError::PlatformAccountNeedsAcceptance);
+        }
+
+        // Rewards always need some source, switch to `permissionless_rewards` to accept any
+        if let Some(reward_authority) = params.reward_authority {
+            if reward_authority == Pubkey::default() {
+                return Err(VaultError::InvalidVaultConfig);
+            }
+            self.reward_authority = reward_authority;
+        }
+
+        if let Some(permissionless_rewards) = params.permissionless_rewards {
+            self.permissionless_rewards = permissionless_rewards;
+        }
+
+        // Only arms later reconciles, an existing shortfall pause stays until unpaused
+        if let Some(pause_on_shortfall) = params.pause_on_shortfall {
+            self.pause_on_shortfall = pause_on_shortfall;
+        }
+
+        // `check_instant_config_update` keeps it out of `update_vault_config`
+        if let Some(config_timelock_seconds) = params.config_timelock_seconds {
+            if !(0..=MAX_CONFIG_TIMELOCK_SECONDS).contains(&config_timelock_seconds) {
+                return Err(VaultError::InvalidVaultConfig);
+            }
+            self.config_timelock_seconds = config_timelock_seconds;
+        }
+
+        // The next checkpoint is spaced from the latest one at the new interval
+        if let Some(checkpoint_interval) = params.checkpoint_interval {
+            if !(MIN_CHECKPOINT_INTERVAL..=MAX_CHECKPOINT_INTERVAL).contains(&checkpoint_interval) {
+                return Err(VaultError::InvalidVaultConfig);
+            }
+            self.checkpoint_interval = checkpoint_interval;
+        }
+
+        if let Some(management_fee_bps) = params.management_fee_bps {
+            if management_fee_bps > MAX_MANAGEMENT_FEE_BPS {
+                return Err(VaultError::InvalidVaultConfig);
+            }
+            self.management_fee_bps = management_fee_bps;
+        }
+
+        if let Some(instant_unstake_fee_bps) = params.instant_unstake_fee_bps {
+            if instant_unstake_fee_bps > MAX_INSTANT_UNSTAKE_FEE {
+                return Err(VaultError::InvalidVaultConfig);
+            }
+            self.instant_unstake_fee_bps = instant_unstake_fee_bps;
+        }
+
+        if let Some(stake_cooldown_seconds) = params.stake_cooldown_seconds {
+            if !(1..=MAX_STAKE_COOLDOWN_SECONDS).contains(&stake_cooldown_seconds) {
+                return Err(VaultError::InvalidVaultConfig);
+            }
+            self.stake_cooldown_seconds = stake_cooldown_seconds;
+        }
+
+        // Gains up to now are not charged at the new rate
+        if let Some(performance_fee_bps) = params.performance_fee_bps {
+            // Fee shares are minted without share tokens
+            if performance_fee_bps > MAX_PERFORMANCE_FEE_BPS
+                || (performance_fee_bps > 0 && self.is_tokenized())
+            {
+                return Err(VaultError::InvalidVaultConfig);
+            }
+            self.performance_fee_bps = performance_fee_bps;
+            self.high_water_mark = self.high_water_mark.max(self.active_share_value_or_zero()?);
+        }
+
+        if let Some(deposit_fee_bps) = params.deposit_fee_bps {
+            if deposit_fee_bps > MAX_DEPOSIT_FEE_BPS {
+                return Err(VaultError::InvalidVaultConfig);
+            }
+            self.deposit_fee_bps = deposit_fee_bps;
+        }
+
+        // Fee shares are minted without share tokens
+        if let Some(deposit_fee_to_owner) = params.deposit_fee_to_owner {
+            if deposit_fee_to_owner && self.is_tokenized() {
+                return Err(VaultError::InvalidVaultConfig);
+            }
+            self.deposit_fee_to_owner = deposit_fee_to_owner;
+        }
+
+        // Requests already made are charged at the rate in force when they execute
+        if let Some(withdrawal_fee_bps) = params.withdrawal_fee_bps {
+            if withdrawal_fee_bps > MAX_WITHDRAWAL_FEE_BPS {
+                return Err(VaultError::InvalidVaultConfig);
+            }
+            self.withdrawal_fee_bps = withdrawal_fee_bps;
+        }
+
+        // Also applies to requests already made, measured from their maturity
+        if let Some(unstake_request_expiry) = params.unstake_request_expiry {
+            if unstake_request_expiry != 0
+                && !(MIN_UNSTAKE_REQUEST_EXPIRY..=MAX_UNSTAKE_REQUEST_EXPIRY).contains(&unstake_request_expiry)
+            {
+                return Err(VaultError::InvalidVaultConfig);
+            }
+            self.unstake_request_expiry = unstake_request_expiry;
         }
 
-        if let Some(platform_account) = params.platform_account {
-            self.platform_account = platform_account;
+        // Applies to later rewards, whatever is vesting keeps its `vesting_end`
+        if let Some(reward_vesting_period) = params.reward_vesting_period {
+            if !(0..=MAX_REWARD_VESTING_PERIOD).contains(&reward_vesting_period) {
+                return Err(VaultError::InvalidVaultConfig);
+            }
+            self.reward_vesting_period = reward_vesting_period;
         }
 
         Ok(())
     }
 
-    pub fn get_signer_seeds(&self) -> [&[u8]; 3] {
-        [b"vault", self.name.as_ref(), std::slice::from_ref(&self.bump)]
+    /// Permissioned vaults only admit `authority` with its allowlist marker. Exits never
+    /// check this, so a wallet removed from the allowlist can still leave
+    pub fn check_depositor_allowed(
+        &self,
+        vault: Pubkey,
+        authority: Pubkey,
+        allowed_depositor: Option<&AllowedDepositor>,
+    ) -> VaultResult<()> {
+        if !self.permissioned {
+            return Ok(());
+        }
+        match allowed_depositor {
+            Some(marker) if marker.vault == vault && marker.authority == authority => Ok(()),
+            _ => Err(VaultError::DepositorNotAllowed),
+        }
     }
 
-    /// Get available assets (total_assets - reserved_assets)
-    /// This represents assets that actively participate in rewards
-    pub fn get_available_assets(&self) -> VaultResult<u64> {
-        self.total_assets.safe_sub(self.reserved_assets)
+    /// Rewards must be signed by `reward_authority` or a registered program PDA, unless the
+    /// vault accepts permissionless rewards. Vaults from before `reward_authority` keep
+    /// accepting any signing source until the owner sets one or registers a program source
+    pub fn check_reward_source(
+        &self,
+        vault: Pubkey,
+        authority: Pubkey,
+        authority_signed: bool,
+        reward_source: Option<&ProgramRewardSource>,
+    ) -> VaultResult<()> {
+        if self.permissionless_rewards {
+            return Ok(());
+        }
+        if authority_signed && self.reward_authority != Pubkey::default() && authority == self.reward_authority {
+            return Ok(());
+        }
+        if let Some(source) = reward_source {
+            if source.vault == vault && source.authority == authority && authority_signed {
+                return Ok(());
+            }
+        }
+
+        if self.reward_authority != Pubkey::default() {
+            Err(VaultError::UnauthorizedRewardSource)
+        } else if self.program_reward_sources > 0 {
+            Err(VaultError::RewardSourceNotRegistered)
+        } else {
+            Ok(())
+        }
     }
 
-    /// Get active shares (total_shares - pending_unstake_shares)  
-    /// This represents shares that actively participate in rewards
-    pub fn get_active_shares(&self) -> VaultResult<u64> {
-        self.total_shares.safe_sub(self.pending_unstake_shares)
+    /// Configuration fields covered by `config_digest`
+    pub fn config_snapshot(&self) -> VaultConfigSnapshot {
+        VaultConfigSnapshot {
+            owner: self.owner,
+            platform_account: self.platform_account,
+            token_mint: self.token_mint,
+            unstake_lockup_period: self.unstake_lockup_period,
+            platform_share_bps: self.platform_share_bps,
+            min_stake_amount: self.min_stake_amount,
+            max_total_assets: self.max_total_assets,
+            paused_operations: self.paused_mask(),
+            instant_unstake_fee_bps: self.instant_unstake_fee_bps,
+            stake_cooldown_seconds: self.stake_cooldown_seconds,
+            permissioned: self.permissioned,
+            max_stake_per_depositor: self.max_stake_per_depositor,
+            reward_vesting_period: self.reward_vesting_period,
+            performance_fee_bps: self.performance_fee_bps,
+            withdrawal_fee_bps: self.withdrawal_fee_bps,
+            unstake_request_expiry: self.unstake_request_expiry,
+            reward_authority: self.reward_authority,
+            permissionless_rewards: self.permissionless_rewards,
+            pause_on_shortfall: self.pause_on_shortfall,
+            config_timelock_seconds: self.config_timelock_seconds,
+            min_unstake_amount: self.min_unstake_amount,
+            checkpoint_interval: self.checkpoint_interval,
+            management_fee_bps: self.management_fee_bps,
+            deposit_fee_bps: self.deposit_fee_bps,
+            deposit_fee_to_owner: self.deposit_fee_to_owner,
+        }
     }
 
-    /// Get current share value for active participants
-    /// share_value = available_assets / active_shares
-    pub fn get_active_share_value(&self) -> VaultResult<u128> {
-        let available_assets = self.get_available_assets()?;
-        let active_shares = self.get_active_shares()?;
-        
-        if active_shares == 0 {
-            // EDGE CASE: When all shares are pending, return 1:1 ratio for new stakers
-            // This is reasonable because there are no active participants to dilute
-            return Ok(SafeCast::<u128>::safe_cast(&PRECISION)?);
+    /// SHA-256 over the Borsh serialization of `config_snapshot`
+    /// Stable across runs so an approved proposal can reference the exact resulting value
+    pub fn config_digest(&self) -> VaultResult<[u8; 32]> {
+        let mut bytes = Vec::with_capacity(VaultConfigSnapshot::LEN);
+        self.config_snapshot()
+            .serialize(&mut bytes)
+            .map_err(|_| VaultError::InvalidVaultConfig)?;
+        Ok(anchor_lang::solana_program::hash::hash(&bytes).to_bytes())
+    }
+
+    /// Paused operations, including the legacy booleans of a vault not updated since
+    pub fn paused_mask(&self) -> u32 {
+        self.paused_operations | legacy_paused_operations(self.is_paused, self.withdrawals_paused)
+    }
+
+    /// Whether any of `operations` is paused
+    pub fn is_operation_paused(&self, operations: u32) -> bool {
+        self.paused_mask() & operations != 0
+    }
+
+    /// Fails when any of `operations` is paused, with the error the pause used to raise
+    /// for that family
+    pub fn check_not_paused(&self, operations: u32) -> VaultResult<()> {
+        let paused = self.paused_mask() & operations;
+        if paused == 0 {
+            Ok(())
+        } else if paused & (PAUSE_STAKE | PAUSE_ADD_REWARDS) != 0 {
+            Err(VaultError::VaultPaused)
+        } else if paused & PAUSE_WITHDRAWALS != 0 {
+            Err(VaultError::WithdrawalsPaused)
+        } else {
+            Err(VaultError::OperationPaused)
         }
+    }
 
-        SafeCast::<u128>::safe_cast(&available_assets)?
-            .safe_mul(SafeCast::<u128>::safe_cast(&PRECISION)?)?
-            .safe_div(SafeCast::<u128>::safe_cast(&active_shares)?)
+    /// Mask after applying `params`: `paused_operations` replaces the whole mask, the
+    /// deposits and withdrawals flags set or clear their legacy groups. Mixing the two is
+    /// rejected, as is `is_paused` disagreeing with `deposits_paused`
+    fn update_paused_operations(&self, params: &UpdateVaultConfigParams) -> VaultResult<u32> {
+        // `is_paused` is the original name of the deposits flag, both may be given if they agree
+        let deposits_paused = match (params.is_paused, params.deposits_paused) {
+            (Some(is_paused), Some(deposits_paused)) if is_paused != deposits_paused => {
+                return Err(VaultError::InvalidVaultConfig);
+            }
+            (is_paused, deposits_paused) => deposits_paused.or(is_paused),
+        };
+
+        let mut mask = self.paused_mask();
+        if let Some(paused_operations) = params.paused_operations {
+            if paused_operations & !PAUSE_ALL != 0
+                || deposits_paused.is_some()
+                || params.withdrawals_paused.is_some()
+            {
+                return Err(VaultError::InvalidVaultConfig);
+            }
+            mask = paused_operations;
+        }
+        if let Some(deposits_paused) = deposits_paused {
+            mask = set_bits(mask, PAUSE_LEGACY_DEPOSITS, deposits_paused);
+        }
+        if let Some(withdrawals_paused) = params.withdrawals_paused {
+            mask = set_bits(mask, PAUSE_WITHDRAWALS, withdrawals_paused);
+        }
+        Ok(mask)
     }
 
-    /// CRITICAL: Verify vault state invariants to prevent accounting errors
-    /// This should be called after any state-modifying operation
-    pub fn verify_invariants(&self) -> VaultResult<()> {
-        // Invariant 1: total_assets = available_assets + reserved_assets
-        let available_assets = self.get_available_assets()?;
-        let expected_total = available_assets.safe_add(self.reserved_assets)?;
-        if self.total_assets != expected_total {
-            msg!("INVARIANT VIOLATION: total_assets ({}) != available_assets ({}) + reserved_assets ({})", 
-                 self.total_assets, available_assets, self.reserved_assets);
-            return Err(VaultError::InvariantViolation);
+    /// Whether `update_vault_config` may apply `params` without a proposal: pauses always can,
+    /// anything else only while the vault has no timelock. The timelock itself never can
+    pub fn check_instant_config_update(&self, params: &UpdateVaultConfigParams) -> VaultResult<()> {
+        if params.config_timelock_seconds.is_some() {
+            return Err(VaultError::ConfigUpdateTimelocked);
         }
+        if self.config_timelock_seconds > 0 && !params.is_pause_only() {
+            return Err(VaultError::ConfigUpdateTimelocked);
+        }
+        Ok(())
+    }
 
-        // Invariant 2: total_shares = active_shares + pending_shares
-        let active_shares = self.get_active_shares()?;
-        let expected_total_shares = active_shares.safe_add(self.pending_unstake_shares)?;
-        if self.total_shares != expected_total_shares {
-            msg!("INVARIANT VIOLATION: total_shares ({}) != active_shares ({}) + pending_shares ({})", 
-                 self.total_shares, active_shares, self.pending_unstake_shares);
-            return Err(VaultError::InvariantViolation);
+    /// Platform share taken from the next add_rewards, 0 while fee accrual is paused
+    pub fn accrued_platform_share_bps(&self) -> u64 {
+        if self.is_operation_paused(PAUSE_FEE_ACCRUAL) {
+            0
+        } else {
+            self.platform_share_bps
         }
+    }
 
-        // Invariant 3: reserved_assets should never exceed total_assets
-        if self.reserved_assets > self.total_assets {
-            msg!("INVARIANT VIOLATION: reserved_assets ({}) > total_assets ({})", 
-                 self.reserved_assets, self.total_assets);
-            return Err(VaultError::InvariantViolation);
+    /// Performance fee charged by the next crystallization, 0 while fee accrual is paused
+    pub fn accrued_performance_fee_bps(&self) -> u64 {
+        if self.is_operation_paused(PAUSE_FEE_ACCRUAL) {
+            0
+        } else {
+            self.performance_fee_bps
         }
+    }
 
-        // Invariant 4: pending_unstake_shares should never exceed total_shares
-        if self.pending_unstake_shares > self.total_shares {
-            msg!("INVARIANT VIOLATION: pending_unstake_shares ({}) > total_shares ({})", 
-                 self.pending_unstake_shares, self.total_shares);
-            return Err(VaultError::InvariantViolation);
+    /// Charge the performance fee on the active share value's gain above `high_water_mark`:
+    /// shares worth the fee are minted into `owner_shares` and the mark moves up to the share
+    /// value after the fee. Pending shares are priced from `reserved_assets`, their frozen
+    /// value is never charged. Returns (fee_shares, fee_amount)
+    pub fn crystallize_performance_fee(&mut self) -> VaultResult<(u64, u64)> {
+        // Legacy owner shares are outside total_shares until migrated, fee shares are not
+        if self.version == 0 {
+            return Ok((0, 0));
         }
 
-        Ok(())
+        let share_value = self.active_share_value_or_zero()?;
+        if share_value == 0 {
+            return Ok((0, 0));
+        }
+        if self.high_water_mark == 0 {
+            self.high_water_mark = share_value;
+            return Ok((0, 0));
+        }
+
+        let (fee_shares, high_water_mark) = vault_math::calculate_performance_fee(
+            self.get_available_assets()?,
+            self.get_active_shares()?,
+            self.high_water_mark,
+            self.accrued_performance_fee_bps(),
+        )?;
+        self.high_water_mark = high_water_mark;
+        if fee_shares == 0 {
+            return Ok((0, 0));
+        }
+
+        self.total_shares = self.total_shares.safe_add(fee_shares)?;
+        self.owner_shares = self.owner_shares.safe_add(fee_shares)?;
+
+        self.verify_invariants()?;
+
+        Ok((fee_shares, self.shares_value(fee_shares)?))
     }
 
-    /// Apply rebase mechanism when shares become too large relative to assets
-    pub fn apply_rebase(&mut self) -> VaultResult<Option<u128>> {
-        if self.total_assets == 0 || self.total_shares <= self.total_assets {
-            return Ok(None);
+    /// Move performance fee shares from `owner_shares` into the synced owner fee depositor.
+    /// Legacy owner shares need `migrate_owner_shares` instead. Returns the shares moved
+    pub fn claim_owner_shares(&mut self, owner_fee_depositor: &mut VaultDepositor) -> VaultResult<u64> {
+        let claimed_shares = self.owner_shares;
+        if claimed_shares == 0 {
+            return Ok(0);
         }
-        
-        // SECURITY: Prevent extreme rebase scenarios
-        let ratio = (SafeCast::<u128>::safe_cast(&self.total_shares)?
-            .safe_div(SafeCast::<u128>::safe_cast(&self.total_assets.max(1))?)?);
-        
-        if ratio > 1_000_000 {  // If shares are >1M times assets, something is very wrong
-            return Err(VaultError::InvariantViolation);
+        if self.version == 0 {
+            return Err(VaultError::OwnerSharesNotMigrated);
+        }
+        if owner_fee_depositor.needs_rebase_sync(self.rebase_version) {
+            return Err(VaultError::RebaseSyncRequired);
         }
 
-        let (expo_diff, rebase_divisor) =
-            vault_math::calculate_rebase_factor(self.total_shares, self.total_assets)?;
+        owner_fee_depositor.settle_secondary_rewards(self.secondary_rewards_per_share)?;
+        owner_fee_depositor.shares = owner_fee_depositor.shares.safe_add(claimed_shares)?;
+        self.owner_shares = 0;
+        Ok(claimed_shares)
+    }
 
-        if expo_diff > 0 {
-            // Apply rebase by dividing shares
-            self.total_shares = (SafeCast::<u128>::safe_cast(&self.total_shares)?
-                .safe_div(rebase_divisor)?)
-            .safe_cast()?;
-            self.shares_base = self.shares_base.safe_add(expo_diff)?;
-            self.rebase_version = self.rebase_version.safe_add(1)?;
+    /// Cooldown between a stake and the depositor's next exit, in seconds.
+    /// Accounts created before the field existed read 0 and get the default
+    pub fn stake_cooldown(&self) -> i64 {
+        if self.stake_cooldown_seconds == 0 {
+            DEFAULT_STAKE_COOLDOWN_SECONDS
+        } else {
+            self.stake_cooldown_seconds
+        }
+    }
 
-            msg!(
-                "Vault rebase applied: expo_diff={}, divisor={}",
-                expo_diff,
-                rebase_divisor
-            );
-            return Ok(Some(rebase_divisor));
+    /// Least spacing between two share value checkpoints, in seconds.
+    /// Accounts created before the field existed read 0 and get the default
+    pub fn effective_checkpoint_interval(&self) -> i64 {
+        if self.checkpoint_interval == 0 {
+            DEFAULT_CHECKPOINT_INTERVAL
+        } else {
+            self.checkpoint_interval
         }
+    }
 
-        Ok(None)
+    /// The share value entry `record_checkpoint` and `add_rewards` write at `timestamp`
+    pub fn share_value_checkpoint(&self, timestamp: i64) -> VaultResult<ShareValueCheckpoint> {
+        Ok(ShareValueCheckpoint {
+            timestamp,
+            active_share_value: self.active_share_value_or_zero()?,
+            total_assets: self.total_assets,
+            total_shares: self.total_shares,
+            shares_base: self.shares_base,
+        })
     }
 
+    /// Whether `begin_decommission` has been called
+    pub fn is_decommissioning(&self) -> bool {
+        self.decommission_started_at != 0
+    }
 
-    /// Get the effective share value considering rebase
-    pub fn get_effective_share_value(&self) -> VaultResult<u128> {
-        if self.total_shares == 0 {
-            return Ok(0);
+    /// Whether depositor shares are mirrored by tokens of `share_mint`
+    pub fn is_tokenized(&self) -> bool {
+        self.share_mint != Pubkey::default()
+    }
+
+    /// Start decommissioning: blocks stakes and rewards, waives the lockup after `notice_period`.
+    /// Rewards still vesting are released at once, nobody can stake to snipe them anymore and
+    /// the last exits must not leave them behind for `finalize_decommission` to sweep
+    pub fn begin_decommission(&mut self, current_time: i64, notice_period: i64) -> VaultResult<()> {
+        if self.is_decommissioning() {
+            return Err(VaultError::VaultDecommissioning);
+        }
+        if !(MIN_DECOMMISSION_NOTICE..=MAX_DECOMMISSION_NOTICE).contains(&notice_period) {
+            return Err(VaultError::InvalidVaultConfig);
         }
 
-        let base_value = (SafeCast::<u128>::safe_cast(&self.total_assets)?)
-            .safe_mul(SafeCast::<u128>::safe_cast(&PRECISION)?)?
-            .safe_div(SafeCast::<u128>::safe_cast(&self.total_shares)?)?;
+        let unvested_rewards = self.unvested_rewards;
+        self.unvested_rewards = 0;
+        self.vesting_end = current_time;
+        self.last_vesting_update = current_time;
+        self.release_rewards(unvested_rewards)?;
 
-        // Adjust for rebase factor
-        let rebase_multiplier = 10u128.pow(self.shares_base);
-        base_value.safe_mul(rebase_multiplier)
+        self.decommission_started_at = current_time;
+        self.decommission_notice_ends_at = current_time.safe_add(notice_period)?;
+        Ok(())
     }
-}
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
-pub struct InitializeVaultParams {
-    pub unstake_lockup_period: Option<i64>,
-    pub management_fee: Option<u64>,
-    pub min_stake_amount: Option<u64>,
-    pub max_total_assets: Option<u64>,
-}
+    /// First step of an ownership transfer; a new proposal replaces a pending one
+    pub fn propose_owner(&mut self, new_owner: Pubkey) -> VaultResult<()> {
+        if new_owner == Pubkey::default() || new_owner == self.owner {
+            return Err(VaultError::InvalidVaultConfig);
+        }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+        self.pending_owner = new_owner;
+        Ok(())
+    }
+
+    /// Hand the vault to the pending owner. Returns the previous owner
+    pub fn accept_ownership(&mut self) -> VaultResult<Pubkey> {
+        if !self.has_pending_owner() {
+            return Err(VaultError::NoPendingOwner);
+        }
+
+        let previous_owner = self.owner;
+        self.owner = self.pending_owner;
+        self.pending_owner = Pubkey::default();
+        Ok(previous_owner)
+    }
+
+    /// Accept the pending transfer and settle everything tied to the previous owner:
+    /// fee shares accrued in the owner fee depositor move into the previous owner's personal
+    /// position, the fee depositor is handed to the new owner empty, and a platform account
+    /// that was the previous owner follows the new one. Unclaimed performance fees are
+    /// settled with them. Both depositors must be synced
+    pub fn hand_off_ownership(
+        &mut self,
+        owner_fee_depositor: &mut VaultDepositor,
+        previous_owner_depositor: &mut VaultDepositor,
+    ) -> VaultResult<OwnershipHandoff> {
+        if self.owner_shares != 0 && self.version == 0 {
+            return Err(VaultError::OwnerSharesNotMigrated);
+        }
+        if owner_fee_depositor.authority != self.owner || previous_owner_depositor.authority != self.owner {
+            return Err(VaultError::Unauthorized);
+        }
+        if owner_fee_depositor.needs_rebase_sync(self.rebase_version)
+            || previous_owner_depositor.needs_rebase_sync(self.rebase_version)
+        {
+            return Err(VaultError::RebaseSyncRequired);
+        }
+
+        self.claim_owner_shares(owner_fee_depositor)?;
+
+        let previous_owner = self.accept_ownership()?;
+
+        // Secondary rewards the fee shares earned follow them to the previous owner
+        owner_fee_depositor.settle_secondary_rewards(self.secondary_rewards_per_share)?;
+        previous_owner_depositor.settle_secondary_rewards(self.secondary_rewards_per_share)?;
+        previous_owner_depositor.secondary_rewards_owed = previous_owner_depositor
+            .secondary_rewards_owed
+            .safe_add(owner_fee_depositor.secondary_rewards_owed)?;
+        owner_fee_depositor.secondary_rewards_owed = 0;
+
+        let settled_fee_shares = owner_fee_depositor.shares;
+        previous_owner_depositor.shares = previous_owner_depositor.shares.safe_add(settled_fee_shares)?;
+        owner_fee_depositor.shares = 0;
+        owner_fee_depositor.authority = self.owner;
+
+        let platform_account_reset = self.platform_account == previous_owner;
+        if platform_account_reset {
+            self.platform_account = self.owner;
+        }
+        if self.reward_authority == previous_owner {
+            self.reward_authority = self.owner;
+        }
+
+        self.verify_invariants()?;
+
+        Ok(OwnershipHandoff {
+            previous_owner,
+            settled_fee_shares,
+            platform_account_reset,
+        })
+    }
+
+    /// Withdraw a pending proposal. Returns the owner that was proposed
+    pub fn cancel_owner_transfer(&mut self) -> VaultResult<Pubkey> {
+        if !self.has_pending_owner() {
+            return Err(VaultError::NoPendingOwner);
+        }
+
+        let cancelled_owner = self.pending_owner;
+        self.pending_owner = Pubkey::default();
+        Ok(cancelled_owner)
+    }
+
+    pub fn has_pending_owner(&self) -> bool {
+        self.pending_owner != Pubkey::default()
+    }
+
+    /// First step of a platform account change; a new proposal replaces a pending one.
+    /// `cosigned` is whether the current platform account signed the proposal
+    pub fn propose_platform_account(&mut self, new_platform_account: Pubkey, cosigned: bool) -> VaultResult<()> {
+        if new_platform_account == Pubkey::default() || new_platform_account == self.platform_account {
+            return Err(VaultError::InvalidVaultConfig);
+        }
+        if self.platform_cosign_required && !cosigned {
+            return Err(VaultError::PlatformCosignRequired);
+        }
+
+        self.pending_platform_account = new_platform_account;
+        Ok(())
+    }
+
+    /// Point rewards at the pending platform account. Returns the previous one
+    pub fn accept_platform_account(&mut self) -> VaultResult<Pubkey> {
+        if !self.has_pending_platform_account() {
+            return Err(VaultError::NoPendingPlatformAccount);
+        }
+
+        let previous_platform_account = self.platform_account;
+        self.platform_account = self.pending_platform_account;
+        self.pending_platform_account = Pubkey::default();
+        Ok(previous_platform_account)
+    }
+
+    /// Withdraw a pending platform account proposal. Returns the account that was proposed
+    pub fn cancel_platform_account_proposal(&mut self) -> VaultResult<Pubkey> {
+        if !self.has_pending_platform_account() {
+            return Err(VaultError::NoPendingPlatformAccount);
+        }
+
+        let cancelled_platform_account = self.pending_platform_account;
+        self.pending_platform_account = Pubkey::default();
+        Ok(cancelled_platform_account)
+    }
+
+    pub fn has_pending_platform_account(&self) -> bool {
+        self.pending_platform_account != Pubkey::default()
+    }
+
+    /// Lower fees of a protocol governed vault and optionally pause its deposits, on behalf of
+    /// the protocol admin. Never raises a fee, never disables instant unstake and never
+    /// touches the unstake flow, so depositors can always leave
+    pub fn override_fees(&mut self, params: &FeeOverrideParams) -> VaultResult<()> {
+        if !self.protocol_governed {
+            return Err(VaultError::NotProtocolGoverned);
+        }
+
+        let lower = |current: u64, new: Option<u64>| -> VaultResult<u64> {
+            match new {
+                Some(new) if new > current => Err(VaultError::FeeOverrideRaisesFee),
+                Some(new) => Ok(new),
+                None => Ok(current),
+            }
+        };
+
+        let platform_share_bps = lower(self.platform_share_bps, params.platform_share_bps)?;
+        if platform_share_bps != self.platform_share_bps {
+            // The owner's next step up waits a full interval, as after any change
+            self.platform_share_bps = platform_share_bps;
+            self.last_fee_change = get_current_timestamp();
+        }
+        self.management_fee_bps = lower(self.management_fee_bps, params.management_fee_bps)?;
+        self.performance_fee_bps = lower(self.performance_fee_bps, params.performance_fee_bps)?;
+        self.withdrawal_fee_bps = lower(self.withdrawal_fee_bps, params.withdrawal_fee_bps)?;
+        self.deposit_fee_bps = lower(self.deposit_fee_bps, params.deposit_fee_bps)?;
+
+        // A fee of 0 turns instant unstake off, which would take an exit away from depositors
+        let instant_unstake_fee_bps = lower(self.instant_unstake_fee_bps, params.instant_unstake_fee_bps)?;
+        if instant_unstake_fee_bps == 0 && self.instant_unstake_fee_bps != 0 {
+            return Err(VaultError::InvalidVaultConfig);
+        }
+        self.instant_unstake_fee_bps = instant_unstake_fee_bps;
+
+        if params.pause_deposits {
+            self.paused_operations = self.paused_mask() | PAUSE_LEGACY_DEPOSITS;
+        }
+
+        Ok(())
+    }
+
+    /// Lockup that applies to unstake requests at `current_time`
+    /// Once the decommission notice has ended everyone can exit immediately
+    pub fn effective_unstake_lockup(&self, current_time: i64) -> i64 {
+        if self.is_decommissioning() && current_time >= self.decommission_notice_ends_at {
+            0
+        } else {
+            self.unstake_lockup_period
+        }
+    }
+
+    /// Whether a request made at `request_time` has stayed uncollected for
+    /// `unstake_request_expiry` after it matured. Requests never expire while decommissioning,
+    /// everyone is on their way out then
+    pub fn is_unstake_request_expired(&self, request_time: i64, current_time: i64) -> bool {
+        self.unstake_request_expiry > 0
+            && !self.is_decommissioning()
+            && current_time
+                >= request_time
+                    .saturating_add(self.unstake_lockup_period)
+                    .saturating_add(self.unstake_request_expiry)
+    }
+
+    /// Whether unresponsive depositors may be paid out by the forced distribution crank
+    pub fn can_force_distribute(&self, current_time: i64) -> bool {
+        self.is_decommissioning()
+            && current_time >= self.decommission_notice_ends_at.saturating_add(DECOMMISSION_FORCE_TIMEOUT)
+    }
+
+    /// Amount `force_exit` pays for a position, without changing any state
+    pub fn force_exit_amount(&self, active_shares: u64, frozen_amount: u64) -> VaultResult<u64> {
+        let active_amount: u64 = SafeCast::<u128>::safe_cast(&active_shares)?
+            .safe_mul(self.active_share_value_or_zero()?)?
+            .safe_div(SafeCast::<u128>::safe_cast(&PRECISION)?)?
+            .safe_cast()?;
+        active_amount.safe_add(frozen_amount)
+    }
+
+    /// Remove a depositor's whole position during forced distribution
+    /// Active shares are paid at the active share value, pending shares at their frozen amount
+    pub fn force_exit(
+        &mut self,
+        active_shares: u64,
+        pending_shares: u64,
+        frozen_amount: u64,
+    ) -> VaultResult<u64> {
+        let total_amount = self.force_exit_amount(active_shares, frozen_amount)?;
+
+        self.release_pending(pending_shares, frozen_amount)?;
+
+        self.total_shares = self.total_shares.safe_sub(active_shares)?;
+        self.total_assets = self.total_assets.safe_sub(total_amount)?;
+
+        self.verify_invariants()?;
+
+        Ok(total_amount)
+    }
+
+    /// Freeze exactly `amount` assets out of `available_shares` for an unstake request at the
+    /// current active share value. Returns (shares, frozen_amount, asset_per_share_at_request)
+    pub fn freeze_unstake(&mut self, amount: u64, available_shares: u64) -> VaultResult<(u64, u64, u128)> {
+        self.check_not_paused(PAUSE_REQUEST_UNSTAKE)?;
+
+        let (shares, frozen_amount) = self.price_exit(amount, available_shares)?;
+        self.reserve_unstake(shares, frozen_amount, available_shares)
+    }
+
+    /// Freeze all of `shares` for an unstake request at their current value.
+    /// Returns (shares, frozen_amount, asset_per_share_at_request)
+    pub fn freeze_unstake_all(&mut self, shares: u64) -> VaultResult<(u64, u64, u128)> {
+        self.check_not_paused(PAUSE_REQUEST_UNSTAKE)?;
+
+        let (shares, frozen_amount) = self.price_exit_all(shares)?;
+        self.reserve_unstake(shares, frozen_amount, shares)
+    }
+
+    /// Count priced request shares as pending and their assets as reserved
+    fn reserve_unstake(&mut self, shares: u64, frozen_amount: u64, available_shares: u64) -> VaultResult<(u64, u64, u128)> {
+        // Each request costs an execution, only a full exit may be smaller than the minimum
+        if shares < available_shares && frozen_amount < self.min_unstake_amount {
+            return Err(VaultError::UnstakeBelowMinimum);
+        }
+
+        // Requested shares stop earning rewards immediately
+        self.pending_unstake_shares = self.pending_unstake_shares.safe_add(shares)?;
+        self.reserved_assets = self.reserved_assets.safe_add(frozen_amount)?;
+
+        self.verify_invariants()?;
+
+        // Store the price that reproduces the reserved amount exactly, otherwise execution
+        // and cancellation release one unit less than was reserved here
+        Ok((shares, frozen_amount, UnstakeRequest::exact_price(frozen_amount, shares)?))
+    }
+
+    /// Shares leaving the active pool for exactly `amount` assets at the active share value.
+    /// Exits of a whole position price their share count with `price_exit_all`, a `u64::MAX`
+    /// amount fails with `AmbiguousMaxAmount`. Returns (shares, amount)
+    pub(crate) fn price_exit(&self, amount: u64, available_shares: u64) -> VaultResult<(u64, u64)> {
+        if amount == 0 {
+            return Err(VaultError::InvalidAmount);
+        }
+        if amount == u64::MAX {
+            return Err(VaultError::AmbiguousMaxAmount);
+        }
+
+        let (available_assets, active_shares) = self.exit_pool(VaultError::InsufficientFunds)?;
+
+        // ANTI-ROUNDING ATTACK: freeze the exact requested amount, then derive the shares
+        // rounded up, so the burned shares are always worth at least the amount
+        let shares = vault_math::mul_div(amount, active_shares, available_assets, Rounding::Up)?;
+
+        if shares == 0 {
+            return Err(VaultError::InvalidAmount);
+        }
+        if shares > available_shares {
+            return Err(VaultError::InsufficientFunds);
+        }
+
+        Ok((shares, amount))
+    }
+
+    /// All of `shares` leaving the active pool at their current value, rounded down.
+    /// Returns (shares, amount)
+    pub(crate) fn price_exit_all(&self, shares: u64) -> VaultResult<(u64, u64)> {
+        let (available_assets, active_shares) = self.exit_pool(VaultError::InvalidAmount)?;
+        if shares == 0 {
+            return Err(VaultError::InvalidAmount);
+        }
+
+        let amount = vault_math::mul_div(shares, available_assets, active_shares, Rounding::Down)?;
+        Ok((shares, amount))
+    }
+
+    /// Available assets and active shares an exit is priced against, `no_active_shares` when
+    /// no depositor has any to exit with
+    fn exit_pool(&self, no_active_shares: VaultError) -> VaultResult<(u64, u64)> {
+        // Refuses shares that price at zero; the conversions use the exact ratio
+        self.get_active_share_value()?;
+        let available_assets = self.get_available_assets()?;
+        let active_shares = self.get_active_shares()?;
+        if active_shares == 0 {
+            return Err(no_active_shares);
+        }
+        Ok((available_assets, active_shares))
+    }
+
+    /// Price an instant unstake of exactly `amount` assets out of `available_shares`, without
+    /// changing any state. The penalty is rounded up in favour of remaining stakers.
+    /// Returns (shares, payout, penalty)
+    pub fn instant_unstake_quote(&self, amount: u64, available_shares: u64) -> VaultResult<(u64, u64, u64)> {
+        self.check_instant_unstake()?;
+        let (shares, gross_amount) = self.price_exit(amount, available_shares)?;
+        self.with_instant_unstake_penalty(shares, gross_amount)
+    }
+
+    /// Price an instant unstake of all of `shares`, as `instant_unstake_quote` does.
+    /// Returns (shares, payout, penalty)
+    pub fn instant_unstake_all_quote(&self, shares: u64) -> VaultResult<(u64, u64, u64)> {
+        self.check_instant_unstake()?;
+        let (shares, gross_amount) = self.price_exit_all(shares)?;
+        self.with_instant_unstake_penalty(shares, gross_amount)
+    }
+
+    /// Whether instant exits are enabled and not paused
+    fn check_instant_unstake(&self) -> VaultResult<()> {
+        if self.instant_unstake_fee_bps == 0 {
+            return Err(VaultError::InstantUnstakeDisabled);
+        }
+
+        // An instant exit is a request and its execution in one step
+        self.check_not_paused(PAUSE_REQUEST_UNSTAKE | PAUSE_UNSTAKE)
+    }
+
+    /// Split the gross value of an instant exit into payout and penalty
+    fn with_instant_unstake_penalty(&self, shares: u64, gross_amount: u64) -> VaultResult<(u64, u64, u64)> {
+        let penalty: u64 = SafeCast::<u128>::safe_cast(&gross_amount)?
+            .safe_mul(SafeCast::<u128>::safe_cast(&self.instant_unstake_fee_bps)?)?
+            .safe_add(SafeCast::<u128>::safe_cast(&(BASIS_POINTS_PRECISION - 1))?)?
+            .safe_div(SafeCast::<u128>::safe_cast(&BASIS_POINTS_PRECISION)?)?
+            .safe_cast()?;
+
+        Ok((shares, gross_amount.safe_sub(penalty)?, penalty))
+    }
+
+    /// Burn `shares` for `payout` assets. The penalty withheld from the payout stays in
+    /// `total_assets`, raising the active share value for the remaining stakers
+    pub fn instant_unstake(&mut self, shares: u64, payout: u64) -> VaultResult<()> {
+        self.total_shares = self.total_shares.safe_sub(shares)?;
+        self.total_assets = self.total_assets.safe_sub(payout)?;
+
+        self.verify_invariants()
+    }
+
+    /// Price an owner redemption of exactly `amount` assets out of the owner fee depositor's
+    /// `available_shares`, at the active share value and without penalty.
+    /// Returns (shares, amount)
+    pub fn owner_redemption_quote(&self, amount: u64, available_shares: u64) -> VaultResult<(u64, u64)> {
+        self.check_not_paused(PAUSE_UNSTAKE)?;
+        self.price_exit(amount, available_shares)
+    }
+
+    /// Price an owner redemption of every one of the owner fee depositor's `shares`.
+    /// Returns (shares, amount)
+    pub fn owner_redemption_all_quote(&self, shares: u64) -> VaultResult<(u64, u64)> {
+        self.check_not_paused(PAUSE_UNSTAKE)?;
+        self.price_exit_all(shares)
+    }
+
+    /// Burn redeemed owner fee shares together with the assets paid out for them
+    pub fn redeem_owner_shares(&mut self, shares: u64, amount: u64) -> VaultResult<()> {
+        self.total_shares = self.total_shares.safe_sub(shares)?;
+        self.total_assets = self.total_assets.safe_sub(amount)?;
+
+        self.verify_invariants()
+    }
+
+    /// Return a pending request's frozen assets to the active pool.
+    /// The depositor gets active shares worth exactly the frozen amount at today's share value,
+    /// so reactivating a request never moves the value of anyone else's shares.
+    /// Returns the number of active shares credited to the depositor
+    pub fn restore_unstake(&mut self, request: &UnstakeRequest) -> VaultResult<u64> {
+        let frozen_amount = request.frozen_amount()?;
+        let share_value = self.get_active_share_value()?;
+
+        self.release_pending(request.shares, frozen_amount)?;
+
+        let restored_shares: u64 = SafeCast::<u128>::safe_cast(&frozen_amount)?
+            .safe_mul(SafeCast::<u128>::safe_cast(&PRECISION)?)?
+            .safe_div(share_value)?
+            .safe_cast()?;
+        self.total_shares = self.total_shares.safe_add(restored_shares)?;
+
+        self.verify_invariants()?;
+
+        Ok(restored_shares)
+    }
+
+    /// Withdrawal fee on a matured request's `frozen_amount`, rounded up in favour of the
+    /// remaining stakers. Waived while decommissioning, when everyone has to leave, and when
+    /// no active shares remain to receive it
+    pub fn withdrawal_fee(&self, frozen_amount: u64) -> VaultResult<u64> {
+        if self.withdrawal_fee_bps == 0 || self.is_decommissioning() || self.get_active_shares()? <= self.dead_shares {
+            return Ok(0);
+        }
+
+        SafeCast::<u128>::safe_cast(&frozen_amount)?
+            .safe_mul(SafeCast::<u128>::safe_cast(&self.withdrawal_fee_bps)?)?
+            .safe_add(SafeCast::<u128>::safe_cast(&(BASIS_POINTS_PRECISION - 1))?)?
+            .safe_div(SafeCast::<u128>::safe_cast(&BASIS_POINTS_PRECISION)?)?
+            .safe_cast()
+    }
+
+    /// Settle a matured request: its shares are burned and its frozen amount less the
+    /// withdrawal fee leaves the vault. The fee stays in `total_assets`, raising the active
+    /// share value for the remaining stakers. Returns (payout, withdrawal fee)
+    pub fn complete_unstake(&mut self, request: &UnstakeRequest) -> VaultResult<(u64, u64)> {
+        self.check_not_paused(PAUSE_UNSTAKE)?;
+
+        let amount = request.frozen_amount()?;
+        let fee = self.withdrawal_fee(amount)?;
+        let payout = amount.safe_sub(fee)?;
+
+        self.release_pending(request.shares, amount)?;
+        self.total_assets = self.total_assets.safe_sub(payout)?;
+
+        self.verify_invariants()?;
+
+        Ok((payout, fee))
+    }
+
+    /// Count a 1-share floor hit and return the value it handed to the depositor
+    /// at the current active share value
+    pub fn record_precision_floor(&mut self, floor: &PrecisionFloor) -> VaultResult<u64> {
+        self.precision_floor_events = self.precision_floor_events.safe_add(1)?;
+
+        let share_value = self.active_share_value_or_zero()?;
+        let granted_value = SafeCast::<u128>::safe_cast(&floor.granted_shares)?
+            .safe_mul(share_value)?
+            .safe_div(SafeCast::<u128>::safe_cast(&PRECISION)?)?;
+        // What the original shares are worth in post-rebase units, without rounding
+        let exact_value = SafeCast::<u128>::safe_cast(&floor.original_shares)?
+            .safe_mul(share_value)?
+            .safe_div(SafeCast::<u128>::safe_cast(&PRECISION)?.safe_mul(floor.rebase_divisor)?)?;
+
+        granted_value.saturating_sub(exact_value).safe_cast()
+    }
+
+    /// Bring `depositor` in line with the latest rebase before its shares are priced,
+    /// counting a precision floor hit. Returns the sync and the floor's value delta
+    pub fn sync_depositor(&mut self, depositor: &mut VaultDepositor) -> VaultResult<(RebaseSync, u64)> {
+        let sync = depositor.sync_rebase(self)?;
+        let value_delta = match &sync.floor {
+            Some(floor) => self.record_precision_floor(floor)?,
+            None => 0,
+        };
+        Ok((sync, value_delta))
+    }
+
+    /// Remove a request's pending shares and reserved assets from the vault counters.
+    /// Requests round down with the counter on every rebase and a 1-share floor is counted
+    /// by `count_pending_floor`, so a request holding more than is pending is a broken invariant
+    fn release_pending(&mut self, shares: u64, frozen_amount: u64) -> VaultResult<()> {
+        if shares > self.pending_unstake_shares {
+            msg!("INVARIANT VIOLATION: request shares ({}) > pending_unstake_shares ({})",
+                 shares, self.pending_unstake_shares);
+            return Err(VaultError::InvariantRequestShares);
+        }
+        self.pending_unstake_shares = self.pending_unstake_shares.safe_sub(shares)?;
+        self.total_shares = self.total_shares.safe_sub(shares)?;
+        self.reserved_assets = self.reserved_assets.safe_sub(frozen_amount)?;
+        Ok(())
+    }
+
+    /// Count the shares a rebase's 1-share floor added to a pending request. They are
+    /// pending shares backed by the request's frozen amount, so the active share value does
+    /// not move
+    pub(crate) fn count_pending_floor(&mut self, shares: u64) -> VaultResult<()> {
+        self.pending_unstake_shares = self.pending_unstake_shares.safe_add(shares)?;
+        self.total_shares = self.total_shares.safe_add(shares)?;
+        Ok(())
+    }
+
+    pub fn get_signer_seeds(&self) -> [&[u8]; 3] {
+        [b"vault", self.name.as_ref(), std::slice::from_ref(&self.bump)]
+    }
+
+    /// Get available assets (total_assets - reserved_assets)
+    /// This represents assets that actively participate in rewards. Unvested rewards are
+    /// not part of `total_assets` until `accrue_rewards` releases them
+    pub fn get_available_assets(&self) -> VaultResult<u64> {
+        self.total_assets.safe_sub(self.reserved_assets)
+    }
+
+    /// Tokens the vault token account must hold: `total_assets` plus the rewards still vesting
+    pub fn accounted_balance(&self) -> VaultResult<u64> {
+        self.total_assets.safe_add(self.unvested_rewards)
+    }
+
+    /// Tokens in the vault token account beyond `accounted_balance`. Transfers that bypassed
+    /// `add_rewards` end up here
+    pub fn skimmable_surplus(&self, balance: u64) -> VaultResult<u64> {
+        Ok(balance.saturating_sub(self.accounted_balance()?))
+    }
+
+    /// Compare the vault token account `balance` with `accounted_balance` at `slot` and return
+    /// the shortfall. A covered balance records the slot, a shortfall pauses
+    /// `PAUSE_ON_SHORTFALL` when the vault opted into `pause_on_shortfall`
+    pub fn reconcile(&mut self, balance: u64, slot: u64) -> VaultResult<u64> {
+        let shortfall = self.accounted_balance()?.saturating_sub(balance);
+        if shortfall == 0 {
+            self.last_reconcile_slot = slot;
+        } else if self.pause_on_shortfall {
+            self.paused_operations |= PAUSE_ON_SHORTFALL;
+        }
+
+        Ok(shortfall)
+    }
+
+    /// Get active shares (total_shares - pending_unstake_shares)  
+    /// This represents shares that actively participate in rewards
+    pub fn get_active_shares(&self) -> VaultResult<u64> {
+        self.total_shares.safe_sub(self.pending_unstake_shares)
+    }
+
+    /// Depositor position counted towards the minimum stake, only when synced with the
+    /// vault's rebase so stale pre-rebase share counts cannot inflate it
+    pub fn depositor_stake_value(&self, depositor: &VaultDepositor) -> VaultResult<u64> {
+        if depositor.last_rebase_version == self.rebase_version {
+            self.shares_value(depositor.shares)
+        } else {
+            Ok(0)
+        }
+    }
+
+    /// Smallest stake accepted from a depositor whose position is worth `depositor_value`
+    pub fn min_stake_for(&self, depositor_value: u64) -> u64 {
+        self.min_stake_amount.saturating_sub(depositor_value)
+    }
+
+    /// Deposit fee withheld from a stake of `amount`
+    pub fn deposit_fee(&self, amount: u64) -> VaultResult<u64> {
+        SafeCast::<u128>::safe_cast(&amount)?
+            .safe_mul(SafeCast::<u128>::safe_cast(&self.deposit_fee_bps)?)?
+            .safe_div(SafeCast::<u128>::safe_cast(&BASIS_POINTS_PRECISION)?)?
+            .safe_cast()
+    }
+
+    /// Assets that can still be staked before `max_total_assets` is reached
+    pub fn remaining_capacity(&self) -> u64 {
+        self.max_total_assets.saturating_sub(self.total_assets)
+    }
+
+    /// Assets a depositor whose position is worth `depositor_value` can still stake before
+    /// `max_stake_per_depositor`. Only stakes check it, so rewards growing a position past the
+    /// cap never block its exit
+    pub fn depositor_remaining_capacity(&self, depositor_value: u64) -> u64 {
+        match self.max_stake_per_depositor {
+            0 | u64::MAX => u64::MAX,
+            cap => cap.saturating_sub(depositor_value),
+        }
+    }
+
+    /// Asset value of `shares` active shares at the current active share value
+    pub fn shares_value(&self, shares: u64) -> VaultResult<u64> {
+        SafeCast::<u128>::safe_cast(&shares)?
+            .safe_mul(self.active_share_value_or_zero()?)?
+            .safe_div(SafeCast::<u128>::safe_cast(&PRECISION)?)?
+            .safe_cast()
+    }
+
+    /// Get current share value for active participants
+    /// share_value = available_assets / active_shares
+    pub fn get_active_share_value(&self) -> VaultResult<u128> {
+        let available_assets = self.get_available_assets()?;
+        let active_shares = self.get_active_shares()?;
+        
+        if active_shares == 0 {
+            // EDGE CASE: When all shares are pending, return 1:1 ratio for new stakers
+            // This is reasonable because there are no active participants to dilute
+            return Ok(SafeCast::<u128>::safe_cast(&PRECISION)?);
+        }
+
+        let share_value = SafeCast::<u128>::safe_cast(&available_assets)?
+            .safe_mul(SafeCast::<u128>::safe_cast(&PRECISION)?)?
+            .safe_div(SafeCast::<u128>::safe_cast(&active_shares)?)?;
+
+        // A zero price would turn every later amount -> shares conversion into a division by zero
+        if share_value == 0 {
+            return Err(VaultError::ShareValueUnderflow);
+        }
+
+        Ok(share_value)
+    }
+
+    /// The vault as the next instruction at `current_time` would price it: vested rewards
+    /// released and a due rebase applied, on a copy so view instructions persist nothing
+    pub fn projected(&self, current_time: i64) -> VaultResult<Vault> {
+        let mut vault = self.clone();
+        vault.accrue_rewards(current_time)?;
+        vault.apply_rebase()?;
+        Ok(vault)
+    }
+
+    /// Active share value for payouts and events, where worthless shares are a valid answer.
+    /// Anything minting or pricing shares must use `get_active_share_value`
+    pub fn active_share_value_or_zero(&self) -> VaultResult<u128> {
+        match self.get_active_share_value() {
+            Err(VaultError::ShareValueUnderflow) => Ok(0),
+            value => value,
+        }
+    }
+
+    /// Count a depositor position opened by `initialize_vault_depositor`, or on first use by
+    /// `stake_for`, `transfer_shares` and `accept_ownership`
+    pub fn record_depositor_opened(&mut self) -> VaultResult<()> {
+        self.depositor_count = self.depositor_count.safe_add(1)?;
+        Ok(())
+    }
+
+    /// Follow a depositor position's active shares moving between zero and nonzero in
+    /// `active_depositor_count`. Saturates, legacy positions were never counted in
+    pub fn record_active_shares_change(&mut self, shares_before: u64, shares_after: u64) {
+        if shares_before == 0 && shares_after > 0 {
+            self.active_depositor_count = self.active_depositor_count.saturating_add(1);
+        } else if shares_before > 0 && shares_after == 0 {
+            self.active_depositor_count = self.active_depositor_count.saturating_sub(1);
+        }
+    }
+
+    /// Move an account `migrate_vault` sized to `Vault::LEN` to `VAULT_VERSION`. A legacy
+    /// account still holding owner shares stays at version 0 until `migrate_owner_shares`.
+    /// Returns the version the account was at
+    pub fn migrate_layout(&mut self) -> VaultResult<u8> {
+        let from_version = self.version;
+        if from_version > VAULT_VERSION {
+            return Err(VaultError::UnsupportedVaultVersion);
+        }
+        // Returns before the accumulator existed are not known, it starts from here
+        if self.cumulative_return == 0 {
+            self.cumulative_return = SHARE_PRECISION;
+        }
+        if from_version == 0 && self.owner_shares != 0 {
+            return Ok(from_version);
+        }
+
+        self.version = VAULT_VERSION;
+        Ok(from_version)
+    }
+
+    /// CRITICAL: Verify vault state invariants to prevent accounting errors
+    /// This should be called after any state-modifying operation
+    /// Only the invariants that hold for the account's `version` are checked, so legacy
+    /// accounts keep working during a rolling migration. Each invariant has its own error.
+    pub fn verify_invariants(&self) -> VaultResult<()> {
+        // A newer layout may hold fields this program would misread
+        if self.version > VAULT_VERSION {
+            msg!("Vault version {} is newer than this program's {}", self.version, VAULT_VERSION);
+            return Err(VaultError::UnsupportedVaultVersion);
+        }
+
+        // Invariant 3: reserved_assets should never exceed total_assets
+        if self.reserved_assets > self.total_assets {
+            msg!("INVARIANT VIOLATION: reserved_assets ({}) > total_assets ({})", 
+                 self.reserved_assets, self.total_assets);
+            return Err(VaultError::InvariantReservedAssets);
+        }
+
+        // Invariant 4: pending_unstake_shares should never exceed total_shares
+        if self.pending_unstake_shares > self.total_shares {
+            msg!("INVARIANT VIOLATION: pending_unstake_shares ({}) > total_shares ({})", 
+                 self.pending_unstake_shares, self.total_shares);
+            return Err(VaultError::InvariantPendingShares);
+        }
+
+        // Invariant 1 needs the vault token account balance, see `verify_solvency`
+
+        // Invariant 2: total_shares = active_shares + pending_shares
+        let active_shares = self.get_active_shares()?;
+        let expected_total_shares = active_shares.safe_add(self.pending_unstake_shares)?;
+        if self.total_shares != expected_total_shares {
+            msg!("INVARIANT VIOLATION: total_shares ({}) != active_shares ({}) + pending_shares ({})", 
+                 self.total_shares, active_shares, self.pending_unstake_shares);
+            return Err(VaultError::InvariantTotalShares);
+        }
+
+        if self.version == 0 {
+            return Ok(());
+        }
+
+        // Invariant 5 (v1): legacy owner shares are active shares. Legacy accounts may still
+        // carry owner shares that were never minted into total_shares until migrated.
+        // Dead shares are active shares held by nobody, next to the owner's
+        if self.owner_shares.safe_add(self.dead_shares)? > active_shares {
+            msg!("INVARIANT VIOLATION: owner_shares ({}) + dead_shares ({}) > active_shares ({})",
+                 self.owner_shares, self.dead_shares, active_shares);
+            return Err(VaultError::InvariantOwnerShares);
+        }
+
+        // Invariant 6 (v1): the decommission window is either unset or well ordered.
+        // Legacy accounts stored arbitrary `_reserved` bytes in these fields.
+        let window_ok = if self.decommission_started_at == 0 {
+            self.decommission_notice_ends_at == 0
+        } else {
+            self.decommission_notice_ends_at >= self.decommission_started_at
+        };
+        if !window_ok {
+            msg!("INVARIANT VIOLATION: decommission notice ends ({}) before it started ({})",
+                 self.decommission_notice_ends_at, self.decommission_started_at);
+            return Err(VaultError::InvariantDecommissionWindow);
+        }
+
+        // Invariant 7 (v1): share tokens are denominated in un-rebased shares
+        if self.is_tokenized() && self.shares_base != 0 {
+            msg!("INVARIANT VIOLATION: tokenized vault rebased to shares_base {}", self.shares_base);
+            return Err(VaultError::InvariantTokenizedRebase);
+        }
+
+        // Invariant 8 (v1): unvested rewards sit outside total_assets, so Invariants 1-4 hold
+        // for the released assets alone; the bucket itself needs a window it is released over
+        if self.unvested_rewards != 0 && self.last_vesting_update > self.vesting_end {
+            msg!("INVARIANT VIOLATION: {} unvested rewards past their vesting end ({})",
+                 self.unvested_rewards, self.vesting_end);
+            return Err(VaultError::InvariantVestingWindow);
+        }
+
+        Ok(())
+    }
+
+    /// Reconcile the share mint's `supply` with the internal accounting, which stays the source
+    /// of truth: every active share but the dead shares has exactly one token. `force_distribute`
+    /// cannot burn the tokens of the positions it pays out, so once decommissioning the supply
+    /// only has to cover the active shares
+    pub fn verify_share_supply(&self, supply: u64) -> VaultResult<()> {
+        let active_shares = self.get_active_shares()?.safe_sub(self.dead_shares)?;
+        let reconciled = if self.is_decommissioning() {
+            supply >= active_shares
+        } else {
+            supply == active_shares
+        };
+        if !reconciled {
+            msg!("INVARIANT VIOLATION: share mint supply ({}) != active_shares ({})",
+                 supply, active_shares);
+            return Err(VaultError::InvariantShareSupply);
+        }
+        Ok(())
+    }
+
+    /// Invariant 1: the vault token account `balance` backs the frozen unstake requests first
+    /// and then everything else the vault accounts for. Unlike `reconcile`, which only reports,
+    /// a shortfall fails the instruction
+    pub fn verify_solvency(&self, balance: u64) -> VaultResult<()> {
+        if balance < self.reserved_assets {
+            msg!("INVARIANT VIOLATION: vault balance ({}) < reserved_assets ({})",
+                 balance, self.reserved_assets);
+            return Err(VaultError::InvariantViolation);
+        }
+
+        let accounted = self.accounted_balance()?;
+        if balance < accounted {
+            msg!("INVARIANT VIOLATION: vault balance ({}) < accounted balance ({})",
+                 balance, accounted);
+            return Err(VaultError::InvariantViolation);
+        }
+        Ok(())
+    }
+
+    /// Apply rebase mechanism when shares become too large relative to assets. One call divides
+    /// by at most `10^MAX_REBASE_EXPO`, so a vault far out of range keeps operating and gets
+    /// there over several rebases instead of failing every instruction
+    pub fn apply_rebase(&mut self) -> VaultResult<Option<u128>> {
+        let expo_diff = self.rebase_expo()?.min(MAX_REBASE_EXPO);
+        if expo_diff == 0 {
+            return Ok(None);
+        }
+
+        self.rebase_by(expo_diff).map(Some)
+    }
+
+    /// Owner escape hatch: divide the shares by `10^expo_diff` in one step, up to the full
+    /// rebase `apply_rebase` would spread over several calls. Never rebases below one share
+    /// per asset
+    pub fn force_rebase(&mut self, expo_diff: u32) -> VaultResult<u128> {
+        if expo_diff == 0 || expo_diff > self.rebase_expo()? {
+            return Err(VaultError::InvalidAmount);
+        }
+
+        self.rebase_by(expo_diff)
+    }
+
+    /// Power of ten that brings the shares back between one and ten times the assets
+    fn rebase_expo(&self) -> VaultResult<u32> {
+        if self.total_assets == 0 || self.total_shares <= self.total_assets {
+            return Ok(0);
+        }
+        
+        // Share tokens cannot be divided in their holders' wallets, a tokenized vault keeps
+        // shares_base at 0 and lives with the precision loss instead
+        if self.is_tokenized() {
+            return Ok(0);
+        }
+
+        let (expo_diff, _) = vault_math::calculate_rebase_factor(self.total_shares, self.total_assets)?;
+        Ok(expo_diff)
+    }
+
+    fn rebase_by(&mut self, expo_diff: u32) -> VaultResult<u128> {
+        let rebase_divisor = 10u128.pow(expo_diff);
+
+        // Apply rebase by dividing shares
+        self.total_shares = (SafeCast::<u128>::safe_cast(&self.total_shares)?
+            .safe_div(rebase_divisor)?)
+        .safe_cast()?;
+        // Pending and owner shares are part of total_shares and must shrink with it,
+        // otherwise the active share count (and price) jumps after the rebase
+        self.pending_unstake_shares = (SafeCast::<u128>::safe_cast(&self.pending_unstake_shares)?
+            .safe_div(rebase_divisor)?)
+        .safe_cast()?;
+        self.owner_shares = (SafeCast::<u128>::safe_cast(&self.owner_shares)?
+            .safe_div(rebase_divisor)?)
+        .safe_cast()?;
+        self.dead_shares = (SafeCast::<u128>::safe_cast(&self.dead_shares)?
+            .safe_div(rebase_divisor)?)
+        .safe_cast()?;
+        // Fewer shares for the same assets: every share is worth `rebase_divisor` times more
+        self.high_water_mark = self.high_water_mark.safe_mul(rebase_divisor)?;
+        self.shares_base = self.shares_base.safe_add(expo_diff)?;
+        self.rebase_version = self.rebase_version.safe_add(1)?;
+
+        msg!(
+            "Vault rebase applied: expo_diff={}, divisor={}",
+            expo_diff,
+            rebase_divisor
+        );
+        Ok(rebase_divisor)
+    }
+
+    /// Get the effective share value considering rebase
+    pub fn get_effective_share_value(&self) -> VaultResult<u128> {
+        if self.total_shares == 0 {
+            return Ok(0);
+        }
+
+        let base_value = (SafeCast::<u128>::safe_cast(&self.total_assets)?)
+            .safe_mul(SafeCast::<u128>::safe_cast(&PRECISION)?)?
+            .safe_div(SafeCast::<u128>::safe_cast(&self.total_shares)?)?;
+
+        // Adjust for rebase factor
+        let rebase_multiplier = 10u128.pow(self.shares_base);
+        base_value.safe_mul(rebase_multiplier)
+    }
+}
+
+/// Pause mask equivalent to the legacy booleans: `is_paused` stops everything except the
+/// unstake flow, `withdrawals_paused` stops requests and their execution
+pub fn legacy_paused_operations(is_paused: bool, withdrawals_paused: bool) -> u32 {
+    let mut mask = 0;
+    if is_paused {
+        mask |= PAUSE_LEGACY_DEPOSITS;
+    }
+    if withdrawals_paused {
+        mask |= PAUSE_WITHDRAWALS;
+    }
+    mask
+}
+
+fn set_bits(mask: u32, bits: u32, paused: bool) -> u32 {
+    if paused {
+        mask | bits
+    } else {
+        mask & !bits
+    }
+}
+
+/// Canonical configuration of a vault, in the field order hashed by `Vault::config_digest`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct VaultConfigSnapshot {
+    pub owner: Pubkey,
+    pub platform_account: Pubkey,
+    pub token_mint: Pubkey,
+    pub unstake_lockup_period: i64,
+    pub platform_share_bps: u64,
+    pub min_stake_amount: u64,
+    pub max_total_assets: u64,
+    pub paused_operations: u32,
+    pub instant_unstake_fee_bps: u64,
+    pub stake_cooldown_seconds: i64,
+    pub permissioned: bool,
+    pub max_stake_per_depositor: u64,
+    pub reward_vesting_period: i64,
+    pub performance_fee_bps: u64,
+    pub withdrawal_fee_bps: u64,
+    pub unstake_request_expiry: i64,
+    pub reward_authority: Pubkey,
+    pub permissionless_rewards: bool,
+    pub pause_on_shortfall: bool,
+    pub config_timelock_seconds: i64,
+    pub min_unstake_amount: u64,
+    pub checkpoint_interval: i64,
+    pub management_fee_bps: u64,
+    pub deposit_fee_bps: u64,
+    pub deposit_fee_to_owner: bool,
+}
+
+impl VaultConfigSnapshot {
+    pub const LEN: usize = 32 + // owner
+        32 + // platform_account
+        32 + // token_mint
+        8 + // unstake_lockup_period
+        8 + // platform_share_bps
+        8 + // min_stake_amount
+        8 + // max_total_assets
+        4 + // paused_operations
+        8 + // instant_unstake_fee_bps
+        8 + // stake_cooldown_seconds
+        1 + // permissioned
+        8 + // max_stake_per_depositor
+        8 + // reward_vesting_period
+        8 + // performance_fee_bps
+        8 + // withdrawal_fee_bps
+        8 + // unstake_request_expiry
+        32 + // reward_authority
+        1 + // permissionless_rewards
+        1 + // pause_on_shortfall
+        8 + // config_timelock_seconds
+        8 + // min_unstake_amount
+        8 + // checkpoint_interval
+        8 + // management_fee_bps
+        8 + // deposit_fee_bps
+        1; // deposit_fee_to_owner
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default)]
+pub struct InitializeVaultParams {
+    pub unstake_lockup_period: Option<i64>,
+    pub platform_share_bps: Option<u64>,
+    pub min_stake_amount: Option<u64>,
+    pub max_total_assets: Option<u64>,
+    pub management_fee_bps: Option<u64>,
+    pub protocol_governed: bool,
+}
+
+/// Fees the protocol admin lowers with `override_vault_fees` (None = unchanged)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq)]
+pub struct FeeOverrideParams {
+    pub platform_share_bps: Option<u64>,
+    pub management_fee_bps: Option<u64>,
+    pub performance_fee_bps: Option<u64>,
+    pub withdrawal_fee_bps: Option<u64>,
+    pub instant_unstake_fee_bps: Option<u64>,
+    pub deposit_fee_bps: Option<u64>,
+    /// Pause stakes, new depositors, rewards and fee accrual, as the owner's `deposits_paused`
+    pub pause_deposits: bool,
+}
+
+/// What `hand_off_ownership` settled for the previous owner
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct OwnershipHandoff {
+    pub previous_owner: Pubkey,
+    /// Fee shares moved from the owner fee depositor into the previous owner's position
+    pub settled_fee_shares: u64,
+    /// The platform account was the previous owner and now is the new owner
+    pub platform_account_reset: bool,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq)]
 pub struct UpdateVaultConfigParams {
     pub unstake_lockup_period: Option<i64>,
-    pub management_fee: Option<u64>,
+    pub platform_share_bps: Option<u64>,
     pub min_stake_amount: Option<u64>,
     pub max_total_assets: Option<u64>,
+    /// Same as `deposits_paused`, kept for existing callers
     pub is_paused: Option<bool>,
     pub platform_account: Option<Pubkey>,
+    pub instant_unstake_fee_bps: Option<u64>,
+    pub stake_cooldown_seconds: Option<i64>,
+    pub deposits_paused: Option<bool>,
+    pub withdrawals_paused: Option<bool>,
+    pub permissioned: Option<bool>,
+    pub max_stake_per_depositor: Option<u64>,
+    /// Replaces the whole pause mask, cannot be combined with the pause flags above
+    pub paused_operations: Option<u32>,
+    pub reward_vesting_period: Option<i64>,
+    pub performance_fee_bps: Option<u64>,
+    pub withdrawal_fee_bps: Option<u64>,
+    /// 0 disables expiry
+    pub unstake_request_expiry: Option<i64>,
+    pub reward_authority: Option<Pubkey>,
+    pub permissionless_rewards: Option<bool>,
+    pub pause_on_shortfall: Option<bool>,
+    /// Only accepted through `propose_config_update`
+    pub config_timelock_seconds: Option<i64>,
+    pub min_unstake_amount: Option<u64>,
+    pub checkpoint_interval: Option<i64>,
+    pub management_fee_bps: Option<u64>,
+    pub deposit_fee_bps: Option<u64>,
+    pub deposit_fee_to_owner: Option<bool>,
+}
+
+impl UpdateVaultConfigParams {
+    /// Borsh size with every field set
+    pub const MAX_LEN: usize = 9 + // unstake_lockup_period
+        9 + // platform_share_bps
+        9 + // min_stake_amount
+        9 + // max_total_assets
+        2 + // is_paused
+        33 + // platform_account
+        9 + // instant_unstake_fee_bps
+        9 + // stake_cooldown_seconds
+        2 + // deposits_paused
+        2 + // withdrawals_paused
+        2 + // permissioned
+        9 + // max_stake_per_depositor
+        5 + // paused_operations
+        9 + // reward_vesting_period
+        9 + // performance_fee_bps
+        9 + // withdrawal_fee_bps
+        9 + // unstake_request_expiry
+        33 + // reward_authority
+        2 + // permissionless_rewards
+        2 + // pause_on_shortfall
+        9 + // config_timelock_seconds
+        9 + // min_unstake_amount
+        9 + // checkpoint_interval
+        9 + // management_fee_bps
+        9 + // deposit_fee_bps
+        2; // deposit_fee_to_owner
+
+    /// Whether the params only pause or resume operations
+    pub fn is_pause_only(&self) -> bool {
+        let without_pauses = UpdateVaultConfigParams {
+            is_paused: None,
+            deposits_paused: None,
+            withdrawals_paused: None,
+            paused_operations: None,
+            ..self.clone()
+        };
+        without_pauses == UpdateVaultConfigParams::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn funded_vault() -> Vault {
+        Vault {
+            total_shares: 1_000,
+            total_assets: 2_000,
+            unstake_lockup_period: FOURTEEN_DAYS,
+            ..Vault::default()
+        }
+    }
+
+    #[test]
+    fn test_decommission_waives_lockup_after_notice() {
+        let mut vault = funded_vault();
+        assert_eq!(vault.effective_unstake_lockup(0), FOURTEEN_DAYS);
+
+        assert!(vault.begin_decommission(100, ONE_DAY).is_err());
+        vault.begin_decommission(100, ONE_WEEK).unwrap();
+        assert!(vault.is_decommissioning());
+        assert!(matches!(
+            vault.begin_decommission(200, ONE_WEEK),
+            Err(VaultError::VaultDecommissioning)
+        ));
+
+        // Lockup still applies during the notice period
+        assert_eq!(vault.effective_unstake_lockup(100 + ONE_WEEK - 1), FOURTEEN_DAYS);
+        assert_eq!(vault.effective_unstake_lockup(100 + ONE_WEEK), 0);
+
+        // Forced distribution only after the long timeout
+        assert!(!vault.can_force_distribute(100 + ONE_WEEK));
+        assert!(vault.can_force_distribute(100 + ONE_WEEK + DECOMMISSION_FORCE_TIMEOUT));
+    }
+
+    #[test]
+    fn test_force_exit_pays_active_and_frozen_value() {
+        let mut vault = funded_vault();
+        // One depositor has 200 pending shares frozen at 300 assets
+        vault.pending_unstake_shares = 200;
+        vault.reserved_assets = 300;
+        // Active share value = (2000 - 300) / (1000 - 200) = 2.125
+
+        let amount = vault.force_exit(400, 200, 300).unwrap();
+        assert_eq!(amount, 400 * 2125 / 1000 + 300);
+        assert_eq!(vault.total_shares, 400);
+        assert_eq!(vault.pending_unstake_shares, 0);
+        assert_eq!(vault.reserved_assets, 0);
+        assert_eq!(vault.total_assets, 2_000 - amount);
+
+        // The last depositor drains the vault completely
+        let amount = vault.force_exit(400, 0, 0).unwrap();
+        assert_eq!(amount, 850);
+        assert_eq!(vault.total_shares, 0);
+        assert_eq!(vault.total_assets, 0);
+    }
+
+    #[test]
+    fn test_precision_floor_is_counted_with_value_delta() {
+        let mut vault = funded_vault();
+        let mut depositor = crate::state::VaultDepositor {
+            shares: 5,
+            ..Default::default()
+        };
+
+        // 5 shares / 10 rounds to 0 and is bumped to 1
+        let floor = depositor.apply_rebase(10, 1).unwrap().unwrap();
+        assert_eq!(depositor.shares, 1);
+        assert_eq!((floor.original_shares, floor.computed_shares, floor.granted_shares), (5, 0, 1));
+
+        // At 2 assets per share: granted 1 share = 2, exact 0.5 share = 1
+        assert_eq!(vault.record_precision_floor(&floor).unwrap(), 1);
+        assert_eq!(vault.precision_floor_events, 1);
+
+        // No floor when the division leaves shares behind
+        depositor.shares = 50;
+        assert!(depositor.apply_rebase(10, 2).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_config_digest_tracks_config_fields_only() {
+        let mut vault = funded_vault();
+        let digest = vault.config_digest().unwrap();
+        assert_eq!(digest, vault.config_digest().unwrap());
+
+        // Balances are not configuration
+        vault.total_assets += 1;
+        vault.total_shares += 1;
+        assert_eq!(digest, vault.config_digest().unwrap());
+
+        vault.platform_share_bps += 1;
+        assert_ne!(digest, vault.config_digest().unwrap());
+    }
+
+    /// Pre-upgrade account bytes: legacy fields only, zero-padded to the current size the way
+    /// a realloc would, so `version` reads back as 0. Owner shares were never minted.
+    fn legacy_fixture() -> Vec<u8> {
+        let mut data = Vault::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&[0u8; 32 * 6]); // name, pubkey, owner, platform_account, token_mint, vault_token_account
+        data.extend_from_slice(&1_000u64.to_le_bytes()); // total_shares
+        data.extend_from_slice(&2_000u64.to_le_bytes()); // total_assets
+        data.extend_from_slice(&0u64.to_le_bytes()); // total_rewards
+        data.extend_from_slice(&0u128.to_le_bytes()); // rewards_per_share
+        data.extend_from_slice(&0i64.to_le_bytes()); // last_rewards_update
+        data.extend_from_slice(&FOURTEEN_DAYS.to_le_bytes()); // unstake_lockup_period
+        data.extend_from_slice(&DEFAULT_PLATFORM_SHARE_BPS.to_le_bytes()); // platform_share_bps
+        data.extend_from_slice(&0u64.to_le_bytes()); // min_stake_amount
+        data.extend_from_slice(&u64::MAX.to_le_bytes()); // max_total_assets
+        data.push(0); // is_paused
+        data.extend_from_slice(&0i64.to_le_bytes()); // created_at
+        data.extend_from_slice(&0u32.to_le_bytes()); // shares_base
+        data.extend_from_slice(&0u32.to_le_bytes()); // rebase_version
+        data.extend_from_slice(&5_000u64.to_le_bytes()); // owner_shares
+        data.extend_from_slice(&100u64.to_le_bytes()); // pending_unstake_shares
+        data.extend_from_slice(&200u64.to_le_bytes()); // reserved_assets
+        data.push(255); // bump
+        data.resize(8 + Vault::LEN, 0);
+        data
+    }
+
+    #[test]
+    fn test_legacy_account_passes_v0_invariants() {
+        let data = legacy_fixture();
+        let vault = Vault::try_deserialize(&mut data.as_slice()).unwrap();
+        assert_eq!(vault.version, 0);
+        assert_eq!(vault.owner_shares, 5_000);
+        assert_eq!(vault.bump, 255);
+        // The former `management_fee` bytes are the reward split, the annual fee starts unset
+        assert_eq!((vault.platform_share_bps, vault.management_fee_bps), (DEFAULT_PLATFORM_SHARE_BPS, 0));
+        vault.verify_invariants().unwrap();
+
+        // The same account claimed as v1 fails on the owner share invariant only
+        let claimed = Vault { version: VAULT_VERSION, ..vault };
+        assert!(matches!(claimed.verify_invariants(), Err(VaultError::InvariantOwnerShares)));
+    }
+
+    #[test]
+    fn test_migrated_account_passes_full_invariants() {
+        let data = legacy_fixture();
+        let mut vault = Vault::try_deserialize(&mut data.as_slice()).unwrap();
+        vault.owner_shares = 0;
+        vault.version = VAULT_VERSION;
+        vault.verify_invariants().unwrap();
+
+        vault.begin_decommission(100, ONE_WEEK).unwrap();
+        vault.verify_invariants().unwrap();
+        vault.decommission_notice_ends_at = 50;
+        assert!(matches!(vault.verify_invariants(), Err(VaultError::InvariantDecommissionWindow)));
+        vault.decommission_started_at = 0;
+        assert!(matches!(vault.verify_invariants(), Err(VaultError::InvariantDecommissionWindow)));
+    }
+
+    #[test]
+    fn test_invariant_errors_identify_the_failed_check() {
+        let vault = Vault { reserved_assets: 3_000, ..funded_vault() };
+        assert!(matches!(vault.verify_invariants(), Err(VaultError::InvariantReservedAssets)));
+        let vault = Vault { pending_unstake_shares: 2_000, ..funded_vault() };
+        assert!(matches!(vault.verify_invariants(), Err(VaultError::InvariantPendingShares)));
+    }
+
+    #[test]
+    fn test_min_stake_grandfathers_large_positions() {
+        let mut vault = Vault {
+            min_stake_amount: 100,
+            max_total_assets: u64::MAX,
+            ..funded_vault()
+        };
+
+        // Small top-up by a depositor holding 500 shares worth 1_000
+        let position = vault.shares_value(500).unwrap();
+        assert_eq!(position, 1_000);
+        assert_eq!(vault.stake(10, position).unwrap(), 5);
+
+        // Small first stake by a new depositor still fails
+        assert!(matches!(vault.stake(10, 0), Err(VaultError::MinimumStakeAmountNotMet)));
+        // Small stake whose resulting position reaches the minimum passes
+        assert!(vault.stake(10, 90).is_ok());
+    }
+
+    #[test]
+    fn test_instant_unstake_penalty_stays_with_stakers() {
+        let mut vault = funded_vault();
+        assert!(matches!(vault.instant_unstake_quote(200, 500), Err(VaultError::InstantUnstakeDisabled)));
+
+        let too_high = UpdateVaultConfigParams {
+            unstake_lockup_period: None,
+            platform_share_bps: None,
+            min_stake_amount: None,
+            max_total_assets: None,
+            is_paused: None,
+            platform_account: None,
+            instant_unstake_fee_bps: Some(MAX_INSTANT_UNSTAKE_FEE + 1),
+            stake_cooldown_seconds: None,
+            deposits_paused: None,
+            withdrawals_paused: None,
+            permissioned: None,
+            max_stake_per_depositor: None,
+            paused_operations: None,
+            reward_vesting_period: None,
+            performance_fee_bps: None,
+            withdrawal_fee_bps: None,
+            unstake_request_expiry: None,
+            reward_authority: None,
+            permissionless_rewards: None,
+            pause_on_shortfall: None,
+            config_timelock_seconds: None,
+            min_unstake_amount: None,
+            checkpoint_interval: None,
+            management_fee_bps: None,
+            deposit_fee_bps: None,
+            deposit_fee_to_owner: None,
+        };
+        assert!(matches!(vault.update_config(too_high.clone()), Err(VaultError::InvalidVaultConfig)));
+        vault
+            .update_config(UpdateVaultConfigParams { instant_unstake_fee_bps: Some(300), ..too_high })
+            .unwrap();
+
+        // 100 shares worth 200 at 2.0 per share, 3% penalty rounded up
+        let (shares, payout, penalty) = vault.instant_unstake_quote(200, 500).unwrap();
+        assert_eq!((shares, payout, penalty), (100, 194, 6));
+        let value_before = vault.get_active_share_value().unwrap();
+        vault.instant_unstake(shares, payout).unwrap();
+        assert_eq!(vault.total_assets, 1_806);
+        assert!(vault.get_active_share_value().unwrap() > value_before);
+        assert!(matches!(vault.instant_unstake_quote(2_000, 500), Err(VaultError::InsufficientFunds)));
+    }
+
+    #[test]
+    fn test_instant_unstake_ignores_reserved_assets() {
+        let mut vault = Vault { instant_unstake_fee_bps: 100, ..funded_vault() };
+        // Half the shares are pending and hold 1_000 reserved assets
+        assert!(matches!(vault.freeze_unstake(u64::MAX, 1_000), Err(VaultError::AmbiguousMaxAmount)));
+        vault.freeze_unstake(1_000, 1_000).unwrap();
+        assert_eq!(vault.reserved_assets, 1_000);
+
+        // The remaining 500 active shares share only the 1_000 available assets
+        assert!(matches!(vault.instant_unstake_quote(u64::MAX, 500), Err(VaultError::AmbiguousMaxAmount)));
+        let (shares, payout, penalty) = vault.instant_unstake_all_quote(500).unwrap();
+        assert_eq!((shares, payout + penalty), (500, 1_000));
+        vault.instant_unstake(shares, payout).unwrap();
+        assert_eq!(vault.reserved_assets, 1_000);
+        assert_eq!(vault.get_available_assets().unwrap(), penalty);
+    }
+
+    #[test]
+    fn test_owner_redemption_leaves_reserved_assets_and_share_value() {
+        let mut vault = funded_vault();
+        // 400 shares pending with 800 reserved, the owner holds 200 of the active shares
+        vault.freeze_unstake(800, 1_000).unwrap();
+        let value_before = vault.get_active_share_value().unwrap();
+
+        assert!(matches!(vault.owner_redemption_quote(1_000, 200), Err(VaultError::InsufficientFunds)));
+        assert!(matches!(vault.owner_redemption_quote(u64::MAX, 200), Err(VaultError::AmbiguousMaxAmount)));
+        let (shares, amount) = vault.owner_redemption_all_quote(200).unwrap();
+        assert_eq!((shares, amount), (200, 400));
+        vault.redeem_owner_shares(shares, amount).unwrap();
+
+        assert_eq!(vault.reserved_assets, 800);
+        assert_eq!(vault.get_active_share_value().unwrap(), value_before);
+        assert_eq!((vault.total_shares, vault.total_assets), (800, 1_600));
+    }
+
+    #[test]
+    fn test_rewards_skip_shares_pending_unstake() {
+        crate::state::test_clock::install();
+        let mut vault = Vault { cumulative_return: SHARE_PRECISION, max_total_assets: u64::MAX, ..funded_vault() };
+        // 500 shares pending with 1_000 reserved
+        vault.freeze_unstake(1_000, 1_000).unwrap();
+
+        // The whole reward goes to the 500 active shares, the reserved amount stays as frozen
+        vault.add_rewards(500).unwrap();
+        assert_eq!(vault.reserved_assets, 1_000);
+        assert_eq!(vault.get_active_shares().unwrap(), 500);
+        assert_eq!(vault.get_available_assets().unwrap(), 1_500);
+        assert_eq!(vault.get_active_share_value().unwrap(), 3 * PRECISION as u128);
+    }
+
+    #[test]
+    fn test_cancel_after_rewards_releases_counters_at_todays_value() {
+        crate::state::test_clock::install();
+        let mut vault = Vault { cumulative_return: SHARE_PRECISION, max_total_assets: u64::MAX, ..funded_vault() };
+        let (shares, frozen_amount, asset_per_share) = vault.freeze_unstake(1_000, 1_000).unwrap();
+        let request = UnstakeRequest { shares, request_time: 0, asset_per_share_at_request: asset_per_share };
+        vault.add_rewards(500).unwrap();
+        let active_value_before = vault.shares_value(500).unwrap();
+
+        // The frozen 1_000 come back as 333 shares at 3 per share, the reward stays with the rest
+        let restored = vault.restore_unstake(&request).unwrap();
+        assert_eq!(restored, 333);
+        assert_eq!((vault.pending_unstake_shares, vault.reserved_assets), (0, 0));
+        assert_eq!((vault.total_shares, vault.total_assets), (833, 2_500));
+        assert!(vault.shares_value(restored).unwrap() <= frozen_amount);
+        assert!(vault.shares_value(500).unwrap() >= active_value_before);
+    }
+
+    #[test]
+    fn test_stake_cooldown_is_configurable_within_bounds() {
+        // Accounts created before the field existed read 0
+        let mut vault = funded_vault();
+        assert_eq!(vault.stake_cooldown(), DEFAULT_STAKE_COOLDOWN_SECONDS);
+
+        let params = UpdateVaultConfigParams {
+            unstake_lockup_period: None,
+            platform_share_bps: None,
+            min_stake_amount: None,
+            max_total_assets: None,
+            is_paused: None,
+            platform_account: None,
+            instant_unstake_fee_bps: None,
+            stake_cooldown_seconds: Some(0),
+            deposits_paused: None,
+            withdrawals_paused: None,
+            permissioned: None,
+            max_stake_per_depositor: None,
+            paused_operations: None,
+            reward_vesting_period: None,
+            performance_fee_bps: None,
+            withdrawal_fee_bps: None,
+            unstake_request_expiry: None,
+            reward_authority: None,
+            permissionless_rewards: None,
+            pause_on_shortfall: None,
+            config_timelock_seconds: None,
+            min_unstake_amount: None,
+            checkpoint_interval: None,
+            management_fee_bps: None,
+            deposit_fee_bps: None,
+            deposit_fee_to_owner: None,
+        };
+        assert!(matches!(vault.update_config(params.clone()), Err(VaultError::InvalidVaultConfig)));
+        let too_long = UpdateVaultConfigParams {
+            stake_cooldown_seconds: Some(MAX_STAKE_COOLDOWN_SECONDS + 1),
+            ..params.clone()
+        };
+        assert!(matches!(vault.update_config(too_long), Err(VaultError::InvalidVaultConfig)));
+
+        let depositor = VaultDepositor { last_stake_time: 1_000, ..VaultDepositor::default() };
+        assert!(matches!(depositor.check_stake_cooldown(&vault, 1_299), Err(VaultError::StakeCooldownNotMet)));
+        assert!(depositor.check_stake_cooldown(&vault, 1_300).is_ok());
+
+        vault.update_config(UpdateVaultConfigParams { stake_cooldown_seconds: Some(60), ..params }).unwrap();
+        assert!(matches!(depositor.check_stake_cooldown(&vault, 1_059), Err(VaultError::StakeCooldownNotMet)));
+        assert!(depositor.check_stake_cooldown(&vault, 1_060).is_ok());
+    }
+
+    #[test]
+    fn test_pause_flags_gate_deposits_and_withdrawals_independently() {
+        crate::state::test_clock::install();
+        let combinations = [(false, false), (true, false), (false, true), (true, true)];
+        for (deposits_paused, withdrawals_paused) in combinations {
+            let mut vault = Vault {
+                max_total_assets: u64::MAX,
+                instant_unstake_fee_bps: 100,
+                ..funded_vault()
+            };
+            // A pending request of 100 shares frozen at 2.0 per share
+            let (_, _, asset_per_share) = vault.freeze_unstake(200, 1_000).unwrap();
+            let request = UnstakeRequest {
+                shares: 100,
+                request_time: 0,
+                asset_per_share_at_request: asset_per_share,
+            };
+
+            let params = UpdateVaultConfigParams {
+                unstake_lockup_period: None,
+                platform_share_bps: None,
+                min_stake_amount: None,
+                max_total_assets: None,
+                is_paused: None,
+                platform_account: None,
+                instant_unstake_fee_bps: None,
+                stake_cooldown_seconds: None,
+                deposits_paused: Some(deposits_paused),
+                withdrawals_paused: Some(withdrawals_paused),
+                permissioned: None,
+                max_stake_per_depositor: None,
+                paused_operations: None,
+                reward_vesting_period: None,
+                performance_fee_bps: None,
+                withdrawal_fee_bps: None,
+                unstake_request_expiry: None,
+                reward_authority: None,
+                permissionless_rewards: None,
+                pause_on_shortfall: None,
+                config_timelock_seconds: None,
+                min_unstake_amount: None,
+                checkpoint_interval: None,
+                management_fee_bps: None,
+                deposit_fee_bps: None,
+                deposit_fee_to_owner: None,
+            };
+            vault.update_config(params).unwrap();
+            assert_eq!(vault.is_operation_paused(PAUSE_STAKE), deposits_paused);
+
+            let deposit = vault.clone().stake(100, 0);
+            let reward = vault.clone().add_rewards(100);
+            let request_unstake = vault.clone().freeze_unstake(100, 900);
+            let execute = vault.clone().complete_unstake(&request);
+            let instant = vault.instant_unstake_quote(100, 900);
+            let redeem = vault.owner_redemption_quote(100, 900);
+
+            assert_eq!(deposit.is_err(), deposits_paused);
+            assert_eq!(reward.is_err(), deposits_paused);
+            if deposits_paused {
+                assert!(matches!(deposit, Err(VaultError::VaultPaused)));
+            }
+            let withdrawals = [
+                request_unstake.map(|_| ()),
+                execute.map(|_| ()),
+                instant.map(|_| ()),
+                redeem.map(|_| ()),
+            ];
+            for result in withdrawals {
+                if withdrawals_paused {
+                    assert!(matches!(result, Err(VaultError::WithdrawalsPaused)));
+                } else {
+                    result.unwrap();
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_is_paused_param_is_the_deposits_flag() {
+        let mut vault = funded_vault();
+        let params = UpdateVaultConfigParams {
+            unstake_lockup_period: None,
+            platform_share_bps: None,
+            min_stake_amount: None,
+            max_total_assets: None,
+            is_paused: Some(true),
+            platform_account: None,
+            instant_unstake_fee_bps: None,
+            stake_cooldown_seconds: None,
+            deposits_paused: None,
+            withdrawals_paused: None,
+            permissioned: None,
+            max_stake_per_depositor: None,
+            paused_operations: None,
+            reward_vesting_period: None,
+            performance_fee_bps: None,
+            withdrawal_fee_bps: None,
+            unstake_request_expiry: None,
+            reward_authority: None,
+            permissionless_rewards: None,
+            pause_on_shortfall: None,
+            config_timelock_seconds: None,
+            min_unstake_amount: None,
+            checkpoint_interval: None,
+            management_fee_bps: None,
+            deposit_fee_bps: None,
+            deposit_fee_to_owner: None,
+        };
+        vault.update_config(params.clone()).unwrap();
+        assert_eq!(vault.paused_mask(), PAUSE_LEGACY_DEPOSITS);
+
+        let conflicting = UpdateVaultConfigParams { is_paused: Some(false), deposits_paused: Some(true), ..params };
+        assert!(matches!(vault.update_config(conflicting), Err(VaultError::InvalidVaultConfig)));
+    }
+
+    #[test]
+    fn test_default_vault_splits_rewards_evenly() {
+        crate::state::test_clock::install();
+        let mut vault = Vault::default();
+        let params = InitializeVaultParams {
+            unstake_lockup_period: None,
+            platform_share_bps: None,
+            min_stake_amount: None,
+            max_total_assets: None,
+            management_fee_bps: None,
+            protocol_governed: false,
+        };
+        let key = Pubkey::new_unique();
+        vault.initialize([0; 32], key, key, key, key, key, params, 255).unwrap();
+
+        // Half of each reward to the platform and no annual fee on assets
+        assert_eq!(vault.platform_share_bps, DEFAULT_PLATFORM_SHARE_BPS);
+        assert_eq!(vault.platform_share_bps * 2, BASIS_POINTS_PRECISION);
+        assert_eq!(vault.instant_unstake_fee_bps, 0);
+    }
+
+    #[test]
+    fn test_projected_vault_prices_like_the_next_instruction() {
+        crate::state::test_clock::install();
+        crate::state::test_clock::set_time(crate::state::test_clock::START_TIME + 5 * ONE_DAY);
+        let mut vault = Vault {
+            total_shares: 20_000,
+            total_assets: 1_000,
+            unvested_rewards: 100,
+            last_vesting_update: crate::state::test_clock::START_TIME,
+            vesting_end: crate::state::test_clock::START_TIME + 10 * ONE_DAY,
+            max_total_assets: u64::MAX,
+            version: 1,
+            ..Vault::default()
+        };
+
+        // Half the rewards vested and the shares rebased, on the copy only
+        let projected = vault.projected(crate::state::test_clock::now()).unwrap();
+        assert_eq!((projected.total_assets, projected.total_shares, projected.shares_base), (1_050, 2_000, 1));
+        assert_eq!((vault.total_assets, vault.total_shares, vault.shares_base), (1_000, 20_000, 0));
+
+        // A preview on the copy mints what the stake instruction then mints
+        let preview = projected.clone().stake(105, 0).unwrap();
+        vault.accrue_rewards(crate::state::test_clock::now()).unwrap();
+        assert_eq!(vault.stake(105, 0).unwrap(), preview);
+        assert_eq!(preview, 200);
+    }
+
+    #[test]
+    fn test_share_value_underflow_surfaces_as_error() {
+        // Every asset is reserved for pending requests, the 500 active shares are backed by nothing
+        let mut vault = Vault {
+            total_shares: 1_000,
+            pending_unstake_shares: 500,
+            total_assets: 1_000,
+            reserved_assets: 1_000,
+            max_total_assets: u64::MAX,
+            unstake_lockup_period: FOURTEEN_DAYS,
+            ..Vault::default()
+        };
+        vault.verify_invariants().unwrap();
+        assert!(matches!(vault.get_active_share_value(), Err(VaultError::ShareValueUnderflow)));
+        assert_eq!(vault.active_share_value_or_zero().unwrap(), 0);
+
+        assert!(matches!(vault.stake(1_000, 0), Err(VaultError::ShareValueUnderflow)));
+        assert!(matches!(vault.freeze_unstake(50, 500), Err(VaultError::ShareValueUnderflow)));
+        assert!(matches!(vault.freeze_unstake_all(500), Err(VaultError::ShareValueUnderflow)));
+
+        // Payouts still value the worthless shares at zero
+        assert_eq!(vault.shares_value(500).unwrap(), 0);
+        assert_eq!(vault.force_exit_amount(500, 25).unwrap(), 25);
+    }
+
+    #[test]
+    fn test_two_step_ownership_transfer() {
+        let owner = Pubkey::new_unique();
+        let new_owner = Pubkey::new_unique();
+        let mut vault = Vault { owner, ..funded_vault() };
+
+        assert!(matches!(vault.accept_ownership(), Err(VaultError::NoPendingOwner)));
+        assert!(matches!(vault.propose_owner(owner), Err(VaultError::InvalidVaultConfig)));
+        assert!(matches!(vault.propose_owner(Pubkey::default()), Err(VaultError::InvalidVaultConfig)));
+
+        // Proposing changes nothing until the new owner accepts
+        vault.propose_owner(new_owner).unwrap();
+        assert_eq!(vault.owner, owner);
+        assert_eq!(vault.accept_ownership().unwrap(), owner);
+        assert_eq!(vault.owner, new_owner);
+        assert!(!vault.has_pending_owner());
+    }
+
+    #[test]
+    fn test_cancelled_ownership_transfer_cannot_be_accepted() {
+        let owner = Pubkey::new_unique();
+        let typo = Pubkey::new_unique();
+        let mut vault = Vault { owner, ..funded_vault() };
+
+        vault.propose_owner(typo).unwrap();
+        assert_eq!(vault.cancel_owner_transfer().unwrap(), typo);
+        assert!(matches!(vault.accept_ownership(), Err(VaultError::NoPendingOwner)));
+        assert!(matches!(vault.cancel_owner_transfer(), Err(VaultError::NoPendingOwner)));
+        assert_eq!(vault.owner, owner);
+    }
+
+    #[test]
+    fn test_platform_account_changes_only_once_accepted() {
+        let platform = Pubkey::new_unique();
+        let new_platform = Pubkey::new_unique();
+        let mut vault = Vault { platform_account: platform, ..funded_vault() };
+
+        let direct = UpdateVaultConfigParams { platform_account: Some(new_platform), ..Default::default() };
+        assert!(matches!(vault.update_config(direct), Err(VaultError::PlatformAccountNeedsAcceptance)));
+        assert!(matches!(vault.accept_platform_account(), Err(VaultError::NoPendingPlatformAccount)));
+        assert!(matches!(vault.propose_platform_account(platform, false), Err(VaultError::InvalidVaultConfig)));
+
+        // Rewards keep going to the current platform account until the proposal is accepted
+        vault.propose_platform_account(new_platform, false).unwrap();
+        assert_eq!(vault.platform_account, platform);
+        assert_eq!(vault.accept_platform_account().unwrap(), platform);
+        assert_eq!(vault.platform_account, new_platform);
+        assert!(!vault.has_pending_platform_account());
+
+        let typo = Pubkey::new_unique();
+        vault.propose_platform_account(typo, false).unwrap();
+        assert_eq!(vault.cancel_platform_account_proposal().unwrap(), typo);
+        assert!(matches!(vault.accept_platform_account(), Err(VaultError::NoPendingPlatformAccount)));
+        assert_eq!(vault.platform_account, new_platform);
+    }
+
+    #[test]
+    fn test_platform_cosign_requirement() {
+        let mut vault = Vault { platform_cosign_required: true, ..funded_vault() };
+        let new_platform = Pubkey::new_unique();
+
+        assert!(matches!(
+            vault.propose_platform_account(new_platform, false),
+            Err(VaultError::PlatformCosignRequired)
+        ));
+        vault.propose_platform_account(new_platform, true).unwrap();
+        assert_eq!(vault.pending_platform_account, new_platform);
+    }
+
+    #[test]
+    fn test_fee_override_only_lowers_fees_of_governed_vaults() {
+        let mut vault = Vault {
+            platform_share_bps: 5_000,
+            management_fee_bps: 300,
+            instant_unstake_fee_bps: 1_000,
+            ..funded_vault()
+        };
+        let params = FeeOverrideParams {
+            platform_share_bps: Some(1_000),
+            management_fee_bps: Some(0),
+            pause_deposits: true,
+            ..Default::default()
+        };
+        assert!(matches!(vault.override_fees(&params), Err(VaultError::NotProtocolGoverned)));
+
+        vault.protocol_governed = true;
+        let raise = FeeOverrideParams { management_fee_bps: Some(301), ..Default::default() };
+        assert!(matches!(vault.override_fees(&raise), Err(VaultError::FeeOverrideRaisesFee)));
+        let disable_instant = FeeOverrideParams { instant_unstake_fee_bps: Some(0), ..Default::default() };
+        assert!(matches!(vault.override_fees(&disable_instant), Err(VaultError::InvalidVaultConfig)));
+
+        vault.override_fees(&params).unwrap();
+        assert_eq!((vault.platform_share_bps, vault.management_fee_bps), (1_000, 0));
+        assert_eq!(vault.instant_unstake_fee_bps, 1_000);
+        assert!(vault.is_operation_paused(PAUSE_STAKE));
+        assert!(!vault.is_operation_paused(PAUSE_WITHDRAWALS | PAUSE_CANCEL));
+    }
+
+    #[test]
+    fn test_stake_capacity_matches_stake_checks() {
+        let mut vault = Vault {
+            min_stake_amount: 50,
+            max_total_assets: 2_100,
+            ..funded_vault()
+        };
+        assert_eq!(vault.remaining_capacity(), 100);
+        assert_eq!(vault.min_stake_for(0), 50);
+        assert_eq!(vault.min_stake_for(30), 20);
+        assert_eq!(vault.min_stake_for(1_000), 0);
+
+        assert!(matches!(vault.stake(101, 0), Err(VaultError::VaultIsFull)));
+        assert!(matches!(vault.stake(19, 30), Err(VaultError::MinimumStakeAmountNotMet)));
+        vault.stake(100, 0).unwrap();
+        assert_eq!(vault.remaining_capacity(), 0);
+
+        // A lowered cap below current assets saturates at zero
+        vault.max_total_assets = 1_000;
+        assert_eq!(vault.remaining_capacity(), 0);
+    }
+    
+    #[test]
+    fn test_permissioned_vault_requires_a_matching_marker() {
+        let vault_key = Pubkey::new_unique();
+        let alice = Pubkey::new_unique();
+        let bob = Pubkey::new_unique();
+        let marker = AllowedDepositor { vault: vault_key, authority: alice, added_at: 0, bump: 255 };
+        
+        // Open vaults ignore the marker entirely
+        let mut vault = funded_vault();
+        assert!(vault.check_depositor_allowed(vault_key, bob, None).is_ok());
+        assert!(vault.check_depositor_allowed(vault_key, bob, Some(&marker)).is_ok());
+        
+        let params = UpdateVaultConfigParams {
+            unstake_lockup_period: None,
+            platform_share_bps: None,
+            min_stake_amount: None,
+            max_total_assets: None,
+            is_paused: None,
+            platform_account: None,
+            instant_unstake_fee_bps: None,
+            stake_cooldown_seconds: None,
+            deposits_paused: None,
+            withdrawals_paused: None,
+            permissioned: Some(true),
+            max_stake_per_depositor: None,
+            paused_operations: None,
+            reward_vesting_period: None,
+            performance_fee_bps: None,
+            withdrawal_fee_bps: None,
+            unstake_request_expiry: None,
+            reward_authority: None,
+            permissionless_rewards: None,
+            pause_on_shortfall: None,
+            config_timelock_seconds: None,
+            min_unstake_amount: None,
+            checkpoint_interval: None,
+            management_fee_bps: None,
+            deposit_fee_bps: None,
+            deposit_fee_to_owner: None,
+        };
+        vault.update_config(params).unwrap();
+        assert!(vault.check_depositor_allowed(vault_key, alice, Some(&marker)).is_ok());
+        assert!(matches!(vault.check_depositor_allowed(vault_key, alice, None), Err(VaultError::DepositorNotAllowed)));
+        // Someone else's marker, or one from another vault, does not count
+        assert!(matches!(
+            vault.check_depositor_allowed(vault_key, bob, Some(&marker)),
+            Err(VaultError::DepositorNotAllowed)
+        ));
+        assert!(matches!(
+            vault.check_depositor_allowed(Pubkey::new_unique(), alice, Some(&marker)),
+            Err(VaultError::DepositorNotAllowed)
+        ));
+    }
+    
+    #[test]
+    fn test_per_depositor_cap_limits_stakes_but_not_growth() {
+        let mut vault = Vault {
+            max_total_assets: u64::MAX,
+            max_stake_per_depositor: 1_000,
+            ..funded_vault()
+        };
+        assert_eq!(vault.depositor_remaining_capacity(0), 1_000);
+        assert_eq!(vault.depositor_remaining_capacity(400), 600);
+        assert!(matches!(vault.stake(601, 400), Err(VaultError::DepositorCapExceeded)));
+        vault.stake(600, 400).unwrap();
+        
+        // Rewards took the position above the cap: no more stakes, nothing else changes
+        assert_eq!(vault.depositor_remaining_capacity(1_200), 0);
+        assert!(matches!(vault.stake(1, 1_200), Err(VaultError::DepositorCapExceeded)));
+        
+        for unlimited in [0, u64::MAX] {
+            vault.max_stake_per_depositor = unlimited;
+            assert_eq!(vault.depositor_remaining_capacity(1_200), u64::MAX);
+            vault.stake(5_000, 1_200).unwrap();
+        }
+    }
+    
+    #[test]
+    fn test_each_paused_operation_bit_gates_only_its_family() {
+        crate::state::test_clock::install();
+        let bits = [
+            PAUSE_STAKE,
+            PAUSE_REQUEST_UNSTAKE,
+            PAUSE_UNSTAKE,
+            PAUSE_CANCEL,
+            PAUSE_ADD_REWARDS,
+            PAUSE_INIT_DEPOSITOR,
+            PAUSE_FEE_ACCRUAL,
+            PAUSE_TRANSFER,
+        ];
+        assert_eq!(bits.iter().fold(0, |mask, bit| mask | bit), PAUSE_ALL);
+        
+        for bit in bits {
+            let mut vault = Vault {
+                max_total_assets: u64::MAX,
+                instant_unstake_fee_bps: 100,
+                platform_share_bps: 5_000,
+                ..funded_vault()
+            };
+            // A pending request of 100 shares frozen at 2.0 per share
+            let (_, _, asset_per_share) = vault.freeze_unstake(200, 1_000).unwrap();
+            let request = UnstakeRequest {
+                shares: 100,
+                request_time: 0,
+                asset_per_share_at_request: asset_per_share,
+            };
+            vault.paused_operations = bit;
+            
+            let mut depositor = VaultDepositor { unstake_request: request.clone(), ..VaultDepositor::default() };
+            let outcomes = [
+                (PAUSE_STAKE, vault.clone().stake(100, 0).map(|_| ())),
+                (PAUSE_REQUEST_UNSTAKE, vault.clone().freeze_unstake(100, 900).map(|_| ())),
+                (PAUSE_UNSTAKE, vault.clone().complete_unstake(&request).map(|_| ())),
+                (PAUSE_CANCEL, depositor.cancel_unstake(&mut vault.clone()).map(|_| ())),
+                (PAUSE_ADD_REWARDS, vault.clone().add_rewards(100)),
+                (PAUSE_INIT_DEPOSITOR, vault.check_not_paused(PAUSE_INIT_DEPOSITOR)),
+                (PAUSE_TRANSFER, vault.check_not_paused(PAUSE_TRANSFER)),
+                (PAUSE_REQUEST_UNSTAKE | PAUSE_UNSTAKE, vault.instant_unstake_quote(100, 900).map(|_| ())),
+                (PAUSE_UNSTAKE, vault.owner_redemption_quote(100, 900).map(|_| ())),
+            ];
+            for (family, result) in outcomes {
+                match result {
+                    Ok(()) => assert_eq!(family & bit, 0, "bit {:#b} let {:#b} through", bit, family),
+                    Err(error) => {
+                        assert_ne!(family & bit, 0, "bit {:#b} blocked {:#b}: {:?}", bit, family, error);
+                        let expected = if bit & (PAUSE_STAKE | PAUSE_ADD_REWARDS) != 0 {
+                            VaultError::VaultPaused
+                        } else if bit & PAUSE_WITHDRAWALS != 0 {
+                            VaultError::WithdrawalsPaused
+                        } else {
+                            VaultError::OperationPaused
+                        };
+                        assert_eq!(std::mem::discriminant(&error), std::mem::discriminant(&expected));
+                    }
+                }
+            }
+            
+            // Fee accrual is not rejected, the platform just takes nothing
+            let expected_share = if bit == PAUSE_FEE_ACCRUAL { 0 } else { 5_000 };
+            assert_eq!(vault.accrued_platform_share_bps(), expected_share);
+        }
+    }
+    
+    #[test]
+    fn test_legacy_pause_flags_fold_into_the_mask() {
+        let mut vault = Vault { is_paused: true, withdrawals_paused: true, ..funded_vault() };
+        assert_eq!(legacy_paused_operations(true, false), PAUSE_LEGACY_DEPOSITS);
+        assert_eq!(vault.paused_mask(), PAUSE_LEGACY_DEPOSITS | PAUSE_WITHDRAWALS);
+        // Only the unstake flow is left out of the legacy deposits pause
+        assert!(!vault.is_operation_paused(PAUSE_CANCEL));
+        
+        let params = UpdateVaultConfigParams {
+            unstake_lockup_period: None,
+            platform_share_bps: None,
+            min_stake_amount: None,
+            max_total_assets: None,
+            is_paused: None,
+            platform_account: None,
+            instant_unstake_fee_bps: None,
+            stake_cooldown_seconds: None,
+            deposits_paused: None,
+            withdrawals_paused: None,
+            permissioned: None,
+            max_stake_per_depositor: None,
+            paused_operations: None,
+            reward_vesting_period: None,
+            performance_fee_bps: None,
+            withdrawal_fee_bps: None,
+            unstake_request_expiry: None,
+            reward_authority: None,
+            permissionless_rewards: None,
+            pause_on_shortfall: None,
+            config_timelock_seconds: None,
+            min_unstake_amount: None,
+            checkpoint_interval: None,
+            management_fee_bps: None,
+            deposit_fee_bps: None,
+            deposit_fee_to_owner: None,
+        };
+        vault.update_config(params.clone()).unwrap();
+        assert!(!vault.is_paused && !vault.withdrawals_paused);
+        assert_eq!(vault.paused_operations, PAUSE_LEGACY_DEPOSITS | PAUSE_WITHDRAWALS);
+        
+        // The flags only touch their own group
+        let resume_withdrawals = UpdateVaultConfigParams { withdrawals_paused: Some(false), ..params.clone() };
+        vault.update_config(resume_withdrawals).unwrap();
+        assert_eq!(vault.paused_mask(), PAUSE_LEGACY_DEPOSITS);
+        
+        let mask = UpdateVaultConfigParams { paused_operations: Some(PAUSE_ADD_REWARDS), ..params.clone() };
+        vault.update_config(mask.clone()).unwrap();
+        assert_eq!(vault.paused_mask(), PAUSE_ADD_REWARDS);
+        
+        let unknown_bit = UpdateVaultConfigParams { paused_operations: Some(PAUSE_ALL + 1), ..params.clone() };
+        assert!(matches!(vault.update_config(unknown_bit), Err(VaultError::InvalidVaultConfig)));
+        let mixed = UpdateVaultConfigParams { deposits_paused: Some(true), ..mask };
+        assert!(matches!(vault.update_config(mixed), Err(VaultError::InvalidVaultConfig)));
+        assert_eq!(vault.paused_mask(), PAUSE_ADD_REWARDS);
+    }
+
+    #[test]
+    fn test_rebase_leaves_shares_between_one_and_ten_times_the_assets() {
+        for total_shares in [19_999u64, 100_000, 150_000, 999_999, 1_000_001, 15_000_000] {
+            let mut vault = Vault { total_shares, total_assets: 1_000, version: 1, ..Vault::default() };
+            vault.apply_rebase().unwrap();
+            assert!(
+                vault.total_shares >= vault.total_assets && vault.total_shares < 10 * vault.total_assets,
+                "{} shares rebased to {}",
+                total_shares,
+                vault.total_shares
+            );
+        }
+        // Below ten shares per asset there is nothing to divide by
+        let mut vault = Vault { total_shares: 9_999, total_assets: 1_000, ..Vault::default() };
+        assert_eq!(vault.apply_rebase().unwrap(), None);
+    }
+
+    #[test]
+    fn test_far_out_of_range_vault_rebases_in_steps_or_by_force() {
+        let far = Vault { total_shares: 15_000_000_000_000, total_assets: 1_000, version: 1, ..Vault::default() };
+
+        let mut vault = far.clone();
+        assert_eq!(vault.apply_rebase().unwrap(), Some(1_000_000));
+        assert_eq!(vault.apply_rebase().unwrap(), Some(10_000));
+        assert_eq!(vault.apply_rebase().unwrap(), None);
+        assert_eq!((vault.total_shares, vault.shares_base, vault.rebase_version), (1_500, 10, 2));
+
+        // The owner gets there in one step, but never below one share per asset
+        let mut forced = far.clone();
+        assert!(matches!(forced.force_rebase(0), Err(VaultError::InvalidAmount)));
+        assert!(matches!(forced.force_rebase(11), Err(VaultError::InvalidAmount)));
+        assert_eq!(forced.force_rebase(10).unwrap(), 10_000_000_000);
+        assert_eq!((forced.total_shares, forced.shares_base, forced.rebase_version), (1_500, 10, 1));
+        assert!(matches!(forced.force_rebase(1), Err(VaultError::InvalidAmount)));
+    }
+
+    #[test]
+    fn test_tokenized_vault_never_rebases_and_reconciles_supply() {
+        // Share value fell below one unit: an untokenized vault rebases
+        let mut vault = Vault { total_shares: 5_000, total_assets: 20, version: 1, ..Vault::default() };
+        let mut tokenized = Vault { share_mint: Pubkey::new_unique(), ..vault.clone() };
+        assert!(vault.apply_rebase().unwrap().is_some());
+        assert_eq!(tokenized.apply_rebase().unwrap(), None);
+        assert_eq!(tokenized.shares_base, 0);
+
+        tokenized.shares_base = 1;
+        assert!(matches!(tokenized.verify_invariants(), Err(VaultError::InvariantTokenizedRebase)));
+        tokenized.shares_base = 0;
+
+        // One token per active share, pending shares have been burned
+        tokenized.pending_unstake_shares = 1_000;
+        tokenized.verify_share_supply(4_000).unwrap();
+        assert!(matches!(tokenized.verify_share_supply(4_001), Err(VaultError::InvariantShareSupply)));
+        assert!(matches!(tokenized.verify_share_supply(3_999), Err(VaultError::InvariantShareSupply)));
+
+        // Tokens of force-distributed positions stay outstanding while decommissioning
+        tokenized.decommission_started_at = 1;
+        tokenized.verify_share_supply(4_001).unwrap();
+        assert!(matches!(tokenized.verify_share_supply(3_999), Err(VaultError::InvariantShareSupply)));
+    }
+
+    #[test]
+    fn test_registered_program_source_gates_rewards() {
+        let vault_key = Pubkey::new_unique();
+        let router = Pubkey::new_unique();
+        let seeds = vec![b"fee_router".to_vec()];
+        let authority = ProgramRewardSource::derive_authority(&router, &seeds).unwrap();
+        assert_eq!(authority, Pubkey::find_program_address(&[b"fee_router"], &router).0);
+        assert!(ProgramRewardSource::derive_authority(&router, &[vec![0; 33]]).is_none());
+
+        let source = ProgramRewardSource {
+            vault: vault_key,
+            program_id: router,
+            seeds_hash: ProgramRewardSource::hash_seeds(&seeds),
+            authority,
+            ..ProgramRewardSource::default()
+        };
+        let wallet = Pubkey::new_unique();
+
+        // A vault from before reward authorities accepts any source until one is registered
+        let mut vault = funded_vault();
+        vault.check_reward_source(vault_key, wallet, true, None).unwrap();
+
+        vault.program_reward_sources = 1;
+        vault.check_reward_source(vault_key, authority, true, Some(&source)).unwrap();
+        assert!(matches!(vault.check_reward_source(vault_key, wallet, true, None), Err(VaultError::RewardSourceNotRegistered)));
+        assert!(matches!(
+            vault.check_reward_source(vault_key, authority, false, Some(&source)),
+            Err(VaultError::RewardSourceNotRegistered)
+        ));
+        // Another program's PDA for the same seeds is a different address
+        let rogue = ProgramRewardSource::derive_authority(&Pubkey::new_unique(), &seeds).unwrap();
+        assert!(matches!(
+            vault.check_reward_source(vault_key, rogue, true, Some(&source)),
+            Err(VaultError::RewardSourceNotRegistered)
+        ));
+        assert!(matches!(
+            vault.check_reward_source(Pubkey::new_unique(), authority, true, Some(&source)),
+            Err(VaultError::RewardSourceNotRegistered)
+        ));
+    }
+    
+    #[test]
+    fn test_reward_authority_and_permissionless_rewards() {
+        let vault_key = Pubkey::new_unique();
+        let treasury = Pubkey::new_unique();
+        let stranger = Pubkey::new_unique();
+        let router_authority = Pubkey::new_unique();
+        let source = ProgramRewardSource {
+            vault: vault_key,
+            authority: router_authority,
+            ..ProgramRewardSource::default()
+        };
+
+        // The authority must sign, anyone else is refused
+        let mut vault = Vault { reward_authority: treasury, ..funded_vault() };
+        vault.check_reward_source(vault_key, treasury, true, None).unwrap();
+        assert!(matches!(vault.check_reward_source(vault_key, treasury, false, None), Err(VaultError::UnauthorizedRewardSource)));
+        assert!(matches!(vault.check_reward_source(vault_key, stranger, true, None), Err(VaultError::UnauthorizedRewardSource)));
+
+        // Registered program sources are accepted alongside the authority
+        vault.program_reward_sources = 1;
+        vault.check_reward_source(vault_key, router_authority, true, Some(&source)).unwrap();
+        vault.check_reward_source(vault_key, treasury, true, None).unwrap();
+        assert!(matches!(
+            vault.check_reward_source(vault_key, stranger, true, Some(&source)),
+            Err(VaultError::UnauthorizedRewardSource)
+        ));
+
+        let params = UpdateVaultConfigParams {
+            unstake_lockup_period: None,
+            platform_share_bps: None,
+            min_stake_amount: None,
+            max_total_assets: None,
+            is_paused: None,
+            platform_account: None,
+            instant_unstake_fee_bps: None,
+            stake_cooldown_seconds: None,
+            deposits_paused: None,
+            withdrawals_paused: None,
+            permissioned: None,
+            max_stake_per_depositor: None,
+            paused_operations: None,
+            reward_vesting_period: None,
+            performance_fee_bps: None,
+            withdrawal_fee_bps: None,
+            unstake_request_expiry: None,
+            reward_authority: Some(Pubkey::default()),
+            permissionless_rewards: None,
+            pause_on_shortfall: None,
+            config_timelock_seconds: None,
+            min_unstake_amount: None,
+            checkpoint_interval: None,
+            management_fee_bps: None,
+            deposit_fee_bps: None,
+            deposit_fee_to_owner: None,
+        };
+        assert!(matches!(vault.update_config(params.clone()), Err(VaultError::InvalidVaultConfig)));
+
+        // The old behaviour: any signing source
+        vault
+            .update_config(UpdateVaultConfigParams { reward_authority: None, permissionless_rewards: Some(true), ..params.clone() })
+            .unwrap();
+        vault.check_reward_source(vault_key, stranger, true, None).unwrap();
+
+        vault
+            .update_config(UpdateVaultConfigParams { reward_authority: Some(stranger), permissionless_rewards: Some(false), ..params })
+            .unwrap();
+        vault.check_reward_source(vault_key, stranger, true, None).unwrap();
+        assert!(matches!(vault.check_reward_source(vault_key, treasury, true, None), Err(VaultError::UnauthorizedRewardSource)));
+    }
+    
+    #[test]
+    fn test_transfer_shares_moves_only_active_shares_at_face_value() {
+        let vault = Vault { rebase_version: 2, ..funded_vault() };
+        let mut alice = VaultDepositor {
+            shares: 300,
+            last_stake_time: 500,
+            last_rebase_version: 2,
+            unstake_request: UnstakeRequest { shares: 100, request_time: 400, asset_per_share_at_request: PRECISION as u128 },
+            ..VaultDepositor::default()
+        };
+        let mut bob = VaultDepositor { shares: 50, last_stake_time: 200, last_rebase_version: 1, ..VaultDepositor::default() };
+        
+        assert!(matches!(alice.transfer_shares(&mut bob, &vault, 100), Err(VaultError::RebaseSyncRequired)));
+        bob.last_rebase_version = 2;
+        assert!(matches!(alice.transfer_shares(&mut bob, &vault, 0), Err(VaultError::InvalidAmount)));
+        // The pending 100 shares are not transferable
+        assert!(matches!(alice.transfer_shares(&mut bob, &vault, 301), Err(VaultError::InsufficientFunds)));
+        
+        assert_eq!(alice.transfer_shares(&mut bob, &vault, 100).unwrap(), 100);
+        assert_eq!((alice.shares, bob.shares), (200, 150));
+        // The recipient inherits the later cooldown, never an earlier one
+        assert_eq!(bob.last_stake_time, 500);
+        assert_eq!(alice.unstake_request.shares, 100);
+        
+        bob.last_stake_time = 900;
+        assert!(matches!(alice.transfer_shares(&mut bob, &vault, u64::MAX), Err(VaultError::AmbiguousMaxAmount)));
+        assert_eq!(alice.transfer_shares_all(&mut bob, &vault).unwrap(), 200);
+        assert_eq!((alice.shares, bob.shares, bob.last_stake_time), (0, 350, 900));
+        assert!(matches!(alice.transfer_shares_all(&mut bob, &vault), Err(VaultError::InsufficientFunds)));
+    }
+    
+    #[test]
+    fn test_rewards_vest_linearly_and_restart_with_each_reward() {
+        crate::state::test_clock::install();
+        let start = crate::state::test_clock::START_TIME;
+        crate::state::test_clock::set_time(start);
+        let mut vault = Vault { version: 1, reward_vesting_period: 1_000, max_total_assets: u64::MAX, ..funded_vault() };
+        
+        vault.add_rewards(1_000).unwrap();
+        assert_eq!((vault.total_assets, vault.unvested_rewards, vault.vesting_end), (2_000, 1_000, start + 1_000));
+        assert_eq!(vault.vested_rewards(start + 250).unwrap(), 250);
+        assert_eq!(vault.accrue_rewards(start + 250).unwrap(), 250);
+        assert_eq!(vault.total_assets, 2_250);
+        // Accruing twice in a row releases nothing more
+        assert_eq!(vault.accrue_rewards(start + 250).unwrap(), 0);
+        
+        // The remaining 750 and the new reward vest together over a fresh period
+        crate::state::test_clock::set_time(start + 500);
+        vault.add_rewards(1_250).unwrap();
+        assert_eq!(vault.total_assets, 2_500);
+        assert_eq!((vault.unvested_rewards, vault.vesting_end), (1_750, start + 1_500));
+        assert_eq!(vault.accrue_rewards(start + 1_000).unwrap(), 875);
+        assert_eq!(vault.accrue_rewards(start + 5_000).unwrap(), 875);
+        assert_eq!((vault.total_assets, vault.unvested_rewards, vault.total_rewards), (4_250, 0, 2_250));
+        vault.verify_invariants().unwrap();
+        
+        // Decommissioning releases whatever is still vesting
+        crate::state::test_clock::set_time(start + 5_000);
+        vault.add_rewards(100).unwrap();
+        vault.begin_decommission(start + 5_100, ONE_WEEK).unwrap();
+        assert_eq!((vault.total_assets, vault.unvested_rewards), (4_350, 0));
+        
+        let too_long = UpdateVaultConfigParams {
+            unstake_lockup_period: None,
+            platform_share_bps: None,
+            min_stake_amount: None,
+            max_total_assets: None,
+            is_paused: None,
+            platform_account: None,
+            instant_unstake_fee_bps: None,
+            stake_cooldown_seconds: None,
+            deposits_paused: None,
+            withdrawals_paused: None,
+            permissioned: None,
+            max_stake_per_depositor: None,
+            paused_operations: None,
+            reward_vesting_period: Some(MAX_REWARD_VESTING_PERIOD + 1),
+            performance_fee_bps: None,
+            withdrawal_fee_bps: None,
+            unstake_request_expiry: None,
+            reward_authority: None,
+            permissionless_rewards: None,
+            pause_on_shortfall: None,
+            config_timelock_seconds: None,
+            min_unstake_amount: None,
+            checkpoint_interval: None,
+            management_fee_bps: None,
+            deposit_fee_bps: None,
+            deposit_fee_to_owner: None,
+        };
+        assert!(matches!(vault.update_config(too_long.clone()), Err(VaultError::InvalidVaultConfig)));
+        vault.update_config(UpdateVaultConfigParams { reward_vesting_period: Some(0), ..too_long }).unwrap();
+        assert_eq!(vault.reward_vesting_period, 0);
+        
+        let stuck = Vault { unvested_rewards: 5, vesting_end: start, last_vesting_update: start + 1, ..vault };
+        assert!(matches!(stuck.verify_invariants(), Err(VaultError::InvariantVestingWindow)));
+    }
+
+    #[test]
+    fn test_withdrawal_fee_stays_with_remaining_stakers() {
+        let mut vault = Vault { withdrawal_fee_bps: 50, ..funded_vault() };
+        // 100 shares frozen at 2.0 per share, 0.5% fee rounded up
+        let (shares, _, asset_per_share) = vault.freeze_unstake(200, 1_000).unwrap();
+        let request = UnstakeRequest { shares, request_time: 0, asset_per_share_at_request: asset_per_share };
+        assert_eq!(vault.withdrawal_fee(200).unwrap(), 1);
+
+        let value_before = vault.get_active_share_value().unwrap();
+        assert_eq!(vault.complete_unstake(&request).unwrap(), (199, 1));
+        assert_eq!((vault.total_shares, vault.total_assets, vault.reserved_assets), (900, 1_801, 0));
+        assert!(vault.get_active_share_value().unwrap() > value_before);
+
+        // Nobody is left to receive it, or everyone has to leave
+        let all_pending = Vault { pending_unstake_shares: 900, reserved_assets: 1_801, ..vault.clone() };
+        assert_eq!(all_pending.withdrawal_fee(1_801).unwrap(), 0);
+        vault.begin_decommission(100, ONE_WEEK).unwrap();
+        assert_eq!(vault.withdrawal_fee(200).unwrap(), 0);
+
+        let too_high = UpdateVaultConfigParams {
+            unstake_lockup_period: None,
+            platform_share_bps: None,
+            min_stake_amount: None,
+            max_total_assets: None,
+            is_paused: None,
+            platform_account: None,
+            instant_unstake_fee_bps: None,
+            stake_cooldown_seconds: None,
+            deposits_paused: None,
+            withdrawals_paused: None,
+            permissioned: None,
+            max_stake_per_depositor: None,
+            paused_operations: None,
+            reward_vesting_period: None,
+            performance_fee_bps: None,
+            withdrawal_fee_bps: Some(MAX_WITHDRAWAL_FEE_BPS + 1),
+            unstake_request_expiry: None,
+            reward_authority: None,
+            permissionless_rewards: None,
+            pause_on_shortfall: None,
+            config_timelock_seconds: None,
+            min_unstake_amount: None,
+            checkpoint_interval: None,
+            management_fee_bps: None,
+            deposit_fee_bps: None,
+            deposit_fee_to_owner: None,
+        };
+        assert!(matches!(vault.update_config(too_high.clone()), Err(VaultError::InvalidVaultConfig)));
+        vault.update_config(UpdateVaultConfigParams { withdrawal_fee_bps: Some(MAX_WITHDRAWAL_FEE_BPS), ..too_high }).unwrap();
+        assert_eq!(vault.withdrawal_fee_bps, MAX_WITHDRAWAL_FEE_BPS);
+    }
+
+    #[test]
+    fn test_deposit_fee_benefits_stakers_or_owner() {
+        // 1_000 staked at 2.0 per share, 1% fee: 500 shares gross, 5 of them withheld
+        let mut vault = Vault { deposit_fee_bps: 100, max_total_assets: 10_000, version: VAULT_VERSION, ..funded_vault() };
+        assert_eq!(vault.deposit_fee(1_000).unwrap(), 10);
+        let value_before = vault.get_active_share_value().unwrap();
+        assert_eq!(vault.stake(1_000, 0).unwrap(), 495);
+        assert_eq!((vault.total_shares, vault.total_assets, vault.owner_shares), (1_495, 3_000, 0));
+        assert!(vault.get_active_share_value().unwrap() > value_before);
+
+        let mut to_owner = Vault { deposit_fee_to_owner: true, ..vault.clone() };
+        assert_eq!(to_owner.stake(1_000, 0).unwrap(), 494);
+        assert_eq!(to_owner.owner_shares, 4);
+        assert_eq!(to_owner.total_shares, 1_495 + 498);
+
+        // The minimum applies to the gross amount
+        let mut min_gross = Vault { min_stake_amount: 1_000, ..vault.clone() };
+        min_gross.stake(1_000, 0).unwrap();
+
+        let params = UpdateVaultConfigParams { deposit_fee_bps: Some(MAX_DEPOSIT_FEE_BPS + 1), ..Default::default() };
+        assert!(matches!(vault.update_config(params), Err(VaultError::InvalidVaultConfig)));
+    }
+    #[test]
+    fn test_unstake_request_expiry_bounds_and_decommission() {
+        let mut vault = funded_vault();
+        let matured = vault.unstake_lockup_period;
+        assert!(!vault.is_unstake_request_expired(0, i64::MAX));
+
+        let params = UpdateVaultConfigParams {
+            unstake_lockup_period: None,
+            platform_share_bps: None,
+            min_stake_amount: None,
+            max_total_assets: None,
+            is_paused: None,
+            platform_account: None,
+            instant_unstake_fee_bps: None,
+            stake_cooldown_seconds: None,
+            deposits_paused: None,
+            withdrawals_paused: None,
+            permissioned: None,
+            max_stake_per_depositor: None,
+            paused_operations: None,
+            reward_vesting_period: None,
+            performance_fee_bps: None,
+            withdrawal_fee_bps: None,
+            unstake_request_expiry: Some(MIN_UNSTAKE_REQUEST_EXPIRY - 1),
+            reward_authority: None,
+            permissionless_rewards: None,
+            pause_on_shortfall: None,
+            config_timelock_seconds: None,
+            min_unstake_amount: None,
+            checkpoint_interval: None,
+            management_fee_bps: None,
+            deposit_fee_bps: None,
+            deposit_fee_to_owner: None,
+        };
+        assert!(matches!(vault.update_config(params.clone()), Err(VaultError::InvalidVaultConfig)));
+        let too_long = UpdateVaultConfigParams { unstake_request_expiry: Some(MAX_UNSTAKE_REQUEST_EXPIRY + 1), ..params.clone() };
+        assert!(matches!(vault.update_config(too_long), Err(VaultError::InvalidVaultConfig)));
+        vault.update_config(UpdateVaultConfigParams { unstake_request_expiry: Some(30 * ONE_DAY), ..params.clone() }).unwrap();
+
+        // Counted from maturity, not from the request
+        assert!(!vault.is_unstake_request_expired(0, matured + 30 * ONE_DAY - 1));
+        assert!(vault.is_unstake_request_expired(0, matured + 30 * ONE_DAY));
+
+        // Everyone is leaving, nothing is restored
+        vault.begin_decommission(100, ONE_WEEK).unwrap();
+        assert!(!vault.is_unstake_request_expired(0, matured + 30 * ONE_DAY));
+
+        vault.update_config(UpdateVaultConfigParams { unstake_request_expiry: Some(0), ..params }).unwrap();
+        assert_eq!(vault.unstake_request_expiry, 0);
+    }
+
+    #[test]
+    fn test_skimmable_surplus_excludes_unvested_rewards() {
+        crate::state::test_clock::install();
+        crate::state::test_clock::set_time(crate::state::test_clock::START_TIME);
+        let mut vault = Vault { version: 1, reward_vesting_period: 1_000, max_total_assets: u64::MAX, ..funded_vault() };
+        
+        assert_eq!(vault.skimmable_surplus(2_000).unwrap(), 0);
+        // Never below the accounted assets
+        assert_eq!(vault.skimmable_surplus(1_500).unwrap(), 0);
+        assert_eq!(vault.skimmable_surplus(2_300).unwrap(), 300);
+        
+        // Rewards still vesting are already in the token account, not surplus
+        vault.add_rewards(500).unwrap();
+        assert_eq!(vault.skimmable_surplus(2_500).unwrap(), 0);
+        assert_eq!(vault.skimmable_surplus(2_800).unwrap(), 300);
+    }
+
+    #[test]
+    fn test_reconcile_records_slot_or_pauses_on_shortfall() {
+        let mut vault = Vault { version: 1, unvested_rewards: 100, ..funded_vault() };
+        
+        assert_eq!(vault.reconcile(2_100, 7).unwrap(), 0);
+        assert_eq!(vault.last_reconcile_slot, 7);
+        
+        // A shortfall is reported but pauses nothing unless the vault opted in
+        assert_eq!(vault.reconcile(2_050, 8).unwrap(), 50);
+        assert_eq!((vault.last_reconcile_slot, vault.paused_mask()), (7, 0));
+        
+        vault.pause_on_shortfall = true;
+        assert_eq!(vault.reconcile(1_000, 9).unwrap(), 1_100);
+        assert_eq!(vault.last_reconcile_slot, 7);
+        assert!(vault.is_operation_paused(PAUSE_STAKE));
+        assert!(vault.is_operation_paused(PAUSE_UNSTAKE));
+        assert!(!vault.is_operation_paused(PAUSE_CANCEL | PAUSE_ADD_REWARDS));
+        
+        // Covering the balance again does not unpause, the owner does
+        assert_eq!(vault.reconcile(2_100, 10).unwrap(), 0);
+        assert_eq!(vault.last_reconcile_slot, 10);
+        assert_eq!(vault.paused_mask(), PAUSE_ON_SHORTFALL);
+    }
+
+    #[test]
+    fn test_solvency_fails_on_any_shortfall() {
+        let vault = Vault { version: 1, reserved_assets: 500, unvested_rewards: 100, ..funded_vault() };
+        vault.verify_solvency(2_100).unwrap();
+        vault.verify_solvency(5_000).unwrap();
+
+        // Short of the vesting rewards only, then short of the frozen requests too
+        assert!(matches!(vault.verify_solvency(2_099), Err(VaultError::InvariantViolation)));
+        assert!(matches!(vault.verify_solvency(2_000), Err(VaultError::InvariantViolation)));
+        assert!(matches!(vault.verify_solvency(499), Err(VaultError::InvariantViolation)));
+
+        // The old bookkeeping invariants cannot see a missing balance at all
+        vault.verify_invariants().unwrap();
+    }
+
+    #[test]
+    fn test_config_timelock_leaves_only_pauses_instant() {
+        let mut vault = Vault { version: 1, unstake_lockup_period: FOURTEEN_DAYS, ..Vault::default() };
+        let fee = UpdateVaultConfigParams { platform_share_bps: Some(4_000), ..Default::default() };
+        let pause = UpdateVaultConfigParams { deposits_paused: Some(true), withdrawals_paused: Some(false), ..Default::default() };
+        let timelock = UpdateVaultConfigParams { config_timelock_seconds: Some(2 * ONE_DAY), ..Default::default() };
+        assert!(pause.is_pause_only());
+        assert!(!fee.is_pause_only());
+        
+        // Without a timelock everything applies at once, except the timelock itself
+        vault.check_instant_config_update(&fee).unwrap();
+        assert!(matches!(vault.check_instant_config_update(&timelock), Err(VaultError::ConfigUpdateTimelocked)));
+        
+        let too_long = UpdateVaultConfigParams { config_timelock_seconds: Some(MAX_CONFIG_TIMELOCK_SECONDS + 1), ..Default::default() };
+        assert!(matches!(vault.update_config(too_long), Err(VaultError::InvalidVaultConfig)));
+        vault.update_config(timelock).unwrap();
+        assert_eq!(vault.config_snapshot().config_timelock_seconds, 2 * ONE_DAY);
+        
+        assert!(matches!(vault.check_instant_config_update(&fee), Err(VaultError::ConfigUpdateTimelocked)));
+        let pause_and_fee = UpdateVaultConfigParams { platform_share_bps: Some(4_000), ..pause.clone() };
+        assert!(matches!(vault.check_instant_config_update(&pause_and_fee), Err(VaultError::ConfigUpdateTimelocked)));
+        vault.check_instant_config_update(&pause).unwrap();
+        vault.update_config(pause).unwrap();
+        assert!(vault.is_operation_paused(PAUSE_STAKE));
+    }
+
+    #[test]
+    fn test_platform_share_moves_in_bounded_steps() {
+        crate::state::test_clock::install();
+        let start = crate::state::test_clock::START_TIME;
+        crate::state::test_clock::set_time(start);
+        let mut vault = Vault { version: 1, platform_share_bps: 5_000, ..funded_vault() };
+        let fee = |bps: u64| UpdateVaultConfigParams { platform_share_bps: Some(bps), ..Default::default() };
+        
+        // Raising: one step of at most 500 bps
+        assert!(matches!(vault.update_config(fee(5_501)), Err(VaultError::InvalidVaultConfig)));
+        vault.update_config(fee(5_500)).unwrap();
+        assert_eq!((vault.platform_share_bps, vault.last_fee_change), (5_500, start));
+        
+        // Back to back: the next step waits for the interval, setting the same fee is no change
+        assert!(matches!(vault.update_config(fee(5_400)), Err(VaultError::InvalidVaultConfig)));
+        vault.update_config(fee(5_500)).unwrap();
+        crate::state::test_clock::set_time(start + MIN_PLATFORM_SHARE_CHANGE_INTERVAL - 1);
+        assert!(matches!(vault.update_config(fee(5_400)), Err(VaultError::InvalidVaultConfig)));
+        
+        // Lowering: bounded the same way
+        crate::state::test_clock::set_time(start + MIN_PLATFORM_SHARE_CHANGE_INTERVAL);
+        assert!(matches!(vault.update_config(fee(4_999)), Err(VaultError::InvalidVaultConfig)));
+        vault.update_config(fee(5_000)).unwrap();
+        assert_eq!((vault.platform_share_bps, vault.last_fee_change), (5_000, start + MIN_PLATFORM_SHARE_CHANGE_INTERVAL));
+    }
+
+    #[test]
+    fn test_management_fee_is_bounded_apart_from_platform_share() {
+        let mut vault = Vault { platform_share_bps: 5_000, ..funded_vault() };
+        let fee = |bps: u64| UpdateVaultConfigParams { management_fee_bps: Some(bps), ..Default::default() };
+
+        assert!(matches!(vault.update_config(fee(MAX_MANAGEMENT_FEE_BPS + 1)), Err(VaultError::InvalidVaultConfig)));
+        // Not stepped like the platform share, and the reward split does not move
+        vault.update_config(fee(MAX_MANAGEMENT_FEE_BPS)).unwrap();
+        vault.update_config(fee(0)).unwrap();
+        assert_eq!((vault.management_fee_bps, vault.platform_share_bps, vault.last_fee_change), (0, 5_000, 0));
+    }
+
+    #[test]
+    fn test_signer_seeds_derive_the_vault_with_the_stored_bump() {
+        let mut name = [0u8; 32];
+        name[..10].copy_from_slice(b"FOCX_Vault");
+        let (vault_key, bump) = Pubkey::find_program_address(&[b"vault", &name], &crate::ID);
+
+        // Vault-signed CPIs use the stored bump only, no runtime search
+        let vault = Vault { name, bump, ..Vault::default() };
+        assert_eq!(Pubkey::create_program_address(&vault.get_signer_seeds(), &crate::ID).unwrap(), vault_key);
+
+        // A wrong stored bump signs for another address, or none, until `repair_bump` fixes it
+        let broken = Vault { bump: bump.wrapping_sub(1), ..vault.clone() };
+        assert_ne!(Pubkey::create_program_address(&broken.get_signer_seeds(), &crate::ID).ok(), Some(vault_key));
+    }
+
+    #[test]
+    fn test_migrate_layout_waits_for_legacy_owner_shares() {
+        // Accounts older than the current layout do not deserialize until grown
+        let data = legacy_fixture();
+        let legacy_len = 8 + 32 * 6 + 8 * 3 + 16 + 8 * 5 + 1 + 8 + 4 * 2 + 8 * 3 + 1; // through bump
+        assert!(Vault::try_deserialize(&mut &data[..legacy_len]).is_err());
+
+        let mut vault = Vault::try_deserialize(&mut data.as_slice()).unwrap();
+        assert_eq!(vault.migrate_layout().unwrap(), 0);
+        assert_eq!(vault.version, 0);
+
+        vault.owner_shares = 0;
+        assert_eq!(vault.migrate_layout().unwrap(), 0);
+        assert_eq!(vault.version, VAULT_VERSION);
+        assert_eq!(vault.migrate_layout().unwrap(), VAULT_VERSION);
+
+        // A layout from a newer program is never interpreted
+        vault.version = VAULT_VERSION + 1;
+        assert!(matches!(vault.migrate_layout(), Err(VaultError::UnsupportedVaultVersion)));
+        assert!(matches!(vault.verify_invariants(), Err(VaultError::UnsupportedVaultVersion)));
+    }
+
+    #[test]
+    fn test_depositor_migration_keeps_shares_and_pending_request() {
+        // Diluted far enough to rebase, the depositor holds half of the shares
+        let mut vault = Vault { total_shares: 1_000_000_000, ..funded_vault() };
+        let mut depositor = VaultDepositor { shares: 500_000_000, ..VaultDepositor::default() };
+
+        // Mid-lifecycle: a rebase synced the way the baseline did, by the whole shares_base and
+        // without last_shares_base, then a request
+        vault.apply_rebase().unwrap();
+        depositor.apply_rebase(10u128.pow(vault.shares_base), vault.rebase_version).unwrap();
+        assert_eq!((depositor.shares, depositor.last_shares_base), (vault.total_shares / 2, 0));
+        let (requested_shares, frozen_amount) = depositor.request_unstake(&mut vault, 400, 1_000).unwrap();
+
+        // A v1 account, created before versioning
+        let mut data = Vec::new();
+        depositor.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), VaultDepositor::LEN);
+        assert!(!VaultDepositor::try_deserialize(&mut data.as_slice()).unwrap().has_v2_layout());
+
+        // migrate_depositor: realloc with zeroed bytes, the version stamp and the shares base
+        data.resize(VaultDepositor::LEN_V2, 0);
+        let mut migrated = VaultDepositor::try_deserialize(&mut data.as_slice()).unwrap();
+        migrated.version = DEPOSITOR_VERSION;
+        assert!(migrated.backfill_shares_base(&vault));
+        migrated.try_serialize(&mut data.as_mut_slice()).unwrap();
+        assert!(data[VaultDepositor::LEN..].iter().all(|byte| *byte == 0));
+
+        let mut migrated = VaultDepositor::try_deserialize(&mut data.as_slice()).unwrap();
+        assert!(migrated.has_v2_layout());
+        assert_eq!(migrated.last_shares_base, vault.shares_base);
+        assert_eq!(migrated.shares, depositor.shares);
+        assert_eq!(migrated.unstake_request.shares, requested_shares);
+        assert_eq!(migrated.unstake_request.frozen_amount().unwrap(), frozen_amount);
+
+        // A loss rebases again, the migrated depositor is divided by that rebase only
+        vault.total_assets = vault.reserved_assets + 100;
+        let base_before = vault.shares_base;
+        vault.apply_rebase().unwrap();
+        assert_eq!(vault.rebase_version, 2);
+        let divisor = 10u64.pow(vault.shares_base - base_before);
+        assert!(matches!(depositor.sync_rebase(&mut vault.clone()), Err(VaultError::DepositorNotMigrated)));
+        migrated.sync_rebase(&mut vault).unwrap();
+        assert_eq!(migrated.shares, depositor.shares / divisor);
+
+        // The pending request still pays out once matured
+        let (_, amount) = migrated.execute_unstake(&mut vault, 1_000 + FOURTEEN_DAYS).unwrap();
+        assert_eq!(amount, frozen_amount);
+    }
+
+    #[test]
+    fn test_active_depositor_count_follows_zero_transitions() {
+        let mut vault = funded_vault();
+        vault.record_depositor_opened().unwrap();
+        vault.record_depositor_opened().unwrap();
+        assert_eq!(vault.depositor_count, 2);
+
+        vault.record_active_shares_change(0, 100);
+        vault.record_active_shares_change(0, 50);
+        vault.record_active_shares_change(100, 40);
+        assert_eq!(vault.active_depositor_count, 2);
+
+        vault.record_active_shares_change(40, 0);
+        assert_eq!(vault.active_depositor_count, 1);
+
+        // A legacy position emptied after the upgrade never went through the counter
+        vault.record_active_shares_change(50, 0);
+        vault.record_active_shares_change(30, 0);
+        assert_eq!(vault.active_depositor_count, 0);
+    }
+
+    #[test]
+    fn test_cost_basis_follows_requests_and_realizes_a_loss() {
+        let mut vault = funded_vault();
+        // 500 shares bought for 1_200 tokens, now worth 1_000
+        let mut depositor = VaultDepositor { shares: 500, ..VaultDepositor::default() };
+        depositor.record_staked(1_200).unwrap();
+
+        // A cancelled request hands its cost basis back
+        depositor.request_unstake(&mut vault, 400, 1_000).unwrap();
+        assert_eq!((depositor.cost_basis, depositor.unstake_cost_basis), (720, 480));
+        depositor.cancel_unstake(&mut vault).unwrap();
+        assert_eq!((depositor.cost_basis, depositor.unstake_cost_basis), (1_200, 0));
+
+        let (_, amount) = depositor.request_unstake(&mut vault, 400, 2_000).unwrap();
+        let (_, received) = depositor.execute_unstake(&mut vault, 2_000 + FOURTEEN_DAYS).unwrap();
+        assert_eq!(received, amount);
+        assert_eq!(depositor.realized_pnl, 400 - 480);
+        assert_eq!((depositor.cost_basis, depositor.unstake_cost_basis), (720, 0));
+
+        // Exiting the rest at a profit offsets the loss
+        vault.total_assets = vault.total_assets.safe_add(600).unwrap();
+        depositor.request_unstake_all(&mut vault, 3_000_000).unwrap();
+        let (_, payout) = depositor.execute_unstake(&mut vault, 3_000_000 + FOURTEEN_DAYS).unwrap();
+        assert!(payout > 720);
+        assert_eq!(depositor.cost_basis, 0);
+        assert_eq!(depositor.realized_pnl, 400 - 480 + payout as i64 - 720);
+    }
+
+    #[test]
+    fn test_checkpoint_interval_bounds_and_legacy_default() {
+        let mut vault = funded_vault();
+        assert_eq!(vault.effective_checkpoint_interval(), DEFAULT_CHECKPOINT_INTERVAL);
+
+        for interval in [MIN_CHECKPOINT_INTERVAL - 1, MAX_CHECKPOINT_INTERVAL + 1, 0] {
+            let params = UpdateVaultConfigParams { checkpoint_interval: Some(interval), ..Default::default() };
+            assert!(matches!(vault.update_config(params), Err(VaultError::InvalidVaultConfig)));
+        }
+        let params = UpdateVaultConfigParams { checkpoint_interval: Some(ONE_WEEK), ..Default::default() };
+        vault.update_config(params).unwrap();
+        assert_eq!(vault.effective_checkpoint_interval(), ONE_WEEK);
+    }
+
+    #[test]
+    fn test_cumulative_return_moves_only_with_rewards() {
+        crate::state::test_clock::install();
+        let mut vault = Vault { cumulative_return: SHARE_PRECISION, max_total_assets: u64::MAX, ..funded_vault() };
+
+        // 2_000 assets earning 200 is a 10% growth
+        vault.add_rewards(200).unwrap();
+        assert_eq!(vault.cumulative_return, SHARE_PRECISION * 11 / 10);
+        assert_eq!(vault.lifetime_rewards_per_active_share, SHARE_PRECISION / 5);
+
+        // Money in and out changes the assets, not the return
+        let (cumulative, per_share) = (vault.cumulative_return, vault.lifetime_rewards_per_active_share);
+        let shares = vault.stake(1_100, 0).unwrap();
+        let mut depositor = VaultDepositor { shares, ..VaultDepositor::default() };
+        depositor.request_unstake(&mut vault, 550, 1_000).unwrap();
+        depositor.execute_unstake(&mut vault, 1_000 + FOURTEEN_DAYS).unwrap();
+        assert_eq!((vault.cumulative_return, vault.lifetime_rewards_per_active_share), (cumulative, per_share));
+
+        // A second 10% compounds on the first
+        let available = vault.get_available_assets().unwrap();
+        vault.add_rewards(available / 10).unwrap();
+        assert_eq!(vault.cumulative_return, SHARE_PRECISION * 121 / 100);
+    }
+
+    #[test]
+    fn test_secondary_rewards_follow_active_shares_only() {
+        crate::state::test_clock::install();
+        let mut vault = Vault { max_total_assets: u64::MAX, ..funded_vault() };
+        let mut a = VaultDepositor { shares: 600, ..VaultDepositor::default() };
+        let mut b = VaultDepositor { shares: 400, ..VaultDepositor::default() };
+        assert!(matches!(vault.add_secondary_rewards(1_000), Err(VaultError::SecondaryRewardsNotEnabled)));
+
+        vault.secondary_reward_mint = Pubkey::new_unique();
+        vault.add_secondary_rewards(1_000).unwrap();
+        assert_eq!(a.calculate_pending_rewards(vault.secondary_rewards_per_share).unwrap(), 600);
+
+        // A stake after the distribution earns none of it
+        let shares = vault.stake(1_000, 0).unwrap();
+        let mut c = VaultDepositor::default();
+        c.stake(shares, vault.secondary_rewards_per_share).unwrap();
+        assert_eq!(c.calculate_pending_rewards(vault.secondary_rewards_per_share).unwrap(), 0);
+
+        // Half of `a` goes pending unstake and stops earning
+        let (frozen, _) = a.request_unstake(&mut vault, 600, 1_000).unwrap();
+        assert_eq!((frozen, a.shares, a.secondary_rewards_owed), (300, 300, 600));
+        vault.add_secondary_rewards(1_200).unwrap();
+
+        let claimed: Vec<u64> = [&mut a, &mut b, &mut c]
+            .into_iter()
+            .map(|depositor| depositor.take_secondary_rewards(vault.secondary_rewards_per_share).unwrap())
+            .collect();
+        assert_eq!(claimed, vec![600 + 300, 400 + 400, 500]);
+        assert_eq!(claimed.iter().sum::<u64>(), vault.total_secondary_rewards);
+        assert!(matches!(
+            a.take_secondary_rewards(vault.secondary_rewards_per_share),
+            Err(VaultError::NoSecondaryRewards)
+        ));
+    }
 }
@@ -0,0 +1,183 @@
+use anchor_lang::prelude::*;
+
+/// Entries kept by `VaultCheckpoints`, over seven months of daily checkpoints. 365 would not
+/// fit the 10 KiB an account created inside an instruction can have
+pub const CHECKPOINT_CAPACITY: usize = 230;
+
+/// The vault's share value at one point in time
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct ShareValueCheckpoint {
+    /// When the checkpoint was written
+    pub timestamp: i64,
+    /// Value of one active share, scaled by PRECISION
+    pub active_share_value: u128,
+    /// The vault's total_assets
+    pub total_assets: u64,
+    /// The vault's total_shares
+    pub total_shares: u64,
+    /// The vault's shares_base. A rebase multiplies the share value by 10 per step, so values
+    /// only compare once scaled by `10^-shares_base`
+    pub shares_base: u32,
+}
+
+impl ShareValueCheckpoint {
+    pub const LEN: usize = 8 + // timestamp
+        16 + // active_share_value
+        8 + // total_assets
+        8 + // total_shares
+        4; // shares_base
+}
+
+/// Ring buffer of share value checkpoints of one vault, so APY can be read from chain
+/// instead of replaying transactions. Optional, created by `initialize_vault_checkpoints`
+#[account]
+pub struct VaultCheckpoints {
+    /// The vault the checkpoints belong to
+    pub vault: Pubkey,
+    /// Slot the next checkpoint is written to
+    pub next_index: u16,
+    /// Slots written so far, at most CHECKPOINT_CAPACITY
+    pub len: u16,
+    /// Bump seed for PDA
+    pub bump: u8,
+    /// The checkpoints, oldest overwritten first once full
+    pub checkpoints: [ShareValueCheckpoint; CHECKPOINT_CAPACITY],
+}
+
+impl Default for VaultCheckpoints {
+    fn default() -> Self {
+        Self {
+            vault: Pubkey::default(),
+            next_index: 0,
+            len: 0,
+            bump: 0,
+            checkpoints: [ShareValueCheckpoint::default(); CHECKPOINT_CAPACITY],
+        }
+    }
+}
+
+// MAX_PERMITTED_DATA_INCREASE, the most an account created by CPI can hold
+static_assertions::const_assert!(VaultCheckpoints::LEN <= 10_240);
+
+impl VaultCheckpoints {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // vault
+        2 + // next_index
+        2 + // len
+        1 + // bump
+        ShareValueCheckpoint::LEN * CHECKPOINT_CAPACITY; // checkpoints
+
+    /// The most recent checkpoint
+    pub fn latest(&self) -> Option<&ShareValueCheckpoint> {
+        if self.len == 0 {
+            return None;
+        }
+        let index = (self.next_index as usize + CHECKPOINT_CAPACITY - 1) % CHECKPOINT_CAPACITY;
+        Some(&self.checkpoints[index])
+    }
+
+    /// Whether a checkpoint at `timestamp` is at least `interval` after the latest one
+    pub fn is_due(&self, timestamp: i64, interval: i64) -> bool {
+        match self.latest() {
+            Some(latest) => timestamp >= latest.timestamp.saturating_add(interval),
+            None => true,
+        }
+    }
+
+    /// Append `checkpoint` when due, overwriting the oldest once full. Returns the slot
+    /// written, None when the interval since the latest checkpoint has not passed
+    pub fn record(&mut self, checkpoint: ShareValueCheckpoint, interval: i64) -> Option<usize> {
+        if !self.is_due(checkpoint.timestamp, interval) {
+            return None;
+        }
+
+        let index = self.next_index as usize;
+        self.checkpoints[index] = checkpoint;
+        self.next_index = ((index + 1) % CHECKPOINT_CAPACITY) as u16;
+        if (self.len as usize) < CHECKPOINT_CAPACITY {
+            self.len += 1;
+        }
+
+        Some(index)
+    }
+
+    /// Checkpoints from oldest to newest
+    pub fn iter_chronological(&self) -> impl Iterator<Item = &ShareValueCheckpoint> {
+        let len = self.len as usize;
+        let start = (self.next_index as usize + CHECKPOINT_CAPACITY - len) % CHECKPOINT_CAPACITY;
+        (0..len).map(move |offset| &self.checkpoints[(start + offset) % CHECKPOINT_CAPACITY])
+    }
+
+    /// The newest checkpoint written at or before `timestamp`. Periods without checkpoints
+    /// fall back to the last one before them
+    pub fn at_or_before(&self, timestamp: i64) -> Option<&ShareValueCheckpoint> {
+        self.iter_chronological()
+            .take_while(|checkpoint| checkpoint.timestamp <= timestamp)
+            .last()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::ONE_DAY;
+
+    fn checkpoint(day: i64) -> ShareValueCheckpoint {
+        ShareValueCheckpoint {
+            timestamp: day * ONE_DAY,
+            active_share_value: 1_000 + day as u128,
+            total_assets: 1_000,
+            total_shares: 1_000,
+            shares_base: 0,
+        }
+    }
+
+    #[test]
+    fn test_record_respects_interval() {
+        let mut checkpoints = VaultCheckpoints::default();
+        assert!(checkpoints.latest().is_none());
+
+        assert_eq!(checkpoints.record(checkpoint(1), ONE_DAY), Some(0));
+        let early = ShareValueCheckpoint { timestamp: 2 * ONE_DAY - 1, ..checkpoint(2) };
+        assert_eq!(checkpoints.record(early, ONE_DAY), None);
+        assert_eq!(checkpoints.record(checkpoint(2), ONE_DAY), Some(1));
+        assert_eq!(checkpoints.len, 2);
+        assert_eq!(checkpoints.latest(), Some(&checkpoint(2)));
+    }
+
+    #[test]
+    fn test_ring_buffer_wraps_oldest_first() {
+        let mut checkpoints = VaultCheckpoints::default();
+        let total = CHECKPOINT_CAPACITY as i64 + 5;
+        for day in 0..total {
+            checkpoints.record(checkpoint(day), ONE_DAY).unwrap();
+        }
+
+        assert_eq!(checkpoints.len as usize, CHECKPOINT_CAPACITY);
+        assert_eq!(checkpoints.next_index, 5);
+        assert_eq!(checkpoints.latest(), Some(&checkpoint(total - 1)));
+
+        // The five oldest days were overwritten, the rest come back in order
+        let days: Vec<i64> = checkpoints.iter_chronological().map(|c| c.timestamp / ONE_DAY).collect();
+        assert_eq!(days.len(), CHECKPOINT_CAPACITY);
+        assert_eq!(days[0], 5);
+        assert!(days.windows(2).all(|pair| pair[1] == pair[0] + 1));
+        assert!(checkpoints.at_or_before(4 * ONE_DAY).is_none());
+        assert_eq!(checkpoints.at_or_before(5 * ONE_DAY), Some(&checkpoint(5)));
+    }
+
+    #[test]
+    fn test_sparse_periods_fall_back_to_earlier_checkpoint() {
+        let mut checkpoints = VaultCheckpoints::default();
+        for day in [0, 1, 9, 30] {
+            checkpoints.record(checkpoint(day), ONE_DAY).unwrap();
+        }
+
+        // Nobody cranked between day 1 and day 9
+        assert_eq!(checkpoints.at_or_before(5 * ONE_DAY), Some(&checkpoint(1)));
+        assert_eq!(checkpoints.at_or_before(9 * ONE_DAY), Some(&checkpoint(9)));
+        assert_eq!(checkpoints.at_or_before(29 * ONE_DAY), Some(&checkpoint(9)));
+        assert_eq!(checkpoints.at_or_before(100 * ONE_DAY), Some(&checkpoint(30)));
+        assert!(checkpoints.at_or_before(-1).is_none());
+    }
+}
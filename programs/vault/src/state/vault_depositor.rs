@@ -2,9 +2,31 @@ use anchor_lang::prelude::*;
 use crate::constants::*;
 use crate::error::*;
 use crate::utils::*;
-use crate::state::UnstakeRequest;
+use crate::state::{UnstakeRequest, Vault};
 use crate::math::{SafeMath, SafeCast, vault_math};
 
+/// A rebase that rounded a depositor's shares up to the 1-share floor
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PrecisionFloor {
+    /// Shares before the rebase
+    pub original_shares: u64,
+    /// Shares the division produced
+    pub computed_shares: u64,
+    /// Shares actually granted
+    pub granted_shares: u64,
+    /// Divisor of the rebase
+    pub rebase_divisor: u128,
+}
+
+/// Outcome of syncing a depositor with the vault's rebase
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct RebaseSync {
+    /// False when the depositor was already synced
+    pub synced: bool,
+    /// Set when the 1-share precision floor kicked in
+    pub floor: Option<PrecisionFloor>,
+}
+
 #[account]
 #[derive(Default)]
 pub struct VaultDepositor {
@@ -14,7 +36,8 @@ pub struct VaultDepositor {
     pub authority: Pubkey,
     /// The depositor's shares
     pub shares: u64,
-    /// The depositor's rewards debt (for reward calculation)
+    /// The vault's `secondary_rewards_per_share` when the secondary rewards of the active
+    /// shares were last settled into `secondary_rewards_owed`
     pub rewards_debt: u128,
     /// Last time rewards were claimed
     pub last_rewards_claim: i64,
@@ -32,8 +55,22 @@ pub struct VaultDepositor {
     pub last_rebase_version: u32,
     /// Last time user staked (for MEV protection)
     pub last_stake_time: i64,
+    /// Vault shares_base at the last sync (0 on accounts that never synced a rebase)
+    pub last_shares_base: u32,
+    /// Account layout version (0 = `LEN` bytes, created before versioning)
+    pub version: u8,
+    pub _padding: [u8; 3],
+    /// Tokens staked into the active shares, less the part released by exits
+    /// (0 on positions opened before cost basis was tracked)
+    pub cost_basis: u64,
+    /// Cost basis that moved with the shares of the pending unstake request
+    pub unstake_cost_basis: u64,
+    /// Tokens received on exits less the cost basis they released, negative at a loss
+    pub realized_pnl: i64,
+    /// Secondary reward tokens settled to the depositor and not claimed yet
+    pub secondary_rewards_owed: u64,
     /// Reserved for future use
-    pub _reserved: [u64; 6],
+    pub _reserved: [u32; 2],
 }
 
 impl VaultDepositor {
@@ -50,7 +87,20 @@ impl VaultDepositor {
         8 + // created_at
         4 + // last_rebase_version
         8 + // last_stake_time
-        48; // _reserved
+        4 + // last_shares_base
+        1 + // version
+        3 + // _padding
+        8 + // cost_basis
+        8 + // unstake_cost_basis
+        8 + // realized_pnl
+        8 + // secondary_rewards_owed
+        8; // _reserved
+
+    /// Size of the v2 layout: the fields above followed by `V2_EXTENSION_LEN` bytes, zeroed by
+    /// `initialize_vault_depositor` or `migrate_depositor`, for the features that outgrew
+    /// `_reserved` (further unstake requests, a delegate). Trailing bytes are not
+    /// deserialized, so instructions work on accounts of either size
+    pub const LEN_V2: usize = Self::LEN + V2_EXTENSION_LEN;
 
     pub fn initialize(
         &mut self,
@@ -69,51 +119,118 @@ impl VaultDepositor {
         self.created_at = get_current_timestamp();
         self.last_rebase_version = 0;
         self.last_stake_time = 0;
+        self.last_shares_base = 0;
+        self.version = DEPOSITOR_VERSION;
+        self.cost_basis = 0;
+        self.unstake_cost_basis = 0;
+        self.realized_pnl = 0;
+        self.secondary_rewards_owed = 0;
         
         Ok(())
     }
 
-    pub fn stake(&mut self, shares: u64, _rewards_per_share: u128) -> VaultResult<()> {
-        // Add new shares - with automatic compounding, no need to track rewards debt
+    /// Whether the account has the `LEN_V2` bytes later features store their fields in
+    pub fn has_v2_layout(&self) -> bool {
+        self.version >= DEPOSITOR_VERSION
+    }
+
+    /// Add `shares` to the position. `rewards_per_share` is the vault's
+    /// `secondary_rewards_per_share`: the new shares earn no secondary reward added before
+    pub fn stake(&mut self, shares: u64, rewards_per_share: u128) -> VaultResult<()> {
+        // Primary rewards compound into the share value, only the secondary ones need a debt
+        self.settle_secondary_rewards(rewards_per_share)?;
         self.shares = self.shares.safe_add(shares)?;
         
         // MEV PROTECTION: Record stake time to prevent same-block unstake
         self.last_stake_time = get_current_timestamp();
         
-        // Note: rewards_per_share is ignored in the new compounding model
-        // Rewards are automatically compounded into the vault's total_assets
-        
         Ok(())
     }
 
-    pub fn unstake(&mut self, shares: u64, _rewards_per_share: u128) -> VaultResult<()> {
+    /// Record the tokens a stake put into the position, net of transfer fees
+    pub fn record_staked(&mut self, received: u64) -> VaultResult<()> {
+        self.total_staked = self.total_staked.safe_add(received)?;
+        self.cost_basis = self.cost_basis.safe_add(received)?;
+        Ok(())
+    }
+
+    /// Take the share of `cost_basis` that `shares` of the `shares_held` active shares carry
+    pub fn release_cost_basis(&mut self, shares: u64, shares_held: u64) -> VaultResult<u64> {
+        let released = vault_math::calculate_assets(shares, shares_held, self.cost_basis)?;
+        self.cost_basis = self.cost_basis.safe_sub(released)?;
+        Ok(released)
+    }
+
+    /// Book an exit that paid `proceeds` for shares bought at `cost_basis`
+    pub fn realize_pnl(&mut self, proceeds: u64, cost_basis: u64) -> VaultResult<()> {
+        let proceeds: i64 = proceeds.safe_cast()?;
+        let cost_basis: i64 = cost_basis.safe_cast()?;
+        self.realized_pnl = self.realized_pnl.safe_add(proceeds.safe_sub(cost_basis)?)?;
+        Ok(())
+    }
+
+    pub fn unstake(&mut self, shares: u64, vault: &Vault) -> VaultResult<()> {
         if shares > self.shares {
             return Err(VaultError::InsufficientFunds);
         }
         
         // MEV PROTECTION: Prevent same-slot stake-unstake sandwich attacks
-        let current_time = get_current_timestamp();
-        const MIN_STAKE_DURATION: i64 = 1; // 1 second for testing (change to 300 for production)
-        if current_time < self.last_stake_time + MIN_STAKE_DURATION {
-            return Err(VaultError::StakeCooldownNotMet);
-        }
+        self.check_stake_cooldown(vault, get_current_timestamp())?;
         
-        // Reduce shares - with automatic compounding, no need to track rewards debt
+        self.settle_secondary_rewards(vault.secondary_rewards_per_share)?;
         self.shares = self.shares.safe_sub(shares)?;
         
-        // Note: rewards_per_share is ignored in the new compounding model
-        // User automatically benefits from compounded rewards through share value appreciation
-        
         Ok(())
     }
 
-    pub fn calculate_pending_rewards(&self, _rewards_per_share: u128) -> VaultResult<u64> {
-        // In the new compounding model, there are no separate pending rewards
-        // All rewards are automatically compounded into share value
-        // Users can see their gains by comparing current share value vs initial investment
-        Ok(0)
+    /// Secondary rewards the active shares earned since the last settle, at the vault's
+    /// `secondary_rewards_per_share` of `rewards_per_share`. Primary rewards compound into the
+    /// share value and are never pending
+    pub fn calculate_pending_rewards(&self, rewards_per_share: u128) -> VaultResult<u64> {
+        if self.shares == 0 || rewards_per_share <= self.rewards_debt {
+            return Ok(0);
+        }
+
+        // The accumulator counts shares before any rebase, as of the depositor's last sync
+        let base_shares = 10u128
+            .checked_pow(self.last_shares_base)
+            .ok_or(VaultError::MathOverflow)?
+            .safe_mul(self.shares as u128)?;
+        base_shares
+            .safe_mul(rewards_per_share.safe_sub(self.rewards_debt)?)?
+            .safe_div(SHARE_PRECISION)?
+            .safe_cast()
+    }
+
+    /// Move the pending secondary rewards into `secondary_rewards_owed`. Runs before every
+    /// change of `shares`, so shares only earn what is added while they are active.
+    /// Returns the amount settled
+    pub fn settle_secondary_rewards(&mut self, rewards_per_share: u128) -> VaultResult<u64> {
+        let pending = self.calculate_pending_rewards(rewards_per_share)?;
+        self.secondary_rewards_owed = self.secondary_rewards_owed.safe_add(pending)?;
+        self.rewards_debt = rewards_per_share;
+        Ok(pending)
     }
 
+    /// Settle and hand out everything owed, for `claim_secondary_rewards`
+    pub fn take_secondary_rewards(&mut self, rewards_per_share: u128) -> VaultResult<u64> {
+        self.settle_secondary_rewards(rewards_per_share)?;
+        let owed = self.secondary_rewards_owed;
+        if owed == 0 {
+            return Err(VaultError::NoSecondaryRewards);
+        }
+        self.secondary_rewards_owed = 0;
+        Ok(owed)
+    }
+
+
+    /// Exits are refused until the vault's stake cooldown has passed since the last stake
+    pub fn check_stake_cooldown(&self, vault: &Vault, current_time: i64) -> VaultResult<()> {
+        if current_time < self.last_stake_time.saturating_add(vault.stake_cooldown()) {
+            return Err(VaultError::StakeCooldownNotMet);
+        }
+        Ok(())
+    }
 
     pub fn can_unstake(&self, current_time: i64, lockup_period: i64) -> bool {
         if !self.unstake_request.is_pending() {
@@ -124,18 +241,11 @@ impl VaultDepositor {
     }
 
 
-    /// Legacy function for backward compatibility
-    /// In the new compounding model, rewards debt is not used
-    fn update_rewards_debt(&mut self, _rewards_per_share: u128) -> VaultResult<()> {
-        // No longer needed in the compounding model
-        // Keeping for backward compatibility
-        Ok(())
-    }
-
     /// Apply rebase to user's shares with precision protection and version tracking
-    pub fn apply_rebase(&mut self, rebase_divisor: u128, new_rebase_version: u32) -> VaultResult<()> {
+    /// Returns the details when the 1-share floor was applied to the active shares
+    pub fn apply_rebase(&mut self, rebase_divisor: u128, new_rebase_version: u32) -> VaultResult<Option<PrecisionFloor>> {
         if rebase_divisor <= 1 {
-            return Ok(());
+            return Ok(None);
         }
 
         // Protect against precision loss - ensure user keeps at least 1 share if they had any
@@ -143,29 +253,274 @@ impl VaultDepositor {
         self.shares = (SafeCast::<u128>::safe_cast(&self.shares)?.safe_div(rebase_divisor)?).safe_cast()?;
         
         // If user had shares but rebase reduced them to 0, give them 1 share minimum
+        let mut floor = None;
         if original_shares > 0 && self.shares == 0 {
+            floor = Some(PrecisionFloor {
+                original_shares,
+                computed_shares: self.shares,
+                granted_shares: 1,
+                rebase_divisor,
+            });
             self.shares = 1;
         }
-        
-        // Update unstake request shares if pending
-        if self.unstake_request.is_pending() {
-            let original_request_shares = self.unstake_request.shares;
-            self.unstake_request.shares = (SafeCast::<u128>::safe_cast(&self.unstake_request.shares)?.safe_div(rebase_divisor)?).safe_cast()?;
-            
-            // Apply same precision protection to unstake request
-            if original_request_shares > 0 && self.unstake_request.shares == 0 {
-                self.unstake_request.shares = 1;
-            }
-        }
 
         // Update rebase version to prevent race conditions
         self.last_rebase_version = new_rebase_version;
 
+        Ok(floor)
+    }
+
+    /// Freeze exactly `amount` assets of the position for unstaking at today's share value.
+    /// Fails with `UnstakeRequestAlreadyExists` while a request is pending: replacing it would
+    /// restart its lockup, it has to be executed or cancelled first.
+    /// Returns (shares, frozen_amount)
+    pub fn request_unstake(&mut self, vault: &mut Vault, amount: u64, current_time: i64) -> VaultResult<(u64, u64)> {
+        if amount == 0 {
+            return Err(VaultError::InvalidAmount);
+        }
+
+        self.prepare_request(vault, current_time)?;
+        let frozen = vault.freeze_unstake(amount, self.shares)?;
+        vault.last_activity = current_time;
+        self.record_request(frozen, current_time)
+    }
+
+    /// Freeze every active share for unstaking at today's share value, as `request_unstake`
+    /// does. Returns (shares, frozen_amount)
+    pub fn request_unstake_all(&mut self, vault: &mut Vault, current_time: i64) -> VaultResult<(u64, u64)> {
+        self.prepare_request(vault, current_time)?;
+        let frozen = vault.freeze_unstake_all(self.shares)?;
+        vault.last_activity = current_time;
+        self.record_request(frozen, current_time)
+    }
+
+    /// Checks every request passes before its shares are frozen, settling their secondary rewards
+    fn prepare_request(&mut self, vault: &Vault, current_time: i64) -> VaultResult<()> {
+        if self.unstake_request.is_pending() {
+            return Err(VaultError::UnstakeRequestAlreadyExists);
+        }
+
+        // Check if there are any active shares to provide a price reference
+        if vault.get_active_shares()? == 0 {
+            return Err(VaultError::NoActiveShares);
+        }
+
+        // MEV PROTECTION: Apply same cooldown to request_unstake
+        self.check_stake_cooldown(vault, current_time)?;
+
+        // Shares frozen for unstaking stop earning secondary rewards
+        self.settle_secondary_rewards(vault.secondary_rewards_per_share)?;
         Ok(())
     }
 
+    /// Move the shares frozen by `Vault::freeze_unstake` from the position into the request.
+    /// Returns (shares, frozen_amount)
+    fn record_request(&mut self, (shares, frozen_amount, asset_per_share): (u64, u64, u128), current_time: i64) -> VaultResult<(u64, u64)> {
+        self.unstake_cost_basis = self.release_cost_basis(shares, self.shares)?;
+        self.shares = self.shares.safe_sub(shares)?;
+
+        self.unstake_request.shares = shares;
+        self.unstake_request.request_time = current_time;
+        self.unstake_request.asset_per_share_at_request = asset_per_share;
+
+        Ok((shares, frozen_amount))
+    }
+
+    /// Freeze the `shares` a term stake just minted, in the same instruction and so at the
+    /// same share value. The stake cooldown is skipped on purpose: a stake and a request
+    /// priced identically leave no value to sandwich. Returns the frozen amount
+    pub fn request_term_unstake(&mut self, vault: &mut Vault, shares: u64, current_time: i64) -> VaultResult<u64> {
+        // Merging into an existing request would restart its lockup
+        if self.unstake_request.is_pending() {
+            return Err(VaultError::UnstakeRequestAlreadyExists);
+        }
+
+        self.settle_secondary_rewards(vault.secondary_rewards_per_share)?;
+        let frozen = vault.freeze_unstake_all(shares)?;
+        let (_, frozen_amount) = self.record_request(frozen, current_time)?;
+
+        Ok(frozen_amount)
+    }
+
+    /// Cancel the pending request and return its frozen assets to the active pool.
+    /// Returns (frozen_amount, restored_shares)
+    pub fn cancel_unstake(&mut self, vault: &mut Vault) -> VaultResult<(u64, u64)> {
+        vault.check_not_paused(PAUSE_CANCEL)?;
+
+        if !self.unstake_request.is_pending() {
+            return Err(VaultError::NoUnstakeRequest);
+        }
+
+        self.settle_secondary_rewards(vault.secondary_rewards_per_share)?;
+        let frozen_amount = self.unstake_request.frozen_amount()?;
+        let restored_shares = vault.restore_unstake(&self.unstake_request)?;
+        self.shares = self.shares.safe_add(restored_shares)?;
+        self.restore_unstake_cost_basis()?;
+        self.unstake_request.reset();
+
+        Ok((frozen_amount, restored_shares))
+    }
+
+    /// Return the pending request's cost basis to the active shares it is restored into
+    fn restore_unstake_cost_basis(&mut self) -> VaultResult<()> {
+        self.cost_basis = self.cost_basis.safe_add(self.unstake_cost_basis)?;
+        self.unstake_cost_basis = 0;
+        Ok(())
+    }
+
+    /// Restore a matured request left uncollected past `vault.unstake_request_expiry`, exactly
+    /// as `cancel_unstake` would. Returns (frozen amount, restored shares)
+    pub fn expire_unstake(&mut self, vault: &mut Vault, current_time: i64) -> VaultResult<(u64, u64)> {
+        if !self.unstake_request.is_pending() {
+            return Err(VaultError::NoUnstakeRequest);
+        }
+        if !vault.is_unstake_request_expired(self.unstake_request.request_time, current_time) {
+            return Err(VaultError::UnstakeRequestNotExpired);
+        }
+
+        self.cancel_unstake(vault)
+    }
+
+    /// Price an instant exit of exactly `amount` assets from the active shares, without
+    /// changing any state. Returns (shares, payout, penalty)
+    pub fn quote_instant_unstake(&self, vault: &Vault, amount: u64, current_time: i64) -> VaultResult<(u64, u64, u64)> {
+        // MEV PROTECTION: Apply same cooldown to instant unstake
+        self.check_stake_cooldown(vault, current_time)?;
+
+        vault.instant_unstake_quote(amount, self.shares)
+    }
+
+    /// Price an instant exit of every active share. Returns (shares, payout, penalty)
+    pub fn quote_instant_unstake_all(&self, vault: &Vault, current_time: i64) -> VaultResult<(u64, u64, u64)> {
+        self.check_stake_cooldown(vault, current_time)?;
+
+        vault.instant_unstake_all_quote(self.shares)
+    }
+
+    /// Burn `shares` from the active position for `payout`, as priced by `quote_instant_unstake`.
+    /// A pending request is left untouched
+    pub fn execute_instant_unstake(&mut self, vault: &mut Vault, shares: u64, payout: u64) -> VaultResult<()> {
+        self.settle_secondary_rewards(vault.secondary_rewards_per_share)?;
+        let released = self.release_cost_basis(shares, self.shares)?;
+        self.shares = self.shares.safe_sub(shares)?;
+        vault.instant_unstake(shares, payout)?;
+        self.total_unstaked = self.total_unstaked.safe_add(payout)?;
+        self.realize_pnl(payout, released)?;
+
+        Ok(())
+    }
+
+    /// Settle the pending request once the vault's effective lockup has passed.
+    /// Returns (shares, amount) where amount is the frozen amount less the withdrawal fee
+    pub fn execute_unstake(&mut self, vault: &mut Vault, current_time: i64) -> VaultResult<(u64, u64)> {
+        let lockup_period = vault.effective_unstake_lockup(current_time);
+        if !self.can_unstake(current_time, lockup_period) {
+            return Err(VaultError::UnstakeLockupNotFinished);
+        }
+
+        let shares = self.unstake_request.shares;
+        let (amount, _) = vault.complete_unstake(&self.unstake_request)?;
+
+        // Shares already left the depositor's balance at request time
+        self.total_unstaked = self.total_unstaked.safe_add(amount)?;
+        self.realize_pnl(amount, self.unstake_cost_basis)?;
+        self.unstake_cost_basis = 0;
+        self.unstake_request.reset();
+        vault.last_activity = current_time;
+
+        Ok((shares, amount))
+    }
+
+    /// Bring the depositor's shares in line with the vault's rebase version
+    pub fn sync_rebase(&mut self, vault: &mut Vault) -> VaultResult<RebaseSync> {
+        if !self.needs_rebase_sync(vault.rebase_version) {
+            return Ok(RebaseSync::default());
+        }
+        // Depositors from before `last_shares_base` synced earlier rebases by the whole base,
+        // the base they are in is unknown until `migrate_depositor` fills it in
+        if self.last_rebase_version > 0 && self.last_shares_base == 0 && vault.shares_base > 0 {
+            return Err(VaultError::DepositorNotMigrated);
+        }
+
+        // Only divide by the rebases that happened since the last sync
+        let expo_diff = vault.shares_base.safe_sub(self.last_shares_base)?;
+        let mut floor = None;
+        if expo_diff > 0 {
+            // Settled in the old share base, which `calculate_pending_rewards` reads
+            self.settle_secondary_rewards(vault.secondary_rewards_per_share)?;
+            let rebase_divisor = 10u128.pow(expo_diff);
+            floor = self.apply_rebase(rebase_divisor, vault.rebase_version)?;
+            // The frozen payout of the request does not change, so a floor on its shares
+            // moves no value and is not reported. The vault counts the added share as pending
+            let floor_shares = self.unstake_request.rescale(rebase_divisor)?;
+            vault.count_pending_floor(floor_shares)?;
+        } else {
+            // Even if no shares_base change, update the version to prevent unnecessary sync calls
+            self.last_rebase_version = vault.rebase_version;
+        }
+        self.last_shares_base = vault.shares_base;
+
+        Ok(RebaseSync { synced: true, floor })
+    }
+
+    /// Fill in `last_shares_base` on a depositor created before the field. Only a depositor
+    /// synced with the vault's latest rebase is known to be in its current base. Returns
+    /// whether it did
+    pub fn backfill_shares_base(&mut self, vault: &Vault) -> bool {
+        if self.last_rebase_version != vault.rebase_version {
+            return false;
+        }
+        self.last_shares_base = vault.shares_base;
+        true
+    }
+
     /// Check if user needs to sync with vault rebase
     pub fn needs_rebase_sync(&self, vault_rebase_version: u32) -> bool {
         self.last_rebase_version < vault_rebase_version
     }
+
+    /// Move exactly `shares` active shares to `recipient` at face value.
+    /// Shares frozen in an unstake request stay behind. The recipient keeps the later of both
+    /// stake times, so a transfer cannot shorten the stake cooldown. Returns the shares moved
+    pub fn transfer_shares(&mut self, recipient: &mut VaultDepositor, vault: &Vault, shares: u64) -> VaultResult<u64> {
+        if shares == 0 {
+            return Err(VaultError::InvalidAmount);
+        }
+        // Moving the whole position is `transfer_shares_all`
+        if shares == u64::MAX {
+            return Err(VaultError::AmbiguousMaxAmount);
+        }
+
+        self.move_shares(recipient, vault, shares)
+    }
+
+    /// Move every active share to `recipient`, as `transfer_shares` does. Returns the shares moved
+    pub fn transfer_shares_all(&mut self, recipient: &mut VaultDepositor, vault: &Vault) -> VaultResult<u64> {
+        self.move_shares(recipient, vault, self.shares)
+    }
+
+    /// Move `shares` of the active position, the share count already decided by the caller
+    fn move_shares(&mut self, recipient: &mut VaultDepositor, vault: &Vault, shares: u64) -> VaultResult<u64> {
+        // Shares of different rebase versions are different units
+        if self.needs_rebase_sync(vault.rebase_version) || recipient.needs_rebase_sync(vault.rebase_version) {
+            return Err(VaultError::RebaseSyncRequired);
+        }
+
+        if shares == 0 || shares > self.shares {
+            return Err(VaultError::InsufficientFunds);
+        }
+
+        // Secondary rewards earned so far stay with the sender
+        self.settle_secondary_rewards(vault.secondary_rewards_per_share)?;
+        recipient.settle_secondary_rewards(vault.secondary_rewards_per_share)?;
+
+        // Cost basis travels with the shares, nothing is realized
+        let released = self.release_cost_basis(shares, self.shares)?;
+        recipient.cost_basis = recipient.cost_basis.safe_add(released)?;
+        self.shares = self.shares.safe_sub(shares)?;
+        recipient.shares = recipient.shares.safe_add(shares)?;
+        recipient.last_stake_time = recipient.last_stake_time.max(self.last_stake_time);
+
+        Ok(shares)
+    }
 }
\ No newline at end of file
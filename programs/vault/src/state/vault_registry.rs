@@ -0,0 +1,97 @@
+use anchor_lang::prelude::*;
+use crate::error::*;
+
+/// Vaults listed by one `VaultRegistry` page. A page grows by 32 bytes per vault, this keeps
+/// a full page within the 10 KiB an account can be grown to inside one instruction
+pub const MAX_REGISTRY_PAGE_VAULTS: usize = 256;
+
+/// Page of the list of every vault, so clients can discover vaults without knowing their
+/// names or scanning program accounts. Created by `initialize_registry`
+/// Seeds: `[b"vault_registry", page (u16 little endian)]`
+#[account]
+#[derive(Default)]
+pub struct VaultRegistry {
+    /// Index of this page
+    pub page: u16,
+    /// Bump seed for PDA
+    pub bump: u8,
+    /// Vaults registered on this page, in no particular order
+    pub vaults: Vec<Pubkey>,
+}
+
+impl VaultRegistry {
+    /// Space of a page listing `vault_count` vaults
+    pub const fn space(vault_count: usize) -> usize {
+        8 + // discriminator
+        2 + // page
+        1 + // bump
+        4 + 32 * vault_count // vaults
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.vaults.len() >= MAX_REGISTRY_PAGE_VAULTS
+    }
+
+    /// Append `vault`, the caller grows the account to `space(vaults.len())` first
+    pub fn register(&mut self, vault: Pubkey) -> VaultResult<()> {
+        if self.is_full() {
+            return Err(VaultError::RegistryPageFull);
+        }
+        if self.vaults.contains(&vault) {
+            return Err(VaultError::InvalidVaultConfig);
+        }
+
+        self.vaults.push(vault);
+        Ok(())
+    }
+
+    /// Drop `vault` from the page, moving the last entry into its place. The freed space is
+    /// kept for the next vault registered here
+    pub fn unregister(&mut self, vault: &Pubkey) -> VaultResult<()> {
+        let index = self
+            .vaults
+            .iter()
+            .position(|registered| registered == vault)
+            .ok_or(VaultError::VaultNotRegistered)?;
+
+        self.vaults.swap_remove(index);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_full_page_fits_a_single_instruction_growth() {
+        // MAX_PERMITTED_DATA_INCREASE, from an empty page to a full one
+        assert!(VaultRegistry::space(MAX_REGISTRY_PAGE_VAULTS) - VaultRegistry::space(0) <= 10_240);
+    }
+
+    #[test]
+    fn test_register_and_unregister() {
+        let mut registry = VaultRegistry::default();
+        let (first, second, third) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+
+        for vault in [first, second, third] {
+            registry.register(vault).unwrap();
+        }
+        assert!(matches!(registry.register(second), Err(VaultError::InvalidVaultConfig)));
+
+        registry.unregister(&first).unwrap();
+        assert_eq!(registry.vaults, vec![third, second]);
+        assert!(matches!(registry.unregister(&first), Err(VaultError::VaultNotRegistered)));
+    }
+
+    #[test]
+    fn test_page_capacity() {
+        let mut registry = VaultRegistry::default();
+        for _ in 0..MAX_REGISTRY_PAGE_VAULTS {
+            registry.register(Pubkey::new_unique()).unwrap();
+        }
+
+        assert!(registry.is_full());
+        assert!(matches!(registry.register(Pubkey::new_unique()), Err(VaultError::RegistryPageFull)));
+    }
+}
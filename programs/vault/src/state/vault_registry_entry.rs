@@ -0,0 +1,27 @@
+use anchor_lang::prelude::*;
+
+/// Lookup entry letting clients find a vault by owner and token mint without knowing its name
+/// Seeds: `[b"vault_by_mint", owner, token_mint, index (u16 little endian)]`
+#[account]
+#[derive(Default)]
+pub struct VaultRegistryEntry {
+    /// The vault this entry points to
+    pub vault: Pubkey,
+    /// Owner of the vault at registration
+    pub owner: Pubkey,
+    /// The vault's token mint
+    pub token_mint: Pubkey,
+    /// Index among the owner's vaults of this mint
+    pub index: u16,
+    /// Bump seed for PDA
+    pub bump: u8,
+}
+
+impl VaultRegistryEntry {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // vault
+        32 + // owner
+        32 + // token_mint
+        2 + // index
+        1; // bump
+}
@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use crate::error::VaultError;
 
 pub fn get_current_timestamp() -> i64 {
     Clock::get().unwrap().unix_timestamp
@@ -7,4 +8,24 @@ pub fn get_current_timestamp() -> i64 {
 /// Vault signer seeds - returns seeds that can be used with CpiContext
 pub fn get_vault_signer_seeds<'a>(name: &'a [u8], bump: &'a [u8]) -> [&'a [u8]; 3] {
     [b"vault", name, bump]
-}
\ No newline at end of file
+}
+
+/// Slippage bound on what an instruction hands out: `realized` must reach `min_out`.
+/// A zero bound is no check, as sent by clients from before the bound existed
+pub fn check_min_out(realized: u64, min_out: u64) -> Result<()> {
+    if realized < min_out {
+        msg!("Slippage exceeded: {} < minimum {}", realized, min_out);
+        return Err(VaultError::SlippageExceeded.into());
+    }
+    Ok(())
+}
+
+/// Slippage bound on what an instruction takes: `realized` must not exceed `max_in`,
+/// zero is no check
+pub fn check_max_in(realized: u64, max_in: u64) -> Result<()> {
+    if max_in != 0 && realized > max_in {
+        msg!("Slippage exceeded: {} > maximum {}", realized, max_in);
+        return Err(VaultError::SlippageExceeded.into());
+    }
+    Ok(())
+}
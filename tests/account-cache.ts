@@ -0,0 +1,105 @@
+import { expect } from 'chai'
+import * as fs from 'fs'
+import * as os from 'os'
+import * as path from 'path'
+import { AccountInfo, Keypair, PublicKey, Transaction, TransactionInstruction } from '@solana/web3.js'
+import { AccountCache, writableAccounts } from '../client/account-cache'
+
+describe('Account cache', () => {
+  const programId = Keypair.generate().publicKey
+  const vault = Keypair.generate().publicKey
+  const depositor = Keypair.generate().publicKey
+  const mint = Keypair.generate().publicKey
+
+  let dir: string
+  let clock: number
+  let loads: number
+
+  const account = (lamports: number): AccountInfo<Buffer> => ({
+    data: Buffer.from([lamports % 256]),
+    owner: programId,
+    lamports,
+    executable: false,
+  })
+
+  // RPC stub answering every read with `lamports` at `slot`
+  const loader = (slot: number, lamports: number) => async () => {
+    loads++
+    return { context: { slot }, value: [account(lamports)] }
+  }
+
+  beforeEach(() => {
+    dir = fs.mkdtempSync(path.join(os.tmpdir(), 'account-cache-'))
+    clock = 1_000_000
+    loads = 0
+  })
+
+  afterEach(() => {
+    fs.rmSync(dir, { recursive: true, force: true })
+  })
+
+  const cache = () => new AccountCache(dir, { ttlSeconds: 10, staleSeconds: 30, now: () => clock })
+
+  it('serves fresh entries, revalidates stale ones in the background and refetches expired ones', async () => {
+    const store = cache()
+    await store.fetch([vault], loader(100, 1))
+    expect(loads).to.equal(1)
+
+    clock += 9_000
+    const fresh = await store.fetch([vault], loader(101, 2))
+    expect(loads).to.equal(1)
+    expect(fresh.value[0]!.lamports).to.equal(1)
+
+    clock += 2_000
+    const stale = await store.fetch([vault], loader(102, 3))
+    expect(stale.value[0]!.lamports).to.equal(1)
+    await store.settle()
+    expect(loads).to.equal(2)
+    expect(store.lookup(vault)!.account!.lamports).to.equal(3)
+    expect(store.lookup(vault)!.fresh).to.equal(true)
+
+    clock += 40_000
+    expect(store.lookup(vault)).to.equal(undefined)
+    const expired = await store.fetch([vault], loader(103, 4))
+    expect(loads).to.equal(3)
+    expect(expired.value[0]!.lamports).to.equal(4)
+  })
+
+  it('invalidates the accounts a confirmed stake wrote', async () => {
+    const user = Keypair.generate()
+    const stake = new Transaction().add(
+      new TransactionInstruction({
+        programId,
+        keys: [
+          { pubkey: vault, isSigner: false, isWritable: true },
+          { pubkey: depositor, isSigner: false, isWritable: true },
+          { pubkey: mint, isSigner: false, isWritable: false },
+          { pubkey: user.publicKey, isSigner: true, isWritable: true },
+        ],
+        data: Buffer.alloc(0),
+      })
+    )
+    stake.feePayer = user.publicKey
+    stake.recentBlockhash = PublicKey.default.toBase58()
+
+    const store = cache()
+    await store.fetch([vault, depositor, mint], async () => {
+      loads++
+      return { context: { slot: 100 }, value: [account(1), account(1), account(1)] }
+    })
+
+    store.invalidate(writableAccounts(stake.compileMessage()), 105)
+    expect(store.lookup(vault)).to.equal(undefined)
+    expect(store.lookup(depositor)).to.equal(undefined)
+    expect(store.lookup(mint)!.fresh).to.equal(true)
+
+    // a read from before the stake landed, e.g. by another CLI process, is not stored
+    store.store(vault, 104, account(1))
+    expect(store.lookup(vault)).to.equal(undefined)
+
+    const after = await store.fetch([vault], loader(106, 2))
+    expect(loads).to.equal(2)
+    expect(after.value[0]!.lamports).to.equal(2)
+    expect(store.lookup(vault)!.slot).to.equal(106)
+  })
+})
@@ -0,0 +1,369 @@
+import * as anchor from '@coral-xyz/anchor'
+import { Program } from '@coral-xyz/anchor'
+import { SimpleVault } from '../target/types/simple_vault'
+import {
+  TOKEN_PROGRAM_ID,
+  createMint,
+  createAccount,
+  mintTo,
+  freezeAccount,
+  thawAccount,
+} from '@solana/spl-token'
+import {
+  SystemProgram,
+  SYSVAR_RENT_PUBKEY,
+  Keypair,
+  PublicKey,
+} from '@solana/web3.js'
+import { expect } from 'chai'
+import { expectVaultError } from './helpers'
+
+const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms))
+const NO_CLIENT_ID = new Array(16).fill(0)
+
+describe('Account validation (negative tests)', () => {
+  const provider = anchor.AnchorProvider.env()
+  anchor.setProvider(provider)
+  const program = anchor.workspace.SimpleVault as Program<SimpleVault>
+  const payer = (provider.wallet as anchor.Wallet).payer
+
+  // fresh vault per run so the suite does not depend on devnet state
+  const vaultName = `validation-${Date.now() % 1_000_000}`
+  const vaultNameBuffer = Buffer.alloc(32)
+  vaultNameBuffer.write(vaultName)
+
+  const [vaultPDA] = PublicKey.findProgramAddressSync(
+    [Buffer.from('vault'), vaultNameBuffer],
+    program.programId
+  )
+  const [vaultTokenAccount] = PublicKey.findProgramAddressSync(
+    [Buffer.from('vault_token_account'), vaultPDA.toBuffer()],
+    program.programId
+  )
+  const [vaultDepositor] = PublicKey.findProgramAddressSync(
+    [Buffer.from('vault_depositor'), vaultPDA.toBuffer(), payer.publicKey.toBuffer()],
+    program.programId
+  )
+
+  let tokenMint: PublicKey
+  let otherMint: PublicKey
+  let userTokenAccount: PublicKey
+  let platformTokenAccount: PublicKey
+  let wrongMintAccount: PublicKey
+  let wrongAuthorityAccount: PublicKey
+
+  before(async () => {
+    tokenMint = await createMint(provider.connection, payer, payer.publicKey, null, 9)
+    otherMint = await createMint(provider.connection, payer, payer.publicKey, null, 9)
+
+    userTokenAccount = await createAccount(provider.connection, payer, tokenMint, payer.publicKey)
+    platformTokenAccount = await createAccount(
+      provider.connection,
+      payer,
+      tokenMint,
+      payer.publicKey,
+      Keypair.generate()
+    )
+    // same mint but not owned by the vault PDA
+    wrongAuthorityAccount = await createAccount(
+      provider.connection,
+      payer,
+      tokenMint,
+      payer.publicKey,
+      Keypair.generate()
+    )
+    wrongMintAccount = await createAccount(provider.connection, payer, otherMint, payer.publicKey)
+
+    await mintTo(provider.connection, payer, tokenMint, userTokenAccount, payer, 1_000_000_000_000)
+
+    await program.methods
+      .initializeVault({
+        name: Array.from(vaultNameBuffer),
+        platformAccount: payer.publicKey,
+        unstakeLockupPeriod: new anchor.BN(600),
+        platformShareBps: new anchor.BN(5000),
+        minStakeAmount: new anchor.BN(0),
+        maxTotalAssets: null,
+        managementFeeBps: null,
+        registryIndex: null,
+        protocolGoverned: null,
+      } as any)
+      .accounts({
+        vault: vaultPDA,
+        owner: payer.publicKey,
+        tokenMint,
+        vaultTokenAccount,
+        protocolStats: null,
+        registryEntry: null,
+        vaultRegistry: null,
+        protocolConfig: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      } as any)
+      .rpc()
+
+    // The 2 second wait before exiting covers a 1 second cooldown, not the 300 second default
+    await program.methods
+      .updateVaultConfig({ stakeCooldownSeconds: new anchor.BN(1) } as any)
+      .accounts({ vault: vaultPDA, owner: payer.publicKey } as any)
+      .rpc()
+
+    await program.methods
+      .initializeVaultDepositor()
+      .accounts({
+        vault: vaultPDA,
+        vaultDepositor,
+        authority: payer.publicKey,
+        systemProgram: SystemProgram.programId,
+      } as any)
+      .rpc()
+
+    await program.methods
+      .stake(new anchor.BN(100_000_000_000), new anchor.BN(0), NO_CLIENT_ID)
+      .accounts({
+        vault: vaultPDA,
+        vaultDepositor,
+        vaultTokenAccount,
+        userTokenAccount,
+        authority: payer.publicKey,
+        tokenMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      } as any)
+      .rpc()
+    await sleep(2000)
+  })
+
+  const stakeWith = (overrides: Record<string, PublicKey>) =>
+    program.methods
+      .stake(new anchor.BN(1_000_000_000), new anchor.BN(0), NO_CLIENT_ID)
+      .accounts({
+        vault: vaultPDA,
+        vaultDepositor,
+        vaultTokenAccount,
+        userTokenAccount,
+        authority: payer.publicKey,
+        tokenMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        ...overrides,
+      } as any)
+      .rpc()
+
+  const addRewardsWith = (overrides: Record<string, PublicKey>) =>
+    program.methods
+      .addRewards(new anchor.BN(1_000_000_000))
+      .accounts({
+        vault: vaultPDA,
+        vaultTokenAccount,
+        rewardSourceAccount: userTokenAccount,
+        platformTokenAccount,
+        rewardSourceAuthority: payer.publicKey,
+        tokenMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        ...overrides,
+      } as any)
+      .rpc()
+
+  const unstakeWith = (overrides: Record<string, PublicKey>) =>
+    program.methods
+      .unstake(new anchor.BN(0), NO_CLIENT_ID)
+      .accounts({
+        vault: vaultPDA,
+        vaultDepositor,
+        vaultTokenAccount,
+        userTokenAccount,
+        authority: payer.publicKey,
+        tokenMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        ...overrides,
+      } as any)
+      .rpc()
+
+  it('stake rejects a vault token account with the wrong mint or authority', async () => {
+    await expectVaultError(stakeWith({ vaultTokenAccount: wrongMintAccount }), 'InvalidTokenAccount')
+    await expectVaultError(
+      stakeWith({ vaultTokenAccount: wrongAuthorityAccount }),
+      'InvalidTokenAccount'
+    )
+  })
+
+  it('add_rewards rejects a vault token account with the wrong mint or authority', async () => {
+    await expectVaultError(
+      addRewardsWith({ vaultTokenAccount: wrongMintAccount }),
+      'InvalidTokenAccount'
+    )
+    await expectVaultError(
+      addRewardsWith({ vaultTokenAccount: wrongAuthorityAccount }),
+      'InvalidTokenAccount'
+    )
+  })
+
+  it('unstake rejects a vault token account with the wrong mint or authority', async () => {
+    await expectVaultError(unstakeWith({ vaultTokenAccount: wrongMintAccount }), 'InvalidTokenAccount')
+    await expectVaultError(
+      unstakeWith({ vaultTokenAccount: wrongAuthorityAccount }),
+      'InvalidTokenAccount'
+    )
+  })
+
+  it('add_rewards rejects the same token account in two roles', async () => {
+    await expectVaultError(
+      addRewardsWith({ rewardSourceAccount: vaultTokenAccount }),
+      'InvalidTokenAccount'
+    )
+    await expectVaultError(
+      addRewardsWith({ platformTokenAccount: vaultTokenAccount }),
+      'InvalidTokenAccount'
+    )
+    await expectVaultError(
+      addRewardsWith({ platformTokenAccount: userTokenAccount }),
+      'InvalidTokenAccount'
+    )
+  })
+
+  it('stake and unstake reject the vault token account as the user token account', async () => {
+    await expectVaultError(stakeWith({ userTokenAccount: vaultTokenAccount }), 'InvalidTokenAccount')
+    await expectVaultError(unstakeWith({ userTokenAccount: vaultTokenAccount }), 'InvalidTokenAccount')
+  })
+
+  it('rejects a token mint other than the vault underlying', async () => {
+    await expectVaultError(stakeWith({ tokenMint: otherMint }), 'InvalidTokenMint')
+    await expectVaultError(addRewardsWith({ tokenMint: otherMint }), 'InvalidTokenMint')
+    await expectVaultError(unstakeWith({ tokenMint: otherMint }), 'InvalidTokenMint')
+  })
+
+  it('request_unstake rejects a literal u64::MAX amount', async () => {
+    await expectVaultError(
+      program.methods
+        .requestUnstake(new anchor.BN('18446744073709551615'), new anchor.BN(0), NO_CLIENT_ID)
+        .accounts({ vault: vaultPDA, vaultDepositor, authority: payer.publicKey } as any)
+        .rpc(),
+      'AmbiguousMaxAmount'
+    )
+  })
+})
+
+describe('Frozen vault token account', () => {
+  const provider = anchor.AnchorProvider.env()
+  anchor.setProvider(provider)
+  const program = anchor.workspace.SimpleVault as Program<SimpleVault>
+  const payer = (provider.wallet as anchor.Wallet).payer
+
+  const vaultName = `frozen-${Date.now() % 1_000_000}`
+  const vaultNameBuffer = Buffer.alloc(32)
+  vaultNameBuffer.write(vaultName)
+
+  const [vaultPDA] = PublicKey.findProgramAddressSync(
+    [Buffer.from('vault'), vaultNameBuffer],
+    program.programId
+  )
+  const [vaultTokenAccount] = PublicKey.findProgramAddressSync(
+    [Buffer.from('vault_token_account'), vaultPDA.toBuffer()],
+    program.programId
+  )
+  const [vaultDepositor] = PublicKey.findProgramAddressSync(
+    [Buffer.from('vault_depositor'), vaultPDA.toBuffer(), payer.publicKey.toBuffer()],
+    program.programId
+  )
+
+  let tokenMint: PublicKey
+  let userTokenAccount: PublicKey
+  let platformTokenAccount: PublicKey
+
+  before(async () => {
+    // the payer is the mint's freeze authority
+    tokenMint = await createMint(provider.connection, payer, payer.publicKey, payer.publicKey, 9)
+    userTokenAccount = await createAccount(provider.connection, payer, tokenMint, payer.publicKey)
+    platformTokenAccount = await createAccount(
+      provider.connection,
+      payer,
+      tokenMint,
+      payer.publicKey,
+      Keypair.generate()
+    )
+    await mintTo(provider.connection, payer, tokenMint, userTokenAccount, payer, 1_000_000_000_000)
+
+    await program.methods
+      .initializeVault({
+        name: Array.from(vaultNameBuffer),
+        platformAccount: payer.publicKey,
+        unstakeLockupPeriod: new anchor.BN(600),
+        platformShareBps: new anchor.BN(5000),
+        minStakeAmount: new anchor.BN(0),
+        maxTotalAssets: null,
+        managementFeeBps: null,
+        registryIndex: null,
+        protocolGoverned: null,
+      } as any)
+      .accounts({
+        vault: vaultPDA,
+        owner: payer.publicKey,
+        tokenMint,
+        vaultTokenAccount,
+        protocolStats: null,
+        registryEntry: null,
+        vaultRegistry: null,
+        protocolConfig: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      } as any)
+      .rpc()
+
+    await program.methods
+      .initializeVaultDepositor()
+      .accounts({
+        vault: vaultPDA,
+        vaultDepositor,
+        authority: payer.publicKey,
+        systemProgram: SystemProgram.programId,
+      } as any)
+      .rpc()
+  })
+
+  const stake = () =>
+    program.methods
+      .stake(new anchor.BN(1_000_000_000), new anchor.BN(0), NO_CLIENT_ID)
+      .accounts({
+        vault: vaultPDA,
+        vaultDepositor,
+        vaultTokenAccount,
+        userTokenAccount,
+        authority: payer.publicKey,
+        tokenMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      } as any)
+      .rpc()
+
+  const addRewards = () =>
+    program.methods
+      .addRewards(new anchor.BN(1_000_000_000))
+      .accounts({
+        vault: vaultPDA,
+        vaultTokenAccount,
+        rewardSourceAccount: userTokenAccount,
+        platformTokenAccount,
+        rewardSourceAuthority: payer.publicKey,
+        tokenMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      } as any)
+      .rpc()
+
+  it('stake and add_rewards fail with TokenAccountFrozen while the vault account is frozen', async () => {
+    await stake()
+    const before = await program.account.vault.fetch(vaultPDA)
+
+    await freezeAccount(provider.connection, payer, vaultTokenAccount, tokenMint, payer)
+    await expectVaultError(stake(), 'TokenAccountFrozen')
+    await expectVaultError(addRewards(), 'TokenAccountFrozen')
+
+    const after = await program.account.vault.fetch(vaultPDA)
+    expect(after.totalAssets.toString()).to.equal(before.totalAssets.toString())
+    expect(after.totalShares.toString()).to.equal(before.totalShares.toString())
+
+    // thawing restores normal operation
+    await thawAccount(provider.connection, payer, vaultTokenAccount, tokenMint, payer)
+    await stake()
+    await addRewards()
+  })
+})
@@ -18,6 +18,7 @@ import {
 import * as fs from 'fs'
 import * as os from 'os'
 import contract_info from '../client/contract_info.json'
+import { getRegistryEntryPDA, getVaultRegistryPDA, nextRegistryIndex, openRegistryPage } from '../client/vault-registry'
 
 describe('admin_initialization', () => {
   // Set Anchor environment
@@ -137,6 +138,14 @@ describe('admin_initialization', () => {
     )
     console.log(`✅ Vault Token Account: ${vaultTokenAccount.toString()}`)
 
+    // Share Mint PDA: ["share_mint", vault_pda], only created for tokenized vaults
+    const [shareMintPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from('share_mint'), vaultPDA.toBuffer()],
+      program.programId
+    )
+    const shareMint = contract_info.tokenize_shares ? shareMintPDA : null
+    console.log(`✅ Share Mint: ${shareMint ? shareMint.toString() : 'not tokenized'}`)
+
     // ========== Step 4: Create reward source account ==========
     console.log('\n📋 Step 4: Create reward source account')
 
@@ -220,13 +229,35 @@ describe('admin_initialization', () => {
       `   - Unstake lockup period: ${contract_info.unstake_lockup_period} hours`
     )
     console.log(
-      `   - Management fee: ${contract_info.management_fee}% annualized`
+      `   - Platform share: ${contract_info.platform_share_bps / 100}% of rewards`
     )
     console.log(
       `   - Minimum stake amount: ${contract_info.min_stake_amount} USDC`
     )
     console.log(`   - Maximum total assets: Unlimited`)
 
+    // Register the vault so clients can find it by owner and mint
+    const registryIndex = await nextRegistryIndex(program, owner.publicKey, tokenMint)
+    const [registryEntry] = getRegistryEntryPDA(
+      program.programId,
+      owner.publicKey,
+      tokenMint,
+      registryIndex
+    )
+    console.log(`   - Registry index: ${registryIndex}`)
+
+    // List the vault on the first registry page with room, creating the page if needed
+    const registryPage = await openRegistryPage(program)
+    const [vaultRegistry] = getVaultRegistryPDA(program.programId, registryPage.page)
+    if (!registryPage.exists) {
+      await program.methods
+        .initializeRegistry(registryPage.page)
+        .accounts({ vaultRegistry, payer: owner.publicKey, systemProgram: SystemProgram.programId } as any)
+        .signers([owner])
+        .rpc()
+    }
+    console.log(`   - Registry page: ${registryPage.page}`)
+
     await program.methods
       .initializeVault({
         name: Array.from(vaultNameBuffer), // Vault name (32 bytes)
@@ -234,15 +265,21 @@ describe('admin_initialization', () => {
         unstakeLockupPeriod: new anchor.BN(
           contract_info.unstake_lockup_period * 60 * 60
         ), // 24h lockup period (minimum requirement)
-        managementFee: new anchor.BN(contract_info.management_fee), // 0% annualized management fee (000 basis points)
+        platformShareBps: new anchor.BN(contract_info.platform_share_bps), // Platform's share of each reward (5000 basis points = 50%)
         minStakeAmount: new anchor.BN(contract_info.min_stake_amount * 1e9), // 1 USDC minimum stake
         maxTotalAssets: null, // Unlimited total assets
+        managementFeeBps: new anchor.BN(contract_info.management_fee_bps), // Annual management fee, not accrued yet
+        registryIndex, // Lookup entry index for this owner and mint
+        protocolGoverned: null, // Governed by the protocol config only when it is supplied
       })
       .accounts({
         vault: vaultPDA, // Vault PDA
         owner: owner.publicKey, // Admin wallet
         tokenMint: tokenMint, // USDC mint
         vaultTokenAccount: vaultTokenAccount, // Vault token account
+        registryEntry, // Vault lookup entry by owner and mint
+        vaultRegistry, // Registry page listing every vault
+        shareMint, // Share token mint, null keeps shares internal
         tokenProgram: TOKEN_PROGRAM_ID, // SPL Token program
         systemProgram: SystemProgram.programId, // System program
         rent: SYSVAR_RENT_PUBKEY, // Rent sysvar
@@ -250,6 +287,13 @@ describe('admin_initialization', () => {
       .signers([owner]) // Admin signature
       .rpc()
 
+    // The user tests wait 2 seconds before exiting, which covers a 1 second cooldown, not the 300 second default
+    await program.methods
+      .updateVaultConfig({ stakeCooldownSeconds: new anchor.BN(1) } as any)
+      .accounts({ vault: vaultPDA, owner: owner.publicKey } as any)
+      .signers([owner])
+      .rpc()
+
     console.log('✅ Vault contract initialization successful!')
 
     // ========== Step 7: Verify initialization results ==========
@@ -263,10 +307,11 @@ describe('admin_initialization', () => {
     console.log(`   ✅ Total Shares: ${vault.totalShares.toString()}`)
     console.log(`   ✅ Total Assets: ${vault.totalAssets.toString()}`)
     console.log(
-      `   ✅ Management Fee: ${vault.managementFee.toString()} basis points (${
-        vault.managementFee.toNumber() / 100
+      `   ✅ Platform Share: ${vault.platformShareBps.toString()} basis points (${
+        vault.platformShareBps.toNumber() / 100
       }%)`
     )
+    console.log(`   ✅ Management Fee: ${vault.managementFeeBps.toString()} basis points a year`)
     console.log(
       `   ✅ Min Stake Amount: ${vault.minStakeAmount.toNumber() / 1e9} USDC`
     )
@@ -291,7 +336,9 @@ describe('admin_initialization', () => {
     console.log(`🏦 Vault Token Account: ${vaultTokenAccount.toString()}`)
     console.log(`💸 Reward source account: ${rewardSourceAccount.toString()}`)
     console.log(
-      `💼 Management fee: ${vault.managementFee.toNumber() / 100}% annualized`
+      `💼 Platform share: ${vault.platformShareBps.toNumber() / 100}% of rewards, management fee: ${
+        vault.managementFeeBps.toNumber() / 100
+      }% a year`
     )
     console.log(
       `⏰ Unstake lockup period: ${
@@ -0,0 +1,122 @@
+import * as anchor from '@coral-xyz/anchor'
+import { Program } from '@coral-xyz/anchor'
+import { SimpleVault } from '../target/types/simple_vault'
+import { TOKEN_PROGRAM_ID, createMint } from '@solana/spl-token'
+import { SystemProgram, SYSVAR_RENT_PUBKEY, PublicKey } from '@solana/web3.js'
+import { expect } from 'chai'
+import { expectVaultError } from './helpers'
+
+const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms))
+
+describe('Config timelock', () => {
+  const provider = anchor.AnchorProvider.env()
+  anchor.setProvider(provider)
+  const program = anchor.workspace.SimpleVault as Program<SimpleVault>
+  const payer = (provider.wallet as anchor.Wallet).payer
+
+  const vaultName = `timelock-${Date.now() % 1_000_000}`
+  const vaultNameBuffer = Buffer.alloc(32)
+  vaultNameBuffer.write(vaultName)
+
+  const [vaultPDA] = PublicKey.findProgramAddressSync([Buffer.from('vault'), vaultNameBuffer], program.programId)
+  const [vaultTokenAccount] = PublicKey.findProgramAddressSync(
+    [Buffer.from('vault_token_account'), vaultPDA.toBuffer()],
+    program.programId
+  )
+  const [pendingConfigUpdate] = PublicKey.findProgramAddressSync(
+    [Buffer.from('pending_config_update'), vaultPDA.toBuffer()],
+    program.programId
+  )
+
+  const TIMELOCK_SECONDS = 4
+
+  const update = (params: any) =>
+    program.methods
+      .updateVaultConfig(params)
+      .accounts({ vault: vaultPDA, owner: payer.publicKey } as any)
+      .rpc()
+  const propose = (params: any) =>
+    program.methods
+      .proposeConfigUpdate(params)
+      .accounts({
+        vault: vaultPDA,
+        pendingConfigUpdate,
+        owner: payer.publicKey,
+        systemProgram: SystemProgram.programId,
+      } as any)
+      .rpc()
+  const commit = () =>
+    program.methods
+      .commitConfigUpdate()
+      .accounts({ vault: vaultPDA, pendingConfigUpdate, owner: payer.publicKey } as any)
+      .rpc()
+  const cancel = () =>
+    program.methods
+      .cancelConfigUpdate()
+      .accounts({ vault: vaultPDA, pendingConfigUpdate, owner: payer.publicKey } as any)
+      .rpc()
+
+  before(async () => {
+    const tokenMint = await createMint(provider.connection, payer, payer.publicKey, null, 9)
+    await program.methods
+      .initializeVault({
+        name: Array.from(vaultNameBuffer),
+        platformAccount: payer.publicKey,
+        unstakeLockupPeriod: new anchor.BN(600),
+        platformShareBps: new anchor.BN(5000),
+        minStakeAmount: new anchor.BN(0),
+        maxTotalAssets: null,
+        managementFeeBps: null,
+        registryIndex: null,
+        protocolGoverned: null,
+      } as any)
+      .accounts({
+        vault: vaultPDA,
+        owner: payer.publicKey,
+        tokenMint,
+        vaultTokenAccount,
+        protocolStats: null,
+        registryEntry: null,
+        vaultRegistry: null,
+        protocolConfig: null,
+        shareMint: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      } as any)
+      .rpc()
+  })
+
+  it('sets the timelock only through a proposal', async () => {
+    await expectVaultError(update({ configTimelockSeconds: new anchor.BN(TIMELOCK_SECONDS) }), 'ConfigUpdateTimelocked')
+
+    // no timelock yet, so the proposal can be committed at once
+    await propose({ configTimelockSeconds: new anchor.BN(TIMELOCK_SECONDS) })
+    await commit()
+    const vault = await program.account.vault.fetch(vaultPDA)
+    expect(vault.configTimelockSeconds.toNumber()).to.equal(TIMELOCK_SECONDS)
+    expect(await provider.connection.getAccountInfo(pendingConfigUpdate)).to.be.null
+  })
+
+  it('keeps pauses instant and delays everything else', async () => {
+    await expectVaultError(update({ platformShareBps: new anchor.BN(4500) }), 'ConfigUpdateTimelocked')
+    await update({ depositsPaused: true })
+    await update({ depositsPaused: false })
+
+    await propose({ platformShareBps: new anchor.BN(4500) })
+    await expectVaultError(commit(), 'ConfigTimelockNotElapsed')
+
+    await sleep((TIMELOCK_SECONDS + 2) * 1000)
+    await commit()
+    expect((await program.account.vault.fetch(vaultPDA)).platformShareBps.toNumber()).to.equal(4500)
+  })
+
+  it('rejects an invalid proposal up front and cancels a pending one', async () => {
+    await expectVaultError(propose({ platformShareBps: new anchor.BN(20_000) }), 'InvalidVaultConfig')
+
+    await propose({ minStakeAmount: new anchor.BN(1_000_000) })
+    await cancel()
+    expect(await provider.connection.getAccountInfo(pendingConfigUpdate)).to.be.null
+    expect((await program.account.vault.fetch(vaultPDA)).minStakeAmount.toNumber()).to.equal(0)
+  })
+})
@@ -0,0 +1,114 @@
+import { expect } from 'chai'
+import * as anchor from '@coral-xyz/anchor'
+import { DASHBOARD_KEYS, DashboardData, renderDashboard, sparkline, summarizeEvent } from '../client/dashboard'
+
+describe('Dashboard', () => {
+  const BN = (value: number | string) => new anchor.BN(value)
+  const NOW = 1_700_000_000
+  const DAY = 24 * 60 * 60
+
+  const vault = {
+    totalAssets: BN('3000000000000'),
+    reservedAssets: BN('500000000000'),
+    totalShares: BN('2900000000000'),
+    pendingUnstakeShares: BN('480000000000'),
+    rebaseVersion: 2,
+    sharesBase: 3,
+    isPaused: false,
+    withdrawalsPaused: true,
+    pausedOperations: 1 << 4,
+    unstakeLockupPeriod: BN(14 * DAY),
+    decommissionStartedAt: BN(0),
+    decommissionNoticeEndsAt: BN(0),
+  }
+
+  const depositor = (authority: string, shares: number, requestTime: number, lastRebaseVersion = 2) => ({
+    authority,
+    lastRebaseVersion,
+    unstakeRequest: {
+      shares: BN(shares),
+      requestTime: BN(requestTime),
+      // 1.0 per share, frozen amount = shares
+      assetPerShareAtRequest: BN('1000000000000'),
+    },
+  })
+
+  const fixture: DashboardData = {
+    vaultName: 'FOCX_Vault',
+    vaultAddress: 'Vau1tAddress1111111111111111111111111111111',
+    vault,
+    depositors: [
+      depositor('AliceAuthority11111111111111111111111111111', 300_000_000_000, NOW - 14 * DAY - 1),
+      depositor('BobAuthority1111111111111111111111111111111', 200_000_000_000, NOW - 13 * DAY),
+      depositor('CarolAuthority111111111111111111111111111111', 0, 0, 1),
+    ],
+    shareValues: [1.0, 1.01, 1.02, 1.015, 1.03],
+    events: [
+      {
+        at: NOW - 60,
+        event: {
+          name: 'stakeEvent',
+          data: { authority: 'CarolAuthority111111111111111111111111111111', amount: BN('100000000000'), shares: BN(97) },
+        },
+      },
+      {
+        at: NOW - 30,
+        event: {
+          name: 'unstakeRequestedEvent',
+          data: { authority: 'BobAuthority1111111111111111111111111111111', amount: BN('200000000000'), shares: BN(200) },
+        },
+      },
+    ],
+    now: NOW,
+  }
+
+  it('renders every pane into a fixed-size frame', () => {
+    const lines = renderDashboard(fixture, 100, 32)
+    expect(lines).to.have.length(32)
+    lines.forEach((line) => expect(line).to.have.length(100))
+
+    const frame = lines.join('\n')
+    expect(lines[0]).to.contain('FOCX_Vault')
+    expect(frame).to.contain('total assets    3000.00 USDC')
+    expect(frame).to.contain('paused          request-unstake, unstake, add-rewards')
+    expect(frame).to.contain('rebase          v2, base 10^3')
+    expect(frame).to.contain('1.030000000 per share')
+    expect(frame).to.contain(sparkline(fixture.shareValues, 46))
+    expect(lines[lines.length - 1].trim()).to.equal(DASHBOARD_KEYS)
+  })
+
+  it('lists pending unstakes by unlock time with countdowns', () => {
+    const frame = renderDashboard(fixture, 100, 32).join('\n')
+    expect(frame).to.contain('Pending unstakes (2)')
+    const alice = frame.indexOf('Alic…1111  300.00 USDC  ready')
+    const bob = frame.indexOf('BobA…1111  200.00 USDC  in 1d')
+    expect(alice).to.be.greaterThan(-1)
+    expect(bob).to.be.greaterThan(alice)
+  })
+
+  it('shows crank status and the newest event first', () => {
+    const lines = renderDashboard(fixture, 100, 32)
+    const frame = lines.join('\n')
+    expect(frame).to.contain('crank_rebase       idle')
+    expect(frame).to.contain('sync_rebase_batch  due, 1 depositor(s) behind')
+    expect(frame).to.contain('force_distribute   idle')
+    const requested = lines.findIndex((line) => line.includes('unstakeRequestedEvent  BobA…1111  200.00 USDC'))
+    const staked = lines.findIndex((line) => line.includes('stakeEvent  Caro…1111  100.00 USDC  97 shares'))
+    expect(requested).to.be.greaterThan(-1)
+    expect(staked).to.be.greaterThan(requested)
+  })
+
+  it('summarizes a rebase with its divisor and the shares left', () => {
+    const rebase = {
+      name: 'rebaseAppliedEvent',
+      data: { expoDiff: 3, rebaseDivisor: BN(1000), sharesBase: 6, rebaseVersion: 2, totalShares: BN('2900000000') },
+    }
+    expect(summarizeEvent(rebase)).to.equal('rebaseAppliedEvent  ÷1000  base 10^6  2900000000 total shares')
+  })
+
+  it('scales the sparkline between the lowest and highest sample', () => {
+    expect(sparkline([1, 2, 3], 10)).to.equal('▁▅█')
+    expect(sparkline([5, 5], 10)).to.equal('▁▁')
+    expect(sparkline([1, 2, 3, 4], 2)).to.equal('▁█')
+  })
+})
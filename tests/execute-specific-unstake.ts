@@ -134,13 +134,14 @@ async function main() {
     
     // Execute unstake
     const unstakeTx = await program.methods
-      .unstake()
+      .unstake(new anchor.BN(0), new Array(16).fill(0))
       .accounts({
         vault: vaultPDA,
         vaultDepositor: userVaultDepositor,
         vaultTokenAccount: vaultTokenAccount,
         userTokenAccount: userTokenAccount,
         authority: userKeypair.publicKey,
+        tokenMint: vault.tokenMint,
         tokenProgram: TOKEN_PROGRAM_ID,
       } as any)
       .signers([userKeypair])
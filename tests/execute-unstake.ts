@@ -187,13 +187,14 @@ async function main() {
       const balanceBefore = await getAccount(provider.connection, userTokenAccount);
       
       const unstakeTx = await program.methods
-        .unstake()
+        .unstake(new anchor.BN(0), new Array(16).fill(0))
         .accounts({
           vault: vaultPDA,
           vaultDepositor: depositorPDA,
           vaultTokenAccount: vaultTokenAccount,
           userTokenAccount: userTokenAccount,
           authority: userKeypair.publicKey,
+          tokenMint,
           tokenProgram: TOKEN_PROGRAM_ID,
         } as any)
         .signers([userKeypair])
@@ -350,6 +351,7 @@ async function main() {
         rewardSourceAccount: adminTokenAccount,
         platformTokenAccount: platformTokenAccount,
         rewardSourceAuthority: user1.publicKey, // Using user1 as admin for this test
+        tokenMint,
         tokenProgram: TOKEN_PROGRAM_ID,
       } as any)
       .signers([user1])
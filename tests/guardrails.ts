@@ -0,0 +1,80 @@
+import { expect } from 'chai'
+import * as fs from 'fs'
+import * as os from 'os'
+import * as path from 'path'
+import {
+  DEFAULT_GUARDRAILS,
+  GuardrailConfig,
+  checkPlatformShare,
+  checkStake,
+  checkUnstakeDust,
+  confirmGuardrails,
+  loadGuardrailConfig,
+} from '../client/guardrails'
+
+describe('CLI guardrails', () => {
+  const usdc = (amount: number) => amount * 1e9
+  const config: GuardrailConfig = { ...DEFAULT_GUARDRAILS, maxStakeAmount: 1_000 }
+
+  describe('stake amount', () => {
+    it('passes a stake within the wallet share and absolute limit', () => {
+      expect(checkStake(usdc(100), usdc(1_000), config)).to.be.empty
+    })
+
+    it('flags a stake above the wallet percentage', () => {
+      const warnings = checkStake(usdc(950), usdc(1_000), config)
+      expect(warnings.map((w) => w.rule)).to.deep.equal(['stake-wallet-percent'])
+    })
+
+    it('flags a stake above the absolute threshold', () => {
+      const warnings = checkStake(usdc(5_000), usdc(100_000), config)
+      expect(warnings.map((w) => w.rule)).to.deep.equal(['stake-absolute'])
+    })
+
+    it('skips the absolute check when no threshold is configured', () => {
+      expect(checkStake(usdc(5_000), usdc(100_000), DEFAULT_GUARDRAILS)).to.be.empty
+    })
+  })
+
+  describe('platform share', () => {
+    it('passes a fee at the bound', () => {
+      expect(checkPlatformShare(5_000, config)).to.be.empty
+    })
+
+    it('flags 10000 basis points typed as a percentage', () => {
+      const warnings = checkPlatformShare(10_000, config)
+      expect(warnings.map((w) => w.rule)).to.deep.equal(['fee-bound'])
+      expect(warnings[0].message).to.contain('100%')
+    })
+  })
+
+  describe('unstake dust', () => {
+    it('flags a remainder below the minimum stake', () => {
+      const warnings = checkUnstakeDust(usdc(100), usdc(99.5), usdc(1))
+      expect(warnings.map((w) => w.rule)).to.deep.equal(['unstake-dust'])
+    })
+
+    it('passes a full exit and a remainder above the minimum stake', () => {
+      expect(checkUnstakeDust(usdc(100), usdc(100), usdc(1))).to.be.empty
+      expect(checkUnstakeDust(usdc(100), null, usdc(1))).to.be.empty
+      expect(checkUnstakeDust(usdc(100), usdc(50), usdc(1))).to.be.empty
+    })
+  })
+
+  describe('profile and confirmation', () => {
+    it('merges the profile guardrails over the defaults', () => {
+      const profilePath = path.join(fs.mkdtempSync(path.join(os.tmpdir(), 'guardrails-')), 'vault-profile.json')
+      fs.writeFileSync(profilePath, JSON.stringify({ guardrails: { maxPlatformShareBps: 3_000 } }))
+
+      const loaded = loadGuardrailConfig(profilePath)
+      expect(loaded.maxPlatformShareBps).to.equal(3_000)
+      expect(loaded.maxStakeWalletPercent).to.equal(DEFAULT_GUARDRAILS.maxStakeWalletPercent)
+      expect(loadGuardrailConfig(profilePath + '.missing')).to.deep.equal(DEFAULT_GUARDRAILS)
+    })
+
+    it('lets --force through and resolves when nothing was flagged', async () => {
+      await confirmGuardrails([], false)
+      await confirmGuardrails(checkPlatformShare(10_000, config), true)
+    })
+  })
+})
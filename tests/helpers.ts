@@ -0,0 +1,13 @@
+import { expect } from 'chai'
+
+// expect an Anchor program error with the given VaultError code, also when raised inside a CPI
+export const expectVaultError = async (promise: Promise<any>, code: string) => {
+  try {
+    await promise
+  } catch (error: any) {
+    const raised = error?.error?.errorCode?.code ?? (error?.logs ?? []).join('\n')
+    expect(raised, `${error}`).to.contain(code)
+    return
+  }
+  expect.fail(`expected ${code}`)
+}
@@ -0,0 +1,48 @@
+import { expect } from 'chai'
+import * as anchor from '@coral-xyz/anchor'
+import {
+  formatFraction,
+  formatHoldersCsv,
+  parseHoldersCsv,
+  verifyHolders,
+  withFractions,
+} from '../client/holders'
+
+describe('Holder snapshots', () => {
+  const positions = [
+    { authority: 'alice', shares: new anchor.BN(1) },
+    { authority: 'bob', shares: new anchor.BN(1) },
+    { authority: 'carol', shares: new anchor.BN(1) },
+  ]
+
+  it('round-trips the CSV and verifies fractions that round down', () => {
+    const csv = formatHoldersCsv({ vault: 'vault', requestedSlot: 10, slot: 12, holders: withFractions(positions) })
+    expect(csv.split('\n')[0]).to.equal('# vault=vault slot=12 requested_slot=10')
+
+    const holders = parseHoldersCsv(csv)
+    expect(holders.map((h) => formatFraction(h.fraction))).to.deep.equal([
+      '0.333333333333333333',
+      '0.333333333333333333',
+      '0.333333333333333333',
+    ])
+    const verification = verifyHolders(holders)
+    expect(verification.ok).to.equal(true)
+    expect(verification.fractionSum).to.equal('0.999999999999999999')
+  })
+
+  it('rejects fractions that do not sum to one', () => {
+    const holders = withFractions(positions).slice(0, 2)
+    holders.forEach((h) => (h.fraction = h.fraction.divn(2)))
+    const verification = verifyHolders(holders)
+    expect(verification.ok).to.equal(false)
+    expect(verification.problems[0]).to.contain('sum to 0.333333333333333333')
+  })
+
+  it('rejects a fraction that does not match its shares', () => {
+    const holders = withFractions(positions)
+    holders[0].shares = new anchor.BN(2)
+    const verification = verifyHolders(holders)
+    expect(verification.ok).to.equal(false)
+    expect(verification.problems.some((p) => p.includes('does not match its shares'))).to.equal(true)
+  })
+})
@@ -541,13 +541,14 @@ describe('Multi-User Stake Rewards Distribution (Simplified)', () => {
     const user1StakeAmount = 100 * 1e9
     validateUSDCAmount(user1StakeAmount, "User1 stake amount")
     const user1StakeTx = await program.methods
-      .stake(new anchor.BN(user1StakeAmount))
+      .stake(new anchor.BN(user1StakeAmount), new anchor.BN(0), new Array(16).fill(0))
       .accounts({
         vault: vaultPDA,
         vaultDepositor: user1VaultDepositor,
         vaultTokenAccount: vaultTokenAccount,
         userTokenAccount: user1TokenAccount,
         authority: user1.publicKey,
+        tokenMint,
         tokenProgram: TOKEN_PROGRAM_ID,
       } as any)
       .signers([user1])
@@ -558,13 +559,14 @@ describe('Multi-User Stake Rewards Distribution (Simplified)', () => {
     const user2StakeAmount = 200 * 1e9
     validateUSDCAmount(user2StakeAmount, "User2 stake amount")
     const user2StakeTx = await program.methods
-      .stake(new anchor.BN(user2StakeAmount))
+      .stake(new anchor.BN(user2StakeAmount), new anchor.BN(0), new Array(16).fill(0))
       .accounts({
         vault: vaultPDA,
         vaultDepositor: user2VaultDepositor,
         vaultTokenAccount: vaultTokenAccount,
         userTokenAccount: user2TokenAccount,
         authority: user2.publicKey,
+        tokenMint,
         tokenProgram: TOKEN_PROGRAM_ID,
       } as any)
       .signers([user2])
@@ -575,13 +577,14 @@ describe('Multi-User Stake Rewards Distribution (Simplified)', () => {
     const user3StakeAmount = 300 * 1e9
     validateUSDCAmount(user3StakeAmount, "User3 stake amount")
     const user3StakeTx = await program.methods
-      .stake(new anchor.BN(user3StakeAmount))
+      .stake(new anchor.BN(user3StakeAmount), new anchor.BN(0), new Array(16).fill(0))
       .accounts({
         vault: vaultPDA,
         vaultDepositor: user3VaultDepositor,
         vaultTokenAccount: vaultTokenAccount,
         userTokenAccount: user3TokenAccount,
         authority: user3.publicKey,
+        tokenMint,
         tokenProgram: TOKEN_PROGRAM_ID,
       } as any)
       .signers([user3])
@@ -635,6 +638,7 @@ describe('Multi-User Stake Rewards Distribution (Simplified)', () => {
         rewardSourceAccount: adminTokenAccount,
         platformTokenAccount: platformTokenAccount,
         rewardSourceAuthority: adminWallet.publicKey,
+        tokenMint,
         tokenProgram: TOKEN_PROGRAM_ID,
       } as any)
       .signers([adminWallet])
@@ -893,7 +897,7 @@ describe('Multi-User Stake Rewards Distribution (Simplified)', () => {
     // Request partial unstake (wait 2s for MEV protection)
     await sleep(2000)
     const user2RequestUnstakeTx = await program.methods
-      .requestUnstake(new anchor.BN(user2PartialUnstakeAmount))
+      .requestUnstake(new anchor.BN(user2PartialUnstakeAmount), new anchor.BN(0), new Array(16).fill(0))
       .accounts({
         vault: vaultPDA,
         vaultDepositor: user2VaultDepositor,
@@ -920,13 +924,14 @@ describe('Multi-User Stake Rewards Distribution (Simplified)', () => {
       
       // Execute partial unstake
       const user2UnstakeTx = await program.methods
-        .unstake()
+        .unstake(new anchor.BN(0), new Array(16).fill(0))
         .accounts({
           vault: vaultPDA,
           vaultDepositor: user2VaultDepositor,
           vaultTokenAccount: vaultTokenAccount,
           userTokenAccount: user2TokenAccount,
           authority: user2.publicKey,
+          tokenMint,
           tokenProgram: TOKEN_PROGRAM_ID,
         } as any)
         .signers([user2])
@@ -963,7 +968,7 @@ describe('Multi-User Stake Rewards Distribution (Simplified)', () => {
     // Wait 2s for MEV protection before request unstake
     await sleep(2000)
     const user3RequestTx = await program.methods
-      .requestUnstake(new anchor.BN(user3UnstakeAmount))
+      .requestUnstake(new anchor.BN(user3UnstakeAmount), new anchor.BN(0), new Array(16).fill(0))
       .accounts({
         vault: vaultPDA,
         vaultDepositor: user3VaultDepositor,
@@ -1006,7 +1011,7 @@ describe('Multi-User Stake Rewards Distribution (Simplified)', () => {
     // Reference to user1StakeAmount from previous test (need to recalculate since it's in different scope)
     const user1StakeAmount = 100 * 1e9
 
-    // User1 requests to unstake all shares (using u64::MAX to unstake all)
+    // User1 requests to unstake all shares (using request_unstake_all)
     const user1Depositor = await program.account.vaultDepositor.fetch(
       user1VaultDepositor
     )
@@ -1015,7 +1020,7 @@ describe('Multi-User Stake Rewards Distribution (Simplified)', () => {
     // Wait 2s for MEV protection before request unstake
     await sleep(2000)
     const requestUnstakeTx = await program.methods
-      .requestUnstake(new anchor.BN("18446744073709551615")) // u64::MAX to unstake all
+      .requestUnstakeAll(new anchor.BN(0), new Array(16).fill(0))
       .accounts({
         vault: vaultPDA,
         vaultDepositor: user1VaultDepositor,
@@ -1048,13 +1053,14 @@ describe('Multi-User Stake Rewards Distribution (Simplified)', () => {
 
     try {
       const unstakeTx = await program.methods
-        .unstake()
+        .unstake(new anchor.BN(0), new Array(16).fill(0))
         .accounts({
           vault: vaultPDA,
           vaultDepositor: user1VaultDepositor,
           vaultTokenAccount: vaultTokenAccount,
           userTokenAccount: user1TokenAccount,
           authority: user1.publicKey,
+          tokenMint,
           tokenProgram: TOKEN_PROGRAM_ID,
         } as any)
         .signers([user1])
@@ -1096,6 +1102,7 @@ describe('Multi-User Stake Rewards Distribution (Simplified)', () => {
         rewardSourceAccount: adminTokenAccount,
         platformTokenAccount: platformTokenAccount,
         rewardSourceAuthority: adminWallet.publicKey,
+        tokenMint,
         tokenProgram: TOKEN_PROGRAM_ID,
       } as any)
       .signers([adminWallet])
@@ -0,0 +1,34 @@
+import { expect } from 'chai'
+import {
+  PAUSE_ALL,
+  PAUSE_BITS,
+  PAUSE_LEGACY_DEPOSITS,
+  PAUSE_WITHDRAWALS,
+  applyPauseParams,
+  parsePausedOperations,
+  pausedMask,
+  pausedOperationNames,
+} from '../client/pause'
+
+describe('Paused operations', () => {
+  it('parses operation names into the program bits', () => {
+    expect(parsePausedOperations('stake,add-rewards')).to.equal(0b10001)
+    expect(parsePausedOperations('all')).to.equal(PAUSE_ALL)
+    expect(pausedOperationNames(parsePausedOperations('cancel, fee-accrual'))).to.deep.equal(['cancel', 'fee-accrual'])
+    expect(() => parsePausedOperations('stake,deposit')).to.throw("Unknown operation 'deposit'")
+  })
+
+  it('reads legacy flags as their masks', () => {
+    const legacy = { isPaused: true, withdrawalsPaused: false, pausedOperations: 0 }
+    expect(pausedOperationNames(pausedMask(legacy))).to.deep.equal(['stake', 'add-rewards', 'init-depositor', 'fee-accrual'])
+    expect(pausedMask({ ...legacy, isPaused: false, withdrawalsPaused: true })).to.equal(PAUSE_WITHDRAWALS)
+  })
+
+  it('applies config params like update_config', () => {
+    const mask = PAUSE_BITS.cancel | PAUSE_WITHDRAWALS
+    expect(applyPauseParams(mask, { depositsPaused: true })).to.equal(mask | PAUSE_LEGACY_DEPOSITS)
+    expect(applyPauseParams(mask, { withdrawalsPaused: false })).to.equal(PAUSE_BITS.cancel)
+    expect(applyPauseParams(mask, { pausedOperations: PAUSE_BITS.stake })).to.equal(PAUSE_BITS.stake)
+    expect(applyPauseParams(mask, {})).to.equal(mask)
+  })
+})
@@ -0,0 +1,107 @@
+import { expect } from 'chai'
+import * as anchor from '@coral-xyz/anchor'
+import { PublicKey } from '@solana/web3.js'
+import {
+  CachedPriceSource,
+  FilePriceSource,
+  PriceQuote,
+  PriceSource,
+  formatDecimal,
+  formatUsd,
+} from '../client/price-source'
+
+const USDC = 'EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v'
+const OTHER = 'DXDVt289yXEcqXDd9Ub3HqSBTWwrmNB8DzQEagv9Svtu'
+
+describe('File price source', () => {
+  it('parses TOML entries exactly, skipping comments and tables', () => {
+    const prices = FilePriceSource.parse(
+      ['# prices.toml', '[prices]', `"${USDC}" = 0.9998`, `${OTHER} = "1e-3" # quoted`, ''].join('\n')
+    )
+    expect(prices.size).to.equal(2)
+    expect(formatDecimal(prices.get(USDC)!)).to.equal('0.9998')
+    expect(prices.get(USDC)!.mantissa.toString()).to.equal('9998')
+    expect(prices.get(USDC)!.expo).to.equal(-4)
+    expect(formatDecimal(prices.get(OTHER)!)).to.equal('0.001')
+  })
+
+  it('parses JSON files', () => {
+    const prices = FilePriceSource.parse(`{ "${USDC}": 1.0, "${OTHER}": "123.456789012345678901" }`)
+    expect(formatDecimal(prices.get(USDC)!)).to.equal('1')
+    // more digits than a float holds
+    expect(formatDecimal(prices.get(OTHER)!)).to.equal('123.456789012345678901')
+  })
+
+  it('rejects malformed and negative prices', () => {
+    expect(() => FilePriceSource.parse(`"${USDC}" = abc`)).to.throw('Invalid price entry')
+    expect(() => FilePriceSource.parse(`"${USDC}" = -1`)).to.throw('Invalid price')
+    expect(() => FilePriceSource.parse(`"${USDC}" = .`)).to.throw('Invalid price')
+  })
+})
+
+describe('USD formatting', () => {
+  const quote = (mantissa: string, expo: number): PriceQuote => ({
+    mint: new PublicKey(USDC),
+    price: { mantissa: new anchor.BN(mantissa), expo },
+    source: 'test',
+    timestamp: 0,
+  })
+
+  it('prices base units without rounding through a float', () => {
+    // 3 * 0.1 is 0.30000000000000004 as a float
+    expect(formatUsd(new anchor.BN(3), quote('1', -1), 0)).to.equal(
+      '$0.30 (price 0.1 via test @ 1970-01-01T00:00:00.000Z)'
+    )
+    // u64::MAX base units at a Pyth style price, past 2^53
+    expect(formatUsd(new anchor.BN('18446744073709551615'), quote('99980000', -8))).to.contain(
+      '$18443054724.89 (price 0.99980000'
+    )
+  })
+
+  it('rounds half a cent up', () => {
+    expect(formatUsd(new anchor.BN(5_000_000), quote('1', 0))).to.contain('$0.01 ')
+    expect(formatUsd(new anchor.BN(4_999_999), quote('1', 0))).to.contain('$0.00 ')
+    expect(formatUsd(new anchor.BN(2), quote('25', 2), 0)).to.contain('$5000.00 ')
+  })
+})
+
+describe('Cached price source', () => {
+  const realNow = Date.now
+  let now = 1_000_000
+
+  class CountingSource implements PriceSource {
+    readonly label = 'counting'
+    calls = 0
+    async price(mint: PublicKey): Promise<PriceQuote> {
+      this.calls += 1
+      return { mint, price: { mantissa: new anchor.BN(this.calls), expo: 0 }, source: this.label, timestamp: now }
+    }
+  }
+
+  beforeEach(() => {
+    Date.now = () => now * 1000
+  })
+
+  afterEach(() => {
+    Date.now = realNow
+  })
+
+  it('serves a quote until the TTL has passed, per mint', async () => {
+    const inner = new CountingSource()
+    const cached = new CachedPriceSource(inner, 60)
+    const usdc = new PublicKey(USDC)
+
+    const first = await cached.price(usdc)
+    now += 59
+    expect(await cached.price(usdc)).to.equal(first)
+    expect(inner.calls).to.equal(1)
+
+    await cached.price(new PublicKey(OTHER))
+    expect(inner.calls).to.equal(2)
+
+    now += 1
+    const refreshed = await cached.price(usdc)
+    expect(refreshed.price.mantissa.toNumber()).to.equal(3)
+    expect(inner.calls).to.equal(3)
+  })
+})
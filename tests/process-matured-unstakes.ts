@@ -0,0 +1,122 @@
+import * as anchor from '@coral-xyz/anchor'
+import { Program } from '@coral-xyz/anchor'
+import { SimpleVault } from '../target/types/simple_vault'
+import { TOKEN_PROGRAM_ID, createMint, createAccount, mintTo } from '@solana/spl-token'
+import { SystemProgram, SYSVAR_RENT_PUBKEY, Keypair, PublicKey } from '@solana/web3.js'
+import { expect } from 'chai'
+import { expectVaultError } from './helpers'
+
+const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms))
+const NO_CLIENT_ID = new Array(16).fill(0)
+
+describe('Process matured unstakes', () => {
+  const provider = anchor.AnchorProvider.env()
+  anchor.setProvider(provider)
+  const program = anchor.workspace.SimpleVault as Program<SimpleVault>
+  const payer = (provider.wallet as anchor.Wallet).payer
+
+  const vaultName = `batch-unstake-${Date.now() % 1_000_000}`
+  const vaultNameBuffer = Buffer.alloc(32)
+  vaultNameBuffer.write(vaultName)
+
+  const [vaultPDA] = PublicKey.findProgramAddressSync([Buffer.from('vault'), vaultNameBuffer], program.programId)
+  const [vaultTokenAccount] = PublicKey.findProgramAddressSync(
+    [Buffer.from('vault_token_account'), vaultPDA.toBuffer()],
+    program.programId
+  )
+  const [vaultDepositor] = PublicKey.findProgramAddressSync(
+    [Buffer.from('vault_depositor'), vaultPDA.toBuffer(), payer.publicKey.toBuffer()],
+    program.programId
+  )
+
+  let tokenMint: PublicKey
+  let userTokenAccount: PublicKey
+
+  const processBatch = (remainingAccounts: PublicKey[]) =>
+    program.methods
+      .processMaturedUnstakes()
+      .accounts({ vault: vaultPDA, vaultTokenAccount, tokenMint, tokenProgram: TOKEN_PROGRAM_ID } as any)
+      .remainingAccounts(remainingAccounts.map((pubkey) => ({ pubkey, isWritable: true, isSigner: false })))
+      .rpc()
+
+  before(async () => {
+    tokenMint = await createMint(provider.connection, payer, payer.publicKey, null, 9)
+    userTokenAccount = await createAccount(provider.connection, payer, tokenMint, payer.publicKey)
+    await mintTo(provider.connection, payer, tokenMint, userTokenAccount, payer, 1_000_000_000_000)
+
+    await program.methods
+      .initializeVault({
+        name: Array.from(vaultNameBuffer),
+        platformAccount: payer.publicKey,
+        unstakeLockupPeriod: new anchor.BN(600),
+        platformShareBps: new anchor.BN(5000),
+        minStakeAmount: new anchor.BN(0),
+        maxTotalAssets: null,
+        managementFeeBps: null,
+        registryIndex: null,
+        protocolGoverned: null,
+      } as any)
+      .accounts({
+        vault: vaultPDA,
+        owner: payer.publicKey,
+        tokenMint,
+        vaultTokenAccount,
+        protocolStats: null,
+        registryEntry: null,
+        vaultRegistry: null,
+        protocolConfig: null,
+        shareMint: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      } as any)
+      .rpc()
+
+    // The 2 second wait before exiting covers a 1 second cooldown, not the 300 second default
+    await program.methods
+      .updateVaultConfig({ stakeCooldownSeconds: new anchor.BN(1) } as any)
+      .accounts({ vault: vaultPDA, owner: payer.publicKey } as any)
+      .rpc()
+
+    await program.methods
+      .initializeVaultDepositor()
+      .accounts({ vault: vaultPDA, vaultDepositor, authority: payer.publicKey, systemProgram: SystemProgram.programId } as any)
+      .rpc()
+    await program.methods
+      .stake(new anchor.BN(100_000_000_000), new anchor.BN(0), NO_CLIENT_ID)
+      .accounts({
+        vault: vaultPDA,
+        vaultDepositor,
+        vaultTokenAccount,
+        userTokenAccount,
+        tokenMint,
+        authority: payer.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      } as any)
+      .rpc()
+    await sleep(2000)
+    await program.methods
+      .requestUnstake(new anchor.BN(40_000_000_000), new anchor.BN(0), NO_CLIENT_ID)
+      .accounts({ vault: vaultPDA, vaultDepositor, authority: payer.publicKey } as any)
+      .rpc()
+  })
+
+  it('skips a request still in its lockup', async () => {
+    const before = await program.account.vault.fetch(vaultPDA)
+    await processBatch([vaultDepositor, userTokenAccount])
+
+    const after = await program.account.vault.fetch(vaultPDA)
+    expect(after.reservedAssets.toString()).to.equal(before.reservedAssets.toString())
+    const depositor = await program.account.vaultDepositor.fetch(vaultDepositor)
+    expect(depositor.unstakeRequest.shares.toNumber()).to.be.greaterThan(0)
+  })
+
+  it('rejects an odd number of remaining accounts', async () => {
+    await expectVaultError(processBatch([vaultDepositor]), 'InvalidAmount')
+  })
+
+  it("rejects a token account not owned by the depositor's authority", async () => {
+    const otherTokenAccount = await createAccount(provider.connection, payer, tokenMint, Keypair.generate().publicKey)
+    await expectVaultError(processBatch([vaultDepositor, otherTokenAccount]), 'Unauthorized')
+  })
+})
@@ -0,0 +1,58 @@
+import { expect } from 'chai'
+import * as anchor from '@coral-xyz/anchor'
+import { StakeCapacity, U64_MAX, maxIn, minOut, stakeRejection } from '../client/quote'
+
+describe('Stake capacity', () => {
+  const capacity = (overrides: Partial<StakeCapacity> = {}): StakeCapacity => ({
+    vaultRemainingCapacity: new anchor.BN(100),
+    depositorRemainingCapacity: U64_MAX,
+    minStakeAmount: new anchor.BN(20),
+    depositorValue: new anchor.BN(30),
+    isPaused: false,
+    isDecommissioning: false,
+    ...overrides,
+  })
+
+  it('accepts amounts between the minimum and the remaining capacity', () => {
+    expect(stakeRejection(capacity(), 20)).to.equal(null)
+    expect(stakeRejection(capacity(), new anchor.BN(100))).to.equal(null)
+  })
+
+  it('rejects in the order the program checks', () => {
+    expect(stakeRejection(capacity({ isPaused: true, isDecommissioning: true }), 1)).to.equal('Vault is paused')
+    expect(stakeRejection(capacity({ isDecommissioning: true }), 1)).to.equal('Vault is being decommissioned')
+    expect(stakeRejection(capacity({ vaultRemainingCapacity: new anchor.BN(0) }), 1)).to.contain(
+      'Minimum stake amount not met'
+    )
+    expect(stakeRejection(capacity(), 101)).to.contain('Vault is full: 100 base units of capacity left')
+    const capped = capacity({ depositorRemainingCapacity: new anchor.BN(50) })
+    expect(stakeRejection(capped, 101)).to.contain('Vault is full')
+    expect(stakeRejection(capped, 51)).to.contain('per-depositor cap: 50 base units left')
+    expect(stakeRejection(capped, 50)).to.equal(null)
+  })
+
+  it('rejects the u64::MAX sentinel before any other check', () => {
+    expect(stakeRejection(capacity({ isPaused: true }), U64_MAX)).to.contain('u64::MAX is not a stake amount')
+  })
+})
+
+describe('Slippage bounds', () => {
+  it('widens the expectation by the tolerance, rounding against the user', () => {
+    expect(minOut(10_000, 50).toNumber()).to.equal(9_950)
+    expect(minOut(999, 50).toNumber()).to.equal(994)
+    expect(maxIn(10_000, 50).toNumber()).to.equal(10_050)
+    expect(maxIn(999, 50).toNumber()).to.equal(1_004)
+  })
+
+  it('never turns a non-zero expectation into the disabled bound', () => {
+    expect(minOut(1, 10000).toNumber()).to.equal(1)
+    expect(minOut(0, 50).toNumber()).to.equal(0)
+    expect(maxIn(new anchor.BN(7), 0).toNumber()).to.equal(7)
+  })
+
+  it('rejects tolerances outside 0..10000 bps', () => {
+    expect(() => minOut(100, -1)).to.throw('basis points')
+    expect(() => maxIn(100, 10001)).to.throw('basis points')
+    expect(() => minOut(100, 0.5)).to.throw('basis points')
+  })
+})
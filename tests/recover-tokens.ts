@@ -0,0 +1,105 @@
+import * as anchor from '@coral-xyz/anchor'
+import { Program } from '@coral-xyz/anchor'
+import { SimpleVault } from '../target/types/simple_vault'
+import { TOKEN_PROGRAM_ID, createMint, createAccount, getAccount, mintTo } from '@solana/spl-token'
+import { SystemProgram, SYSVAR_RENT_PUBKEY, Keypair, PublicKey } from '@solana/web3.js'
+import { expect } from 'chai'
+import { expectVaultError } from './helpers'
+
+describe('Recover tokens', () => {
+  const provider = anchor.AnchorProvider.env()
+  anchor.setProvider(provider)
+  const program = anchor.workspace.SimpleVault as Program<SimpleVault>
+  const payer = (provider.wallet as anchor.Wallet).payer
+
+  const vaultName = `recover-${Date.now() % 1_000_000}`
+  const vaultNameBuffer = Buffer.alloc(32)
+  vaultNameBuffer.write(vaultName)
+
+  const [vaultPDA] = PublicKey.findProgramAddressSync([Buffer.from('vault'), vaultNameBuffer], program.programId)
+  const [vaultTokenAccount] = PublicKey.findProgramAddressSync(
+    [Buffer.from('vault_token_account'), vaultPDA.toBuffer()],
+    program.programId
+  )
+
+  let tokenMint: PublicKey
+  let foreignMint: PublicKey
+  // foreign tokens sent to the vault PDA by mistake
+  let strayAccount: PublicKey
+  let destination: PublicKey
+
+  const recover = (source: PublicKey, to: PublicKey, mint: PublicKey, owner: Keypair = payer) =>
+    program.methods
+      .recoverTokens()
+      .accounts({
+        vault: vaultPDA,
+        sourceTokenAccount: source,
+        destinationTokenAccount: to,
+        mint,
+        owner: owner.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      } as any)
+      .signers([owner])
+      .rpc()
+
+  before(async () => {
+    tokenMint = await createMint(provider.connection, payer, payer.publicKey, null, 9)
+    foreignMint = await createMint(provider.connection, payer, payer.publicKey, null, 6)
+
+    await program.methods
+      .initializeVault({
+        name: Array.from(vaultNameBuffer),
+        platformAccount: payer.publicKey,
+        unstakeLockupPeriod: new anchor.BN(600),
+        platformShareBps: new anchor.BN(5000),
+        minStakeAmount: new anchor.BN(0),
+        maxTotalAssets: null,
+        managementFeeBps: null,
+        registryIndex: null,
+        protocolGoverned: null,
+      } as any)
+      .accounts({
+        vault: vaultPDA,
+        owner: payer.publicKey,
+        tokenMint,
+        vaultTokenAccount,
+        protocolStats: null,
+        registryEntry: null,
+        vaultRegistry: null,
+        protocolConfig: null,
+        shareMint: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      } as any)
+      .rpc()
+
+    strayAccount = await createAccount(provider.connection, payer, foreignMint, vaultPDA, Keypair.generate())
+    destination = await createAccount(provider.connection, payer, foreignMint, payer.publicKey)
+    await mintTo(provider.connection, payer, foreignMint, strayAccount, payer, 5_000_000)
+  })
+
+  it('rejects anyone but the owner', async () => {
+    const stranger = Keypair.generate()
+    await expectVaultError(recover(strayAccount, destination, foreignMint, stranger), 'Unauthorized')
+  })
+
+  it('sends the whole balance of a foreign mint to the destination', async () => {
+    await recover(strayAccount, destination, foreignMint)
+    expect(Number((await getAccount(provider.connection, strayAccount)).amount)).to.equal(0)
+    expect(Number((await getAccount(provider.connection, destination)).amount)).to.equal(5_000_000)
+
+    // nothing left to recover
+    await expectVaultError(recover(strayAccount, destination, foreignMint), 'InvalidAmount')
+  })
+
+  it('never recovers the staking mint', async () => {
+    const ownerTokenAccount = await createAccount(provider.connection, payer, tokenMint, payer.publicKey)
+    await expectVaultError(recover(vaultTokenAccount, ownerTokenAccount, tokenMint), 'InvalidTokenMint')
+
+    // also not from a second account of the staking mint owned by the vault
+    const strayStakingAccount = await createAccount(provider.connection, payer, tokenMint, vaultPDA, Keypair.generate())
+    await mintTo(provider.connection, payer, tokenMint, strayStakingAccount, payer, 1_000_000_000)
+    await expectVaultError(recover(strayStakingAccount, ownerTokenAccount, tokenMint), 'InvalidTokenMint')
+  })
+})
@@ -0,0 +1,193 @@
+import * as anchor from '@coral-xyz/anchor'
+import { Program } from '@coral-xyz/anchor'
+import { SimpleVault } from '../target/types/simple_vault'
+import { MockFeeRouter } from '../target/types/mock_fee_router'
+import { TOKEN_PROGRAM_ID, createMint, createAccount, mintTo } from '@solana/spl-token'
+import { SystemProgram, SYSVAR_RENT_PUBKEY, Keypair, PublicKey } from '@solana/web3.js'
+import { expect } from 'chai'
+import { expectVaultError } from './helpers'
+
+const NO_CLIENT_ID = new Array(16).fill(0)
+
+describe('Reward provenance (program reward sources)', () => {
+  const provider = anchor.AnchorProvider.env()
+  anchor.setProvider(provider)
+  const program = anchor.workspace.SimpleVault as Program<SimpleVault>
+  const router = anchor.workspace.MockFeeRouter as Program<MockFeeRouter>
+  const payer = (provider.wallet as anchor.Wallet).payer
+
+  const vaultName = `provenance-${Date.now() % 1_000_000}`
+  const vaultNameBuffer = Buffer.alloc(32)
+  vaultNameBuffer.write(vaultName)
+
+  const [vaultPDA] = PublicKey.findProgramAddressSync([Buffer.from('vault'), vaultNameBuffer], program.programId)
+  const [vaultTokenAccount] = PublicKey.findProgramAddressSync(
+    [Buffer.from('vault_token_account'), vaultPDA.toBuffer()],
+    program.programId
+  )
+  const [vaultDepositor] = PublicKey.findProgramAddressSync(
+    [Buffer.from('vault_depositor'), vaultPDA.toBuffer(), payer.publicKey.toBuffer()],
+    program.programId
+  )
+
+  const seed = Buffer.from('fees')
+  const [routerAuthority] = PublicKey.findProgramAddressSync([Buffer.from('fee_router'), seed], router.programId)
+  const rewardSourcePDA = (authority: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from('reward_source'), vaultPDA.toBuffer(), authority.toBuffer()],
+      program.programId
+    )[0]
+
+  let tokenMint: PublicKey
+  let userTokenAccount: PublicKey
+  let platformTokenAccount: PublicKey
+  let feeAccount: PublicKey
+
+  const register = (programId: PublicKey, seeds: Buffer[]) => {
+    const [authority] = PublicKey.findProgramAddressSync(seeds, programId)
+    return program.methods
+      .registerProgramRewardSource(programId, seeds)
+      .accounts({
+        vault: vaultPDA,
+        rewardSource: rewardSourcePDA(authority),
+        owner: payer.publicKey,
+        systemProgram: SystemProgram.programId,
+      } as any)
+      .rpc()
+  }
+
+  const setPermissionlessRewards = (permissionlessRewards: boolean) =>
+    program.methods
+      .updateVaultConfig({ permissionlessRewards } as any)
+      .accounts({ vault: vaultPDA, owner: payer.publicKey } as any)
+      .rpc()
+
+  const push = (amount: number, rewardSource: PublicKey | null) =>
+    router.methods
+      .pushRewards(seed, new anchor.BN(amount))
+      .accounts({
+        vault: vaultPDA,
+        vaultTokenAccount,
+        feeAccount,
+        platformTokenAccount,
+        routerAuthority,
+        rewardSource,
+        vaultProgram: program.programId,
+        tokenMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      } as any)
+      .rpc()
+
+  before(async () => {
+    tokenMint = await createMint(provider.connection, payer, payer.publicKey, null, 9)
+    userTokenAccount = await createAccount(provider.connection, payer, tokenMint, payer.publicKey)
+    platformTokenAccount = await createAccount(provider.connection, payer, tokenMint, payer.publicKey, Keypair.generate())
+    // fees collect in an account owned by the router PDA
+    feeAccount = await createAccount(provider.connection, payer, tokenMint, routerAuthority, Keypair.generate())
+    await mintTo(provider.connection, payer, tokenMint, userTokenAccount, payer, 1_000_000_000_000)
+    await mintTo(provider.connection, payer, tokenMint, feeAccount, payer, 100_000_000_000)
+
+    await program.methods
+      .initializeVault({
+        name: Array.from(vaultNameBuffer),
+        platformAccount: payer.publicKey,
+        unstakeLockupPeriod: new anchor.BN(600),
+        platformShareBps: new anchor.BN(5000),
+        minStakeAmount: new anchor.BN(0),
+        maxTotalAssets: null,
+        managementFeeBps: null,
+        registryIndex: null,
+        protocolGoverned: null,
+      } as any)
+      .accounts({
+        vault: vaultPDA,
+        owner: payer.publicKey,
+        tokenMint,
+        vaultTokenAccount,
+        protocolStats: null,
+        registryEntry: null,
+        vaultRegistry: null,
+        protocolConfig: null,
+        shareMint: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      } as any)
+      .rpc()
+
+    await program.methods
+      .initializeVaultDepositor()
+      .accounts({ vault: vaultPDA, vaultDepositor, authority: payer.publicKey, systemProgram: SystemProgram.programId } as any)
+      .rpc()
+
+    await program.methods
+      .stake(new anchor.BN(100_000_000_000), new anchor.BN(0), NO_CLIENT_ID)
+      .accounts({
+        vault: vaultPDA,
+        vaultDepositor,
+        vaultTokenAccount,
+        userTokenAccount,
+        authority: payer.publicKey,
+        tokenMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      } as any)
+      .rpc()
+  })
+
+  it('accepts rewards from any signer only while rewards are permissionless', async () => {
+    // a new vault accepts its owner as reward authority and nobody else
+    const vault = await program.account.vault.fetch(vaultPDA)
+    expect(vault.rewardAuthority.toBase58()).to.equal(payer.publicKey.toBase58())
+    await expectVaultError(push(1_000_000_000, null), 'UnauthorizedRewardSource')
+
+    await setPermissionlessRewards(true)
+    await push(1_000_000_000, null)
+    await setPermissionlessRewards(false)
+  })
+
+  it('rejects the router once only another program is registered', async () => {
+    // same seeds under a different program derive a different PDA
+    await register(SystemProgram.programId, [Buffer.from('fee_router'), seed])
+    const vault = await program.account.vault.fetch(vaultPDA)
+    expect(vault.programRewardSources).to.equal(1)
+    await expectVaultError(push(1_000_000_000, null), 'UnauthorizedRewardSource')
+  })
+
+  it('accepts rewards pushed by the registered router PDA through CPI', async () => {
+    await register(router.programId, [Buffer.from('fee_router'), seed])
+    const source = await program.account.programRewardSource.fetch(rewardSourcePDA(routerAuthority))
+    expect(source.programId.toBase58()).to.equal(router.programId.toBase58())
+    expect(source.authority.toBase58()).to.equal(routerAuthority.toBase58())
+
+    const before = await program.account.vault.fetch(vaultPDA)
+    await push(2_000_000_000, rewardSourcePDA(routerAuthority))
+    const after = await program.account.vault.fetch(vaultPDA)
+    // half of the reward goes to the platform
+    expect(after.totalAssets.sub(before.totalAssets).toNumber()).to.equal(1_000_000_000)
+  })
+
+  it('accepts the reward authority signing directly and rejects other wallets', async () => {
+    const addRewards = (authority: Keypair, sourceAccount: PublicKey) =>
+      program.methods
+        .addRewards(new anchor.BN(1_000_000_000))
+        .accounts({
+          vault: vaultPDA,
+          vaultTokenAccount,
+          rewardSourceAccount: sourceAccount,
+          platformTokenAccount,
+          rewardSourceAuthority: authority.publicKey,
+          rewardSource: null,
+          tokenMint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        } as any)
+        .signers([authority])
+        .rpc()
+
+    await addRewards(payer, userTokenAccount)
+
+    const stranger = Keypair.generate()
+    const strangerTokenAccount = await createAccount(provider.connection, payer, tokenMint, stranger.publicKey)
+    await mintTo(provider.connection, payer, tokenMint, strangerTokenAccount, payer, 10_000_000_000)
+    await expectVaultError(addRewards(stranger, strangerTokenAccount), 'UnauthorizedRewardSource')
+  })
+})
@@ -0,0 +1,163 @@
+import * as anchor from '@coral-xyz/anchor'
+import { Program } from '@coral-xyz/anchor'
+import { SimpleVault } from '../target/types/simple_vault'
+import { TOKEN_PROGRAM_ID, createMint, createAccount, getAccount, mintTo } from '@solana/spl-token'
+import { SystemProgram, SYSVAR_RENT_PUBKEY, PublicKey } from '@solana/web3.js'
+import { expect } from 'chai'
+import { expectVaultError } from './helpers'
+
+const NO_CLIENT_ID = new Array(16).fill(0)
+
+describe('Secondary rewards', () => {
+  const provider = anchor.AnchorProvider.env()
+  anchor.setProvider(provider)
+  const program = anchor.workspace.SimpleVault as Program<SimpleVault>
+  const payer = (provider.wallet as anchor.Wallet).payer
+
+  const vaultName = `secondary-${Date.now() % 1_000_000}`
+  const vaultNameBuffer = Buffer.alloc(32)
+  vaultNameBuffer.write(vaultName)
+
+  const [vaultPDA] = PublicKey.findProgramAddressSync([Buffer.from('vault'), vaultNameBuffer], program.programId)
+  const [vaultTokenAccount] = PublicKey.findProgramAddressSync(
+    [Buffer.from('vault_token_account'), vaultPDA.toBuffer()],
+    program.programId
+  )
+  const [vaultDepositor] = PublicKey.findProgramAddressSync(
+    [Buffer.from('vault_depositor'), vaultPDA.toBuffer(), payer.publicKey.toBuffer()],
+    program.programId
+  )
+  const [secondaryRewardTokenAccount] = PublicKey.findProgramAddressSync(
+    [Buffer.from('secondary_reward_token_account'), vaultPDA.toBuffer()],
+    program.programId
+  )
+
+  let tokenMint: PublicKey
+  let userTokenAccount: PublicKey
+  let secondaryMint: PublicKey
+  let userSecondaryAccount: PublicKey
+
+  const setMint = () =>
+    program.methods
+      .setSecondaryRewardMint()
+      .accounts({
+        vault: vaultPDA,
+        secondaryRewardMint: secondaryMint,
+        secondaryRewardTokenAccount,
+        owner: payer.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      } as any)
+      .rpc()
+
+  const addSecondary = (amount: number) =>
+    program.methods
+      .addSecondaryRewards(new anchor.BN(amount))
+      .accounts({
+        vault: vaultPDA,
+        secondaryRewardTokenAccount,
+        rewardSourceAccount: userSecondaryAccount,
+        secondaryRewardMint: secondaryMint,
+        rewardSourceAuthority: payer.publicKey,
+        rewardSource: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      } as any)
+      .rpc()
+
+  const claim = () =>
+    program.methods
+      .claimSecondaryRewards()
+      .accounts({
+        vault: vaultPDA,
+        vaultDepositor,
+        secondaryRewardTokenAccount,
+        userTokenAccount: userSecondaryAccount,
+        secondaryRewardMint: secondaryMint,
+        authority: payer.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      } as any)
+      .rpc()
+
+  before(async () => {
+    tokenMint = await createMint(provider.connection, payer, payer.publicKey, null, 9)
+    userTokenAccount = await createAccount(provider.connection, payer, tokenMint, payer.publicKey)
+    await mintTo(provider.connection, payer, tokenMint, userTokenAccount, payer, 1_000_000_000_000)
+    secondaryMint = await createMint(provider.connection, payer, payer.publicKey, null, 6)
+    userSecondaryAccount = await createAccount(provider.connection, payer, secondaryMint, payer.publicKey)
+    await mintTo(provider.connection, payer, secondaryMint, userSecondaryAccount, payer, 1_000_000_000)
+
+    await program.methods
+      .initializeVault({
+        name: Array.from(vaultNameBuffer),
+        platformAccount: payer.publicKey,
+        unstakeLockupPeriod: new anchor.BN(600),
+        platformShareBps: new anchor.BN(5000),
+        minStakeAmount: new anchor.BN(0),
+        maxTotalAssets: null,
+        managementFeeBps: null,
+        registryIndex: null,
+        protocolGoverned: null,
+      } as any)
+      .accounts({
+        vault: vaultPDA,
+        owner: payer.publicKey,
+        tokenMint,
+        vaultTokenAccount,
+        protocolStats: null,
+        registryEntry: null,
+        vaultRegistry: null,
+        protocolConfig: null,
+        shareMint: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      } as any)
+      .rpc()
+
+    await program.methods
+      .initializeVaultDepositor()
+      .accounts({ vault: vaultPDA, vaultDepositor, authority: payer.publicKey, systemProgram: SystemProgram.programId } as any)
+      .rpc()
+  })
+
+  it('sets the secondary reward mint once', async () => {
+    await setMint()
+
+    const vault = await program.account.vault.fetch(vaultPDA)
+    expect(vault.secondaryRewardMint.toBase58()).to.equal(secondaryMint.toBase58())
+    expect(vault.secondaryRewardTokenAccount.toBase58()).to.equal(secondaryRewardTokenAccount.toBase58())
+    await expectVaultError(setMint(), 'SecondaryRewardMintAlreadySet')
+  })
+
+  it('refuses secondary rewards nobody could claim', async () => {
+    await expectVaultError(addSecondary(1_000_000), 'NoActiveShares')
+  })
+
+  it('pays a staker what its active shares earned, without compounding', async () => {
+    await program.methods
+      .stake(new anchor.BN(100_000_000_000), new anchor.BN(0), NO_CLIENT_ID)
+      .accounts({
+        vault: vaultPDA,
+        vaultDepositor,
+        vaultTokenAccount,
+        userTokenAccount,
+        tokenMint,
+        authority: payer.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      } as any)
+      .rpc()
+    const before = await program.account.vault.fetch(vaultPDA)
+
+    await addSecondary(5_000_000)
+    const after = await program.account.vault.fetch(vaultPDA)
+    expect(after.totalAssets.toString()).to.equal(before.totalAssets.toString())
+    expect(after.totalSecondaryRewards.toNumber()).to.equal(5_000_000)
+
+    const balanceBefore = Number((await getAccount(provider.connection, userSecondaryAccount)).amount)
+    await claim()
+    const balanceAfter = Number((await getAccount(provider.connection, userSecondaryAccount)).amount)
+    expect(balanceAfter - balanceBefore).to.equal(5_000_000)
+
+    await expectVaultError(claim(), 'NoSecondaryRewards')
+  })
+})
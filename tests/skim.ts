@@ -0,0 +1,116 @@
+import * as anchor from '@coral-xyz/anchor'
+import { Program } from '@coral-xyz/anchor'
+import { SimpleVault } from '../target/types/simple_vault'
+import { TOKEN_PROGRAM_ID, createMint, createAccount, getAccount, mintTo, transfer } from '@solana/spl-token'
+import { SystemProgram, SYSVAR_RENT_PUBKEY, PublicKey } from '@solana/web3.js'
+import { expect } from 'chai'
+
+const NO_CLIENT_ID = new Array(16).fill(0)
+
+describe('Vault token balance checks', () => {
+  const provider = anchor.AnchorProvider.env()
+  anchor.setProvider(provider)
+  const program = anchor.workspace.SimpleVault as Program<SimpleVault>
+  const payer = (provider.wallet as anchor.Wallet).payer
+
+  const vaultName = `skim-${Date.now() % 1_000_000}`
+  const vaultNameBuffer = Buffer.alloc(32)
+  vaultNameBuffer.write(vaultName)
+
+  const [vaultPDA] = PublicKey.findProgramAddressSync([Buffer.from('vault'), vaultNameBuffer], program.programId)
+  const [vaultTokenAccount] = PublicKey.findProgramAddressSync(
+    [Buffer.from('vault_token_account'), vaultPDA.toBuffer()],
+    program.programId
+  )
+  const [vaultDepositor] = PublicKey.findProgramAddressSync(
+    [Buffer.from('vault_depositor'), vaultPDA.toBuffer(), payer.publicKey.toBuffer()],
+    program.programId
+  )
+
+  let tokenMint: PublicKey
+  let userTokenAccount: PublicKey
+
+  const skim = () => program.methods.skim().accounts({ vault: vaultPDA, vaultTokenAccount } as any).rpc()
+
+  before(async () => {
+    tokenMint = await createMint(provider.connection, payer, payer.publicKey, null, 9)
+    userTokenAccount = await createAccount(provider.connection, payer, tokenMint, payer.publicKey)
+    await mintTo(provider.connection, payer, tokenMint, userTokenAccount, payer, 1_000_000_000_000)
+
+    await program.methods
+      .initializeVault({
+        name: Array.from(vaultNameBuffer),
+        platformAccount: payer.publicKey,
+        unstakeLockupPeriod: new anchor.BN(600),
+        platformShareBps: new anchor.BN(5000),
+        minStakeAmount: new anchor.BN(0),
+        maxTotalAssets: null,
+        managementFeeBps: null,
+        registryIndex: null,
+        protocolGoverned: null,
+      } as any)
+      .accounts({
+        vault: vaultPDA,
+        owner: payer.publicKey,
+        tokenMint,
+        vaultTokenAccount,
+        protocolStats: null,
+        registryEntry: null,
+        vaultRegistry: null,
+        protocolConfig: null,
+        shareMint: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      } as any)
+      .rpc()
+
+    await program.methods
+      .initializeVaultDepositor()
+      .accounts({ vault: vaultPDA, vaultDepositor, authority: payer.publicKey, systemProgram: SystemProgram.programId } as any)
+      .rpc()
+    await program.methods
+      .stake(new anchor.BN(100_000_000_000), new anchor.BN(0), NO_CLIENT_ID)
+      .accounts({
+        vault: vaultPDA,
+        vaultDepositor,
+        vaultTokenAccount,
+        userTokenAccount,
+        tokenMint,
+        authority: payer.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      } as any)
+      .rpc()
+  })
+
+  it('does nothing while the balance matches total_assets', async () => {
+    const before = await program.account.vault.fetch(vaultPDA)
+    await skim()
+    const after = await program.account.vault.fetch(vaultPDA)
+    expect(after.totalAssets.toString()).to.equal(before.totalAssets.toString())
+  })
+
+  it('adds a direct transfer to the stakers like a reward', async () => {
+    const before = await program.account.vault.fetch(vaultPDA)
+    await transfer(provider.connection, payer, userTokenAccount, vaultTokenAccount, payer, 5_000_000_000)
+    await skim()
+
+    const after = await program.account.vault.fetch(vaultPDA)
+    expect(after.totalAssets.sub(before.totalAssets).toNumber()).to.equal(5_000_000_000)
+    expect(after.totalRewards.sub(before.totalRewards).toNumber()).to.equal(5_000_000_000)
+    expect(after.totalShares.toString()).to.equal(before.totalShares.toString())
+    const balance = Number((await getAccount(provider.connection, vaultTokenAccount)).amount)
+    expect(balance).to.equal(after.totalAssets.toNumber())
+
+    // skimmed once only
+    await skim()
+    expect((await program.account.vault.fetch(vaultPDA)).totalAssets.toString()).to.equal(after.totalAssets.toString())
+  })
+
+  it('reconcile records the slot while the balance covers the accounted assets', async () => {
+    await program.methods.reconcile().accounts({ vault: vaultPDA, vaultTokenAccount } as any).rpc()
+    const vault = await program.account.vault.fetch(vaultPDA)
+    expect(vault.lastReconcileSlot.toNumber()).to.be.greaterThan(0)
+    expect(vault.pausedOperations).to.equal(0)
+  })
+})
@@ -147,7 +147,7 @@ async function main() {
   const unstakeAssetAmount = Math.floor(initialUserValue.value / 2); // Half of asset value
   
   const requestUnstakeTx = await program.methods
-    .requestUnstake(new anchor.BN(unstakeAssetAmount))
+    .requestUnstake(new anchor.BN(unstakeAssetAmount), new anchor.BN(0), new Array(16).fill(0))
     .accounts({
       vault: vaultPDA,
       vaultDepositor: user3VaultDepositor,
@@ -197,6 +197,7 @@ async function main() {
       rewardSourceAccount: adminTokenAccount,
       platformTokenAccount: platformTokenAccount,
       rewardSourceAuthority: admin.publicKey,
+      tokenMint,
       tokenProgram: TOKEN_PROGRAM_ID,
     } as any)
     .signers([admin])
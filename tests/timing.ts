@@ -0,0 +1,48 @@
+import { expect } from 'chai'
+import { TimingMetricsSink, instrumentedFetch } from '../client/metrics'
+
+describe('--timing report', () => {
+  const rpcBody = (method: string) => ({ body: JSON.stringify({ jsonrpc: '2.0', id: 1, method, params: [] }) })
+
+  // stands in for the RPC node: answers after `delayMs`, replaying `statuses` in order
+  const mockRpc = (delayMs: number, statuses: number[] = [200]) => {
+    let call = 0
+    return async () => {
+      await new Promise((resolve) => setTimeout(resolve, delayMs))
+      const status = statuses[Math.min(call++, statuses.length - 1)]
+      return { status, ok: status < 400 }
+    }
+  }
+
+  it('counts calls per method and flags the slow ones', async () => {
+    const sink = new TimingMetricsSink(50)
+    const fast = instrumentedFetch(sink, mockRpc(1))
+    const slow = instrumentedFetch(sink, mockRpc(80))
+
+    await fast('http://rpc', rpcBody('getAccountInfo'))
+    await fast('http://rpc', rpcBody('getAccountInfo'))
+    await slow('http://rpc', rpcBody('getAccountInfo'))
+    await fast('http://rpc', rpcBody('getLatestBlockhash'))
+
+    const report = sink.report()
+    expect(report).to.match(/3 getAccountInfo calls totalling \d+ ms/)
+    expect(report).to.contain('1 getLatestBlockhash call totalling')
+    expect(report).to.match(/slow getAccountInfo: \d+ ms \(threshold 50 ms\)/)
+    expect(report).not.to.contain('slow getLatestBlockhash')
+  })
+
+  it('reports send retries and the confirmation wait', async () => {
+    const sink = new TimingMetricsSink(10_000)
+    const rateLimited = instrumentedFetch(sink, mockRpc(1, [429, 200]))
+
+    await rateLimited('http://rpc', rpcBody('sendTransaction'))
+    sink.recordTransaction({ instruction: 'stake', confirmMs: 1234, ok: true })
+
+    const report = sink.report()
+    expect(report).to.contain('1 sendTransaction call totalling')
+    expect(report).to.contain('(1 retries)')
+    expect(report).to.contain('2 send attempts')
+    expect(report).to.contain('stake: confirmed in 1234 ms')
+    expect(report).not.to.contain('slow')
+  })
+})
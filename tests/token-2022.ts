@@ -0,0 +1,237 @@
+import * as anchor from '@coral-xyz/anchor'
+import { Program } from '@coral-xyz/anchor'
+import { SimpleVault } from '../target/types/simple_vault'
+import {
+  ExtensionType,
+  TOKEN_2022_PROGRAM_ID,
+  createAccount,
+  createInitializeMintInstruction,
+  createInitializePermanentDelegateInstruction,
+  createInitializeTransferFeeConfigInstruction,
+  getAccount,
+  getMintLen,
+  mintTo,
+} from '@solana/spl-token'
+import {
+  Keypair,
+  PublicKey,
+  SystemProgram,
+  SYSVAR_RENT_PUBKEY,
+  Transaction,
+  TransactionInstruction,
+  sendAndConfirmTransaction,
+} from '@solana/web3.js'
+import { expect } from 'chai'
+import { expectVaultError } from './helpers'
+
+const NO_CLIENT_ID = new Array(16).fill(0)
+
+describe('Token-2022 underlying mints', () => {
+  const provider = anchor.AnchorProvider.env()
+  anchor.setProvider(provider)
+  const program = anchor.workspace.SimpleVault as Program<SimpleVault>
+  const payer = (provider.wallet as anchor.Wallet).payer
+
+  // 1% transfer fee, large enough cap to never bind here
+  const TRANSFER_FEE_BPS = 100
+  const MAX_FEE = BigInt(1_000_000_000_000)
+
+  const vaultAccounts = (name: string) => {
+    const nameBuffer = Buffer.alloc(32)
+    nameBuffer.write(name)
+    const [vault] = PublicKey.findProgramAddressSync([Buffer.from('vault'), nameBuffer], program.programId)
+    const [vaultTokenAccount] = PublicKey.findProgramAddressSync(
+      [Buffer.from('vault_token_account'), vault.toBuffer()],
+      program.programId
+    )
+    return { nameBuffer, vault, vaultTokenAccount }
+  }
+
+  // Token-2022 mint carrying `extensions`, initialized by `initialize` before the mint itself
+  const createMint2022 = async (
+    extensions: ExtensionType[],
+    initialize: (mint: PublicKey) => TransactionInstruction[]
+  ): Promise<PublicKey> => {
+    const mint = Keypair.generate()
+    const space = getMintLen(extensions)
+    const lamports = await provider.connection.getMinimumBalanceForRentExemption(space)
+    const tx = new Transaction().add(
+      SystemProgram.createAccount({
+        fromPubkey: payer.publicKey,
+        newAccountPubkey: mint.publicKey,
+        space,
+        lamports,
+        programId: TOKEN_2022_PROGRAM_ID,
+      }),
+      ...initialize(mint.publicKey),
+      createInitializeMintInstruction(mint.publicKey, 9, payer.publicKey, null, TOKEN_2022_PROGRAM_ID)
+    )
+    await sendAndConfirmTransaction(provider.connection, tx, [payer, mint])
+    return mint.publicKey
+  }
+
+  const initializeVault = (name: string, tokenMint: PublicKey) => {
+    const { nameBuffer, vault, vaultTokenAccount } = vaultAccounts(name)
+    return program.methods
+      .initializeVault({
+        name: Array.from(nameBuffer),
+        platformAccount: payer.publicKey,
+        unstakeLockupPeriod: new anchor.BN(600),
+        platformShareBps: new anchor.BN(5000),
+        minStakeAmount: new anchor.BN(0),
+        maxTotalAssets: null,
+        managementFeeBps: null,
+        registryIndex: null,
+        protocolGoverned: null,
+      } as any)
+      .accounts({
+        vault,
+        owner: payer.publicKey,
+        tokenMint,
+        vaultTokenAccount,
+        protocolStats: null,
+        registryEntry: null,
+        vaultRegistry: null,
+        protocolConfig: null,
+        shareMint: null,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      } as any)
+      .rpc()
+  }
+
+  describe('with a transfer fee', () => {
+    const name = `t22-fee-${Date.now() % 1_000_000}`
+    const { vault, vaultTokenAccount } = vaultAccounts(name)
+    const [vaultDepositor] = PublicKey.findProgramAddressSync(
+      [Buffer.from('vault_depositor'), vault.toBuffer(), payer.publicKey.toBuffer()],
+      program.programId
+    )
+
+    let tokenMint: PublicKey
+    let userTokenAccount: PublicKey
+    let platformTokenAccount: PublicKey
+
+    const vaultBalance = async () =>
+      Number((await getAccount(provider.connection, vaultTokenAccount, undefined, TOKEN_2022_PROGRAM_ID)).amount)
+
+    before(async () => {
+      tokenMint = await createMint2022([ExtensionType.TransferFeeConfig], (mint) => [
+        createInitializeTransferFeeConfigInstruction(
+          mint,
+          payer.publicKey,
+          payer.publicKey,
+          TRANSFER_FEE_BPS,
+          MAX_FEE,
+          TOKEN_2022_PROGRAM_ID
+        ),
+      ])
+      userTokenAccount = await createAccount(
+        provider.connection,
+        payer,
+        tokenMint,
+        payer.publicKey,
+        undefined,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      )
+      platformTokenAccount = await createAccount(
+        provider.connection,
+        payer,
+        tokenMint,
+        payer.publicKey,
+        Keypair.generate(),
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      )
+      await mintTo(
+        provider.connection,
+        payer,
+        tokenMint,
+        userTokenAccount,
+        payer,
+        1_000_000_000_000,
+        [],
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+      )
+
+      await initializeVault(name, tokenMint)
+      await program.methods
+        .initializeVaultDepositor()
+        .accounts({ vault, vaultDepositor, authority: payer.publicKey, systemProgram: SystemProgram.programId } as any)
+        .rpc()
+    })
+
+    it('credits a stake with the amount that arrived after the fee', async () => {
+      await program.methods
+        .stake(new anchor.BN(100_000_000_000), new anchor.BN(0), NO_CLIENT_ID)
+        .accounts({
+          vault,
+          vaultDepositor,
+          vaultTokenAccount,
+          userTokenAccount,
+          tokenMint,
+          authority: payer.publicKey,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        } as any)
+        .rpc()
+
+      const state = await program.account.vault.fetch(vault)
+      expect(state.totalAssets.toNumber()).to.equal(99_000_000_000)
+      expect(await vaultBalance()).to.equal(state.totalAssets.toNumber())
+    })
+
+    it('counts only the vault share of rewards that arrived', async () => {
+      const before = await program.account.vault.fetch(vault)
+      await program.methods
+        .addRewards(new anchor.BN(2_000_000_000))
+        .accounts({
+          vault,
+          vaultTokenAccount,
+          rewardSourceAccount: userTokenAccount,
+          platformTokenAccount,
+          tokenMint,
+          rewardSourceAuthority: payer.publicKey,
+          rewardSource: null,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
+        } as any)
+        .rpc()
+
+      const after = await program.account.vault.fetch(vault)
+      // half goes to the platform, the vault half loses 1% in transit
+      expect(after.totalAssets.sub(before.totalAssets).toNumber()).to.equal(990_000_000)
+      expect(await vaultBalance()).to.equal(after.totalAssets.toNumber())
+    })
+
+    it('rejects a token mint other than the vault underlying', async () => {
+      const otherMint = await createMint2022([], () => [])
+      await expectVaultError(
+        program.methods
+          .stake(new anchor.BN(1_000_000_000), new anchor.BN(0), NO_CLIENT_ID)
+          .accounts({
+            vault,
+            vaultDepositor,
+            vaultTokenAccount,
+            userTokenAccount,
+            tokenMint: otherMint,
+            authority: payer.publicKey,
+            tokenProgram: TOKEN_2022_PROGRAM_ID,
+          } as any)
+          .rpc(),
+        'InvalidTokenMint'
+      )
+    })
+  })
+
+  it('rejects a mint with a permanent delegate', async () => {
+    const tokenMint = await createMint2022([ExtensionType.PermanentDelegate], (mint) => [
+      createInitializePermanentDelegateInstruction(mint, payer.publicKey, TOKEN_2022_PROGRAM_ID),
+    ])
+    await expectVaultError(
+      initializeVault(`t22-delegate-${Date.now() % 1_000_000}`, tokenMint),
+      'UnsupportedMintExtension'
+    )
+  })
+})
@@ -0,0 +1,113 @@
+import { expect } from 'chai'
+import {
+  Keypair,
+  PublicKey,
+  Transaction,
+  TransactionInstruction,
+} from '@solana/web3.js'
+import {
+  PACKET_DATA_SIZE,
+  PlannedInstruction,
+  TxPlanner,
+  TxPlannerError,
+} from '../client/tx-planner'
+
+describe('TxPlanner', () => {
+  const payer = Keypair.generate().publicKey
+  const programId = Keypair.generate().publicKey
+
+  // instruction with a fixed data payload and no extra accounts
+  const dataIx = (bytes: number): TransactionInstruction =>
+    new TransactionInstruction({
+      programId,
+      keys: [],
+      data: Buffer.alloc(bytes),
+    })
+
+  const item = (bytes: number, extra: Partial<PlannedInstruction> = {}): PlannedInstruction => ({
+    instruction: dataIx(bytes),
+    ...extra,
+  })
+
+  it('measures the same size as a serialized legacy transaction', () => {
+    const planner = new TxPlanner({ payer, signerCount: 1 })
+    const ix = dataIx(100)
+
+    const tx = new Transaction({ feePayer: payer, recentBlockhash: PublicKey.default.toBase58() })
+    tx.add(ix)
+    const expected = tx.serialize({ requireAllSignatures: false, verifySignatures: false }).length
+
+    expect(planner.measure([ix])).to.equal(expected)
+  })
+
+  it('keeps everything in one transaction when it fits exactly', () => {
+    const planner = new TxPlanner({ payer, signerCount: 1 })
+    const base = planner.measure([dataIx(0)])
+    const exact = dataIx(PACKET_DATA_SIZE - base)
+    expect(planner.measure([exact])).to.equal(PACKET_DATA_SIZE)
+
+    const plan = planner.plan([{ instruction: exact }])
+    expect(plan.transactions).to.have.length(1)
+    expect(plan.transactions[0].size).to.equal(PACKET_DATA_SIZE)
+  })
+
+  it('splits when one byte over the limit', () => {
+    const planner = new TxPlanner({ payer, signerCount: 1 })
+    const plan = planner.plan([item(600), item(600)])
+
+    expect(plan.transactions).to.have.length(2)
+    plan.transactions.forEach((tx) => expect(tx.size).to.be.at.most(PACKET_DATA_SIZE))
+  })
+
+  it('packs greedily into the minimal number of transactions', () => {
+    const planner = new TxPlanner({ payer, signerCount: 1 })
+    const plan = planner.plan([item(300), item(300), item(300), item(300), item(300)])
+
+    expect(plan.transactions.map((tx) => tx.instructions.length)).to.deep.equal([3, 2])
+  })
+
+  it('accounts for additional signers', () => {
+    const single = new TxPlanner({ payer, signerCount: 1 })
+    const triple = new TxPlanner({ payer, signerCount: 3 })
+    expect(triple.measure([dataIx(10)]) - single.measure([dataIx(10)])).to.equal(128)
+  })
+
+  it('keeps atomic instructions together and carries rollback notes', () => {
+    const planner = new TxPlanner({ payer, signerCount: 1 })
+    const plan = planner.plan([
+      item(500, { label: 'create-depositor' }),
+      item(300, { label: 'create-ata' }),
+      item(300, { label: 'stake', atomicWith: [1], dependsOn: [0], rollbackNote: 'close ATA' }),
+    ])
+
+    expect(plan.transactions.map((tx) => tx.labels)).to.deep.equal([
+      ['create-depositor'],
+      ['create-ata', 'stake'],
+    ])
+    expect(plan.transactions[1].rollbackNotes).to.deep.equal(['close ATA'])
+  })
+
+  it('refuses to split an atomic group that does not fit', () => {
+    const planner = new TxPlanner({ payer, signerCount: 1 })
+    expect(() => planner.plan([item(700), item(700, { atomicWith: [0] })])).to.throw(
+      TxPlannerError,
+      /cannot be split/
+    )
+  })
+
+  it('rejects dependencies that point forward', () => {
+    const planner = new TxPlanner({ payer, signerCount: 1 })
+    expect(() => planner.plan([item(10, { dependsOn: [1] }), item(10)])).to.throw(
+      TxPlannerError,
+      /not ordered before/
+    )
+  })
+
+  it('rejects non-contiguous atomic groups', () => {
+    const planner = new TxPlanner({ payer, signerCount: 1 })
+    expect(() => planner.plan([item(10), item(10), item(10, { atomicWith: [0] })])).to.throw(
+      TxPlannerError,
+      /not contiguous/
+    )
+  })
+})
@@ -0,0 +1,85 @@
+import * as anchor from '@coral-xyz/anchor'
+import { Program } from '@coral-xyz/anchor'
+import { SimpleVault } from '../target/types/simple_vault'
+import { TOKEN_PROGRAM_ID, createMint } from '@solana/spl-token'
+import { SystemProgram, SYSVAR_RENT_PUBKEY, PublicKey } from '@solana/web3.js'
+import { expect } from 'chai'
+import { expectVaultError } from './helpers'
+
+describe('Vault checkpoints', () => {
+  const provider = anchor.AnchorProvider.env()
+  anchor.setProvider(provider)
+  const program = anchor.workspace.SimpleVault as Program<SimpleVault>
+  const payer = (provider.wallet as anchor.Wallet).payer
+
+  const vaultName = `checkpoints-${Date.now() % 1_000_000}`
+  const vaultNameBuffer = Buffer.alloc(32)
+  vaultNameBuffer.write(vaultName)
+
+  const [vaultPDA] = PublicKey.findProgramAddressSync([Buffer.from('vault'), vaultNameBuffer], program.programId)
+  const [vaultTokenAccount] = PublicKey.findProgramAddressSync(
+    [Buffer.from('vault_token_account'), vaultPDA.toBuffer()],
+    program.programId
+  )
+  const [vaultCheckpoints] = PublicKey.findProgramAddressSync(
+    [Buffer.from('vault_checkpoints'), vaultPDA.toBuffer()],
+    program.programId
+  )
+
+  const record = () =>
+    program.methods.recordCheckpoint().accounts({ vault: vaultPDA, vaultCheckpoints } as any).rpc()
+
+  before(async () => {
+    const tokenMint = await createMint(provider.connection, payer, payer.publicKey, null, 9)
+
+    await program.methods
+      .initializeVault({
+        name: Array.from(vaultNameBuffer),
+        platformAccount: payer.publicKey,
+        unstakeLockupPeriod: new anchor.BN(600),
+        platformShareBps: new anchor.BN(5000),
+        minStakeAmount: new anchor.BN(0),
+        maxTotalAssets: null,
+        managementFeeBps: null,
+        registryIndex: null,
+        protocolGoverned: null,
+      } as any)
+      .accounts({
+        vault: vaultPDA,
+        owner: payer.publicKey,
+        tokenMint,
+        vaultTokenAccount,
+        protocolStats: null,
+        registryEntry: null,
+        vaultRegistry: null,
+        protocolConfig: null,
+        shareMint: null,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      } as any)
+      .rpc()
+
+    await program.methods
+      .initializeVaultCheckpoints()
+      .accounts({ vault: vaultPDA, vaultCheckpoints, payer: payer.publicKey, systemProgram: SystemProgram.programId } as any)
+      .rpc()
+  })
+
+  it('records the first checkpoint at once', async () => {
+    await record()
+
+    const checkpoints = await program.account.vaultCheckpoints.fetch(vaultCheckpoints)
+    expect(checkpoints.vault.toBase58()).to.equal(vaultPDA.toBase58())
+    expect(checkpoints.len).to.equal(1)
+    expect(checkpoints.nextIndex).to.equal(1)
+    expect(checkpoints.checkpoints[0].timestamp.toNumber()).to.be.greaterThan(0)
+  })
+
+  it('rejects a checkpoint before the interval passed', async () => {
+    await expectVaultError(record(), 'CheckpointNotDue')
+
+    const checkpoints = await program.account.vaultCheckpoints.fetch(vaultCheckpoints)
+    expect(checkpoints.len).to.equal(1)
+  })
+})